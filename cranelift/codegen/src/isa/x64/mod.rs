@@ -1,6 +1,6 @@
 //! X86_64-bit Instruction Set Architecture.
 
-pub use self::inst::{args, CallInfo, EmitInfo, EmitState, Inst};
+pub use self::inst::{args, CallInfo, EmitInfo, EmitState, Inst, ReturnCallInfo};
 
 use super::{OwnedTargetIsa, TargetIsa};
 use crate::dominator_tree::DominatorTree;
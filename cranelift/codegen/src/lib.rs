@@ -58,7 +58,7 @@ pub use crate::machinst::buffer::{
     MachTextSectionBuilder, MachTrap,
 };
 pub use crate::machinst::{
-    CompiledCode, Final, MachBuffer, MachBufferFinalized, MachInst, MachInstEmit,
+    CompiledCode, Final, MachAtomicRmwOp, MachBuffer, MachBufferFinalized, MachInst, MachInstEmit,
     MachInstEmitState, MachLabel, RealReg, Reg, TextSectionBuilder, VCodeConstantData,
     VCodeConstants, Writable,
 };
@@ -177,10 +177,12 @@ use hashbrown::HashMap;
 use std::collections::HashMap;
 
 pub use crate::frontend::{FuncInstBuilder, FunctionBuilder, FunctionBuilderContext};
+pub use crate::mem2reg::promote_stack_slots;
 pub use crate::switch::Switch;
 pub use crate::variable::Variable;
 
 mod frontend;
+mod mem2reg;
 mod ssa;
 mod switch;
 mod variable;
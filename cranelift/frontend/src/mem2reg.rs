@@ -0,0 +1,416 @@
+//! Promotion of explicit stack slots to SSA values.
+//!
+//! Frontends frequently lower source-level locals to `stack_slot`s accessed
+//! through `stack_load` and `stack_store`. As long as a slot's address never
+//! escapes through `stack_addr`, every access to it is visible in the IR, and
+//! the slot can be rewritten into SSA values with the same machinery that
+//! `FunctionBuilder` uses for `Variable`s.
+
+use crate::ssa::SSABuilder;
+use crate::Variable;
+use alloc::vec::Vec;
+use cranelift_codegen::entity::{EntityRef, SecondaryMap};
+use cranelift_codegen::ir::{Function, Inst, InstructionData, Opcode, StackSlot, Type};
+
+/// A single promotable location: a byte offset within a stack slot, always
+/// accessed with the same type.
+#[derive(Clone, Copy)]
+struct Location {
+    offset: i32,
+    ty: Type,
+    var: Variable,
+}
+
+#[derive(Clone, Default)]
+enum SlotState {
+    /// No access to this slot has been seen yet.
+    #[default]
+    Unused,
+    /// All accesses so far can be promoted.
+    Promotable(Vec<Location>),
+    /// The slot's address escapes, or its accesses overlap in ways that can't
+    /// be expressed as independent values.
+    Escaped,
+}
+
+/// Promotes explicit stack slots to SSA values where possible.
+///
+/// A sized stack slot is promoted when it's only accessed through
+/// `stack_load` and `stack_store`, and all of those accesses use
+/// non-overlapping, in-bounds byte ranges, with each range always accessed at
+/// the same type. Each such range becomes a variable: stores define it, loads
+/// are replaced by its current value, and block parameters are added where
+/// definitions merge. Slots whose address is taken with `stack_addr` are left
+/// untouched.
+///
+/// Loads of a promoted location which isn't stored to on some path to them
+/// read zero, matching how `FunctionBuilder` treats uninitialized variables.
+/// Promoted slots remain declared in the function, but are no longer
+/// accessed.
+pub fn promote_stack_slots(func: &mut Function) {
+    let mut slots = SecondaryMap::<StackSlot, SlotState>::new();
+    let mut num_vars = 0;
+    for block in func.layout.blocks() {
+        for inst in func.layout.block_insts(block) {
+            let (slot, offset, ty) = match access(func, inst) {
+                Some(access) => access,
+                None => continue,
+            };
+            let ty = match ty {
+                Some(ty) => ty,
+                None => {
+                    slots[slot] = SlotState::Escaped;
+                    continue;
+                }
+            };
+            let size = func.sized_stack_slots[slot].size;
+            let in_bounds = offset >= 0
+                && u32::try_from(offset)
+                    .ok()
+                    .and_then(|offset| offset.checked_add(ty.bytes()))
+                    .map_or(false, |end| end <= size);
+            if !in_bounds {
+                slots[slot] = SlotState::Escaped;
+                continue;
+            }
+            if let SlotState::Unused = slots[slot] {
+                slots[slot] = SlotState::Promotable(Vec::new());
+            }
+            let locations = match &mut slots[slot] {
+                SlotState::Promotable(locations) => locations,
+                _ => continue,
+            };
+            let existing = locations
+                .iter()
+                .find(|loc| overlaps(loc, offset, ty))
+                .map(|loc| loc.offset == offset && loc.ty == ty);
+            match existing {
+                Some(true) => {}
+                Some(false) => slots[slot] = SlotState::Escaped,
+                None => {
+                    locations.push(Location {
+                        offset,
+                        ty,
+                        var: Variable::new(num_vars),
+                    });
+                    num_vars += 1;
+                }
+            }
+        }
+    }
+    if num_vars == 0 {
+        return;
+    }
+
+    let location = |func: &Function, inst: Inst| {
+        let (slot, offset, ty) = access(func, inst)?;
+        match &slots[slot] {
+            SlotState::Promotable(locations) => locations
+                .iter()
+                .find(|loc| loc.offset == offset && Some(loc.ty) == ty)
+                .copied(),
+            _ => None,
+        }
+    };
+
+    let mut ssa = SSABuilder::default();
+    let blocks = func.layout.blocks().collect::<Vec<_>>();
+    for &block in &blocks {
+        ssa.declare_block(block);
+    }
+    for &block in &blocks {
+        let branch = match func.layout.last_inst(block) {
+            Some(inst) => inst,
+            None => continue,
+        };
+        let mut succs = func.dfg.insts[branch]
+            .branch_destination(&func.dfg.jump_tables)
+            .iter()
+            .map(|call| call.block(&func.dfg.value_lists))
+            .collect::<Vec<_>>();
+        succs.sort_unstable();
+        succs.dedup();
+        for succ in succs {
+            ssa.declare_block_predecessor(succ, branch);
+        }
+    }
+
+    let mut insts = Vec::new();
+    for &block in &blocks {
+        insts.clear();
+        insts.extend(func.layout.block_insts(block));
+        for &inst in &insts {
+            let loc = match location(func, inst) {
+                Some(loc) => loc,
+                None => continue,
+            };
+            match func.dfg.insts[inst] {
+                InstructionData::StackStore { arg, .. } => {
+                    ssa.def_var(loc.var, arg, block);
+                    func.layout.remove_inst(inst);
+                }
+                InstructionData::StackLoad { .. } => {
+                    let (value, _) = ssa.use_var(func, loc.var, loc.ty, block);
+                    let result = func.dfg.first_result(inst);
+                    func.layout.remove_inst(inst);
+                    func.dfg.detach_results(inst);
+                    func.dfg.change_to_alias(result, value);
+                }
+                _ => unreachable!(),
+            }
+        }
+    }
+    ssa.seal_all_blocks(func);
+}
+
+/// Returns the stack slot and offset that `inst` accesses along with the
+/// accessed type, or `None` for the type if the access lets the slot's
+/// address escape.
+fn access(func: &Function, inst: Inst) -> Option<(StackSlot, i32, Option<Type>)> {
+    match func.dfg.insts[inst] {
+        InstructionData::StackLoad {
+            opcode: Opcode::StackLoad,
+            stack_slot,
+            offset,
+        } => {
+            let ty = func.dfg.value_type(func.dfg.first_result(inst));
+            Some((stack_slot, offset.into(), Some(ty)))
+        }
+        InstructionData::StackStore {
+            opcode: Opcode::StackStore,
+            arg,
+            stack_slot,
+            offset,
+        } => Some((stack_slot, offset.into(), Some(func.dfg.value_type(arg)))),
+        InstructionData::StackLoad {
+            stack_slot, offset, ..
+        } => Some((stack_slot, offset.into(), None)),
+        _ => None,
+    }
+}
+
+fn overlaps(loc: &Location, offset: i32, ty: Type) -> bool {
+    let start = i64::from(offset);
+    let end = start + i64::from(ty.bytes());
+    let loc_start = i64::from(loc.offset);
+    let loc_end = loc_start + i64::from(loc.ty.bytes());
+    start < loc_end && loc_start < end
+}
+
+#[cfg(test)]
+mod tests {
+    use super::promote_stack_slots;
+    use crate::frontend::{FunctionBuilder, FunctionBuilderContext};
+    use alloc::string::ToString;
+    use cranelift_codegen::ir::types::*;
+    use cranelift_codegen::ir::{
+        AbiParam, Function, InstBuilder, Signature, StackSlotData, StackSlotKind, UserFuncName,
+    };
+    use cranelift_codegen::isa::CallConv;
+    use cranelift_codegen::settings;
+    use cranelift_codegen::verifier::verify_function;
+
+    fn build(params: &[Type], f: impl FnOnce(&mut FunctionBuilder)) -> Function {
+        let mut sig = Signature::new(CallConv::SystemV);
+        sig.params
+            .extend(params.iter().map(|&ty| AbiParam::new(ty)));
+        sig.returns.push(AbiParam::new(I32));
+        let mut fn_ctx = FunctionBuilderContext::new();
+        let mut func = Function::with_name_signature(UserFuncName::testcase("sample"), sig);
+        {
+            let mut builder = FunctionBuilder::new(&mut func, &mut fn_ctx);
+            f(&mut builder);
+            builder.seal_all_blocks();
+            builder.finalize();
+        }
+        func
+    }
+
+    fn check(mut func: Function, expected_ir: &str) {
+        promote_stack_slots(&mut func);
+        let flags = settings::Flags::new(settings::builder());
+        if let Err(errors) = verify_function(&func, &flags) {
+            panic!("{}\n{}", func.display(), errors)
+        }
+        let actual_ir = func.display().to_string();
+        assert!(
+            expected_ir == actual_ir,
+            "Expected:\n{}\nGot:\n{}",
+            expected_ir,
+            actual_ir
+        );
+    }
+
+    #[test]
+    fn straight_line() {
+        let func = build(&[I32], |builder| {
+            let slot =
+                builder.create_sized_stack_slot(StackSlotData::new(StackSlotKind::ExplicitSlot, 8));
+            let block0 = builder.create_block();
+            builder.append_block_params_for_function_params(block0);
+            builder.switch_to_block(block0);
+            let x = builder.block_params(block0)[0];
+            builder.ins().stack_store(x, slot, 0);
+            let one = builder.ins().iconst(I32, 1);
+            builder.ins().stack_store(one, slot, 4);
+            let a = builder.ins().stack_load(I32, slot, 0);
+            let b = builder.ins().stack_load(I32, slot, 4);
+            let sum = builder.ins().iadd(a, b);
+            builder.ins().return_(&[sum]);
+        });
+        check(
+            func,
+            "function %sample(i32) -> i32 system_v {
+    ss0 = explicit_slot 8
+
+block0(v0: i32):
+    v2 -> v0
+    v1 = iconst.i32 1
+    v3 -> v1
+    v4 = iadd v2, v3  ; v3 = 1
+    return v4
+}
+",
+        );
+    }
+
+    #[test]
+    fn diamond() {
+        let func = build(&[I32], |builder| {
+            let slot =
+                builder.create_sized_stack_slot(StackSlotData::new(StackSlotKind::ExplicitSlot, 4));
+            let block0 = builder.create_block();
+            let block1 = builder.create_block();
+            let block2 = builder.create_block();
+            let block3 = builder.create_block();
+            builder.append_block_params_for_function_params(block0);
+            builder.switch_to_block(block0);
+            let x = builder.block_params(block0)[0];
+            let zero = builder.ins().iconst(I32, 0);
+            builder.ins().stack_store(zero, slot, 0);
+            builder.ins().brif(x, block1, &[], block2, &[]);
+
+            builder.switch_to_block(block1);
+            builder.ins().stack_store(x, slot, 0);
+            builder.ins().jump(block3, &[]);
+
+            builder.switch_to_block(block2);
+            builder.ins().jump(block3, &[]);
+
+            builder.switch_to_block(block3);
+            let value = builder.ins().stack_load(I32, slot, 0);
+            builder.ins().return_(&[value]);
+        });
+        check(
+            func,
+            "function %sample(i32) -> i32 system_v {
+    ss0 = explicit_slot 4
+
+block0(v0: i32):
+    v1 = iconst.i32 0
+    brif v0, block1, block2
+
+block1:
+    jump block3(v0)
+
+block2:
+    jump block3(v1)  ; v1 = 0
+
+block3(v3: i32):
+    v2 -> v3
+    return v2
+}
+",
+        );
+    }
+
+    #[test]
+    fn loop_carried() {
+        let func = build(&[I32], |builder| {
+            let slot =
+                builder.create_sized_stack_slot(StackSlotData::new(StackSlotKind::ExplicitSlot, 4));
+            let block0 = builder.create_block();
+            let block1 = builder.create_block();
+            let block2 = builder.create_block();
+            builder.append_block_params_for_function_params(block0);
+            builder.switch_to_block(block0);
+            let n = builder.block_params(block0)[0];
+            let zero = builder.ins().iconst(I32, 0);
+            builder.ins().stack_store(zero, slot, 0);
+            builder.ins().jump(block1, &[]);
+
+            builder.switch_to_block(block1);
+            let i = builder.ins().stack_load(I32, slot, 0);
+            let next = builder.ins().iadd_imm(i, 1);
+            builder.ins().stack_store(next, slot, 0);
+            builder.ins().brif(next, block1, &[], block2, &[]);
+
+            builder.switch_to_block(block2);
+            let value = builder.ins().stack_load(I32, slot, 0);
+            let value = builder.ins().iadd(value, n);
+            builder.ins().return_(&[value]);
+        });
+        check(
+            func,
+            "function %sample(i32) -> i32 system_v {
+    ss0 = explicit_slot 4
+
+block0(v0: i32):
+    v1 = iconst.i32 0
+    jump block1(v1)  ; v1 = 0
+
+block1(v6: i32):
+    v2 -> v6
+    v3 = iadd_imm v2, 1
+    v7 -> v3
+    v4 -> v7
+    brif v3, block1(v3), block2
+
+block2:
+    v5 = iadd.i32 v4, v0
+    return v5
+}
+",
+        );
+    }
+
+    #[test]
+    fn escaping_slots_are_not_promoted() {
+        let func = build(&[I32], |builder| {
+            let escaped =
+                builder.create_sized_stack_slot(StackSlotData::new(StackSlotKind::ExplicitSlot, 4));
+            let mixed =
+                builder.create_sized_stack_slot(StackSlotData::new(StackSlotKind::ExplicitSlot, 8));
+            let block0 = builder.create_block();
+            builder.append_block_params_for_function_params(block0);
+            builder.switch_to_block(block0);
+            let x = builder.block_params(block0)[0];
+            builder.ins().stack_store(x, escaped, 0);
+            builder.ins().stack_addr(I64, escaped, 0);
+            builder.ins().stack_store(x, mixed, 0);
+            let a = builder.ins().stack_load(I8, mixed, 0);
+            let a = builder.ins().uextend(I32, a);
+            let b = builder.ins().stack_load(I32, escaped, 0);
+            let sum = builder.ins().iadd(a, b);
+            builder.ins().return_(&[sum]);
+        });
+        check(
+            func,
+            "function %sample(i32) -> i32 system_v {
+    ss0 = explicit_slot 4
+    ss1 = explicit_slot 8
+
+block0(v0: i32):
+    stack_store v0, ss0
+    v1 = stack_addr.i64 ss0
+    stack_store v0, ss1
+    v2 = stack_load.i8 ss1
+    v3 = uextend.i32 v2
+    v4 = stack_load.i32 ss0
+    v5 = iadd v3, v4
+    return v5
+}
+",
+        );
+    }
+}
@@ -602,6 +602,34 @@ pub trait FuncEnvironment: TargetEnvironment {
         Ok(())
     }
 
+    /// Whether or not the translator should call
+    /// [`FuncEnvironment::save_frame_state`] at each point where the function
+    /// may trap or call another function.
+    fn tracks_frame_state(&self) -> bool {
+        false
+    }
+
+    /// Optional callback for the `FunctionEnvironment` performing this
+    /// translation to record the function's locals and operand stack at
+    /// `srcloc`, for example so that their values can be located after a trap.
+    ///
+    /// This is only called when [`FuncEnvironment::tracks_frame_state`]
+    /// returns `true`, before each operator that may trap or call another
+    /// function, with `srcloc` being that operator's source location. The
+    /// `locals` are the types of all the function's locals, which are the
+    /// `Variable`s numbered from zero in declaration order and are labeled
+    /// with `ValueLabel`s of the same number, and `stack` holds the values on
+    /// the operand stack and their types, from bottom to top.
+    fn save_frame_state(
+        &mut self,
+        _builder: &mut FunctionBuilder,
+        _srcloc: ir::SourceLoc,
+        _locals: &[wasmparser::ValType],
+        _stack: &[(ir::Value, wasmparser::ValType)],
+    ) -> WasmResult<()> {
+        Ok(())
+    }
+
     /// Whether or not to force relaxed simd instructions to have deterministic
     /// lowerings meaning they will produce the same results across all hosts,
     /// regardless of the cost to performance.
@@ -13,6 +13,7 @@ use cranelift_codegen::entity::EntityRef;
 use cranelift_codegen::ir::{self, Block, InstBuilder, ValueLabel};
 use cranelift_codegen::timing;
 use cranelift_frontend::{FunctionBuilder, FunctionBuilderContext, Variable};
+use std::vec::Vec;
 use wasmparser::{self, BinaryReader, FuncValidator, FunctionBody, WasmModuleResources};
 
 /// WebAssembly to Cranelift IR function translator.
@@ -255,10 +256,21 @@ fn parse_function_body<FE: FuncEnvironment + ?Sized>(
     debug_assert_eq!(state.control_stack.len(), 1, "State not initialized");
 
     environ.before_translate_function(builder, state)?;
+    let frame_state_locals = environ.tracks_frame_state().then(|| {
+        (0..validator.len_locals())
+            .map(|i| validator.get_local_type(i).unwrap())
+            .collect::<Vec<_>>()
+    });
     while !reader.eof() {
         let pos = reader.original_position();
-        builder.set_srcloc(cur_srcloc(&reader));
+        let srcloc = cur_srcloc(&reader);
+        builder.set_srcloc(srcloc);
         let op = reader.read_operator()?;
+        if let Some(locals) = &frame_state_locals {
+            if state.reachable && may_trap_or_call(&op) {
+                save_frame_state(validator, builder, srcloc, state, environ, locals)?;
+            }
+        }
         validator.op(pos, &op)?;
         environ.before_translate_operator(&op, builder, state)?;
         translate_operator(validator, &op, builder, state, environ)?;
@@ -288,6 +300,193 @@ fn parse_function_body<FE: FuncEnvironment + ?Sized>(
     Ok(())
 }
 
+/// Passes the current types and values of the operand stack, as seen by both
+/// `validator` and `state`, to `environ` to be recorded along with the locals.
+fn save_frame_state<FE: FuncEnvironment + ?Sized>(
+    validator: &FuncValidator<impl WasmModuleResources>,
+    builder: &mut FunctionBuilder,
+    srcloc: ir::SourceLoc,
+    state: &FuncTranslationState,
+    environ: &mut FE,
+    locals: &[wasmparser::ValType],
+) -> WasmResult<()> {
+    // In reachable code the validator's operand stack has the same shape as
+    // our own, so pair up their values from the top of the stack down.
+    let height = state.stack.len();
+    debug_assert_eq!(validator.operand_stack_height() as usize, height);
+    let stack = state
+        .stack
+        .iter()
+        .enumerate()
+        .map(|(i, val)| {
+            let ty = validator.get_operand_type(height - i - 1).flatten()?;
+            Some((*val, ty))
+        })
+        .collect::<Option<Vec<_>>>();
+    match stack {
+        Some(stack) => environ.save_frame_state(builder, srcloc, locals, &stack),
+        None => Ok(()),
+    }
+}
+
+/// Returns whether `op` may trap or transfer control to another function,
+/// which is where a function's frame state may need to be recovered.
+fn may_trap_or_call(op: &wasmparser::Operator) -> bool {
+    use wasmparser::Operator::*;
+    match op {
+        Unreachable
+        | Call { .. }
+        | CallIndirect { .. }
+        | CallRef { .. }
+        | ReturnCall { .. }
+        | ReturnCallIndirect { .. }
+        | ReturnCallRef { .. }
+        | RefAsNonNull
+        | I32DivS
+        | I32DivU
+        | I32RemS
+        | I32RemU
+        | I64DivS
+        | I64DivU
+        | I64RemS
+        | I64RemU
+        | I32TruncF32S
+        | I32TruncF32U
+        | I32TruncF64S
+        | I32TruncF64U
+        | I64TruncF32S
+        | I64TruncF32U
+        | I64TruncF64S
+        | I64TruncF64U
+        | MemoryGrow { .. }
+        | MemoryInit { .. }
+        | MemoryCopy { .. }
+        | MemoryFill { .. }
+        | MemoryAtomicNotify { .. }
+        | MemoryAtomicWait32 { .. }
+        | MemoryAtomicWait64 { .. }
+        | TableGet { .. }
+        | TableSet { .. }
+        | TableGrow { .. }
+        | TableFill { .. }
+        | TableCopy { .. }
+        | TableInit { .. } => true,
+
+        // Every operator with a `memarg` accesses linear memory and so may
+        // trap when out of bounds.
+        I32Load { .. }
+        | I64Load { .. }
+        | F32Load { .. }
+        | F64Load { .. }
+        | I32Load8S { .. }
+        | I32Load8U { .. }
+        | I32Load16S { .. }
+        | I32Load16U { .. }
+        | I64Load8S { .. }
+        | I64Load8U { .. }
+        | I64Load16S { .. }
+        | I64Load16U { .. }
+        | I64Load32S { .. }
+        | I64Load32U { .. }
+        | I32Store { .. }
+        | I64Store { .. }
+        | F32Store { .. }
+        | F64Store { .. }
+        | I32Store8 { .. }
+        | I32Store16 { .. }
+        | I64Store8 { .. }
+        | I64Store16 { .. }
+        | I64Store32 { .. }
+        | V128Load { .. }
+        | V128Load8x8S { .. }
+        | V128Load8x8U { .. }
+        | V128Load16x4S { .. }
+        | V128Load16x4U { .. }
+        | V128Load32x2S { .. }
+        | V128Load32x2U { .. }
+        | V128Load8Splat { .. }
+        | V128Load16Splat { .. }
+        | V128Load32Splat { .. }
+        | V128Load64Splat { .. }
+        | V128Load32Zero { .. }
+        | V128Load64Zero { .. }
+        | V128Store { .. }
+        | V128Load8Lane { .. }
+        | V128Load16Lane { .. }
+        | V128Load32Lane { .. }
+        | V128Load64Lane { .. }
+        | V128Store8Lane { .. }
+        | V128Store16Lane { .. }
+        | V128Store32Lane { .. }
+        | V128Store64Lane { .. }
+        | I32AtomicLoad { .. }
+        | I64AtomicLoad { .. }
+        | I32AtomicLoad8U { .. }
+        | I32AtomicLoad16U { .. }
+        | I64AtomicLoad8U { .. }
+        | I64AtomicLoad16U { .. }
+        | I64AtomicLoad32U { .. }
+        | I32AtomicStore { .. }
+        | I64AtomicStore { .. }
+        | I32AtomicStore8 { .. }
+        | I32AtomicStore16 { .. }
+        | I64AtomicStore8 { .. }
+        | I64AtomicStore16 { .. }
+        | I64AtomicStore32 { .. }
+        | I32AtomicRmwAdd { .. }
+        | I64AtomicRmwAdd { .. }
+        | I32AtomicRmw8AddU { .. }
+        | I32AtomicRmw16AddU { .. }
+        | I64AtomicRmw8AddU { .. }
+        | I64AtomicRmw16AddU { .. }
+        | I64AtomicRmw32AddU { .. }
+        | I32AtomicRmwSub { .. }
+        | I64AtomicRmwSub { .. }
+        | I32AtomicRmw8SubU { .. }
+        | I32AtomicRmw16SubU { .. }
+        | I64AtomicRmw8SubU { .. }
+        | I64AtomicRmw16SubU { .. }
+        | I64AtomicRmw32SubU { .. }
+        | I32AtomicRmwAnd { .. }
+        | I64AtomicRmwAnd { .. }
+        | I32AtomicRmw8AndU { .. }
+        | I32AtomicRmw16AndU { .. }
+        | I64AtomicRmw8AndU { .. }
+        | I64AtomicRmw16AndU { .. }
+        | I64AtomicRmw32AndU { .. }
+        | I32AtomicRmwOr { .. }
+        | I64AtomicRmwOr { .. }
+        | I32AtomicRmw8OrU { .. }
+        | I32AtomicRmw16OrU { .. }
+        | I64AtomicRmw8OrU { .. }
+        | I64AtomicRmw16OrU { .. }
+        | I64AtomicRmw32OrU { .. }
+        | I32AtomicRmwXor { .. }
+        | I64AtomicRmwXor { .. }
+        | I32AtomicRmw8XorU { .. }
+        | I32AtomicRmw16XorU { .. }
+        | I64AtomicRmw8XorU { .. }
+        | I64AtomicRmw16XorU { .. }
+        | I64AtomicRmw32XorU { .. }
+        | I32AtomicRmwXchg { .. }
+        | I64AtomicRmwXchg { .. }
+        | I32AtomicRmw8XchgU { .. }
+        | I32AtomicRmw16XchgU { .. }
+        | I64AtomicRmw8XchgU { .. }
+        | I64AtomicRmw16XchgU { .. }
+        | I64AtomicRmw32XchgU { .. }
+        | I32AtomicRmwCmpxchg { .. }
+        | I64AtomicRmwCmpxchg { .. }
+        | I32AtomicRmw8CmpxchgU { .. }
+        | I32AtomicRmw16CmpxchgU { .. }
+        | I64AtomicRmw8CmpxchgU { .. }
+        | I64AtomicRmw16CmpxchgU { .. }
+        | I64AtomicRmw32CmpxchgU { .. } => true,
+
+        _ => false,
+    }
+}
+
 /// Get the current source location from a reader.
 fn cur_srcloc(reader: &BinaryReader) -> ir::SourceLoc {
     // We record source locations as byte code offsets relative to the beginning of the file.
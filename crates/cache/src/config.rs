@@ -1,5 +1,6 @@
 //! Module for configuring the cache system.
 
+use super::store::FileSystemStore;
 use super::Worker;
 use anyhow::{anyhow, bail, Context, Result};
 use directories_next::ProjectDirs;
@@ -14,6 +15,7 @@ use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicUsize, Ordering::SeqCst};
 use std::sync::Arc;
 use std::time::Duration;
+use wasmtime_environ::CacheStore;
 
 // wrapped, so we have named section in config,
 // also, for possible future compatibility
@@ -92,12 +94,15 @@ pub struct CacheConfig {
     worker: Option<Worker>,
     #[serde(skip)]
     state: Arc<CacheState>,
+    #[serde(skip)]
+    store: Option<Arc<dyn CacheStore>>,
 }
 
 #[derive(Default, Debug)]
 struct CacheState {
     hits: AtomicUsize,
     misses: AtomicUsize,
+    evictions: AtomicUsize,
 }
 
 /// Creates a new configuration file at specified path, or default path if None is passed.
@@ -333,6 +338,7 @@ impl CacheConfig {
             files_total_size_limit_percent_if_deleting: None,
             worker: None,
             state: Arc::new(CacheState::default()),
+            store: None,
         }
     }
 
@@ -364,6 +370,48 @@ impl CacheConfig {
         Ok(config)
     }
 
+    /// Creates a new set of configuration for a cache which keeps compiled
+    /// modules in `store` rather than on the local filesystem.
+    ///
+    /// All settings have their default values, and since the cache has no
+    /// directory, the settings for its cleanup and recompression are unused.
+    pub fn new_with_store(store: Arc<dyn CacheStore>) -> Result<Self> {
+        let mut config = Self::new_cache_enabled_template();
+        config.validate_worker_event_queue_size_or_default();
+        config.validate_baseline_compression_level_or_default()?;
+        config.validate_optimized_compression_level_or_default()?;
+        config.store = Some(store);
+        Ok(config)
+    }
+
+    /// Keeps compiled modules in `store` rather than on the local filesystem
+    /// under [`CacheConfig::directory`].
+    ///
+    /// Modules are still compressed with the configured compression level and
+    /// carry a checksum which is verified when they're read back, but custom
+    /// stores are responsible for their own eviction: the cache's cleanup and
+    /// recompression only manage the local filesystem.
+    pub fn set_store(&mut self, store: Arc<dyn CacheStore>) {
+        self.store = Some(store);
+    }
+
+    /// Returns whether modules are kept in a custom store set with
+    /// [`CacheConfig::set_store`].
+    pub fn has_custom_store(&self) -> bool {
+        self.store.is_some()
+    }
+
+    pub(crate) fn store(&self) -> Arc<dyn CacheStore> {
+        match &self.store {
+            Some(store) => store.clone(),
+            None => Arc::new(FileSystemStore::new(self.modules_directory())),
+        }
+    }
+
+    pub(crate) fn modules_directory(&self) -> PathBuf {
+        self.directory().join("modules")
+    }
+
     fn spawn_worker(&mut self) {
         if self.enabled {
             self.worker = Some(Worker::start_new(self));
@@ -376,23 +424,53 @@ impl CacheConfig {
     }
 
     /// Returns the number of cache hits seen so far
+    ///
+    /// This only counts hits of this configuration in the current process;
+    /// see [`CacheConfig::counters`] for the totals of the cache directory.
     pub fn cache_hits(&self) -> usize {
         self.state.hits.load(SeqCst)
     }
 
     /// Returns the number of cache misses seen so far
+    ///
+    /// This only counts misses of this configuration in the current process;
+    /// see [`CacheConfig::counters`] for the totals of the cache directory.
     pub fn cache_misses(&self) -> usize {
         self.state.misses.load(SeqCst)
     }
 
-    pub(crate) fn on_cache_get_async(&self, path: impl AsRef<Path>) {
+    /// Returns the number of modules the cleanup task has evicted from the
+    /// cache so far to stay within its size and file count limits
+    ///
+    /// This only counts evictions by this configuration's cleanup task in the
+    /// current process; see [`CacheConfig::counters`] for the totals of the
+    /// cache directory.
+    pub fn cache_evictions(&self) -> usize {
+        self.state.evictions.load(SeqCst)
+    }
+
+    pub(crate) fn on_cache_evict(&self) {
+        self.state.evictions.fetch_add(1, SeqCst);
+    }
+
+    pub(crate) fn on_cache_get_async(&self, key: &str) {
         self.state.hits.fetch_add(1, SeqCst);
-        self.worker().on_cache_get_async(path)
+        if self.store.is_none() {
+            // The counters are updated here rather than by the worker, which
+            // short-lived processes often exit before it gets to run.
+            self.update_counters(|c| c.hits += 1);
+            self.worker()
+                .on_cache_get_async(self.modules_directory().join(key))
+        }
     }
 
-    pub(crate) fn on_cache_update_async(&self, path: impl AsRef<Path>) {
+    pub(crate) fn on_cache_update_async(&self, key: &str) {
         self.state.misses.fetch_add(1, SeqCst);
-        self.worker().on_cache_update_async(path)
+        if self.store.is_none() {
+            self.update_counters(|c| c.misses += 1);
+            self.worker()
+                .on_cache_update_async(self.modules_directory().join(key))
+        }
     }
 
     fn load_and_parse_file(config_file: Option<&Path>) -> Result<Self> {
@@ -0,0 +1,275 @@
+//! Inspection and management of the modules stored in the cache.
+
+use super::worker::{read_stats_file, write_stats_file, ModuleCacheStatistics};
+use super::{fs_write_atomic, CacheConfig};
+use anyhow::{bail, Context, Result};
+use log::{trace, warn};
+use serde_derive::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Information about a compiled module stored in the cache.
+#[derive(Debug, Clone)]
+pub struct CacheEntryInfo {
+    path: PathBuf,
+    compiler: String,
+    key: String,
+    size: u64,
+    usages: u64,
+    last_used: Option<SystemTime>,
+    pinned: bool,
+}
+
+impl CacheEntryInfo {
+    /// Returns the path of the file holding the compressed module.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the compiler and version the module was compiled with, e.g.
+    /// `wasmtime-cranelift-19.0.0`.
+    pub fn compiler(&self) -> &str {
+        &self.compiler
+    }
+
+    /// Returns the key identifying the module within its compiler's entries.
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Returns the size of the module on disk, in bytes.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Returns how many times the module has been stored in or loaded from
+    /// the cache.
+    pub fn usages(&self) -> u64 {
+        self.usages
+    }
+
+    /// Returns when the module was last stored in or loaded from the cache, if
+    /// known.
+    pub fn last_used(&self) -> Option<SystemTime> {
+        self.last_used
+    }
+
+    /// Returns whether the module is pinned, in which case the cleanup task
+    /// never evicts it.
+    pub fn pinned(&self) -> bool {
+        self.pinned
+    }
+
+    fn stats_path(&self) -> PathBuf {
+        stats_path(&self.path)
+    }
+}
+
+/// The name of the file in the cache directory which holds its
+/// [`CacheCounters`].
+pub(super) const COUNTERS_FILE: &str = "counters.toml";
+
+/// Counters of the cache's hits, misses and evictions, accumulated by all
+/// processes which use the same cache directory.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct CacheCounters {
+    #[serde(default)]
+    pub(crate) hits: u64,
+    #[serde(default)]
+    pub(crate) misses: u64,
+    #[serde(default)]
+    pub(crate) evictions: u64,
+}
+
+impl CacheCounters {
+    /// Returns how many times a module was found in the cache.
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    /// Returns how many times a module wasn't found in the cache, and was
+    /// compiled and stored in it instead.
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+
+    /// Returns how many modules the cleanup task has evicted from the cache to
+    /// stay within its size and file count limits.
+    pub fn evictions(&self) -> u64 {
+        self.evictions
+    }
+}
+
+impl CacheConfig {
+    /// Returns the counters persisted in the cache directory, which cover all
+    /// processes that have used it.
+    ///
+    /// Unlike [`CacheConfig::cache_hits`] and the other per-process counters,
+    /// these survive across runs. They are updated on a best-effort basis
+    /// though: updates from processes using the cache at the same time can be
+    /// lost, as can evictions by a cleanup task which didn't finish.
+    ///
+    /// Returns zeroed counters if the cache is disabled or keeps its modules
+    /// in a custom [`CacheStore`](crate::CacheStore).
+    pub fn counters(&self) -> Result<CacheCounters> {
+        if !self.enabled() || self.has_custom_store() {
+            return Ok(CacheCounters::default());
+        }
+        let path = self.counters_path();
+        match fs::read_to_string(&path) {
+            Ok(contents) => toml::from_str(&contents)
+                .with_context(|| format!("failed to parse {}", path.display())),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(CacheCounters::default()),
+            Err(e) => Err(e).with_context(|| format!("failed to read {}", path.display())),
+        }
+    }
+
+    /// Applies `update` to the counters persisted in the cache directory.
+    ///
+    /// Failures are only logged, like for the modules' statistics files.
+    pub(crate) fn update_counters(&self, update: impl FnOnce(&mut CacheCounters)) {
+        let path = self.counters_path();
+        let mut counters = fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| {
+                toml::from_str(&contents)
+                    .map_err(|err| {
+                        trace!(
+                            "Failed to parse counters file, path: {}, err: {}",
+                            path.display(),
+                            err
+                        )
+                    })
+                    .ok()
+            })
+            .unwrap_or_default();
+        update(&mut counters);
+        let result = toml::to_string_pretty(&counters)
+            .map_err(|err| err.to_string())
+            .and_then(|serialized| {
+                fs_write_atomic(&path, "counters", serialized.as_bytes())
+                    .map_err(|err| err.to_string())
+            });
+        if let Err(err) = result {
+            warn!(
+                "Failed to write counters file, path: {}, err: {}",
+                path.display(),
+                err
+            );
+        }
+    }
+
+    fn counters_path(&self) -> PathBuf {
+        self.directory().join(COUNTERS_FILE)
+    }
+
+    /// Lists the modules stored in the cache, for all compilers.
+    ///
+    /// Returns an empty list if the cache is disabled or keeps its modules in
+    /// a custom [`CacheStore`](crate::CacheStore).
+    pub fn entries(&self) -> Result<Vec<CacheEntryInfo>> {
+        let mut entries = Vec::new();
+        if !self.enabled() || self.has_custom_store() {
+            return Ok(entries);
+        }
+        let modules_dir = self.modules_directory();
+        for compiler in read_dir_if_exists(&modules_dir)? {
+            let compiler = compiler?;
+            if !compiler.file_type()?.is_dir() {
+                continue;
+            }
+            let compiler_name = compiler.file_name().to_string_lossy().into_owned();
+            for module in read_dir_if_exists(&compiler.path())? {
+                let module = module?;
+                let path = module.path();
+                // Skip stats files, locks, and temporary files.
+                if path.extension().is_some() || !module.file_type()?.is_file() {
+                    continue;
+                }
+                let metadata = module.metadata()?;
+                let stats_path = stats_path(&path);
+                let stats = read_stats_file(&stats_path);
+                let last_used = fs::metadata(&stats_path)
+                    .and_then(|m| m.modified())
+                    .or_else(|_| metadata.modified())
+                    .ok();
+                entries.push(CacheEntryInfo {
+                    key: module.file_name().to_string_lossy().into_owned(),
+                    compiler: compiler_name.clone(),
+                    size: metadata.len(),
+                    usages: stats.as_ref().map_or(0, |s| s.usages),
+                    pinned: stats.as_ref().map_or(false, |s| s.pinned),
+                    last_used,
+                    path,
+                });
+            }
+        }
+        Ok(entries)
+    }
+
+    /// Pins the module, so that the cleanup task never evicts it and
+    /// [`CacheConfig::purge`] skips it.
+    ///
+    /// Pinned modules don't count towards the cache's size and file count
+    /// limits.
+    pub fn pin(&self, entry: &CacheEntryInfo) -> Result<()> {
+        self.set_pinned(entry, true)
+    }
+
+    /// Unpins a module previously pinned with [`CacheConfig::pin`].
+    pub fn unpin(&self, entry: &CacheEntryInfo) -> Result<()> {
+        self.set_pinned(entry, false)
+    }
+
+    fn set_pinned(&self, entry: &CacheEntryInfo, pinned: bool) -> Result<()> {
+        let stats_path = entry.stats_path();
+        let mut stats =
+            read_stats_file(&stats_path).unwrap_or_else(|| ModuleCacheStatistics::default(self));
+        stats.pinned = pinned;
+        if !write_stats_file(&stats_path, &stats) {
+            bail!("failed to write stats file: {}", stats_path.display());
+        }
+        Ok(())
+    }
+
+    /// Removes the module from the cache, even if it is pinned.
+    pub fn remove(&self, entry: &CacheEntryInfo) -> Result<()> {
+        fs::remove_file(&entry.path)
+            .with_context(|| format!("failed to remove {}", entry.path.display()))?;
+        match fs::remove_file(entry.stats_path()) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => {
+                Err(e).with_context(|| format!("failed to remove {}", entry.stats_path().display()))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Removes all modules which aren't pinned from the cache, returning how
+    /// many were removed.
+    pub fn purge(&self) -> Result<usize> {
+        let mut removed = 0;
+        for entry in self.entries()? {
+            if !entry.pinned {
+                self.remove(&entry)?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+}
+
+fn stats_path(module_path: &Path) -> PathBuf {
+    let mut name = module_path.file_name().unwrap_or_default().to_owned();
+    name.push(".stats");
+    module_path.with_file_name(name)
+}
+
+fn read_dir_if_exists(path: &Path) -> Result<Vec<io::Result<fs::DirEntry>>> {
+    match fs::read_dir(path) {
+        Ok(entries) => Ok(entries.collect()),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e).with_context(|| format!("failed to read {}", path.display())),
+    }
+}
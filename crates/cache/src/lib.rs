@@ -1,25 +1,31 @@
 use base64::Engine;
-use log::{debug, trace, warn};
+use log::{trace, warn};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use std::hash::Hash;
 use std::hash::Hasher;
 use std::io::Write;
-use std::path::{Path, PathBuf};
+use std::path::Path;
+use std::sync::Arc;
 use std::{fs, io};
 
 #[macro_use] // for tests
 mod config;
+mod entries;
+mod store;
 mod worker;
 
 pub use config::{create_new_config, CacheConfig};
+pub use entries::{CacheCounters, CacheEntryInfo};
+pub use wasmtime_environ::CacheStore;
 use worker::Worker;
 
 /// Module level cache entry.
 pub struct ModuleCacheEntry<'config>(Option<ModuleCacheEntryInner<'config>>);
 
 struct ModuleCacheEntryInner<'config> {
-    root_path: PathBuf,
+    compiler_dir: String,
+    store: Arc<dyn CacheStore>,
     cache_config: &'config CacheConfig,
 }
 
@@ -89,18 +95,18 @@ impl<'config> ModuleCacheEntry<'config> {
         // standard encoding uses '/' which can't be used for filename
         let hash = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(&hash);
 
-        if let Some(cached_val) = inner.get_data(&hash) {
+        let key = format!("{}/{}", inner.compiler_dir, hash);
+
+        if let Some(cached_val) = inner.get_data(&key) {
             if let Some(val) = deserialize(state, cached_val) {
-                let mod_cache_path = inner.root_path.join(&hash);
-                inner.cache_config.on_cache_get_async(&mod_cache_path); // call on success
+                inner.cache_config.on_cache_get_async(&key); // call on success
                 return Ok(val);
             }
         }
         let val_to_cache = compute(state)?;
         if let Some(bytes) = serialize(state, &val_to_cache) {
-            if inner.update_data(&hash, &bytes).is_some() {
-                let mod_cache_path = inner.root_path.join(&hash);
-                inner.cache_config.on_cache_update_async(&mod_cache_path); // call on success
+            if inner.update_data(&key, &bytes).is_some() {
+                inner.cache_config.on_cache_update_async(&key); // call on success
             }
         }
         Ok(val_to_cache)
@@ -142,67 +148,39 @@ impl<'config> ModuleCacheEntryInner<'config> {
                 comp_ver = env!("GIT_REV"),
             )
         };
-        let root_path = cache_config.directory().join("modules").join(compiler_dir);
+        let store = cache_config.store();
 
         Self {
-            root_path,
+            compiler_dir,
+            store,
             cache_config,
         }
     }
 
-    fn get_data(&self, hash: &str) -> Option<Vec<u8>> {
-        let mod_cache_path = self.root_path.join(hash);
-        trace!("get_data() for path: {}", mod_cache_path.display());
-        let compressed_cache_bytes = fs::read(&mod_cache_path).ok()?;
+    fn get_data(&self, key: &str) -> Option<Vec<u8>> {
+        trace!("get_data() for key: {}", key);
+        let compressed_cache_bytes = self.store.get(key.as_bytes())?;
+        // The compressed data carries a checksum, so this also catches
+        // corruption in the store.
         let cache_bytes = zstd::decode_all(&compressed_cache_bytes[..])
             .map_err(|err| warn!("Failed to decompress cached code: {}", err))
             .ok()?;
         Some(cache_bytes)
     }
 
-    fn update_data(&self, hash: &str, serialized_data: &[u8]) -> Option<()> {
-        let mod_cache_path = self.root_path.join(hash);
-        trace!("update_data() for path: {}", mod_cache_path.display());
-        let compressed_data = zstd::encode_all(
-            &serialized_data[..],
+    fn update_data(&self, key: &str, serialized_data: &[u8]) -> Option<()> {
+        trace!("update_data() for key: {}", key);
+        let compressed_data = compress(
+            serialized_data,
             self.cache_config.baseline_compression_level(),
         )
         .map_err(|err| warn!("Failed to compress cached code: {}", err))
         .ok()?;
 
-        // Optimize syscalls: first, try writing to disk. It should succeed in most cases.
-        // Otherwise, try creating the cache directory and retry writing to the file.
-        if fs_write_atomic(&mod_cache_path, "mod", &compressed_data).is_ok() {
-            return Some(());
-        }
-
-        debug!(
-            "Attempting to create the cache directory, because \
-             failed to write cached code to disk, path: {}",
-            mod_cache_path.display(),
-        );
-
-        let cache_dir = mod_cache_path.parent().unwrap();
-        fs::create_dir_all(cache_dir)
-            .map_err(|err| {
-                warn!(
-                    "Failed to create cache directory, path: {}, message: {}",
-                    cache_dir.display(),
-                    err
-                )
-            })
-            .ok()?;
-
-        match fs_write_atomic(&mod_cache_path, "mod", &compressed_data) {
-            Ok(_) => Some(()),
-            Err(err) => {
-                warn!(
-                    "Failed to write file with rename, target path: {}, err: {}",
-                    mod_cache_path.display(),
-                    err
-                );
-                None
-            }
+        if self.store.insert(key.as_bytes(), compressed_data) {
+            Some(())
+        } else {
+            None
         }
     }
 }
@@ -217,6 +195,15 @@ impl Hasher for Sha256Hasher {
     }
 }
 
+/// Compresses `data` for storage in the cache, including a checksum which is
+/// verified when it is decompressed.
+fn compress(data: &[u8], level: i32) -> io::Result<Vec<u8>> {
+    let mut encoder = zstd::Encoder::new(Vec::new(), level)?;
+    encoder.include_checksum(true)?;
+    encoder.write_all(data)?;
+    encoder.finish()
+}
+
 // Assumption: path inside cache directory.
 // Then, we don't have to use sound OS-specific exclusive file access.
 // Note: there's no need to remove temporary file here - cleanup task will do it later.
@@ -0,0 +1,75 @@
+//! The default [`CacheStore`], which keeps modules on the local filesystem
+//! under the cache directory.
+
+use super::fs_write_atomic;
+use log::{debug, warn};
+use std::borrow::Cow;
+use std::fs;
+use std::path::PathBuf;
+use wasmtime_environ::CacheStore;
+
+#[derive(Debug)]
+pub(crate) struct FileSystemStore {
+    root_path: PathBuf,
+}
+
+impl FileSystemStore {
+    pub(crate) fn new(root_path: PathBuf) -> Self {
+        Self { root_path }
+    }
+
+    /// Returns the path of the file holding `key`, which is relative to the
+    /// store's root.
+    pub(crate) fn path(&self, key: &[u8]) -> Option<PathBuf> {
+        let key = std::str::from_utf8(key).ok()?;
+        Some(self.root_path.join(key))
+    }
+}
+
+impl CacheStore for FileSystemStore {
+    fn get(&self, key: &[u8]) -> Option<Cow<[u8]>> {
+        let mod_cache_path = self.path(key)?;
+        fs::read(mod_cache_path).ok().map(Cow::Owned)
+    }
+
+    fn insert(&self, key: &[u8], value: Vec<u8>) -> bool {
+        let mod_cache_path = match self.path(key) {
+            Some(path) => path,
+            None => return false,
+        };
+
+        // Optimize syscalls: first, try writing to disk. It should succeed in most cases.
+        // Otherwise, try creating the cache directory and retry writing to the file.
+        if fs_write_atomic(&mod_cache_path, "mod", &value).is_ok() {
+            return true;
+        }
+
+        debug!(
+            "Attempting to create the cache directory, because \
+             failed to write cached code to disk, path: {}",
+            mod_cache_path.display(),
+        );
+
+        let cache_dir = mod_cache_path.parent().unwrap();
+        if let Err(err) = fs::create_dir_all(cache_dir) {
+            warn!(
+                "Failed to create cache directory, path: {}, message: {}",
+                cache_dir.display(),
+                err
+            );
+            return false;
+        }
+
+        match fs_write_atomic(&mod_cache_path, "mod", &value) {
+            Ok(_) => true,
+            Err(err) => {
+                warn!(
+                    "Failed to write file with rename, target path: {}, err: {}",
+                    mod_cache_path.display(),
+                    err
+                );
+                false
+            }
+        }
+    }
+}
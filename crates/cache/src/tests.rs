@@ -90,3 +90,121 @@ fn test_write_read_cache() {
     entry1.get_data::<_, i32, i32>(4, |_| panic!()).unwrap();
     entry2.get_data::<_, i32, i32>(1, |_| panic!()).unwrap();
 }
+
+#[test]
+fn test_entries_pin_and_purge() {
+    let (_tempdir, cache_dir, config_path) = test_prolog();
+    let cache_config = load_config!(
+        config_path,
+        "[cache]\n\
+         enabled = true\n\
+         directory = '{cache_dir}'\n\
+         worker-event-queue-size = '16'\n\
+         cleanup-interval = '1h'",
+        cache_dir
+    );
+
+    let entry1 = ModuleCacheEntry::from_inner(ModuleCacheEntryInner::new("test-1", &cache_config));
+    let entry2 = ModuleCacheEntry::from_inner(ModuleCacheEntryInner::new("test-2", &cache_config));
+    entry1.get_data::<_, i32, i32>(1, |_| Ok(100)).unwrap();
+    entry1.get_data::<_, i32, i32>(2, |_| Ok(100)).unwrap();
+    entry1.get_data::<_, i32, i32>(2, |_| panic!()).unwrap();
+    entry2.get_data::<_, i32, i32>(1, |_| Ok(100)).unwrap();
+    cache_config.worker().wait_for_all_events_handled();
+    assert_eq!(cache_config.cache_hits(), 1);
+    assert_eq!(cache_config.cache_misses(), 3);
+
+    // The counters are also persisted in the cache directory, where other
+    // configurations using it see them.
+    let other_config = load_config!(
+        config_path,
+        "[cache]\n\
+         enabled = true\n\
+         directory = '{cache_dir}'\n\
+         cleanup-interval = '1h'",
+        cache_dir
+    );
+    assert_eq!(other_config.cache_hits(), 0);
+    let counters = other_config.counters().unwrap();
+    assert_eq!(counters.hits(), 1);
+    assert_eq!(counters.misses(), 3);
+    assert_eq!(counters.evictions(), 0);
+
+    let mut entries = cache_config.entries().unwrap();
+    entries.sort_by_key(|e| (e.compiler().to_string(), e.usages()));
+    assert_eq!(entries.len(), 3);
+    assert!(entries[0].compiler().starts_with("test-1-"));
+    assert!(entries[1].compiler().starts_with("test-1-"));
+    assert!(entries[2].compiler().starts_with("test-2-"));
+    assert_eq!(entries[0].usages(), 1);
+    assert_eq!(entries[1].usages(), 2);
+    assert!(entries
+        .iter()
+        .all(|e| e.size() > 0 && e.last_used().is_some()));
+    assert!(entries.iter().all(|e| !e.pinned()));
+
+    // Pinned entries survive a purge, and stay pinned when used again.
+    cache_config.pin(&entries[1]).unwrap();
+    assert_eq!(cache_config.purge().unwrap(), 2);
+    let remaining = cache_config.entries().unwrap();
+    assert_eq!(remaining.len(), 1);
+    assert_eq!(remaining[0].key(), entries[1].key());
+    assert!(remaining[0].pinned());
+    entry1.get_data::<_, i32, i32>(1, |_| Ok(100)).unwrap();
+    entry1.get_data::<_, i32, i32>(2, |_| panic!()).unwrap();
+    cache_config.worker().wait_for_all_events_handled();
+    assert_eq!(cache_config.entries().unwrap().len(), 2);
+    assert_eq!(cache_config.purge().unwrap(), 1);
+
+    // Unpinned entries can be purged again.
+    cache_config.unpin(&remaining[0]).unwrap();
+    assert_eq!(cache_config.purge().unwrap(), 1);
+    assert!(cache_config.entries().unwrap().is_empty());
+}
+
+#[derive(Debug, Default)]
+struct InMemoryStore(std::sync::Mutex<std::collections::HashMap<Vec<u8>, Vec<u8>>>);
+
+impl CacheStore for InMemoryStore {
+    fn get(&self, key: &[u8]) -> Option<std::borrow::Cow<[u8]>> {
+        let value = self.0.lock().unwrap().get(key)?.clone();
+        Some(value.into())
+    }
+
+    fn insert(&self, key: &[u8], value: Vec<u8>) -> bool {
+        self.0.lock().unwrap().insert(key.to_vec(), value);
+        true
+    }
+}
+
+#[test]
+fn test_custom_store() {
+    let store = Arc::new(InMemoryStore::default());
+    let cache_config = CacheConfig::new_with_store(store.clone()).unwrap();
+    assert!(cache_config.enabled());
+    assert!(cache_config.has_custom_store());
+
+    let entry = ModuleCacheEntry::from_inner(ModuleCacheEntryInner::new("test", &cache_config));
+    assert_eq!(entry.get_data::<_, i32, i32>(1, |_| Ok(100)).unwrap(), 100);
+    assert_eq!(entry.get_data::<_, i32, i32>(1, |_| panic!()).unwrap(), 100);
+    assert_eq!(entry.get_data::<_, i32, i32>(2, |_| Ok(200)).unwrap(), 200);
+    assert_eq!(cache_config.cache_hits(), 1);
+    assert_eq!(cache_config.cache_misses(), 2);
+    assert!(cache_config.entries().unwrap().is_empty());
+
+    // Stored values are compressed just like on the filesystem, and
+    // corrupting them is detected so the value is computed again.
+    let mut values = store.0.lock().unwrap();
+    assert_eq!(values.len(), 2);
+    for value in values.values_mut() {
+        assert!(zstd::decode_all(&value[..]).is_ok());
+        let last = value.len() - 1;
+        value[last] ^= 0xff;
+        assert!(zstd::decode_all(&value[..]).is_err());
+    }
+    drop(values);
+    assert_eq!(entry.get_data::<_, i32, i32>(1, |_| Ok(100)).unwrap(), 100);
+    assert_eq!(entry.get_data::<_, i32, i32>(1, |_| panic!()).unwrap(), 100);
+    assert_eq!(cache_config.cache_hits(), 2);
+    assert_eq!(cache_config.cache_misses(), 3);
+}
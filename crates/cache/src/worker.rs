@@ -5,7 +5,8 @@
 //! but we guarantee eventual consistency and fault tolerancy.
 //! Background tasks can be CPU intensive, but the worker thread has low priority.
 
-use super::{fs_write_atomic, CacheConfig};
+use super::entries::COUNTERS_FILE;
+use super::{compress, fs_write_atomic, CacheConfig};
 use log::{debug, info, trace, warn};
 use serde_derive::{Deserialize, Serialize};
 use std::cmp;
@@ -149,17 +150,21 @@ impl fmt::Debug for Worker {
 }
 
 #[derive(Serialize, Deserialize)]
-struct ModuleCacheStatistics {
+pub(super) struct ModuleCacheStatistics {
     pub usages: u64,
     #[serde(rename = "optimized-compression")]
     pub compression_level: i32,
+    /// Pinned modules are never removed by the cleanup task.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub pinned: bool,
 }
 
 impl ModuleCacheStatistics {
-    fn default(cache_config: &CacheConfig) -> Self {
+    pub(super) fn default(cache_config: &CacheConfig) -> Self {
         Self {
             usages: 0,
             compression_level: cache_config.baseline_compression_level(),
+            pinned: false,
         }
     }
 }
@@ -320,7 +325,7 @@ impl WorkerThread {
         );
 
         let recompressed_cache_bytes = unwrap_or_warn!(
-            zstd::encode_all(&cache_bytes[..], opt_compr_lvl),
+            compress(&cache_bytes, opt_compr_lvl),
             return,
             "Failed to compress cached code",
             path
@@ -401,9 +406,10 @@ impl WorkerThread {
             .expect("Expected valid cache file name");
         let stats_path = path.with_file_name(format!("{}.stats", filename));
 
-        // create and write stats file
+        // create and write stats file, keeping the module pinned if it was
         let mut stats = ModuleCacheStatistics::default(&self.cache_config);
         stats.usages += 1;
+        stats.pinned = read_stats_file(&stats_path).map_or(false, |old| old.pinned);
         write_stats_file(&stats_path, &stats);
 
         // ---------------------- step 2: perform cleanup task if needed
@@ -498,6 +504,7 @@ impl WorkerThread {
             }
         }
 
+        let mut evicted = 0;
         if let Some(idx) = start_delete_idx {
             for item in &cache_index[idx..] {
                 let (result, path, entity) = match item {
@@ -510,16 +517,26 @@ impl WorkerThread {
                         (fs::remove_dir_all(path), path, "directory")
                     }
                 };
-                if let Err(err) = result {
-                    warn!(
+                match result {
+                    Ok(()) => {
+                        if let CacheEntry::Recognized { .. } = item {
+                            self.cache_config.on_cache_evict();
+                            evicted += 1;
+                        }
+                    }
+                    Err(err) => warn!(
                         "Failed to remove {} during cleanup, path: {}, err: {}",
                         entity,
                         path.display(),
                         err
-                    );
+                    ),
                 }
             }
         }
+        if evicted > 0 {
+            self.cache_config
+                .update_counters(|c| c.evictions += evicted);
+        }
 
         trace!("Task finished: clean up cache");
     }
@@ -591,6 +608,9 @@ impl WorkerThread {
                 match (level, path.is_dir()) {
                     (0..=1, true) => enter_dir(vec, &path, level + 1, cache_config),
                     (0..=1, false) => {
+                        if level == 0 && path.file_name() == Some(OsStr::new(COUNTERS_FILE)) {
+                            continue; // the cache's counters aren't subject to cleanup
+                        }
                         if level == 0
                             && path.file_stem() == Some(OsStr::new(".cleanup"))
                                 && path.extension().is_some()
@@ -659,6 +679,10 @@ impl WorkerThread {
                 // construct a cache entry
                 match (mod_, stats_, is_mod) {
                     (Some((mod_path, mod_entry)), Some((stats_path, stats_entry)), true) => {
+                        // pinned modules don't take part in the cleanup at all
+                        if read_stats_file(stats_path).map_or(false, |stats| stats.pinned) {
+                            continue;
+                        }
                         let mod_metadata = unwrap_or!(
                             mod_entry.metadata(),
                             add_unrecognized_and!([file: stats_path, file: mod_path], continue),
@@ -727,7 +751,7 @@ impl WorkerThread {
     }
 }
 
-fn read_stats_file(path: &Path) -> Option<ModuleCacheStatistics> {
+pub(super) fn read_stats_file(path: &Path) -> Option<ModuleCacheStatistics> {
     fs::read_to_string(path)
         .map_err(|err| {
             trace!(
@@ -748,7 +772,7 @@ fn read_stats_file(path: &Path) -> Option<ModuleCacheStatistics> {
         .ok()
 }
 
-fn write_stats_file(path: &Path, stats: &ModuleCacheStatistics) -> bool {
+pub(super) fn write_stats_file(path: &Path, stats: &ModuleCacheStatistics) -> bool {
     toml::to_string_pretty(&stats)
         .map_err(|err| {
             warn!(
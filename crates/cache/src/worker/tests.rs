@@ -576,6 +576,59 @@ fn test_on_update_cleanup_lru_policy() {
     }
 }
 
+#[test]
+fn test_on_update_cleanup_skips_pinned() {
+    let (_tempdir, cache_dir, config_path) = test_prolog();
+    let cache_config = load_config!(
+        config_path,
+        "[cache]\n\
+         enabled = true\n\
+         directory = '{cache_dir}'\n\
+         worker-event-queue-size = '16'\n\
+         file-count-soft-limit = '2'\n\
+         file-count-limit-percent-if-deleting = '50%'",
+        cache_dir
+    );
+    assert!(cache_config.enabled());
+    let worker = Worker::start_new(&cache_config);
+
+    let mods_files_dir = cache_dir.join("target-triple").join("compiler-version");
+    fs::create_dir_all(&mods_files_dir).expect("Failed to create directories");
+    let nonexistent_mod_file = cache_dir.join("nonexistent-mod");
+
+    // the oldest modules are pinned, so only the unpinned ones are evicted
+    let mut pinned_stats = ModuleCacheStatistics::default(&cache_config);
+    pinned_stats.pinned = true;
+    let pinned_stats = toml::to_string_pretty(&pinned_stats).unwrap();
+    let mods = [
+        (29, true),
+        (28, true),
+        (27, false),
+        (26, false),
+        (25, false),
+    ];
+    for (i, (secs_ago, pinned)) in mods.iter().enumerate() {
+        let mod_file = mods_files_dir.join(format!("mod-{}", i));
+        let stats_file = mods_files_dir.join(format!("mod-{}.stats", i));
+        let offset = Duration::from_secs(*secs_ago);
+        create_file_with_mtime(&mod_file, "module", "past", &offset);
+        if *pinned {
+            create_file_with_mtime(&stats_file, &pinned_stats, "past", &offset);
+        }
+    }
+
+    worker.on_cache_update_async(nonexistent_mod_file);
+    worker.wait_for_all_events_handled();
+    assert_eq!(worker.events_dropped(), 0);
+
+    let alive = (0..mods.len())
+        .map(|i| mods_files_dir.join(format!("mod-{}", i)).exists())
+        .collect::<Vec<_>>();
+    assert_eq!(alive, [true, true, false, false, true]);
+    assert_eq!(cache_config.cache_evictions(), 2);
+    assert_eq!(cache_config.counters().unwrap().evictions(), 2);
+}
+
 // clock drift should be applied to mod cache & stats, too
 // however, postpone deleting files to as late as possible
 #[test]
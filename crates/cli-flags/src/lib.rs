@@ -77,6 +77,10 @@ wasmtime_option_group! {
         /// Configure attempting to initialize linear memory via a
         /// copy-on-write mapping (default: yes)
         pub memory_init_cow: Option<bool>,
+
+        /// Inline small functions into their direct callers within the same
+        /// module (default: no)
+        pub inlining: Option<bool>,
     }
 
     enum Optimize {
@@ -176,6 +180,9 @@ wasmtime_option_group! {
         pub max_tables: Option<usize>,
         /// Maximum number of WebAssembly linear memories allowed to be created.
         pub max_memories: Option<usize>,
+        /// Maximum number of live handles allowed in each component resource
+        /// table, including WASI's table of host resources.
+        pub max_resource_handles: Option<usize>,
         /// Force a trap to be raised on `memory.grow` and `table.grow` failure
         /// instead of returning -1 from these instructions.
         ///
@@ -422,6 +429,11 @@ impl CommonOptions {
             level => config.cranelift_opt_level(level),
             _ => err,
         }
+        match_feature! {
+            ["cranelift" : self.opts.inlining]
+            enable => config.cranelift_inlining(enable),
+            true => err,
+        }
         match_feature! {
             ["cranelift" : self.wasm.nan_canonicalization]
             enable => config.cranelift_nan_canonicalization(enable),
@@ -19,6 +19,19 @@ macro_rules! gentest {
                     }
                 });
             }
+            mod mocks {
+                wasmtime::component::bindgen!({
+                    path: $path,
+                    mocks: true,
+                });
+            }
+            mod mocks_async {
+                wasmtime::component::bindgen!({
+                    path: $path,
+                    async: true,
+                    mocks: true,
+                });
+            }
         }
     };
 }
@@ -143,6 +156,7 @@ mod trappable_errors {
             "demo:pkg/a/b" => MyX,
             "demo:pkg/c/b" => MyX,
         },
+        mocks: true,
     });
 
     #[allow(dead_code)]
@@ -135,6 +135,14 @@ where
         self.metadata.address_map = address_map;
     }
 
+    /// Rewrites the source location of each instruction in the function's
+    /// address map with `f`.
+    pub fn map_address_map_srclocs(&mut self, mut f: impl FnMut(FilePos) -> FilePos) {
+        for inst in self.metadata.address_map.instructions.iter_mut() {
+            inst.srcloc = f(inst.srcloc);
+        }
+    }
+
     /// Get a reference to the unwind information from the
     /// function's metadata.
     pub fn unwind_info(&self) -> Option<&UnwindInfo> {
@@ -38,6 +38,7 @@ use wasmtime_environ::{
 
 #[cfg(feature = "component-model")]
 mod component;
+mod inline;
 
 struct IncrementalCacheContext {
     #[cfg(feature = "incremental-cache")]
@@ -148,7 +149,9 @@ impl wasmtime_environ::Compiler for Compiler {
             index: func_index.as_u32(),
         });
 
-        if self.tunables.generate_native_debuginfo {
+        // Value labels are used both for DWARF and to find the values of
+        // locals and the operand stack for core dumps.
+        if self.tunables.generate_native_debuginfo || self.tunables.generate_frame_state {
             context.func.collect_debug_info();
         }
 
@@ -217,6 +220,28 @@ impl wasmtime_environ::Compiler for Compiler {
             &mut func_env,
         )?;
 
+        // Inlining is skipped when generating DWARF, which can't describe
+        // inlined frames yet, when generating frame state for core dumps,
+        // which describes physical frames, and with proof-carrying code,
+        // whose facts aren't carried over from callees.
+        let inlined_calls = if self.tunables.inlining
+            && !self.tunables.generate_native_debuginfo
+            && !self.tunables.generate_frame_state
+            && !isa.flags().enable_pcc()
+        {
+            let calls = inline::Inliner::new(
+                self,
+                translation,
+                types,
+                validator.resources(),
+                &mut compiler.cx.func_translator,
+            )
+            .run(&mut context.func)?;
+            Some(calls)
+        } else {
+            None
+        };
+
         if let Some(path) = &self.clif_dir {
             use std::io::Write;
 
@@ -228,7 +253,17 @@ impl wasmtime_environ::Compiler for Compiler {
             write!(output, "{}", context.func.display()).unwrap();
         }
 
-        let (info, func) = compiler.finish_with_info(Some((&body, &self.tunables)))?;
+        let frame_state = func_env.take_frame_state();
+        let (mut info, mut func) = compiler.finish_with_info(Some((&body, &self.tunables)))?;
+        if let Some(calls) = inlined_calls {
+            info.inlined_calls = calls.finish(&mut func).into();
+        }
+        if self.tunables.generate_frame_state {
+            let ranges = &func.metadata().value_labels_ranges;
+            let (frame_states, saved_registers) = frame_state.finish(isa, &func.buffer, ranges);
+            info.frame_states = frame_states.into();
+            info.frame_saved_registers = saved_registers.map(Into::into);
+        }
 
         let timing = cranelift_codegen::timing::take_current();
         log::debug!("{:?} translated in {:?}", func_index, timing.total());
@@ -1065,11 +1100,16 @@ impl FunctionCompiler<'_> {
         }
 
         if body_and_tunables
-            .map(|(_, t)| t.generate_native_debuginfo)
+            .map(|(_, t)| t.generate_native_debuginfo || t.generate_frame_state)
             .unwrap_or(false)
         {
             compiled_function.set_value_labels_ranges(compiled_code.value_labels_ranges.clone());
+        }
 
+        if body_and_tunables
+            .map(|(_, t)| t.generate_native_debuginfo)
+            .unwrap_or(false)
+        {
             // DWARF debugging needs the CFA-based unwind information even on Windows.
             if !matches!(
                 compiled_function.metadata().unwind_info,
@@ -1094,6 +1134,9 @@ impl FunctionCompiler<'_> {
             WasmFunctionInfo {
                 start_srcloc: compiled_function.metadata().address_map.start_srcloc,
                 stack_maps: stack_maps.into(),
+                frame_states: Default::default(),
+                frame_saved_registers: Default::default(),
+                inlined_calls: Default::default(),
             },
             compiled_function,
         ))
@@ -0,0 +1,550 @@
+//! Inlining of small wasm functions into their direct callers.
+//!
+//! When `Tunables::inlining` is enabled each defined function is translated
+//! as usual, after which direct `call`s to small functions defined in the same
+//! module are replaced with a copy of the callee's CLIF. The callee is
+//! translated from its retained wasm body with its own `FuncEnvironment`, so
+//! the inlined code behaves exactly as it would behind a call: fuel and epoch
+//! state are flushed and reloaded around the original call site, and the
+//! callee's own calls are left as calls.
+//!
+//! Trap and address-map information for inlined instructions points at the
+//! callee's wasm instruction while the native frame belongs to the caller.
+//! Since these offsets lie outside of the caller's body, backtraces recover the
+//! callee from them and report it as a frame of its own. The caller's frame
+//! below it points at the inlined call, which is recorded for each range of
+//! inlined code by `InlinedCalls`. Stack maps need no special handling as
+//! they're computed for the caller as a whole after inlining.
+//!
+//! Inlining decisions only depend on the module being compiled, and the
+//! result is an ordinary function, so the incremental compilation cache keys
+//! on the post-inlining CLIF like any other function.
+
+use crate::compiler::Compiler;
+use crate::func_environ::FuncEnvironment;
+use crate::wasm_call_signature;
+use cranelift_codegen::ir::{
+    self, BlockCall, ExternalName, GlobalValueData, InstBuilder, InstructionData, Opcode,
+    SourceLoc, UserExternalName, UserFuncName, ValueList,
+};
+use cranelift_codegen::MachSrcLoc;
+use cranelift_entity::packed_option::PackedOption;
+use cranelift_entity::{EntityRef, SecondaryMap};
+use cranelift_wasm::{FuncIndex, FuncTranslator};
+use std::collections::HashMap;
+use wasmparser::ValidatorResources;
+use wasmtime_cranelift_shared::{CompiledFuncEnv, CompiledFunction};
+use wasmtime_environ::{
+    CompileError, FilePos, InlinedCallInformation, ModuleTranslation, ModuleTypesBuilder,
+};
+
+/// Functions whose wasm body, including locals, is at most this many bytes
+/// are considered for inlining.
+const MAX_INLINED_BODY_SIZE: usize = 100;
+
+/// The maximum number of call sites inlined into a single function, bounding
+/// how much any one caller can grow.
+const MAX_INLINED_CALL_SITES: usize = 64;
+
+pub(super) struct Inliner<'a, 'data> {
+    compiler: &'a Compiler,
+    translation: &'a ModuleTranslation<'data>,
+    types: &'a ModuleTypesBuilder,
+    resources: &'a ValidatorResources,
+    func_translator: &'a mut FuncTranslator,
+
+    /// Translated callees, or `None` for callees which can't be inlined.
+    callees: HashMap<FuncIndex, Option<ir::Function>>,
+}
+
+impl<'a, 'data> Inliner<'a, 'data> {
+    pub(super) fn new(
+        compiler: &'a Compiler,
+        translation: &'a ModuleTranslation<'data>,
+        types: &'a ModuleTypesBuilder,
+        resources: &'a ValidatorResources,
+        func_translator: &'a mut FuncTranslator,
+    ) -> Self {
+        Inliner {
+            compiler,
+            translation,
+            types,
+            resources,
+            func_translator,
+            callees: HashMap::new(),
+        }
+    }
+
+    /// Inlines eligible direct calls within `func`, which must have been
+    /// translated from the wasm function named by its `UserFuncName`.
+    ///
+    /// The returned `InlinedCalls` must be used to finish `func` once it has
+    /// been compiled.
+    pub(super) fn run(&mut self, func: &mut ir::Function) -> Result<InlinedCalls, CompileError> {
+        let mut calls = InlinedCalls::new(self.translation);
+        let caller = match &func.name {
+            UserFuncName::User(name) => FuncIndex::from_u32(name.index),
+            UserFuncName::Testcase(_) => return Ok(calls),
+        };
+
+        let mut sites = Vec::new();
+        for block in func.layout.blocks() {
+            for inst in func.layout.block_insts(block) {
+                let func_ref = match func.dfg.insts[inst] {
+                    InstructionData::Call {
+                        opcode: Opcode::Call,
+                        func_ref,
+                        ..
+                    } => func_ref,
+                    _ => continue,
+                };
+                let callee = match &func.dfg.ext_funcs[func_ref].name {
+                    ExternalName::User(name) => &func.params.user_named_funcs()[*name],
+                    _ => continue,
+                };
+                if callee.namespace != 0 {
+                    continue;
+                }
+                let callee = FuncIndex::from_u32(callee.index);
+                if callee != caller && self.is_small(callee) {
+                    sites.push((inst, callee));
+                }
+            }
+        }
+        sites.truncate(MAX_INLINED_CALL_SITES);
+
+        for (inst, callee) in sites {
+            if !self.callees.contains_key(&callee) {
+                let translated = self.translate(callee)?;
+                self.callees.insert(callee, translated);
+            }
+            if let Some(callee) = &self.callees[&callee] {
+                inline_call(func, inst, callee, &mut calls);
+            }
+        }
+        Ok(calls)
+    }
+
+    fn is_small(&self, callee: FuncIndex) -> bool {
+        let defined = match self.translation.module.defined_func_index(callee) {
+            Some(index) => index,
+            None => return false,
+        };
+        let body = &self.translation.inlinable_function_bodies[defined].body;
+        body.range().len() <= MAX_INLINED_BODY_SIZE
+    }
+
+    /// Translates `callee` into a standalone function, returning `None` if
+    /// the result uses features which the splicing below doesn't support.
+    fn translate(&mut self, callee: FuncIndex) -> Result<Option<ir::Function>, CompileError> {
+        let compiler = self.compiler;
+        let isa = &*compiler.isa;
+        let module = &self.translation.module;
+        let input =
+            &self.translation.inlinable_function_bodies[module.defined_func_index(callee).unwrap()];
+        let wasm_func_ty = &self.types[module.functions[callee].signature];
+
+        let mut func = ir::Function::with_name_signature(
+            UserFuncName::User(UserExternalName {
+                namespace: 0,
+                index: callee.as_u32(),
+            }),
+            wasm_call_signature(isa, wasm_func_ty, &compiler.tunables),
+        );
+        let mut func_env = FuncEnvironment::new(
+            isa,
+            self.translation,
+            self.types,
+            &compiler.tunables,
+            compiler.wmemcheck,
+        );
+        let mut validator = input
+            .validator(callee, self.resources)
+            .into_validator(Default::default());
+        self.func_translator.translate_body(
+            &mut validator,
+            input.body.clone(),
+            &mut func,
+            &mut func_env,
+        )?;
+
+        let inlinable = func.dynamic_stack_slots.is_empty()
+            && func.dfg.dynamic_types.is_empty()
+            && func.memory_types.is_empty()
+            && func.layout.blocks().all(|block| {
+                func.layout.block_insts(block).all(|inst| {
+                    !matches!(
+                        func.dfg.insts[inst].opcode(),
+                        Opcode::ReturnCall | Opcode::ReturnCallIndirect
+                    )
+                })
+            });
+        Ok(if inlinable { Some(func) } else { None })
+    }
+}
+
+/// The calls inlined into a function, and the source locations standing in
+/// for the inlined instructions' wasm offsets until the function is compiled.
+///
+/// An inlined instruction is identified by both its offset in the callee and
+/// the offset of the call it was inlined at, so each such pair is given a
+/// source location of its own. These are allocated past the end of the last
+/// function body so that they can't be mistaken for wasm offsets.
+pub(super) struct InlinedCalls {
+    /// The first source location allocated.
+    base: u32,
+
+    /// The callee's source location and the call's, for each allocated source
+    /// location in order.
+    srclocs: Vec<(SourceLoc, SourceLoc)>,
+
+    /// The source location allocated for each pair in `srclocs`.
+    allocated: HashMap<(SourceLoc, SourceLoc), SourceLoc>,
+}
+
+impl InlinedCalls {
+    fn new(translation: &ModuleTranslation<'_>) -> InlinedCalls {
+        let end = translation
+            .inlinable_function_bodies
+            .values()
+            .map(|input| input.body.range().end)
+            .max()
+            .unwrap_or(0);
+        InlinedCalls {
+            base: u32::try_from(end).unwrap_or(u32::MAX),
+            srclocs: Vec::new(),
+            allocated: HashMap::new(),
+        }
+    }
+
+    /// Returns the source location of an instruction at `srcloc` in a callee
+    /// which is inlined at `call_site`.
+    ///
+    /// This is `srcloc` itself if the call can't be recorded.
+    fn srcloc(&mut self, srcloc: SourceLoc, call_site: SourceLoc) -> SourceLoc {
+        if srcloc.is_default() || call_site.is_default() {
+            return srcloc;
+        }
+        if let Some(allocated) = self.allocated.get(&(srcloc, call_site)) {
+            return *allocated;
+        }
+        let bits = u32::try_from(self.srclocs.len())
+            .ok()
+            .and_then(|index| self.base.checked_add(index))
+            .filter(|bits| *bits != u32::MAX);
+        let allocated = match bits {
+            Some(bits) => SourceLoc::new(bits),
+            None => return srcloc,
+        };
+        self.srclocs.push((srcloc, call_site));
+        self.allocated.insert((srcloc, call_site), allocated);
+        allocated
+    }
+
+    /// Returns the callee's and the call's source locations for which `bits`
+    /// was allocated, if it was.
+    fn lookup(&self, bits: u32) -> Option<(SourceLoc, SourceLoc)> {
+        let index = bits.checked_sub(self.base)?;
+        self.srclocs.get(usize::try_from(index).ok()?).copied()
+    }
+
+    /// Gives the inlined instructions of the compiled `func` their callee's
+    /// wasm offsets in its address map, and returns the ranges of its code
+    /// which were inlined along with the calls they were inlined at.
+    pub(super) fn finish<E: CompiledFuncEnv>(
+        &self,
+        func: &mut CompiledFunction<E>,
+    ) -> Vec<InlinedCallInformation> {
+        let mut calls = Vec::<InlinedCallInformation>::new();
+        if self.srclocs.is_empty() {
+            return calls;
+        }
+        for &MachSrcLoc { start, end, loc } in func.buffer.get_srclocs_sorted() {
+            let call_site = match self.lookup(loc.bits()) {
+                Some((_, call_site)) => FilePos::new(call_site.bits()),
+                None => continue,
+            };
+            match calls.last_mut() {
+                Some(last) if last.end == start && last.call_site == call_site => last.end = end,
+                _ => calls.push(InlinedCallInformation {
+                    start,
+                    end,
+                    call_site,
+                }),
+            }
+        }
+        func.map_address_map_srclocs(|pos| {
+            match pos.file_offset().and_then(|bits| self.lookup(bits)) {
+                Some((srcloc, _)) => FilePos::new(srcloc.bits()),
+                None => pos,
+            }
+        });
+        calls
+    }
+}
+
+/// Replaces the direct `call` instruction `call` within `func` with a copy of
+/// the body of `callee`, recording the call in `calls`.
+fn inline_call(
+    func: &mut ir::Function,
+    call: ir::Inst,
+    callee: &ir::Function,
+    calls: &mut InlinedCalls,
+) {
+    let call_site = func.srcloc(call);
+    let call_block = func.layout.inst_block(call).unwrap();
+    let cold = func.layout.is_cold(call_block);
+    let args = func.dfg.inst_args(call).to_vec();
+
+    // Everything after the call moves to a new block whose parameters take
+    // the place of the call's results.
+    let cont = func.dfg.make_block();
+    let after_call = func.layout.next_inst(call).unwrap();
+    func.layout.split_block(cont, after_call);
+    if cold {
+        func.layout.set_cold(cont);
+    }
+    let results = func.dfg.detach_results(call);
+    for i in 0..results.len(&func.dfg.value_lists) {
+        let result = results.get(i, &func.dfg.value_lists).unwrap();
+        let ty = func.dfg.value_type(result);
+        let param = func.dfg.append_block_param(cont, ty);
+        func.dfg.change_to_alias(result, param);
+    }
+
+    let entities = Entities::import(func, callee);
+
+    let mut blocks = SecondaryMap::<ir::Block, PackedOption<ir::Block>>::new();
+    let mut values = SecondaryMap::<ir::Value, PackedOption<ir::Value>>::new();
+    for block in callee.layout.blocks() {
+        let new_block = func.dfg.make_block();
+        func.layout.insert_block(new_block, cont);
+        if cold || callee.layout.is_cold(block) {
+            func.layout.set_cold(new_block);
+        }
+        for &param in callee.dfg.block_params(block) {
+            let ty = callee.dfg.value_type(param);
+            values[param] = func.dfg.append_block_param(new_block, ty).into();
+        }
+        blocks[block] = new_block.into();
+    }
+
+    // Copy all instructions over first, and then rewrite their operands once
+    // every callee value has a counterpart in the caller.
+    let mut insts = Vec::new();
+    for block in callee.layout.blocks() {
+        let new_block = blocks[block].unwrap();
+        for inst in callee.layout.block_insts(block) {
+            let data = copy_inst(func, callee, inst, &entities, &blocks, cont);
+            let new_inst = func.dfg.make_inst(data);
+            func.layout.append_inst(new_inst, new_block);
+            for &result in callee.dfg.inst_results(inst) {
+                let ty = callee.dfg.value_type(result);
+                values[result] = func.dfg.append_result(new_inst, ty).into();
+            }
+            let srcloc = callee.srcloc(inst);
+            if !srcloc.is_default() {
+                func.set_srcloc(new_inst, calls.srcloc(srcloc, call_site));
+            }
+            insts.push(new_inst);
+        }
+    }
+    for inst in insts {
+        func.dfg.map_inst_values(inst, |_, value| {
+            values[callee.dfg.resolve_aliases(value)].unwrap()
+        });
+    }
+
+    let entry = blocks[callee.layout.entry_block().unwrap()].unwrap();
+    func.dfg.replace(call).jump(entry, &args);
+}
+
+/// Entities of a callee which have been recreated in its caller. Each caller
+/// entity was pushed in order, so the callee's index maps to the caller's by
+/// a fixed offset.
+struct Entities {
+    sig_refs: usize,
+    func_refs: usize,
+    global_values: usize,
+    stack_slots: usize,
+    tables: usize,
+}
+
+impl Entities {
+    fn import(func: &mut ir::Function, callee: &ir::Function) -> Entities {
+        let entities = Entities {
+            sig_refs: func.dfg.signatures.len(),
+            func_refs: func.dfg.ext_funcs.len(),
+            global_values: func.global_values.len(),
+            stack_slots: func.sized_stack_slots.len(),
+            tables: func.tables.len(),
+        };
+
+        for sig in callee.dfg.signatures.values() {
+            func.import_signature(sig.clone());
+        }
+        for ext_func in callee.dfg.ext_funcs.values() {
+            let data = ir::ExtFuncData {
+                name: import_name(func, callee, &ext_func.name),
+                signature: entities.sig_ref(ext_func.signature),
+                colocated: ext_func.colocated,
+            };
+            func.import_function(data);
+        }
+        for gv in callee.global_values.values() {
+            let mut data = gv.clone();
+            match &mut data {
+                GlobalValueData::Load { base, .. } | GlobalValueData::IAddImm { base, .. } => {
+                    *base = entities.global_value(*base);
+                }
+                GlobalValueData::Symbol { name, .. } => {
+                    *name = import_name(func, callee, name);
+                }
+                _ => {}
+            }
+            func.create_global_value(data);
+        }
+        for slot in callee.sized_stack_slots.values() {
+            func.create_sized_stack_slot(slot.clone());
+        }
+        for table in callee.tables.values() {
+            let mut data = table.clone();
+            data.base_gv = entities.global_value(data.base_gv);
+            data.bound_gv = entities.global_value(data.bound_gv);
+            func.create_table(data);
+        }
+        entities
+    }
+
+    fn sig_ref(&self, sig_ref: ir::SigRef) -> ir::SigRef {
+        offset(sig_ref, self.sig_refs)
+    }
+
+    fn func_ref(&self, func_ref: ir::FuncRef) -> ir::FuncRef {
+        offset(func_ref, self.func_refs)
+    }
+
+    fn global_value(&self, global_value: ir::GlobalValue) -> ir::GlobalValue {
+        offset(global_value, self.global_values)
+    }
+
+    fn stack_slot(&self, stack_slot: ir::StackSlot) -> ir::StackSlot {
+        offset(stack_slot, self.stack_slots)
+    }
+
+    fn table(&self, table: ir::Table) -> ir::Table {
+        offset(table, self.tables)
+    }
+}
+
+fn offset<E: EntityRef>(entity: E, base: usize) -> E {
+    E::new(base + entity.index())
+}
+
+fn import_name(
+    func: &mut ir::Function,
+    callee: &ir::Function,
+    name: &ExternalName,
+) -> ExternalName {
+    match name {
+        ExternalName::User(name) => {
+            let name = callee.params.user_named_funcs()[*name].clone();
+            ExternalName::User(func.declare_imported_user_function(name))
+        }
+        other => other.clone(),
+    }
+}
+
+/// Copies the instruction `inst` of `callee` into `func`, translating all of
+/// its entity references but leaving its value operands referring to callee
+/// values. A `return` becomes a jump to `cont`.
+fn copy_inst(
+    func: &mut ir::Function,
+    callee: &ir::Function,
+    inst: ir::Inst,
+    entities: &Entities,
+    blocks: &SecondaryMap<ir::Block, PackedOption<ir::Block>>,
+    cont: ir::Block,
+) -> InstructionData {
+    let pool = &callee.dfg.value_lists;
+    let copy_list = |list: ValueList, func: &mut ir::Function| {
+        ValueList::from_slice(list.as_slice(pool), &mut func.dfg.value_lists)
+    };
+    let copy_block_call = |call: BlockCall, func: &mut ir::Function| {
+        BlockCall::new(
+            blocks[call.block(pool)].unwrap(),
+            call.args_slice(pool),
+            &mut func.dfg.value_lists,
+        )
+    };
+
+    let mut data = callee.dfg.insts[inst];
+    match &mut data {
+        InstructionData::MultiAry {
+            opcode: Opcode::Return,
+            args,
+        } => {
+            return InstructionData::Jump {
+                opcode: Opcode::Jump,
+                destination: BlockCall::new(cont, args.as_slice(pool), &mut func.dfg.value_lists),
+            };
+        }
+        InstructionData::MultiAry { args, .. } => *args = copy_list(*args, func),
+        InstructionData::Call { args, func_ref, .. } => {
+            *args = copy_list(*args, func);
+            *func_ref = entities.func_ref(*func_ref);
+        }
+        InstructionData::CallIndirect { args, sig_ref, .. } => {
+            *args = copy_list(*args, func);
+            *sig_ref = entities.sig_ref(*sig_ref);
+        }
+        InstructionData::FuncAddr { func_ref, .. } => {
+            *func_ref = entities.func_ref(*func_ref);
+        }
+        InstructionData::Jump { destination, .. } => {
+            *destination = copy_block_call(*destination, func);
+        }
+        InstructionData::Brif { blocks, .. } => {
+            for block in blocks {
+                *block = copy_block_call(*block, func);
+            }
+        }
+        InstructionData::BranchTable { table, .. } => {
+            let jt = &callee.dfg.jump_tables[*table];
+            let default = copy_block_call(jt.default_block(), func);
+            let entries = jt
+                .as_slice()
+                .iter()
+                .map(|call| copy_block_call(*call, func))
+                .collect::<Vec<_>>();
+            *table = func.create_jump_table(ir::JumpTableData::new(default, &entries));
+        }
+        InstructionData::UnaryGlobalValue { global_value, .. } => {
+            *global_value = entities.global_value(*global_value);
+        }
+        InstructionData::StackLoad { stack_slot, .. }
+        | InstructionData::StackStore { stack_slot, .. } => {
+            *stack_slot = entities.stack_slot(*stack_slot);
+        }
+        InstructionData::TableAddr { table, .. } => {
+            *table = entities.table(*table);
+        }
+        InstructionData::UnaryConst {
+            constant_handle, ..
+        } => {
+            let constant = callee.dfg.constants.get(*constant_handle).clone();
+            *constant_handle = func.dfg.constants.insert(constant);
+        }
+        InstructionData::Shuffle { imm, .. } => {
+            *imm = func
+                .dfg
+                .immediates
+                .push(callee.dfg.immediates[*imm].clone());
+        }
+        InstructionData::DynamicStackLoad { .. } | InstructionData::DynamicStackStore { .. } => {
+            unreachable!("functions with dynamic stack slots are not inlined")
+        }
+        _ => {}
+    }
+    data
+}
@@ -0,0 +1,228 @@
+//! Tables describing where the values of a function's locals and operand stack
+//! are stored at each point where it may trap or call another function.
+//!
+//! These are generated when `Tunables::generate_frame_state` is enabled so that
+//! core dumps can include the values, without changing the generated code.
+//! During translation the values on the operand stack at each such point are
+//! given `ValueLabel`s, next to the labels that `cranelift-wasm` already gives
+//! to locals. Once the function is compiled, the register allocator's
+//! locations for those labels are looked up at the offset of each trap and
+//! call. Constants on the operand stack are recorded as they are, since
+//! they're materialized where they're used rather than kept anywhere.
+//!
+//! Values may be kept in registers, so the callee-saved registers which each
+//! function saves in its prologue are recorded as well. Together with the
+//! registers at the time of a trap, this lets the registers of every frame on
+//! the stack be recovered.
+
+use cranelift_codegen::ir::{SourceLoc, ValueLabel};
+use cranelift_codegen::isa::unwind::UnwindInst;
+use cranelift_codegen::isa::TargetIsa;
+use cranelift_codegen::{
+    Final, LabelValueLoc, MachBufferFinalized, MachSrcLoc, Reg, ValueLabelsRanges,
+};
+use cranelift_wasm::get_vmctx_value_label;
+use std::collections::HashMap;
+use wasmtime_environ::{
+    FrameRegister, FrameStateInformation, FrameValue, FrameValueLoc, SavedRegister, WasmValType,
+};
+
+/// An operand stack value recorded in a `FrameStateSites`.
+#[derive(Copy, Clone)]
+pub enum StackValue {
+    /// A value given a label, whose location is looked up once the function
+    /// has been compiled.
+    Label(ValueLabel),
+    /// A constant, with its bits.
+    Const(u64),
+    /// A value which couldn't be given a label, and isn't recorded.
+    Unknown,
+}
+
+/// The frame state recorded while translating a function.
+#[derive(Default)]
+pub struct FrameStateSites {
+    /// The types of the function's locals, whose labels are their indices.
+    locals: Option<Box<[WasmValType]>>,
+
+    /// The operand stack values and their types before each operator which
+    /// may trap or call, keyed by the operator's source location.
+    stacks: HashMap<SourceLoc, Box<[(StackValue, WasmValType)]>>,
+
+    /// The number of labels given to operand stack values so far.
+    num_stack_labels: u32,
+}
+
+impl FrameStateSites {
+    /// Returns whether the types of the function's locals have been recorded.
+    pub fn has_locals(&self) -> bool {
+        self.locals.is_some()
+    }
+
+    /// Records the types of the function's locals.
+    pub fn set_locals(&mut self, locals: Box<[WasmValType]>) {
+        self.locals = Some(locals);
+    }
+
+    /// Returns a new label for an operand stack value, distinct from the
+    /// labels of the function's `num_locals` locals.
+    ///
+    /// Returns `None` once labels run out, in which case the value isn't
+    /// recorded.
+    pub fn new_stack_label(&mut self, num_locals: usize) -> Option<ValueLabel> {
+        let label = u32::try_from(num_locals)
+            .ok()?
+            .checked_add(self.num_stack_labels)?;
+        if label >= get_vmctx_value_label().as_u32() {
+            return None;
+        }
+        self.num_stack_labels += 1;
+        Some(ValueLabel::from_u32(label))
+    }
+
+    /// Records the operand stack values and their types before the operator at
+    /// `srcloc`.
+    pub fn add(&mut self, srcloc: SourceLoc, stack: Box<[(StackValue, WasmValType)]>) {
+        self.stacks.insert(srcloc, stack);
+    }
+
+    /// Looks up where the recorded values are stored at each trap and call in
+    /// the compiled function's `buffer`, given the locations of its value
+    /// labels in `ranges`.
+    ///
+    /// Also returns the callee-saved registers which the function saves, if
+    /// they're described by its unwind info.
+    pub fn finish(
+        self,
+        isa: &dyn TargetIsa,
+        buffer: &MachBufferFinalized<Final>,
+        ranges: &ValueLabelsRanges,
+    ) -> (Vec<FrameStateInformation>, Option<Vec<SavedRegister>>) {
+        let locals = match self.locals {
+            Some(locals) => locals,
+            None => return (Vec::new(), None),
+        };
+        let srclocs = buffer.get_srclocs_sorted();
+
+        // Traps happen with the pc at the trapping instruction, while callers'
+        // pcs are the return address just past the call instruction. Either way
+        // the state needed is the one in effect at that pc.
+        let traps = buffer.traps().iter().map(|trap| (trap.offset, trap.offset));
+        let calls = buffer
+            .call_sites()
+            .iter()
+            .map(|call| (call.ret_addr - 1, call.ret_addr));
+
+        let mut frame_states = Vec::new();
+        for (inst_offset, code_offset) in traps.chain(calls) {
+            let stack = match srcloc_at(srclocs, inst_offset).and_then(|s| self.stacks.get(&s)) {
+                Some(stack) => stack,
+                None => continue,
+            };
+            let labeled = |label: ValueLabel, ty: WasmValType| FrameValue {
+                ty,
+                loc: label_loc(isa, ranges, label, code_offset),
+            };
+            frame_states.push(FrameStateInformation {
+                code_offset,
+                locals: locals
+                    .iter()
+                    .enumerate()
+                    .map(|(i, ty)| labeled(ValueLabel::new(i), *ty))
+                    .collect(),
+                stack: stack
+                    .iter()
+                    .map(|(value, ty)| match value {
+                        StackValue::Label(label) => labeled(*label, *ty),
+                        StackValue::Const(bits) => FrameValue {
+                            ty: *ty,
+                            loc: Some(FrameValueLoc::Const(*bits)),
+                        },
+                        StackValue::Unknown => FrameValue { ty: *ty, loc: None },
+                    })
+                    .collect(),
+            });
+        }
+        frame_states.sort_unstable_by_key(|info| info.code_offset);
+        frame_states.dedup_by_key(|info| info.code_offset);
+        (frame_states, saved_registers(isa, buffer))
+    }
+}
+
+/// Returns the source location of the instruction at `offset`.
+fn srcloc_at(srclocs: &[MachSrcLoc<Final>], offset: u32) -> Option<SourceLoc> {
+    let index = srclocs
+        .partition_point(|srcloc| srcloc.start <= offset)
+        .checked_sub(1)?;
+    let srcloc = &srclocs[index];
+    if offset < srcloc.end && !srcloc.loc.is_default() {
+        Some(srcloc.loc)
+    } else {
+        None
+    }
+}
+
+/// Returns where the value labeled `label` is stored at `offset`.
+fn label_loc(
+    isa: &dyn TargetIsa,
+    ranges: &ValueLabelsRanges,
+    label: ValueLabel,
+    offset: u32,
+) -> Option<FrameValueLoc> {
+    // Ranges are keyed by the end offsets of instructions, so a range covering
+    // `offset` holds the value once the instruction before `offset` is done.
+    // Locals are given the same label each time they're set, so prefer the
+    // range that started last, holding the latest value.
+    let range = ranges
+        .get(&label)?
+        .iter()
+        .filter(|range| range.start <= offset && offset < range.end)
+        .max_by_key(|range| range.start)?;
+    match range.loc {
+        LabelValueLoc::CFAOffset(offset) => i32::try_from(offset).ok().map(FrameValueLoc::Stack),
+        LabelValueLoc::Reg(reg) => register(isa, reg).map(FrameValueLoc::Reg),
+    }
+}
+
+/// Returns the callee-saved registers which the function compiled into
+/// `buffer` saves in its prologue, and where.
+///
+/// Returns `None` if the function has no unwind info, or if a saved register
+/// can't be described.
+fn saved_registers(
+    isa: &dyn TargetIsa,
+    buffer: &MachBufferFinalized<Final>,
+) -> Option<Vec<SavedRegister>> {
+    // Registers are saved in the clobber area, which is described relative to
+    // the frame pointer. The frame pointer is in turn described relative to
+    // the caller's stack pointer, which is the canonical frame address.
+    let mut clobbers = None;
+    let mut saved = Vec::new();
+    for (_, inst) in buffer.unwind_info.iter() {
+        match *inst {
+            UnwindInst::DefineNewFrame {
+                offset_upward_to_caller_sp,
+                offset_downward_to_clobbers,
+            } => {
+                clobbers = Some(
+                    -i64::from(offset_upward_to_caller_sp) - i64::from(offset_downward_to_clobbers),
+                );
+            }
+            UnwindInst::SaveReg {
+                clobber_offset,
+                reg,
+            } => {
+                let cfa_offset = i32::try_from(clobbers? + i64::from(clobber_offset)).ok()?;
+                let reg = register(isa, reg.into())?;
+                saved.push(SavedRegister { reg, cfa_offset });
+            }
+            _ => {}
+        }
+    }
+    clobbers.map(|_| saved)
+}
+
+/// Returns the DWARF register number of `reg`.
+fn register(isa: &dyn TargetIsa, reg: Reg) -> Option<FrameRegister> {
+    isa.map_regalloc_reg_to_dwarf(reg).ok().map(FrameRegister)
+}
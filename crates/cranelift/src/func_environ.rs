@@ -1,3 +1,4 @@
+use crate::frame_state::{FrameStateSites, StackValue};
 use cfg_if::cfg_if;
 use cranelift_codegen::cursor::FuncCursor;
 use cranelift_codegen::ir;
@@ -161,6 +162,10 @@ pub struct FuncEnvironment<'module_environment> {
 
     fuel_consumed: i64,
 
+    /// The locals and operand stack values recorded at each operator that
+    /// may trap or call, when `Tunables::generate_frame_state` is enabled.
+    frame_state: FrameStateSites,
+
     #[cfg(feature = "wmemcheck")]
     wmemcheck: bool,
 }
@@ -206,11 +211,18 @@ impl<'module_environment> FuncEnvironment<'module_environment> {
             // Start with at least one fuel being consumed because even empty
             // functions should consume at least some fuel.
             fuel_consumed: 1,
+            frame_state: FrameStateSites::default(),
             #[cfg(feature = "wmemcheck")]
             wmemcheck,
         }
     }
 
+    /// Takes the frame state recorded while translating the function, for
+    /// `FrameStateSites::finish` once it has been compiled.
+    pub fn take_frame_state(&mut self) -> FrameStateSites {
+        mem::take(&mut self.frame_state)
+    }
+
     fn pointer_type(&self) -> ir::Type {
         self.isa.pointer_type()
     }
@@ -2627,6 +2639,44 @@ impl<'module_environment> cranelift_wasm::FuncEnvironment for FuncEnvironment<'m
         Ok(())
     }
 
+    fn tracks_frame_state(&self) -> bool {
+        self.tunables.generate_frame_state
+    }
+
+    fn save_frame_state(
+        &mut self,
+        builder: &mut FunctionBuilder,
+        srcloc: ir::SourceLoc,
+        locals: &[wasmparser::ValType],
+        stack: &[(ir::Value, wasmparser::ValType)],
+    ) -> WasmResult<()> {
+        // Nothing is emitted here. The locals are already labeled, and giving
+        // the operand stack values labels as well lets their locations be
+        // looked up once the function has been compiled. Constants are
+        // rematerialized where they're used, so they have no location and are
+        // recorded directly instead.
+        if !self.frame_state.has_locals() {
+            let locals = locals.iter().map(|ty| self.convert_valtype(*ty)).collect();
+            self.frame_state.set_locals(locals);
+        }
+        let mut values = Vec::with_capacity(stack.len());
+        for (val, ty) in stack {
+            let value = match constant_bits(builder.func, *val) {
+                Some(bits) => StackValue::Const(bits),
+                None => match self.frame_state.new_stack_label(locals.len()) {
+                    Some(label) => {
+                        builder.set_val_label(*val, label);
+                        StackValue::Label(label)
+                    }
+                    None => StackValue::Unknown,
+                },
+            };
+            values.push((value, self.convert_valtype(*ty)));
+        }
+        self.frame_state.add(srcloc, values.into());
+        Ok(())
+    }
+
     fn relaxed_simd_deterministic(&self) -> bool {
         self.tunables.relaxed_simd_deterministic
     }
@@ -2754,3 +2804,26 @@ impl<'module_environment> cranelift_wasm::FuncEnvironment for FuncEnvironment<'m
         }
     }
 }
+
+/// Returns the bits of `val` if it's defined by a constant instruction.
+fn constant_bits(func: &Function, val: ir::Value) -> Option<u64> {
+    let inst = match func.dfg.value_def(func.dfg.resolve_aliases(val)) {
+        ir::ValueDef::Result(inst, _) => inst,
+        _ => return None,
+    };
+    match func.dfg.insts[inst] {
+        ir::InstructionData::UnaryImm {
+            opcode: ir::Opcode::Iconst,
+            imm,
+        } => Some(imm.bits() as u64),
+        ir::InstructionData::UnaryIeee32 {
+            opcode: ir::Opcode::F32const,
+            imm,
+        } => Some(imm.bits().into()),
+        ir::InstructionData::UnaryIeee64 {
+            opcode: ir::Opcode::F64const,
+            imm,
+        } => Some(imm.bits()),
+        _ => None,
+    }
+}
@@ -16,6 +16,7 @@ use wasmtime_environ::Tunables;
 mod builder;
 mod compiler;
 mod debug;
+mod frame_state;
 mod func_environ;
 
 type CompiledFunctionsMetadata<'a> = PrimaryMap<DefinedFuncIndex, &'a CompiledFunctionMetadata>;
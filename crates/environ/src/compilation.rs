@@ -4,7 +4,7 @@
 use crate::{obj, Tunables};
 use crate::{
     DefinedFuncIndex, FilePos, FuncIndex, FunctionBodyData, ModuleTranslation, ModuleTypesBuilder,
-    PrimaryMap, StackMap, WasmError, WasmFuncType,
+    PrimaryMap, StackMap, WasmError, WasmFuncType, WasmValType,
 };
 use anyhow::Result;
 use object::write::{Object, SymbolId};
@@ -18,12 +18,15 @@ use std::sync::Arc;
 use thiserror::Error;
 
 /// Information about a function, such as trap information, address map,
-/// and stack maps.
+/// stack maps, frame state, and inlined calls.
 #[derive(Serialize, Deserialize, Default)]
 #[allow(missing_docs)]
 pub struct WasmFunctionInfo {
     pub start_srcloc: FilePos,
     pub stack_maps: Box<[StackMapInformation]>,
+    pub frame_states: Box<[FrameStateInformation]>,
+    pub frame_saved_registers: Option<Box<[SavedRegister]>>,
+    pub inlined_calls: Box<[InlinedCallInformation]>,
 }
 
 /// Description of where a function is located in the text section of a
@@ -49,6 +52,95 @@ pub struct StackMapInformation {
     pub stack_map: StackMap,
 }
 
+/// Where the values of a function's locals and operand stack are stored at a
+/// point where it may trap or call another function.
+///
+/// These are only generated when `Tunables::generate_frame_state` is enabled,
+/// and are used to recover the values for core dumps.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct FrameStateInformation {
+    /// The offset of the trapping instruction, or the return address of the
+    /// call, within the function's native code. It is relative to the
+    /// beginning of the function.
+    pub code_offset: u32,
+
+    /// The function's locals, in the order they're declared.
+    pub locals: Box<[FrameValue]>,
+
+    /// The function's operand stack, from the bottom of the stack to the top.
+    pub stack: Box<[FrameValue]>,
+}
+
+/// The type of a local or operand stack value in a `FrameStateInformation`,
+/// and where it's stored.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+pub struct FrameValue {
+    /// The WebAssembly type of the value.
+    pub ty: WasmValType,
+
+    /// Where the value is stored, or `None` if it isn't available at this
+    /// point, for example because it has been optimized away.
+    pub loc: Option<FrameValueLoc>,
+}
+
+/// Where a `FrameValue` is stored.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+pub enum FrameValueLoc {
+    /// The value is in its frame's stack, at this offset relative to the
+    /// frame's canonical frame address (the value of the stack pointer just
+    /// before the function was called).
+    Stack(i32),
+
+    /// The value is in a register.
+    Reg(FrameRegister),
+
+    /// The value is a constant with these bits, which is materialized where
+    /// it's used instead of being stored anywhere.
+    Const(u64),
+}
+
+/// A machine register holding a `FrameValue`, identified by its DWARF
+/// register number.
+///
+/// Only the low 64 bits of floating-point and vector registers are used.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq)]
+pub struct FrameRegister(pub u16);
+
+/// A callee-saved register which a function saves in its frame.
+///
+/// These are only known for functions compiled with native unwind info, which
+/// describes where each register is saved.
+///
+/// Once the function's prologue is done the register's value, as seen by the
+/// function's caller, is at `cfa_offset` from the function's canonical frame
+/// address.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+pub struct SavedRegister {
+    /// The saved register.
+    pub reg: FrameRegister,
+
+    /// Where the register is saved, relative to the canonical frame address.
+    pub cfa_offset: i32,
+}
+
+/// A range of a function's native code which was inlined into it from
+/// another function, along with the call which was inlined.
+///
+/// The address map gives the inlined function's wasm offsets for this code,
+/// and this gives the offset of the call within the function itself.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct InlinedCallInformation {
+    /// The start of the range within the function's native code. It is
+    /// relative to the beginning of the function.
+    pub start: u32,
+
+    /// The end of the range, exclusive.
+    pub end: u32,
+
+    /// The wasm offset of the inlined call.
+    pub call_site: FilePos,
+}
+
 /// An error while compiling WebAssembly to machine code.
 #[derive(Error, Debug)]
 pub enum CompileError {
@@ -65,7 +157,8 @@ pub enum CompileError {
     DebugInfoNotSupported,
 }
 
-/// Implementation of an incremental compilation's key/value cache store.
+/// Implementation of a key/value cache store, used both by incremental
+/// compilation and to keep compiled modules in the module cache.
 ///
 /// In theory, this could just be Cranelift's `CacheKvStore` trait, but it is not as we want to
 /// make sure that wasmtime isn't too tied to Cranelift internals (and as a matter of fact, we
@@ -211,9 +211,11 @@ impl<'data> Translator<'_, 'data> {
             // translation, validation, etc. Note that multi-memory is
             // specifically enabled here since the adapter module is highly
             // likely to use that if anything is actually indirected through
-            // memory.
+            // memory. Bulk memory is enabled as well since `memory.copy` is
+            // used to copy lists and strings between memories.
             let mut validator = Validator::new_with_features(WasmFeatures {
                 multi_memory: true,
+                bulk_memory: true,
                 ..*self.validator.features()
             });
             let translation = ModuleEnvironment::new(
@@ -1184,6 +1184,22 @@ impl Compiler<'_, '_> {
         self.validate_string_inbounds(src, src_byte_len);
         self.validate_string_inbounds(&dst, dst_byte_len.idx);
 
+        // Latin1 has no invalid byte sequences so a latin1-to-latin1 copy
+        // needs no validation, meaning a plain `memory.copy` can be used
+        // instead of importing a transcoder. Note that utf8 and utf16 copies
+        // still go through the host to validate the source encoding.
+        if src_enc == FE::Latin1 && dst_enc == FE::Latin1 {
+            if let Some(copy) = self.memory_copy_instruction(src.opts, dst_opts) {
+                self.instruction(LocalGet(dst.ptr.idx));
+                self.instruction(LocalGet(src.ptr.idx));
+                self.instruction(LocalGet(dst_byte_len.idx));
+                self.instruction(copy);
+                self.free_temp_local(dst_byte_len);
+                assert!(src_byte_len_tmp.is_none());
+                return dst;
+            }
+        }
+
         // If the validations pass then the host `transcode` intrinsic
         // is invoked. This will either raise a trap or otherwise succeed
         // in which case we're done.
@@ -1846,13 +1862,26 @@ impl Compiler<'_, '_> {
             Trap::ListByteLengthOverflow,
         );
 
-        self.free_temp_local(src_byte_len);
-        self.free_temp_local(dst_byte_len);
+        // Lists of integers and floats have the same representation in both
+        // the source and the destination, so if the element types are the
+        // same then the whole list is copied with a single `memory.copy`
+        // instead of translating it element-by-element.
+        let copy = if is_bitwise_copyable(src_element_ty, dst_element_ty) {
+            self.memory_copy_instruction(src_opts, dst_opts)
+        } else {
+            None
+        };
 
         // This is the main body of the loop to actually translate list types.
         // Note that if both element sizes are 0 then this won't actually do
         // anything so the loop is removed entirely.
-        if src_size > 0 || dst_size > 0 {
+        if let Some(copy) = copy {
+            assert_eq!(src_size, dst_size);
+            self.instruction(LocalGet(dst_mem.addr.idx));
+            self.instruction(LocalGet(src_mem.addr.idx));
+            self.instruction(LocalGet(dst_byte_len.idx));
+            self.instruction(copy);
+        } else if src_size > 0 || dst_size > 0 {
             // This block encompasses the entire loop and is use to exit before even
             // entering the loop if the list size is zero.
             self.instruction(Block(BlockType::Empty));
@@ -1913,6 +1942,9 @@ impl Compiler<'_, '_> {
             self.free_temp_local(remaining);
         }
 
+        self.free_temp_local(src_byte_len);
+        self.free_temp_local(dst_byte_len);
+
         // Store the ptr/length in the desired destination
         match dst {
             Destination::Stack(s, _) => {
@@ -1936,6 +1968,25 @@ impl Compiler<'_, '_> {
         self.free_temp_local(dst_mem.addr);
     }
 
+    /// Returns a `memory.copy` instruction from the memory of `src` to the
+    /// memory of `dst`, or `None` if one can't be used.
+    ///
+    /// Both memories must use the same index type so the same byte length can
+    /// be used for both of them.
+    fn memory_copy_instruction(
+        &self,
+        src: &Options,
+        dst: &Options,
+    ) -> Option<Instruction<'static>> {
+        if src.memory64 != dst.memory64 {
+            return None;
+        }
+        Some(MemoryCopy {
+            src_mem: src.memory.unwrap().as_u32(),
+            dst_mem: dst.memory.unwrap().as_u32(),
+        })
+    }
+
     fn calculate_list_byte_len(
         &mut self,
         opts: &Options,
@@ -3135,6 +3186,26 @@ fn next_field_offset<'a>(
     mem.bump(offset)
 }
 
+/// Returns whether a list element of type `src` is translated to `dst` by
+/// copying its bytes verbatim, meaning there's no validation or conversion to
+/// perform.
+fn is_bitwise_copyable(src: &InterfaceType, dst: &InterfaceType) -> bool {
+    use InterfaceType::*;
+    match (src, dst) {
+        (U8, U8)
+        | (S8, S8)
+        | (U16, U16)
+        | (S16, S16)
+        | (U32, U32)
+        | (S32, S32)
+        | (U64, U64)
+        | (S64, S64)
+        | (Float32, Float32)
+        | (Float64, Float64) => true,
+        _ => false,
+    }
+}
+
 impl<'a> Memory<'a> {
     fn memarg(&self, align: u32) -> MemArg {
         MemArg {
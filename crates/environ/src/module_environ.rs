@@ -18,7 +18,7 @@ use wasmparser::types::{CoreTypeId, Types};
 use wasmparser::{
     CompositeType, CustomSectionReader, DataKind, ElementItems, ElementKind, Encoding,
     ExternalKind, FuncToValidate, FunctionBody, NameSectionReader, Naming, Operator, Parser,
-    Payload, TypeRef, Validator, ValidatorResources,
+    Payload, TypeRef, Validator, ValidatorResources, WasmFeatures, WasmModuleResources,
 };
 use wasmtime_types::ModuleInternedTypeIndex;
 
@@ -53,6 +53,10 @@ pub struct ModuleTranslation<'data> {
     /// References to the function bodies.
     pub function_body_inputs: PrimaryMap<DefinedFuncIndex, FunctionBodyData<'data>>,
 
+    /// Copies of the function bodies which callers may inline, only populated
+    /// when `Tunables::inlining` is enabled.
+    pub inlinable_function_bodies: PrimaryMap<DefinedFuncIndex, InlinableFunctionBody<'data>>,
+
     /// A list of type signatures which are considered exported from this
     /// module, or those that can possibly be called. This list is sorted, and
     /// trampolines for each of these signatures are required.
@@ -94,6 +98,10 @@ pub struct ModuleTranslation<'data> {
     /// which function is currently being defined.
     code_index: u32,
 
+    /// Raw type indices of the functions declared in the function section,
+    /// only recorded when `Tunables::inlining` is enabled.
+    defined_func_type_indices: Vec<u32>,
+
     /// The type information of the current module made available at the end of the
     /// validation process.
     types: Option<Types>,
@@ -116,6 +124,35 @@ pub struct FunctionBodyData<'a> {
     pub validator: FuncToValidate<ValidatorResources>,
 }
 
+/// A function body retained so that it can be translated again as part of one
+/// of its callers.
+#[derive(Clone)]
+pub struct InlinableFunctionBody<'a> {
+    /// The body of the function, containing code and locals.
+    pub body: FunctionBody<'a>,
+    /// The raw type index of the function within its module.
+    pub type_index: u32,
+    /// The features that the module was validated with.
+    pub features: WasmFeatures,
+}
+
+impl InlinableFunctionBody<'_> {
+    /// Creates a validator for this body which uses the `resources` of the
+    /// module it was defined in.
+    pub fn validator<T: WasmModuleResources>(
+        &self,
+        func_index: FuncIndex,
+        resources: T,
+    ) -> FuncToValidate<T> {
+        FuncToValidate::new(
+            func_index.as_u32(),
+            self.type_index,
+            resources,
+            &self.features,
+        )
+    }
+}
+
 #[derive(Debug, Default)]
 #[allow(missing_docs)]
 pub struct DebugInfoData<'a> {
@@ -290,6 +327,9 @@ impl<'a, 'data> ModuleEnvironment<'a, 'data> {
 
                 for entry in functions {
                     let sigindex = entry?;
+                    if self.tunables.inlining {
+                        self.result.defined_func_type_indices.push(sigindex);
+                    }
                     let ty = TypeIndex::from_u32(sigindex);
                     let sig_index = self.result.module.types[ty].unwrap_function();
                     self.result.module.push_function(sig_index);
@@ -561,6 +601,17 @@ impl<'a, 'data> ModuleEnvironment<'a, 'data> {
                         });
                 }
                 body.allow_memarg64(self.validator.features().memory64);
+                if self.tunables.inlining {
+                    let type_index =
+                        self.result.defined_func_type_indices[self.result.code_index as usize];
+                    self.result
+                        .inlinable_function_bodies
+                        .push(InlinableFunctionBody {
+                            body: body.clone(),
+                            type_index,
+                            features: *self.validator.features(),
+                        });
+                }
                 self.result
                     .function_body_inputs
                     .push(FunctionBodyData { validator, body });
@@ -54,6 +54,16 @@ pub struct Tunables {
 
     /// Whether or not Wasm functions can be tail-called or not.
     pub tail_callable: bool,
+
+    /// Whether or not small functions defined in a module are inlined into
+    /// their direct callers within the same module.
+    pub inlining: bool,
+
+    /// Whether or not to generate tables describing where the values of
+    /// WebAssembly locals and operand stack are stored at each call and
+    /// trapping instruction, so that they can be included in core dumps.
+    /// This doesn't change the generated code.
+    pub generate_frame_state: bool,
 }
 
 impl Tunables {
@@ -92,6 +102,8 @@ impl Tunables {
             debug_adapter_modules: false,
             relaxed_simd_deterministic: false,
             tail_callable: false,
+            inlining: false,
+            generate_frame_state: false,
         }
     }
 
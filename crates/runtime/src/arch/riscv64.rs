@@ -28,6 +28,12 @@ pub unsafe fn get_next_older_pc_from_fp(fp: usize) -> usize {
 // And the current frame pointer points to the next older frame pointer.
 pub const NEXT_OLDER_FP_FROM_FP_OFFSET: usize = 0;
 
+// The canonical frame address is just past the saved frame pointer and return
+// address, which the frame pointer points to.
+pub fn get_cfa_from_fp(fp: usize) -> Option<usize> {
+    Some(fp + 16)
+}
+
 pub fn reached_entry_sp(fp: usize, entry_sp: usize) -> bool {
     fp >= entry_sp
 }
@@ -21,6 +21,12 @@ pub unsafe fn get_next_older_pc_from_fp(fp: usize) -> usize {
 // by the current "FP".
 pub const NEXT_OLDER_FP_FROM_FP_OFFSET: usize = 0;
 
+// The locations of values in frame state tables aren't described relative to
+// the backchain pointer, so they can't be recovered here.
+pub fn get_cfa_from_fp(_fp: usize) -> Option<usize> {
+    None
+}
+
 pub fn reached_entry_sp(fp: usize, entry_sp: usize) -> bool {
     fp > entry_sp
 }
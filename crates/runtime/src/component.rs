@@ -526,7 +526,7 @@ impl ComponentInstance {
 
     /// Implementation of the `resource.new` intrinsic for `i32`
     /// representations.
-    pub fn resource_new32(&mut self, resource: TypeResourceTableIndex, rep: u32) -> u32 {
+    pub fn resource_new32(&mut self, resource: TypeResourceTableIndex, rep: u32) -> Result<u32> {
         self.resource_tables().resource_new(Some(resource), rep)
     }
 
@@ -560,6 +560,30 @@ impl ComponentInstance {
         }
     }
 
+    /// Limits each of the resource tables of this component to at most `max`
+    /// live handles.
+    pub fn set_max_resource_handles(&mut self, max: usize) {
+        for table in self.component_resource_tables.values_mut() {
+            table.set_max_handles(max);
+        }
+    }
+
+    /// Returns all `own` handles which are currently live in the resource
+    /// tables of this component, as the table they're in, the representation
+    /// of the resource and the name of the component function which was
+    /// executing when the handle was created, if known.
+    pub fn live_resource_handles(
+        &self,
+    ) -> impl Iterator<Item = (TypeResourceTableIndex, u32, Option<&Arc<str>>)> + '_ {
+        self.component_resource_tables
+            .iter()
+            .flat_map(|(ty, table)| {
+                table
+                    .owned_reps()
+                    .map(move |(rep, origin)| (ty, rep, origin))
+            })
+    }
+
     /// Returns the runtime state of resources associated with this component.
     #[inline]
     pub fn component_resource_tables(
@@ -595,7 +619,7 @@ impl ComponentInstance {
     ) -> Result<u32> {
         let mut tables = self.resource_tables();
         let rep = tables.resource_lift_own(Some(src), idx)?;
-        Ok(tables.resource_lower_own(Some(dst), rep))
+        tables.resource_lower_own(Some(dst), rep)
     }
 
     pub(crate) fn resource_transfer_borrow(
@@ -618,11 +642,11 @@ impl ComponentInstance {
         if dst_owns_resource {
             return Ok(rep);
         }
-        Ok(tables.resource_lower_borrow(Some(dst), rep))
+        tables.resource_lower_borrow(Some(dst), rep)
     }
 
     pub(crate) fn resource_enter_call(&mut self) {
-        self.resource_tables().enter_call()
+        self.resource_tables().enter_call(None)
     }
 
     pub(crate) fn resource_exit_call(&mut self) -> Result<()> {
@@ -725,6 +749,11 @@ impl OwnedComponentInstance {
         unsafe { self.instance_mut().set_lowering(idx, lowering) }
     }
 
+    /// See `ComponentInstance::set_max_resource_handles`
+    pub fn set_max_resource_handles(&mut self, max: usize) {
+        unsafe { self.instance_mut().set_max_resource_handles(max) }
+    }
+
     /// See `ComponentInstance::set_resource_drop`
     pub fn set_trampoline(
         &mut self,
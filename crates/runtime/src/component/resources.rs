@@ -24,7 +24,9 @@
 //! namely in the `Resource<T>` and `ResourceAny` types.
 
 use anyhow::{bail, Result};
+use std::collections::HashMap;
 use std::mem;
+use std::sync::Arc;
 use wasmtime_environ::component::TypeResourceTableIndex;
 use wasmtime_environ::PrimaryMap;
 
@@ -67,12 +69,17 @@ pub struct ResourceTables<'a> {
 
 /// An individual slab of resources used for a single table within a component.
 /// Not much fancier than a general slab data structure.
-#[derive(Default)]
 pub struct ResourceTable {
     /// Next slot to allocate, or `self.slots.len()` if they're all full.
     next: u32,
     /// Runtime state of all slots.
     slots: Vec<Slot>,
+    /// Maximum number of handles which can be live in this table at once.
+    max_handles: usize,
+    /// The name of the component function which was executing when each
+    /// `own` handle was created, for handles created while
+    /// [`CallContexts::track_origins`] was enabled.
+    origins: HashMap<u32, Arc<str>>,
 }
 
 enum Slot {
@@ -100,12 +107,14 @@ enum Slot {
 #[derive(Default)]
 pub struct CallContexts {
     scopes: Vec<CallContext>,
+    track_origins: bool,
 }
 
 #[derive(Default)]
 struct CallContext {
     lenders: Vec<Lender>,
     borrow_count: u32,
+    func: Option<Arc<str>>,
 }
 
 #[derive(Copy, Clone)]
@@ -122,11 +131,25 @@ impl ResourceTables<'_> {
         }
     }
 
+    /// Returns the name of the innermost component function being called
+    /// which has one.
+    fn current_func(&self) -> Option<Arc<str>> {
+        if !self.calls.track_origins {
+            return None;
+        }
+        self.calls
+            .scopes
+            .iter()
+            .rev()
+            .find_map(|cx| cx.func.clone())
+    }
+
     /// Implementation of the `resource.new` canonical intrinsic.
     ///
     /// Note that this is the same as `resource_lower_own`.
-    pub fn resource_new(&mut self, ty: Option<TypeResourceTableIndex>, rep: u32) -> u32 {
-        self.table(ty).insert(Slot::Own { rep, lend_count: 0 })
+    pub fn resource_new(&mut self, ty: Option<TypeResourceTableIndex>, rep: u32) -> Result<u32> {
+        let origin = self.current_func();
+        self.table(ty).insert_own(rep, origin)
     }
 
     /// Implementation of the `resource.rep` canonical intrinsic.
@@ -169,12 +192,17 @@ impl ResourceTables<'_> {
     /// This will insert the specified representation into the specified type
     /// table.
     ///
-    /// Note that this operation is infallible, and additionally that this is
-    /// the same as `resource_new` implementation-wise.
+    /// This operation fails if the table is full, and otherwise this is the
+    /// same as `resource_new` implementation-wise.
     ///
     /// This is an implementation of the canonical ABI `lower_own` function.
-    pub fn resource_lower_own(&mut self, ty: Option<TypeResourceTableIndex>, rep: u32) -> u32 {
-        self.table(ty).insert(Slot::Own { rep, lend_count: 0 })
+    pub fn resource_lower_own(
+        &mut self,
+        ty: Option<TypeResourceTableIndex>,
+        rep: u32,
+    ) -> Result<u32> {
+        let origin = self.current_func();
+        self.table(ty).insert_own(rep, origin)
     }
 
     /// Attempts to remove an "own" handle from the specified table and its
@@ -237,18 +265,36 @@ impl ResourceTables<'_> {
     /// function. The other half of this implementation is located on
     /// `VMComponentContext` which handles the special case of avoiding borrow
     /// tracking entirely.
-    pub fn resource_lower_borrow(&mut self, ty: Option<TypeResourceTableIndex>, rep: u32) -> u32 {
+    pub fn resource_lower_borrow(
+        &mut self,
+        ty: Option<TypeResourceTableIndex>,
+        rep: u32,
+    ) -> Result<u32> {
         let scope = self.calls.scopes.len() - 1;
+        let idx = self.table(ty).insert(Slot::Borrow { rep, scope })?;
         let borrow_count = &mut self.calls.scopes.last_mut().unwrap().borrow_count;
         *borrow_count = borrow_count.checked_add(1).unwrap();
-        self.table(ty).insert(Slot::Borrow { rep, scope })
+        Ok(idx)
     }
 
     /// Enters a new calling context, starting a fresh count of borrows and
     /// such.
+    ///
+    /// The `func` is the name of the component function being called, if
+    /// known. When [`CallContexts::track_origins`] is enabled, `own` handles
+    /// created during the call record the name of the innermost named
+    /// function being called.
     #[inline]
-    pub fn enter_call(&mut self) {
-        self.calls.scopes.push(CallContext::default());
+    pub fn enter_call(&mut self, func: Option<&Arc<str>>) {
+        let func = if self.calls.track_origins {
+            func.cloned()
+        } else {
+            None
+        };
+        self.calls.scopes.push(CallContext {
+            func,
+            ..CallContext::default()
+        });
     }
 
     /// Exits the previously pushed calling context.
@@ -277,14 +323,70 @@ impl ResourceTables<'_> {
     }
 }
 
+impl CallContexts {
+    /// Starts recording the name of the component function which creates
+    /// each `own` handle, as returned by [`ResourceTable::owned_reps`].
+    ///
+    /// This is off by default as it has a cost on every call.
+    pub fn track_origins(&mut self) {
+        self.track_origins = true;
+    }
+
+    /// Returns whether [`CallContexts::track_origins`] has been enabled.
+    pub fn tracks_origins(&self) -> bool {
+        self.track_origins
+    }
+}
+
+impl Default for ResourceTable {
+    fn default() -> ResourceTable {
+        ResourceTable {
+            next: 0,
+            slots: Vec::new(),
+            max_handles: usize::MAX,
+            origins: HashMap::new(),
+        }
+    }
+}
+
 impl ResourceTable {
+    /// Limits the number of handles which can be live in this table at once
+    /// to `max`.
+    ///
+    /// Inserting a handle beyond this limit returns an error. Handles already
+    /// in the table are unaffected.
+    pub fn set_max_handles(&mut self, max: usize) {
+        self.max_handles = max;
+    }
+
+    /// Returns the representations of all `own` handles currently in this
+    /// table, along with the name of the component function which was
+    /// executing when each handle was created, if known.
+    pub fn owned_reps(&self) -> impl Iterator<Item = (u32, Option<&Arc<str>>)> + '_ {
+        self.slots
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, slot)| match slot {
+                Slot::Own { rep, .. } => Some((*rep, self.origins.get(&(idx as u32)))),
+                _ => None,
+            })
+    }
+
     fn next(&self) -> usize {
         self.next as usize
     }
 
-    fn insert(&mut self, new: Slot) -> u32 {
+    fn insert(&mut self, new: Slot) -> Result<u32> {
         let next = self.next();
         if next == self.slots.len() {
+            // Free slots are always reused first, so all slots are in use at
+            // this point and the table is full if it can't grow.
+            if self.slots.len() >= self.max_handles {
+                bail!(
+                    "resource table limit exceeded: cannot have more than {} handles",
+                    self.max_handles
+                );
+            }
             self.slots.push(Slot::Free {
                 next: self.next.checked_add(1).unwrap(),
             });
@@ -294,7 +396,15 @@ impl ResourceTable {
             Slot::Free { next } => next,
             _ => unreachable!(),
         };
-        u32::try_from(ret).unwrap()
+        Ok(u32::try_from(ret).unwrap())
+    }
+
+    fn insert_own(&mut self, rep: u32, origin: Option<Arc<str>>) -> Result<u32> {
+        let idx = self.insert(Slot::Own { rep, lend_count: 0 })?;
+        if let Some(origin) = origin {
+            self.origins.insert(idx, origin);
+        }
+        Ok(idx)
     }
 
     fn rep(&self, idx: u32) -> Result<u32> {
@@ -324,6 +434,9 @@ impl ResourceTable {
             Slot::Free { next: self.next },
         );
         self.next = idx;
+        if !self.origins.is_empty() {
+            self.origins.remove(&idx);
+        }
         Ok(ret)
     }
 }
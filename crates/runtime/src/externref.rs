@@ -110,7 +110,7 @@ use std::mem;
 use std::ops::Deref;
 use std::ptr::{self, NonNull};
 use std::sync::atomic::{self, AtomicUsize, Ordering};
-use wasmtime_environ::StackMap;
+use wasmtime_environ::{FrameStateInformation, SavedRegister, StackMap};
 
 /// An external reference to some opaque data.
 ///
@@ -803,6 +803,14 @@ pub trait ModuleInfoLookup {
 pub trait ModuleInfo {
     /// Lookup the stack map at a program counter value.
     fn lookup_stack_map(&self, pc: usize) -> Option<&StackMap>;
+
+    /// Lookup where the locals and operand stack of a frame are stored at a
+    /// program counter value, along with the callee-saved registers which the
+    /// frame's function saves, if they're known.
+    fn lookup_frame_state(
+        &self,
+        pc: usize,
+    ) -> Option<(&FrameStateInformation, Option<&[SavedRegister]>)>;
 }
 
 #[derive(Debug, Default)]
@@ -441,7 +441,7 @@ unsafe extern "C" fn unwind(
         } else {
             None
         };
-        state.set_jit_trap(wasm_pc, wasm_fp, faulting_addr);
+        state.set_jit_trap(wasm_pc, wasm_fp, faulting_addr, None);
         state.take_jmp_buf()
     });
     debug_assert!(!jmp_buf.is_null());
@@ -1,6 +1,6 @@
 //! Trap handling on Unix based on POSIX signals.
 
-use crate::traphandlers::tls;
+use crate::traphandlers::{tls, TrapRegisters};
 use crate::VMContext;
 use std::cell::RefCell;
 use std::io;
@@ -127,7 +127,7 @@ unsafe extern "C" fn trap_handler(
             libc::SIGSEGV | libc::SIGBUS => Some((*siginfo).si_addr() as usize),
             _ => None,
         };
-        info.set_jit_trap(pc, fp, faulting_addr);
+        info.set_jit_trap(pc, fp, faulting_addr, get_registers(context));
         // On macOS this is a bit special, unfortunately. If we were to
         // `siglongjmp` out of the signal handler that notably does
         // *not* reset the sigaltstack state of our signal handler. This
@@ -263,6 +263,60 @@ unsafe fn get_pc_and_fp(cx: *mut libc::c_void, _signum: libc::c_int) -> (*const
     }
 }
 
+/// Returns the general-purpose registers saved in the signal context `cx`, on
+/// platforms where core dumps can recover values held in registers.
+unsafe fn get_registers(cx: *mut libc::c_void) -> Option<TrapRegisters> {
+    cfg_if::cfg_if! {
+        if #[cfg(all(target_os = "linux", target_arch = "x86_64"))] {
+            let cx = &*(cx as *const libc::ucontext_t);
+            // Listed in the order of their DWARF register numbers.
+            let gregs = [
+                libc::REG_RAX as usize,
+                libc::REG_RDX as usize,
+                libc::REG_RCX as usize,
+                libc::REG_RBX as usize,
+                libc::REG_RSI as usize,
+                libc::REG_RDI as usize,
+                libc::REG_RBP as usize,
+                libc::REG_RSP as usize,
+                libc::REG_R8 as usize,
+                libc::REG_R9 as usize,
+                libc::REG_R10 as usize,
+                libc::REG_R11 as usize,
+                libc::REG_R12 as usize,
+                libc::REG_R13 as usize,
+                libc::REG_R14 as usize,
+                libc::REG_R15 as usize,
+            ];
+            let mut registers = TrapRegisters::default();
+            for (dwarf, greg) in (0..).zip(gregs) {
+                registers.set(dwarf, cx.uc_mcontext.gregs[greg] as u64);
+            }
+            Some(registers)
+        } else if #[cfg(all(target_os = "linux", target_arch = "aarch64"))] {
+            let cx = &*(cx as *const libc::ucontext_t);
+            let mut registers = TrapRegisters::default();
+            for (dwarf, value) in (0..).zip(cx.uc_mcontext.regs) {
+                registers.set(dwarf, value);
+            }
+            registers.set(31, cx.uc_mcontext.sp);
+            Some(registers)
+        } else if #[cfg(all(target_os = "linux", target_arch = "riscv64"))] {
+            let cx = &*(cx as *const libc::ucontext_t);
+            let mut registers = TrapRegisters::default();
+            // The first slot holds the pc, in place of the zero register.
+            registers.set(0, 0);
+            for (dwarf, value) in (1..).zip(&cx.uc_mcontext.__gregs[1..]) {
+                registers.set(dwarf, *value);
+            }
+            Some(registers)
+        } else {
+            let _ = cx;
+            None
+        }
+    }
+}
+
 // This is only used on macOS targets for calling an unwinding shim
 // function to ensure that we return from the signal handler.
 //
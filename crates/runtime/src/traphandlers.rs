@@ -5,7 +5,7 @@ mod backtrace;
 mod coredump;
 
 use crate::sys::traphandlers;
-use crate::{Instance, VMContext, VMRuntimeLimits};
+use crate::{Instance, Store, VMContext, VMRuntimeLimits};
 use anyhow::Error;
 use std::any::Any;
 use std::cell::{Cell, UnsafeCell};
@@ -14,7 +14,7 @@ use std::ptr;
 use std::sync::Once;
 
 pub use self::backtrace::{Backtrace, Frame};
-pub use self::coredump::CoreDumpStack;
+pub use self::coredump::{CoreDumpStack, TrapRegisters};
 pub use self::tls::{tls_eager_initialize, AsyncWasmCallState, PreviousAsyncWasmCallState};
 
 pub use traphandlers::SignalHandler;
@@ -213,8 +213,9 @@ where
     F: FnMut(*mut VMContext),
 {
     let limits = Instance::from_vmctx(caller, |i| i.runtime_limits());
+    let coredump_store = capture_coredump.then(|| Instance::from_vmctx(caller, |i| i.store()));
 
-    let result = CallThreadState::new(signal_handler, capture_backtrace, capture_coredump, *limits)
+    let result = CallThreadState::new(signal_handler, capture_backtrace, coredump_store, *limits)
         .with(|cx| {
             traphandlers::wasmtime_setjmp(
                 cx.jmp_buf.as_ptr(),
@@ -255,7 +256,10 @@ mod call_thread_state {
         pub(super) jmp_buf: Cell<*const u8>,
         pub(super) signal_handler: Option<*const SignalHandler<'static>>,
         pub(super) capture_backtrace: bool,
-        pub(super) capture_coredump: bool,
+
+        // The store whose modules describe the frame state of Wasm frames,
+        // present only if core dumps are captured.
+        pub(super) coredump_store: Option<*mut dyn Store>,
 
         pub(crate) limits: *const VMRuntimeLimits,
 
@@ -289,7 +293,7 @@ mod call_thread_state {
         pub(super) fn new(
             signal_handler: Option<*const SignalHandler<'static>>,
             capture_backtrace: bool,
-            capture_coredump: bool,
+            coredump_store: Option<*mut dyn Store>,
             limits: *const VMRuntimeLimits,
         ) -> CallThreadState {
             CallThreadState {
@@ -297,7 +301,7 @@ mod call_thread_state {
                 jmp_buf: Cell::new(ptr::null()),
                 signal_handler,
                 capture_backtrace,
-                capture_coredump,
+                coredump_store,
                 limits,
                 prev: Cell::new(ptr::null()),
                 old_last_wasm_exit_fp: Cell::new(unsafe { *(*limits).last_wasm_exit_fp.get() }),
@@ -377,7 +381,7 @@ impl CallThreadState {
                 needs_backtrace: false,
                 ..
             }) => (None, None),
-            UnwindReason::Trap(_) => (self.capture_backtrace(self.limits, None), self.capture_coredump(self.limits, None)),
+            UnwindReason::Trap(_) => (self.capture_backtrace(self.limits, None), self.capture_coredump(self.limits, None, None)),
         };
         unsafe {
             (*self.unwind.get())
@@ -437,9 +441,15 @@ impl CallThreadState {
     }
 
     #[cfg_attr(miri, allow(dead_code))] // miri doesn't handle traps yet
-    pub(crate) fn set_jit_trap(&self, pc: *const u8, fp: usize, faulting_addr: Option<usize>) {
+    pub(crate) fn set_jit_trap(
+        &self,
+        pc: *const u8,
+        fp: usize,
+        faulting_addr: Option<usize>,
+        registers: Option<TrapRegisters>,
+    ) {
         let backtrace = self.capture_backtrace(self.limits, Some((pc as usize, fp)));
-        let coredump = self.capture_coredump(self.limits, Some((pc as usize, fp)));
+        let coredump = self.capture_coredump(self.limits, Some((pc as usize, fp)), registers);
         unsafe {
             (*self.unwind.get()).as_mut_ptr().write((
                 UnwindReason::Trap(TrapReason::Jit {
@@ -468,11 +478,17 @@ impl CallThreadState {
         &self,
         limits: *const VMRuntimeLimits,
         trap_pc_and_fp: Option<(usize, usize)>,
+        trap_registers: Option<TrapRegisters>,
     ) -> Option<CoreDumpStack> {
-        if !self.capture_coredump {
-            return None;
-        }
-        Some(CoreDumpStack::new(&self, limits, trap_pc_and_fp))
+        let store = self.coredump_store?;
+        let modules = unsafe { (*store).externref_activations_table().1 };
+        Some(CoreDumpStack::new(
+            &self,
+            limits,
+            trap_pc_and_fp,
+            trap_registers,
+            modules,
+        ))
     }
 
     pub(crate) fn iter<'a>(&'a self) -> impl Iterator<Item = &Self> + 'a {
@@ -1,6 +1,7 @@
 use wasm_encoder::CoreDumpValue;
+use wasmtime_environ::{FrameRegister, FrameValue, FrameValueLoc, WasmValType};
 
-use crate::{Backtrace, VMRuntimeLimits};
+use crate::{arch, Backtrace, ModuleInfoLookup, VMRuntimeLimits};
 
 use super::CallThreadState;
 
@@ -10,29 +11,158 @@ pub struct CoreDumpStack {
     /// The backtrace containing the stack frames for the CoreDump
     pub bt: Backtrace,
 
-    /// Unimplemented
+    /// The locals for each stack frame.
+    ///
     /// The indices of the locals and operand_stack all map to each other (ie.
-    /// index 0 is the locals for the first frame in the backtrace, etc)
+    /// index 0 is the locals for the first frame in the backtrace, etc). Frames
+    /// of code compiled without `Tunables::generate_frame_state` have no
+    /// locals, and values which can't be recovered are missing.
     pub locals: Vec<Vec<CoreDumpValue>>,
 
-    /// Unimplemented
-    /// The operands for each stack frame
+    /// The operands for each stack frame, from the bottom of the stack to the
+    /// top.
     pub operand_stack: Vec<Vec<CoreDumpValue>>,
 }
 
+/// The number of registers in `TrapRegisters`, which covers the DWARF numbers
+/// of the general-purpose and the first 32 floating-point or vector registers
+/// on every supported architecture.
+const NUM_REGISTERS: usize = 96;
+
+/// The values of the machine registers when a trap happened, keyed by DWARF
+/// register number.
+///
+/// Only the low 64 bits of floating-point and vector registers are kept, and
+/// registers default to being unknown.
+#[derive(Clone)]
+pub struct TrapRegisters {
+    values: [Option<u64>; NUM_REGISTERS],
+}
+
+impl Default for TrapRegisters {
+    fn default() -> Self {
+        Self {
+            values: [None; NUM_REGISTERS],
+        }
+    }
+}
+
+impl TrapRegisters {
+    /// Sets the value of the register with DWARF number `reg`.
+    pub fn set(&mut self, reg: u16, value: u64) {
+        if let Some(slot) = self.values.get_mut(usize::from(reg)) {
+            *slot = Some(value);
+        }
+    }
+
+    fn get(&self, reg: FrameRegister) -> Option<u64> {
+        self.values.get(usize::from(reg.0)).copied().flatten()
+    }
+}
+
 impl CoreDumpStack {
     /// Capture a core dump of the current wasm state
+    ///
+    /// `trap_registers` are the registers at the time of the trap at
+    /// `trap_pc_and_fp`, if they're available.
     pub fn new(
         cts: &CallThreadState,
         limits: *const VMRuntimeLimits,
         trap_pc_and_fp: Option<(usize, usize)>,
+        trap_registers: Option<TrapRegisters>,
+        modules: &dyn ModuleInfoLookup,
     ) -> Self {
         let bt = unsafe { Backtrace::new_with_trap_state(limits, cts, trap_pc_and_fp) };
 
+        let mut locals = Vec::with_capacity(bt.frames().len());
+        let mut operand_stack = Vec::with_capacity(bt.frames().len());
+
+        // The trap's registers belong to the first frame. The registers of
+        // each following frame are those of the frame before it, with the
+        // callee-saved registers which that frame saved restored. Other
+        // registers may have been changed since, but values are only recorded
+        // in a caller's frame where they're live across the call, which keeps
+        // them out of any register the callee is free to change.
+        let mut registers = trap_pc_and_fp.and(trap_registers);
+        let mut frames = bt.frames().peekable();
+        while let Some(frame) = frames.next() {
+            let state = modules
+                .lookup(frame.pc())
+                .and_then(|module| module.lookup_frame_state(frame.pc()));
+            let cfa = arch::get_cfa_from_fp(frame.fp());
+            let (saved, cfa) = match (state, cfa) {
+                (Some((info, saved)), Some(cfa)) => {
+                    let read =
+                        |value: &FrameValue| unsafe { read_value(cfa, registers.as_ref(), value) };
+                    locals.push(info.locals.iter().map(read).collect());
+                    operand_stack.push(info.stack.iter().map(read).collect());
+                    (saved, cfa)
+                }
+                _ => {
+                    locals.push(vec![]);
+                    operand_stack.push(vec![]);
+                    registers = None;
+                    continue;
+                }
+            };
+
+            // The saved registers only describe the next frame if it's the
+            // direct caller of this one, rather than separated from it by
+            // host frames.
+            let is_caller = frames.peek().map_or(false, |next| unsafe {
+                *(frame.fp() as *const usize).add(arch::NEXT_OLDER_FP_FROM_FP_OFFSET) == next.fp()
+            });
+            registers = match (registers, saved) {
+                (Some(mut registers), Some(saved)) if is_caller => {
+                    for saved in saved {
+                        let addr = cfa.wrapping_add_signed(saved.cfa_offset as isize);
+                        let value = unsafe { std::ptr::read_unaligned(addr as *const u64) };
+                        registers.set(saved.reg.0, value);
+                    }
+                    Some(registers)
+                }
+                _ => None,
+            };
+        }
+
         Self {
             bt,
-            locals: vec![],
-            operand_stack: vec![],
+            locals,
+            operand_stack,
+        }
+    }
+}
+
+/// Reads `value` out of the frame whose canonical frame address is `cfa`,
+/// and whose registers are `registers`.
+unsafe fn read_value(
+    cfa: usize,
+    registers: Option<&TrapRegisters>,
+    value: &FrameValue,
+) -> CoreDumpValue {
+    let bits = match value.loc {
+        Some(FrameValueLoc::Stack(offset)) => {
+            let addr = cfa.wrapping_add_signed(offset as isize);
+            match value.ty {
+                WasmValType::I32 | WasmValType::F32 => {
+                    u64::from(std::ptr::read_unaligned(addr as *const u32))
+                }
+                WasmValType::I64 | WasmValType::F64 => std::ptr::read_unaligned(addr as *const u64),
+                WasmValType::V128 | WasmValType::Ref(_) => return CoreDumpValue::Missing,
+            }
         }
+        Some(FrameValueLoc::Reg(reg)) => match registers.and_then(|r| r.get(reg)) {
+            Some(bits) => bits,
+            None => return CoreDumpValue::Missing,
+        },
+        Some(FrameValueLoc::Const(bits)) => bits,
+        None => return CoreDumpValue::Missing,
+    };
+    match value.ty {
+        WasmValType::I32 => CoreDumpValue::I32(bits as u32 as i32),
+        WasmValType::I64 => CoreDumpValue::I64(bits as i64),
+        WasmValType::F32 => CoreDumpValue::F32(f32::from_bits(bits as u32)),
+        WasmValType::F64 => CoreDumpValue::F64(f64::from_bits(bits)),
+        WasmValType::V128 | WasmValType::Ref(_) => CoreDumpValue::Missing,
     }
 }
@@ -0,0 +1,28 @@
+use std::{
+    error::Error,
+    fs::{self, File},
+    io::{Seek, SeekFrom, Write},
+};
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let mut file = File::create("file.txt")?;
+
+    // The filesystem only allows a megabyte to be stored, so huge sizes are
+    // refused rather than allocated.
+    let err = file.set_len(1 << 40).unwrap_err();
+    assert_eq!(err.raw_os_error(), Some(libc::ENOSPC));
+    file.seek(SeekFrom::Start(1 << 40))?;
+    let err = file.write_all(b"x").unwrap_err();
+    assert_eq!(err.raw_os_error(), Some(libc::ENOSPC));
+    assert_eq!(file.metadata()?.len(), 0);
+
+    file.set_len(1 << 20)?;
+    assert!(file.set_len((1 << 20) + 1).is_err());
+    drop(file);
+
+    // Removing the file makes its space available again.
+    fs::remove_file("file.txt")?;
+    File::create("other.txt")?.set_len(1 << 20)?;
+
+    Ok(())
+}
@@ -0,0 +1,84 @@
+use anyhow::Result;
+use std::fs;
+use wasi_nn::*;
+
+pub fn main() -> Result<()> {
+    let model = fs::read("fixture/model.onnx").unwrap();
+    println!("Read ONNX model, size in bytes: {}", model.len());
+
+    let graph = GraphBuilder::new(GraphEncoding::Onnx, ExecutionTarget::CPU)
+        .build_from_bytes([&model])?;
+    println!("Loaded graph into wasi-nn with ID: {}", graph);
+
+    let mut context = graph.init_execution_context()?;
+    println!("Created wasi-nn execution context with ID: {}", context);
+
+    // Load the same image used by the OpenVINO test and convert it to the
+    // input expected by MobileNet v2 (see `to_nchw_rgb`).
+    let data = fs::read("fixture/tensor.bgr").unwrap();
+    println!("Read input tensor, size in bytes: {}", data.len());
+    let data = to_nchw_rgb(&data);
+    context.set_input(0, wasi_nn::TensorType::F32, &[1, 3, 224, 224], &data)?;
+
+    // Execute the inference.
+    context.compute()?;
+    println!("Executed graph inference");
+
+    // Retrieve the output.
+    let mut output_buffer = vec![0f32; 1000];
+    context.get_output(0, &mut output_buffer[..])?;
+    let results = sort_results(&output_buffer);
+    println!("Found results, sorted top 5: {:?}", &results[..5]);
+
+    // The image is classified as 963 = "pizza", as it is by the OpenVINO test.
+    assert_eq!(results[0].0, 963);
+
+    Ok(())
+}
+
+const WIDTH: usize = 224;
+const HEIGHT: usize = 224;
+
+// The ImageNet per-channel mean and standard deviation, in RGB order, that the
+// ONNX model zoo's MobileNet v2 was trained with.
+const MEAN: [f32; 3] = [0.485, 0.456, 0.406];
+const STD: [f32; 3] = [0.229, 0.224, 0.225];
+
+// The fixture tensor is laid out as NHWC with BGR pixels in the range
+// `0..=255`, as the OpenVINO model expects. The ONNX model instead takes NCHW
+// RGB pixels scaled to `0..=1` and normalized with `MEAN` and `STD`, so this
+// transposes and normalizes the tensor into those bytes.
+fn to_nchw_rgb(bgr: &[u8]) -> Vec<u8> {
+    let pixels: Vec<f32> = bgr
+        .chunks_exact(4)
+        .map(|b| f32::from_le_bytes(b.try_into().unwrap()))
+        .collect();
+    assert_eq!(pixels.len(), 3 * WIDTH * HEIGHT);
+    let mut rgb = vec![0f32; pixels.len()];
+    for y in 0..HEIGHT {
+        for x in 0..WIDTH {
+            let pixel = &pixels[(y * WIDTH + x) * 3..][..3];
+            for c in 0..3 {
+                let value = pixel[2 - c] / 255.0;
+                rgb[(c * HEIGHT + y) * WIDTH + x] = (value - MEAN[c]) / STD[c];
+            }
+        }
+    }
+    rgb.iter().flat_map(|f| f.to_le_bytes()).collect()
+}
+
+// Sort the buffer of scores. The graph places the score for each class at the
+// index for that class (e.g. the score of class 42 is placed at buffer[42]).
+fn sort_results(buffer: &[f32]) -> Vec<InferenceResult> {
+    let mut results: Vec<InferenceResult> = buffer
+        .iter()
+        .enumerate()
+        .map(|(c, p)| InferenceResult(c, *p))
+        .collect();
+    results.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+    results
+}
+
+// A wrapper for class ID and match probabilities.
+#[derive(Debug, PartialEq)]
+struct InferenceResult(usize, f32);
@@ -0,0 +1,231 @@
+//! Implements a `wasi-nn` [`BackendInner`] using the pure-Rust ONNX runtime
+//! from `tract`.
+//!
+//! Unlike OpenVINO this requires no system libraries, but it only supports
+//! running models on the CPU.
+
+use super::{BackendError, BackendExecutionContext, BackendFromDir, BackendGraph, BackendInner};
+use crate::wit::types::{ExecutionTarget, GraphEncoding, Tensor, TensorType};
+use crate::{ExecutionContext, Graph};
+use anyhow::{anyhow, bail};
+use std::{path::Path, sync::Arc};
+use tract_onnx::prelude::{
+    DatumType, Framework, InferenceFact, InferenceModel, InferenceModelExt, TValue, TVec,
+    TypedModel, TypedRunnableModel,
+};
+
+#[derive(Default)]
+pub struct OnnxBackend;
+
+impl BackendInner for OnnxBackend {
+    fn encoding(&self) -> GraphEncoding {
+        GraphEncoding::Onnx
+    }
+
+    fn load(&mut self, builders: &[&[u8]], target: ExecutionTarget) -> Result<Graph, BackendError> {
+        if builders.len() != 1 {
+            return Err(BackendError::InvalidNumberOfBuilders(1, builders.len()));
+        }
+        if target != ExecutionTarget::Cpu {
+            return Err(
+                anyhow!("the ONNX backend only supports CPU execution, not {target:?}").into(),
+            );
+        }
+
+        // Only parse the model here: the shapes of its inputs may be left
+        // unspecified until the guest sets them, and `tract` can only optimize
+        // a model once they are known.
+        let model = tract_onnx::onnx().model_for_read(&mut &builders[0][..])?;
+        let num_inputs = model.input_outlets()?.len();
+        let box_: Box<dyn BackendGraph> = Box::new(OnnxGraph {
+            model: Arc::new(model),
+            num_inputs,
+        });
+        Ok(box_.into())
+    }
+
+    fn as_dir_loadable(&mut self) -> Option<&mut dyn BackendFromDir> {
+        Some(self)
+    }
+}
+
+impl BackendFromDir for OnnxBackend {
+    fn load_from_dir(
+        &mut self,
+        path: &Path,
+        target: ExecutionTarget,
+    ) -> Result<Graph, BackendError> {
+        let model = std::fs::read(path.join("model.onnx")).map_err(anyhow::Error::from)?;
+        self.load(&[&model], target)
+    }
+}
+
+struct OnnxGraph {
+    model: Arc<InferenceModel>,
+    num_inputs: usize,
+}
+
+impl BackendGraph for OnnxGraph {
+    fn init_execution_context(&self) -> Result<ExecutionContext, BackendError> {
+        let box_: Box<dyn BackendExecutionContext> = Box::new(OnnxExecutionContext {
+            model: self.model.clone(),
+            inputs: vec![None; self.num_inputs],
+            plan: None,
+            outputs: TVec::new(),
+        });
+        Ok(box_.into())
+    }
+}
+
+type Plan = TypedRunnableModel<TypedModel>;
+
+struct OnnxExecutionContext {
+    model: Arc<InferenceModel>,
+    inputs: Vec<Option<tract_onnx::prelude::Tensor>>,
+    /// The optimized model along with the input shapes it was optimized for;
+    /// this is reused as long as the guest keeps passing inputs of the same
+    /// shapes.
+    plan: Option<(Vec<(DatumType, Vec<usize>)>, Arc<Plan>)>,
+    outputs: TVec<TValue>,
+}
+
+impl OnnxExecutionContext {
+    fn plan(&mut self, facts: Vec<(DatumType, Vec<usize>)>) -> anyhow::Result<Arc<Plan>> {
+        if let Some((cached, plan)) = &self.plan {
+            if *cached == facts {
+                return Ok(plan.clone());
+            }
+        }
+        let mut model = (*self.model).clone();
+        for (i, (dt, shape)) in facts.iter().enumerate() {
+            let shape = shape.iter().copied().collect::<TVec<usize>>();
+            model = model.with_input_fact(i, InferenceFact::dt_shape(*dt, shape))?;
+        }
+        let plan = Arc::new(model.into_optimized()?.into_runnable()?);
+        self.plan = Some((facts, plan.clone()));
+        Ok(plan)
+    }
+}
+
+impl BackendExecutionContext for OnnxExecutionContext {
+    fn set_input(&mut self, index: u32, tensor: &Tensor) -> Result<(), BackendError> {
+        let slot = self
+            .inputs
+            .get_mut(index as usize)
+            .ok_or_else(|| anyhow!("the model has no input at index {index}"))?;
+        let dt = map_tensor_type_to_datum_type(tensor.tensor_type)?;
+        let shape = tensor
+            .dimensions
+            .iter()
+            .map(|&d| d as usize)
+            .collect::<Vec<_>>();
+        let len = shape
+            .iter()
+            .try_fold(dt.size_of(), |len, &d| len.checked_mul(d))
+            .ok_or_else(|| anyhow!("tensor shape {shape:?} is too large"))?;
+        if tensor.data.len() != len {
+            return Err(anyhow!(
+                "expected {len} bytes of tensor data for shape {shape:?}, found {}",
+                tensor.data.len()
+            )
+            .into());
+        }
+        // SAFETY: every bit pattern is a valid value of the numeric types
+        // wasi-nn supports, and the data was checked to match the shape above.
+        let input = unsafe { tract_onnx::prelude::Tensor::from_raw_dt(dt, &shape, &tensor.data)? };
+        *slot = Some(input);
+        Ok(())
+    }
+
+    fn compute(&mut self) -> Result<(), BackendError> {
+        let mut facts = Vec::with_capacity(self.inputs.len());
+        let mut inputs = TVec::with_capacity(self.inputs.len());
+        for (i, input) in self.inputs.iter().enumerate() {
+            let Some(input) = input else {
+                return Err(anyhow!("input {i} has not been set").into());
+            };
+            facts.push((input.datum_type(), input.shape().to_vec()));
+            inputs.push(TValue::from(input.clone()));
+        }
+        let plan = self.plan(facts)?;
+        self.outputs = plan.run(inputs)?;
+        Ok(())
+    }
+
+    fn get_output(&mut self, index: u32, destination: &mut [u8]) -> Result<u32, BackendError> {
+        let output = self
+            .outputs
+            .get(index as usize)
+            .ok_or_else(|| anyhow!("no output at index {index}; has compute been called?"))?;
+        let bytes = output.as_bytes();
+        if bytes.len() > destination.len() {
+            return Err(BackendError::NotEnoughMemory(bytes.len()));
+        }
+
+        // Copy the tensor data into the destination buffer.
+        destination[..bytes.len()].copy_from_slice(bytes);
+        Ok(bytes.len() as u32)
+    }
+}
+
+/// Return `tract`'s datum type for the `TensorType` enum provided by wasi-nn.
+fn map_tensor_type_to_datum_type(tensor_type: TensorType) -> anyhow::Result<DatumType> {
+    Ok(match tensor_type {
+        TensorType::Fp16 => DatumType::F16,
+        TensorType::Fp32 => DatumType::F32,
+        TensorType::Fp64 => DatumType::F64,
+        TensorType::U8 => DatumType::U8,
+        TensorType::I32 => DatumType::I32,
+        TensorType::I64 => DatumType::I64,
+        TensorType::Bf16 => bail!("the ONNX backend does not support bf16 tensors"),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn rejects_non_cpu_targets() {
+        let mut backend = OnnxBackend;
+        let model: &[u8] = &[];
+        let err = backend
+            .load(&[model], ExecutionTarget::Gpu)
+            .err()
+            .expect("loading for a GPU should fail");
+        assert!(matches!(err, BackendError::BackendAccess(_)));
+    }
+
+    #[test]
+    fn rejects_extra_builders() {
+        let mut backend = OnnxBackend;
+        let model: &[u8] = &[];
+        let err = backend
+            .load(&[model, model], ExecutionTarget::Cpu)
+            .err()
+            .expect("loading from two buffers should fail");
+        assert!(matches!(err, BackendError::InvalidNumberOfBuilders(1, 2)));
+    }
+
+    #[test]
+    fn rejects_overflowing_shapes() {
+        let mut ctx = OnnxExecutionContext {
+            model: Arc::new(InferenceModel::default()),
+            inputs: vec![None],
+            plan: None,
+            outputs: TVec::new(),
+        };
+        // With 4-byte elements this wraps around to a length of zero.
+        let tensor = Tensor {
+            dimensions: vec![1 << 31, 1 << 31, 1 << 31, 1 << 31],
+            tensor_type: TensorType::Fp32,
+            data: vec![],
+        };
+        let err = ctx
+            .set_input(0, &tensor)
+            .err()
+            .expect("an overflowing shape should fail");
+        assert!(matches!(err, BackendError::BackendAccess(_)));
+        assert!(ctx.inputs[0].is_none());
+    }
+}
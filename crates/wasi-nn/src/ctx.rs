@@ -71,7 +71,7 @@ pub enum WasiNnError {
 pub enum UsageError {
     #[error("Invalid context; has the load function been called?")]
     InvalidContext,
-    #[error("Only OpenVINO's IR is currently supported, passed encoding: {0:?}")]
+    #[error("No backend is available for the passed encoding: {0:?}")]
     InvalidEncoding(GraphEncoding),
     #[error("OpenVINO expects only two buffers (i.e. [ir, weights]), passed: {0}")]
     InvalidNumberOfBuilders(u32),
@@ -4,6 +4,9 @@
 //! This module checks:
 //! - that OpenVINO can be found in the environment
 //! - that some ML model artifacts can be downloaded and cached.
+//!
+//! The ONNX backend needs no system libraries, so its tests only check for the
+//! model artifacts; see [`check_onnx`].
 
 use anyhow::{anyhow, Context, Result};
 use std::{env, fs, path::Path, path::PathBuf, process::Command, sync::Mutex};
@@ -16,10 +19,16 @@ pub fn artifacts_dir() -> PathBuf {
 /// Early-return from a test if the test environment is not met. If the `CI`
 /// or `FORCE_WASINN_TEST_CHECK` environment variables are set, though, this
 /// will return an error instead.
+///
+/// By default this runs [`check`]; another check, such as [`check_onnx`], can
+/// be passed instead.
 #[macro_export]
 macro_rules! check_test {
     () => {
-        if let Err(e) = $crate::testing::check() {
+        $crate::check_test!($crate::testing::check())
+    };
+    ($check:expr) => {
+        if let Err(e) = $check {
             if std::env::var_os("CI").is_some()
                 || std::env::var_os("FORCE_WASINN_TEST_CHECK").is_some()
             {
@@ -39,6 +48,12 @@ pub fn check() -> Result<()> {
     Ok(())
 }
 
+/// Return `Ok` if the artifacts for testing the ONNX backend are available.
+pub fn check_onnx() -> Result<()> {
+    check_onnx_artifacts_are_available()?;
+    Ok(())
+}
+
 /// Return `Ok` if we find a working OpenVINO installation.
 fn check_openvino_is_installed() -> Result<()> {
     match std::panic::catch_unwind(|| println!("> found openvino version: {}", openvino::version()))
@@ -80,6 +95,21 @@ fn check_openvino_artifacts_are_available() -> Result<()> {
     Ok(())
 }
 
+/// Return `Ok` if we find the cached MobileNet ONNX model; this will download
+/// it if necessary. The input tensor is shared with the OpenVINO artifacts.
+fn check_onnx_artifacts_are_available() -> Result<()> {
+    check_openvino_artifacts_are_available()?;
+    let _exclusively_retrieve_artifacts = ARTIFACTS.lock().unwrap();
+    const URL: &str = "https://github.com/onnx/models/raw/main/validated/vision/classification/mobilenet/model/mobilenetv2-7.onnx";
+    let local_path = artifacts_dir().join("model.onnx");
+    if !local_path.is_file() {
+        download(URL, &local_path).with_context(|| "unable to retrieve test artifact")?;
+    } else {
+        println!("> using cached artifact: {}", local_path.display())
+    }
+    Ok(())
+}
+
 /// Retrieve the bytes at the `from` URL and place them in the `to` file.
 fn download(from: &str, to: &Path) -> anyhow::Result<()> {
     let mut curl = Command::new("curl");
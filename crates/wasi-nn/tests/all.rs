@@ -6,15 +6,28 @@ use test_programs_artifacts::*;
 use wasi_common::sync::{Dir, WasiCtxBuilder};
 use wasi_common::WasiCtx;
 use wasmtime::{Config, Engine, Linker, Module, Store};
-use wasmtime_wasi_nn::{backend, testing, InMemoryRegistry, WasiNnCtx};
+use wasmtime_wasi_nn::{backend, testing, Backend, InMemoryRegistry, WasiNnCtx};
 
 const PREOPENED_DIR_NAME: &str = "fixture";
 
-/// Run a wasi-nn test program. This is modeled after
+/// Run a wasi-nn test program with the OpenVINO backend. This is modeled after
 /// `crates/wasi/tests/all/main.rs` but still uses the older preview1 API for
 /// file reads.
 fn run(path: &str, preload_model: bool) -> Result<()> {
     wasmtime_wasi_nn::check_test!();
+    let openvino = backend::openvino::OpenvinoBackend::default();
+    run_with_backend(path, openvino.into(), preload_model)
+}
+
+/// Run a wasi-nn test program with the pure-Rust ONNX backend, which needs
+/// no system libraries.
+#[cfg(feature = "onnx")]
+fn run_onnx(path: &str) -> Result<()> {
+    wasmtime_wasi_nn::check_test!(testing::check_onnx());
+    run_with_backend(path, backend::onnx::OnnxBackend.into(), false)
+}
+
+fn run_with_backend(path: &str, backend: Backend, preload_model: bool) -> Result<()> {
     let path = Path::new(path);
     let config = Config::new();
     let engine = Engine::new(&config)?;
@@ -22,7 +35,10 @@ fn run(path: &str, preload_model: bool) -> Result<()> {
     wasmtime_wasi_nn::witx::add_to_linker(&mut linker, |s: &mut Ctx| &mut s.wasi_nn)?;
     wasi_common::sync::add_to_linker(&mut linker, |s: &mut Ctx| &mut s.wasi)?;
     let module = Module::from_file(&engine, path)?;
-    let mut store = Store::new(&engine, Ctx::new(&testing::artifacts_dir(), preload_model)?);
+    let mut store = Store::new(
+        &engine,
+        Ctx::new(&testing::artifacts_dir(), backend, preload_model)?,
+    );
     let instance = linker.instantiate(&mut store, &module)?;
     let start = instance.get_typed_func::<(), ()>(&mut store, "_start")?;
     start.call(&mut store, ())?;
@@ -35,7 +51,7 @@ struct Ctx {
     wasi_nn: WasiNnCtx,
 }
 impl Ctx {
-    fn new(preopen_dir: &Path, preload_model: bool) -> Result<Self> {
+    fn new(preopen_dir: &Path, mut backend: Backend, preload_model: bool) -> Result<Self> {
         // Create the WASI context.
         let preopen_dir = Dir::open_ambient_dir(preopen_dir, cap_std::ambient_authority())?;
         let mut builder = WasiCtxBuilder::new();
@@ -45,13 +61,15 @@ impl Ctx {
         let wasi = builder.build();
 
         // Create the wasi-nn context.
-        let mut openvino = backend::openvino::OpenvinoBackend::default();
         let mut registry = InMemoryRegistry::new();
         let mobilenet_dir = testing::artifacts_dir();
         if preload_model {
-            registry.load(&mut openvino, &mobilenet_dir)?;
+            let backend = backend
+                .as_dir_loadable()
+                .expect("backend should support loading from a directory");
+            registry.load(backend, &mobilenet_dir)?;
         }
-        let wasi_nn = WasiNnCtx::new([openvino.into()], registry.into());
+        let wasi_nn = WasiNnCtx::new([backend], registry.into());
 
         Ok(Self { wasi, wasi_nn })
     }
@@ -90,3 +108,10 @@ fn nn_image_classification() {
 fn nn_image_classification_named() {
     run(NN_IMAGE_CLASSIFICATION_NAMED, true).unwrap()
 }
+
+#[cfg_attr(not(feature = "onnx"), ignore)]
+#[test]
+fn nn_image_classification_onnx() {
+    #[cfg(feature = "onnx")]
+    run_onnx(NN_IMAGE_CLASSIFICATION_ONNX).unwrap()
+}
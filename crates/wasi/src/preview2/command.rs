@@ -1,3 +1,4 @@
+use crate::preview2::record::TracedSockets;
 use crate::preview2::WasiView;
 
 wasmtime::component::bindgen!({
@@ -47,17 +48,18 @@ pub fn add_to_linker<T: WasiView>(l: &mut wasmtime::component::Linker<T>) -> any
     crate::preview2::bindings::cli::terminal_stdin::add_to_linker(l, |t| t)?;
     crate::preview2::bindings::cli::terminal_stdout::add_to_linker(l, |t| t)?;
     crate::preview2::bindings::cli::terminal_stderr::add_to_linker(l, |t| t)?;
-    crate::preview2::bindings::sockets::tcp::add_to_linker(l, |t| t)?;
-    crate::preview2::bindings::sockets::tcp_create_socket::add_to_linker(l, |t| t)?;
-    crate::preview2::bindings::sockets::udp::add_to_linker(l, |t| t)?;
-    crate::preview2::bindings::sockets::udp_create_socket::add_to_linker(l, |t| t)?;
+    crate::preview2::bindings::sockets::tcp::add_to_linker(l, TracedSockets::new)?;
+    crate::preview2::bindings::sockets::tcp_create_socket::add_to_linker(l, TracedSockets::new)?;
+    crate::preview2::bindings::sockets::udp::add_to_linker(l, TracedSockets::new)?;
+    crate::preview2::bindings::sockets::udp_create_socket::add_to_linker(l, TracedSockets::new)?;
     crate::preview2::bindings::sockets::instance_network::add_to_linker(l, |t| t)?;
     crate::preview2::bindings::sockets::network::add_to_linker(l, |t| t)?;
-    crate::preview2::bindings::sockets::ip_name_lookup::add_to_linker(l, |t| t)?;
+    crate::preview2::bindings::sockets::ip_name_lookup::add_to_linker(l, TracedSockets::new)?;
     Ok(())
 }
 
 pub mod sync {
+    use crate::preview2::record::TracedSockets;
     use crate::preview2::WasiView;
 
     wasmtime::component::bindgen!({
@@ -110,13 +112,19 @@ pub mod sync {
         crate::preview2::bindings::cli::terminal_stdin::add_to_linker(l, |t| t)?;
         crate::preview2::bindings::cli::terminal_stdout::add_to_linker(l, |t| t)?;
         crate::preview2::bindings::cli::terminal_stderr::add_to_linker(l, |t| t)?;
-        crate::preview2::bindings::sockets::tcp::add_to_linker(l, |t| t)?;
-        crate::preview2::bindings::sockets::tcp_create_socket::add_to_linker(l, |t| t)?;
-        crate::preview2::bindings::sockets::udp::add_to_linker(l, |t| t)?;
-        crate::preview2::bindings::sockets::udp_create_socket::add_to_linker(l, |t| t)?;
+        crate::preview2::bindings::sockets::tcp::add_to_linker(l, TracedSockets::new)?;
+        crate::preview2::bindings::sockets::tcp_create_socket::add_to_linker(
+            l,
+            TracedSockets::new,
+        )?;
+        crate::preview2::bindings::sockets::udp::add_to_linker(l, TracedSockets::new)?;
+        crate::preview2::bindings::sockets::udp_create_socket::add_to_linker(
+            l,
+            TracedSockets::new,
+        )?;
         crate::preview2::bindings::sockets::instance_network::add_to_linker(l, |t| t)?;
         crate::preview2::bindings::sockets::network::add_to_linker(l, |t| t)?;
-        crate::preview2::bindings::sockets::ip_name_lookup::add_to_linker(l, |t| t)?;
+        crate::preview2::bindings::sockets::ip_name_lookup::add_to_linker(l, TracedSockets::new)?;
         Ok(())
     }
 }
@@ -3,11 +3,13 @@ use crate::preview2::{
         host::{monotonic_clock, wall_clock},
         HostMonotonicClock, HostWallClock,
     },
-    filesystem::{Dir, OpenMode},
+    filesystem::{Dir, HostFilesystem, OpenMode},
     network::{SocketAddrCheck, SocketAddrUse},
-    pipe, random, stdio,
+    pipe, random,
+    record::Trace,
+    stdio,
     stdio::{StdinStream, StdoutStream},
-    DirPerms, FilePerms,
+    DirPerms, FilePerms, Recorder, Replayer,
 };
 use cap_rand::{Rng, RngCore, SeedableRng};
 use std::sync::Arc;
@@ -28,6 +30,7 @@ pub struct WasiCtxBuilder {
     wall_clock: Box<dyn HostWallClock + Send>,
     monotonic_clock: Box<dyn HostMonotonicClock + Send>,
     allowed_network_uses: AllowedNetworkUses,
+    trace: Option<Trace>,
     built: bool,
 }
 
@@ -78,6 +81,7 @@ impl WasiCtxBuilder {
             wall_clock: wall_clock(),
             monotonic_clock: monotonic_clock(),
             allowed_network_uses: AllowedNetworkUses::default(),
+            trace: None,
             built: false,
         }
     }
@@ -143,6 +147,19 @@ impl WasiCtxBuilder {
         perms: DirPerms,
         file_perms: FilePerms,
         path: impl AsRef<str>,
+    ) -> &mut Self {
+        self.preopened_filesystem(dir, perms, file_perms, path)
+    }
+
+    /// Preopens a directory backed by `fs` rather than the host's filesystem,
+    /// such as a [`MemoryFilesystem`](crate::preview2::MemoryFilesystem), and
+    /// makes it available to the guest at `path`.
+    pub fn preopened_filesystem(
+        &mut self,
+        fs: impl HostFilesystem,
+        perms: DirPerms,
+        file_perms: FilePerms,
+        path: impl AsRef<str>,
     ) -> &mut Self {
         let mut open_mode = OpenMode::empty();
         if perms.contains(DirPerms::READ) {
@@ -152,7 +169,7 @@ impl WasiCtxBuilder {
             open_mode |= OpenMode::WRITE;
         }
         self.preopens.push((
-            Dir::new(dir, perms, file_perms, open_mode),
+            Dir::new(Arc::new(fs), perms, file_perms, open_mode),
             path.as_ref().to_owned(),
         ));
         self
@@ -228,6 +245,29 @@ impl WasiCtxBuilder {
         self
     }
 
+    /// Records the results of the guest's calls to clocks, random number
+    /// generators, stdin, preopened directories, and sockets to `recorder`.
+    ///
+    /// See the [`record`](crate::preview2::record) module for what is and
+    /// isn't recorded.
+    pub fn record(&mut self, recorder: Recorder) -> &mut Self {
+        self.trace = Some(Trace::Record(recorder));
+        self
+    }
+
+    /// Replays the results of the guest's calls to clocks, random number
+    /// generators, stdin, preopened directories, and sockets from `replayer`
+    /// rather than using the configured sources.
+    ///
+    /// Preopened directories must be configured with the same paths and
+    /// permissions as when the trace was recorded, but their contents are
+    /// never accessed. Sockets never touch the network, so they don't need
+    /// to be allowed.
+    pub fn replay(&mut self, replayer: Replayer) -> &mut Self {
+        self.trace = Some(Trace::Replay(replayer));
+        self
+    }
+
     /// Uses the configured context so far to construct the final `WasiCtx`.
     ///
     /// Note that each `WasiCtxBuilder` can only be used to "build" once, and
@@ -240,23 +280,36 @@ impl WasiCtxBuilder {
         assert!(!self.built);
 
         let Self {
-            stdin,
+            mut stdin,
             stdout,
             stderr,
             env,
             args,
-            preopens,
+            mut preopens,
             socket_addr_check,
-            random,
-            insecure_random,
-            insecure_random_seed,
-            wall_clock,
-            monotonic_clock,
+            mut random,
+            mut insecure_random,
+            mut insecure_random_seed,
+            mut wall_clock,
+            mut monotonic_clock,
             allowed_network_uses,
+            trace,
             built: _,
         } = mem::replace(self, Self::new());
         self.built = true;
 
+        if let Some(trace) = &trace {
+            stdin = trace.stdin(stdin);
+            random = trace.random(random);
+            insecure_random = trace.insecure_random(insecure_random);
+            insecure_random_seed = trace.insecure_random_seed(insecure_random_seed);
+            wall_clock = trace.wall_clock(wall_clock);
+            monotonic_clock = trace.monotonic_clock(monotonic_clock);
+            for (dir, _) in preopens.iter_mut() {
+                dir.dir = trace.filesystem(dir.dir.clone());
+            }
+        }
+
         WasiCtx {
             stdin,
             stdout,
@@ -271,6 +324,7 @@ impl WasiCtxBuilder {
             wall_clock,
             monotonic_clock,
             allowed_network_uses,
+            trace,
         }
     }
 }
@@ -294,6 +348,7 @@ pub struct WasiCtx {
     pub(crate) stderr: Box<dyn StdoutStream>,
     pub(crate) socket_addr_check: SocketAddrCheck,
     pub(crate) allowed_network_uses: AllowedNetworkUses,
+    pub(crate) trace: Option<Trace>,
 }
 
 pub struct AllowedNetworkUses {
@@ -4,9 +4,20 @@ use crate::preview2::{
 };
 use anyhow::anyhow;
 use bytes::{Bytes, BytesMut};
+use cap_fs_ext::SystemTimeSpec;
+use std::any::Any;
 use std::io;
 use std::mem;
 use std::sync::Arc;
+use std::time::SystemTime;
+
+mod memory;
+mod os;
+mod overlay;
+mod tar;
+
+pub use self::memory::MemoryFilesystem;
+pub use self::overlay::OverlayFilesystem;
 
 pub type FsResult<T> = Result<T, FsError>;
 
@@ -24,6 +35,227 @@ impl From<io::Error> for FsError {
     }
 }
 
+/// Backends report filesystem errors as `io::Error`, so this allows them to
+/// produce a specific WASI error code where no operating system error exists.
+///
+/// The code is recovered when the `io::Error` is converted back into an
+/// `ErrorCode`.
+impl From<types::ErrorCode> for io::Error {
+    fn from(code: types::ErrorCode) -> io::Error {
+        io::Error::new(io::ErrorKind::Other, code)
+    }
+}
+
+/// A directory in a filesystem which can be preopened for a guest.
+///
+/// This is the extension point for providing guests with filesystem contents
+/// which do not come from the host's operating system. An implementation is
+/// provided for [`cap_std::fs::Dir`], which is what
+/// [`WasiCtxBuilder::preopened_dir`] uses, along with the
+/// [`MemoryFilesystem`] and [`OverlayFilesystem`] backends. Custom backends
+/// are installed with [`WasiCtxBuilder::preopened_filesystem`].
+///
+/// All paths are relative to this directory and implementations must not
+/// allow them to resolve to anything outside of it, either through absolute
+/// paths, `..` components, or symlinks. Permissions configured on the
+/// `WasiCtxBuilder` are checked before any of these methods are called.
+///
+/// Methods are blocking and are always invoked on a blocking thread, so
+/// implementations are free to perform blocking I/O. Errors are reported as
+/// `io::Error`, and a specific WASI error code can be returned through the
+/// `From<ErrorCode> for io::Error` implementation.
+///
+/// [`WasiCtxBuilder::preopened_dir`]: crate::preview2::WasiCtxBuilder::preopened_dir
+/// [`WasiCtxBuilder::preopened_filesystem`]: crate::preview2::WasiCtxBuilder::preopened_filesystem
+pub trait HostFilesystem: Send + Sync + 'static {
+    /// Returns `self` as `Any`, which is used to check whether the
+    /// directories passed to [`HostFilesystem::rename_at`] and
+    /// [`HostFilesystem::link_at`] belong to the same backend.
+    fn as_any(&self) -> &dyn Any;
+
+    /// Opens the file or directory at `path`.
+    fn open_at(&self, path: &str, options: &OpenOptions) -> io::Result<Opened>;
+
+    /// Returns the metadata of this directory.
+    fn metadata(&self) -> io::Result<Metadata>;
+
+    /// Returns the metadata of the object at `path`, following a symlink at
+    /// the final component of `path` if `follow_symlinks` is set.
+    fn metadata_at(&self, path: &str, follow_symlinks: bool) -> io::Result<Metadata>;
+
+    /// Returns the entries of this directory, not including `.` and `..`.
+    fn read_dir(&self) -> io::Result<Vec<io::Result<types::DirectoryEntry>>>;
+
+    /// Creates a directory at `path`.
+    fn create_dir_at(&self, path: &str) -> io::Result<()>;
+
+    /// Removes the empty directory at `path`.
+    fn remove_dir_at(&self, path: &str) -> io::Result<()>;
+
+    /// Removes the file or symlink at `path`.
+    fn unlink_file_at(&self, path: &str) -> io::Result<()>;
+
+    /// Renames `old_path` in this directory to `new_path` in `new_dir`.
+    ///
+    /// Backends should fail with `ErrorCode::CrossDevice` if `new_dir` is
+    /// not part of the same filesystem.
+    fn rename_at(
+        &self,
+        old_path: &str,
+        new_dir: &dyn HostFilesystem,
+        new_path: &str,
+    ) -> io::Result<()>;
+
+    /// Creates a hard link at `new_path` in `new_dir` to the object at
+    /// `old_path` in this directory, without following symlinks.
+    ///
+    /// Backends should fail with `ErrorCode::CrossDevice` if `new_dir` is
+    /// not part of the same filesystem.
+    fn link_at(
+        &self,
+        old_path: &str,
+        new_dir: &dyn HostFilesystem,
+        new_path: &str,
+    ) -> io::Result<()>;
+
+    /// Creates a symlink at `path` which points to `target`.
+    fn symlink_at(&self, target: &str, path: &str) -> io::Result<()>;
+
+    /// Returns the target of the symlink at `path`.
+    fn readlink_at(&self, path: &str) -> io::Result<String>;
+
+    /// Sets the timestamps of this directory, leaving `None` values
+    /// unchanged.
+    fn set_times(
+        &self,
+        atim: Option<SystemTimeSpec>,
+        mtim: Option<SystemTimeSpec>,
+    ) -> io::Result<()>;
+
+    /// Sets the timestamps of the object at `path`, leaving `None` values
+    /// unchanged.
+    fn set_times_at(
+        &self,
+        path: &str,
+        follow_symlinks: bool,
+        atim: Option<SystemTimeSpec>,
+        mtim: Option<SystemTimeSpec>,
+    ) -> io::Result<()>;
+
+    /// Returns the synchronization flags this directory was opened with.
+    /// Read and write flags are tracked separately and need not be included.
+    fn descriptor_flags(&self) -> io::Result<types::DescriptorFlags> {
+        Ok(types::DescriptorFlags::empty())
+    }
+
+    /// Synchronizes the directory's data to storage.
+    fn sync_data(&self) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Synchronizes the directory's data and metadata to storage.
+    fn sync(&self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// A file opened through a [`HostFilesystem`].
+///
+/// Like [`HostFilesystem`], methods are blocking and are always invoked on a
+/// blocking thread.
+pub trait HostFile: Send + Sync + 'static {
+    /// Reads from the file at `offset`, returning the number of bytes read,
+    /// which is zero at the end of the file.
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize>;
+
+    /// Writes to the file at `offset`, extending it if necessary, and returns
+    /// the number of bytes written.
+    fn write_at(&self, buf: &[u8], offset: u64) -> io::Result<usize>;
+
+    /// Writes to the end of the file and returns the number of bytes written.
+    fn append(&self, buf: &[u8]) -> io::Result<usize>;
+
+    /// Truncates or extends the file to `size` bytes.
+    fn set_len(&self, size: u64) -> io::Result<()>;
+
+    /// Returns the metadata of this file.
+    fn metadata(&self) -> io::Result<Metadata>;
+
+    /// Sets the timestamps of this file, leaving `None` values unchanged.
+    fn set_times(
+        &self,
+        atim: Option<SystemTimeSpec>,
+        mtim: Option<SystemTimeSpec>,
+    ) -> io::Result<()>;
+
+    /// Provides the backend with advice about how the file will be accessed.
+    fn advise(&self, offset: u64, len: u64, advice: types::Advice) -> io::Result<()> {
+        let _ = (offset, len, advice);
+        Ok(())
+    }
+
+    /// Returns the synchronization flags this file was opened with. Read and
+    /// write flags are tracked separately and need not be included.
+    fn descriptor_flags(&self) -> io::Result<types::DescriptorFlags> {
+        Ok(types::DescriptorFlags::empty())
+    }
+
+    /// Synchronizes the file's data to storage.
+    fn sync_data(&self) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Synchronizes the file's data and metadata to storage.
+    fn sync(&self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Options for [`HostFilesystem::open_at`].
+///
+/// Permission checks have already been performed when these are passed to a
+/// backend, and unsupported flags such as the synchronization flags have
+/// already been rejected.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct OpenOptions {
+    /// Open the file for reading.
+    pub read: bool,
+    /// Open the file for writing.
+    pub write: bool,
+    /// Create the file if it doesn't exist.
+    pub create: bool,
+    /// Fail if the file already exists. Only set along with `create`.
+    pub exclusive: bool,
+    /// Truncate the file to zero length.
+    pub truncate: bool,
+    /// Fail if the opened object is not a directory.
+    pub directory: bool,
+    /// Follow a symlink at the final component of the path.
+    pub follow_symlinks: bool,
+}
+
+/// The result of [`HostFilesystem::open_at`].
+pub enum Opened {
+    Dir(Arc<dyn HostFilesystem>),
+    File(Arc<dyn HostFile>),
+}
+
+/// Metadata about a file, directory, or symlink in a [`HostFilesystem`].
+#[derive(Clone, Debug)]
+pub struct Metadata {
+    pub type_: types::DescriptorType,
+    pub link_count: u64,
+    pub size: u64,
+    pub accessed: Option<SystemTime>,
+    pub modified: Option<SystemTime>,
+    pub status_changed: Option<SystemTime>,
+    /// Together with `ino`, uniquely identifies an object within a running
+    /// program. These are the basis for `is-same-object` and the metadata
+    /// hashes, which become preview1 inode numbers.
+    pub dev: u64,
+    pub ino: u64,
+}
+
 pub enum Descriptor {
     File(File),
     Dir(Dir),
@@ -76,14 +308,14 @@ bitflags::bitflags! {
 }
 
 pub struct File {
-    /// The operating system File this struct is mediating access to.
+    /// The backend file this struct is mediating access to.
     ///
     /// Wrapped in an Arc because the same underlying file is used for
     /// implementing the stream types. A copy is also needed for
     /// [`spawn_blocking`].
     ///
     /// [`spawn_blocking`]: Self::spawn_blocking
-    pub file: Arc<cap_std::fs::File>,
+    pub file: Arc<dyn HostFile>,
     /// Permissions to enforce on access to the file. These permissions are
     /// specified by a user of the `crate::preview2::WasiCtxBuilder`, and are
     /// enforced prior to any enforced by the underlying operating system.
@@ -96,23 +328,23 @@ pub struct File {
 }
 
 impl File {
-    pub fn new(file: cap_std::fs::File, perms: FilePerms, open_mode: OpenMode) -> Self {
+    pub fn new(file: Arc<dyn HostFile>, perms: FilePerms, open_mode: OpenMode) -> Self {
         Self {
-            file: Arc::new(file),
+            file,
             perms,
             open_mode,
         }
     }
 
     /// Spawn a task on tokio's blocking thread for performing blocking
-    /// syscalls on the underlying [`cap_std::fs::File`].
+    /// operations on the underlying [`HostFile`].
     pub(crate) async fn spawn_blocking<F, R>(&self, body: F) -> R
     where
-        F: FnOnce(&cap_std::fs::File) -> R + Send + 'static,
+        F: FnOnce(&dyn HostFile) -> R + Send + 'static,
         R: Send + 'static,
     {
         let f = self.file.clone();
-        spawn_blocking(move || body(&f)).await
+        spawn_blocking(move || body(&*f)).await
     }
 }
 
@@ -126,13 +358,12 @@ bitflags::bitflags! {
 
 #[derive(Clone)]
 pub struct Dir {
-    /// The operating system file descriptor this struct is mediating access
-    /// to.
+    /// The backend directory this struct is mediating access to.
     ///
     /// Wrapped in an Arc because a copy is needed for [`spawn_blocking`].
     ///
     /// [`spawn_blocking`]: Self::spawn_blocking
-    pub dir: Arc<cap_std::fs::Dir>,
+    pub dir: Arc<dyn HostFilesystem>,
     /// Permissions to enforce on access to this directory. These permissions
     /// are specified by a user of the `crate::preview2::WasiCtxBuilder`, and
     /// are enforced prior to any enforced by the underlying operating system.
@@ -151,13 +382,13 @@ pub struct Dir {
 
 impl Dir {
     pub fn new(
-        dir: cap_std::fs::Dir,
+        dir: Arc<dyn HostFilesystem>,
         perms: DirPerms,
         file_perms: FilePerms,
         open_mode: OpenMode,
     ) -> Self {
         Dir {
-            dir: Arc::new(dir),
+            dir,
             perms,
             file_perms,
             open_mode,
@@ -165,28 +396,27 @@ impl Dir {
     }
 
     /// Spawn a task on tokio's blocking thread for performing blocking
-    /// syscalls on the underlying [`cap_std::fs::Dir`].
+    /// operations on the underlying [`HostFilesystem`].
     pub(crate) async fn spawn_blocking<F, R>(&self, body: F) -> R
     where
-        F: FnOnce(&cap_std::fs::Dir) -> R + Send + 'static,
+        F: FnOnce(&dyn HostFilesystem) -> R + Send + 'static,
         R: Send + 'static,
     {
         let d = self.dir.clone();
-        spawn_blocking(move || body(&d)).await
+        spawn_blocking(move || body(&*d)).await
     }
 }
 
 pub struct FileInputStream {
-    file: Arc<cap_std::fs::File>,
+    file: Arc<dyn HostFile>,
     position: u64,
 }
 impl FileInputStream {
-    pub fn new(file: Arc<cap_std::fs::File>, position: u64) -> Self {
+    pub fn new(file: Arc<dyn HostFile>, position: u64) -> Self {
         Self { file, position }
     }
 
     pub async fn read(&mut self, size: usize) -> Result<Bytes, StreamError> {
-        use system_interface::fs::FileIoExt;
         let f = Arc::clone(&self.file);
         let p = self.position;
         let (r, mut buf) = spawn_blocking(move || {
@@ -223,7 +453,7 @@ pub(crate) enum FileOutputMode {
 }
 
 pub(crate) struct FileOutputStream {
-    file: Arc<cap_std::fs::File>,
+    file: Arc<dyn HostFile>,
     mode: FileOutputMode,
     state: OutputState,
 }
@@ -239,14 +469,14 @@ enum OutputState {
 }
 
 impl FileOutputStream {
-    pub fn write_at(file: Arc<cap_std::fs::File>, position: u64) -> Self {
+    pub fn write_at(file: Arc<dyn HostFile>, position: u64) -> Self {
         Self {
             file,
             mode: FileOutputMode::Position(position),
             state: OutputState::Ready,
         }
     }
-    pub fn append(file: Arc<cap_std::fs::File>) -> Self {
+    pub fn append(file: Arc<dyn HostFile>) -> Self {
         Self {
             file,
             mode: FileOutputMode::Append,
@@ -260,7 +490,6 @@ const FILE_WRITE_CAPACITY: usize = 1024 * 1024;
 
 impl HostOutputStream for FileOutputStream {
     fn write(&mut self, buf: Bytes) -> Result<(), StreamError> {
-        use system_interface::fs::FileIoExt;
         match self.state {
             OutputState::Ready => {}
             OutputState::Closed => return Err(StreamError::Closed),
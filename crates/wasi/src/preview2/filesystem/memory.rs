@@ -0,0 +1,1461 @@
+//! A [`HostFilesystem`] backend which keeps its contents in memory.
+
+use super::{tar, HostFile, HostFilesystem, Metadata, OpenOptions, Opened};
+use crate::preview2::bindings::filesystem::types::{self, DescriptorType, ErrorCode};
+use cap_fs_ext::SystemTimeSpec;
+use std::any::Any;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::io;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering::Relaxed};
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock, RwLock};
+use std::time::{Duration, SystemTime};
+
+/// The number of symlinks which may be followed while resolving a single
+/// path before failing with `error-code::loop`, matching Linux.
+const MAX_SYMLINK_EXPANSIONS: u32 = 40;
+
+/// Device numbers handed out to filesystems so that objects in distinct
+/// filesystems never compare as the same object. Zero is left for real
+/// devices which report it.
+static NEXT_DEV: AtomicU64 = AtomicU64::new(1 << 63);
+
+/// The number of bytes which guests may store in a filesystem's files unless
+/// configured otherwise with [`MemoryFilesystem::quota`].
+const DEFAULT_QUOTA: u64 = 1 << 30;
+
+/// A filesystem whose files, directories and symlinks live in memory.
+///
+/// A `MemoryFilesystem` is a handle to a directory within the filesystem, and
+/// is preopened with
+/// [`WasiCtxBuilder::preopened_filesystem`](crate::preview2::WasiCtxBuilder::preopened_filesystem).
+/// Cloning it produces another handle to the same directory, so contents can
+/// be inspected by the embedder after a guest has run.
+///
+/// Contents can be populated with [`MemoryFilesystem::insert_file`] and the
+/// related methods, or loaded from a tar archive with
+/// [`MemoryFilesystem::from_tar`]. [`MemoryFilesystem::read_only`] prevents
+/// guests from modifying the filesystem, and [`MemoryFilesystem::quota`]
+/// limits how much they can store in it. A filesystem created with
+/// [`MemoryFilesystem::overlay`] is a writable layer over another filesystem.
+#[derive(Clone)]
+pub struct MemoryFilesystem {
+    dir: Arc<DirNode>,
+    shared: Arc<Shared>,
+}
+
+/// State shared by all handles into the same filesystem.
+struct Shared {
+    dev: u64,
+    next_ino: AtomicU64,
+    read_only: AtomicBool,
+    quota: Arc<Quota>,
+    /// Held while modifying directory entries. Directory locks are only
+    /// nested while this is held, and only one directory lock is taken at a
+    /// time otherwise, so locking can't deadlock.
+    namespace: Mutex<()>,
+    /// Files copied from a lower layer with more than one link, keyed by
+    /// their device and inode numbers there, so that they remain linked.
+    lower_links: Mutex<HashMap<(u64, u64), Node>>,
+}
+
+#[derive(Clone)]
+enum Node {
+    Dir(Arc<DirNode>),
+    File(Arc<FileNode>),
+    Symlink(Arc<SymlinkNode>),
+}
+
+struct DirNode {
+    ino: u64,
+    state: Mutex<DirState>,
+}
+
+struct DirState {
+    entries: BTreeMap<String, Node>,
+    times: Times,
+    /// The directory in a lower layer whose entries haven't been copied into
+    /// `entries` yet, for filesystems created with
+    /// [`MemoryFilesystem::overlay`]. See [`DirNode::lock`].
+    lower: Option<Lower>,
+}
+
+struct Lower {
+    dir: Arc<dyn HostFilesystem>,
+    shared: Arc<Shared>,
+}
+
+struct FileNode {
+    ino: u64,
+    state: RwLock<FileState>,
+    quota: Arc<Quota>,
+}
+
+struct FileState {
+    data: Vec<u8>,
+    /// The file in a lower layer whose contents haven't been copied into
+    /// `data` yet, for filesystems created with
+    /// [`MemoryFilesystem::overlay`]. See [`FileNode::copy_up`].
+    lower: Option<LowerFile>,
+    times: Times,
+    nlink: u64,
+}
+
+struct LowerFile {
+    dir: Arc<dyn HostFilesystem>,
+    name: String,
+    size: u64,
+    /// The file opened for reading, once it has been read from.
+    file: OnceLock<Arc<dyn HostFile>>,
+}
+
+struct SymlinkNode {
+    ino: u64,
+    target: String,
+    times: Mutex<Times>,
+}
+
+/// The number of bytes stored in a filesystem's files, which are shared with
+/// each file so that they can be released once it's dropped.
+struct Quota {
+    used: AtomicU64,
+    limit: AtomicU64,
+}
+
+#[derive(Copy, Clone)]
+struct Times {
+    accessed: SystemTime,
+    modified: SystemTime,
+    changed: SystemTime,
+}
+
+/// The result of resolving a path within a [`MemoryFilesystem`].
+struct Resolved {
+    /// The directories walked through, from the directory the path is
+    /// relative to, up to the directory containing the final component.
+    stack: Vec<Arc<DirNode>>,
+    /// The final component of the path, or `None` if the path refers to the
+    /// last directory in `stack` itself, as with `.` or `a/..`.
+    name: Option<String>,
+    /// Whether the path ended with a slash, in which case it must refer to a
+    /// directory.
+    trailing_slash: bool,
+}
+
+impl MemoryFilesystem {
+    /// Creates a new filesystem containing an empty root directory.
+    pub fn new() -> MemoryFilesystem {
+        let shared = Arc::new(Shared {
+            dev: NEXT_DEV.fetch_add(1, Relaxed),
+            next_ino: AtomicU64::new(1),
+            read_only: AtomicBool::new(false),
+            quota: Arc::new(Quota {
+                used: AtomicU64::new(0),
+                limit: AtomicU64::new(DEFAULT_QUOTA),
+            }),
+            namespace: Mutex::new(()),
+            lower_links: Mutex::new(HashMap::new()),
+        });
+        MemoryFilesystem {
+            dir: shared.new_dir(Times::now()),
+            shared,
+        }
+    }
+
+    /// Creates a new filesystem layered over the directory `lower`, which is
+    /// never modified.
+    ///
+    /// The filesystem initially has the same contents as `lower`, and
+    /// modifications are only made in memory, so `lower` can be shared by
+    /// several filesystems. A directory's entries are copied from `lower` the
+    /// first time the directory is accessed, and changes made to `lower`
+    /// after that aren't visible. Files are read from `lower` until they're
+    /// opened for writing, at which point their contents are copied into
+    /// memory. Several lower layers can be combined with an
+    /// [`OverlayFilesystem`](crate::preview2::OverlayFilesystem).
+    pub fn overlay(lower: Arc<dyn HostFilesystem>) -> io::Result<MemoryFilesystem> {
+        let fs = MemoryFilesystem::new();
+        let times = Times::from_metadata(&lower.metadata()?);
+        let mut state = fs.dir.state.lock().unwrap();
+        state.times = times;
+        state.lower = Some(Lower {
+            dir: lower,
+            shared: fs.shared.clone(),
+        });
+        drop(state);
+        Ok(fs)
+    }
+
+    /// Creates a new filesystem with the contents of the tar archive
+    /// `archive`.
+    ///
+    /// Regular files, directories, symlinks and hard links are supported,
+    /// including the GNU and PAX extensions for long paths. Other kinds of
+    /// entries, such as devices, are skipped. Ownership and permissions are
+    /// not recorded.
+    pub fn from_tar(archive: &[u8]) -> io::Result<MemoryFilesystem> {
+        let fs = MemoryFilesystem::new();
+        for entry in tar::entries(archive)? {
+            let time = SystemTime::UNIX_EPOCH + Duration::from_secs(entry.mtime);
+            let times = Times {
+                accessed: time,
+                modified: time,
+                changed: time,
+            };
+            let node = match entry.kind {
+                tar::EntryKind::File(data) => fs.shared.new_file(data.to_vec(), times),
+                tar::EntryKind::Dir => {
+                    fs.insert_dir(&entry.path)?;
+                    if let Node::Dir(dir) = fs.lookup_inserted(&entry.path)? {
+                        dir.state.lock().unwrap().times = times;
+                    }
+                    continue;
+                }
+                tar::EntryKind::Symlink(target) => fs.shared.new_symlink(target, times),
+                tar::EntryKind::HardLink(target) => {
+                    let node = fs.lookup_inserted(&target)?;
+                    match &node {
+                        Node::File(file) => file.state.write().unwrap().nlink += 1,
+                        Node::Symlink(_) => {}
+                        Node::Dir(_) => return Err(ErrorCode::NotPermitted.into()),
+                    }
+                    node
+                }
+            };
+            fs.insert(&entry.path, node)?;
+        }
+        Ok(fs)
+    }
+
+    /// Prevents guests from modifying this filesystem, through this or any
+    /// other handle to it.
+    ///
+    /// Attempts to do so fail with `error-code::read-only`. The embedder can
+    /// still modify the filesystem with methods such as
+    /// [`MemoryFilesystem::insert_file`].
+    pub fn read_only(self) -> MemoryFilesystem {
+        self.shared.read_only.store(true, Relaxed);
+        self
+    }
+
+    /// Limits the total size of the files which guests can store in this
+    /// filesystem to `bytes`, which defaults to 1 GiB.
+    ///
+    /// Writes and truncations which would grow files beyond the limit fail
+    /// with `error-code::insufficient-space`, as does opening a file from a
+    /// lower layer for writing if its contents don't fit. Contents added by
+    /// the embedder or loaded from a tar archive count towards the limit but
+    /// are never refused.
+    pub fn quota(self, bytes: u64) -> MemoryFilesystem {
+        self.shared.quota.limit.store(bytes, Relaxed);
+        self
+    }
+
+    /// Creates a file at `path` containing `contents`, replacing any file or
+    /// symlink which is already there.
+    ///
+    /// Missing parent directories are created. Symlinks are not followed.
+    pub fn insert_file(&self, path: &str, contents: impl Into<Vec<u8>>) -> io::Result<()> {
+        let node = self.shared.new_file(contents.into(), Times::now());
+        self.insert(path, node)
+    }
+
+    /// Creates a directory at `path` along with any missing parents, like
+    /// `mkdir -p`.
+    pub fn insert_dir(&self, path: &str) -> io::Result<()> {
+        let _namespace = self.shared.namespace.lock().unwrap();
+        self.insert_parents(path, true)?;
+        Ok(())
+    }
+
+    /// Creates a symlink at `path` pointing to `target`, replacing any file
+    /// or symlink which is already there.
+    ///
+    /// Missing parent directories are created. Symlinks are not followed.
+    pub fn insert_symlink(&self, path: &str, target: &str) -> io::Result<()> {
+        let node = self.shared.new_symlink(target.to_string(), Times::now());
+        self.insert(path, node)
+    }
+
+    fn insert(&self, path: &str, node: Node) -> io::Result<()> {
+        let _namespace = self.shared.namespace.lock().unwrap();
+        let (parent, name) = self.insert_parents(path, false)?;
+        let name = name.ok_or(ErrorCode::IsDirectory)?;
+        let mut parent = parent.lock()?;
+        if let Some(Node::Dir(_)) = parent.entries.get(&name) {
+            return Err(ErrorCode::IsDirectory.into());
+        }
+        if let Some(replaced) = parent.entries.insert(name, node) {
+            replaced.unlinked();
+        }
+        Ok(())
+    }
+
+    /// Walks `path` without following symlinks, creating directories as
+    /// needed. Returns the directory containing the final component and the
+    /// component itself, which is also created as a directory if
+    /// `create_last` is set.
+    fn insert_parents(
+        &self,
+        path: &str,
+        create_last: bool,
+    ) -> io::Result<(Arc<DirNode>, Option<String>)> {
+        let mut components = split(path)?;
+        let mut dir = self.dir.clone();
+        while let Some(component) = components.pop_front() {
+            match component.as_str() {
+                "." => continue,
+                ".." => return Err(ErrorCode::Invalid.into()),
+                _ => {}
+            }
+            if components.is_empty() && !create_last {
+                return Ok((dir, Some(component)));
+            }
+            let next = {
+                let mut state = dir.lock()?;
+                match state.entries.get(&component) {
+                    Some(Node::Dir(next)) => next.clone(),
+                    Some(_) => return Err(ErrorCode::NotDirectory.into()),
+                    None => {
+                        let next = self.shared.new_dir(Times::now());
+                        state.entries.insert(component, Node::Dir(next.clone()));
+                        next
+                    }
+                }
+            };
+            dir = next;
+        }
+        Ok((dir, None))
+    }
+
+    /// Looks up `path` as created by the `insert_*` methods.
+    fn lookup_inserted(&self, path: &str) -> io::Result<Node> {
+        let mut node = Node::Dir(self.dir.clone());
+        for component in split(path)? {
+            if component == "." {
+                continue;
+            }
+            node = match node {
+                Node::Dir(dir) => dir.lookup(&component)?.ok_or(ErrorCode::NoEntry)?,
+                _ => return Err(ErrorCode::NotDirectory.into()),
+            };
+        }
+        Ok(node)
+    }
+
+    /// Returns a handle to `dir` in the same filesystem.
+    fn handle(&self, dir: Arc<DirNode>) -> MemoryFilesystem {
+        MemoryFilesystem {
+            dir,
+            shared: self.shared.clone(),
+        }
+    }
+
+    /// Returns `dir` as a handle into the same filesystem as `self`.
+    fn same_filesystem<'a>(&self, dir: &'a dyn HostFilesystem) -> io::Result<&'a MemoryFilesystem> {
+        match dir.as_any().downcast_ref::<MemoryFilesystem>() {
+            Some(dir) if Arc::ptr_eq(&dir.shared, &self.shared) => Ok(dir),
+            _ => Err(ErrorCode::CrossDevice.into()),
+        }
+    }
+
+    /// Resolves `path` relative to this directory.
+    ///
+    /// Symlinks in intermediate components are always followed, and a
+    /// symlink in the final component is followed if `follow_symlinks` is set
+    /// or the path ends with a slash. Paths may not escape this directory.
+    fn resolve(&self, path: &str, follow_symlinks: bool) -> io::Result<Resolved> {
+        let trailing_slash = path.ends_with('/');
+        let follow_symlinks = follow_symlinks || trailing_slash;
+        let mut components = split(path)?;
+        let mut stack = vec![self.dir.clone()];
+        let mut expansions = 0;
+
+        while let Some(component) = components.pop_front() {
+            let last = components.is_empty();
+            match component.as_str() {
+                "." => {}
+                ".." => {
+                    if stack.len() == 1 {
+                        return Err(escape());
+                    }
+                    stack.pop();
+                }
+                _ => match stack.last().unwrap().lookup(&component)? {
+                    Some(Node::Symlink(link)) if !last || follow_symlinks => {
+                        expansions += 1;
+                        if expansions > MAX_SYMLINK_EXPANSIONS {
+                            return Err(ErrorCode::Loop.into());
+                        }
+                        let target = split(&link.target)?;
+                        if target.is_empty() {
+                            return Err(ErrorCode::NoEntry.into());
+                        }
+                        for component in target.into_iter().rev() {
+                            components.push_front(component);
+                        }
+                    }
+                    Some(Node::Dir(dir)) if !last => stack.push(dir),
+                    Some(_) if !last => return Err(ErrorCode::NotDirectory.into()),
+                    None if !last => return Err(ErrorCode::NoEntry.into()),
+                    _ => {
+                        return Ok(Resolved {
+                            stack,
+                            name: Some(component),
+                            trailing_slash,
+                        })
+                    }
+                },
+            }
+        }
+
+        Ok(Resolved {
+            stack,
+            name: None,
+            trailing_slash,
+        })
+    }
+
+    /// Resolves `path` to the object it refers to.
+    fn resolve_node(&self, path: &str, follow_symlinks: bool) -> io::Result<Node> {
+        let resolved = self.resolve(path, follow_symlinks)?;
+        resolved.node()?.ok_or_else(|| ErrorCode::NoEntry.into())
+    }
+}
+
+impl Default for MemoryFilesystem {
+    fn default() -> MemoryFilesystem {
+        MemoryFilesystem::new()
+    }
+}
+
+impl HostFilesystem for MemoryFilesystem {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn open_at(&self, path: &str, options: &OpenOptions) -> io::Result<Opened> {
+        let resolved = self.resolve(path, options.follow_symlinks)?;
+        let file = match resolved.node()? {
+            Some(_) if options.create && options.exclusive => {
+                return Err(ErrorCode::Exist.into());
+            }
+            Some(Node::Dir(dir)) => {
+                if options.write || options.truncate {
+                    return Err(ErrorCode::IsDirectory.into());
+                }
+                return Ok(Opened::Dir(Arc::new(self.handle(dir))));
+            }
+            Some(Node::Symlink(_)) if options.directory => {
+                return Err(ErrorCode::NotDirectory.into());
+            }
+            // Opening a symlink without following it fails, as with
+            // `O_NOFOLLOW`.
+            Some(Node::Symlink(_)) => return Err(ErrorCode::Loop.into()),
+            Some(Node::File(_)) if options.directory => {
+                return Err(ErrorCode::NotDirectory.into());
+            }
+            Some(Node::File(file)) => {
+                if options.truncate {
+                    self.shared.check_writable()?;
+                    let mut state = file.state.write().unwrap();
+                    // Truncated contents of a lower layer needn't be copied.
+                    state.lower = None;
+                    file.resize(&mut state, 0)?;
+                    state.times.modify();
+                } else if options.write {
+                    self.shared.check_writable()?;
+                    file.copy_up(&mut file.state.write().unwrap())?;
+                }
+                file
+            }
+            None => {
+                if !options.create {
+                    return Err(ErrorCode::NoEntry.into());
+                }
+                if resolved.trailing_slash {
+                    return Err(ErrorCode::IsDirectory.into());
+                }
+                self.shared.check_writable()?;
+                let _namespace = self.shared.namespace.lock().unwrap();
+                let file = match self.shared.new_file(Vec::new(), Times::now()) {
+                    Node::File(file) => file,
+                    _ => unreachable!(),
+                };
+                let name = resolved.name.clone().unwrap();
+                let mut parent = resolved.parent().lock()?;
+                if parent.entries.contains_key(&name) {
+                    return Err(ErrorCode::Exist.into());
+                }
+                parent.entries.insert(name, Node::File(file.clone()));
+                parent.times.modify();
+                file
+            }
+        };
+        if options.write {
+            self.shared.check_writable()?;
+        }
+        Ok(Opened::File(Arc::new(MemoryFile {
+            file,
+            shared: self.shared.clone(),
+            read: options.read,
+            write: options.write,
+        })))
+    }
+
+    fn metadata(&self) -> io::Result<Metadata> {
+        Ok(Node::Dir(self.dir.clone()).metadata(&self.shared))
+    }
+
+    fn metadata_at(&self, path: &str, follow_symlinks: bool) -> io::Result<Metadata> {
+        Ok(self
+            .resolve_node(path, follow_symlinks)?
+            .metadata(&self.shared))
+    }
+
+    fn read_dir(&self) -> io::Result<Vec<io::Result<types::DirectoryEntry>>> {
+        let state = self.dir.lock()?;
+        Ok(state
+            .entries
+            .iter()
+            .map(|(name, node)| {
+                Ok(types::DirectoryEntry {
+                    type_: node.descriptor_type(),
+                    name: name.clone(),
+                })
+            })
+            .collect())
+    }
+
+    fn create_dir_at(&self, path: &str) -> io::Result<()> {
+        self.shared.check_writable()?;
+        let _namespace = self.shared.namespace.lock().unwrap();
+        let resolved = self.resolve(path, false)?;
+        let name = resolved.name.ok_or(ErrorCode::Exist)?;
+        let mut parent = resolved.stack.last().unwrap().lock()?;
+        if parent.entries.contains_key(&name) {
+            return Err(ErrorCode::Exist.into());
+        }
+        let dir = self.shared.new_dir(Times::now());
+        parent.entries.insert(name, Node::Dir(dir));
+        parent.times.modify();
+        Ok(())
+    }
+
+    fn remove_dir_at(&self, path: &str) -> io::Result<()> {
+        self.shared.check_writable()?;
+        let _namespace = self.shared.namespace.lock().unwrap();
+        let resolved = self.resolve(path, false)?;
+        let name = resolved.name.ok_or(ErrorCode::Invalid)?;
+        let mut parent = resolved.stack.last().unwrap().lock()?;
+        match parent.entries.get(&name) {
+            Some(Node::Dir(dir)) => {
+                if !dir.lock()?.entries.is_empty() {
+                    return Err(ErrorCode::NotEmpty.into());
+                }
+            }
+            Some(_) => return Err(ErrorCode::NotDirectory.into()),
+            None => return Err(ErrorCode::NoEntry.into()),
+        }
+        parent.entries.remove(&name);
+        parent.times.modify();
+        Ok(())
+    }
+
+    fn unlink_file_at(&self, path: &str) -> io::Result<()> {
+        self.shared.check_writable()?;
+        let _namespace = self.shared.namespace.lock().unwrap();
+        let resolved = self.resolve(path, false)?;
+        let name = resolved.name.ok_or(ErrorCode::IsDirectory)?;
+        let mut parent = resolved.stack.last().unwrap().lock()?;
+        match parent.entries.get(&name) {
+            Some(Node::Dir(_)) => return Err(ErrorCode::IsDirectory.into()),
+            Some(_) if resolved.trailing_slash => return Err(ErrorCode::NotDirectory.into()),
+            Some(node) => node.unlinked(),
+            None => return Err(ErrorCode::NoEntry.into()),
+        }
+        parent.entries.remove(&name);
+        parent.times.modify();
+        Ok(())
+    }
+
+    fn rename_at(
+        &self,
+        old_path: &str,
+        new_dir: &dyn HostFilesystem,
+        new_path: &str,
+    ) -> io::Result<()> {
+        self.shared.check_writable()?;
+        let new_dir = self.same_filesystem(new_dir)?;
+        let _namespace = self.shared.namespace.lock().unwrap();
+
+        let old = self.resolve(old_path, false)?;
+        let old_name = old.name.clone().ok_or(ErrorCode::Busy)?;
+        let node = old.node()?.ok_or(ErrorCode::NoEntry)?;
+        let is_dir = matches!(node, Node::Dir(_));
+
+        let new = new_dir.resolve(new_path, false)?;
+        let new_name = new.name.clone().ok_or(ErrorCode::Busy)?;
+        if new.trailing_slash && !is_dir {
+            return Err(ErrorCode::NotDirectory.into());
+        }
+        // A directory can't be moved inside of itself.
+        if let Node::Dir(dir) = &node {
+            if new.stack.iter().any(|d| Arc::ptr_eq(d, dir)) {
+                return Err(ErrorCode::Invalid.into());
+            }
+        }
+        match (&node, new.parent().lookup(&new_name)?) {
+            (_, Some(existing)) if existing.ptr_eq(&node) => return Ok(()),
+            (Node::Dir(_), Some(Node::Dir(existing))) => {
+                if !existing.lock()?.entries.is_empty() {
+                    return Err(ErrorCode::NotEmpty.into());
+                }
+            }
+            (Node::Dir(_), Some(_)) => return Err(ErrorCode::NotDirectory.into()),
+            (_, Some(Node::Dir(_))) => return Err(ErrorCode::IsDirectory.into()),
+            (_, Some(existing)) => existing.unlinked(),
+            (_, None) => {}
+        }
+
+        {
+            let mut parent = old.parent().lock()?;
+            parent.entries.remove(&old_name);
+            parent.times.modify();
+        }
+        let mut parent = new.parent().lock()?;
+        parent.entries.insert(new_name, node);
+        parent.times.modify();
+        Ok(())
+    }
+
+    fn link_at(
+        &self,
+        old_path: &str,
+        new_dir: &dyn HostFilesystem,
+        new_path: &str,
+    ) -> io::Result<()> {
+        self.shared.check_writable()?;
+        let new_dir = self.same_filesystem(new_dir)?;
+        let _namespace = self.shared.namespace.lock().unwrap();
+
+        let old = self.resolve(old_path, false)?;
+        let node = match old.node()?.ok_or(ErrorCode::NoEntry)? {
+            Node::Dir(_) => return Err(ErrorCode::NotPermitted.into()),
+            node => node,
+        };
+
+        let new = new_dir.resolve(new_path, false)?;
+        let new_name = new.name.clone().ok_or(ErrorCode::Exist)?;
+        let mut parent = new.parent().lock()?;
+        if parent.entries.contains_key(&new_name) {
+            return Err(ErrorCode::Exist.into());
+        }
+        if new.trailing_slash {
+            return Err(ErrorCode::NoEntry.into());
+        }
+        if let Node::File(file) = &node {
+            let mut state = file.state.write().unwrap();
+            state.nlink += 1;
+            state.times.changed = SystemTime::now();
+        }
+        parent.entries.insert(new_name, node);
+        parent.times.modify();
+        Ok(())
+    }
+
+    fn symlink_at(&self, target: &str, path: &str) -> io::Result<()> {
+        self.shared.check_writable()?;
+        let _namespace = self.shared.namespace.lock().unwrap();
+        let resolved = self.resolve(path, false)?;
+        let name = resolved.name.ok_or(ErrorCode::Exist)?;
+        let mut parent = resolved.stack.last().unwrap().lock()?;
+        match parent.entries.get(&name) {
+            Some(Node::Dir(_)) => return Err(ErrorCode::Exist.into()),
+            Some(_) if resolved.trailing_slash => return Err(ErrorCode::NotDirectory.into()),
+            Some(_) => return Err(ErrorCode::Exist.into()),
+            None if resolved.trailing_slash => return Err(ErrorCode::NoEntry.into()),
+            None => {}
+        }
+        let link = self.shared.new_symlink(target.to_string(), Times::now());
+        parent.entries.insert(name, link);
+        parent.times.modify();
+        Ok(())
+    }
+
+    fn readlink_at(&self, path: &str) -> io::Result<String> {
+        match self.resolve_node(path, false)? {
+            Node::Symlink(link) => Ok(link.target.clone()),
+            _ => Err(ErrorCode::Invalid.into()),
+        }
+    }
+
+    fn set_times(
+        &self,
+        atim: Option<SystemTimeSpec>,
+        mtim: Option<SystemTimeSpec>,
+    ) -> io::Result<()> {
+        self.shared.check_writable()?;
+        Node::Dir(self.dir.clone()).set_times(atim, mtim);
+        Ok(())
+    }
+
+    fn set_times_at(
+        &self,
+        path: &str,
+        follow_symlinks: bool,
+        atim: Option<SystemTimeSpec>,
+        mtim: Option<SystemTimeSpec>,
+    ) -> io::Result<()> {
+        self.shared.check_writable()?;
+        self.resolve_node(path, follow_symlinks)?
+            .set_times(atim, mtim);
+        Ok(())
+    }
+}
+
+/// A file opened from a [`MemoryFilesystem`].
+struct MemoryFile {
+    file: Arc<FileNode>,
+    shared: Arc<Shared>,
+    read: bool,
+    write: bool,
+}
+
+impl MemoryFile {
+    /// Fails like an operating system would for a file which wasn't opened
+    /// for writing, or if the filesystem is read-only.
+    fn check_writable(&self) -> io::Result<()> {
+        if !self.write {
+            return Err(ErrorCode::BadDescriptor.into());
+        }
+        self.shared.check_writable()
+    }
+}
+
+impl HostFile for MemoryFile {
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        if !self.read {
+            return Err(ErrorCode::BadDescriptor.into());
+        }
+        let state = self.file.state.read().unwrap();
+        if let Some(lower) = &state.lower {
+            let n = usize::try_from(lower.size.saturating_sub(offset))
+                .unwrap_or(usize::MAX)
+                .min(buf.len());
+            return lower.file()?.read_at(&mut buf[..n], offset);
+        }
+        let start = usize::try_from(offset)
+            .unwrap_or(usize::MAX)
+            .min(state.data.len());
+        let n = buf.len().min(state.data.len() - start);
+        buf[..n].copy_from_slice(&state.data[start..][..n]);
+        Ok(n)
+    }
+
+    fn write_at(&self, buf: &[u8], offset: u64) -> io::Result<usize> {
+        self.check_writable()?;
+        let start = usize::try_from(offset).map_err(|_| ErrorCode::FileTooLarge)?;
+        let end = start
+            .checked_add(buf.len())
+            .ok_or(ErrorCode::FileTooLarge)?;
+        let mut state = self.file.state.write().unwrap();
+        self.file.copy_up(&mut state)?;
+        if state.data.len() < end {
+            self.file.resize(&mut state, end)?;
+        }
+        state.data[start..end].copy_from_slice(buf);
+        state.times.modify();
+        Ok(buf.len())
+    }
+
+    fn append(&self, buf: &[u8]) -> io::Result<usize> {
+        self.check_writable()?;
+        let mut state = self.file.state.write().unwrap();
+        self.file.copy_up(&mut state)?;
+        let start = state.data.len();
+        let end = start
+            .checked_add(buf.len())
+            .ok_or(ErrorCode::FileTooLarge)?;
+        self.file.resize(&mut state, end)?;
+        state.data[start..].copy_from_slice(buf);
+        state.times.modify();
+        Ok(buf.len())
+    }
+
+    fn set_len(&self, size: u64) -> io::Result<()> {
+        self.check_writable()?;
+        let size = usize::try_from(size).map_err(|_| ErrorCode::FileTooLarge)?;
+        let mut state = self.file.state.write().unwrap();
+        self.file.copy_up(&mut state)?;
+        self.file.resize(&mut state, size)?;
+        state.times.modify();
+        Ok(())
+    }
+
+    fn metadata(&self) -> io::Result<Metadata> {
+        Ok(Node::File(self.file.clone()).metadata(&self.shared))
+    }
+
+    fn set_times(
+        &self,
+        atim: Option<SystemTimeSpec>,
+        mtim: Option<SystemTimeSpec>,
+    ) -> io::Result<()> {
+        self.shared.check_writable()?;
+        Node::File(self.file.clone()).set_times(atim, mtim);
+        Ok(())
+    }
+}
+
+impl Shared {
+    fn ino(&self) -> u64 {
+        self.next_ino.fetch_add(1, Relaxed)
+    }
+
+    fn check_writable(&self) -> io::Result<()> {
+        if self.read_only.load(Relaxed) {
+            Err(ErrorCode::ReadOnly.into())
+        } else {
+            Ok(())
+        }
+    }
+
+    fn new_dir(&self, times: Times) -> Arc<DirNode> {
+        Arc::new(DirNode {
+            ino: self.ino(),
+            state: Mutex::new(DirState {
+                entries: BTreeMap::new(),
+                times,
+                lower: None,
+            }),
+        })
+    }
+
+    fn new_file(&self, data: Vec<u8>, times: Times) -> Node {
+        self.quota.used.fetch_add(data.len() as u64, Relaxed);
+        Node::File(Arc::new(FileNode {
+            ino: self.ino(),
+            state: RwLock::new(FileState {
+                data,
+                lower: None,
+                times,
+                nlink: 1,
+            }),
+            quota: self.quota.clone(),
+        }))
+    }
+
+    fn new_symlink(&self, target: String, times: Times) -> Node {
+        Node::Symlink(Arc::new(SymlinkNode {
+            ino: self.ino(),
+            target,
+            times: Mutex::new(times),
+        }))
+    }
+}
+
+impl DirNode {
+    /// Locks this directory's entries.
+    ///
+    /// If the directory was copied from a lower layer, its entries are
+    /// copied from there first. Subdirectories and the contents of files are
+    /// copied lazily in turn, while symlinks are copied in full.
+    fn lock(&self) -> io::Result<MutexGuard<'_, DirState>> {
+        let mut state = self.state.lock().unwrap();
+        if let Some(lower) = &state.lower {
+            let entries = lower.copy_entries()?;
+            state.entries = entries;
+            state.lower = None;
+        }
+        Ok(state)
+    }
+
+    fn lookup(&self, name: &str) -> io::Result<Option<Node>> {
+        Ok(self.lock()?.entries.get(name).cloned())
+    }
+}
+
+impl FileNode {
+    /// Copies the contents of the file from a lower layer into memory, if it
+    /// hasn't been already, so that it can be modified.
+    ///
+    /// This fails without copying anything if the contents would exceed the
+    /// filesystem's quota.
+    fn copy_up(&self, state: &mut FileState) -> io::Result<()> {
+        let lower = match &state.lower {
+            Some(lower) => lower,
+            None => return Ok(()),
+        };
+        let size = usize::try_from(lower.size).map_err(|_| ErrorCode::FileTooLarge)?;
+        self.quota.reserve(lower.size)?;
+        let read = || -> io::Result<Vec<u8>> {
+            let mut data = Vec::new();
+            if data.try_reserve_exact(size).is_err() {
+                return Err(ErrorCode::InsufficientSpace.into());
+            }
+            data.resize(size, 0);
+            let file = lower.file()?;
+            let mut len = 0;
+            while len < size {
+                match file.read_at(&mut data[len..], len as u64)? {
+                    0 => break,
+                    n => len += n,
+                }
+            }
+            data.truncate(len);
+            Ok(data)
+        };
+        match read() {
+            Ok(data) => {
+                self.quota.release((size - data.len()) as u64);
+                state.data = data;
+                state.lower = None;
+                Ok(())
+            }
+            Err(e) => {
+                self.quota.release(lower.size);
+                Err(e)
+            }
+        }
+    }
+
+    /// Grows or shrinks the file's contents to `len` bytes, failing without
+    /// changing them if that would exceed the filesystem's quota or the
+    /// memory can't be allocated.
+    fn resize(&self, state: &mut FileState, len: usize) -> io::Result<()> {
+        let current = state.data.len();
+        if len <= current {
+            state.data.truncate(len);
+            state.data.shrink_to_fit();
+            self.quota.release((current - len) as u64);
+            return Ok(());
+        }
+        let additional = len - current;
+        self.quota.reserve(additional as u64)?;
+        if state.data.try_reserve_exact(additional).is_err() {
+            self.quota.release(additional as u64);
+            return Err(ErrorCode::InsufficientSpace.into());
+        }
+        state.data.resize(len, 0);
+        Ok(())
+    }
+}
+
+impl Drop for FileNode {
+    fn drop(&mut self) {
+        let state = self.state.get_mut().unwrap_or_else(|e| e.into_inner());
+        let len = state.data.len();
+        self.quota.release(len as u64);
+    }
+}
+
+impl Quota {
+    fn reserve(&self, bytes: u64) -> io::Result<()> {
+        let limit = self.limit.load(Relaxed);
+        self.used
+            .fetch_update(Relaxed, Relaxed, |used| {
+                used.checked_add(bytes).filter(|&used| used <= limit)
+            })
+            .map_err(|_| ErrorCode::InsufficientSpace)?;
+        Ok(())
+    }
+
+    fn release(&self, bytes: u64) {
+        self.used.fetch_sub(bytes, Relaxed);
+    }
+}
+
+impl Lower {
+    /// Copies the entries of this lower directory into new nodes. Objects
+    /// other than files, directories and symlinks are skipped.
+    fn copy_entries(&self) -> io::Result<BTreeMap<String, Node>> {
+        let mut entries = BTreeMap::new();
+        for entry in self.dir.read_dir()? {
+            let name = entry?.name;
+            let metadata = self.dir.metadata_at(&name, false)?;
+            let times = Times::from_metadata(&metadata);
+            let node = match metadata.type_ {
+                DescriptorType::Directory => {
+                    let options = OpenOptions {
+                        directory: true,
+                        ..OpenOptions::default()
+                    };
+                    let dir = match self.dir.open_at(&name, &options)? {
+                        Opened::Dir(dir) => dir,
+                        Opened::File(_) => return Err(ErrorCode::NotDirectory.into()),
+                    };
+                    let node = self.shared.new_dir(times);
+                    node.state.lock().unwrap().lower = Some(Lower {
+                        dir,
+                        shared: self.shared.clone(),
+                    });
+                    Node::Dir(node)
+                }
+                DescriptorType::RegularFile => self.copy_file(&name, &metadata, times)?,
+                DescriptorType::SymbolicLink => {
+                    let target = self.dir.readlink_at(&name)?;
+                    self.shared.new_symlink(target, times)
+                }
+                _ => continue,
+            };
+            entries.insert(name, node);
+        }
+        Ok(entries)
+    }
+
+    fn copy_file(&self, name: &str, metadata: &Metadata, times: Times) -> io::Result<Node> {
+        // Links are counted as in the lower layer, including those which
+        // haven't been copied yet.
+        let key = (metadata.dev, metadata.ino);
+        if metadata.link_count > 1 {
+            if let Some(node) = self.shared.lower_links.lock().unwrap().get(&key) {
+                return Ok(node.clone());
+            }
+        }
+        let file = Arc::new(FileNode {
+            ino: self.shared.ino(),
+            state: RwLock::new(FileState {
+                data: Vec::new(),
+                lower: Some(LowerFile {
+                    dir: self.dir.clone(),
+                    name: name.to_string(),
+                    size: metadata.size,
+                    file: OnceLock::new(),
+                }),
+                times,
+                nlink: metadata.link_count,
+            }),
+            quota: self.shared.quota.clone(),
+        });
+        if metadata.link_count > 1 {
+            self.shared
+                .lower_links
+                .lock()
+                .unwrap()
+                .insert(key, Node::File(file.clone()));
+        }
+        Ok(Node::File(file))
+    }
+}
+
+impl LowerFile {
+    /// Returns the file, opening it for reading if it hasn't been already.
+    fn file(&self) -> io::Result<&Arc<dyn HostFile>> {
+        if let Some(file) = self.file.get() {
+            return Ok(file);
+        }
+        let options = OpenOptions {
+            read: true,
+            ..OpenOptions::default()
+        };
+        let file = match self.dir.open_at(&self.name, &options)? {
+            Opened::File(file) => file,
+            Opened::Dir(_) => return Err(ErrorCode::IsDirectory.into()),
+        };
+        Ok(self.file.get_or_init(|| file))
+    }
+}
+
+impl Node {
+    fn ptr_eq(&self, other: &Node) -> bool {
+        match (self, other) {
+            (Node::Dir(a), Node::Dir(b)) => Arc::ptr_eq(a, b),
+            (Node::File(a), Node::File(b)) => Arc::ptr_eq(a, b),
+            (Node::Symlink(a), Node::Symlink(b)) => Arc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+
+    fn descriptor_type(&self) -> DescriptorType {
+        match self {
+            Node::Dir(_) => DescriptorType::Directory,
+            Node::File(_) => DescriptorType::RegularFile,
+            Node::Symlink(_) => DescriptorType::SymbolicLink,
+        }
+    }
+
+    fn metadata(&self, shared: &Shared) -> Metadata {
+        let (ino, link_count, size, times) = match self {
+            Node::Dir(dir) => (dir.ino, 1, 0, dir.state.lock().unwrap().times),
+            Node::File(file) => {
+                let state = file.state.read().unwrap();
+                let size = match &state.lower {
+                    Some(lower) => lower.size,
+                    None => state.data.len() as u64,
+                };
+                (file.ino, state.nlink, size, state.times)
+            }
+            Node::Symlink(link) => (
+                link.ino,
+                1,
+                link.target.len() as u64,
+                *link.times.lock().unwrap(),
+            ),
+        };
+        Metadata {
+            type_: self.descriptor_type(),
+            link_count,
+            size,
+            accessed: Some(times.accessed),
+            modified: Some(times.modified),
+            status_changed: Some(times.changed),
+            dev: shared.dev,
+            ino,
+        }
+    }
+
+    fn set_times(&self, atim: Option<SystemTimeSpec>, mtim: Option<SystemTimeSpec>) {
+        let update = |times: &mut Times| {
+            if let Some(atim) = atim {
+                times.accessed = system_time_from(atim);
+            }
+            if let Some(mtim) = mtim {
+                times.modified = system_time_from(mtim);
+            }
+            times.changed = SystemTime::now();
+        };
+        match self {
+            Node::Dir(dir) => update(&mut dir.state.lock().unwrap().times),
+            Node::File(file) => update(&mut file.state.write().unwrap().times),
+            Node::Symlink(link) => update(&mut link.times.lock().unwrap()),
+        }
+    }
+
+    /// Records that a directory entry referring to this node was removed.
+    fn unlinked(&self) {
+        if let Node::File(file) = self {
+            let mut state = file.state.write().unwrap();
+            state.nlink -= 1;
+            state.times.changed = SystemTime::now();
+        }
+    }
+}
+
+impl Resolved {
+    fn parent(&self) -> &Arc<DirNode> {
+        self.stack.last().unwrap()
+    }
+
+    /// Returns the object the path refers to, or `None` if the final
+    /// component doesn't exist.
+    fn node(&self) -> io::Result<Option<Node>> {
+        let name = match &self.name {
+            Some(name) => name,
+            None => return Ok(Some(Node::Dir(self.parent().clone()))),
+        };
+        match self.parent().lookup(name)? {
+            Some(Node::Dir(dir)) => Ok(Some(Node::Dir(dir))),
+            Some(_) if self.trailing_slash => Err(ErrorCode::NotDirectory.into()),
+            node => Ok(node),
+        }
+    }
+}
+
+impl Times {
+    fn now() -> Times {
+        let now = SystemTime::now();
+        Times {
+            accessed: now,
+            modified: now,
+            changed: now,
+        }
+    }
+
+    /// Returns the times in `metadata`, using the current time for those
+    /// which are unknown.
+    fn from_metadata(metadata: &Metadata) -> Times {
+        let now = SystemTime::now();
+        Times {
+            accessed: metadata.accessed.unwrap_or(now),
+            modified: metadata.modified.unwrap_or(now),
+            changed: metadata.status_changed.unwrap_or(now),
+        }
+    }
+
+    fn modify(&mut self) {
+        let now = SystemTime::now();
+        self.modified = now;
+        self.changed = now;
+    }
+}
+
+fn system_time_from(spec: SystemTimeSpec) -> SystemTime {
+    match spec {
+        SystemTimeSpec::SymbolicNow => SystemTime::now(),
+        SystemTimeSpec::Absolute(time) => time.into_std(),
+    }
+}
+
+/// Splits `path` into its components, rejecting paths which can't be
+/// resolved relative to a directory.
+fn split(path: &str) -> io::Result<VecDeque<String>> {
+    if path.contains('\0') {
+        return Err(ErrorCode::Invalid.into());
+    }
+    if path.is_empty() {
+        return Err(ErrorCode::NoEntry.into());
+    }
+    if path.starts_with('/') {
+        return Err(escape());
+    }
+    Ok(path
+        .split('/')
+        .filter(|c| !c.is_empty())
+        .map(|c| c.to_string())
+        .collect())
+}
+
+/// The error for paths which would resolve outside of the directory they're
+/// relative to, which is the same as cap-std's.
+fn escape() -> io::Error {
+    ErrorCode::NotPermitted.into()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn open(fs: &MemoryFilesystem, path: &str, options: OpenOptions) -> io::Result<Opened> {
+        fs.open_at(path, &options)
+    }
+
+    fn error_code(err: io::Error) -> ErrorCode {
+        ErrorCode::from(err)
+    }
+
+    fn read(fs: &MemoryFilesystem, path: &str) -> io::Result<Vec<u8>> {
+        let options = OpenOptions {
+            read: true,
+            follow_symlinks: true,
+            ..OpenOptions::default()
+        };
+        match open(fs, path, options)? {
+            Opened::File(file) => {
+                let mut buf = vec![0; file.metadata()?.size as usize];
+                let n = file.read_at(&mut buf, 0)?;
+                assert_eq!(n, buf.len());
+                Ok(buf)
+            }
+            Opened::Dir(_) => Err(ErrorCode::IsDirectory.into()),
+        }
+    }
+
+    #[test]
+    fn paths_stay_inside_the_directory() {
+        let fs = MemoryFilesystem::new();
+        fs.insert_file("a/b/file", "contents").unwrap();
+        fs.insert_symlink("a/up", "../..").unwrap();
+        fs.insert_symlink("a/abs", "/a").unwrap();
+
+        assert_eq!(read(&fs, "a/./b/../b//file").unwrap(), b"contents");
+        for path in ["/a/b/file", "../a", "a/../..", "a/up/a", "a/abs/b"] {
+            let err = fs.metadata_at(path, true).unwrap_err();
+            assert_eq!(error_code(err), ErrorCode::NotPermitted, "{path}");
+        }
+
+        // Handles to subdirectories can't be escaped either.
+        let sub = match open(&fs, "a", OpenOptions::default()).unwrap() {
+            Opened::Dir(dir) => dir,
+            Opened::File(_) => unreachable!(),
+        };
+        assert!(sub.metadata_at("b/file", false).is_ok());
+        let err = sub.metadata_at("../a", false).unwrap_err();
+        assert_eq!(error_code(err), ErrorCode::NotPermitted);
+    }
+
+    #[test]
+    fn symlinks() {
+        let fs = MemoryFilesystem::new();
+        fs.insert_file("dir/file", "contents").unwrap();
+        fs.symlink_at("dir/file", "link").unwrap();
+        fs.symlink_at("loop", "loop").unwrap();
+
+        assert_eq!(read(&fs, "link").unwrap(), b"contents");
+        assert_eq!(fs.readlink_at("link").unwrap(), "dir/file");
+        let err = open(&fs, "link", OpenOptions::default()).err().unwrap();
+        assert_eq!(error_code(err), ErrorCode::Loop);
+        let err = fs.metadata_at("loop", true).unwrap_err();
+        assert_eq!(error_code(err), ErrorCode::Loop);
+        let err = fs.metadata_at("dir/file/", false).unwrap_err();
+        assert_eq!(error_code(err), ErrorCode::NotDirectory);
+
+        fs.unlink_file_at("dir/file").unwrap();
+        let err = fs.metadata_at("link", true).unwrap_err();
+        assert_eq!(error_code(err), ErrorCode::NoEntry);
+    }
+
+    #[test]
+    fn rename_and_link() {
+        let fs = MemoryFilesystem::new();
+        fs.insert_file("file", "contents").unwrap();
+        fs.create_dir_at("dir").unwrap();
+
+        fs.link_at("file", &fs, "dir/link").unwrap();
+        assert_eq!(fs.metadata_at("file", false).unwrap().link_count, 2);
+        fs.rename_at("file", &fs, "renamed").unwrap();
+        assert_eq!(read(&fs, "renamed").unwrap(), b"contents");
+        fs.unlink_file_at("renamed").unwrap();
+        assert_eq!(fs.metadata_at("dir/link", false).unwrap().link_count, 1);
+
+        let err = fs.rename_at("dir", &fs, "dir/sub").unwrap_err();
+        assert_eq!(error_code(err), ErrorCode::Invalid);
+        let err = fs.remove_dir_at("dir").unwrap_err();
+        assert_eq!(error_code(err), ErrorCode::NotEmpty);
+
+        let other = MemoryFilesystem::new();
+        let err = fs.rename_at("dir", &other, "dir").unwrap_err();
+        assert_eq!(error_code(err), ErrorCode::CrossDevice);
+    }
+
+    #[test]
+    fn read_only() {
+        let fs = MemoryFilesystem::new();
+        fs.insert_file("file", "contents").unwrap();
+        let fs = fs.read_only();
+
+        let write = OpenOptions {
+            write: true,
+            ..OpenOptions::default()
+        };
+        let err = open(&fs, "file", write).err().unwrap();
+        assert_eq!(error_code(err), ErrorCode::ReadOnly);
+        let err = fs.create_dir_at("dir").unwrap_err();
+        assert_eq!(error_code(err), ErrorCode::ReadOnly);
+        let err = fs.unlink_file_at("file").unwrap_err();
+        assert_eq!(error_code(err), ErrorCode::ReadOnly);
+        assert_eq!(read(&fs, "file").unwrap(), b"contents");
+    }
+
+    #[test]
+    fn quota() {
+        let fs = MemoryFilesystem::new().quota(100);
+        fs.insert_file("existing", vec![0; 60]).unwrap();
+        let options = OpenOptions {
+            create: true,
+            write: true,
+            ..OpenOptions::default()
+        };
+        let file = match open(&fs, "file", options).unwrap() {
+            Opened::File(file) => file,
+            Opened::Dir(_) => unreachable!(),
+        };
+
+        // Huge sizes are refused rather than allocated.
+        let err = file.set_len(1 << 40).unwrap_err();
+        assert_eq!(error_code(err), ErrorCode::InsufficientSpace);
+        let err = file.write_at(b"x", 1 << 40).unwrap_err();
+        assert_eq!(error_code(err), ErrorCode::InsufficientSpace);
+        let err = file.write_at(b"x", u64::MAX).unwrap_err();
+        assert_eq!(error_code(err), ErrorCode::FileTooLarge);
+        assert_eq!(file.metadata().unwrap().size, 0);
+
+        file.set_len(40).unwrap();
+        let err = file.append(b"x").unwrap_err();
+        assert_eq!(error_code(err), ErrorCode::InsufficientSpace);
+
+        // Space is released by truncating and unlinking files.
+        file.set_len(30).unwrap();
+        assert_eq!(file.append(b"0123456789").unwrap(), 10);
+        fs.unlink_file_at("existing").unwrap();
+        file.set_len(100).unwrap();
+        let err = file.set_len(101).unwrap_err();
+        assert_eq!(error_code(err), ErrorCode::InsufficientSpace);
+        assert_eq!(file.metadata().unwrap().size, 100);
+    }
+
+    #[test]
+    fn overlay() {
+        let lower = MemoryFilesystem::new();
+        lower.insert_file("dir/file", "lower").unwrap();
+        lower.insert_file("dir/other", "other").unwrap();
+        lower.link_at("dir/file", &lower, "hard").unwrap();
+        lower.insert_symlink("link", "dir/file").unwrap();
+        let lower = lower.read_only();
+
+        let fs = MemoryFilesystem::overlay(Arc::new(lower.clone())).unwrap();
+        assert_eq!(read(&fs, "link").unwrap(), b"lower");
+        assert_eq!(fs.metadata_at("hard", false).unwrap().link_count, 2);
+
+        // Modifications are only made in the upper layer.
+        let write = OpenOptions {
+            write: true,
+            truncate: true,
+            ..OpenOptions::default()
+        };
+        match open(&fs, "hard", write).unwrap() {
+            Opened::File(file) => assert_eq!(file.write_at(b"upper", 0).unwrap(), 5),
+            Opened::Dir(_) => unreachable!(),
+        }
+        fs.rename_at("dir/file", &fs, "renamed").unwrap();
+        fs.unlink_file_at("dir/other").unwrap();
+        fs.remove_dir_at("dir").unwrap();
+        assert_eq!(read(&fs, "renamed").unwrap(), b"upper");
+        let err = fs.metadata_at("link", true).unwrap_err();
+        assert_eq!(error_code(err), ErrorCode::NoEntry);
+
+        assert_eq!(read(&lower, "dir/file").unwrap(), b"lower");
+        assert_eq!(read(&lower, "dir/other").unwrap(), b"other");
+        assert_eq!(read(&lower, "hard").unwrap(), b"lower");
+    }
+
+    #[test]
+    fn overlay_copies_files_lazily() {
+        let lower = MemoryFilesystem::new();
+        lower.insert_file("big", vec![1; 80]).unwrap();
+        lower.insert_file("small", "lower").unwrap();
+        let lower = lower.read_only();
+
+        // Files which are only read don't count towards the quota.
+        let fs = MemoryFilesystem::overlay(Arc::new(lower))
+            .unwrap()
+            .quota(50);
+        assert_eq!(read(&fs, "big").unwrap(), vec![1; 80]);
+        assert_eq!(fs.metadata_at("big", false).unwrap().size, 80);
+        assert_eq!(fs.shared.quota.used.load(Relaxed), 0);
+
+        // Copying a file which doesn't fit is refused, leaving it readable.
+        let write = OpenOptions {
+            write: true,
+            ..OpenOptions::default()
+        };
+        let err = open(&fs, "big", write).err().unwrap();
+        assert_eq!(error_code(err), ErrorCode::InsufficientSpace);
+        assert_eq!(read(&fs, "big").unwrap(), vec![1; 80]);
+
+        // Other files are copied when opened for writing.
+        match open(&fs, "small", write).unwrap() {
+            Opened::File(file) => assert_eq!(file.write_at(b"U", 0).unwrap(), 1),
+            Opened::Dir(_) => unreachable!(),
+        }
+        assert_eq!(read(&fs, "small").unwrap(), b"Uower");
+        assert_eq!(fs.shared.quota.used.load(Relaxed), 5);
+    }
+
+    #[test]
+    fn tar_archives() {
+        let mut archive = Vec::new();
+        tar::test::append(&mut archive, "./dir/", b'5', "", b"");
+        tar::test::append(&mut archive, "dir/file", b'0', "", b"contents");
+        tar::test::append(&mut archive, "dir/hard", b'1', "dir/file", b"");
+        tar::test::append(&mut archive, "soft", b'2', "dir/file", b"");
+        archive.extend_from_slice(&[0; 1024]);
+
+        let fs = MemoryFilesystem::from_tar(&archive).unwrap();
+        assert_eq!(read(&fs, "dir/file").unwrap(), b"contents");
+        assert_eq!(read(&fs, "dir/hard").unwrap(), b"contents");
+        assert_eq!(read(&fs, "soft").unwrap(), b"contents");
+        assert_eq!(fs.metadata_at("dir/file", false).unwrap().link_count, 2);
+
+        let mut names = fs
+            .read_dir()
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.unwrap().name)
+            .collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(names, ["dir", "soft"]);
+
+        archive[0] ^= 1;
+        assert!(MemoryFilesystem::from_tar(&archive).is_err());
+    }
+}
@@ -0,0 +1,202 @@
+//! A read-only [`HostFilesystem`] backend which merges other filesystems.
+
+use super::{HostFilesystem, Metadata, OpenOptions, Opened};
+use crate::preview2::bindings::filesystem::types::{self, ErrorCode};
+use cap_fs_ext::SystemTimeSpec;
+use std::any::Any;
+use std::collections::HashSet;
+use std::io;
+use std::sync::Arc;
+
+/// A read-only union of several filesystems.
+///
+/// Paths are looked up in each layer in order and the first layer containing
+/// a path takes precedence, except that directories present in multiple
+/// layers are merged. A file in one layer hides everything at and below the
+/// same path in later layers.
+///
+/// Each layer resolves paths independently, so a symlink in one layer is only
+/// followed within that layer. Attempts to modify the overlay fail with
+/// `error-code::read-only`, even if the underlying layers are writable. A
+/// writable layer can be added on top with [`MemoryFilesystem::overlay`].
+///
+/// [`MemoryFilesystem::overlay`]: crate::preview2::MemoryFilesystem::overlay
+#[derive(Clone)]
+pub struct OverlayFilesystem {
+    layers: Vec<Arc<dyn HostFilesystem>>,
+}
+
+impl OverlayFilesystem {
+    /// Creates an overlay of `layers`, with earlier layers taking precedence
+    /// over later ones.
+    pub fn new(layers: impl IntoIterator<Item = Arc<dyn HostFilesystem>>) -> OverlayFilesystem {
+        OverlayFilesystem {
+            layers: layers.into_iter().collect(),
+        }
+    }
+
+    /// Returns the result of `f` for the first layer in which it doesn't fail
+    /// with `error-code::no-entry`.
+    fn first<T>(&self, mut f: impl FnMut(&dyn HostFilesystem) -> io::Result<T>) -> io::Result<T> {
+        for layer in self.layers.iter() {
+            match f(&**layer) {
+                Err(e) if is_no_entry(&e) => continue,
+                result => return result,
+            }
+        }
+        Err(ErrorCode::NoEntry.into())
+    }
+}
+
+impl HostFilesystem for OverlayFilesystem {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn open_at(&self, path: &str, options: &OpenOptions) -> io::Result<Opened> {
+        if options.write || options.create || options.truncate {
+            return Err(ErrorCode::ReadOnly.into());
+        }
+        let mut dirs = Vec::new();
+        for layer in self.layers.iter() {
+            match layer.open_at(path, options) {
+                Ok(Opened::Dir(dir)) => dirs.push(dir),
+                Ok(Opened::File(file)) if dirs.is_empty() => return Ok(Opened::File(file)),
+                Err(e) if is_no_entry(&e) => continue,
+                Err(e) if dirs.is_empty() => return Err(e),
+                // A file or an error in a later layer ends the merged
+                // directory.
+                Ok(Opened::File(_)) | Err(_) => break,
+            }
+        }
+        if dirs.is_empty() {
+            return Err(ErrorCode::NoEntry.into());
+        }
+        // Directories are always wrapped, even if only one layer has them, so
+        // that they remain read-only.
+        Ok(Opened::Dir(Arc::new(OverlayFilesystem { layers: dirs })))
+    }
+
+    fn metadata(&self) -> io::Result<Metadata> {
+        self.first(|layer| layer.metadata())
+    }
+
+    fn metadata_at(&self, path: &str, follow_symlinks: bool) -> io::Result<Metadata> {
+        self.first(|layer| layer.metadata_at(path, follow_symlinks))
+    }
+
+    fn read_dir(&self) -> io::Result<Vec<io::Result<types::DirectoryEntry>>> {
+        let mut names = HashSet::new();
+        let mut entries = Vec::new();
+        for layer in self.layers.iter() {
+            for entry in layer.read_dir()? {
+                match entry {
+                    Ok(entry) if !names.insert(entry.name.clone()) => {}
+                    entry => entries.push(entry),
+                }
+            }
+        }
+        Ok(entries)
+    }
+
+    fn create_dir_at(&self, _path: &str) -> io::Result<()> {
+        Err(ErrorCode::ReadOnly.into())
+    }
+
+    fn remove_dir_at(&self, _path: &str) -> io::Result<()> {
+        Err(ErrorCode::ReadOnly.into())
+    }
+
+    fn unlink_file_at(&self, _path: &str) -> io::Result<()> {
+        Err(ErrorCode::ReadOnly.into())
+    }
+
+    fn rename_at(
+        &self,
+        _old_path: &str,
+        _new_dir: &dyn HostFilesystem,
+        _new_path: &str,
+    ) -> io::Result<()> {
+        Err(ErrorCode::ReadOnly.into())
+    }
+
+    fn link_at(
+        &self,
+        _old_path: &str,
+        _new_dir: &dyn HostFilesystem,
+        _new_path: &str,
+    ) -> io::Result<()> {
+        Err(ErrorCode::ReadOnly.into())
+    }
+
+    fn symlink_at(&self, _target: &str, _path: &str) -> io::Result<()> {
+        Err(ErrorCode::ReadOnly.into())
+    }
+
+    fn readlink_at(&self, path: &str) -> io::Result<String> {
+        self.first(|layer| layer.readlink_at(path))
+    }
+
+    fn set_times(
+        &self,
+        _atim: Option<SystemTimeSpec>,
+        _mtim: Option<SystemTimeSpec>,
+    ) -> io::Result<()> {
+        Err(ErrorCode::ReadOnly.into())
+    }
+
+    fn set_times_at(
+        &self,
+        _path: &str,
+        _follow_symlinks: bool,
+        _atim: Option<SystemTimeSpec>,
+        _mtim: Option<SystemTimeSpec>,
+    ) -> io::Result<()> {
+        Err(ErrorCode::ReadOnly.into())
+    }
+}
+
+fn is_no_entry(err: &io::Error) -> bool {
+    ErrorCode::from(err) == ErrorCode::NoEntry
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::preview2::MemoryFilesystem;
+
+    #[test]
+    fn layers() {
+        let upper = MemoryFilesystem::new();
+        upper.insert_file("both/upper", "upper").unwrap();
+        upper.insert_file("shadowed", "upper").unwrap();
+        upper.insert_file("file", "").unwrap();
+        let lower = MemoryFilesystem::new();
+        lower.insert_file("both/lower", "lower").unwrap();
+        lower.insert_file("shadowed", "lower longer").unwrap();
+        lower.insert_file("file/hidden", "").unwrap();
+
+        let overlay =
+            OverlayFilesystem::new([Arc::new(upper) as Arc<dyn HostFilesystem>, Arc::new(lower)]);
+        assert_eq!(overlay.metadata_at("shadowed", false).unwrap().size, 5);
+        assert!(overlay.metadata_at("both/lower", false).is_ok());
+        let err = overlay.metadata_at("file/hidden", false).unwrap_err();
+        assert_eq!(ErrorCode::from(err), ErrorCode::NotDirectory);
+
+        let both = match overlay.open_at("both", &OpenOptions::default()).unwrap() {
+            Opened::Dir(dir) => dir,
+            Opened::File(_) => unreachable!(),
+        };
+        let mut names = both
+            .read_dir()
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.unwrap().name)
+            .collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(names, ["lower", "upper"]);
+
+        let err = both.unlink_file_at("upper").unwrap_err();
+        assert_eq!(ErrorCode::from(err), ErrorCode::ReadOnly);
+    }
+}
@@ -0,0 +1,235 @@
+//! A minimal reader for tar archives, used to populate a
+//! [`MemoryFilesystem`](super::MemoryFilesystem).
+//!
+//! This understands the ustar format along with the GNU long name and PAX
+//! path extensions, which covers archives produced by common tools.
+
+use std::io;
+
+const BLOCK_SIZE: usize = 512;
+
+pub(super) struct Entry<'a> {
+    /// The path of the entry, relative to the root of the archive.
+    pub path: String,
+    /// The modification time, in seconds since the Unix epoch.
+    pub mtime: u64,
+    pub kind: EntryKind<'a>,
+}
+
+pub(super) enum EntryKind<'a> {
+    File(&'a [u8]),
+    Dir,
+    Symlink(String),
+    /// A hard link to the entry with this path, which appears earlier in the
+    /// archive.
+    HardLink(String),
+}
+
+/// Returns the entries in `archive`, skipping kinds of entries which can't
+/// be represented in a `MemoryFilesystem`.
+pub(super) fn entries(archive: &[u8]) -> io::Result<Vec<Entry<'_>>> {
+    let mut entries = Vec::new();
+    let mut long_path = None;
+    let mut long_link = None;
+    let mut rest = archive;
+
+    while rest.len() >= BLOCK_SIZE {
+        let (header, after_header) = rest.split_at(BLOCK_SIZE);
+        if header.iter().all(|b| *b == 0) {
+            break;
+        }
+        if checksum(header) != number(&header[148..156])? {
+            return Err(invalid("invalid tar header checksum"));
+        }
+
+        let size = usize::try_from(number(&header[124..136])?)
+            .map_err(|_| invalid("tar entry is too large"))?;
+        let padded = size
+            .checked_next_multiple_of(BLOCK_SIZE)
+            .filter(|padded| *padded <= after_header.len())
+            .ok_or_else(|| invalid("truncated tar entry"))?;
+        let data = &after_header[..size];
+        rest = &after_header[padded..];
+
+        let kind = match header[156] {
+            b'0' | b'\0' | b'7' => EntryKind::File(data),
+            b'5' => EntryKind::Dir,
+            b'2' => EntryKind::Symlink(long_link.take().map_or_else(|| link_name(header), Ok)?),
+            b'1' => EntryKind::HardLink(normalize(
+                &long_link.take().map_or_else(|| link_name(header), Ok)?,
+            )),
+            // GNU long names apply to the next entry.
+            b'L' => {
+                long_path = Some(string(data.split(|b| *b == 0).next().unwrap())?);
+                continue;
+            }
+            b'K' => {
+                long_link = Some(string(data.split(|b| *b == 0).next().unwrap())?);
+                continue;
+            }
+            // PAX extended headers apply to the next entry.
+            b'x' => {
+                for (key, value) in pax_records(data)? {
+                    match key {
+                        "path" => long_path = Some(value.to_string()),
+                        "linkpath" => long_link = Some(value.to_string()),
+                        _ => {}
+                    }
+                }
+                continue;
+            }
+            // Global PAX headers, devices, FIFOs and vendor extensions.
+            _ => {
+                long_path = None;
+                long_link = None;
+                continue;
+            }
+        };
+
+        let path = match long_path.take() {
+            Some(path) => path,
+            None => path_name(header)?,
+        };
+        let path = normalize(&path);
+        // The root directory itself is often included as `./`.
+        if path.is_empty() {
+            continue;
+        }
+        entries.push(Entry {
+            path,
+            mtime: number(&header[136..148])?,
+            kind,
+        });
+    }
+
+    Ok(entries)
+}
+
+fn path_name(header: &[u8]) -> io::Result<String> {
+    let name = string(field(&header[0..100]))?;
+    let prefix = if &header[257..262] == b"ustar" {
+        string(field(&header[345..500]))?
+    } else {
+        String::new()
+    };
+    if prefix.is_empty() {
+        Ok(name)
+    } else {
+        Ok(format!("{prefix}/{name}"))
+    }
+}
+
+fn link_name(header: &[u8]) -> io::Result<String> {
+    string(field(&header[157..257]))
+}
+
+/// Strips the leading `./` or `/` and trailing slashes which archives commonly
+/// include.
+fn normalize(path: &str) -> String {
+    let mut path = path.trim_start_matches('/');
+    while let Some(rest) = path.strip_prefix("./") {
+        path = rest.trim_start_matches('/');
+    }
+    if path == "." {
+        path = "";
+    }
+    path.trim_end_matches('/').to_string()
+}
+
+/// Returns a NUL-terminated header field without the terminator.
+fn field(bytes: &[u8]) -> &[u8] {
+    bytes.split(|b| *b == 0).next().unwrap()
+}
+
+fn string(bytes: &[u8]) -> io::Result<String> {
+    String::from_utf8(bytes.to_vec()).map_err(|_| invalid("tar entry path is not UTF-8"))
+}
+
+/// Parses a numeric header field, which is either octal text or, as a GNU
+/// extension, big-endian binary when the high bit of the first byte is set.
+fn number(bytes: &[u8]) -> io::Result<u64> {
+    if bytes[0] & 0x80 != 0 {
+        return bytes[1..]
+            .iter()
+            .try_fold(u64::from(bytes[0] & 0x7f), |n, b| {
+                n.checked_mul(256)
+                    .map(|n| n | u64::from(*b))
+                    .ok_or_else(|| invalid("tar header number is too large"))
+            });
+    }
+    let text = std::str::from_utf8(field(bytes))
+        .map_err(|_| invalid("invalid tar header number"))?
+        .trim_matches(' ');
+    if text.is_empty() {
+        return Ok(0);
+    }
+    u64::from_str_radix(text, 8).map_err(|_| invalid("invalid tar header number"))
+}
+
+/// Computes the checksum of a header, in which the checksum field itself is
+/// counted as spaces.
+fn checksum(header: &[u8]) -> u64 {
+    header
+        .iter()
+        .enumerate()
+        .map(|(i, b)| {
+            if (148..156).contains(&i) {
+                u64::from(b' ')
+            } else {
+                u64::from(*b)
+            }
+        })
+        .sum()
+}
+
+/// Parses the `<length> <key>=<value>\n` records of a PAX extended header.
+fn pax_records(mut data: &[u8]) -> io::Result<Vec<(&str, &str)>> {
+    let mut records = Vec::new();
+    while !data.is_empty() && data[0] != 0 {
+        let space = data
+            .iter()
+            .position(|b| *b == b' ')
+            .ok_or_else(|| invalid("invalid PAX record"))?;
+        let len = std::str::from_utf8(&data[..space])
+            .ok()
+            .and_then(|len| len.parse::<usize>().ok())
+            .filter(|len| *len > space + 1 && *len <= data.len())
+            .ok_or_else(|| invalid("invalid PAX record"))?;
+        let record = std::str::from_utf8(&data[space + 1..len - 1])
+            .map_err(|_| invalid("PAX record is not UTF-8"))?;
+        if let Some((key, value)) = record.split_once('=') {
+            records.push((key, value));
+        }
+        data = &data[len..];
+    }
+    Ok(records)
+}
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+#[cfg(test)]
+pub(super) mod test {
+    use super::BLOCK_SIZE;
+
+    /// Appends an entry with a ustar header to `archive`.
+    pub fn append(archive: &mut Vec<u8>, path: &str, kind: u8, link: &str, data: &[u8]) {
+        let mut header = [0; BLOCK_SIZE];
+        header[..path.len()].copy_from_slice(path.as_bytes());
+        header[100..107].copy_from_slice(b"0000644");
+        header[124..135].copy_from_slice(format!("{:011o}", data.len()).as_bytes());
+        header[136..147].copy_from_slice(b"14000000000");
+        header[156] = kind;
+        header[157..157 + link.len()].copy_from_slice(link.as_bytes());
+        header[257..263].copy_from_slice(b"ustar\0");
+        header[263..265].copy_from_slice(b"00");
+        let checksum = super::checksum(&header);
+        header[148..155].copy_from_slice(format!("{checksum:06o}\0").as_bytes());
+        header[155] = b' ';
+
+        archive.extend_from_slice(&header);
+        archive.extend_from_slice(data);
+        archive.resize(archive.len().next_multiple_of(BLOCK_SIZE), 0);
+    }
+}
@@ -5,9 +5,10 @@ use crate::preview2::bindings::filesystem::types::{
 };
 use crate::preview2::bindings::io::streams::{InputStream, OutputStream};
 use crate::preview2::filesystem::{
-    Descriptor, Dir, File, FileInputStream, FileOutputStream, OpenMode, ReaddirIterator,
+    Descriptor, Dir, File, FileInputStream, FileOutputStream, Metadata, OpenMode, OpenOptions,
+    Opened, ReaddirIterator,
 };
-use crate::preview2::{DirPerms, FilePerms, FsError, FsResult, WasiView};
+use crate::preview2::{DirPerms, FilePerms, FsError, FsResult, SystemTimeSpec, WasiView};
 use anyhow::Context;
 use wasmtime::component::Resource;
 
@@ -60,18 +61,6 @@ impl<T: WasiView> HostDescriptor for T {
         len: types::Filesize,
         advice: types::Advice,
     ) -> FsResult<()> {
-        use system_interface::fs::{Advice as A, FileIoExt};
-        use types::Advice;
-
-        let advice = match advice {
-            Advice::Normal => A::Normal,
-            Advice::Sequential => A::Sequential,
-            Advice::Random => A::Random,
-            Advice::WillNeed => A::WillNeed,
-            Advice::DontNeed => A::DontNeed,
-            Advice::NoReuse => A::NoReuse,
-        };
-
         let f = self.table().get(&fd)?.file()?;
         f.spawn_blocking(move |f| f.advise(offset, len, advice))
             .await?;
@@ -82,26 +71,8 @@ impl<T: WasiView> HostDescriptor for T {
         let descriptor = self.table().get(&fd)?;
 
         match descriptor {
-            Descriptor::File(f) => {
-                match f.spawn_blocking(|f| f.sync_data()).await {
-                    Ok(()) => Ok(()),
-                    // On windows, `sync_data` uses `FileFlushBuffers` which fails with
-                    // `ERROR_ACCESS_DENIED` if the file is not upen for writing. Ignore
-                    // this error, for POSIX compatibility.
-                    #[cfg(windows)]
-                    Err(e)
-                        if e.raw_os_error()
-                            == Some(windows_sys::Win32::Foundation::ERROR_ACCESS_DENIED as _) =>
-                    {
-                        Ok(())
-                    }
-                    Err(e) => Err(e.into()),
-                }
-            }
-            Descriptor::Dir(d) => {
-                d.spawn_blocking(|d| Ok(d.open(std::path::Component::CurDir)?.sync_data()?))
-                    .await
-            }
+            Descriptor::File(f) => Ok(f.spawn_blocking(|f| f.sync_data()).await?),
+            Descriptor::Dir(d) => Ok(d.spawn_blocking(|d| d.sync_data()).await?),
         }
     }
 
@@ -109,28 +80,12 @@ impl<T: WasiView> HostDescriptor for T {
         &mut self,
         fd: Resource<types::Descriptor>,
     ) -> FsResult<types::DescriptorFlags> {
-        use system_interface::fs::{FdFlags, GetSetFdFlags};
         use types::DescriptorFlags;
 
-        fn get_from_fdflags(flags: FdFlags) -> DescriptorFlags {
-            let mut out = DescriptorFlags::empty();
-            if flags.contains(FdFlags::DSYNC) {
-                out |= DescriptorFlags::REQUESTED_WRITE_SYNC;
-            }
-            if flags.contains(FdFlags::RSYNC) {
-                out |= DescriptorFlags::DATA_INTEGRITY_SYNC;
-            }
-            if flags.contains(FdFlags::SYNC) {
-                out |= DescriptorFlags::FILE_INTEGRITY_SYNC;
-            }
-            out
-        }
-
         let descriptor = self.table().get(&fd)?;
         match descriptor {
             Descriptor::File(f) => {
-                let flags = f.spawn_blocking(|f| f.get_fd_flags()).await?;
-                let mut flags = get_from_fdflags(flags);
+                let mut flags = f.spawn_blocking(|f| f.descriptor_flags()).await?;
                 if f.open_mode.contains(OpenMode::READ) {
                     flags |= DescriptorFlags::READ;
                 }
@@ -140,8 +95,7 @@ impl<T: WasiView> HostDescriptor for T {
                 Ok(flags)
             }
             Descriptor::Dir(d) => {
-                let flags = d.spawn_blocking(|d| d.get_fd_flags()).await?;
-                let mut flags = get_from_fdflags(flags);
+                let mut flags = d.spawn_blocking(|d| d.descriptor_flags()).await?;
                 if d.open_mode.contains(OpenMode::READ) {
                     flags |= DescriptorFlags::READ;
                 }
@@ -162,7 +116,7 @@ impl<T: WasiView> HostDescriptor for T {
         match descriptor {
             Descriptor::File(f) => {
                 let meta = f.spawn_blocking(|f| f.metadata()).await?;
-                Ok(descriptortype_from(meta.file_type()))
+                Ok(meta.type_)
             }
             Descriptor::Dir(_) => Ok(types::DescriptorType::Directory),
         }
@@ -187,8 +141,6 @@ impl<T: WasiView> HostDescriptor for T {
         atim: types::NewTimestamp,
         mtim: types::NewTimestamp,
     ) -> FsResult<()> {
-        use fs_set_times::SetTimes;
-
         let descriptor = self.table().get(&fd)?;
         match descriptor {
             Descriptor::File(f) => {
@@ -218,9 +170,6 @@ impl<T: WasiView> HostDescriptor for T {
         len: types::Filesize,
         offset: types::Filesize,
     ) -> FsResult<(Vec<u8>, bool)> {
-        use std::io::IoSliceMut;
-        use system_interface::fs::FileIoExt;
-
         let table = self.table();
 
         let f = table.get(&fd)?.file()?;
@@ -231,7 +180,7 @@ impl<T: WasiView> HostDescriptor for T {
         let (mut buffer, r) = f
             .spawn_blocking(move |f| {
                 let mut buffer = vec![0; len.try_into().unwrap_or(usize::MAX)];
-                let r = f.read_vectored_at(&mut [IoSliceMut::new(&mut buffer)], offset);
+                let r = f.read_at(&mut buffer, offset);
                 (buffer, r)
             })
             .await;
@@ -256,18 +205,13 @@ impl<T: WasiView> HostDescriptor for T {
         buf: Vec<u8>,
         offset: types::Filesize,
     ) -> FsResult<types::Filesize> {
-        use std::io::IoSlice;
-        use system_interface::fs::FileIoExt;
-
         let table = self.table();
         let f = table.get(&fd)?.file()?;
         if !f.perms.contains(FilePerms::WRITE) {
             return Err(ErrorCode::NotPermitted.into());
         }
 
-        let bytes_written = f
-            .spawn_blocking(move |f| f.write_vectored_at(&[IoSlice::new(&buf)], offset))
-            .await?;
+        let bytes_written = f.spawn_blocking(move |f| f.write_at(&buf, offset)).await?;
 
         Ok(types::Filesize::try_from(bytes_written).expect("usize fits in Filesize"))
     }
@@ -282,58 +226,11 @@ impl<T: WasiView> HostDescriptor for T {
             return Err(ErrorCode::NotPermitted.into());
         }
 
-        enum ReaddirError {
-            Io(std::io::Error),
-            IllegalSequence,
-        }
-        impl From<std::io::Error> for ReaddirError {
-            fn from(e: std::io::Error) -> ReaddirError {
-                ReaddirError::Io(e)
-            }
-        }
-
         let entries = d
-            .spawn_blocking(|d| {
-                // Both `entries` and `metadata` perform syscalls, which is why they are done
-                // within this `block` call, rather than delay calculating the metadata
-                // for entries when they're demanded later in the iterator chain.
-                Ok::<_, std::io::Error>(
-                    d.entries()?
-                        .map(|entry| {
-                            let entry = entry?;
-                            let meta = entry.metadata()?;
-                            let type_ = descriptortype_from(meta.file_type());
-                            let name = entry
-                                .file_name()
-                                .into_string()
-                                .map_err(|_| ReaddirError::IllegalSequence)?;
-                            Ok(types::DirectoryEntry { type_, name })
-                        })
-                        .collect::<Vec<Result<types::DirectoryEntry, ReaddirError>>>(),
-                )
-            })
+            .spawn_blocking(|d| d.read_dir())
             .await?
-            .into_iter();
-
-        // On windows, filter out files like `C:\DumpStack.log.tmp` which we
-        // can't get full metadata for.
-        #[cfg(windows)]
-        let entries = entries.filter(|entry| {
-            use windows_sys::Win32::Foundation::{ERROR_ACCESS_DENIED, ERROR_SHARING_VIOLATION};
-            if let Err(ReaddirError::Io(err)) = entry {
-                if err.raw_os_error() == Some(ERROR_SHARING_VIOLATION as i32)
-                    || err.raw_os_error() == Some(ERROR_ACCESS_DENIED as i32)
-                {
-                    return false;
-                }
-            }
-            true
-        });
-        let entries = entries.map(|r| match r {
-            Ok(r) => Ok(r),
-            Err(ReaddirError::Io(e)) => Err(e.into()),
-            Err(ReaddirError::IllegalSequence) => Err(ErrorCode::IllegalByteSequence.into()),
-        });
+            .into_iter()
+            .map(|r| r.map_err(Into::into));
         Ok(table.push(ReaddirIterator::new(entries))?)
     }
 
@@ -341,26 +238,8 @@ impl<T: WasiView> HostDescriptor for T {
         let descriptor = self.table().get(&fd)?;
 
         match descriptor {
-            Descriptor::File(f) => {
-                match f.spawn_blocking(|f| f.sync_all()).await {
-                    Ok(()) => Ok(()),
-                    // On windows, `sync_data` uses `FileFlushBuffers` which fails with
-                    // `ERROR_ACCESS_DENIED` if the file is not upen for writing. Ignore
-                    // this error, for POSIX compatibility.
-                    #[cfg(windows)]
-                    Err(e)
-                        if e.raw_os_error()
-                            == Some(windows_sys::Win32::Foundation::ERROR_ACCESS_DENIED as _) =>
-                    {
-                        Ok(())
-                    }
-                    Err(e) => Err(e.into()),
-                }
-            }
-            Descriptor::Dir(d) => {
-                d.spawn_blocking(|d| Ok(d.open(std::path::Component::CurDir)?.sync_all()?))
-                    .await
-            }
+            Descriptor::File(f) => Ok(f.spawn_blocking(|f| f.sync()).await?),
+            Descriptor::Dir(d) => Ok(d.spawn_blocking(|d| d.sync()).await?),
         }
     }
 
@@ -374,7 +253,7 @@ impl<T: WasiView> HostDescriptor for T {
         if !d.perms.contains(DirPerms::MUTATE) {
             return Err(ErrorCode::NotPermitted.into());
         }
-        d.spawn_blocking(move |d| d.create_dir(&path)).await?;
+        d.spawn_blocking(move |d| d.create_dir_at(&path)).await?;
         Ok(())
     }
 
@@ -388,7 +267,7 @@ impl<T: WasiView> HostDescriptor for T {
             }
             Descriptor::Dir(d) => {
                 // No permissions check on stat: if opened, allowed to stat it
-                let meta = d.spawn_blocking(|d| d.dir_metadata()).await?;
+                let meta = d.spawn_blocking(|d| d.metadata()).await?;
                 Ok(descriptorstat_from(meta))
             }
         }
@@ -406,11 +285,10 @@ impl<T: WasiView> HostDescriptor for T {
             return Err(ErrorCode::NotPermitted.into());
         }
 
-        let meta = if symlink_follow(path_flags) {
-            d.spawn_blocking(move |d| d.metadata(&path)).await?
-        } else {
-            d.spawn_blocking(move |d| d.symlink_metadata(&path)).await?
-        };
+        let follow = symlink_follow(path_flags);
+        let meta = d
+            .spawn_blocking(move |d| d.metadata_at(&path, follow))
+            .await?;
         Ok(descriptorstat_from(meta))
     }
 
@@ -422,8 +300,6 @@ impl<T: WasiView> HostDescriptor for T {
         atim: types::NewTimestamp,
         mtim: types::NewTimestamp,
     ) -> FsResult<()> {
-        use cap_fs_ext::DirExt;
-
         let table = self.table();
         let d = table.get(&fd)?.dir()?;
         if !d.perms.contains(DirPerms::MUTATE) {
@@ -431,25 +307,9 @@ impl<T: WasiView> HostDescriptor for T {
         }
         let atim = systemtimespec_from(atim)?;
         let mtim = systemtimespec_from(mtim)?;
-        if symlink_follow(path_flags) {
-            d.spawn_blocking(move |d| {
-                d.set_times(
-                    &path,
-                    atim.map(cap_fs_ext::SystemTimeSpec::from_std),
-                    mtim.map(cap_fs_ext::SystemTimeSpec::from_std),
-                )
-            })
-            .await?;
-        } else {
-            d.spawn_blocking(move |d| {
-                d.set_symlink_times(
-                    &path,
-                    atim.map(cap_fs_ext::SystemTimeSpec::from_std),
-                    mtim.map(cap_fs_ext::SystemTimeSpec::from_std),
-                )
-            })
+        let follow = symlink_follow(path_flags);
+        d.spawn_blocking(move |d| d.set_times_at(&path, follow, atim, mtim))
             .await?;
-        }
         Ok(())
     }
 
@@ -476,7 +336,7 @@ impl<T: WasiView> HostDescriptor for T {
         }
         let new_dir_handle = std::sync::Arc::clone(&new_dir.dir);
         old_dir
-            .spawn_blocking(move |d| d.hard_link(&old_path, &new_dir_handle, &new_path))
+            .spawn_blocking(move |d| d.link_at(&old_path, &*new_dir_handle, &new_path))
             .await?;
         Ok(())
     }
@@ -489,8 +349,6 @@ impl<T: WasiView> HostDescriptor for T {
         oflags: types::OpenFlags,
         flags: types::DescriptorFlags,
     ) -> FsResult<Resource<types::Descriptor>> {
-        use cap_fs_ext::{FollowSymlinks, OpenOptionsFollowExt, OpenOptionsMaybeDirExt};
-        use system_interface::fs::{FdFlags, GetSetFdFlags};
         use types::{DescriptorFlags, OpenFlags};
 
         let table = self.table();
@@ -512,42 +370,35 @@ impl<T: WasiView> HostDescriptor for T {
         let mut create = false;
         // Track open mode, for permission check and recording in created descriptor:
         let mut open_mode = OpenMode::empty();
-        // Construct the OpenOptions to give the OS:
-        let mut opts = cap_std::fs::OpenOptions::new();
-        opts.maybe_dir(true);
+        // Construct the OpenOptions to give the backend:
+        let mut opts = OpenOptions::default();
 
         if oflags.contains(OpenFlags::CREATE) {
-            if oflags.contains(OpenFlags::EXCLUSIVE) {
-                opts.create_new(true);
-            } else {
-                opts.create(true);
-            }
+            opts.create = true;
+            opts.exclusive = oflags.contains(OpenFlags::EXCLUSIVE);
             create = true;
-            opts.write(true);
+            opts.write = true;
             open_mode |= OpenMode::WRITE;
         }
 
         if oflags.contains(OpenFlags::TRUNCATE) {
-            opts.truncate(true);
+            opts.truncate = true;
         }
         if flags.contains(DescriptorFlags::READ) {
-            opts.read(true);
+            opts.read = true;
             open_mode |= OpenMode::READ;
         }
         if flags.contains(DescriptorFlags::WRITE) {
-            opts.write(true);
+            opts.write = true;
             open_mode |= OpenMode::WRITE;
         } else {
             // If not opened write, open read. This way the OS lets us open
             // the file, but we can use perms to reject use of the file later.
-            opts.read(true);
+            opts.read = true;
             open_mode |= OpenMode::READ;
         }
-        if symlink_follow(path_flags) {
-            opts.follow(FollowSymlinks::Yes);
-        } else {
-            opts.follow(FollowSymlinks::No);
-        }
+        opts.directory = oflags.contains(OpenFlags::DIRECTORY);
+        opts.follow_symlinks = symlink_follow(path_flags);
 
         // These flags are not yet supported in cap-std:
         if flags.contains(DescriptorFlags::FILE_INTEGRITY_SYNC)
@@ -575,47 +426,19 @@ impl<T: WasiView> HostDescriptor for T {
             Err(ErrorCode::NotPermitted)?;
         }
 
-        // Represents each possible outcome from the spawn_blocking operation.
-        // This makes sure we don't have to give spawn_blocking any way to
-        // manipulate the table.
-        enum OpenResult {
-            Dir(cap_std::fs::Dir),
-            File(cap_std::fs::File),
-            NotDir,
-        }
-
-        let opened = d
-            .spawn_blocking::<_, std::io::Result<OpenResult>>(move |d| {
-                let mut opened = d.open_with(&path, &opts)?;
-                if opened.metadata()?.is_dir() {
-                    Ok(OpenResult::Dir(cap_std::fs::Dir::from_std_file(
-                        opened.into_std(),
-                    )))
-                } else if oflags.contains(OpenFlags::DIRECTORY) {
-                    Ok(OpenResult::NotDir)
-                } else {
-                    // FIXME cap-std needs a nonblocking open option so that files reads and writes
-                    // are nonblocking. Instead we set it after opening here:
-                    let set_fd_flags = opened.new_set_fd_flags(FdFlags::NONBLOCK)?;
-                    opened.set_fd_flags(set_fd_flags)?;
-                    Ok(OpenResult::File(opened))
-                }
-            })
-            .await?;
+        let opened = d.spawn_blocking(move |d| d.open_at(&path, &opts)).await?;
 
         match opened {
-            OpenResult::Dir(dir) => Ok(table.push(Descriptor::Dir(Dir::new(
+            Opened::Dir(dir) => Ok(table.push(Descriptor::Dir(Dir::new(
                 dir,
                 d.perms,
                 d.file_perms,
                 open_mode,
             )))?),
 
-            OpenResult::File(file) => {
+            Opened::File(file) => {
                 Ok(table.push(Descriptor::File(File::new(file, d.file_perms, open_mode)))?)
             }
-
-            OpenResult::NotDir => Err(ErrorCode::NotDirectory.into()),
         }
     }
 
@@ -642,11 +465,7 @@ impl<T: WasiView> HostDescriptor for T {
         if !d.perms.contains(DirPerms::READ) {
             return Err(ErrorCode::NotPermitted.into());
         }
-        let link = d.spawn_blocking(move |d| d.read_link(&path)).await?;
-        Ok(link
-            .into_os_string()
-            .into_string()
-            .map_err(|_| ErrorCode::IllegalByteSequence)?)
+        Ok(d.spawn_blocking(move |d| d.readlink_at(&path)).await?)
     }
 
     async fn remove_directory_at(
@@ -659,7 +478,7 @@ impl<T: WasiView> HostDescriptor for T {
         if !d.perms.contains(DirPerms::MUTATE) {
             return Err(ErrorCode::NotPermitted.into());
         }
-        Ok(d.spawn_blocking(move |d| d.remove_dir(&path)).await?)
+        Ok(d.spawn_blocking(move |d| d.remove_dir_at(&path)).await?)
     }
 
     async fn rename_at(
@@ -680,7 +499,7 @@ impl<T: WasiView> HostDescriptor for T {
         }
         let new_dir_handle = std::sync::Arc::clone(&new_dir.dir);
         Ok(old_dir
-            .spawn_blocking(move |d| d.rename(&old_path, &new_dir_handle, &new_path))
+            .spawn_blocking(move |d| d.rename_at(&old_path, &*new_dir_handle, &new_path))
             .await?)
     }
 
@@ -690,17 +509,15 @@ impl<T: WasiView> HostDescriptor for T {
         src_path: String,
         dest_path: String,
     ) -> FsResult<()> {
-        // On windows, Dir.symlink is provided by DirExt
-        #[cfg(windows)]
-        use cap_fs_ext::DirExt;
-
         let table = self.table();
         let d = table.get(&fd)?.dir()?;
         if !d.perms.contains(DirPerms::MUTATE) {
             return Err(ErrorCode::NotPermitted.into());
         }
-        Ok(d.spawn_blocking(move |d| d.symlink(&src_path, &dest_path))
-            .await?)
+        Ok(
+            d.spawn_blocking(move |d| d.symlink_at(&src_path, &dest_path))
+                .await?,
+        )
     }
 
     async fn unlink_file_at(
@@ -708,15 +525,12 @@ impl<T: WasiView> HostDescriptor for T {
         fd: Resource<types::Descriptor>,
         path: String,
     ) -> FsResult<()> {
-        use cap_fs_ext::DirExt;
-
         let table = self.table();
         let d = table.get(&fd)?.dir()?;
         if !d.perms.contains(DirPerms::MUTATE) {
             return Err(ErrorCode::NotPermitted.into());
         }
-        Ok(d.spawn_blocking(move |d| d.remove_file_or_symlink(&path))
-            .await?)
+        Ok(d.spawn_blocking(move |d| d.unlink_file_at(&path)).await?)
     }
 
     fn read_via_stream(
@@ -795,12 +609,11 @@ impl<T: WasiView> HostDescriptor for T {
         a: Resource<types::Descriptor>,
         b: Resource<types::Descriptor>,
     ) -> anyhow::Result<bool> {
-        use cap_fs_ext::MetadataExt;
         let descriptor_a = self.table().get(&a)?;
         let meta_a = get_descriptor_metadata(descriptor_a).await?;
         let descriptor_b = self.table().get(&b)?;
         let meta_b = get_descriptor_metadata(descriptor_b).await?;
-        if meta_a.dev() == meta_b.dev() && meta_a.ino() == meta_b.ino() {
+        if meta_a.dev == meta_b.dev && meta_a.ino == meta_b.ino {
             // MetadataHashValue does not derive eq, so use a pair of
             // comparisons to check equality:
             debug_assert_eq!(
@@ -834,14 +647,9 @@ impl<T: WasiView> HostDescriptor for T {
         let table = self.table();
         let d = table.get(&fd)?.dir()?;
         // No permissions check on metadata: if dir opened, allowed to stat it
+        let follow = symlink_follow(path_flags);
         let meta = d
-            .spawn_blocking(move |d| {
-                if symlink_follow(path_flags) {
-                    d.metadata(path)
-                } else {
-                    d.symlink_metadata(path)
-                }
-            })
+            .spawn_blocking(move |d| d.metadata_at(&path, follow))
             .await?;
         Ok(calculate_metadata_hash(&meta))
     }
@@ -864,7 +672,7 @@ impl<T: WasiView> HostDirectoryEntryStream for T {
     }
 }
 
-async fn get_descriptor_metadata(fd: &types::Descriptor) -> FsResult<cap_std::fs::Metadata> {
+async fn get_descriptor_metadata(fd: &types::Descriptor) -> FsResult<Metadata> {
     match fd {
         Descriptor::File(f) => {
             // No permissions check on metadata: if opened, allowed to stat it
@@ -872,21 +680,20 @@ async fn get_descriptor_metadata(fd: &types::Descriptor) -> FsResult<cap_std::fs
         }
         Descriptor::Dir(d) => {
             // No permissions check on metadata: if opened, allowed to stat it
-            Ok(d.spawn_blocking(|d| d.dir_metadata()).await?)
+            Ok(d.spawn_blocking(|d| d.metadata()).await?)
         }
     }
 }
 
-fn calculate_metadata_hash(meta: &cap_std::fs::Metadata) -> types::MetadataHashValue {
-    use cap_fs_ext::MetadataExt;
+fn calculate_metadata_hash(meta: &Metadata) -> types::MetadataHashValue {
     // Without incurring any deps, std provides us with a 64 bit hash
     // function:
     use std::hash::Hasher;
     // Note that this means that the metadata hash (which becomes a preview1 ino) may
     // change when a different rustc release is used to build this host implementation:
     let mut hasher = std::collections::hash_map::DefaultHasher::new();
-    hasher.write_u64(meta.dev());
-    hasher.write_u64(meta.ino());
+    hasher.write_u64(meta.dev);
+    hasher.write_u64(meta.ino);
     let lower = hasher.finish();
     // MetadataHashValue has a pair of 64-bit members for representing a
     // single 128-bit number. However, we only have 64 bits of entropy. To
@@ -979,6 +786,11 @@ impl From<std::io::Error> for ErrorCode {
 
 impl<'a> From<&'a std::io::Error> for ErrorCode {
     fn from(err: &'a std::io::Error) -> ErrorCode {
+        // Backends other than the host's filesystem report WASI error codes
+        // directly.
+        if let Some(code) = err.get_ref().and_then(|e| e.downcast_ref::<ErrorCode>()) {
+            return *code;
+        }
         match from_raw_os_error(err.raw_os_error()) {
             Some(errno) => errno,
             None => {
@@ -1008,31 +820,14 @@ impl From<std::num::TryFromIntError> for ErrorCode {
     }
 }
 
-fn descriptortype_from(ft: cap_std::fs::FileType) -> types::DescriptorType {
-    use cap_fs_ext::FileTypeExt;
-    use types::DescriptorType;
-    if ft.is_dir() {
-        DescriptorType::Directory
-    } else if ft.is_symlink() {
-        DescriptorType::SymbolicLink
-    } else if ft.is_block_device() {
-        DescriptorType::BlockDevice
-    } else if ft.is_char_device() {
-        DescriptorType::CharacterDevice
-    } else if ft.is_file() {
-        DescriptorType::RegularFile
-    } else {
-        DescriptorType::Unknown
-    }
-}
-
-fn systemtimespec_from(t: types::NewTimestamp) -> FsResult<Option<fs_set_times::SystemTimeSpec>> {
-    use fs_set_times::SystemTimeSpec;
+fn systemtimespec_from(t: types::NewTimestamp) -> FsResult<Option<SystemTimeSpec>> {
     use types::NewTimestamp;
     match t {
         NewTimestamp::NoChange => Ok(None),
         NewTimestamp::Now => Ok(Some(SystemTimeSpec::SymbolicNow)),
-        NewTimestamp::Timestamp(st) => Ok(Some(SystemTimeSpec::Absolute(systemtime_from(st)?))),
+        NewTimestamp::Timestamp(st) => Ok(Some(SystemTimeSpec::Absolute(
+            cap_std::time::SystemTime::from_std(systemtime_from(st)?),
+        ))),
     }
 }
 
@@ -1048,15 +843,14 @@ fn datetime_from(t: std::time::SystemTime) -> wall_clock::Datetime {
     wall_clock::Datetime::try_from(cap_std::time::SystemTime::from_std(t)).unwrap()
 }
 
-fn descriptorstat_from(meta: cap_std::fs::Metadata) -> types::DescriptorStat {
-    use cap_fs_ext::MetadataExt;
+fn descriptorstat_from(meta: Metadata) -> types::DescriptorStat {
     types::DescriptorStat {
-        type_: descriptortype_from(meta.file_type()),
-        link_count: meta.nlink(),
-        size: meta.len(),
-        data_access_timestamp: meta.accessed().map(|t| datetime_from(t.into_std())).ok(),
-        data_modification_timestamp: meta.modified().map(|t| datetime_from(t.into_std())).ok(),
-        status_change_timestamp: meta.created().map(|t| datetime_from(t.into_std())).ok(),
+        type_: meta.type_,
+        link_count: meta.link_count,
+        size: meta.size,
+        data_access_timestamp: meta.accessed.map(datetime_from),
+        data_modification_timestamp: meta.modified.map(datetime_from),
+        status_change_timestamp: meta.status_changed.map(datetime_from),
     }
 }
 
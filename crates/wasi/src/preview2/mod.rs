@@ -34,6 +34,7 @@ pub mod preview0;
 #[cfg(feature = "preview1-on-preview2")]
 pub mod preview1;
 mod random;
+pub mod record;
 mod stdio;
 mod stream;
 mod tcp;
@@ -43,10 +44,14 @@ mod write_stream;
 pub use self::clocks::{HostMonotonicClock, HostWallClock};
 pub use self::ctx::{WasiCtx, WasiCtxBuilder, WasiView};
 pub use self::error::{I32Exit, TrappableError};
-pub use self::filesystem::{DirPerms, FilePerms, FsError, FsResult};
+pub use self::filesystem::{
+    DirPerms, FilePerms, FsError, FsResult, HostFile, HostFilesystem, MemoryFilesystem, Metadata,
+    OpenOptions, Opened, OverlayFilesystem,
+};
 pub use self::network::{Network, SocketError, SocketResult};
 pub use self::poll::{subscribe, ClosureFuture, MakeFuture, Pollable, PollableFuture, Subscribe};
 pub use self::random::{thread_rng, Deterministic};
+pub use self::record::{Divergence, Recorder, Replayer};
 pub use self::stdio::{
     stderr, stdin, stdout, IsATTY, Stderr, Stdin, StdinStream, Stdout, StdoutStream,
 };
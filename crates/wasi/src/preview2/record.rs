@@ -0,0 +1,1971 @@
+//! Recording and replaying the results of nondeterministic host calls.
+//!
+//! A [`Recorder`] installed with [`WasiCtxBuilder::record`] logs the result
+//! of every call a guest makes to a clock, a random number generator, stdin,
+//! a preopened filesystem, or `wasi:sockets`. A [`Replayer`] installed with
+//! [`WasiCtxBuilder::replay`] feeds those results back to the guest in place
+//! of the real host, so that the guest observes exactly what it observed
+//! while being recorded.
+//!
+//! While replaying, each host call is checked against the next call in the
+//! trace. If the guest makes a different call, or the same call with
+//! different arguments, the replay has diverged: the divergence is logged and
+//! reported by [`Replayer::finish`], and from then on clocks and random
+//! number generators return live values while stdin, filesystem and socket
+//! calls fail.
+//!
+//! Writes to files are passed through while recording and are discarded
+//! while replaying, as their effects are only observable through later reads
+//! which are themselves recorded.
+//!
+//! Sockets are recorded in the same way: every socket, connection, datagram
+//! and name lookup call is logged along with its result, including the data
+//! read from connections and the outcome of writes to them. Replaying never
+//! touches the network. Sockets are created as placeholders which are never
+//! bound or connected, data written to connections is discarded, and socket
+//! pollables are always ready, as the guest observes the recorded results
+//! rather than waiting on the host. Socket calls are traced by a layer over
+//! the `wasi:sockets` host implementations, which
+//! [`command::add_to_linker`](crate::preview2::command::add_to_linker) and
+//! its synchronous counterpart install.
+//!
+//! [`WasiCtxBuilder::record`]: crate::preview2::WasiCtxBuilder::record
+//! [`WasiCtxBuilder::replay`]: crate::preview2::WasiCtxBuilder::replay
+
+use crate::preview2::bindings::filesystem::types::{
+    self, DescriptorFlags, DescriptorType, DirectoryEntry, ErrorCode,
+};
+use crate::preview2::bindings::sockets::network::{
+    ErrorCode as SocketErrorCode, IpAddress, IpAddressFamily, IpSocketAddress,
+};
+use crate::preview2::bindings::sockets::tcp::ShutdownType;
+use crate::preview2::bindings::sockets::udp::{IncomingDatagram, OutgoingDatagram};
+use crate::preview2::network::{to_ipv4_addr, to_ipv6_addr};
+use crate::preview2::{
+    HostFile, HostFilesystem, HostInputStream, HostMonotonicClock, HostOutputStream, HostWallClock,
+    Metadata, OpenOptions, Opened, SocketError, SocketResult, StdinStream, StreamError,
+    StreamResult, Subscribe,
+};
+use bytes::Bytes;
+use cap_fs_ext::SystemTimeSpec;
+use cap_rand::RngCore;
+use cap_std::time::Duration;
+use std::any::Any;
+use std::collections::VecDeque;
+use std::fmt;
+use std::io::{self, Write};
+use std::net::{IpAddr, SocketAddr, SocketAddrV4, SocketAddrV6};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+mod sockets;
+
+pub(crate) use self::sockets::TracedSockets;
+
+const MAGIC: &[u8; 8] = b"wasitrc\0";
+const VERSION: u32 = 1;
+
+/// The kinds of host calls which are recorded.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Event {
+    WallClockNow = 1,
+    WallClockResolution,
+    MonotonicClockNow,
+    MonotonicClockResolution,
+    Random,
+    InsecureRandom,
+    InsecureRandomSeed,
+    StdinIsatty,
+    StdinRead,
+    Filesystem,
+    Socket,
+    SocketRead,
+    SocketWrite,
+}
+
+impl Event {
+    fn from_u8(byte: u8) -> Option<Event> {
+        Some(match byte {
+            1 => Event::WallClockNow,
+            2 => Event::WallClockResolution,
+            3 => Event::MonotonicClockNow,
+            4 => Event::MonotonicClockResolution,
+            5 => Event::Random,
+            6 => Event::InsecureRandom,
+            7 => Event::InsecureRandomSeed,
+            8 => Event::StdinIsatty,
+            9 => Event::StdinRead,
+            10 => Event::Filesystem,
+            11 => Event::Socket,
+            12 => Event::SocketRead,
+            13 => Event::SocketWrite,
+            _ => return None,
+        })
+    }
+}
+
+/// Writes the results of host calls to a trace.
+///
+/// A trace is a header followed by a sequence of events, each of which is
+/// the kind of call, the arguments which identify the call, and its result.
+/// Consecutive draws from the same random number generator are coalesced
+/// into a single event.
+#[derive(Clone)]
+pub struct Recorder(Arc<Mutex<RecorderState>>);
+
+struct RecorderState {
+    out: Box<dyn Write + Send>,
+    /// Random bytes which haven't been written yet, so that they can be
+    /// coalesced with the next draw.
+    random: Option<(Event, Vec<u8>)>,
+    /// The first error encountered while writing the trace, which is reported
+    /// by [`Recorder::flush`].
+    error: Option<io::Error>,
+}
+
+impl Recorder {
+    /// Creates a recorder which writes its trace to `out`.
+    pub fn new(mut out: impl Write + Send + 'static) -> io::Result<Recorder> {
+        out.write_all(MAGIC)?;
+        out.write_all(&VERSION.to_le_bytes())?;
+        Ok(Recorder(Arc::new(Mutex::new(RecorderState {
+            out: Box::new(out),
+            random: None,
+            error: None,
+        }))))
+    }
+
+    /// Creates a recorder which writes its trace to the file at `path`.
+    pub fn create(path: impl AsRef<Path>) -> io::Result<Recorder> {
+        Recorder::new(io::BufWriter::new(std::fs::File::create(path)?))
+    }
+
+    /// Writes out all buffered events.
+    ///
+    /// This must be called once the guest has finished running, as the end
+    /// of the trace may otherwise be lost. Errors encountered while writing
+    /// earlier events are reported here.
+    pub fn flush(&self) -> io::Result<()> {
+        let mut state = self.0.lock().unwrap();
+        state.flush_random();
+        if let Err(e) = state.out.flush() {
+            state.error.get_or_insert(e);
+        }
+        match state.error.take() {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+
+    fn record(&self, event: Event, request: &[u8], response: &[u8]) {
+        let mut state = self.0.lock().unwrap();
+        state.flush_random();
+        state.write(event, request, response);
+    }
+
+    fn record_random(&self, event: Event, bytes: &[u8]) {
+        let mut state = self.0.lock().unwrap();
+        match &mut state.random {
+            Some((pending, buf)) if *pending == event => buf.extend_from_slice(bytes),
+            _ => {
+                state.flush_random();
+                state.random = Some((event, bytes.to_vec()));
+            }
+        }
+    }
+}
+
+impl RecorderState {
+    fn flush_random(&mut self) {
+        if let Some((event, bytes)) = self.random.take() {
+            self.write(event, &[], &bytes);
+        }
+    }
+
+    fn write(&mut self, event: Event, request: &[u8], response: &[u8]) {
+        if self.error.is_some() {
+            return;
+        }
+        let mut buf = Writer::default();
+        buf.u8(event as u8);
+        buf.bytes(request);
+        buf.bytes(response);
+        if let Err(e) = self.out.write_all(&buf.0) {
+            self.error = Some(e);
+        }
+    }
+}
+
+/// Feeds the results of host calls from a trace written by a [`Recorder`]
+/// back to a guest.
+#[derive(Clone)]
+pub struct Replayer(Arc<Mutex<ReplayerState>>);
+
+struct ReplayerState {
+    trace: Vec<u8>,
+    /// The offset of the next event in `trace`.
+    pos: usize,
+    /// The number of events replayed so far.
+    index: u64,
+    /// Random bytes from the last random event which haven't been drawn yet.
+    random: Option<(Event, VecDeque<u8>)>,
+    divergence: Option<Divergence>,
+}
+
+/// An error indicating that a guest being replayed didn't make the same host
+/// calls as when it was recorded.
+#[derive(Clone, Debug)]
+pub struct Divergence {
+    event: u64,
+    message: String,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "replay diverged from the trace at event {}: {}",
+            self.event, self.message
+        )
+    }
+}
+
+impl std::error::Error for Divergence {}
+
+impl Replayer {
+    /// Creates a replayer for the trace in `trace`.
+    pub fn new(trace: Vec<u8>) -> io::Result<Replayer> {
+        if trace.len() < 12 || &trace[..8] != MAGIC {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "not a WASI trace file",
+            ));
+        }
+        let version = u32::from_le_bytes(trace[8..12].try_into().unwrap());
+        if version != VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unsupported WASI trace version {version}"),
+            ));
+        }
+        Ok(Replayer(Arc::new(Mutex::new(ReplayerState {
+            trace,
+            pos: 12,
+            index: 0,
+            random: None,
+            divergence: None,
+        }))))
+    }
+
+    /// Creates a replayer for the trace in the file at `path`.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Replayer> {
+        Replayer::new(std::fs::read(path)?)
+    }
+
+    /// Checks that the guest made exactly the calls in the trace.
+    ///
+    /// This is called once the guest has finished running and fails if the
+    /// replay diverged or if the guest stopped before reaching the end of the
+    /// trace.
+    pub fn finish(&self) -> Result<(), Divergence> {
+        let mut state = self.0.lock().unwrap();
+        if let Some(divergence) = &state.divergence {
+            return Err(divergence.clone());
+        }
+        state.check_random_drained();
+        if state.divergence.is_none() && state.pos < state.trace.len() {
+            let next = state.trace[state.pos];
+            let next = Event::from_u8(next).map_or_else(|| format!("{next}"), |e| format!("{e:?}"));
+            state.diverge(format!(
+                "the guest stopped before making all recorded calls, the next being a {next} call"
+            ));
+        }
+        match &state.divergence {
+            Some(divergence) => Err(divergence.clone()),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns the recorded result of the next call, which is expected to be
+    /// an `event` call with `request` as its arguments, or `None` if the
+    /// replay has diverged.
+    fn replay(&self, event: Event, request: &[u8]) -> Option<Vec<u8>> {
+        let mut state = self.0.lock().unwrap();
+        if state.divergence.is_some() {
+            return None;
+        }
+        state.check_random_drained();
+        state.next(event, request)
+    }
+
+    /// Fills `buf` with recorded random bytes, returning `false` if the
+    /// replay has diverged.
+    fn replay_random(&self, event: Event, buf: &mut [u8]) -> bool {
+        let mut state = self.0.lock().unwrap();
+        if state.divergence.is_some() {
+            return false;
+        }
+        if !matches!(&state.random, Some((pending, _)) if *pending == event) {
+            state.check_random_drained();
+            let bytes = match state.next(event, &[]) {
+                Some(bytes) => bytes,
+                None => return false,
+            };
+            state.random = Some((event, bytes.into()));
+        }
+        let (_, pending) = state.random.as_mut().unwrap();
+        if pending.len() < buf.len() {
+            state.diverge(format!(
+                "the guest drew more bytes from {event:?} than recorded"
+            ));
+            return false;
+        }
+        let n = buf.len();
+        for (dst, src) in buf.iter_mut().zip(pending.drain(..n)) {
+            *dst = src;
+        }
+        true
+    }
+
+    /// Flags a divergence because of an unexpected recorded result.
+    fn malformed(&self, event: Event) {
+        self.0.lock().unwrap().diverge(format!(
+            "the recorded result of a {event:?} call is malformed"
+        ));
+    }
+}
+
+impl ReplayerState {
+    fn next(&mut self, event: Event, request: &[u8]) -> Option<Vec<u8>> {
+        let mut reader = Reader(&self.trace[self.pos..]);
+        let (kind, recorded_request, response) = match reader.u8() {
+            None => {
+                self.diverge(format!(
+                    "the trace ended, but the guest made a {event:?} call"
+                ));
+                return None;
+            }
+            Some(kind) => match (reader.bytes(), reader.bytes()) {
+                (Some(req), Some(resp)) => (kind, req, resp),
+                _ => {
+                    self.diverge("the trace is truncated".to_string());
+                    return None;
+                }
+            },
+        };
+        if kind != event as u8 {
+            let found =
+                Event::from_u8(kind).map_or_else(|| format!("{kind}"), |e| format!("{e:?}"));
+            self.diverge(format!(
+                "expected a {found} call, but the guest made a {event:?} call"
+            ));
+            return None;
+        }
+        if recorded_request != request {
+            self.diverge(format!(
+                "the guest made a {event:?} call with different arguments"
+            ));
+            return None;
+        }
+        let response = response.to_vec();
+        self.pos = self.trace.len() - reader.0.len();
+        self.index += 1;
+        Some(response)
+    }
+
+    /// Flags a divergence if the guest didn't draw all the random bytes of
+    /// the last random event before making another call.
+    fn check_random_drained(&mut self) {
+        if let Some((event, pending)) = self.random.take() {
+            if !pending.is_empty() {
+                self.diverge(format!(
+                    "the guest drew {} fewer bytes from {event:?} than recorded",
+                    pending.len()
+                ));
+            }
+        }
+    }
+
+    fn diverge(&mut self, message: String) {
+        if self.divergence.is_none() {
+            let divergence = Divergence {
+                event: self.index,
+                message,
+            };
+            log::error!("{divergence}");
+            self.divergence = Some(divergence);
+        }
+    }
+}
+
+/// Whether host calls are being recorded or replayed, as configured on a
+/// `WasiCtxBuilder`.
+#[derive(Clone)]
+pub(crate) enum Trace {
+    Record(Recorder),
+    Replay(Replayer),
+}
+
+impl Trace {
+    /// Returns the result of `live` when recording, or the recorded result
+    /// when replaying. After a divergence `live` is used for replays too.
+    fn call<T: Codec>(&self, event: Event, request: &[u8], live: impl FnOnce() -> T) -> T {
+        self.call_with(event, request, live, T::encode, T::decode)
+    }
+
+    fn call_with<T>(
+        &self,
+        event: Event,
+        request: &[u8],
+        live: impl FnOnce() -> T,
+        encode: impl FnOnce(&T, &mut Writer),
+        decode: impl FnOnce(&mut Reader<'_>) -> Option<T>,
+    ) -> T {
+        match self {
+            Trace::Record(recorder) => {
+                let result = live();
+                let mut response = Writer::default();
+                encode(&result, &mut response);
+                recorder.record(event, request, &response.0);
+                result
+            }
+            Trace::Replay(replayer) => {
+                let response = match replayer.replay(event, request) {
+                    Some(response) => response,
+                    None => return live(),
+                };
+                let mut reader = Reader(&response);
+                match decode(&mut reader) {
+                    Some(result) if reader.0.is_empty() => result,
+                    _ => {
+                        replayer.malformed(event);
+                        live()
+                    }
+                }
+            }
+        }
+    }
+
+    pub(crate) fn wall_clock(
+        &self,
+        inner: Box<dyn HostWallClock + Send>,
+    ) -> Box<dyn HostWallClock + Send> {
+        Box::new(TracedWallClock {
+            inner,
+            trace: self.clone(),
+        })
+    }
+
+    pub(crate) fn monotonic_clock(
+        &self,
+        inner: Box<dyn HostMonotonicClock + Send>,
+    ) -> Box<dyn HostMonotonicClock + Send> {
+        Box::new(TracedMonotonicClock {
+            inner,
+            trace: self.clone(),
+        })
+    }
+
+    pub(crate) fn random(&self, inner: Box<dyn RngCore + Send>) -> Box<dyn RngCore + Send> {
+        Box::new(TracedRng {
+            inner,
+            trace: self.clone(),
+            event: Event::Random,
+        })
+    }
+
+    pub(crate) fn insecure_random(
+        &self,
+        inner: Box<dyn RngCore + Send>,
+    ) -> Box<dyn RngCore + Send> {
+        Box::new(TracedRng {
+            inner,
+            trace: self.clone(),
+            event: Event::InsecureRandom,
+        })
+    }
+
+    pub(crate) fn insecure_random_seed(&self, seed: u128) -> u128 {
+        self.call(Event::InsecureRandomSeed, &[], || seed)
+    }
+
+    pub(crate) fn stdin(&self, inner: Box<dyn StdinStream>) -> Box<dyn StdinStream> {
+        Box::new(TracedStdin {
+            inner: self.live(inner),
+            trace: self.clone(),
+        })
+    }
+
+    pub(crate) fn filesystem(&self, inner: Arc<dyn HostFilesystem>) -> Arc<dyn HostFilesystem> {
+        Arc::new(TracedFilesystem {
+            inner: self.live(inner),
+            trace: self.clone(),
+        })
+    }
+
+    /// Returns `inner` when recording, or `None` when replaying, for sources
+    /// of input which aren't used at all while replaying.
+    fn live<T>(&self, inner: T) -> Option<T> {
+        match self {
+            Trace::Record(_) => Some(inner),
+            Trace::Replay(_) => None,
+        }
+    }
+
+    /// Wraps the streams of a connection, which is `None` when replaying.
+    fn socket_streams(
+        &self,
+        inner: Option<(Box<dyn HostInputStream>, Box<dyn HostOutputStream>)>,
+    ) -> (Box<dyn HostInputStream>, Box<dyn HostOutputStream>) {
+        let (input, output) = inner.unzip();
+        let input: Box<dyn HostInputStream> = Box::new(TracedInputStream {
+            inner: input,
+            trace: self.clone(),
+            event: Event::SocketRead,
+        });
+        let output: Box<dyn HostOutputStream> = Box::new(TracedOutputStream {
+            inner: output,
+            trace: self.clone(),
+        });
+        (input, output)
+    }
+}
+
+/// The `wasi:sockets` calls which are recorded, which identify a `Socket`
+/// event along with the arguments of the call.
+#[derive(Copy, Clone)]
+enum SocketOp {
+    TcpCreate = 1,
+    TcpStartBind,
+    TcpFinishBind,
+    TcpStartConnect,
+    TcpFinishConnect,
+    TcpStartListen,
+    TcpFinishListen,
+    TcpAccept,
+    TcpLocalAddress,
+    TcpRemoteAddress,
+    TcpIsListening,
+    TcpSetListenBacklogSize,
+    TcpKeepAliveEnabled,
+    TcpSetKeepAliveEnabled,
+    TcpKeepAliveIdleTime,
+    TcpSetKeepAliveIdleTime,
+    TcpKeepAliveInterval,
+    TcpSetKeepAliveInterval,
+    TcpKeepAliveCount,
+    TcpSetKeepAliveCount,
+    TcpHopLimit,
+    TcpSetHopLimit,
+    TcpReceiveBufferSize,
+    TcpSetReceiveBufferSize,
+    TcpSendBufferSize,
+    TcpSetSendBufferSize,
+    TcpShutdown,
+    UdpCreate,
+    UdpStartBind,
+    UdpFinishBind,
+    UdpStream,
+    UdpLocalAddress,
+    UdpRemoteAddress,
+    UdpUnicastHopLimit,
+    UdpSetUnicastHopLimit,
+    UdpReceiveBufferSize,
+    UdpSetReceiveBufferSize,
+    UdpSendBufferSize,
+    UdpSetSendBufferSize,
+    UdpReceive,
+    UdpCheckSend,
+    UdpSend,
+    ResolveAddresses,
+    ResolveNextAddress,
+}
+
+fn socket_request(op: SocketOp, args: &impl Codec) -> Vec<u8> {
+    let mut request = Writer::default();
+    request.u8(op as u8);
+    args.encode(&mut request);
+    request.0
+}
+
+/// Runs the `wasi:sockets` call `op` with the arguments `args` through
+/// `trace`, if there is one.
+///
+/// While replaying `live` is never called, as the sockets it would use are
+/// placeholders. Calls fail with `unknown` once the replay has diverged.
+fn socket<T: Codec>(
+    trace: Option<Trace>,
+    op: SocketOp,
+    args: &impl Codec,
+    live: impl FnOnce() -> SocketResult<T>,
+) -> SocketResult<T> {
+    let Some(trace) = trace else {
+        return live();
+    };
+    let replaying = matches!(trace, Trace::Replay(_));
+    trace.call(Event::Socket, &socket_request(op, args), || {
+        if replaying {
+            Err(SocketErrorCode::Unknown.into())
+        } else {
+            live()
+        }
+    })
+}
+
+/// Like [`socket`], for calls which create new resources from host state that
+/// can't be recorded, such as a connection.
+///
+/// Only whether the call succeeded is recorded. A successful replayed call
+/// returns `None`, and the caller creates placeholders for its resources.
+fn socket_resources<T>(
+    trace: Option<Trace>,
+    op: SocketOp,
+    args: &impl Codec,
+    live: impl FnOnce() -> SocketResult<T>,
+) -> SocketResult<Option<T>> {
+    let mut value = None;
+    socket(trace, op, args, || {
+        value = Some(live()?);
+        Ok(())
+    })?;
+    Ok(value)
+}
+
+struct TracedWallClock {
+    inner: Box<dyn HostWallClock + Send>,
+    trace: Trace,
+}
+
+impl HostWallClock for TracedWallClock {
+    fn resolution(&self) -> Duration {
+        self.trace
+            .call(Event::WallClockResolution, &[], || self.inner.resolution())
+    }
+
+    fn now(&self) -> Duration {
+        self.trace
+            .call(Event::WallClockNow, &[], || self.inner.now())
+    }
+}
+
+struct TracedMonotonicClock {
+    inner: Box<dyn HostMonotonicClock + Send>,
+    trace: Trace,
+}
+
+impl HostMonotonicClock for TracedMonotonicClock {
+    fn resolution(&self) -> u64 {
+        self.trace.call(Event::MonotonicClockResolution, &[], || {
+            self.inner.resolution()
+        })
+    }
+
+    fn now(&self) -> u64 {
+        self.trace
+            .call(Event::MonotonicClockNow, &[], || self.inner.now())
+    }
+}
+
+struct TracedRng {
+    inner: Box<dyn RngCore + Send>,
+    trace: Trace,
+    event: Event,
+}
+
+impl RngCore for TracedRng {
+    fn next_u32(&mut self) -> u32 {
+        let mut buf = [0; 4];
+        self.fill_bytes(&mut buf);
+        u32::from_le_bytes(buf)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut buf = [0; 8];
+        self.fill_bytes(&mut buf);
+        u64::from_le_bytes(buf)
+    }
+
+    fn fill_bytes(&mut self, buf: &mut [u8]) {
+        match &self.trace {
+            Trace::Record(recorder) => {
+                self.inner.fill_bytes(buf);
+                recorder.record_random(self.event, buf);
+            }
+            Trace::Replay(replayer) => {
+                if !replayer.replay_random(self.event, buf) {
+                    self.inner.fill_bytes(buf);
+                }
+            }
+        }
+    }
+
+    fn try_fill_bytes(&mut self, buf: &mut [u8]) -> Result<(), cap_rand::Error> {
+        self.fill_bytes(buf);
+        Ok(())
+    }
+}
+
+struct TracedStdin {
+    inner: Option<Box<dyn StdinStream>>,
+    trace: Trace,
+}
+
+impl StdinStream for TracedStdin {
+    fn stream(&self) -> Box<dyn HostInputStream> {
+        Box::new(TracedInputStream {
+            inner: self.inner.as_ref().map(|stdin| stdin.stream()),
+            trace: self.trace.clone(),
+            event: Event::StdinRead,
+        })
+    }
+
+    fn isatty(&self) -> bool {
+        self.trace.call(Event::StdinIsatty, &[], || {
+            self.inner.as_ref().is_some_and(|stdin| stdin.isatty())
+        })
+    }
+}
+
+struct TracedInputStream {
+    inner: Option<Box<dyn HostInputStream>>,
+    trace: Trace,
+    event: Event,
+}
+
+impl HostInputStream for TracedInputStream {
+    fn read(&mut self, size: usize) -> StreamResult<Bytes> {
+        let mut request = Writer::default();
+        request.u64(size as u64);
+        let inner = &mut self.inner;
+        self.trace.call(self.event, &request.0, || match inner {
+            Some(stream) => stream.read(size),
+            None => Err(StreamError::Closed),
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl Subscribe for TracedInputStream {
+    async fn ready(&mut self) {
+        // While replaying, recorded reads are always ready.
+        if let Some(stream) = &mut self.inner {
+            stream.ready().await;
+        }
+    }
+}
+
+/// The output stream of a connection, whose writes are passed through while
+/// recording and discarded while replaying.
+struct TracedOutputStream {
+    /// The stream being recorded, or `None` when replaying.
+    inner: Option<Box<dyn HostOutputStream>>,
+    trace: Trace,
+}
+
+impl TracedOutputStream {
+    fn call<T: Codec>(
+        &mut self,
+        request: &[u8],
+        live: impl FnOnce(&mut dyn HostOutputStream) -> StreamResult<T>,
+    ) -> StreamResult<T> {
+        let inner = &mut self.inner;
+        self.trace
+            .call(Event::SocketWrite, request, || match inner {
+                Some(stream) => live(&mut **stream),
+                None => Err(StreamError::Closed),
+            })
+    }
+}
+
+impl HostOutputStream for TracedOutputStream {
+    fn write(&mut self, bytes: Bytes) -> StreamResult<()> {
+        let mut request = Writer::default();
+        request.u8(0);
+        request.u64(bytes.len() as u64);
+        self.call(&request.0, |stream| stream.write(bytes))
+    }
+
+    fn flush(&mut self) -> StreamResult<()> {
+        self.call(&[1], |stream| stream.flush())
+    }
+
+    fn check_write(&mut self) -> StreamResult<usize> {
+        self.call(&[2], |stream| stream.check_write())
+    }
+}
+
+#[async_trait::async_trait]
+impl Subscribe for TracedOutputStream {
+    async fn ready(&mut self) {
+        if let Some(stream) = &mut self.inner {
+            stream.ready().await;
+        }
+    }
+}
+
+/// The filesystem operations which are recorded, which identify a
+/// `Filesystem` event along with the arguments of the operation.
+#[derive(Copy, Clone)]
+enum FsOp {
+    OpenAt = 1,
+    Metadata,
+    MetadataAt,
+    ReadDir,
+    CreateDirAt,
+    RemoveDirAt,
+    UnlinkFileAt,
+    RenameAt,
+    LinkAt,
+    SymlinkAt,
+    ReadlinkAt,
+    SetTimes,
+    SetTimesAt,
+    DescriptorFlags,
+    SyncData,
+    Sync,
+    FileReadAt,
+    FileSetLen,
+    FileMetadata,
+    FileSetTimes,
+    FileDescriptorFlags,
+    FileSyncData,
+    FileSync,
+}
+
+fn fs_request(op: FsOp, args: &[&str]) -> Vec<u8> {
+    let mut request = Writer::default();
+    request.u8(op as u8);
+    for arg in args {
+        request.str(arg);
+    }
+    request.0
+}
+
+struct TracedFilesystem {
+    /// The backend being recorded, or `None` when replaying.
+    inner: Option<Arc<dyn HostFilesystem>>,
+    trace: Trace,
+}
+
+impl TracedFilesystem {
+    fn call<T: Codec>(
+        &self,
+        op: FsOp,
+        args: &[&str],
+        live: impl FnOnce(&dyn HostFilesystem) -> io::Result<T>,
+    ) -> io::Result<T> {
+        self.trace
+            .call(Event::Filesystem, &fs_request(op, args), || {
+                match &self.inner {
+                    Some(inner) => live(&**inner),
+                    None => Err(ErrorCode::Io.into()),
+                }
+            })
+    }
+}
+
+impl HostFilesystem for TracedFilesystem {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn open_at(&self, path: &str, options: &OpenOptions) -> io::Result<Opened> {
+        let flags = [
+            options.read,
+            options.write,
+            options.create,
+            options.exclusive,
+            options.truncate,
+            options.directory,
+            options.follow_symlinks,
+        ]
+        .iter()
+        .map(|b| if *b { "1" } else { "0" })
+        .collect::<String>();
+        let request = fs_request(FsOp::OpenAt, &[path, &flags]);
+        let trace = &self.trace;
+        trace.call_with(
+            Event::Filesystem,
+            &request,
+            || match &self.inner {
+                Some(inner) => Ok(match inner.open_at(path, options)? {
+                    Opened::Dir(dir) => Opened::Dir(trace.filesystem(dir)),
+                    Opened::File(file) => Opened::File(Arc::new(TracedFile {
+                        inner: Some(file),
+                        trace: trace.clone(),
+                    })),
+                }),
+                None => Err(ErrorCode::Io.into()),
+            },
+            |result, w| match result {
+                Ok(opened) => {
+                    w.u8(0);
+                    matches!(opened, Opened::Dir(_)).encode(w);
+                }
+                Err(e) => {
+                    w.u8(1);
+                    ErrorCode::from(e).encode(w);
+                }
+            },
+            |r| {
+                Some(io::Result::<bool>::decode(r)?.map(|is_dir| {
+                    if is_dir {
+                        Opened::Dir(trace.filesystem(Arc::new(NoFilesystem)))
+                    } else {
+                        Opened::File(Arc::new(TracedFile {
+                            inner: None,
+                            trace: trace.clone(),
+                        }))
+                    }
+                }))
+            },
+        )
+    }
+
+    fn metadata(&self) -> io::Result<Metadata> {
+        self.call(FsOp::Metadata, &[], |fs| fs.metadata())
+    }
+
+    fn metadata_at(&self, path: &str, follow_symlinks: bool) -> io::Result<Metadata> {
+        let follow = if follow_symlinks { "1" } else { "0" };
+        self.call(FsOp::MetadataAt, &[path, follow], |fs| {
+            fs.metadata_at(path, follow_symlinks)
+        })
+    }
+
+    fn read_dir(&self) -> io::Result<Vec<io::Result<DirectoryEntry>>> {
+        self.call(FsOp::ReadDir, &[], |fs| fs.read_dir())
+    }
+
+    fn create_dir_at(&self, path: &str) -> io::Result<()> {
+        self.call(FsOp::CreateDirAt, &[path], |fs| fs.create_dir_at(path))
+    }
+
+    fn remove_dir_at(&self, path: &str) -> io::Result<()> {
+        self.call(FsOp::RemoveDirAt, &[path], |fs| fs.remove_dir_at(path))
+    }
+
+    fn unlink_file_at(&self, path: &str) -> io::Result<()> {
+        self.call(FsOp::UnlinkFileAt, &[path], |fs| fs.unlink_file_at(path))
+    }
+
+    fn rename_at(
+        &self,
+        old_path: &str,
+        new_dir: &dyn HostFilesystem,
+        new_path: &str,
+    ) -> io::Result<()> {
+        self.call(FsOp::RenameAt, &[old_path, new_path], |fs| {
+            fs.rename_at(old_path, untraced(new_dir), new_path)
+        })
+    }
+
+    fn link_at(
+        &self,
+        old_path: &str,
+        new_dir: &dyn HostFilesystem,
+        new_path: &str,
+    ) -> io::Result<()> {
+        self.call(FsOp::LinkAt, &[old_path, new_path], |fs| {
+            fs.link_at(old_path, untraced(new_dir), new_path)
+        })
+    }
+
+    fn symlink_at(&self, target: &str, path: &str) -> io::Result<()> {
+        self.call(FsOp::SymlinkAt, &[target, path], |fs| {
+            fs.symlink_at(target, path)
+        })
+    }
+
+    fn readlink_at(&self, path: &str) -> io::Result<String> {
+        self.call(FsOp::ReadlinkAt, &[path], |fs| fs.readlink_at(path))
+    }
+
+    fn set_times(
+        &self,
+        atim: Option<SystemTimeSpec>,
+        mtim: Option<SystemTimeSpec>,
+    ) -> io::Result<()> {
+        self.call(FsOp::SetTimes, &[], |fs| fs.set_times(atim, mtim))
+    }
+
+    fn set_times_at(
+        &self,
+        path: &str,
+        follow_symlinks: bool,
+        atim: Option<SystemTimeSpec>,
+        mtim: Option<SystemTimeSpec>,
+    ) -> io::Result<()> {
+        let follow = if follow_symlinks { "1" } else { "0" };
+        self.call(FsOp::SetTimesAt, &[path, follow], |fs| {
+            fs.set_times_at(path, follow_symlinks, atim, mtim)
+        })
+    }
+
+    fn descriptor_flags(&self) -> io::Result<DescriptorFlags> {
+        self.call(FsOp::DescriptorFlags, &[], |fs| fs.descriptor_flags())
+    }
+
+    fn sync_data(&self) -> io::Result<()> {
+        self.call(FsOp::SyncData, &[], |fs| fs.sync_data())
+    }
+
+    fn sync(&self) -> io::Result<()> {
+        self.call(FsOp::Sync, &[], |fs| fs.sync())
+    }
+}
+
+/// Returns the backend wrapped by `dir` if it's being recorded, so that
+/// backends can recognize their own directories in `rename_at` and `link_at`.
+fn untraced(dir: &dyn HostFilesystem) -> &dyn HostFilesystem {
+    match dir.as_any().downcast_ref::<TracedFilesystem>() {
+        Some(TracedFilesystem {
+            inner: Some(inner), ..
+        }) => &**inner,
+        _ => dir,
+    }
+}
+
+/// A placeholder for directories opened while replaying, which is never
+/// called as `TracedFilesystem` doesn't use its backend while replaying.
+struct NoFilesystem;
+
+impl HostFilesystem for NoFilesystem {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+    fn open_at(&self, _: &str, _: &OpenOptions) -> io::Result<Opened> {
+        Err(ErrorCode::Io.into())
+    }
+    fn metadata(&self) -> io::Result<Metadata> {
+        Err(ErrorCode::Io.into())
+    }
+    fn metadata_at(&self, _: &str, _: bool) -> io::Result<Metadata> {
+        Err(ErrorCode::Io.into())
+    }
+    fn read_dir(&self) -> io::Result<Vec<io::Result<DirectoryEntry>>> {
+        Err(ErrorCode::Io.into())
+    }
+    fn create_dir_at(&self, _: &str) -> io::Result<()> {
+        Err(ErrorCode::Io.into())
+    }
+    fn remove_dir_at(&self, _: &str) -> io::Result<()> {
+        Err(ErrorCode::Io.into())
+    }
+    fn unlink_file_at(&self, _: &str) -> io::Result<()> {
+        Err(ErrorCode::Io.into())
+    }
+    fn rename_at(&self, _: &str, _: &dyn HostFilesystem, _: &str) -> io::Result<()> {
+        Err(ErrorCode::Io.into())
+    }
+    fn link_at(&self, _: &str, _: &dyn HostFilesystem, _: &str) -> io::Result<()> {
+        Err(ErrorCode::Io.into())
+    }
+    fn symlink_at(&self, _: &str, _: &str) -> io::Result<()> {
+        Err(ErrorCode::Io.into())
+    }
+    fn readlink_at(&self, _: &str) -> io::Result<String> {
+        Err(ErrorCode::Io.into())
+    }
+    fn set_times(&self, _: Option<SystemTimeSpec>, _: Option<SystemTimeSpec>) -> io::Result<()> {
+        Err(ErrorCode::Io.into())
+    }
+    fn set_times_at(
+        &self,
+        _: &str,
+        _: bool,
+        _: Option<SystemTimeSpec>,
+        _: Option<SystemTimeSpec>,
+    ) -> io::Result<()> {
+        Err(ErrorCode::Io.into())
+    }
+}
+
+struct TracedFile {
+    /// The file being recorded, or `None` when replaying.
+    inner: Option<Arc<dyn HostFile>>,
+    trace: Trace,
+}
+
+impl TracedFile {
+    fn call<T: Codec>(
+        &self,
+        op: FsOp,
+        request: &[u8],
+        live: impl FnOnce(&dyn HostFile) -> io::Result<T>,
+    ) -> io::Result<T> {
+        let mut full = vec![op as u8];
+        full.extend_from_slice(request);
+        self.trace
+            .call(Event::Filesystem, &full, || match &self.inner {
+                Some(inner) => live(&**inner),
+                None => Err(ErrorCode::Io.into()),
+            })
+    }
+}
+
+impl HostFile for TracedFile {
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        let mut request = Writer::default();
+        request.u64(offset);
+        request.u64(buf.len() as u64);
+        let data = self.call(FsOp::FileReadAt, &request.0, |file| {
+            let mut data = vec![0; buf.len()];
+            let n = file.read_at(&mut data, offset)?;
+            data.truncate(n);
+            Ok(data)
+        })?;
+        if data.len() > buf.len() {
+            return Err(ErrorCode::Io.into());
+        }
+        buf[..data.len()].copy_from_slice(&data);
+        Ok(data.len())
+    }
+
+    fn write_at(&self, buf: &[u8], offset: u64) -> io::Result<usize> {
+        match &self.inner {
+            Some(inner) => inner.write_at(buf, offset),
+            None => Ok(buf.len()),
+        }
+    }
+
+    fn append(&self, buf: &[u8]) -> io::Result<usize> {
+        match &self.inner {
+            Some(inner) => inner.append(buf),
+            None => Ok(buf.len()),
+        }
+    }
+
+    fn set_len(&self, size: u64) -> io::Result<()> {
+        self.call(FsOp::FileSetLen, &size.to_le_bytes(), |file| {
+            file.set_len(size)
+        })
+    }
+
+    fn metadata(&self) -> io::Result<Metadata> {
+        self.call(FsOp::FileMetadata, &[], |file| file.metadata())
+    }
+
+    fn set_times(
+        &self,
+        atim: Option<SystemTimeSpec>,
+        mtim: Option<SystemTimeSpec>,
+    ) -> io::Result<()> {
+        self.call(FsOp::FileSetTimes, &[], |file| file.set_times(atim, mtim))
+    }
+
+    fn advise(&self, offset: u64, len: u64, advice: types::Advice) -> io::Result<()> {
+        match &self.inner {
+            Some(inner) => inner.advise(offset, len, advice),
+            None => Ok(()),
+        }
+    }
+
+    fn descriptor_flags(&self) -> io::Result<DescriptorFlags> {
+        self.call(FsOp::FileDescriptorFlags, &[], |file| {
+            file.descriptor_flags()
+        })
+    }
+
+    fn sync_data(&self) -> io::Result<()> {
+        self.call(FsOp::FileSyncData, &[], |file| file.sync_data())
+    }
+
+    fn sync(&self) -> io::Result<()> {
+        self.call(FsOp::FileSync, &[], |file| file.sync())
+    }
+}
+
+#[derive(Default)]
+struct Writer(Vec<u8>);
+
+impl Writer {
+    fn u8(&mut self, val: u8) {
+        self.0.push(val);
+    }
+
+    fn u32(&mut self, val: u32) {
+        self.0.extend_from_slice(&val.to_le_bytes());
+    }
+
+    fn u64(&mut self, val: u64) {
+        self.0.extend_from_slice(&val.to_le_bytes());
+    }
+
+    fn bytes(&mut self, val: &[u8]) {
+        self.u32(u32::try_from(val.len()).expect("trace entries are limited to 4GiB"));
+        self.0.extend_from_slice(val);
+    }
+
+    fn str(&mut self, val: &str) {
+        self.bytes(val.as_bytes());
+    }
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Option<&'a [u8]> {
+        if self.0.len() < n {
+            return None;
+        }
+        let (bytes, rest) = self.0.split_at(n);
+        self.0 = rest;
+        Some(bytes)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        Some(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        Some(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        Some(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    fn bytes(&mut self) -> Option<&'a [u8]> {
+        let len = self.u32()?;
+        self.take(usize::try_from(len).ok()?)
+    }
+
+    fn string(&mut self) -> Option<String> {
+        String::from_utf8(self.bytes()?.to_vec()).ok()
+    }
+}
+
+/// Serialization of the results of host calls.
+trait Codec: Sized {
+    fn encode(&self, w: &mut Writer);
+    fn decode(r: &mut Reader<'_>) -> Option<Self>;
+}
+
+impl Codec for () {
+    fn encode(&self, _w: &mut Writer) {}
+    fn decode(_r: &mut Reader<'_>) -> Option<Self> {
+        Some(())
+    }
+}
+
+impl Codec for bool {
+    fn encode(&self, w: &mut Writer) {
+        w.u8(u8::from(*self));
+    }
+    fn decode(r: &mut Reader<'_>) -> Option<Self> {
+        match r.u8()? {
+            0 => Some(false),
+            1 => Some(true),
+            _ => None,
+        }
+    }
+}
+
+impl Codec for u64 {
+    fn encode(&self, w: &mut Writer) {
+        w.u64(*self);
+    }
+    fn decode(r: &mut Reader<'_>) -> Option<Self> {
+        r.u64()
+    }
+}
+
+impl Codec for u128 {
+    fn encode(&self, w: &mut Writer) {
+        w.u64(*self as u64);
+        w.u64((*self >> 64) as u64);
+    }
+    fn decode(r: &mut Reader<'_>) -> Option<Self> {
+        let lo = r.u64()?;
+        let hi = r.u64()?;
+        Some(u128::from(lo) | (u128::from(hi) << 64))
+    }
+}
+
+impl Codec for Duration {
+    fn encode(&self, w: &mut Writer) {
+        w.u64(self.as_secs());
+        w.u32(self.subsec_nanos());
+    }
+    fn decode(r: &mut Reader<'_>) -> Option<Self> {
+        let secs = r.u64()?;
+        let nanos = r.u32()?;
+        (nanos < 1_000_000_000).then(|| Duration::new(secs, nanos))
+    }
+}
+
+impl Codec for String {
+    fn encode(&self, w: &mut Writer) {
+        w.str(self);
+    }
+    fn decode(r: &mut Reader<'_>) -> Option<Self> {
+        r.string()
+    }
+}
+
+impl<T: Codec> Codec for Option<T> {
+    fn encode(&self, w: &mut Writer) {
+        match self {
+            None => w.u8(0),
+            Some(val) => {
+                w.u8(1);
+                val.encode(w);
+            }
+        }
+    }
+    fn decode(r: &mut Reader<'_>) -> Option<Self> {
+        match r.u8()? {
+            0 => Some(None),
+            1 => Some(Some(T::decode(r)?)),
+            _ => None,
+        }
+    }
+}
+
+impl Codec for SystemTime {
+    fn encode(&self, w: &mut Writer) {
+        match self.duration_since(SystemTime::UNIX_EPOCH) {
+            Ok(after) => {
+                w.u8(0);
+                after.encode(w);
+            }
+            Err(before) => {
+                w.u8(1);
+                before.duration().encode(w);
+            }
+        }
+    }
+    fn decode(r: &mut Reader<'_>) -> Option<Self> {
+        match r.u8()? {
+            0 => SystemTime::UNIX_EPOCH.checked_add(Duration::decode(r)?),
+            1 => SystemTime::UNIX_EPOCH.checked_sub(Duration::decode(r)?),
+            _ => None,
+        }
+    }
+}
+
+impl<T: Codec> Codec for Vec<T> {
+    fn encode(&self, w: &mut Writer) {
+        w.u32(u32::try_from(self.len()).expect("trace entries are limited to 4GiB"));
+        for item in self {
+            item.encode(w);
+        }
+    }
+    fn decode(r: &mut Reader<'_>) -> Option<Self> {
+        let len = r.u32()?;
+        (0..len).map(|_| T::decode(r)).collect()
+    }
+}
+
+/// Errors are recorded as the WASI error code which the guest observes.
+impl<T: Codec> Codec for io::Result<T> {
+    fn encode(&self, w: &mut Writer) {
+        match self {
+            Ok(val) => {
+                w.u8(0);
+                val.encode(w);
+            }
+            Err(e) => {
+                w.u8(1);
+                ErrorCode::from(e).encode(w);
+            }
+        }
+    }
+    fn decode(r: &mut Reader<'_>) -> Option<Self> {
+        match r.u8()? {
+            0 => Some(Ok(T::decode(r)?)),
+            1 => Some(Err(ErrorCode::decode(r)?.into())),
+            _ => None,
+        }
+    }
+}
+
+impl Codec for u8 {
+    fn encode(&self, w: &mut Writer) {
+        w.u8(*self);
+    }
+    fn decode(r: &mut Reader<'_>) -> Option<Self> {
+        r.u8()
+    }
+}
+
+impl Codec for u32 {
+    fn encode(&self, w: &mut Writer) {
+        w.u32(*self);
+    }
+    fn decode(r: &mut Reader<'_>) -> Option<Self> {
+        r.u32()
+    }
+}
+
+impl Codec for usize {
+    fn encode(&self, w: &mut Writer) {
+        w.u64(*self as u64);
+    }
+    fn decode(r: &mut Reader<'_>) -> Option<Self> {
+        usize::try_from(r.u64()?).ok()
+    }
+}
+
+impl Codec for Bytes {
+    fn encode(&self, w: &mut Writer) {
+        w.bytes(self);
+    }
+    fn decode(r: &mut Reader<'_>) -> Option<Self> {
+        Some(Bytes::copy_from_slice(r.bytes()?))
+    }
+}
+
+/// Stream errors are recorded along with their messages, which may be
+/// reported to the guest.
+impl<T: Codec> Codec for StreamResult<T> {
+    fn encode(&self, w: &mut Writer) {
+        match self {
+            Ok(val) => {
+                w.u8(0);
+                val.encode(w);
+            }
+            Err(StreamError::Closed) => w.u8(1),
+            Err(StreamError::LastOperationFailed(e)) => {
+                w.u8(2);
+                w.str(&format!("{e:?}"));
+            }
+            Err(StreamError::Trap(e)) => {
+                w.u8(3);
+                w.str(&format!("{e:?}"));
+            }
+        }
+    }
+    fn decode(r: &mut Reader<'_>) -> Option<Self> {
+        Some(match r.u8()? {
+            0 => Ok(T::decode(r)?),
+            1 => Err(StreamError::Closed),
+            2 => Err(StreamError::LastOperationFailed(anyhow::anyhow!(
+                r.string()?
+            ))),
+            3 => Err(StreamError::Trap(anyhow::anyhow!(r.string()?))),
+            _ => return None,
+        })
+    }
+}
+
+/// Socket errors are recorded as the error code which the guest observes, or
+/// as the message of the trap they raise.
+impl<T: Codec> Codec for SocketResult<T> {
+    fn encode(&self, w: &mut Writer) {
+        match self {
+            Ok(val) => {
+                w.u8(0);
+                val.encode(w);
+            }
+            Err(e) => match e.downcast_ref() {
+                Some(code) => {
+                    w.u8(1);
+                    SocketErrorCode::encode(code, w);
+                }
+                None => {
+                    w.u8(2);
+                    w.str(&format!("{e:?}"));
+                }
+            },
+        }
+    }
+    fn decode(r: &mut Reader<'_>) -> Option<Self> {
+        match r.u8()? {
+            0 => Some(Ok(T::decode(r)?)),
+            1 => Some(Err(SocketErrorCode::decode(r)?.into())),
+            2 => Some(Err(SocketError::trap(anyhow::anyhow!(r.string()?)))),
+            _ => None,
+        }
+    }
+}
+
+const SOCKET_ERROR_CODES: [SocketErrorCode; 21] = [
+    SocketErrorCode::Unknown,
+    SocketErrorCode::AccessDenied,
+    SocketErrorCode::NotSupported,
+    SocketErrorCode::InvalidArgument,
+    SocketErrorCode::OutOfMemory,
+    SocketErrorCode::Timeout,
+    SocketErrorCode::ConcurrencyConflict,
+    SocketErrorCode::NotInProgress,
+    SocketErrorCode::WouldBlock,
+    SocketErrorCode::InvalidState,
+    SocketErrorCode::NewSocketLimit,
+    SocketErrorCode::AddressNotBindable,
+    SocketErrorCode::AddressInUse,
+    SocketErrorCode::RemoteUnreachable,
+    SocketErrorCode::ConnectionRefused,
+    SocketErrorCode::ConnectionReset,
+    SocketErrorCode::ConnectionAborted,
+    SocketErrorCode::DatagramTooLarge,
+    SocketErrorCode::NameUnresolvable,
+    SocketErrorCode::TemporaryResolverFailure,
+    SocketErrorCode::PermanentResolverFailure,
+];
+
+impl Codec for SocketErrorCode {
+    fn encode(&self, w: &mut Writer) {
+        let index = SOCKET_ERROR_CODES.iter().position(|c| c == self).unwrap();
+        w.u8(index as u8);
+    }
+    fn decode(r: &mut Reader<'_>) -> Option<Self> {
+        SOCKET_ERROR_CODES.get(usize::from(r.u8()?)).copied()
+    }
+}
+
+impl Codec for IpAddressFamily {
+    fn encode(&self, w: &mut Writer) {
+        w.u8(match self {
+            IpAddressFamily::Ipv4 => 4,
+            IpAddressFamily::Ipv6 => 6,
+        });
+    }
+    fn decode(r: &mut Reader<'_>) -> Option<Self> {
+        match r.u8()? {
+            4 => Some(IpAddressFamily::Ipv4),
+            6 => Some(IpAddressFamily::Ipv6),
+            _ => None,
+        }
+    }
+}
+
+impl Codec for IpAddress {
+    fn encode(&self, w: &mut Writer) {
+        match *self {
+            IpAddress::Ipv4(addr) => {
+                w.u8(4);
+                w.u32(to_ipv4_addr(addr).into());
+            }
+            IpAddress::Ipv6(addr) => {
+                w.u8(6);
+                u128::from(to_ipv6_addr(addr)).encode(w);
+            }
+        }
+    }
+    fn decode(r: &mut Reader<'_>) -> Option<Self> {
+        let addr = match r.u8()? {
+            4 => IpAddr::V4(r.u32()?.into()),
+            6 => IpAddr::V6(u128::decode(r)?.into()),
+            _ => return None,
+        };
+        Some(addr.into())
+    }
+}
+
+impl Codec for IpSocketAddress {
+    fn encode(&self, w: &mut Writer) {
+        match SocketAddr::from(*self) {
+            SocketAddr::V4(addr) => {
+                w.u8(4);
+                w.u32((*addr.ip()).into());
+                w.u32(addr.port().into());
+            }
+            SocketAddr::V6(addr) => {
+                w.u8(6);
+                u128::from(*addr.ip()).encode(w);
+                w.u32(addr.port().into());
+                w.u32(addr.flowinfo());
+                w.u32(addr.scope_id());
+            }
+        }
+    }
+    fn decode(r: &mut Reader<'_>) -> Option<Self> {
+        let addr = match r.u8()? {
+            4 => {
+                let ip = r.u32()?.into();
+                let port = u16::try_from(r.u32()?).ok()?;
+                SocketAddr::V4(SocketAddrV4::new(ip, port))
+            }
+            6 => {
+                let ip = u128::decode(r)?.into();
+                let port = u16::try_from(r.u32()?).ok()?;
+                SocketAddr::V6(SocketAddrV6::new(ip, port, r.u32()?, r.u32()?))
+            }
+            _ => return None,
+        };
+        Some(addr.into())
+    }
+}
+
+impl Codec for ShutdownType {
+    fn encode(&self, w: &mut Writer) {
+        w.u8(match self {
+            ShutdownType::Receive => 0,
+            ShutdownType::Send => 1,
+            ShutdownType::Both => 2,
+        });
+    }
+    fn decode(r: &mut Reader<'_>) -> Option<Self> {
+        match r.u8()? {
+            0 => Some(ShutdownType::Receive),
+            1 => Some(ShutdownType::Send),
+            2 => Some(ShutdownType::Both),
+            _ => None,
+        }
+    }
+}
+
+impl Codec for IncomingDatagram {
+    fn encode(&self, w: &mut Writer) {
+        w.bytes(&self.data);
+        self.remote_address.encode(w);
+    }
+    fn decode(r: &mut Reader<'_>) -> Option<Self> {
+        Some(IncomingDatagram {
+            data: r.bytes()?.to_vec(),
+            remote_address: IpSocketAddress::decode(r)?,
+        })
+    }
+}
+
+/// Outgoing datagrams are only recorded as the arguments of `send` calls, so
+/// their data is left out and only its length is checked while replaying.
+impl Codec for OutgoingDatagram {
+    fn encode(&self, w: &mut Writer) {
+        w.u64(self.data.len() as u64);
+        self.remote_address.encode(w);
+    }
+    fn decode(_r: &mut Reader<'_>) -> Option<Self> {
+        None
+    }
+}
+
+const ERROR_CODES: [ErrorCode; 37] = [
+    ErrorCode::Access,
+    ErrorCode::WouldBlock,
+    ErrorCode::Already,
+    ErrorCode::BadDescriptor,
+    ErrorCode::Busy,
+    ErrorCode::Deadlock,
+    ErrorCode::Quota,
+    ErrorCode::Exist,
+    ErrorCode::FileTooLarge,
+    ErrorCode::IllegalByteSequence,
+    ErrorCode::InProgress,
+    ErrorCode::Interrupted,
+    ErrorCode::Invalid,
+    ErrorCode::Io,
+    ErrorCode::IsDirectory,
+    ErrorCode::Loop,
+    ErrorCode::TooManyLinks,
+    ErrorCode::MessageSize,
+    ErrorCode::NameTooLong,
+    ErrorCode::NoDevice,
+    ErrorCode::NoEntry,
+    ErrorCode::NoLock,
+    ErrorCode::InsufficientMemory,
+    ErrorCode::InsufficientSpace,
+    ErrorCode::NotDirectory,
+    ErrorCode::NotEmpty,
+    ErrorCode::NotRecoverable,
+    ErrorCode::Unsupported,
+    ErrorCode::NoTty,
+    ErrorCode::NoSuchDevice,
+    ErrorCode::Overflow,
+    ErrorCode::NotPermitted,
+    ErrorCode::Pipe,
+    ErrorCode::ReadOnly,
+    ErrorCode::InvalidSeek,
+    ErrorCode::TextFileBusy,
+    ErrorCode::CrossDevice,
+];
+
+impl Codec for ErrorCode {
+    fn encode(&self, w: &mut Writer) {
+        let index = ERROR_CODES.iter().position(|c| c == self).unwrap();
+        w.u8(index as u8);
+    }
+    fn decode(r: &mut Reader<'_>) -> Option<Self> {
+        ERROR_CODES.get(usize::from(r.u8()?)).copied()
+    }
+}
+
+const DESCRIPTOR_TYPES: [DescriptorType; 8] = [
+    DescriptorType::Unknown,
+    DescriptorType::BlockDevice,
+    DescriptorType::CharacterDevice,
+    DescriptorType::Directory,
+    DescriptorType::Fifo,
+    DescriptorType::SymbolicLink,
+    DescriptorType::RegularFile,
+    DescriptorType::Socket,
+];
+
+impl Codec for DescriptorType {
+    fn encode(&self, w: &mut Writer) {
+        let index = DESCRIPTOR_TYPES.iter().position(|t| t == self).unwrap();
+        w.u8(index as u8);
+    }
+    fn decode(r: &mut Reader<'_>) -> Option<Self> {
+        DESCRIPTOR_TYPES.get(usize::from(r.u8()?)).copied()
+    }
+}
+
+const DESCRIPTOR_FLAGS: [DescriptorFlags; 6] = [
+    DescriptorFlags::READ,
+    DescriptorFlags::WRITE,
+    DescriptorFlags::FILE_INTEGRITY_SYNC,
+    DescriptorFlags::DATA_INTEGRITY_SYNC,
+    DescriptorFlags::REQUESTED_WRITE_SYNC,
+    DescriptorFlags::MUTATE_DIRECTORY,
+];
+
+impl Codec for DescriptorFlags {
+    fn encode(&self, w: &mut Writer) {
+        let mut bits = 0;
+        for (i, flag) in DESCRIPTOR_FLAGS.iter().enumerate() {
+            if self.contains(*flag) {
+                bits |= 1 << i;
+            }
+        }
+        w.u8(bits);
+    }
+    fn decode(r: &mut Reader<'_>) -> Option<Self> {
+        let bits = r.u8()?;
+        let mut flags = DescriptorFlags::empty();
+        for (i, flag) in DESCRIPTOR_FLAGS.iter().enumerate() {
+            if bits & (1 << i) != 0 {
+                flags |= *flag;
+            }
+        }
+        Some(flags)
+    }
+}
+
+impl Codec for DirectoryEntry {
+    fn encode(&self, w: &mut Writer) {
+        self.type_.encode(w);
+        w.str(&self.name);
+    }
+    fn decode(r: &mut Reader<'_>) -> Option<Self> {
+        Some(DirectoryEntry {
+            type_: DescriptorType::decode(r)?,
+            name: r.string()?,
+        })
+    }
+}
+
+impl Codec for Metadata {
+    fn encode(&self, w: &mut Writer) {
+        self.type_.encode(w);
+        w.u64(self.link_count);
+        w.u64(self.size);
+        self.accessed.encode(w);
+        self.modified.encode(w);
+        self.status_changed.encode(w);
+        w.u64(self.dev);
+        w.u64(self.ino);
+    }
+    fn decode(r: &mut Reader<'_>) -> Option<Self> {
+        Some(Metadata {
+            type_: DescriptorType::decode(r)?,
+            link_count: r.u64()?,
+            size: r.u64()?,
+            accessed: Option::decode(r)?,
+            modified: Option::decode(r)?,
+            status_changed: Option::decode(r)?,
+            dev: r.u64()?,
+            ino: r.u64()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::preview2::MemoryFilesystem;
+
+    fn record(f: impl FnOnce(&Trace)) -> Vec<u8> {
+        #[derive(Clone, Default)]
+        struct Shared(Arc<Mutex<Vec<u8>>>);
+        impl Write for Shared {
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                self.0.lock().unwrap().extend_from_slice(buf);
+                Ok(buf.len())
+            }
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let out = Shared::default();
+        let recorder = Recorder::new(out.clone()).unwrap();
+        f(&Trace::Record(recorder.clone()));
+        recorder.flush().unwrap();
+        let trace = out.0.lock().unwrap().clone();
+        trace
+    }
+
+    struct Counter(std::sync::atomic::AtomicU64);
+
+    impl HostMonotonicClock for Counter {
+        fn resolution(&self) -> u64 {
+            1
+        }
+        fn now(&self) -> u64 {
+            self.0.fetch_add(1, std::sync::atomic::Ordering::SeqCst)
+        }
+    }
+
+    fn counter(start: u64) -> Box<dyn HostMonotonicClock + Send> {
+        Box::new(Counter(start.into()))
+    }
+
+    fn rng(seed: u8) -> Box<dyn RngCore + Send> {
+        Box::new(crate::preview2::Deterministic::new(vec![seed, seed + 1]))
+    }
+
+    #[test]
+    fn clocks_and_random() {
+        let trace = record(|trace| {
+            let clock = trace.monotonic_clock(counter(100));
+            let mut random = trace.random(rng(1));
+            assert_eq!(clock.now(), 100);
+            assert_eq!(random.next_u32(), 0x02010201);
+            let mut buf = [0; 3];
+            random.fill_bytes(&mut buf);
+            assert_eq!(buf, [1, 2, 1]);
+            assert_eq!(clock.now(), 101);
+            assert_eq!(trace.insecure_random_seed(7), 7);
+        });
+
+        let replayer = Replayer::new(trace).unwrap();
+        let trace = Trace::Replay(replayer.clone());
+        let clock = trace.monotonic_clock(counter(0));
+        let mut random = trace.random(rng(50));
+        assert_eq!(clock.now(), 100);
+        let mut buf = [0; 7];
+        random.fill_bytes(&mut buf);
+        assert_eq!(buf, [1, 2, 1, 2, 1, 2, 1]);
+        assert_eq!(clock.now(), 101);
+        assert_eq!(trace.insecure_random_seed(8), 7);
+        replayer.finish().unwrap();
+    }
+
+    #[test]
+    fn divergence() {
+        let trace = record(|trace| {
+            let clock = trace.monotonic_clock(counter(100));
+            clock.now();
+            clock.resolution();
+        });
+
+        // A different call.
+        let replayer = Replayer::new(trace.clone()).unwrap();
+        let clock = Trace::Replay(replayer.clone()).monotonic_clock(counter(5));
+        assert_eq!(clock.resolution(), 1);
+        assert_eq!(clock.now(), 5);
+        let err = replayer.finish().unwrap_err();
+        assert!(err.to_string().contains("at event 0"), "{err}");
+
+        // Stopping early.
+        let replayer = Replayer::new(trace.clone()).unwrap();
+        let clock = Trace::Replay(replayer.clone()).monotonic_clock(counter(5));
+        assert_eq!(clock.now(), 100);
+        let err = replayer.finish().unwrap_err();
+        assert!(err.to_string().contains("stopped before"), "{err}");
+
+        // Continuing past the end.
+        let replayer = Replayer::new(trace).unwrap();
+        let clock = Trace::Replay(replayer.clone()).monotonic_clock(counter(5));
+        clock.now();
+        clock.resolution();
+        clock.now();
+        let err = replayer.finish().unwrap_err();
+        assert!(err.to_string().contains("trace ended"), "{err}");
+
+        assert!(Replayer::new(b"not a trace".to_vec()).is_err());
+    }
+
+    #[test]
+    fn filesystem() {
+        let options = OpenOptions {
+            read: true,
+            follow_symlinks: true,
+            ..OpenOptions::default()
+        };
+        let read = |fs: &dyn HostFilesystem| {
+            let file = match fs.open_at("dir/file", &options).unwrap() {
+                Opened::File(file) => file,
+                Opened::Dir(_) => unreachable!(),
+            };
+            let mut buf = [0; 16];
+            let n = file.read_at(&mut buf, 2).unwrap();
+            assert_eq!(&buf[..n], b"llo");
+            assert_eq!(file.metadata().unwrap().size, 5);
+            let err = fs.metadata_at("missing", false).unwrap_err();
+            assert_eq!(ErrorCode::from(err), ErrorCode::NoEntry);
+            let dir = match fs.open_at("dir", &options).unwrap() {
+                Opened::Dir(dir) => dir,
+                Opened::File(_) => unreachable!(),
+            };
+            let entries = dir.read_dir().unwrap();
+            assert_eq!(entries.len(), 1);
+            assert_eq!(entries[0].as_ref().unwrap().name, "file");
+        };
+
+        let trace = record(|trace| {
+            let fs = MemoryFilesystem::new();
+            fs.insert_file("dir/file", "hello").unwrap();
+            read(&*trace.filesystem(Arc::new(fs)));
+        });
+
+        // The replayed filesystem doesn't need the original contents.
+        let replayer = Replayer::new(trace).unwrap();
+        let fs = Trace::Replay(replayer.clone()).filesystem(Arc::new(MemoryFilesystem::new()));
+        read(&*fs);
+        replayer.finish().unwrap();
+    }
+
+    #[test]
+    fn sockets() {
+        let connect = |trace: &Trace, live: bool| {
+            let addr = IpSocketAddress::from("127.0.0.1:80".parse::<SocketAddr>().unwrap());
+            let refused = socket(
+                Some(trace.clone()),
+                SocketOp::TcpStartConnect,
+                &addr,
+                || {
+                    assert!(live, "replayed calls never reach the host");
+                    Err(SocketErrorCode::ConnectionRefused.into())
+                },
+            );
+            assert_eq!(
+                refused.unwrap_err().downcast().unwrap(),
+                SocketErrorCode::ConnectionRefused
+            );
+
+            let connected =
+                socket_resources(Some(trace.clone()), SocketOp::TcpFinishConnect, &(), || {
+                    assert!(live, "replayed calls never reach the host");
+                    Ok(())
+                })
+                .unwrap();
+            assert_eq!(connected.is_some(), live);
+            let inner = connected.map(|()| {
+                let input: Box<dyn HostInputStream> =
+                    Box::new(crate::preview2::pipe::MemoryInputPipe::new("hello".into()));
+                let output: Box<dyn HostOutputStream> =
+                    Box::new(crate::preview2::pipe::MemoryOutputPipe::new(4));
+                (input, output)
+            });
+            let (mut input, mut output) = trace.socket_streams(inner);
+            assert_eq!(&input.read(3).unwrap()[..], b"hel");
+            assert_eq!(output.check_write().unwrap(), 4);
+            output.write(Bytes::from_static(b"hi")).unwrap();
+            assert!(output.write(Bytes::from_static(b"world")).is_err());
+            assert_eq!(&input.read(16).unwrap()[..], b"lo");
+            assert!(matches!(input.read(16), Err(StreamError::Closed)));
+        };
+
+        let trace = record(|trace| connect(trace, true));
+
+        // Replaying produces the same results without a connection.
+        let replayer = Replayer::new(trace).unwrap();
+        connect(&Trace::Replay(replayer.clone()), false);
+        replayer.finish().unwrap();
+    }
+}
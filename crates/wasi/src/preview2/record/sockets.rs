@@ -0,0 +1,633 @@
+//! Recording and replaying of `wasi:sockets` calls.
+//!
+//! The host implementations of `wasi:sockets` know nothing about traces.
+//! Instead [`TracedSockets`] implements the same host traits by passing each
+//! call on to them, recording its result or replaying it from the context's
+//! trace along the way.
+
+use super::{socket, socket_resources, Codec, SocketOp, Trace};
+use crate::preview2::bindings::io::streams::{InputStream, OutputStream};
+use crate::preview2::bindings::sockets::ip_name_lookup;
+use crate::preview2::bindings::sockets::network::{
+    IpAddress, IpAddressFamily, IpSocketAddress, Network,
+};
+use crate::preview2::bindings::sockets::tcp::{self, ShutdownType};
+use crate::preview2::bindings::sockets::{tcp_create_socket, udp, udp_create_socket};
+use crate::preview2::ip_name_lookup::ResolveAddressStream;
+use crate::preview2::network::SocketAddressFamily;
+use crate::preview2::pipe::{ClosedInputStream, ClosedOutputStream};
+use crate::preview2::tcp::TcpSocket;
+use crate::preview2::udp::{IncomingDatagramStream, OutgoingDatagramStream, SendState, UdpSocket};
+use crate::preview2::{subscribe, Pollable, SocketError, SocketResult, Subscribe, WasiView};
+use std::mem;
+use std::net::SocketAddr;
+use wasmtime::component::Resource;
+
+/// A [`WasiView`] whose `wasi:sockets` calls are recorded or replayed when its
+/// context has a trace, and passed straight through otherwise.
+///
+/// This is what the socket interfaces are added to a linker with, in place of
+/// the view itself.
+#[repr(transparent)]
+pub(crate) struct TracedSockets<T>(T);
+
+impl<T: WasiView> TracedSockets<T> {
+    /// Wraps `view`, for use as the host of `add_to_linker`.
+    pub(crate) fn new(view: &mut T) -> &mut Self {
+        // SAFETY: `TracedSockets` is a transparent wrapper around `T`.
+        unsafe { &mut *(view as *mut T).cast::<Self>() }
+    }
+
+    fn trace(&mut self) -> Option<Trace> {
+        self.0.ctx().trace.clone()
+    }
+
+    /// Makes the call `op` with the arguments `args` through `live`.
+    fn call<R: Codec>(
+        &mut self,
+        op: SocketOp,
+        args: &impl Codec,
+        live: impl FnOnce(&mut T) -> SocketResult<R>,
+    ) -> SocketResult<R> {
+        let trace = self.trace();
+        socket(trace, op, args, || live(&mut self.0))
+    }
+
+    /// Like `call`, for calls which create resources. Returns `None` when a
+    /// successful call is replayed, in which case the caller creates
+    /// placeholders for the resources.
+    fn call_resources<R>(
+        &mut self,
+        op: SocketOp,
+        args: &impl Codec,
+        live: impl FnOnce(&mut T) -> SocketResult<R>,
+    ) -> SocketResult<Option<R>> {
+        let trace = self.trace();
+        socket_resources(trace, op, args, || live(&mut self.0))
+    }
+
+    /// Subscribes to a socket resource through `live`, or to nothing at all
+    /// while replaying.
+    fn subscribe_with(
+        &mut self,
+        live: impl FnOnce(&mut T) -> anyhow::Result<Resource<Pollable>>,
+    ) -> anyhow::Result<Resource<Pollable>> {
+        if matches!(self.0.ctx().trace, Some(Trace::Replay(_))) {
+            let ready = self.0.table().push(Ready)?;
+            return subscribe(self.0.table(), ready);
+        }
+        live(&mut self.0)
+    }
+
+    /// Traces the streams of a new connection, or creates placeholders for
+    /// them as children of `socket` if the connection was replayed.
+    fn connection_streams(
+        &mut self,
+        streams: Option<(Resource<InputStream>, Resource<OutputStream>)>,
+        socket: &Resource<TcpSocket>,
+    ) -> SocketResult<(Resource<InputStream>, Resource<OutputStream>)> {
+        let trace = match self.trace() {
+            Some(trace) => trace,
+            None => return Ok(streams.expect("connections are only replayed from a trace")),
+        };
+        let table = self.0.table();
+        match streams {
+            Some((input, output)) => {
+                let live_input = match mem::replace(
+                    table.get_mut(&input)?,
+                    InputStream::Host(Box::new(ClosedInputStream)),
+                ) {
+                    InputStream::Host(stream) => stream,
+                    InputStream::File(_) => unreachable!("connections aren't files"),
+                };
+                let live_output =
+                    mem::replace(table.get_mut(&output)?, Box::new(ClosedOutputStream));
+                let (traced_input, traced_output) =
+                    trace.socket_streams(Some((live_input, live_output)));
+                *table.get_mut(&input)? = InputStream::Host(traced_input);
+                *table.get_mut(&output)? = traced_output;
+                Ok((input, output))
+            }
+            None => {
+                let (input, output) = trace.socket_streams(None);
+                let input = table.push_child(InputStream::Host(input), socket)?;
+                let output = table.push_child(output, socket)?;
+                Ok((input, output))
+            }
+        }
+    }
+}
+
+/// What socket pollables are subscribed to while replaying. It's always
+/// ready, as the guest observes the recorded results of its calls rather than
+/// waiting on the host.
+struct Ready;
+
+#[async_trait::async_trait]
+impl Subscribe for Ready {
+    async fn ready(&mut self) {}
+}
+
+fn socket_address_family(family: IpAddressFamily) -> SocketAddressFamily {
+    match family {
+        IpAddressFamily::Ipv4 => SocketAddressFamily::Ipv4,
+        IpAddressFamily::Ipv6 => SocketAddressFamily::Ipv6,
+    }
+}
+
+impl<T: WasiView> tcp_create_socket::Host for TracedSockets<T> {
+    fn create_tcp_socket(
+        &mut self,
+        address_family: IpAddressFamily,
+    ) -> SocketResult<Resource<TcpSocket>> {
+        let socket = self.call_resources(SocketOp::TcpCreate, &address_family, |view| {
+            tcp_create_socket::Host::create_tcp_socket(view, address_family)
+        })?;
+        match socket {
+            Some(socket) => Ok(socket),
+            None => {
+                let socket = TcpSocket::replayed(socket_address_family(address_family));
+                Ok(self.0.table().push(socket)?)
+            }
+        }
+    }
+}
+
+impl<T: WasiView> tcp::Host for TracedSockets<T> {}
+
+impl<T: WasiView> tcp::HostTcpSocket for TracedSockets<T> {
+    fn start_bind(
+        &mut self,
+        this: Resource<TcpSocket>,
+        network: Resource<Network>,
+        local_address: IpSocketAddress,
+    ) -> SocketResult<()> {
+        self.call(SocketOp::TcpStartBind, &local_address, |view| {
+            tcp::HostTcpSocket::start_bind(view, this, network, local_address)
+        })
+    }
+
+    fn finish_bind(&mut self, this: Resource<TcpSocket>) -> SocketResult<()> {
+        self.call(SocketOp::TcpFinishBind, &(), |view| {
+            tcp::HostTcpSocket::finish_bind(view, this)
+        })
+    }
+
+    fn start_connect(
+        &mut self,
+        this: Resource<TcpSocket>,
+        network: Resource<Network>,
+        remote_address: IpSocketAddress,
+    ) -> SocketResult<()> {
+        self.call(SocketOp::TcpStartConnect, &remote_address, |view| {
+            tcp::HostTcpSocket::start_connect(view, this, network, remote_address)
+        })
+    }
+
+    fn finish_connect(
+        &mut self,
+        this: Resource<TcpSocket>,
+    ) -> SocketResult<(Resource<InputStream>, Resource<OutputStream>)> {
+        let socket = Resource::<TcpSocket>::new_borrow(this.rep());
+        let streams = self.call_resources(SocketOp::TcpFinishConnect, &(), |view| {
+            tcp::HostTcpSocket::finish_connect(view, this)
+        })?;
+        self.connection_streams(streams, &socket)
+    }
+
+    fn start_listen(&mut self, this: Resource<TcpSocket>) -> SocketResult<()> {
+        self.call(SocketOp::TcpStartListen, &(), |view| {
+            tcp::HostTcpSocket::start_listen(view, this)
+        })
+    }
+
+    fn finish_listen(&mut self, this: Resource<TcpSocket>) -> SocketResult<()> {
+        self.call(SocketOp::TcpFinishListen, &(), |view| {
+            tcp::HostTcpSocket::finish_listen(view, this)
+        })
+    }
+
+    fn accept(
+        &mut self,
+        this: Resource<TcpSocket>,
+    ) -> SocketResult<(
+        Resource<TcpSocket>,
+        Resource<InputStream>,
+        Resource<OutputStream>,
+    )> {
+        let listener = Resource::<TcpSocket>::new_borrow(this.rep());
+        let accepted = self.call_resources(SocketOp::TcpAccept, &(), |view| {
+            tcp::HostTcpSocket::accept(view, this)
+        })?;
+        let (client, streams) = match accepted {
+            Some((client, input, output)) => (client, Some((input, output))),
+            None => {
+                let family = self.0.table().get(&listener)?.family;
+                (self.0.table().push(TcpSocket::replayed(family))?, None)
+            }
+        };
+        let (input, output) = self.connection_streams(streams, &client)?;
+        Ok((client, input, output))
+    }
+
+    fn local_address(&mut self, this: Resource<TcpSocket>) -> SocketResult<IpSocketAddress> {
+        self.call(SocketOp::TcpLocalAddress, &(), |view| {
+            tcp::HostTcpSocket::local_address(view, this)
+        })
+    }
+
+    fn remote_address(&mut self, this: Resource<TcpSocket>) -> SocketResult<IpSocketAddress> {
+        self.call(SocketOp::TcpRemoteAddress, &(), |view| {
+            tcp::HostTcpSocket::remote_address(view, this)
+        })
+    }
+
+    fn is_listening(&mut self, this: Resource<TcpSocket>) -> Result<bool, anyhow::Error> {
+        let listening = self.call(SocketOp::TcpIsListening, &(), |view| {
+            tcp::HostTcpSocket::is_listening(view, this).map_err(SocketError::trap)
+        })?;
+        Ok(listening)
+    }
+
+    fn address_family(
+        &mut self,
+        this: Resource<TcpSocket>,
+    ) -> Result<IpAddressFamily, anyhow::Error> {
+        tcp::HostTcpSocket::address_family(&mut self.0, this)
+    }
+
+    fn set_listen_backlog_size(
+        &mut self,
+        this: Resource<TcpSocket>,
+        value: u64,
+    ) -> SocketResult<()> {
+        self.call(SocketOp::TcpSetListenBacklogSize, &value, |view| {
+            tcp::HostTcpSocket::set_listen_backlog_size(view, this, value)
+        })
+    }
+
+    fn keep_alive_enabled(&mut self, this: Resource<TcpSocket>) -> SocketResult<bool> {
+        self.call(SocketOp::TcpKeepAliveEnabled, &(), |view| {
+            tcp::HostTcpSocket::keep_alive_enabled(view, this)
+        })
+    }
+
+    fn set_keep_alive_enabled(
+        &mut self,
+        this: Resource<TcpSocket>,
+        value: bool,
+    ) -> SocketResult<()> {
+        self.call(SocketOp::TcpSetKeepAliveEnabled, &value, |view| {
+            tcp::HostTcpSocket::set_keep_alive_enabled(view, this, value)
+        })
+    }
+
+    fn keep_alive_idle_time(&mut self, this: Resource<TcpSocket>) -> SocketResult<u64> {
+        self.call(SocketOp::TcpKeepAliveIdleTime, &(), |view| {
+            tcp::HostTcpSocket::keep_alive_idle_time(view, this)
+        })
+    }
+
+    fn set_keep_alive_idle_time(
+        &mut self,
+        this: Resource<TcpSocket>,
+        value: u64,
+    ) -> SocketResult<()> {
+        self.call(SocketOp::TcpSetKeepAliveIdleTime, &value, |view| {
+            tcp::HostTcpSocket::set_keep_alive_idle_time(view, this, value)
+        })
+    }
+
+    fn keep_alive_interval(&mut self, this: Resource<TcpSocket>) -> SocketResult<u64> {
+        self.call(SocketOp::TcpKeepAliveInterval, &(), |view| {
+            tcp::HostTcpSocket::keep_alive_interval(view, this)
+        })
+    }
+
+    fn set_keep_alive_interval(
+        &mut self,
+        this: Resource<TcpSocket>,
+        value: u64,
+    ) -> SocketResult<()> {
+        self.call(SocketOp::TcpSetKeepAliveInterval, &value, |view| {
+            tcp::HostTcpSocket::set_keep_alive_interval(view, this, value)
+        })
+    }
+
+    fn keep_alive_count(&mut self, this: Resource<TcpSocket>) -> SocketResult<u32> {
+        self.call(SocketOp::TcpKeepAliveCount, &(), |view| {
+            tcp::HostTcpSocket::keep_alive_count(view, this)
+        })
+    }
+
+    fn set_keep_alive_count(&mut self, this: Resource<TcpSocket>, value: u32) -> SocketResult<()> {
+        self.call(SocketOp::TcpSetKeepAliveCount, &value, |view| {
+            tcp::HostTcpSocket::set_keep_alive_count(view, this, value)
+        })
+    }
+
+    fn hop_limit(&mut self, this: Resource<TcpSocket>) -> SocketResult<u8> {
+        self.call(SocketOp::TcpHopLimit, &(), |view| {
+            tcp::HostTcpSocket::hop_limit(view, this)
+        })
+    }
+
+    fn set_hop_limit(&mut self, this: Resource<TcpSocket>, value: u8) -> SocketResult<()> {
+        self.call(SocketOp::TcpSetHopLimit, &value, |view| {
+            tcp::HostTcpSocket::set_hop_limit(view, this, value)
+        })
+    }
+
+    fn receive_buffer_size(&mut self, this: Resource<TcpSocket>) -> SocketResult<u64> {
+        self.call(SocketOp::TcpReceiveBufferSize, &(), |view| {
+            tcp::HostTcpSocket::receive_buffer_size(view, this)
+        })
+    }
+
+    fn set_receive_buffer_size(
+        &mut self,
+        this: Resource<TcpSocket>,
+        value: u64,
+    ) -> SocketResult<()> {
+        self.call(SocketOp::TcpSetReceiveBufferSize, &value, |view| {
+            tcp::HostTcpSocket::set_receive_buffer_size(view, this, value)
+        })
+    }
+
+    fn send_buffer_size(&mut self, this: Resource<TcpSocket>) -> SocketResult<u64> {
+        self.call(SocketOp::TcpSendBufferSize, &(), |view| {
+            tcp::HostTcpSocket::send_buffer_size(view, this)
+        })
+    }
+
+    fn set_send_buffer_size(&mut self, this: Resource<TcpSocket>, value: u64) -> SocketResult<()> {
+        self.call(SocketOp::TcpSetSendBufferSize, &value, |view| {
+            tcp::HostTcpSocket::set_send_buffer_size(view, this, value)
+        })
+    }
+
+    fn subscribe(&mut self, this: Resource<TcpSocket>) -> anyhow::Result<Resource<Pollable>> {
+        self.subscribe_with(|view| tcp::HostTcpSocket::subscribe(view, this))
+    }
+
+    fn shutdown(
+        &mut self,
+        this: Resource<TcpSocket>,
+        shutdown_type: ShutdownType,
+    ) -> SocketResult<()> {
+        self.call(SocketOp::TcpShutdown, &shutdown_type, |view| {
+            tcp::HostTcpSocket::shutdown(view, this, shutdown_type)
+        })
+    }
+
+    fn drop(&mut self, this: Resource<TcpSocket>) -> Result<(), anyhow::Error> {
+        tcp::HostTcpSocket::drop(&mut self.0, this)
+    }
+}
+
+impl<T: WasiView> udp_create_socket::Host for TracedSockets<T> {
+    fn create_udp_socket(
+        &mut self,
+        address_family: IpAddressFamily,
+    ) -> SocketResult<Resource<UdpSocket>> {
+        let socket = self.call_resources(SocketOp::UdpCreate, &address_family, |view| {
+            udp_create_socket::Host::create_udp_socket(view, address_family)
+        })?;
+        match socket {
+            Some(socket) => Ok(socket),
+            None => {
+                // A replayed socket is never bound or connected, but datagram
+                // streams still need a host socket to refer to.
+                let socket = UdpSocket::new(address_family.into()).map_err(SocketError::trap)?;
+                Ok(self.0.table().push(socket)?)
+            }
+        }
+    }
+}
+
+impl<T: WasiView> udp::Host for TracedSockets<T> {}
+
+impl<T: WasiView> udp::HostUdpSocket for TracedSockets<T> {
+    fn start_bind(
+        &mut self,
+        this: Resource<UdpSocket>,
+        network: Resource<Network>,
+        local_address: IpSocketAddress,
+    ) -> SocketResult<()> {
+        self.call(SocketOp::UdpStartBind, &local_address, |view| {
+            udp::HostUdpSocket::start_bind(view, this, network, local_address)
+        })
+    }
+
+    fn finish_bind(&mut self, this: Resource<UdpSocket>) -> SocketResult<()> {
+        self.call(SocketOp::UdpFinishBind, &(), |view| {
+            udp::HostUdpSocket::finish_bind(view, this)
+        })
+    }
+
+    fn stream(
+        &mut self,
+        this: Resource<UdpSocket>,
+        remote_address: Option<IpSocketAddress>,
+    ) -> SocketResult<(
+        Resource<IncomingDatagramStream>,
+        Resource<OutgoingDatagramStream>,
+    )> {
+        let socket = Resource::<UdpSocket>::new_borrow(this.rep());
+        let streams = self.call_resources(SocketOp::UdpStream, &remote_address, |view| {
+            udp::HostUdpSocket::stream(view, this, remote_address)
+        })?;
+        if let Some(streams) = streams {
+            return Ok(streams);
+        }
+
+        let table = self.0.table();
+        let udp_socket = table.get(&socket)?;
+        let remote_address = remote_address.map(SocketAddr::from);
+        let incoming_stream = IncomingDatagramStream {
+            inner: udp_socket.inner.clone(),
+            remote_address,
+        };
+        let outgoing_stream = OutgoingDatagramStream {
+            inner: udp_socket.inner.clone(),
+            remote_address,
+            family: udp_socket.family,
+            send_state: SendState::Idle,
+            socket_addr_check: udp_socket.socket_addr_check.clone(),
+        };
+        Ok((
+            table.push_child(incoming_stream, &socket)?,
+            table.push_child(outgoing_stream, &socket)?,
+        ))
+    }
+
+    fn local_address(&mut self, this: Resource<UdpSocket>) -> SocketResult<IpSocketAddress> {
+        self.call(SocketOp::UdpLocalAddress, &(), |view| {
+            udp::HostUdpSocket::local_address(view, this)
+        })
+    }
+
+    fn remote_address(&mut self, this: Resource<UdpSocket>) -> SocketResult<IpSocketAddress> {
+        self.call(SocketOp::UdpRemoteAddress, &(), |view| {
+            udp::HostUdpSocket::remote_address(view, this)
+        })
+    }
+
+    fn address_family(
+        &mut self,
+        this: Resource<UdpSocket>,
+    ) -> Result<IpAddressFamily, anyhow::Error> {
+        udp::HostUdpSocket::address_family(&mut self.0, this)
+    }
+
+    fn unicast_hop_limit(&mut self, this: Resource<UdpSocket>) -> SocketResult<u8> {
+        self.call(SocketOp::UdpUnicastHopLimit, &(), |view| {
+            udp::HostUdpSocket::unicast_hop_limit(view, this)
+        })
+    }
+
+    fn set_unicast_hop_limit(&mut self, this: Resource<UdpSocket>, value: u8) -> SocketResult<()> {
+        self.call(SocketOp::UdpSetUnicastHopLimit, &value, |view| {
+            udp::HostUdpSocket::set_unicast_hop_limit(view, this, value)
+        })
+    }
+
+    fn receive_buffer_size(&mut self, this: Resource<UdpSocket>) -> SocketResult<u64> {
+        self.call(SocketOp::UdpReceiveBufferSize, &(), |view| {
+            udp::HostUdpSocket::receive_buffer_size(view, this)
+        })
+    }
+
+    fn set_receive_buffer_size(
+        &mut self,
+        this: Resource<UdpSocket>,
+        value: u64,
+    ) -> SocketResult<()> {
+        self.call(SocketOp::UdpSetReceiveBufferSize, &value, |view| {
+            udp::HostUdpSocket::set_receive_buffer_size(view, this, value)
+        })
+    }
+
+    fn send_buffer_size(&mut self, this: Resource<UdpSocket>) -> SocketResult<u64> {
+        self.call(SocketOp::UdpSendBufferSize, &(), |view| {
+            udp::HostUdpSocket::send_buffer_size(view, this)
+        })
+    }
+
+    fn set_send_buffer_size(&mut self, this: Resource<UdpSocket>, value: u64) -> SocketResult<()> {
+        self.call(SocketOp::UdpSetSendBufferSize, &value, |view| {
+            udp::HostUdpSocket::set_send_buffer_size(view, this, value)
+        })
+    }
+
+    fn subscribe(&mut self, this: Resource<UdpSocket>) -> anyhow::Result<Resource<Pollable>> {
+        self.subscribe_with(|view| udp::HostUdpSocket::subscribe(view, this))
+    }
+
+    fn drop(&mut self, this: Resource<UdpSocket>) -> Result<(), anyhow::Error> {
+        udp::HostUdpSocket::drop(&mut self.0, this)
+    }
+}
+
+impl<T: WasiView> udp::HostIncomingDatagramStream for TracedSockets<T> {
+    fn receive(
+        &mut self,
+        this: Resource<IncomingDatagramStream>,
+        max_results: u64,
+    ) -> SocketResult<Vec<udp::IncomingDatagram>> {
+        self.call(SocketOp::UdpReceive, &max_results, |view| {
+            udp::HostIncomingDatagramStream::receive(view, this, max_results)
+        })
+    }
+
+    fn subscribe(
+        &mut self,
+        this: Resource<IncomingDatagramStream>,
+    ) -> anyhow::Result<Resource<Pollable>> {
+        self.subscribe_with(|view| udp::HostIncomingDatagramStream::subscribe(view, this))
+    }
+
+    fn drop(&mut self, this: Resource<IncomingDatagramStream>) -> Result<(), anyhow::Error> {
+        udp::HostIncomingDatagramStream::drop(&mut self.0, this)
+    }
+}
+
+impl<T: WasiView> udp::HostOutgoingDatagramStream for TracedSockets<T> {
+    fn check_send(&mut self, this: Resource<OutgoingDatagramStream>) -> SocketResult<u64> {
+        self.call(SocketOp::UdpCheckSend, &(), |view| {
+            udp::HostOutgoingDatagramStream::check_send(view, this)
+        })
+    }
+
+    fn send(
+        &mut self,
+        this: Resource<OutgoingDatagramStream>,
+        datagrams: Vec<udp::OutgoingDatagram>,
+    ) -> SocketResult<u64> {
+        // Only copy the datagrams for the trace if there is one.
+        if self.0.ctx().trace.is_none() {
+            return udp::HostOutgoingDatagramStream::send(&mut self.0, this, datagrams);
+        }
+        let args = datagrams.clone();
+        self.call(SocketOp::UdpSend, &args, |view| {
+            udp::HostOutgoingDatagramStream::send(view, this, datagrams)
+        })
+    }
+
+    fn subscribe(
+        &mut self,
+        this: Resource<OutgoingDatagramStream>,
+    ) -> anyhow::Result<Resource<Pollable>> {
+        self.subscribe_with(|view| udp::HostOutgoingDatagramStream::subscribe(view, this))
+    }
+
+    fn drop(&mut self, this: Resource<OutgoingDatagramStream>) -> Result<(), anyhow::Error> {
+        udp::HostOutgoingDatagramStream::drop(&mut self.0, this)
+    }
+}
+
+impl<T: WasiView> ip_name_lookup::Host for TracedSockets<T> {
+    fn resolve_addresses(
+        &mut self,
+        network: Resource<Network>,
+        name: String,
+    ) -> Result<Resource<ResolveAddressStream>, SocketError> {
+        let args = name.clone();
+        let stream = self.call_resources(SocketOp::ResolveAddresses, &args, |view| {
+            ip_name_lookup::Host::resolve_addresses(view, network, name)
+        })?;
+        match stream {
+            Some(stream) => Ok(stream),
+            // The addresses of a replayed lookup are all replayed by
+            // `resolve_next_address`.
+            None => {
+                let stream = ResolveAddressStream::Done(Ok(Vec::new().into_iter()));
+                Ok(self.0.table().push(stream)?)
+            }
+        }
+    }
+}
+
+impl<T: WasiView> ip_name_lookup::HostResolveAddressStream for TracedSockets<T> {
+    fn resolve_next_address(
+        &mut self,
+        resource: Resource<ResolveAddressStream>,
+    ) -> Result<Option<IpAddress>, SocketError> {
+        self.call(SocketOp::ResolveNextAddress, &(), |view| {
+            ip_name_lookup::HostResolveAddressStream::resolve_next_address(view, resource)
+        })
+    }
+
+    fn subscribe(
+        &mut self,
+        resource: Resource<ResolveAddressStream>,
+    ) -> anyhow::Result<Resource<Pollable>> {
+        self.subscribe_with(|view| {
+            ip_name_lookup::HostResolveAddressStream::subscribe(view, resource)
+        })
+    }
+
+    fn drop(&mut self, resource: Resource<ResolveAddressStream>) -> anyhow::Result<()> {
+        ip_name_lookup::HostResolveAddressStream::drop(&mut self.0, resource)
+    }
+}
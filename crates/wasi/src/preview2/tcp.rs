@@ -295,6 +295,12 @@ impl TcpSocket {
         })
     }
 
+    /// Create a placeholder for a socket whose calls are all replayed from a
+    /// trace, which is never bound or connected.
+    pub(crate) fn replayed(family: SocketAddressFamily) -> Self {
+        Self::from_state(TcpState::Closed, family).unwrap()
+    }
+
     pub(crate) fn as_std_view(&self) -> SocketResult<SocketlikeView<'_, std::net::TcpStream>> {
         use crate::preview2::bindings::sockets::network::ErrorCode;
 
@@ -1,8 +1,9 @@
+use super::append_tar_entry;
 use anyhow::Result;
 use cap_std::ambient_authority;
 use cap_std::fs::Dir;
 use std::io::Write;
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use wasmtime::component::{Component, Linker, ResourceTable};
 use wasmtime::{Config, Engine, Store};
@@ -10,7 +11,8 @@ use wasmtime_wasi::preview2::bindings::wasi::clocks::wall_clock;
 use wasmtime_wasi::preview2::bindings::wasi::filesystem::types as filesystem;
 use wasmtime_wasi::preview2::command::{add_to_linker, Command};
 use wasmtime_wasi::preview2::{
-    self, DirPerms, FilePerms, HostMonotonicClock, HostWallClock, WasiCtx, WasiCtxBuilder, WasiView,
+    self, DirPerms, FilePerms, HostFilesystem, HostMonotonicClock, HostWallClock, MemoryFilesystem,
+    OverlayFilesystem, WasiCtx, WasiCtxBuilder, WasiView,
 };
 
 struct CommandCtx {
@@ -113,6 +115,79 @@ async fn api_read_only() -> Result<()> {
         .map_err(|()| anyhow::anyhow!("command returned with failing exit status"))
 }
 
+// The read-only backends reject modifications on their own, so these are
+// given full permissions.
+#[test_log::test(tokio::test(flavor = "multi_thread"))]
+async fn api_read_only_tar() -> Result<()> {
+    let fs = MemoryFilesystem::from_tar(&read_only_tar())?.read_only();
+
+    let table = ResourceTable::new();
+    let wasi = WasiCtxBuilder::new()
+        .preopened_filesystem(fs, DirPerms::all(), FilePerms::all(), "/")
+        .build();
+
+    let (mut store, command) =
+        instantiate(API_READ_ONLY_COMPONENT, CommandCtx { table, wasi }).await?;
+
+    command
+        .wasi_cli_run()
+        .call_run(&mut store)
+        .await?
+        .map_err(|()| anyhow::anyhow!("command returned with failing exit status"))
+}
+
+#[test_log::test(tokio::test(flavor = "multi_thread"))]
+async fn api_read_only_overlay() -> Result<()> {
+    // The upper layer is writable, but the overlay must not be.
+    let upper = MemoryFilesystem::new();
+    upper.insert_file("bar.txt", "And stood awhile in thought")?;
+    let lower = MemoryFilesystem::from_tar(&read_only_tar())?;
+    let fs = OverlayFilesystem::new([Arc::new(upper) as Arc<dyn HostFilesystem>, Arc::new(lower)]);
+
+    let table = ResourceTable::new();
+    let wasi = WasiCtxBuilder::new()
+        .preopened_filesystem(fs, DirPerms::all(), FilePerms::all(), "/")
+        .build();
+
+    let (mut store, command) =
+        instantiate(API_READ_ONLY_COMPONENT, CommandCtx { table, wasi }).await?;
+
+    command
+        .wasi_cli_run()
+        .call_run(&mut store)
+        .await?
+        .map_err(|()| anyhow::anyhow!("command returned with failing exit status"))
+}
+
+#[test_log::test(tokio::test(flavor = "multi_thread"))]
+async fn api_file_quota() -> Result<()> {
+    let fs = MemoryFilesystem::new().quota(1 << 20);
+    let table = ResourceTable::new();
+    let wasi = WasiCtxBuilder::new()
+        .preopened_filesystem(fs.clone(), DirPerms::all(), FilePerms::all(), "/")
+        .build();
+
+    let (mut store, command) =
+        instantiate(API_FILE_QUOTA_COMPONENT, CommandCtx { table, wasi }).await?;
+
+    command
+        .wasi_cli_run()
+        .call_run(&mut store)
+        .await?
+        .map_err(|()| anyhow::anyhow!("command returned with failing exit status"))?;
+    assert_eq!(fs.metadata_at("other.txt", false)?.size, 1 << 20);
+    Ok(())
+}
+
+/// Builds a tar archive with the contents expected by `api_read_only`.
+fn read_only_tar() -> Vec<u8> {
+    let mut archive = Vec::new();
+    append_tar_entry(&mut archive, "bar.txt", b"And stood awhile in thought");
+    append_tar_entry(&mut archive, "sub/", b"");
+    archive.resize(archive.len() + 1024, 0);
+    archive
+}
+
 // This is tested in the wasi-http crate, but need to satisfy the `foreach_api!`
 // macro above.
 #[allow(dead_code)]
@@ -7,7 +7,8 @@ use wasmtime::{
 use wasmtime_wasi::preview2::{
     pipe::MemoryOutputPipe,
     preview1::{WasiPreview1Adapter, WasiPreview1View},
-    DirPerms, FilePerms, WasiCtx, WasiCtxBuilder, WasiView,
+    DirPerms, FilePerms, HostFilesystem, MemoryFilesystem, OpenOptions, Opened, WasiCtx,
+    WasiCtxBuilder, WasiView,
 };
 
 struct Ctx {
@@ -42,7 +43,66 @@ fn prepare_workspace(exe_name: &str) -> Result<TempDir> {
     Ok(tempdir)
 }
 
-fn store(engine: &Engine, name: &str, inherit_stdio: bool) -> Result<(Store<Ctx>, TempDir)> {
+/// The filesystem backend which is preopened for a test program.
+#[derive(Clone, Copy, Debug)]
+enum Backend {
+    Host,
+    Memory,
+    /// An in-memory layer over a read-only filesystem loaded from a tar
+    /// archive, with the preopen being a directory from the archive.
+    Overlay,
+}
+
+const BACKENDS: [Backend; 3] = [Backend::Host, Backend::Memory, Backend::Overlay];
+
+/// Appends a ustar entry for a regular file, or a directory if `path` ends
+/// with a slash, to `archive`.
+fn append_tar_entry(archive: &mut Vec<u8>, path: &str, data: &[u8]) {
+    let mut header = [0; 512];
+    header[..path.len()].copy_from_slice(path.as_bytes());
+    header[100..107].copy_from_slice(b"0000644");
+    header[124..135].copy_from_slice(format!("{:011o}", data.len()).as_bytes());
+    header[136..147].copy_from_slice(b"14000000000");
+    header[156] = if path.ends_with('/') { b'5' } else { b'0' };
+    header[257..263].copy_from_slice(b"ustar\0");
+    header[263..265].copy_from_slice(b"00");
+    header[148..156].fill(b' ');
+    let checksum = header.iter().map(|b| u64::from(*b)).sum::<u64>();
+    header[148..155].copy_from_slice(format!("{checksum:06o}\0").as_bytes());
+    archive.extend_from_slice(&header);
+    archive.extend_from_slice(data);
+    archive.resize(archive.len().next_multiple_of(512), 0);
+}
+
+/// Returns the `scratch` directory of a writable overlay over a tar archive
+/// which also contains other files.
+fn overlay_scratch_dir() -> Result<MemoryFilesystem> {
+    let mut archive = Vec::new();
+    append_tar_entry(&mut archive, "scratch/", b"");
+    append_tar_entry(&mut archive, "lower/file", b"contents");
+    archive.extend_from_slice(&[0; 1024]);
+    let lower = MemoryFilesystem::from_tar(&archive)?.read_only();
+    let overlay = MemoryFilesystem::overlay(std::sync::Arc::new(lower))?;
+    let options = OpenOptions {
+        directory: true,
+        ..OpenOptions::default()
+    };
+    match overlay.open_at("scratch", &options)? {
+        Opened::Dir(dir) => Ok(dir
+            .as_any()
+            .downcast_ref::<MemoryFilesystem>()
+            .unwrap()
+            .clone()),
+        Opened::File(_) => unreachable!(),
+    }
+}
+
+fn store(
+    engine: &Engine,
+    name: &str,
+    inherit_stdio: bool,
+    backend: Backend,
+) -> Result<(Store<Ctx>, TempDir)> {
     let stdout = MemoryOutputPipe::new(4096);
     let stderr = MemoryOutputPipe::new(4096);
     let workspace = prepare_workspace(name)?;
@@ -59,10 +119,32 @@ fn store(engine: &Engine, name: &str, inherit_stdio: bool) -> Result<(Store<Ctx>
         .args(&[name, "."])
         .inherit_network()
         .allow_ip_name_lookup(true);
-    println!("preopen: {:?}", workspace);
-    let preopen_dir =
-        cap_std::fs::Dir::open_ambient_dir(workspace.path(), cap_std::ambient_authority())?;
-    builder.preopened_dir(preopen_dir, DirPerms::all(), FilePerms::all(), ".");
+    match backend {
+        Backend::Host => {
+            println!("preopen: {:?}", workspace);
+            let preopen_dir =
+                cap_std::fs::Dir::open_ambient_dir(workspace.path(), cap_std::ambient_authority())?;
+            builder.preopened_dir(preopen_dir, DirPerms::all(), FilePerms::all(), ".");
+        }
+        Backend::Memory => {
+            println!("preopen: in-memory filesystem");
+            builder.preopened_filesystem(
+                MemoryFilesystem::new(),
+                DirPerms::all(),
+                FilePerms::all(),
+                ".",
+            );
+        }
+        Backend::Overlay => {
+            println!("preopen: in-memory overlay of a tar archive");
+            builder.preopened_filesystem(
+                overlay_scratch_dir()?,
+                DirPerms::all(),
+                FilePerms::all(),
+                ".",
+            );
+        }
+    }
     for (var, val) in test_programs_artifacts::wasi_tests_environment() {
         builder.env(var, val);
     }
@@ -5,6 +5,14 @@ use wasmtime::{Linker, Module};
 use wasmtime_wasi::preview2::preview1::add_to_linker_async;
 
 async fn run(path: &str, inherit_stdio: bool) -> Result<()> {
+    for backend in BACKENDS {
+        println!("backend: {backend:?}");
+        run_with(path, inherit_stdio, backend).await?;
+    }
+    Ok(())
+}
+
+async fn run_with(path: &str, inherit_stdio: bool, backend: Backend) -> Result<()> {
     let path = Path::new(path);
     let name = path.file_stem().unwrap().to_str().unwrap();
     let mut config = Config::new();
@@ -14,7 +22,7 @@ async fn run(path: &str, inherit_stdio: bool) -> Result<()> {
     add_to_linker_async(&mut linker)?;
 
     let module = Module::from_file(&engine, path)?;
-    let (mut store, _td) = store(&engine, name, inherit_stdio)?;
+    let (mut store, _td) = store(&engine, name, inherit_stdio, backend)?;
     let instance = linker.instantiate_async(&mut store, &module).await?;
     let start = instance.get_typed_func::<(), ()>(&mut store, "_start")?;
     start.call_async(&mut store, ()).await?;
@@ -4,6 +4,14 @@ use test_programs_artifacts::*;
 use wasmtime_wasi::preview2::command::sync::{add_to_linker, Command};
 
 fn run(path: &str, inherit_stdio: bool) -> Result<()> {
+    for backend in BACKENDS {
+        println!("backend: {backend:?}");
+        run_with(path, inherit_stdio, backend)?;
+    }
+    Ok(())
+}
+
+fn run_with(path: &str, inherit_stdio: bool, backend: Backend) -> Result<()> {
     let path = Path::new(path);
     let name = path.file_stem().unwrap().to_str().unwrap();
     let mut config = Config::new();
@@ -12,7 +20,7 @@ fn run(path: &str, inherit_stdio: bool) -> Result<()> {
     let mut linker = Linker::new(&engine);
     add_to_linker(&mut linker)?;
 
-    let (mut store, _td) = store(&engine, name, inherit_stdio)?;
+    let (mut store, _td) = store(&engine, name, inherit_stdio, backend)?;
     let component = Component::from_file(&engine, path)?;
     let (command, _instance) = Command::instantiate(&mut store, &component, &linker)?;
     command
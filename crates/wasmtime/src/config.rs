@@ -148,6 +148,8 @@ struct ConfigTunables {
     debug_adapter_modules: Option<bool>,
     relaxed_simd_deterministic: Option<bool>,
     tail_callable: Option<bool>,
+    inlining: Option<bool>,
+    generate_frame_state: Option<bool>,
 }
 
 /// User-provided configuration for the compiler.
@@ -988,6 +990,27 @@ impl Config {
         self
     }
 
+    /// Configures whether Cranelift inlines small functions into their callers.
+    ///
+    /// When enabled, direct `call` instructions targeting small functions
+    /// defined in the same module are replaced with the body of the callee
+    /// before optimization. Recursive calls, calls to imported functions, and
+    /// calls through tables are never inlined. Inlined callees still show up
+    /// as their own frames in backtraces, pointing at the offset of the
+    /// inlined instruction, above a frame for the caller which points at the
+    /// inlined call.
+    ///
+    /// Inlining is skipped when native DWARF debug information is generated.
+    /// This setting is ignored by Winch.
+    ///
+    /// The default value for this is `false`
+    #[cfg(any(feature = "cranelift", feature = "winch"))]
+    #[cfg_attr(docsrs, doc(cfg(any(feature = "cranelift", feature = "winch"))))]
+    pub fn cranelift_inlining(&mut self, enable: bool) -> &mut Self {
+        self.tunables.inlining = Some(enable);
+        self
+    }
+
     /// Controls whether proof-carrying code (PCC) is used to validate
     /// lowering of Wasm sandbox checks.
     ///
@@ -1132,6 +1155,33 @@ impl Config {
         Ok(self)
     }
 
+    /// Keeps compiled modules in the provided [`CacheStore`] rather than in
+    /// the local cache directory.
+    ///
+    /// This can be used to share compiled modules between machines, for
+    /// example through a key/value service or a directory on a network mount.
+    /// Modules are compressed and checksummed before they're handed to
+    /// `store`, and those which fail their checksum when read back are
+    /// compiled again, so stores don't need to verify their contents
+    /// themselves. They are, however, responsible for evicting old modules.
+    ///
+    /// If a cache configuration was loaded previously its other settings, such
+    /// as compression levels, are kept; otherwise the cache is enabled with
+    /// the default settings.
+    ///
+    /// This method is only available when the `cache` feature of this crate is
+    /// enabled.
+    #[cfg(feature = "cache")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "cache")))]
+    pub fn cache_store(&mut self, store: Arc<dyn CacheStore>) -> Result<&mut Self> {
+        if self.cache_config.enabled() {
+            self.cache_config.set_store(store);
+        } else {
+            self.cache_config = CacheConfig::new_with_store(store)?;
+        }
+        Ok(self)
+    }
+
     /// Sets a custom memory creator.
     ///
     /// Custom memory creators are used when creating host `Memory` objects or when
@@ -1551,11 +1601,23 @@ impl Config {
     /// Configures whether or not a coredump should be generated and attached to
     /// the anyhow::Error when a trap is raised.
     ///
+    /// Enabling this also makes Cranelift record, next to each compiled
+    /// function, where the values of its locals and operand stack are stored
+    /// at every call and instruction that may trap. This doesn't change the
+    /// generated code. Values kept in integer registers are recovered on
+    /// Linux on x86_64, aarch64, and riscv64, from the registers at the time
+    /// of the trap and, for calling frames, the registers saved by their
+    /// callees as described by native unwind info (see
+    /// [`Config::native_unwind_info`]). Other values kept in registers, values
+    /// of functions compiled with Winch, and values of types other than
+    /// `i32`, `i64`, `f32`, and `f64` are reported as missing.
+    ///
     /// This option is disabled by default.
     #[cfg(feature = "coredump")]
     #[cfg_attr(docsrs, doc(cfg(feature = "coredump")))]
     pub fn coredump_on_trap(&mut self, enable: bool) -> &mut Self {
         self.coredump_on_trap = enable;
+        self.tunables.generate_frame_state = Some(enable);
         self
     }
 
@@ -1669,6 +1731,8 @@ impl Config {
             debug_adapter_modules
             relaxed_simd_deterministic
             tail_callable
+            inlining
+            generate_frame_state
         }
 
         if tunables.static_memory_offset_guard_size < tunables.dynamic_memory_offset_guard_size {
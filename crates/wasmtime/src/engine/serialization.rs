@@ -352,6 +352,14 @@ impl Metadata<'_> {
 
             // Just a debugging aid, doesn't affect functionality at all.
             debug_adapter_modules: _,
+
+            // Inlining only changes how code is generated, not what the
+            // generated code expects of the runtime.
+            inlining: _,
+
+            // Frame state is only consulted when capturing a core dump, and
+            // frames without it simply report no locals or operand stack.
+            generate_frame_state: _,
         } = self.tunables;
 
         Self::check_int(
@@ -59,7 +59,8 @@ pub use resources::*;
 #[cfg(feature = "async")]
 pub use store::CallHookHandler;
 pub use store::{
-    AsContext, AsContextMut, CallHook, Store, StoreContext, StoreContextMut, UpdateDeadline,
+    AsContext, AsContextMut, CallHook, CallOptions, CallReport, Store, StoreContext,
+    StoreContextMut, StoreSnapshot, UpdateDeadline,
 };
 pub use trap::*;
 pub use types::*;
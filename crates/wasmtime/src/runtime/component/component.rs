@@ -1,3 +1,5 @@
+use crate::component::matching::InstanceType;
+use crate::component::{types, ResourceType};
 use crate::{
     code::CodeObject, code_memory::CodeMemory, instantiate::MmapVecWrapper,
     type_registry::TypeCollection, Engine, Module, ResourcesRequired,
@@ -10,13 +12,15 @@ use std::ptr::NonNull;
 use std::sync::Arc;
 use wasmtime_environ::component::{
     AllCallFunc, CompiledComponentInfo, ComponentArtifacts, ComponentTypes, GlobalInitializer,
-    InstantiateModule, StaticModuleIndex, TrampolineIndex, TypeComponentIndex, VMComponentOffsets,
+    InstantiateModule, ResourceIndex, StaticModuleIndex, TrampolineIndex, TypeComponentIndex,
+    VMComponentOffsets,
 };
 
 use wasmtime_environ::{FunctionLoc, HostPtr, ObjectKind, PrimaryMap};
 use wasmtime_runtime::component::ComponentRuntimeInfo;
 use wasmtime_runtime::{
-    VMArrayCallFunction, VMFuncRef, VMFunctionBody, VMNativeCallFunction, VMWasmCallFunction,
+    CompiledModuleId, VMArrayCallFunction, VMFuncRef, VMFunctionBody, VMNativeCallFunction,
+    VMWasmCallFunction,
 };
 
 /// A compiled WebAssembly Component.
@@ -56,6 +60,11 @@ pub struct Component {
 }
 
 struct ComponentInner {
+    /// Unique id for this component within its engine, used to tell apart
+    /// the resource types imported by different components before they're
+    /// instantiated.
+    id: CompiledModuleId,
+
     /// Component type index
     ty: TypeComponentIndex,
 
@@ -324,6 +333,7 @@ impl Component {
 
         Ok(Component {
             inner: Arc::new(ComponentInner {
+                id: engine.unique_id_allocator().alloc(),
                 ty,
                 static_modules,
                 code,
@@ -336,6 +346,27 @@ impl Component {
         self.inner.ty
     }
 
+    /// Returns the type of this component as a [`types::Component`].
+    ///
+    /// This can be used to inspect the imports and exports of this component
+    /// before it's instantiated. Resource types imported by this component
+    /// are not substituted here, and are instead represented by types which
+    /// are distinct from any other resource type; see
+    /// [`Linker::substituted_component_type`](crate::component::Linker::substituted_component_type)
+    /// for that.
+    pub fn component_type(&self) -> types::Component {
+        let resources = (0..self.env_component().num_resources)
+            .map(|i| ResourceType::uninstantiated(self.inner.id, ResourceIndex::from_u32(i)))
+            .collect();
+        types::Component::from(
+            self.ty(),
+            &InstanceType {
+                types: self.types(),
+                resources: &Arc::new(resources),
+            },
+        )
+    }
+
     pub(crate) fn env_component(&self) -> &wasmtime_environ::component::Component {
         &self.inner.info.component
     }
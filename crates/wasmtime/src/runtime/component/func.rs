@@ -3,7 +3,7 @@ use crate::component::storage::storage_as_slice;
 use crate::component::types::Type;
 use crate::component::values::Val;
 use crate::store::{StoreOpaque, Stored};
-use crate::{AsContext, AsContextMut, StoreContextMut, ValRaw};
+use crate::{AsContext, AsContextMut, CallOptions, CallReport, StoreContextMut, ValRaw};
 use anyhow::{bail, Context, Result};
 use std::mem::{self, MaybeUninit};
 use std::ptr::NonNull;
@@ -72,6 +72,7 @@ impl<T> MaybeUninitExt<T> for MaybeUninit<T> {
     }
 }
 
+mod forward;
 mod host;
 mod options;
 mod typed;
@@ -105,6 +106,7 @@ pub struct FuncData {
     component_instance: RuntimeComponentInstanceIndex,
     post_return: Option<ExportFunction>,
     post_return_arg: Option<ValRaw>,
+    name: Option<Arc<str>>,
 }
 
 impl Func {
@@ -115,6 +117,7 @@ impl Func {
         ty: TypeFuncIndex,
         func: &CoreDef,
         options: &CanonicalOptions,
+        name: Option<Arc<str>>,
     ) -> Func {
         let export = match data.lookup_def(store, func) {
             Export::Function(f) => f,
@@ -139,6 +142,7 @@ impl Func {
             component_instance,
             post_return,
             post_return_arg: None,
+            name,
         }))
     }
 
@@ -338,6 +342,34 @@ impl Func {
         self.call_impl(&mut store.as_context_mut(), params, results)
     }
 
+    /// Same as [`Func::call`], but with the fuel and epoch budgets configured
+    /// in `options` applied for the duration of this call.
+    ///
+    /// Note that the budgets don't apply to [`Func::post_return`], which is
+    /// still required to be called afterwards.
+    ///
+    /// There is currently no async version of this method, so it can't be
+    /// used with async stores.
+    ///
+    /// For more information see
+    /// [`wasmtime::Func::call_with_options`](crate::Func::call_with_options).
+    ///
+    /// # Panics
+    ///
+    /// Panics in the same situations as [`Func::call`].
+    pub fn call_with_options(
+        &self,
+        mut store: impl AsContextMut,
+        params: &[Val],
+        results: &mut [Val],
+        options: &CallOptions,
+    ) -> Result<CallReport> {
+        let mut store = store.as_context_mut();
+        let ((), report) =
+            store.with_call_options(options, |store| self.call(store, params, results))?;
+        Ok(report)
+    }
+
     /// Exactly like [`Self::call`] except for use on async stores.
     ///
     /// Note that after this [`Func::post_return_async`] will be used instead of
@@ -369,7 +401,7 @@ impl Func {
             .await?
     }
 
-    fn call_impl(
+    pub(crate) fn call_impl(
         &self,
         mut store: impl AsContextMut,
         params: &[Val],
@@ -472,6 +504,7 @@ impl Func {
             ty,
             ..
         } = store.0[self.0];
+        let name = store.0[self.0].name.clone();
 
         let space = &mut MaybeUninit::<ParamsAndResults<LowerParams, LowerReturn>>::uninit();
 
@@ -511,7 +544,7 @@ impl Func {
             flags.set_may_leave(false);
             let instance_ptr = instance.instance_ptr();
             let mut cx = LowerContext::new(store.as_context_mut(), &options, &types, instance_ptr);
-            cx.enter_call();
+            cx.enter_call(name.as_ref());
             let result = lower(
                 &mut cx,
                 params,
@@ -630,7 +663,7 @@ impl Func {
         store.on_fiber(|store| self.post_return_impl(store)).await?
     }
 
-    fn post_return_impl(&self, mut store: impl AsContextMut) -> Result<()> {
+    pub(crate) fn post_return_impl(&self, mut store: impl AsContextMut) -> Result<()> {
         let mut store = store.as_context_mut();
         let data = &mut store.0[self.0];
         let instance = data.instance;
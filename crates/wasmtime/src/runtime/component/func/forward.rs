@@ -0,0 +1,447 @@
+//! Forwarding of calls from one component instance to another's exports.
+//!
+//! Functions defined by `LinkerInstance::instance_exports` are imported by one
+//! instance and call the export of another. In general such calls lift their
+//! arguments into `Val`s on the host and lower them again into the callee,
+//! and likewise for results. For the common case of functions whose types are
+//! made of scalars, strings, and lists of integers this module instead does
+//! what a fused adapter would: flat values are validated and copied as they
+//! are, and the contents of strings and lists are copied once, directly from
+//! one linear memory to the other.
+
+use crate::component::func::{Func, LowerContext, Options};
+use crate::store::StoreOpaque;
+use crate::{AsContextMut, StoreContextMut, ValRaw};
+use anyhow::{bail, Result};
+use std::mem::{self, MaybeUninit};
+use std::ops::Range;
+use std::sync::Arc;
+use wasmtime_environ::component::{
+    ComponentTypes, InterfaceType, StringEncoding, MAX_FLAT_PARAMS, MAX_FLAT_RESULTS,
+};
+use wasmtime_runtime::component::{ComponentInstance, InstanceFlags};
+
+/// How calls to a function are forwarded without `Val`s, as determined by its
+/// type.
+pub struct Forward {
+    params: Box<[Param]>,
+    results: Results,
+    num_flat_params: usize,
+    has_strings: bool,
+}
+
+enum Param {
+    /// A parameter passed as flat values which are validated and copied.
+    Flat(InterfaceType),
+    /// A string or list whose `(ptr, len)` pair is passed as flat values and
+    /// whose contents are copied into the callee's memory.
+    Buffer(Buffer),
+}
+
+enum Results {
+    /// Results passed as at most `MAX_FLAT_RESULTS` flat values, described by
+    /// the results tuple.
+    Flat(InterfaceType),
+    /// Results stored in memory which are made only of integers, so their
+    /// bytes are copied as they are.
+    Bytes { size: u32, align: u32 },
+    /// A single string or list stored in memory as a `(ptr, len)` pair.
+    Buffer(Buffer),
+}
+
+/// The layout of the contents of a string or list.
+#[derive(Copy, Clone)]
+struct Buffer {
+    size: u32,
+    align: u32,
+    is_string: bool,
+}
+
+impl Buffer {
+    fn kind(&self) -> &'static str {
+        if self.is_string {
+            "string"
+        } else {
+            "list"
+        }
+    }
+
+    /// Returns the byte range in `memory` of the contents at `ptr` with `len`
+    /// elements.
+    fn range(&self, memory: &[u8], ptr: u32, len: u32) -> Result<Range<usize>> {
+        // FIXME: needs memory64 support
+        let ptr = usize::try_from(ptr)?;
+        let size = usize::try_from(len)?.checked_mul(usize::try_from(self.size)?);
+        match size.and_then(|size| ptr.checked_add(size)) {
+            Some(end) if end <= memory.len() => {}
+            _ => bail!("{} pointer/length out of bounds of memory", self.kind()),
+        }
+        if ptr % usize::try_from(self.align)? != 0 {
+            bail!("{} pointer is not aligned", self.kind());
+        }
+        let range = ptr..ptr + size.unwrap();
+        if self.is_string {
+            std::str::from_utf8(&memory[range.clone()])?;
+        }
+        Ok(range)
+    }
+}
+
+impl Forward {
+    /// Returns how calls to `func` are forwarded, or `None` if its type
+    /// requires going through `Val`s.
+    pub fn new(store: &StoreOpaque, func: &Func) -> Option<Forward> {
+        let data = &store[func.0];
+        let types = &data.types;
+        let ty = &types[data.ty];
+
+        let params_ty = &types[ty.params];
+        let num_flat_params = params_ty.abi.flat_count(MAX_FLAT_PARAMS)?;
+        let params = params_ty
+            .types
+            .iter()
+            .map(|ty| {
+                if is_flat(types, ty) {
+                    Some(Param::Flat(*ty))
+                } else {
+                    buffer(types, ty).map(Param::Buffer)
+                }
+            })
+            .collect::<Option<Box<[_]>>>()?;
+
+        let results_ty = &types[ty.results];
+        let tuple = InterfaceType::Tuple(ty.results);
+        let results = if results_ty.abi.flat_count(MAX_FLAT_RESULTS).is_some() {
+            if !is_flat(types, &tuple) {
+                return None;
+            }
+            Results::Flat(tuple)
+        } else if let Some(buffer) = match &results_ty.types[..] {
+            [ty] => buffer(types, ty),
+            _ => None,
+        } {
+            Results::Buffer(buffer)
+        } else if is_bytes(types, &tuple) {
+            Results::Bytes {
+                size: results_ty.abi.size32,
+                align: results_ty.abi.align32,
+            }
+        } else {
+            return None;
+        };
+
+        let has_strings = params.iter().any(|param| match param {
+            Param::Buffer(buffer) => buffer.is_string,
+            Param::Flat(_) => false,
+        }) || matches!(results, Results::Buffer(buffer) if buffer.is_string);
+
+        Some(Forward {
+            params,
+            results,
+            num_flat_params,
+            has_strings,
+        })
+    }
+
+    /// Returns whether a call to `func` from a caller using `caller_encoding`
+    /// for strings can be forwarded.
+    ///
+    /// Strings are only copied as they are between instances which both use
+    /// UTF-8.
+    pub fn supports(&self, store: &StoreOpaque, func: &Func, caller: StringEncoding) -> bool {
+        !self.has_strings
+            || (caller == StringEncoding::Utf8
+                && store[func.0].options.string_encoding() == StringEncoding::Utf8)
+    }
+
+    /// Forwards a call to `func` whose flat arguments, and return pointer if
+    /// any, are in `storage`, writing the results back to `storage` or the
+    /// caller's memory.
+    ///
+    /// # Unsafety
+    ///
+    /// This is unsafe as `storage` must hold the arguments of a call to a
+    /// function of the same type as `func`, made by the instance `caller`
+    /// with canonical options `options` and flags `flags`.
+    pub unsafe fn call<T>(
+        &self,
+        store: &mut StoreContextMut<'_, T>,
+        func: &Func,
+        options: &Options,
+        types: &Arc<ComponentTypes>,
+        caller: *mut ComponentInstance,
+        mut flags: InstanceFlags,
+        storage: &mut [MaybeUninit<ValRaw>],
+    ) -> Result<()> {
+        // NB: can use `MaybeUninit::slice_assume_init_ref` when that's stable
+        let args =
+            mem::transmute::<&[MaybeUninit<ValRaw>], &[ValRaw]>(&storage[..self.num_flat_params]);
+        let retptr = match self.results {
+            Results::Flat(_) => None,
+            _ => Some(storage[self.num_flat_params].assume_init()),
+        };
+        let callee_types = store.0[func.0].types.clone();
+        let callee_options = store.0[func.0].options;
+
+        let ret = func.call_raw(
+            store,
+            args,
+            |cx, args, _ty, dst: &mut MaybeUninit<[ValRaw; MAX_FLAT_PARAMS]>| {
+                self.lower_params(cx, options, args, dst)
+            },
+            |_cx, _ty, ret: &[ValRaw; MAX_FLAT_RESULTS]| Ok(*ret),
+        )?;
+
+        flags.set_may_leave(false);
+        let mut cx = LowerContext::new(store.as_context_mut(), options, types, caller);
+        let result = match &self.results {
+            Results::Flat(ty) => copy_flat(
+                &callee_types,
+                *ty,
+                &mut ret.iter(),
+                &mut storage[..].iter_mut(),
+            ),
+            Results::Bytes { size, align } => (|| {
+                let src = callee_options.memory(cx.store.0);
+                let src = validate_inbounds(src, ret[0].get_u32(), *size, *align)?;
+                let src = callee_options.memory(cx.store.0)[src].as_ptr();
+                let dst =
+                    validate_inbounds(cx.as_slice_mut(), retptr.unwrap().get_u32(), *size, *align)?;
+                let dst = cx.as_slice_mut()[dst].as_mut_ptr();
+                std::ptr::copy_nonoverlapping(src, dst, usize::try_from(*size)?);
+                Ok(())
+            })(),
+            Results::Buffer(buffer) => (|| {
+                let src = callee_options.memory(cx.store.0);
+                let pair = validate_inbounds(src, ret[0].get_u32(), 8, 4)?;
+                let ptr = u32::from_le_bytes(src[pair.start..][..4].try_into().unwrap());
+                let len = u32::from_le_bytes(src[pair.start + 4..][..4].try_into().unwrap());
+                let range = buffer.range(src, ptr, len)?;
+                let new_ptr = copy_buffer(&mut cx, &callee_options, *buffer, range)?;
+                let dst = validate_inbounds(cx.as_slice_mut(), retptr.unwrap().get_u32(), 8, 4)?;
+                let dst = &mut cx.as_slice_mut()[dst];
+                dst[..4].copy_from_slice(&new_ptr.to_le_bytes());
+                dst[4..].copy_from_slice(&len.to_le_bytes());
+                Ok(())
+            })(),
+        };
+        flags.set_may_leave(true);
+        result?;
+
+        // The results have been copied out of the callee's memory, so it may
+        // now clean up after the call.
+        func.post_return_impl(store)
+    }
+
+    fn lower_params<T>(
+        &self,
+        cx: &mut LowerContext<'_, T>,
+        options: &Options,
+        args: &[ValRaw],
+        dst: &mut MaybeUninit<[ValRaw; MAX_FLAT_PARAMS]>,
+    ) -> Result<()> {
+        let mut dst =
+            unsafe { mem::transmute::<_, &mut [MaybeUninit<ValRaw>; MAX_FLAT_PARAMS]>(dst) }
+                .iter_mut();
+        let mut src = args.iter();
+        for param in self.params.iter() {
+            match param {
+                Param::Flat(ty) => copy_flat(cx.types, *ty, &mut src, &mut dst)?,
+                Param::Buffer(buffer) => {
+                    let ptr = src.next().unwrap().get_u32();
+                    let len = src.next().unwrap().get_u32();
+                    let range = buffer.range(options.memory(cx.store.0), ptr, len)?;
+                    let new_ptr = copy_buffer(cx, options, *buffer, range)?;
+                    dst.next().unwrap().write(ValRaw::u32(new_ptr));
+                    dst.next().unwrap().write(ValRaw::u32(len));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns whether the flat values of `ty` can be validated and copied one by
+/// one.
+fn is_flat(types: &ComponentTypes, ty: &InterfaceType) -> bool {
+    match ty {
+        InterfaceType::Bool
+        | InterfaceType::S8
+        | InterfaceType::U8
+        | InterfaceType::S16
+        | InterfaceType::U16
+        | InterfaceType::S32
+        | InterfaceType::U32
+        | InterfaceType::S64
+        | InterfaceType::U64
+        | InterfaceType::Float32
+        | InterfaceType::Float64
+        | InterfaceType::Char
+        | InterfaceType::Enum(_)
+        | InterfaceType::Flags(_) => true,
+        InterfaceType::Record(i) => types[*i].fields.iter().all(|f| is_flat(types, &f.ty)),
+        InterfaceType::Tuple(i) => types[*i].types.iter().all(|ty| is_flat(types, ty)),
+        InterfaceType::String
+        | InterfaceType::List(_)
+        | InterfaceType::Variant(_)
+        | InterfaceType::Option(_)
+        | InterfaceType::Result(_)
+        | InterfaceType::Own(_)
+        | InterfaceType::Borrow(_) => false,
+    }
+}
+
+/// Returns whether any bytes in memory are a valid value of `ty`, so that it
+/// can be copied between memories without validation.
+///
+/// Floats are excluded as lifting canonicalizes NaNs.
+fn is_bytes(types: &ComponentTypes, ty: &InterfaceType) -> bool {
+    match ty {
+        InterfaceType::S8
+        | InterfaceType::U8
+        | InterfaceType::S16
+        | InterfaceType::U16
+        | InterfaceType::S32
+        | InterfaceType::U32
+        | InterfaceType::S64
+        | InterfaceType::U64 => true,
+        InterfaceType::Record(i) => types[*i].fields.iter().all(|f| is_bytes(types, &f.ty)),
+        InterfaceType::Tuple(i) => types[*i].types.iter().all(|ty| is_bytes(types, ty)),
+        _ => false,
+    }
+}
+
+/// Returns the layout of the contents of `ty` if it's a string or a list whose
+/// contents can be copied without validating each element.
+fn buffer(types: &ComponentTypes, ty: &InterfaceType) -> Option<Buffer> {
+    match ty {
+        InterfaceType::String => Some(Buffer {
+            size: 1,
+            align: 1,
+            is_string: true,
+        }),
+        InterfaceType::List(i) => {
+            let element = &types[*i].element;
+            if !is_bytes(types, element) {
+                return None;
+            }
+            let abi = types.canonical_abi(element);
+            Some(Buffer {
+                size: abi.size32,
+                align: abi.align32,
+                is_string: false,
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Copies the flat values of `ty` from `src` to `dst`, validating and
+/// normalizing them as lifting and lowering them would.
+fn copy_flat<'a>(
+    types: &ComponentTypes,
+    ty: InterfaceType,
+    src: &mut std::slice::Iter<'_, ValRaw>,
+    dst: &mut impl Iterator<Item = &'a mut MaybeUninit<ValRaw>>,
+) -> Result<()> {
+    let next = |src: &mut std::slice::Iter<'_, ValRaw>| *src.next().unwrap();
+    let val = match ty {
+        InterfaceType::Bool => ValRaw::u32(u32::from(next(src).get_u32() != 0)),
+        InterfaceType::S8 => ValRaw::i32(i32::from(next(src).get_i32() as i8)),
+        InterfaceType::U8 => ValRaw::u32(u32::from(next(src).get_u32() as u8)),
+        InterfaceType::S16 => ValRaw::i32(i32::from(next(src).get_i32() as i16)),
+        InterfaceType::U16 => ValRaw::u32(u32::from(next(src).get_u32() as u16)),
+        InterfaceType::S32 | InterfaceType::U32 | InterfaceType::S64 | InterfaceType::U64 => {
+            next(src)
+        }
+        InterfaceType::Float32 => {
+            let float = f32::from_bits(next(src).get_f32());
+            ValRaw::f32(if float.is_nan() { f32::NAN } else { float }.to_bits())
+        }
+        InterfaceType::Float64 => {
+            let float = f64::from_bits(next(src).get_f64());
+            ValRaw::f64(if float.is_nan() { f64::NAN } else { float }.to_bits())
+        }
+        InterfaceType::Char => {
+            let val = next(src);
+            char::try_from(val.get_u32())?;
+            val
+        }
+        InterfaceType::Enum(i) => {
+            let val = next(src);
+            if usize::try_from(val.get_u32())? >= types[i].names.len() {
+                bail!("unexpected discriminant: {}", val.get_u32());
+            }
+            val
+        }
+        InterfaceType::Flags(i) => {
+            let mut remaining = types[i].names.len();
+            while remaining > 0 {
+                let bits = remaining.min(32);
+                let mask = if bits == 32 {
+                    u32::MAX
+                } else {
+                    (1 << bits) - 1
+                };
+                let val = ValRaw::u32(next(src).get_u32() & mask);
+                dst.next().unwrap().write(val);
+                remaining -= bits;
+            }
+            return Ok(());
+        }
+        InterfaceType::Record(i) => {
+            for field in types[i].fields.iter() {
+                copy_flat(types, field.ty, src, dst)?;
+            }
+            return Ok(());
+        }
+        InterfaceType::Tuple(i) => {
+            for ty in types[i].types.iter() {
+                copy_flat(types, *ty, src, dst)?;
+            }
+            return Ok(());
+        }
+        _ => unreachable!(),
+    };
+    dst.next().unwrap().write(val);
+    Ok(())
+}
+
+/// Copies the contents of a string or list at `range` in `src`'s memory into
+/// memory allocated with `cx`'s `realloc`, returning its new address.
+fn copy_buffer<T>(
+    cx: &mut LowerContext<'_, T>,
+    src: &Options,
+    buffer: Buffer,
+    range: Range<usize>,
+) -> Result<u32> {
+    let len = range.len();
+    // Note that `realloc` checks that the allocation is in bounds.
+    let ptr = cx.realloc(0, 0, buffer.align, len)?;
+    // The source and destination are distinct linear memories which both
+    // belong to the store, so pointers are used to copy from one to the other.
+    let from = src.memory(cx.store.0)[range].as_ptr();
+    let to = cx.as_slice_mut()[ptr..][..len].as_mut_ptr();
+    unsafe {
+        std::ptr::copy_nonoverlapping(from, to, len);
+    }
+    Ok(u32::try_from(ptr)?)
+}
+
+/// Returns the range of `size` bytes at `ptr` in `memory`, checking that it's
+/// in bounds and aligned to `align`.
+fn validate_inbounds(memory: &[u8], ptr: u32, size: u32, align: u32) -> Result<Range<usize>> {
+    // FIXME: needs memory64 support
+    let ptr = usize::try_from(ptr)?;
+    if ptr % usize::try_from(align)? != 0 {
+        bail!("pointer not aligned");
+    }
+    let end = match ptr.checked_add(usize::try_from(size)?) {
+        Some(n) => n,
+        None => bail!("pointer size overflow"),
+    };
+    if end > memory.len() {
+        bail!("pointer out of bounds")
+    }
+    Ok(ptr..end)
+}
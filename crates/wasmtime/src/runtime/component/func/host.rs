@@ -1,7 +1,10 @@
-use crate::component::func::{LiftContext, LowerContext, Options};
+use crate::component::func::forward::Forward;
+use crate::component::func::{Func, LiftContext, LowerContext, Options};
 use crate::component::matching::InstanceType;
 use crate::component::storage::slice_to_storage_mut;
+use crate::component::types::ComponentFunc;
 use crate::component::{ComponentNamedList, ComponentType, Lift, Lower, Type, Val};
+use crate::store::{StoreId, StoreOpaque};
 use crate::{AsContextMut, StoreContextMut, ValRaw};
 use anyhow::{anyhow, bail, Context, Result};
 use std::any::Any;
@@ -20,8 +23,9 @@ use wasmtime_runtime::{VMFuncRef, VMMemoryDefinition, VMOpaqueContext};
 
 pub struct HostFunc {
     entrypoint: VMLoweringCallee,
-    typecheck: Box<dyn (Fn(TypeFuncIndex, &InstanceType<'_>) -> Result<()>) + Send + Sync>,
-    func: Box<dyn Any + Send + Sync>,
+    typecheck: Arc<dyn (Fn(TypeFuncIndex, &InstanceType<'_>) -> Result<()>) + Send + Sync>,
+    func: Arc<dyn Any + Send + Sync>,
+    name: Option<Arc<str>>,
 }
 
 impl HostFunc {
@@ -34,8 +38,9 @@ impl HostFunc {
         let entrypoint = Self::entrypoint::<T, F, P, R>;
         Arc::new(HostFunc {
             entrypoint,
-            typecheck: Box::new(typecheck::<P, R>),
-            func: Box::new(func),
+            typecheck: Arc::new(typecheck::<P, R>),
+            func: Arc::new(func),
+            name: None,
         })
     }
 
@@ -54,11 +59,12 @@ impl HostFunc {
         P: ComponentNamedList + Lift + 'static,
         R: ComponentNamedList + Lower + 'static,
     {
-        let data = data as *const F;
         unsafe {
+            let (func, data) = HostFunc::from_data::<F>(data);
             handle_result(|| {
                 call_host::<_, _, _, _>(
                     cx,
+                    func.name.as_ref(),
                     ty,
                     flags,
                     memory,
@@ -81,7 +87,7 @@ impl HostFunc {
     {
         Arc::new(HostFunc {
             entrypoint: dynamic_entrypoint::<T, F>,
-            typecheck: Box::new({
+            typecheck: Arc::new({
                 let types = types.clone();
 
                 move |expected_index, expected_types| {
@@ -92,7 +98,62 @@ impl HostFunc {
                     }
                 }
             }),
-            func: Box::new(func),
+            func: Arc::new(func),
+            name: None,
+        })
+    }
+
+    /// Same as [`HostFunc::new_dynamic`] except that the function is
+    /// typechecked structurally against the `params` and `results` provided,
+    /// which may come from a different component than the one importing it.
+    pub(crate) fn new_dynamic_with_types<T, F>(
+        func: F,
+        params: Box<[Type]>,
+        results: Box<[Type]>,
+    ) -> Arc<HostFunc>
+    where
+        F: Fn(StoreContextMut<'_, T>, &[Val], &mut [Val]) -> Result<()> + Send + Sync + 'static,
+    {
+        Arc::new(HostFunc {
+            entrypoint: dynamic_entrypoint::<T, F>,
+            typecheck: structural_typecheck(params, results),
+            func: Arc::new(func),
+            name: None,
+        })
+    }
+
+    /// Creates a function which calls the export `callee` of another instance,
+    /// as defined by [`LinkerInstance::instance_exports`].
+    ///
+    /// Calls are forwarded to `callee` directly when its type allows, and
+    /// otherwise go through `func` with [`Val`]s. The function is typechecked
+    /// as with [`HostFunc::new_dynamic_with_types`].
+    ///
+    /// [`LinkerInstance::instance_exports`]: crate::component::LinkerInstance::instance_exports
+    pub(crate) fn new_forwarding<T, F>(
+        store: &StoreOpaque,
+        callee: Func,
+        func: F,
+        params: Box<[Type]>,
+        results: Box<[Type]>,
+    ) -> Arc<HostFunc>
+    where
+        F: Fn(StoreContextMut<'_, T>, &[Val], &mut [Val]) -> Result<()> + Send + Sync + 'static,
+    {
+        let forward = match Forward::new(store, &callee) {
+            Some(forward) => forward,
+            None => return HostFunc::new_dynamic_with_types(func, params, results),
+        };
+        Arc::new(HostFunc {
+            entrypoint: forwarding_entrypoint::<T, F>,
+            typecheck: structural_typecheck(params, results),
+            func: Arc::new(Forwarding {
+                forward,
+                callee,
+                store_id: store.id(),
+                func,
+            }),
+            name: None,
         })
     }
 
@@ -100,13 +161,55 @@ impl HostFunc {
         (self.typecheck)(ty, types)
     }
 
+    /// Returns a copy of this function which is named `name`, the name it's
+    /// defined with in a linker.
+    ///
+    /// The copy shares the closure of this function, so the same function can
+    /// be defined under several names.
+    pub(crate) fn with_name(&self, name: String) -> Arc<HostFunc> {
+        Arc::new(HostFunc {
+            entrypoint: self.entrypoint,
+            typecheck: self.typecheck.clone(),
+            func: self.func.clone(),
+            name: Some(name.into()),
+        })
+    }
+
     pub fn lowering(&self) -> VMLowering {
-        let data = &*self.func as *const (dyn Any + Send + Sync) as *mut u8;
+        let data = self as *const HostFunc as *mut u8;
         VMLowering {
             callee: self.entrypoint,
             data,
         }
     }
+
+    /// Recovers the `HostFunc` and its closure from the `data` pointer of a
+    /// lowering created with [`HostFunc::lowering`].
+    ///
+    /// This is unsafe as `F` must be the type of the closure.
+    unsafe fn from_data<'a, F>(data: *mut u8) -> (&'a HostFunc, *const F) {
+        let func = &*(data as *const HostFunc);
+        let data = &*func.func as *const (dyn Any + Send + Sync) as *const F;
+        (func, data)
+    }
+}
+
+/// Returns a typecheck which succeeds for function types structurally equal to
+/// one with `params` and `results`.
+fn structural_typecheck(
+    params: Box<[Type]>,
+    results: Box<[Type]>,
+) -> Arc<dyn (Fn(TypeFuncIndex, &InstanceType<'_>) -> Result<()>) + Send + Sync> {
+    Arc::new(move |expected_index, expected_types| {
+        let expected = ComponentFunc::from(expected_index, expected_types);
+        if expected.params().eq(params.iter().cloned())
+            && expected.results().eq(results.iter().cloned())
+        {
+            Ok(())
+        } else {
+            Err(anyhow!("function type mismatch"))
+        }
+    })
 }
 
 fn typecheck<P, R>(ty: TypeFuncIndex, types: &InstanceType<'_>) -> Result<()>
@@ -144,6 +247,7 @@ where
 /// the select few places it's intended to be called from.
 unsafe fn call_host<T, Params, Return, F>(
     cx: *mut VMOpaqueContext,
+    func_name: Option<&Arc<str>>,
     ty: TypeFuncIndex,
     mut flags: InstanceFlags,
     memory: *mut VMMemoryDefinition,
@@ -221,7 +325,7 @@ where
         }
     };
     let mut lift = LiftContext::new(cx.0, &options, types, instance);
-    lift.enter_call();
+    lift.enter_call(func_name);
     let params = storage.lift_params(&mut lift, param_tys)?;
 
     let ret = closure(cx.as_context_mut(), params)?;
@@ -311,6 +415,7 @@ unsafe fn handle_result(func: impl FnOnce() -> Result<()>) {
 
 unsafe fn call_host_dynamic<T, F>(
     cx: *mut VMOpaqueContext,
+    func_name: Option<&Arc<str>>,
     ty: TypeFuncIndex,
     mut flags: InstanceFlags,
     memory: *mut VMMemoryDefinition,
@@ -347,7 +452,7 @@ where
     let param_tys = &types[func_ty.params];
     let result_tys = &types[func_ty.results];
     let mut cx = LiftContext::new(store.0, &options, types, instance);
-    cx.enter_call();
+    cx.enter_call(func_name);
     if let Some(param_count) = param_tys.abi.flat_count(MAX_FLAT_PARAMS) {
         // NB: can use `MaybeUninit::slice_assume_init_ref` when that's stable
         let mut iter =
@@ -438,11 +543,12 @@ extern "C" fn dynamic_entrypoint<T, F>(
 ) where
     F: Fn(StoreContextMut<'_, T>, &[Val], &mut [Val]) -> Result<()> + Send + Sync + 'static,
 {
-    let data = data as *const F;
     unsafe {
+        let (func, data) = HostFunc::from_data::<F>(data);
         handle_result(|| {
             call_host_dynamic::<T, _>(
                 cx,
+                func.name.as_ref(),
                 ty,
                 flags,
                 memory,
@@ -454,3 +560,82 @@ extern "C" fn dynamic_entrypoint<T, F>(
         })
     }
 }
+
+/// The data of functions created with [`HostFunc::new_forwarding`].
+struct Forwarding<F> {
+    forward: Forward,
+    callee: Func,
+    store_id: StoreId,
+    /// The closure called with [`Val`]s when a call can't be forwarded.
+    func: F,
+}
+
+extern "C" fn forwarding_entrypoint<T, F>(
+    cx: *mut VMOpaqueContext,
+    data: *mut u8,
+    ty: TypeFuncIndex,
+    flags: InstanceFlags,
+    memory: *mut VMMemoryDefinition,
+    realloc: *mut VMFuncRef,
+    string_encoding: StringEncoding,
+    storage: *mut MaybeUninit<ValRaw>,
+    storage_len: usize,
+) where
+    F: Fn(StoreContextMut<'_, T>, &[Val], &mut [Val]) -> Result<()> + Send + Sync + 'static,
+{
+    unsafe {
+        let (func, data) = HostFunc::from_data::<Forwarding<F>>(data);
+        let data = &*data;
+        let storage = std::slice::from_raw_parts_mut(storage, storage_len);
+        handle_result(|| {
+            let vmcx = VMComponentContext::from_opaque(cx);
+            let instance = (*vmcx).instance();
+            let mut store = StoreContextMut::<T>::from_raw((*instance).store());
+            if store.0.id() != data.store_id {
+                bail!("cannot call an instance's export from a different store");
+            }
+            if !data
+                .forward
+                .supports(store.0, &data.callee, string_encoding)
+            {
+                return call_host_dynamic::<T, _>(
+                    cx,
+                    func.name.as_ref(),
+                    ty,
+                    flags,
+                    memory,
+                    realloc,
+                    string_encoding,
+                    storage,
+                    |store, params, results| (data.func)(store, params, results),
+                );
+            }
+
+            let options = Options::new(
+                store.0.id(),
+                NonNull::new(memory),
+                NonNull::new(realloc),
+                string_encoding,
+            );
+
+            // Perform a dynamic check that this instance can indeed be left,
+            // as `call_host` does.
+            if !flags.may_leave() {
+                bail!("cannot leave component instance");
+            }
+
+            let types = (*instance).component_types();
+            LiftContext::new(store.0, &options, types, instance).enter_call(func.name.as_ref());
+            data.forward.call(
+                &mut store,
+                &data.callee,
+                &options,
+                types,
+                instance,
+                flags,
+                storage,
+            )?;
+            LowerContext::new(store, &options, types, instance).exit_call()
+        })
+    }
+}
@@ -1,7 +1,7 @@
 use crate::component::func::{Func, LiftContext, LowerContext, Options};
 use crate::component::matching::InstanceType;
 use crate::component::storage::{storage_as_slice, storage_as_slice_mut};
-use crate::{AsContextMut, StoreContext, StoreContextMut, ValRaw};
+use crate::{AsContextMut, CallOptions, CallReport, StoreContext, StoreContextMut, ValRaw};
 use anyhow::{anyhow, bail, Context, Result};
 use std::borrow::Cow;
 use std::fmt;
@@ -162,6 +162,28 @@ where
         self.call_impl(store, params)
     }
 
+    /// Same as [`TypedFunc::call`], but with the fuel and epoch budgets
+    /// configured in `options` applied for the duration of this call.
+    ///
+    /// Note that the budgets don't apply to [`TypedFunc::post_return`], and
+    /// there is currently no async version of this method, so it can't be
+    /// used with async stores.
+    ///
+    /// For more information see [`Func::call_with_options`].
+    ///
+    /// # Panics
+    ///
+    /// Panics in the same situations as [`TypedFunc::call`].
+    pub fn call_with_options(
+        &self,
+        mut store: impl AsContextMut,
+        params: Params,
+        options: &CallOptions,
+    ) -> Result<(Return, CallReport)> {
+        let mut store = store.as_context_mut();
+        store.with_call_options(options, |store| self.call(store, params))
+    }
+
     /// Exactly like [`Self::call`], except for use on asynchronous stores.
     ///
     /// # Panics
@@ -1505,6 +1527,8 @@ where
 /// performed when they're iterated.
 ///
 /// Note that this type does not implement the [`Lower`] trait, only [`Lift`].
+/// To lower a `list<u8>` without first buffering it on the host see
+/// [`LowerBytes`].
 pub struct WasmList<T> {
     ptr: usize,
     len: usize,
@@ -1706,6 +1730,123 @@ unsafe impl<T: Lift> Lift for WasmList<T> {
     }
 }
 
+/// A `list<u8>` which is written directly into guest memory when lowered.
+///
+/// Lowering a `&[u8]` or `Vec<u8>` into a component requires the bytes to
+/// already be present in host memory, after which they're copied into a
+/// buffer allocated in the guest's linear memory. For large payloads that's
+/// often one copy too many, so this type instead allocates the guest buffer
+/// and then invokes a callback with a mutable view of it. The callback is
+/// responsible for filling in all `len` bytes of the buffer, for example by
+/// reading from a file or a socket directly into it.
+///
+/// This type can be used anywhere a `list<u8>` is lowered, for example as a
+/// parameter to a [`TypedFunc`] or as the result of a host function.
+///
+/// Note that this type does not implement the [`Lift`] trait, only [`Lower`].
+/// Use [`WasmList<u8>`] and [`WasmList::as_le_slice`] to read a `list<u8>`
+/// from a component without copying it.
+///
+/// # Examples
+///
+/// ```
+/// # use wasmtime::component::{Instance, LowerBytes};
+/// # use wasmtime::Store;
+/// use std::fs::File;
+/// use std::io::Read;
+///
+/// fn process_file(store: &mut Store<()>, instance: &Instance, file: &File) -> anyhow::Result<()> {
+///     let len = usize::try_from(file.metadata()?.len())?;
+///     let contents = LowerBytes::new(len, |buf: &mut [u8]| {
+///         let mut file = file;
+///         file.read_exact(buf)?;
+///         Ok(())
+///     });
+///     let process = instance.get_typed_func::<(LowerBytes<_>,), ()>(&mut *store, "process")?;
+///     process.call(&mut *store, (contents,))?;
+///     process.post_return(&mut *store)?;
+///     Ok(())
+/// }
+/// ```
+pub struct LowerBytes<F> {
+    len: usize,
+    write: F,
+}
+
+impl<F> LowerBytes<F>
+where
+    F: Fn(&mut [u8]) -> Result<()>,
+{
+    /// Creates a new `list<u8>` of `len` bytes whose contents are produced by
+    /// `write`.
+    ///
+    /// When this value is lowered a buffer of `len` bytes is allocated in the
+    /// guest and `write` is called with a slice of linear memory covering
+    /// that buffer. The slice may initially contain arbitrary bytes. Any error
+    /// returned by `write` is propagated as the result of lowering.
+    pub fn new(len: usize, write: F) -> LowerBytes<F> {
+        LowerBytes { len, write }
+    }
+
+    /// Returns the length, in bytes, of this list.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    fn lower_bytes<T>(&self, cx: &mut LowerContext<'_, T>) -> Result<(usize, usize)> {
+        let ptr = cx.realloc(0, 0, 1, self.len)?;
+        // Note that `realloc` has already verified that the returned region is
+        // in-bounds of linear memory.
+        let dst = &mut cx.as_slice_mut()[ptr..][..self.len];
+        (self.write)(dst)?;
+        Ok((ptr, self.len))
+    }
+}
+
+unsafe impl<F> ComponentType for LowerBytes<F> {
+    type Lower = <[u8] as ComponentType>::Lower;
+
+    const ABI: CanonicalAbiInfo = CanonicalAbiInfo::POINTER_PAIR;
+
+    fn typecheck(ty: &InterfaceType, types: &InstanceType<'_>) -> Result<()> {
+        <[u8] as ComponentType>::typecheck(ty, types)
+    }
+}
+
+unsafe impl<F> Lower for LowerBytes<F>
+where
+    F: Fn(&mut [u8]) -> Result<()>,
+{
+    fn lower<U>(
+        &self,
+        cx: &mut LowerContext<'_, U>,
+        ty: InterfaceType,
+        dst: &mut MaybeUninit<[ValRaw; 2]>,
+    ) -> Result<()> {
+        debug_assert!(matches!(ty, InterfaceType::List(_)));
+        let (ptr, len) = self.lower_bytes(cx)?;
+        // See "WRITEPTR64" above for why this is always storing a 64-bit
+        // integer.
+        map_maybe_uninit!(dst[0]).write(ValRaw::i64(ptr as i64));
+        map_maybe_uninit!(dst[1]).write(ValRaw::i64(len as i64));
+        Ok(())
+    }
+
+    fn store<U>(
+        &self,
+        cx: &mut LowerContext<'_, U>,
+        ty: InterfaceType,
+        offset: usize,
+    ) -> Result<()> {
+        debug_assert!(matches!(ty, InterfaceType::List(_)));
+        debug_assert!(offset % (Self::ALIGN32 as usize) == 0);
+        let (ptr, len) = self.lower_bytes(cx)?;
+        *cx.get(offset + 0) = (ptr as i32).to_le_bytes();
+        *cx.get(offset + 4) = (len as i32).to_le_bytes();
+        Ok(())
+    }
+}
+
 /// Verify that the given wasm type is a tuple with the expected fields in the right order.
 fn typecheck_tuple(
     ty: &InterfaceType,
@@ -1,7 +1,8 @@
 use crate::component::func::HostFunc;
 use crate::component::matching::InstanceType;
 use crate::component::{
-    Component, ComponentNamedList, Func, Lift, Lower, ResourceImportIndex, ResourceType, TypedFunc,
+    Component, ComponentNamedList, Func, Lift, Lower, ResourceImportIndex, ResourceLeak,
+    ResourceType, TypedFunc,
 };
 use crate::instance::OwnedImports;
 use crate::linker::DefinitionType;
@@ -14,8 +15,8 @@ use std::ptr::NonNull;
 use std::sync::Arc;
 use wasmtime_environ::component::*;
 use wasmtime_environ::{EntityIndex, EntityType, Global, PrimaryMap, WasmValType};
-use wasmtime_runtime::component::{ComponentInstance, OwnedComponentInstance};
-use wasmtime_runtime::VMFuncRef;
+use wasmtime_runtime::component::{ComponentInstance, InstanceFlags, OwnedComponentInstance};
+use wasmtime_runtime::{SendSyncPtr, VMFuncRef};
 
 /// An instantiated component.
 ///
@@ -223,6 +224,17 @@ impl InstanceData {
         InstanceType::new(self.instance())
     }
 
+    /// Returns the `own` resource handles which are still live in the
+    /// resource tables of this instance.
+    pub fn resource_leaks(&self) -> impl Iterator<Item = ResourceLeak> + '_ {
+        let ty = self.ty();
+        self.instance()
+            .live_resource_handles()
+            .map(move |(table, rep, func)| {
+                ResourceLeak::new(ty.resource_type(table), rep, func.cloned())
+            })
+    }
+
     // NB: This method is only intended to be called during the instantiation
     // process because the `Arc::get_mut` here is fallible and won't generally
     // succeed once the instance has been handed to the embedder. Before that
@@ -279,6 +291,12 @@ impl<'a> Instantiator<'a> {
         store.modules_mut().register_component(component);
         let imported_resources: ImportedResources =
             PrimaryMap::with_capacity(env_component.imported_resources.len());
+        let mut state = OwnedComponentInstance::new(
+            component.runtime_info(),
+            Arc::new(imported_resources),
+            store.traitobj(),
+        );
+        state.set_max_resource_handles(store.resource_handle_limit());
         Instantiator {
             component,
             imports,
@@ -286,11 +304,7 @@ impl<'a> Instantiator<'a> {
             data: InstanceData {
                 instances: PrimaryMap::with_capacity(env_component.num_runtime_instances as usize),
                 component: component.clone(),
-                state: OwnedComponentInstance::new(
-                    component.runtime_info(),
-                    Arc::new(imported_resources),
-                    store.traitobj(),
-                ),
+                state,
                 imports: imports.clone(),
             },
         }
@@ -666,6 +680,7 @@ impl<'store> Exports<'store> {
         let data = self.data.as_ref().unwrap();
         ExportInstance {
             exports: &data.component.env_component().exports,
+            path: None,
             instance: &self.instance,
             data,
             store: self.store,
@@ -703,6 +718,9 @@ impl Drop for Exports<'_> {
 /// can be used to provide nested access to sub-instances.
 pub struct ExportInstance<'a, 'store> {
     exports: &'a IndexMap<String, Export>,
+    /// The names of the instances leading to this one, joined with `#`, or
+    /// `None` for the root or if the store doesn't record names.
+    path: Option<Arc<str>>,
     instance: &'a Instance,
     data: &'a InstanceData,
     store: &'store mut StoreOpaque,
@@ -719,6 +737,7 @@ impl<'a, 'store> ExportInstance<'a, 'store> {
                 *ty,
                 func,
                 options,
+                self.qualified_name(name),
             )),
             Export::ModuleStatic(_)
             | Export::ModuleImport { .. }
@@ -765,6 +784,26 @@ impl<'a, 'store> ExportInstance<'a, 'store> {
         }
     }
 
+    /// Returns the names of all items exported from this instance.
+    pub(crate) fn names(&self) -> impl Iterator<Item = &'a str> {
+        self.exports.keys().map(|name| name.as_str())
+    }
+
+    /// Returns the destructor of the resource exported as `name`, along with
+    /// the flags of the instance that defined the resource, if any.
+    pub(crate) fn resource_destructor(
+        &self,
+        name: &str,
+    ) -> Option<(Option<SendSyncPtr<VMFuncRef>>, Option<InstanceFlags>)> {
+        match self.exports.get(name)? {
+            Export::Type(TypeDef::Resource(id)) => {
+                let (dtor, flags) = self.data.state.dtor_and_flags(*id);
+                Some((dtor.map(SendSyncPtr::new), flags))
+            }
+            _ => None,
+        }
+    }
+
     /// Returns an iterator of all of the exported modules that this instance
     /// contains.
     //
@@ -791,9 +830,25 @@ impl<'a, 'store> ExportInstance<'a, 'store> {
         })
     }
 
+    /// Returns the name of the export `name` of this instance, prefixed with
+    /// the path to this instance.
+    ///
+    /// Names are only needed to attribute resource leaks, so this returns
+    /// `None` unless the store records them.
+    fn qualified_name(&self, name: &str) -> Option<Arc<str>> {
+        if !self.store.component_tracks_resource_origins() {
+            return None;
+        }
+        Some(match &self.path {
+            Some(path) => format!("{path}#{name}").into(),
+            None => name.into(),
+        })
+    }
+
     fn as_mut(&mut self) -> ExportInstance<'a, '_> {
         ExportInstance {
             exports: self.exports,
+            path: self.path.clone(),
             instance: self.instance,
             data: self.data,
             store: self.store,
@@ -812,6 +867,7 @@ impl<'a, 'store> ExportInstance<'a, 'store> {
         match self.exports.get(name)? {
             Export::Instance { exports, .. } => Some(ExportInstance {
                 exports,
+                path: self.qualified_name(name),
                 instance: self.instance,
                 data: self.data,
                 store: self.store,
@@ -1,21 +1,26 @@
 use crate::component::func::HostFunc;
-use crate::component::instance::RuntimeImport;
+use crate::component::instance::{ExportInstance, RuntimeImport};
 use crate::component::matching::{InstanceType, TypeChecker};
-use crate::component::types;
+use crate::component::types::{self, Type};
 use crate::component::{
-    Component, ComponentNamedList, Instance, InstancePre, Lift, Lower, ResourceType, Val,
+    Component, ComponentNamedList, Func, Instance, InstancePre, Lift, Lower, ResourceType, Val,
 };
-use crate::{AsContextMut, Engine, Module, StoreContextMut};
+use crate::{AsContextMut, Engine, Module, StoreContextMut, Trap, ValRaw};
 use anyhow::{anyhow, bail, Context, Result};
 use indexmap::IndexMap;
 use std::collections::hash_map::{Entry, HashMap};
+use std::collections::HashSet;
 use std::future::Future;
 use std::marker;
 use std::ops::Deref;
 use std::pin::Pin;
 use std::sync::Arc;
-use wasmtime_environ::component::TypeDef;
+use wasmtime_environ::component::{
+    ComponentTypes, InterfaceType, ResourceIndex, TypeDef, TypeFuncIndex,
+};
 use wasmtime_environ::{EntityRef, PrimaryMap};
+use wasmtime_runtime::component::InstanceFlags;
+use wasmtime_runtime::{SendSyncPtr, VMFuncRef};
 
 /// A type used to instantiate [`Component`]s.
 ///
@@ -211,6 +216,83 @@ impl<T> Linker<T> {
         ))
     }
 
+    /// Implements any function imports of `component` which aren't defined in
+    /// this linker with a function which traps.
+    ///
+    /// This walks the imports of `component`, including the exports of
+    /// imported instances, and defines each missing function as a stub which
+    /// returns an error when called. Missing instances are defined with
+    /// stubs for all of their contents, and missing resources are defined
+    /// as resource types distinct from any defined by the host. Imports which
+    /// are already defined are left as-is, even if they have the wrong type.
+    ///
+    /// This can be used to instantiate components whose imports are only
+    /// partially implemented, for example in tests which only exercise part
+    /// of a component.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use wasmtime::*;
+    /// # use wasmtime::component::*;
+    /// # fn main() -> anyhow::Result<()> {
+    /// # let mut config = Config::new();
+    /// # config.wasm_component_model(true);
+    /// # let engine = Engine::new(&config)?;
+    /// # let component = Component::new(&engine, r#"
+    /// #     (component
+    /// #         (import "unknown" (instance
+    /// #             (export "f" (func))
+    /// #         ))
+    /// #     )
+    /// # "#)?;
+    /// # let mut store = Store::new(&engine, ());
+    /// let mut linker = Linker::new(&engine);
+    /// linker.define_unknown_imports_as_traps(&component)?;
+    /// linker.instantiate(&mut store, &component)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn define_unknown_imports_as_traps(&mut self, component: &Component) -> Result<()> {
+        self.define_unknown_imports(component, false)
+    }
+
+    /// Implements any function imports of `component` which aren't defined in
+    /// this linker with a function that ignores its arguments and returns
+    /// default values.
+    ///
+    /// Default values are zero, `false`, or empty for primitive types, strings,
+    /// lists and flags; `none` for options; the first case of variants and
+    /// enums; and `ok` for results, with the default value of each payload.
+    /// Functions which return resources have no default value and trap when
+    /// called instead.
+    ///
+    /// Otherwise this is the same as
+    /// [`Linker::define_unknown_imports_as_traps`].
+    pub fn define_unknown_imports_as_default_values(
+        &mut self,
+        component: &Component,
+    ) -> Result<()> {
+        self.define_unknown_imports(component, true)
+    }
+
+    fn define_unknown_imports(
+        &mut self,
+        component: &Component,
+        default_values: bool,
+    ) -> Result<()> {
+        let mut stubs = StubDefiner {
+            types: component.types(),
+            default_values,
+            seen_resources: HashSet::new(),
+        };
+        let mut root = self.root();
+        for (name, ty) in component.env_component().import_types.values() {
+            stubs.define(&mut root, name, ty)?;
+        }
+        Ok(())
+    }
+
     /// Performs a "pre-instantiation" to resolve the imports of the
     /// [`Component`] specified with the items defined within this linker.
     ///
@@ -566,7 +648,99 @@ impl<T> LinkerInstance<'_, T> {
         Ok(self)
     }
 
+    /// Defines a nested instance named `name` within this instance which
+    /// provides the items of the instance of the same name exported from
+    /// `instance`.
+    ///
+    /// This can be used to link components together at runtime: once a
+    /// component exporting an interface has been instantiated, its exports can
+    /// satisfy the imports of other components instantiated afterwards in the
+    /// same [`Store`](crate::Store). Exported functions, resources, modules
+    /// and nested instances are all defined.
+    ///
+    /// Calls into the functions defined here are forwarded to `instance`, and
+    /// are typechecked structurally when the importing component is
+    /// instantiated. Where a function's parameters and results are made of
+    /// scalars, strings, and lists of integers, calls are forwarded directly
+    /// as a fused adapter between the two instances would: arguments are
+    /// validated and passed as they are, and the contents of strings and
+    /// lists are copied once from the caller's linear memory to the callee's,
+    /// and likewise for results. Other types, and strings between instances
+    /// which don't both use UTF-8, are forwarded through the host with
+    /// dynamically-typed [`Val`]s instead. Handles to resources exported from
+    /// `instance` can be passed between components, and dropping an owned
+    /// handle runs the destructor defined by `instance`. This works in both
+    /// synchronous and asynchronous stores.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `instance` does not export an instance named
+    /// `name`, or if `name` is already defined and shadowing is disallowed.
+    ///
+    /// Calling the items defined here from an instance in a different store
+    /// than the one `instance` belongs to will also return an error.
+    pub fn instance_exports(
+        &mut self,
+        mut store: impl AsContextMut<Data = T>,
+        instance: &Instance,
+        name: &str,
+    ) -> Result<()> {
+        let mut store = store.as_context_mut();
+        let items = {
+            let mut exports = instance.exports(store.as_context_mut());
+            let mut exports = exports
+                .instance(name)
+                .ok_or_else(|| anyhow!("instance export `{name}` not found"))?;
+            InstanceItem::collect(&mut exports)
+        };
+        let mut linker = self.instance(name)?;
+        for (name, item) in items {
+            item.define(&mut linker, &store, &name)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the instance `key` within this instance, defining it if it
+    /// doesn't exist yet, or `None` if something other than an instance is
+    /// defined with that name.
+    fn existing_instance(&mut self, key: usize) -> Option<LinkerInstance<'_, T>> {
+        let map = match self
+            .map
+            .entry(key)
+            .or_insert_with(|| Definition::Instance(NameMap::default()))
+        {
+            Definition::Instance(map) => map,
+            _ => return None,
+        };
+        self.path.truncate(self.path_len);
+        self.path.push(key);
+        Some(LinkerInstance {
+            engine: self.engine,
+            path: self.path,
+            path_len: self.path_len + 1,
+            strings: self.strings,
+            map,
+            resource_imports: self.resource_imports,
+            allow_shadowing: self.allow_shadowing,
+            _marker: self._marker,
+        })
+    }
+
     fn insert(&mut self, key: usize, item: Definition) -> Result<()> {
+        let item = match item {
+            Definition::Func(func) => Definition::Func(
+                func.with_name(
+                    self.path
+                        .iter()
+                        .take(self.path_len)
+                        .chain([&key])
+                        .map(|name| &*self.strings.strings[*name])
+                        .collect::<Vec<_>>()
+                        .join("#"),
+                ),
+            ),
+            item => item,
+        };
         match self.map.entry(key) {
             Entry::Occupied(_) if !self.allow_shadowing => {
                 bail!("import of `{}` defined twice", self.strings.strings[key])
@@ -582,6 +756,298 @@ impl<T> LinkerInstance<'_, T> {
     }
 }
 
+/// An item exported from an [`Instance`] which is defined in a linker by
+/// [`LinkerInstance::instance_exports`].
+enum InstanceItem {
+    Func(Func),
+    Module(Module),
+    Resource(
+        ResourceType,
+        Option<SendSyncPtr<VMFuncRef>>,
+        Option<InstanceFlags>,
+    ),
+    Instance(Vec<(String, InstanceItem)>),
+}
+
+impl InstanceItem {
+    fn collect(exports: &mut ExportInstance<'_, '_>) -> Vec<(String, InstanceItem)> {
+        let names = exports.names().collect::<Vec<_>>();
+        let mut items = Vec::new();
+        for name in names {
+            let item = if let Some(func) = exports.func(name) {
+                InstanceItem::Func(func)
+            } else if let Some(module) = exports.module(name) {
+                InstanceItem::Module(module.clone())
+            } else if let Some(ty) = exports.resource(name) {
+                let (dtor, flags) = exports.resource_destructor(name).unwrap();
+                InstanceItem::Resource(ty, dtor, flags)
+            } else if let Some(mut instance) = exports.instance(name) {
+                InstanceItem::Instance(Self::collect(&mut instance))
+            } else {
+                continue;
+            };
+            items.push((name.to_string(), item));
+        }
+        items
+    }
+
+    fn define<T>(
+        self,
+        linker: &mut LinkerInstance<'_, T>,
+        store: &StoreContextMut<'_, T>,
+        name: &str,
+    ) -> Result<()> {
+        let store_id = store.0.id();
+        match self {
+            InstanceItem::Func(func) => {
+                let params = func.params(store);
+                let results = func.results(store);
+                let host = HostFunc::new_forwarding(
+                    store.0,
+                    func,
+                    move |mut store: StoreContextMut<'_, T>,
+                          params: &[Val],
+                          results: &mut [Val]| {
+                        if store.0.id() != store_id {
+                            bail!("cannot call an instance's export from a different store");
+                        }
+                        // Host functions already run on the calling guest's
+                        // fiber in async stores, so the `*_impl` versions of
+                        // these methods are used to work in both kinds of
+                        // store.
+                        func.call_impl(&mut store, params, results)?;
+                        func.post_return_impl(&mut store)
+                    },
+                    params,
+                    results,
+                );
+                let name = linker.strings.intern(name);
+                linker.insert(name, Definition::Func(host))
+            }
+            InstanceItem::Module(module) => linker.module(name, &module),
+            InstanceItem::Resource(ty, dtor, flags) => {
+                linker.resource(name, ty, move |mut store, rep| {
+                    if store.0.id() != store_id {
+                        bail!("cannot drop an instance's resource from a different store");
+                    }
+                    // Implement the reentrance check required by the canonical
+                    // ABI, as `ResourceAny::resource_drop` does.
+                    if let Some(flags) = flags {
+                        if unsafe { !flags.may_enter() } {
+                            bail!(Trap::CannotEnterComponent);
+                        }
+                    }
+                    let dtor = match dtor {
+                        Some(dtor) => dtor.as_non_null(),
+                        None => return Ok(()),
+                    };
+                    let mut args = [ValRaw::u32(rep)];
+                    // This should be safe because `dtor` belongs to the
+                    // instance that exported this resource, which lives as
+                    // long as `store` does, and destructors have been
+                    // type-checked to take a single i32 argument.
+                    unsafe {
+                        crate::Func::call_unchecked_raw(
+                            &mut store,
+                            dtor,
+                            args.as_mut_ptr(),
+                            args.len(),
+                        )
+                    }
+                })?;
+                Ok(())
+            }
+            InstanceItem::Instance(items) => {
+                let mut linker = linker.instance(name)?;
+                for (name, item) in items {
+                    item.define(&mut linker, store, &name)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Type of the resources defined by
+/// [`Linker::define_unknown_imports_as_traps`], which can't be created by the
+/// host.
+struct UnknownResource;
+
+/// State for defining stubs for the unknown imports of a component.
+struct StubDefiner<'a> {
+    types: &'a Arc<ComponentTypes>,
+    default_values: bool,
+    /// Resources which have already been imported, either because they were
+    /// defined in the linker or by a stub. Later imports of these resources
+    /// are `(eq ...)` imports which don't need a definition.
+    seen_resources: HashSet<ResourceIndex>,
+}
+
+impl StubDefiner<'_> {
+    fn define<T>(
+        &mut self,
+        linker: &mut LinkerInstance<'_, T>,
+        name: &str,
+        ty: &TypeDef,
+    ) -> Result<()> {
+        let key = linker.strings.intern(name);
+        let defined = linker.map.contains_key(&key);
+        match *ty {
+            TypeDef::ComponentInstance(index) => {
+                let mut instance = match linker.existing_instance(key) {
+                    Some(instance) => instance,
+                    None => return Ok(()),
+                };
+                let types = self.types;
+                for (name, ty) in types[index].exports.iter() {
+                    self.define(&mut instance, name, ty)?;
+                }
+            }
+            TypeDef::ComponentFunc(index) if !defined => {
+                let path = linker
+                    .path
+                    .iter()
+                    .take(linker.path_len)
+                    .map(|name| &*linker.strings.strings[*name])
+                    .chain([name])
+                    .collect::<Vec<_>>()
+                    .join("#");
+                let func = if self.default_values {
+                    match self.default_results(index) {
+                        Some(results) => HostFunc::new_dynamic(
+                            move |_: StoreContextMut<'_, T>, _: &[Val], dst: &mut [Val]| {
+                                dst.clone_from_slice(&results);
+                                Ok(())
+                            },
+                            index,
+                            self.types,
+                        ),
+                        None => Self::trap(
+                            format!("cannot return default values from unknown import `{path}`"),
+                            index,
+                            self.types,
+                        ),
+                    }
+                } else {
+                    Self::trap(
+                        format!("unknown import: `{path}` has not been defined"),
+                        index,
+                        self.types,
+                    )
+                };
+                linker.insert(key, Definition::Func(func))?;
+            }
+            TypeDef::Resource(index) => {
+                let resource = self.types[index].ty;
+                if self.seen_resources.insert(resource) && !defined {
+                    linker.resource(
+                        name,
+                        ResourceType::host::<UnknownResource>(),
+                        |_, _| Ok(()),
+                    )?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn trap<T>(
+        message: String,
+        index: TypeFuncIndex,
+        types: &Arc<ComponentTypes>,
+    ) -> Arc<HostFunc> {
+        HostFunc::new_dynamic(
+            move |_: StoreContextMut<'_, T>, _: &[Val], _: &mut [Val]| bail!("{message}"),
+            index,
+            types,
+        )
+    }
+
+    /// Returns the default results of the function `index`, or `None` if any
+    /// of them involve resources.
+    fn default_results(&self, index: TypeFuncIndex) -> Option<Vec<Val>> {
+        let results = &self.types[self.types[index].results];
+        if results.types.iter().any(|ty| self.has_resource(ty)) {
+            return None;
+        }
+        // Without resources the types here never need the resources of an
+        // instance, so an empty map suffices.
+        let resources = Arc::new(PrimaryMap::new());
+        let instance = InstanceType {
+            types: self.types,
+            resources: &resources,
+        };
+        Some(
+            results
+                .types
+                .iter()
+                .map(|ty| default_value(&Type::from(ty, &instance)))
+                .collect(),
+        )
+    }
+
+    fn has_resource(&self, ty: &InterfaceType) -> bool {
+        let types = &**self.types;
+        match *ty {
+            InterfaceType::Own(_) | InterfaceType::Borrow(_) => true,
+            InterfaceType::List(i) => self.has_resource(&types[i].element),
+            InterfaceType::Record(i) => types[i].fields.iter().any(|f| self.has_resource(&f.ty)),
+            InterfaceType::Tuple(i) => types[i].types.iter().any(|t| self.has_resource(t)),
+            InterfaceType::Variant(i) => types[i]
+                .cases
+                .iter()
+                .any(|c| c.ty.as_ref().map_or(false, |t| self.has_resource(t))),
+            InterfaceType::Option(i) => self.has_resource(&types[i].ty),
+            InterfaceType::Result(i) => [&types[i].ok, &types[i].err]
+                .into_iter()
+                .any(|t| t.as_ref().map_or(false, |t| self.has_resource(t))),
+            _ => false,
+        }
+    }
+}
+
+/// Returns the default value of `ty`, which must not involve resources.
+fn default_value(ty: &Type) -> Val {
+    let val = match ty {
+        Type::Bool => return Val::Bool(false),
+        Type::S8 => return Val::S8(0),
+        Type::U8 => return Val::U8(0),
+        Type::S16 => return Val::S16(0),
+        Type::U16 => return Val::U16(0),
+        Type::S32 => return Val::S32(0),
+        Type::U32 => return Val::U32(0),
+        Type::S64 => return Val::S64(0),
+        Type::U64 => return Val::U64(0),
+        Type::Float32 => return Val::Float32(0.0),
+        Type::Float64 => return Val::Float64(0.0),
+        Type::Char => return Val::Char('\0'),
+        Type::String => return Val::String("".into()),
+        Type::List(ty) => ty.new_val(Box::new([])),
+        Type::Record(ty) => {
+            let fields = ty
+                .fields()
+                .map(|f| (f.name, default_value(&f.ty)))
+                .collect::<Vec<_>>();
+            ty.new_val(fields)
+        }
+        Type::Tuple(ty) => ty.new_val(ty.types().map(|t| default_value(&t)).collect()),
+        Type::Variant(ty) => {
+            let case = ty.cases().next().expect("variants have at least one case");
+            ty.new_val(case.name, case.ty.map(|t| default_value(&t)))
+        }
+        Type::Enum(ty) => {
+            let name = ty.names().next().expect("enums have at least one case");
+            ty.new_val(name)
+        }
+        Type::Option(ty) => ty.new_val(None),
+        Type::Result(ty) => ty.new_val(Ok(ty.ok().map(|t| default_value(&t)))),
+        Type::Flags(ty) => ty.new_val(&[]),
+        Type::Own(_) | Type::Borrow(_) => unreachable!("resources have no default value"),
+    };
+    val.expect("default values always match their type")
+}
+
 impl Strings {
     fn intern(&mut self, string: &str) -> usize {
         if let Some(idx) = self.string2idx.get(string) {
@@ -54,12 +54,12 @@ pub mod types;
 mod values;
 pub use self::component::Component;
 pub use self::func::{
-    ComponentNamedList, ComponentType, Func, Lift, Lower, TypedFunc, WasmList, WasmStr,
+    ComponentNamedList, ComponentType, Func, Lift, Lower, LowerBytes, TypedFunc, WasmList, WasmStr,
 };
 pub use self::instance::{ExportInstance, Exports, Instance, InstancePre};
 pub use self::linker::{Linker, LinkerInstance, ResourceImportIndex};
-pub use self::resource_table::{ResourceTable, ResourceTableError};
-pub use self::resources::{Resource, ResourceAny};
+pub use self::resource_table::{ResourceTable, ResourceTableEntry, ResourceTableError};
+pub use self::resources::{Resource, ResourceAny, ResourceLeak};
 pub use self::types::{ResourceType, Type};
 pub use self::values::{Enum, Flags, List, OptionVal, Record, ResultVal, Tuple, Val, Variant};
 
@@ -346,6 +346,20 @@ pub(crate) use self::store::ComponentStoreData;
 ///     // This option defaults to `false`.
 ///     tracing: true,
 ///
+///     // Generate a `HostMock` type in the module of each imported interface
+///     // which implements the interface's `Host` traits. Each call to the
+///     // mock is recorded in its `calls` field as a `HostMockCall`, and
+///     // values returned from calls are configured with the mock's
+///     // `return_*` and `expect_*` methods. Resources are backed by the
+///     // mock's `table` field. This is intended for use in tests.
+///     //
+///     // Functions imported directly by the world are likewise mocked by a
+///     // `{World}ImportsMock` type. The `convert_*` methods required by
+///     // `trappable_error_type` are mocked too, but aren't recorded.
+///     //
+///     // This option defaults to `false`.
+///     mocks: true,
+///
 ///     // Imports will be async functions through #[async_trait] and exports
 ///     // are also invoked as async functions. Requires `Config::async_support`
 ///     // to be `true`.
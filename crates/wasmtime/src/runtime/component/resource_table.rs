@@ -1,6 +1,7 @@
 use super::Resource;
 use std::any::Any;
 use std::collections::{BTreeSet, HashMap};
+use std::panic::Location;
 
 #[derive(Debug)]
 /// Errors returned by operations on `ResourceTable`
@@ -33,6 +34,7 @@ impl std::error::Error for ResourceTableError {}
 pub struct ResourceTable {
     entries: Vec<Entry>,
     free_head: Option<usize>,
+    max_entries: usize,
 }
 
 #[derive(Debug)]
@@ -74,14 +76,21 @@ struct TableEntry {
     parent: Option<u32>,
     /// The indicies of any children of this entry.
     children: BTreeSet<u32>,
+    /// The name of the type of `entry`.
+    type_name: &'static str,
+    /// The location in the source that inserted this entry.
+    created_at: &'static Location<'static>,
 }
 
 impl TableEntry {
-    fn new(entry: Box<dyn Any + Send>, parent: Option<u32>) -> Self {
+    #[track_caller]
+    fn new<T: Send + 'static>(entry: T, parent: Option<u32>) -> Self {
         Self {
-            entry,
+            entry: Box::new(entry),
             parent,
             children: BTreeSet::new(),
+            type_name: std::any::type_name::<T>(),
+            created_at: Location::caller(),
         }
     }
     fn add_child(&mut self, child: u32) {
@@ -100,6 +109,7 @@ impl ResourceTable {
         ResourceTable {
             entries: Vec::new(),
             free_head: None,
+            max_entries: usize::MAX,
         }
     }
 
@@ -108,16 +118,30 @@ impl ResourceTable {
         ResourceTable {
             entries: Vec::with_capacity(capacity),
             free_head: None,
+            max_entries: usize::MAX,
         }
     }
 
+    /// Limits the number of entries which can be live in this table at once
+    /// to `max`.
+    ///
+    /// Pushing an entry beyond this limit returns [`ResourceTableError::Full`].
+    /// Entries already in the table are unaffected.
+    pub fn set_max_entries(&mut self, max: usize) {
+        self.max_entries = max;
+    }
+
     /// Inserts a new value `T` into this table, returning a corresponding
     /// `Resource<T>` which can be used to refer to it after it was inserted.
+    ///
+    /// The caller's location is recorded and can be retrieved with
+    /// [`ResourceTable::entries`].
+    #[track_caller]
     pub fn push<T>(&mut self, entry: T) -> Result<Resource<T>, ResourceTableError>
     where
         T: Send + 'static,
     {
-        let idx = self.push_(TableEntry::new(Box::new(entry), None))?;
+        let idx = self.push_(TableEntry::new(entry, None))?;
         Ok(Resource::new_own(idx))
     }
 
@@ -159,6 +183,10 @@ impl ResourceTable {
             self.entries[free] = Entry::Occupied { entry: e };
             Ok(free as u32)
         } else {
+            // The free list is empty so every entry is occupied here.
+            if self.entries.len() >= self.max_entries {
+                return Err(ResourceTableError::Full);
+            }
             let ix = self
                 .entries
                 .len()
@@ -201,8 +229,9 @@ impl ResourceTable {
     ///
     /// Parent-child relationships may not be modified once created. There
     /// is no way to observe these relationships through the [`ResourceTable`]
-    /// methods except for erroring on deletion, or the [`std::fmt::Debug`]
-    /// impl.
+    /// methods except for erroring on deletion, [`ResourceTable::entries`], or
+    /// the [`std::fmt::Debug`] impl.
+    #[track_caller]
     pub fn push_child<T, U>(
         &mut self,
         entry: T,
@@ -214,7 +243,7 @@ impl ResourceTable {
     {
         let parent = parent.rep();
         self.occupied(parent)?;
-        let child = self.push_(TableEntry::new(Box::new(entry), Some(parent)))?;
+        let child = self.push_(TableEntry::new(entry, Some(parent)))?;
         self.occupied_mut(parent)?.add_child(child);
         Ok(Resource::new_own(child))
     }
@@ -297,6 +326,22 @@ impl ResourceTable {
         })
     }
 
+    /// Returns information about all of the entries currently in this table.
+    ///
+    /// This can be used to find resources which were never deleted, for
+    /// example when the `Store` which this table belongs to is destroyed.
+    pub fn entries(&self) -> impl Iterator<Item = ResourceTableEntry> + '_ {
+        self.entries.iter().enumerate().filter_map(|(rep, e)| {
+            let e = e.occupied()?;
+            Some(ResourceTableEntry {
+                rep: rep as u32,
+                type_name: e.type_name,
+                created_at: e.created_at,
+                parent: e.parent,
+            })
+        })
+    }
+
     /// Iterate over all children belonging to the provided parent
     pub fn iter_children<T>(
         &self,
@@ -313,6 +358,42 @@ impl ResourceTable {
     }
 }
 
+/// Information about an entry in a [`ResourceTable`], returned by
+/// [`ResourceTable::entries`].
+#[derive(Debug, Copy, Clone)]
+pub struct ResourceTableEntry {
+    rep: u32,
+    type_name: &'static str,
+    created_at: &'static Location<'static>,
+    parent: Option<u32>,
+}
+
+impl ResourceTableEntry {
+    /// Returns the index of this entry in the table, which is the same as
+    /// [`Resource::rep`] for the resource referring to it.
+    pub fn rep(&self) -> u32 {
+        self.rep
+    }
+
+    /// Returns the name of the type stored in this entry, as produced by
+    /// [`std::any::type_name`].
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+    /// Returns the location of the call to [`ResourceTable::push`] or
+    /// [`ResourceTable::push_child`] which created this entry.
+    pub fn created_at(&self) -> &'static Location<'static> {
+        self.created_at
+    }
+
+    /// Returns the index of this entry's parent, if it was created with
+    /// [`ResourceTable::push_child`].
+    pub fn parent(&self) -> Option<u32> {
+        self.parent
+    }
+}
+
 impl Default for ResourceTable {
     fn default() -> Self {
         ResourceTable::new()
@@ -348,3 +429,39 @@ pub fn test_free_list() {
     let x = table.push(()).unwrap();
     assert_eq!(x.rep(), 2);
 }
+
+#[test]
+pub fn test_max_entries() {
+    let mut table = ResourceTable::new();
+    table.set_max_entries(2);
+
+    let x = table.push(()).unwrap();
+    let _y = table.push(()).unwrap();
+    assert!(matches!(table.push(()), Err(ResourceTableError::Full)));
+
+    // Freed entries can be reused without exceeding the limit.
+    table.delete(x).unwrap();
+    let x = table.push(()).unwrap();
+    assert_eq!(x.rep(), 0);
+    assert!(matches!(table.push(()), Err(ResourceTableError::Full)));
+}
+
+#[test]
+pub fn test_entries() {
+    let mut table = ResourceTable::new();
+
+    let x = table.push(1u32).unwrap();
+    let line = line!() - 1;
+    let y = table.push_child(String::new(), &x).unwrap();
+    table.push(()).unwrap();
+    table.delete(y).unwrap();
+
+    let entries = table.entries().collect::<Vec<_>>();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].rep(), x.rep());
+    assert_eq!(entries[0].type_name(), "u32");
+    assert_eq!(entries[0].created_at().file(), file!());
+    assert_eq!(entries[0].created_at().line(), line);
+    assert_eq!(entries[0].parent(), None);
+    assert_eq!(entries[1].type_name(), "()");
+}
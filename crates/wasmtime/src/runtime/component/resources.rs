@@ -11,11 +11,12 @@ use std::fmt;
 use std::marker;
 use std::mem::MaybeUninit;
 use std::sync::atomic::{AtomicU64, Ordering::Relaxed};
+use std::sync::Arc;
 use wasmtime_environ::component::{
-    CanonicalAbiInfo, DefinedResourceIndex, InterfaceType, TypeResourceTableIndex,
+    CanonicalAbiInfo, DefinedResourceIndex, InterfaceType, ResourceIndex, TypeResourceTableIndex,
 };
 use wasmtime_runtime::component::{ComponentInstance, InstanceFlags, ResourceTables};
-use wasmtime_runtime::{SendSyncPtr, VMFuncRef, ValRaw};
+use wasmtime_runtime::{CompiledModuleId, SendSyncPtr, VMFuncRef, ValRaw};
 
 /// Representation of a resource type in the component model.
 ///
@@ -69,6 +70,15 @@ impl ResourceType {
             },
         }
     }
+
+    pub(crate) fn uninstantiated(
+        component: CompiledModuleId,
+        index: ResourceIndex,
+    ) -> ResourceType {
+        ResourceType {
+            kind: ResourceTypeKind::Uninstantiated { component, index },
+        }
+    }
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -82,6 +92,13 @@ enum ResourceTypeKind {
         instance: usize,
         id: DefinedResourceIndex,
     },
+    // A resource imported by a component which has no concrete type yet. The
+    // `component` field is the unique id of the component the index is
+    // relative to.
+    Uninstantiated {
+        component: CompiledModuleId,
+        index: ResourceIndex,
+    },
 }
 
 /// A host-defined resource in the component model.
@@ -397,15 +414,17 @@ impl<'a> HostResourceTables<'a> {
     /// will point to the `rep` specified as well as recording that it has the
     /// `ty` specified. The returned index is suitable for conversion into
     /// either [`Resource`] or [`ResourceAny`].
-    pub fn host_resource_lower_own(&mut self, rep: u32) -> HostResourceIndex {
-        let idx = self.tables.resource_lower_own(None, rep);
-        self.new_host_index(idx)
+    ///
+    /// Returns an error if the host's table is full.
+    pub fn host_resource_lower_own(&mut self, rep: u32) -> Result<HostResourceIndex> {
+        let idx = self.tables.resource_lower_own(None, rep)?;
+        Ok(self.new_host_index(idx))
     }
 
     /// See [`HostResourceTables::host_resource_lower_own`].
-    pub fn host_resource_lower_borrow(&mut self, rep: u32) -> HostResourceIndex {
-        let idx = self.tables.resource_lower_borrow(None, rep);
-        self.new_host_index(idx)
+    pub fn host_resource_lower_borrow(&mut self, rep: u32) -> Result<HostResourceIndex> {
+        let idx = self.tables.resource_lower_borrow(None, rep)?;
+        Ok(self.new_host_index(idx))
     }
 
     /// Validates that `idx` is still valid for the host tables, notably
@@ -482,7 +501,11 @@ impl<'a> HostResourceTables<'a> {
     /// into a guest-local index.
     ///
     /// The `ty` provided is which table to put this into.
-    pub fn guest_resource_lower_own(&mut self, rep: u32, ty: TypeResourceTableIndex) -> u32 {
+    pub fn guest_resource_lower_own(
+        &mut self,
+        rep: u32,
+        ty: TypeResourceTableIndex,
+    ) -> Result<u32> {
         self.tables.resource_lower_own(Some(ty), rep)
     }
 
@@ -495,7 +518,11 @@ impl<'a> HostResourceTables<'a> {
     /// into a guest has a special case where `rep` is returned directly if `ty`
     /// belongs to the component being lowered into. That property must be
     /// handled by the caller of this function.
-    pub fn guest_resource_lower_borrow(&mut self, rep: u32, ty: TypeResourceTableIndex) -> u32 {
+    pub fn guest_resource_lower_borrow(
+        &mut self,
+        rep: u32,
+        ty: TypeResourceTableIndex,
+    ) -> Result<u32> {
         self.tables.resource_lower_borrow(Some(ty), rep)
     }
 
@@ -521,9 +548,14 @@ impl<'a> HostResourceTables<'a> {
 
     /// Begins a call into the component instance, starting recording of
     /// metadata related to resource borrowing.
+    ///
+    /// The `func` is the name of the component function being called, which
+    /// is recorded in the `own` handles created during the call if a
+    /// [`Store::resource_leak_handler`](crate::Store::resource_leak_handler)
+    /// is configured.
     #[inline]
-    pub fn enter_call(&mut self) {
-        self.tables.enter_call()
+    pub fn enter_call(&mut self, func: Option<&Arc<str>>) {
+        self.tables.enter_call(func)
     }
 
     /// Completes a call into the component instance, validating that it's ok to
@@ -612,7 +644,7 @@ where
                     // can move the rep into the guest table.
                     ResourceState::Index(idx) => cx.host_resource_lift_own(idx)?,
                 };
-                Ok(cx.guest_resource_lower_own(t, rep))
+                cx.guest_resource_lower_own(t, rep)
             }
             InterfaceType::Borrow(t) => {
                 let rep = match self.state.get() {
@@ -632,7 +664,7 @@ where
                     //
                     // Afterwards this is the same as the `idx` case below.
                     ResourceState::NotInTable => {
-                        let idx = cx.host_resource_lower_own(self.rep);
+                        let idx = cx.host_resource_lower_own(self.rep)?;
                         let prev = self.state.swap(ResourceState::Index(idx));
                         assert_eq!(prev, ResourceState::NotInTable);
                         cx.host_resource_lift_borrow(idx)?
@@ -642,7 +674,7 @@ where
                     // out of the table with borrow-tracking employed.
                     ResourceState::Index(idx) => cx.host_resource_lift_borrow(idx)?,
                 };
-                Ok(cx.guest_resource_lower_borrow(t, rep))
+                cx.guest_resource_lower_borrow(t, rep)
             }
             _ => bad_type_info(),
         }
@@ -709,9 +741,10 @@ where
         let mut tables = HostResourceTables::new_host(store.0);
         let ResourceAny { idx, ty, own_state } = resource;
         ensure!(ty == ResourceType::host::<T>(), "resource type mismatch");
-        let (state, rep) = if let Some(OwnState { store, dtor, flags }) = own_state {
+        // Note that the destructor isn't set for resources created with
+        // `ResourceAny::try_from_host_resource`, so it's not checked here.
+        let (state, rep) = if let Some(OwnState { store, flags, .. }) = own_state {
             assert_eq!(store_id, store, "wrong store used to convert resource");
-            assert!(dtor.is_some(), "destructor must be set");
             assert!(flags.is_none(), "flags must not be set");
             let rep = tables.host_resource_lift_own(idx)?;
             (AtomicResourceState::NOT_IN_TABLE, rep)
@@ -864,7 +897,6 @@ impl ResourceAny {
         instance_pre: &InstancePre<U>,
         idx: ResourceImportIndex,
     ) -> Result<Self> {
-        let Resource { rep, state, .. } = resource;
         let store = store.as_context_mut();
         let import = instance_pre
             .resource_import(idx)
@@ -876,34 +908,61 @@ impl ResourceAny {
             bail!("import is not a resource")
         };
         ensure!(*ty == ResourceType::host::<T>(), "resource type mismatch");
+        Self::from_host_resource(resource, store.0, Some(dtor_funcref.into()))
+    }
 
-        let mut tables = HostResourceTables::new_host(store.0);
+    /// Attempts to convert a host [`Resource`] into [`ResourceAny`] without
+    /// the import it was defined with.
+    ///
+    /// The returned resource has the type `ResourceType::host::<T>()`. This
+    /// can be used by host functions which don't have an [`InstancePre`] at
+    /// hand, such as those defined with [`LinkerInstance::func_new`], to
+    /// return resources as a [`Val`](crate::component::Val).
+    ///
+    /// Unlike [`ResourceAny::try_from_resource`] the destructor of the
+    /// resource isn't known here, so it isn't run if an owned resource
+    /// returned by this function is destroyed with
+    /// [`ResourceAny::resource_drop`]. It is still run when a guest which
+    /// the resource was passed to drops it.
+    ///
+    /// [`LinkerInstance::func_new`]: crate::component::LinkerInstance::func_new
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if `resource` has already been
+    /// consumed.
+    pub fn try_from_host_resource<T: 'static>(
+        resource: Resource<T>,
+        mut store: impl AsContextMut,
+    ) -> Result<Self> {
+        Self::from_host_resource(resource, store.as_context_mut().0, None)
+    }
+
+    fn from_host_resource<T: 'static>(
+        resource: Resource<T>,
+        store: &mut StoreOpaque,
+        dtor: Option<SendSyncPtr<VMFuncRef>>,
+    ) -> Result<Self> {
+        let Resource { rep, state, .. } = resource;
+        let store_id = store.id();
+        let mut tables = HostResourceTables::new_host(store);
+        let own_state = || OwnState {
+            dtor,
+            flags: None,
+            store: store_id,
+        };
         let (idx, own_state) = match state.get() {
-            ResourceState::Borrow => (tables.host_resource_lower_borrow(rep), None),
+            ResourceState::Borrow => (tables.host_resource_lower_borrow(rep)?, None),
             ResourceState::NotInTable => {
-                let idx = tables.host_resource_lower_own(rep);
-                (
-                    idx,
-                    Some(OwnState {
-                        dtor: Some(dtor_funcref.into()),
-                        flags: None,
-                        store: store.0.id(),
-                    }),
-                )
+                let idx = tables.host_resource_lower_own(rep)?;
+                (idx, Some(own_state()))
             }
             ResourceState::Taken => bail!("host resource already consumed"),
-            ResourceState::Index(idx) => (
-                idx,
-                Some(OwnState {
-                    dtor: Some(dtor_funcref.into()),
-                    flags: None,
-                    store: store.0.id(),
-                }),
-            ),
+            ResourceState::Index(idx) => (idx, Some(own_state())),
         };
         Ok(Self {
             idx,
-            ty: *ty,
+            ty: ResourceType::host::<T>(),
             own_state,
         })
     }
@@ -1025,14 +1084,14 @@ impl ResourceAny {
                     bail!("mismatched resource types");
                 }
                 let rep = cx.host_resource_lift_own(self.idx)?;
-                Ok(cx.guest_resource_lower_own(t, rep))
+                cx.guest_resource_lower_own(t, rep)
             }
             InterfaceType::Borrow(t) => {
                 if cx.resource_type(t) != self.ty {
                     bail!("mismatched resource types");
                 }
                 let rep = cx.host_resource_lift_borrow(self.idx)?;
-                Ok(cx.guest_resource_lower_borrow(t, rep))
+                cx.guest_resource_lower_borrow(t, rep)
             }
             _ => bad_type_info(),
         }
@@ -1043,7 +1102,7 @@ impl ResourceAny {
             InterfaceType::Own(t) => {
                 let ty = cx.resource_type(t);
                 let (rep, dtor, flags) = cx.guest_resource_lift_own(t, index)?;
-                let idx = cx.host_resource_lower_own(rep);
+                let idx = cx.host_resource_lower_own(rep)?;
                 Ok(ResourceAny {
                     idx,
                     ty,
@@ -1057,7 +1116,7 @@ impl ResourceAny {
             InterfaceType::Borrow(t) => {
                 let ty = cx.resource_type(t);
                 let rep = cx.guest_resource_lift_borrow(t, index)?;
-                let idx = cx.host_resource_lower_borrow(rep);
+                let idx = cx.host_resource_lower_borrow(rep)?;
                 Ok(ResourceAny {
                     idx,
                     ty,
@@ -1135,3 +1194,49 @@ impl PartialEq for OwnState {
 }
 
 impl Eq for OwnState {}
+
+/// A resource handle which was still owned by a component instance when its
+/// [`Store`](crate::Store) was dropped.
+///
+/// These are passed to the handler configured with
+/// [`Store::resource_leak_handler`](crate::Store::resource_leak_handler).
+#[derive(Debug, Clone)]
+pub struct ResourceLeak {
+    ty: ResourceType,
+    rep: u32,
+    func: Option<Arc<str>>,
+}
+
+impl ResourceLeak {
+    pub(crate) fn new(ty: ResourceType, rep: u32, func: Option<Arc<str>>) -> ResourceLeak {
+        ResourceLeak { ty, rep, func }
+    }
+
+    /// Returns the type of the leaked resource.
+    ///
+    /// For host-defined resources this can be compared against
+    /// [`ResourceType::host`] to determine which host type was leaked.
+    pub fn ty(&self) -> ResourceType {
+        self.ty
+    }
+
+    /// Returns the representation of the leaked resource.
+    ///
+    /// For host-defined resources this is the same as [`Resource::rep`], for
+    /// example the index of the resource within a
+    /// [`ResourceTable`](crate::component::ResourceTable).
+    pub fn rep(&self) -> u32 {
+        self.rep
+    }
+
+    /// Returns the name of the component function which was being called
+    /// when the leaked handle was created.
+    ///
+    /// This is the name of the exported function called by the host, such as
+    /// `wasi:http/incoming-handler#handle`, or the name of the import called
+    /// by the guest if the handle was returned from an import. Returns `None`
+    /// if the handle wasn't created during a call from or to the host.
+    pub fn func(&self) -> Option<&str> {
+        self.func.as_deref()
+    }
+}
@@ -1,3 +1,4 @@
+use crate::component::ResourceLeak;
 use crate::store::{StoreData, StoredData};
 
 macro_rules! component_store_data {
@@ -26,3 +27,15 @@ component_store_data! {
     funcs => crate::component::func::FuncData,
     instances => Option<Box<crate::component::instance::InstanceData>>,
 }
+
+impl ComponentStoreData {
+    /// Returns the `own` resource handles which are still live in the
+    /// resource tables of all component instances in this store.
+    pub(crate) fn resource_leaks(&self) -> Vec<ResourceLeak> {
+        self.instances
+            .iter()
+            .flatten()
+            .flat_map(|instance| instance.resource_leaks())
+            .collect()
+    }
+}
@@ -148,6 +148,33 @@ impl Record {
             .zip(self.values.iter())
             .map(|(ty, val)| (ty.name, val))
     }
+
+    /// Returns the value of the field `name`, or `None` if this record has no
+    /// such field.
+    pub fn field(&self, name: &str) -> Option<&Val> {
+        self.fields()
+            .find(|(field, _)| *field == name)
+            .map(|(_, val)| val)
+    }
+
+    /// Returns the value of the field `name` converted to `T`.
+    ///
+    /// This can be used to read fields with types such as `u32`, `&str` or
+    /// `&Record`, for example.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if this record has no field named `name` or if the
+    /// field's value is not of type `T`.
+    pub fn get<'a, T>(&'a self, name: &str) -> Result<T>
+    where
+        T: TryFrom<&'a Val, Error = Error>,
+    {
+        let val = self
+            .field(name)
+            .ok_or_else(|| anyhow!("record has no field named `{name}`"))?;
+        T::try_from(val).with_context(|| format!("type mismatch for field {name} of record"))
+    }
 }
 
 impl fmt::Debug for Record {
@@ -293,6 +320,31 @@ impl Variant {
         self.value.as_deref()
     }
 
+    /// Returns the payload of this variant converted to `T` if this value is
+    /// of the case `name`, or `None` if it's of another case.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `name` is not a case of this variant's type, if the
+    /// case has no payload, or if the payload is not of type `T`.
+    pub fn get<'a, T>(&'a self, name: &str) -> Result<Option<T>>
+    where
+        T: TryFrom<&'a Val, Error = Error>,
+    {
+        if self.discriminant() != name {
+            if !self.ty.cases().any(|case| case.name == name) {
+                bail!("unknown variant case: {name}");
+            }
+            return Ok(None);
+        }
+        let payload = self
+            .payload()
+            .ok_or_else(|| anyhow!("variant case {name} has no payload"))?;
+        T::try_from(payload)
+            .map(Some)
+            .with_context(|| format!("type mismatch for payload of variant case {name}"))
+    }
+
     fn as_generic<'a>(
         &'a self,
         types: &'a ComponentTypes,
@@ -1126,6 +1178,74 @@ impl Val {
     }
 }
 
+macro_rules! try_from_val {
+    ($($variant:ident($ty:ty) => $desc:tt,)*) => {$(
+        impl TryFrom<&Val> for $ty {
+            type Error = Error;
+
+            fn try_from(val: &Val) -> Result<$ty> {
+                match val {
+                    Val::$variant(v) => Ok(*v),
+                    other => bail!("expected `{}`, found `{}`", $desc, other.ty().desc()),
+                }
+            }
+        }
+    )*};
+}
+
+try_from_val! {
+    Bool(bool) => "bool",
+    S8(i8) => "s8",
+    U8(u8) => "u8",
+    S16(i16) => "s16",
+    U16(u16) => "u16",
+    S32(i32) => "s32",
+    U32(u32) => "u32",
+    S64(i64) => "s64",
+    U64(u64) => "u64",
+    Float32(f32) => "float32",
+    Float64(f64) => "float64",
+    Char(char) => "char",
+    Resource(ResourceAny) => "resource",
+}
+
+macro_rules! try_from_val_ref {
+    ($($variant:ident($ty:ty) => $desc:tt,)*) => {$(
+        impl<'a> TryFrom<&'a Val> for &'a $ty {
+            type Error = Error;
+
+            fn try_from(val: &'a Val) -> Result<&'a $ty> {
+                match val {
+                    Val::$variant(v) => Ok(v),
+                    other => bail!("expected `{}`, found `{}`", $desc, other.ty().desc()),
+                }
+            }
+        }
+    )*};
+}
+
+impl<'a> TryFrom<&'a Val> for &'a str {
+    type Error = Error;
+
+    fn try_from(val: &'a Val) -> Result<&'a str> {
+        match val {
+            Val::String(s) => Ok(&**s),
+            other => bail!("expected `string`, found `{}`", other.ty().desc()),
+        }
+    }
+}
+
+try_from_val_ref! {
+    List(List) => "list",
+    Record(Record) => "record",
+    Tuple(Tuple) => "tuple",
+    Variant(Variant) => "variant",
+    Enum(Enum) => "enum",
+    Option(OptionVal) => "option",
+    Result(ResultVal) => "result",
+    Flags(Flags) => "flags",
+}
+
 impl PartialEq for Val {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
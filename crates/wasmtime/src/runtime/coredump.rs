@@ -1,5 +1,7 @@
 use std::{collections::HashMap, fmt};
 
+use wasm_encoder::CoreDumpValue;
+
 use crate::{
     store::StoreOpaque, AsContextMut, FrameInfo, Global, Instance, Memory, Module, StoreContextMut,
     Val, ValType, WasmBacktrace,
@@ -16,8 +18,9 @@ use crate::{
 /// the error is logged.
 ///
 /// Note that some state, such as Wasm locals or values on the operand stack,
-/// may be optimized away by the compiler or otherwise not recovered in the
-/// coredump.
+/// may not be recovered in the coredump. Only `i32`, `i64`, `f32`, and `f64`
+/// values which are stored on the stack at the point of the trap or call are
+/// recovered, and only for code compiled with Cranelift.
 ///
 /// Capturing of wasm coredumps can be configured through the
 /// [`Config::coredump_on_trap`][crate::Config::coredump_on_trap] method.
@@ -34,10 +37,17 @@ pub struct WasmCoreDump {
     memories: Vec<Memory>,
     globals: Vec<Global>,
     backtrace: WasmBacktrace,
+    locals: Vec<Vec<Option<Val>>>,
+    operand_stacks: Vec<Vec<Option<Val>>>,
 }
 
 impl WasmCoreDump {
-    pub(crate) fn new(store: &mut StoreOpaque, backtrace: WasmBacktrace) -> WasmCoreDump {
+    pub(crate) fn new(
+        store: &mut StoreOpaque,
+        backtrace: WasmBacktrace,
+        locals: Vec<Vec<CoreDumpValue>>,
+        operand_stacks: Vec<Vec<CoreDumpValue>>,
+    ) -> WasmCoreDump {
         let modules: Vec<_> = store.modules().all_modules().cloned().collect();
         let instances: Vec<Instance> = store.all_instances().collect();
         let store_memories: Vec<Memory> = store.all_memories().collect();
@@ -50,6 +60,14 @@ impl WasmCoreDump {
             memories: store_memories,
             globals: store_globals,
             backtrace,
+            locals: locals
+                .iter()
+                .map(|l| l.iter().map(to_val).collect())
+                .collect(),
+            operand_stacks: operand_stacks
+                .iter()
+                .map(|s| s.iter().map(to_val).collect())
+                .collect(),
         }
     }
 
@@ -61,6 +79,25 @@ impl WasmCoreDump {
         self.backtrace.frames()
     }
 
+    /// The values of the locals of each of the [`WasmCoreDump::frames`], in
+    /// the same order.
+    ///
+    /// Locals whose values weren't recorded are `None`, and frames whose state
+    /// wasn't recorded at all have no locals.
+    pub fn locals(&self) -> &[Vec<Option<Val>>] {
+        self.locals.as_ref()
+    }
+
+    /// The values on the operand stack of each of the
+    /// [`WasmCoreDump::frames`], in the same order, from the bottom of each
+    /// stack to the top.
+    ///
+    /// Values that weren't recorded are `None`, and frames whose state wasn't
+    /// recorded at all have empty operand stacks.
+    pub fn operand_stacks(&self) -> &[Vec<Option<Val>>] {
+        self.operand_stacks.as_ref()
+    }
+
     /// All modules instantiated inside the store when the core dump was
     /// created.
     pub fn modules(&self) -> &[Module] {
@@ -249,7 +286,7 @@ impl WasmCoreDump {
         {
             let thread_name = "main";
             let mut stack = wasm_encoder::CoreDumpStackSection::new(thread_name);
-            for frame in self.frames() {
+            for (i, frame) in self.frames().iter().enumerate() {
                 // This isn't necessarily the right instance if there are
                 // multiple instances of the same module. See comment above
                 // `module_to_instance` for details.
@@ -262,10 +299,8 @@ impl WasmCoreDump {
                     .and_then(|o| u32::try_from(o).ok())
                     .unwrap_or(0);
 
-                // We can't currently recover locals and the operand stack. We
-                // should eventually be able to do that with Winch though.
-                let locals = [];
-                let operand_stack = [];
+                let locals = self.locals[i].iter().map(from_val);
+                let operand_stack = self.operand_stacks[i].iter().map(from_val);
 
                 stack.frame(instance, func, offset, locals, operand_stack);
             }
@@ -276,6 +311,26 @@ impl WasmCoreDump {
     }
 }
 
+fn to_val(value: &CoreDumpValue) -> Option<Val> {
+    match *value {
+        CoreDumpValue::Missing => None,
+        CoreDumpValue::I32(x) => Some(Val::I32(x)),
+        CoreDumpValue::I64(x) => Some(Val::I64(x)),
+        CoreDumpValue::F32(x) => Some(Val::F32(x.to_bits())),
+        CoreDumpValue::F64(x) => Some(Val::F64(x.to_bits())),
+    }
+}
+
+fn from_val(value: &Option<Val>) -> CoreDumpValue {
+    match value {
+        Some(Val::I32(x)) => CoreDumpValue::I32(*x),
+        Some(Val::I64(x)) => CoreDumpValue::I64(*x),
+        Some(Val::F32(x)) => CoreDumpValue::F32(f32::from_bits(*x)),
+        Some(Val::F64(x)) => CoreDumpValue::F64(f64::from_bits(*x)),
+        _ => CoreDumpValue::Missing,
+    }
+}
+
 impl fmt::Display for WasmCoreDump {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         writeln!(f, "wasm coredump generated while executing {}:", self.name)?;
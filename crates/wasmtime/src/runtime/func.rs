@@ -1,8 +1,8 @@
 use crate::store::{StoreData, StoreOpaque, Stored};
 use crate::type_registry::RegisteredType;
 use crate::{
-    AsContext, AsContextMut, CallHook, Engine, Extern, FuncType, Instance, Module, StoreContext,
-    StoreContextMut, Val, ValRaw, ValType,
+    AsContext, AsContextMut, CallHook, CallOptions, CallReport, Engine, Extern, FuncType, Instance,
+    Module, StoreContext, StoreContextMut, Val, ValRaw, ValType,
 };
 use anyhow::{bail, Context as _, Error, Result};
 use std::ffi::c_void;
@@ -848,6 +848,36 @@ impl Func {
         self.call_impl(&mut store.as_context_mut(), params, results)
     }
 
+    /// Same as [`Func::call`], but with the fuel and epoch budgets configured
+    /// in `options` applied for the duration of this call.
+    ///
+    /// The store's previous epoch deadline is restored after the call
+    /// returns, as is its previous fuel less the fuel consumed by the call.
+    /// On success a [`CallReport`] is returned which describes
+    /// the resources used by the call.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error in the same situations as [`Func::call`], and
+    /// additionally if `options` configures a budget which isn't enabled in
+    /// the store's [`Config`](crate::Config).
+    ///
+    /// # Panics
+    ///
+    /// Panics in the same situations as [`Func::call`].
+    pub fn call_with_options(
+        &self,
+        mut store: impl AsContextMut,
+        params: &[Val],
+        results: &mut [Val],
+        options: &CallOptions,
+    ) -> Result<CallReport> {
+        let mut store = store.as_context_mut();
+        let ((), report) =
+            store.with_call_options(options, |store| self.call(store, params, results))?;
+        Ok(report)
+    }
+
     /// Invokes this function in an "unchecked" fashion, reading parameters and
     /// writing results to `params_and_returns`.
     ///
@@ -1,6 +1,9 @@
 use super::{invoke_wasm_and_catch_traps, HostAbi};
 use crate::store::{AutoAssertNoGc, StoreOpaque};
-use crate::{AsContextMut, ExternRef, Func, FuncType, StoreContextMut, ValRaw, ValType};
+use crate::{
+    AsContextMut, CallOptions, CallReport, ExternRef, Func, FuncType, StoreContextMut, ValRaw,
+    ValType,
+};
 use anyhow::{bail, Result};
 use std::marker;
 use std::mem::{self, MaybeUninit};
@@ -88,6 +91,24 @@ where
         unsafe { Self::call_raw(&mut store, func, params) }
     }
 
+    /// Same as [`TypedFunc::call`], but with the fuel and epoch budgets
+    /// configured in `options` applied for the duration of this call.
+    ///
+    /// For more information see [`Func::call_with_options`].
+    ///
+    /// # Panics
+    ///
+    /// Panics in the same situations as [`TypedFunc::call`].
+    pub fn call_with_options(
+        &self,
+        mut store: impl AsContextMut,
+        params: Params,
+        options: &CallOptions,
+    ) -> Result<(Results, CallReport)> {
+        let mut store = store.as_context_mut();
+        store.with_call_options(options, |store| self.call(store, params))
+    }
+
     /// Invokes this WebAssembly function with the specified parameters.
     ///
     /// Returns either the results of the call, or a [`Trap`] if one happened.
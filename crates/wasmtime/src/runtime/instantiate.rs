@@ -11,8 +11,8 @@ use std::ops::Range;
 use std::str;
 use std::sync::Arc;
 use wasmtime_environ::{
-    CompiledFunctionInfo, CompiledModuleInfo, DefinedFuncIndex, FinishedObject, FuncIndex,
-    FunctionLoc, FunctionName, Metadata, Module, ModuleInternedTypeIndex, ObjectBuilder,
+    CompiledFunctionInfo, CompiledModuleInfo, DefinedFuncIndex, EntityRef, FinishedObject,
+    FuncIndex, FunctionLoc, FunctionName, Metadata, Module, ModuleInternedTypeIndex, ObjectBuilder,
     PrimaryMap, StackMapInformation, WasmFunctionInfo,
 };
 use wasmtime_runtime::{CompiledModuleId, CompiledModuleIdAllocator, MmapVec};
@@ -250,6 +250,20 @@ impl CompiledModule {
         Some((index, text_offset - wasm_func_loc.start))
     }
 
+    /// Lookups the defined function whose wasm body contains the module
+    /// offset `offset`.
+    ///
+    /// Function bodies are laid out in order in the code section, so this is
+    /// the last function starting at or before `offset`.
+    pub fn func_by_wasm_offset(&self, offset: u32) -> Option<DefinedFuncIndex> {
+        match self.funcs.binary_search_values_by_key(&Some(offset), |f| {
+            f.wasm_func_info.start_srcloc.file_offset()
+        }) {
+            Ok(index) => Some(index),
+            Err(index) => Some(DefinedFuncIndex::new(index.index().checked_sub(1)?)),
+        }
+    }
+
     /// Gets the function location information for a given function index.
     pub fn func_loc(&self, index: DefinedFuncIndex) -> &FunctionLoc {
         &self
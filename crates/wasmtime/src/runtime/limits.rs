@@ -146,6 +146,19 @@ pub trait ResourceLimiter {
     fn memories(&self) -> usize {
         DEFAULT_MEMORY_LIMIT
     }
+
+    /// The maximum number of handles that can be live at once in each
+    /// component resource table of a `Store`.
+    ///
+    /// This limit applies individually to the table of each resource type
+    /// within each component instance, as well as to the table of resources
+    /// owned by the host. Creating or passing a handle which would exceed the
+    /// limit, for example with `resource.new`, will fail with a trap.
+    ///
+    /// By default, resource handles will not be limited.
+    fn resource_handles(&self) -> usize {
+        usize::MAX
+    }
 }
 
 /// Used by hosts to limit resource consumption of instances, blocking
@@ -210,6 +223,11 @@ pub trait ResourceLimiterAsync {
     fn memories(&self) -> usize {
         DEFAULT_MEMORY_LIMIT
     }
+
+    /// Identical to [`ResourceLimiter::resource_handles`]`
+    fn resource_handles(&self) -> usize {
+        usize::MAX
+    }
 }
 
 /// Used to build [`StoreLimits`].
@@ -279,6 +297,18 @@ impl StoreLimitsBuilder {
         self
     }
 
+    /// The maximum number of handles that can be live at once in each
+    /// component resource table of a [`Store`](crate::Store).
+    ///
+    /// Creating or passing a resource handle will trap if this limit is
+    /// exceeded.
+    ///
+    /// By default, resource handles will not be limited.
+    pub fn resource_handles(mut self, limit: usize) -> Self {
+        self.0.resource_handles = limit;
+        self
+    }
+
     /// Indicates that a trap should be raised whenever a growth operation
     /// would fail.
     ///
@@ -314,6 +344,7 @@ pub struct StoreLimits {
     instances: usize,
     tables: usize,
     memories: usize,
+    resource_handles: usize,
     trap_on_grow_failure: bool,
 }
 
@@ -325,6 +356,7 @@ impl Default for StoreLimits {
             instances: DEFAULT_INSTANCE_LIMIT,
             tables: DEFAULT_TABLE_LIMIT,
             memories: DEFAULT_MEMORY_LIMIT,
+            resource_handles: usize::MAX,
             trap_on_grow_failure: false,
         }
     }
@@ -395,4 +427,8 @@ impl ResourceLimiter for StoreLimits {
     fn memories(&self) -> usize {
         self.memories
     }
+
+    fn resource_handles(&self) -> usize {
+        self.resource_handles
+    }
 }
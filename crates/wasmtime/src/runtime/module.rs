@@ -1239,6 +1239,29 @@ impl wasmtime_runtime::ModuleInfo for ModuleInner {
 
         Some(&info.stack_maps[index].stack_map)
     }
+
+    fn lookup_frame_state(
+        &self,
+        pc: usize,
+    ) -> Option<(
+        &wasmtime_environ::FrameStateInformation,
+        Option<&[wasmtime_environ::SavedRegister]>,
+    )> {
+        let text_offset = pc - self.module.text().as_ptr() as usize;
+        let (index, func_offset) = self.module.func_by_text_offset(text_offset)?;
+        let info = self.module.wasm_func_info(index);
+
+        // Frame states are only recorded at traps and calls, which are the
+        // only places a frame can be when a core dump is captured.
+        let index = info
+            .frame_states
+            .binary_search_by_key(&func_offset, |i| i.code_offset)
+            .ok()?;
+        Some((
+            &info.frame_states[index],
+            info.frame_saved_registers.as_deref(),
+        ))
+    }
 }
 
 /// A barebones implementation of ModuleRuntimeInfo that is useful for
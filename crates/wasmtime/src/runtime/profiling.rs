@@ -47,6 +47,13 @@ use wasmtime_runtime::Backtrace;
 /// introduces worse bias since samples occur after a certain number of
 /// WebAssembly instructions, which can take different amounts of time.
 ///
+/// Alternatively, for profiles which are exactly reproducible between runs,
+/// create the profiler with [`GuestProfiler::new_with_fuel`] and call
+/// [`GuestProfiler::sample_fuel`] from a callback registered with
+/// [`Store::fuel_callback()`](crate::Store::fuel_callback). Each sample is then
+/// weighted by the fuel consumed since the previous one, so the profile
+/// attributes consumed fuel, rather than time, to functions and call stacks.
+///
 /// You may instead be able to use platform-specific methods, such as
 /// `setitimer(ITIMER_VIRTUAL, ...)` on POSIX-compliant systems, to sample on
 /// a more accurate interval. The only current requirement is that the guest
@@ -79,7 +86,18 @@ pub struct GuestProfiler {
     modules: Vec<(Range<usize>, fxprof_processed_profile::LibraryHandle)>,
     process: fxprof_processed_profile::ProcessHandle,
     thread: fxprof_processed_profile::ThreadHandle,
-    start: Instant,
+    clock: Clock,
+}
+
+/// What a profile's timestamps measure.
+#[cfg(feature = "profiling")]
+#[derive(Debug)]
+enum Clock {
+    /// Wall-clock time since the profiler was created.
+    WallTime(Instant),
+    /// Fuel consumed since the profiler was created, as passed to
+    /// `sample_fuel`.
+    Fuel(u64),
 }
 
 #[cfg(feature = "profiling")]
@@ -100,6 +118,45 @@ impl GuestProfiler {
     /// "Security" section of the [`GuestProfiler`] documentation for guidance
     /// on what modules should not be included in this list.
     pub fn new(module_name: &str, interval: Duration, modules: Vec<(String, Module)>) -> Self {
+        let mut profiler = Self::new_with_clock(
+            module_name,
+            interval,
+            modules,
+            Clock::WallTime(Instant::now()),
+        );
+        profiler
+            .profile
+            .set_reference_timestamp(std::time::SystemTime::now().into());
+        profiler
+    }
+
+    /// Begin profiling a new guest, measuring the fuel it consumes rather
+    /// than wall-clock time.
+    ///
+    /// Timestamps in the resulting profile count units of fuel, displayed as
+    /// if each unit took one nanosecond, and the profile contains nothing else
+    /// which varies between runs: profiling the same modules with the same
+    /// inputs produces identical output. Samples should be added with
+    /// [`GuestProfiler::sample_fuel`].
+    ///
+    /// The `interval` parameter should match the amount of fuel configured
+    /// with [`Store::fuel_callback()`](crate::Store::fuel_callback). The other
+    /// parameters are the same as for [`GuestProfiler::new`].
+    pub fn new_with_fuel(module_name: &str, interval: u64, modules: Vec<(String, Module)>) -> Self {
+        Self::new_with_clock(
+            module_name,
+            Duration::from_nanos(interval),
+            modules,
+            Clock::Fuel(0),
+        )
+    }
+
+    fn new_with_clock(
+        module_name: &str,
+        interval: Duration,
+        modules: Vec<(String, Module)>,
+        clock: Clock,
+    ) -> Self {
         let zero = ReferenceTimestamp::from_millis_since_unix_epoch(0.0);
         let mut profile = Profile::new(module_name, zero, interval.into());
 
@@ -115,16 +172,23 @@ impl GuestProfiler {
 
         modules.sort_unstable_by_key(|(range, _)| range.start);
 
-        profile.set_reference_timestamp(std::time::SystemTime::now().into());
         let process = profile.add_process(module_name, 0, Timestamp::from_nanos_since_reference(0));
         let thread = profile.add_thread(process, 0, Timestamp::from_nanos_since_reference(0), true);
-        let start = Instant::now();
         Self {
             profile,
             modules,
             process,
             thread,
-            start,
+            clock,
+        }
+    }
+
+    fn now(&self) -> Timestamp {
+        match &self.clock {
+            Clock::WallTime(start) => Timestamp::from_nanos_since_reference(
+                start.elapsed().as_nanos().try_into().unwrap(),
+            ),
+            Clock::Fuel(consumed) => Timestamp::from_nanos_since_reference(*consumed),
         }
     }
 
@@ -137,9 +201,28 @@ impl GuestProfiler {
     /// guest since the previous sample. It is allowed to pass `Duration::ZERO`
     /// here if recording CPU usage information is not needed.
     pub fn sample(&mut self, store: impl AsContext, delta: Duration) {
-        let now = Timestamp::from_nanos_since_reference(
-            self.start.elapsed().as_nanos().try_into().unwrap(),
-        );
+        self.add_sample(store, delta, 1);
+    }
+
+    /// Add a sample to the profile, weighted by the amount of `fuel` consumed
+    /// by this guest since the previous sample. It should typically be called
+    /// from a callback registered using
+    /// [`Store::fuel_callback()`](crate::Store::fuel_callback), using
+    /// [`StoreContext::get_fuel`](crate::StoreContext::get_fuel) to find out
+    /// how much fuel was consumed.
+    ///
+    /// For profilers created with [`GuestProfiler::new_with_fuel`] this also
+    /// advances the profile's timestamps by `fuel`.
+    pub fn sample_fuel(&mut self, store: impl AsContext, fuel: u64) {
+        if let Clock::Fuel(consumed) = &mut self.clock {
+            *consumed += fuel;
+        }
+        let weight = i32::try_from(fuel).unwrap_or(i32::MAX);
+        self.add_sample(store, Duration::from_nanos(fuel), weight);
+    }
+
+    fn add_sample(&mut self, store: impl AsContext, delta: Duration, weight: i32) {
+        let now = self.now();
 
         let backtrace = Backtrace::new(store.as_context().0.vmruntime_limits());
         let frames = backtrace
@@ -168,7 +251,7 @@ impl GuestProfiler {
             });
 
         self.profile
-            .add_sample(self.thread, now, frames, delta.into(), 1);
+            .add_sample(self.thread, now, frames, delta.into(), weight);
     }
 
     /// When the guest finishes running, call this function to write the
@@ -178,9 +261,7 @@ impl GuestProfiler {
     ///
     /// [fmt]: https://github.com/firefox-devtools/profiler/blob/main/docs-developer/processed-profile-format.md
     pub fn finish(mut self, output: impl std::io::Write) -> Result<()> {
-        let now = Timestamp::from_nanos_since_reference(
-            self.start.elapsed().as_nanos().try_into().unwrap(),
-        );
+        let now = self.now();
         self.profile.set_thread_end_time(self.thread, now);
         self.profile.set_process_end_time(self.process, now);
 
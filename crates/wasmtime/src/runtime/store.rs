@@ -107,6 +107,8 @@ mod data;
 pub use self::data::*;
 mod func_refs;
 use func_refs::FuncRefs;
+mod snapshot;
+pub use self::snapshot::StoreSnapshot;
 
 /// A [`Store`] is a collection of WebAssembly instances and host-defined state.
 ///
@@ -219,6 +221,10 @@ pub struct StoreInner<T> {
     call_hook: Option<CallHookInner<T>>,
     epoch_deadline_behavior:
         Option<Box<dyn FnMut(StoreContextMut<T>) -> Result<UpdateDeadline> + Send + Sync>>,
+    fuel_callback: Option<Box<dyn FnMut(StoreContextMut<T>) -> Result<()> + Send + Sync>>,
+    #[cfg(feature = "component-model")]
+    resource_leak_handler:
+        Option<Box<dyn FnOnce(&mut T, &[crate::component::ResourceLeak]) + Send + Sync>>,
     // for comments about `ManuallyDrop`, see `Store::into_data`
     data: ManuallyDrop<T>,
 }
@@ -256,6 +262,71 @@ pub enum UpdateDeadline {
     Yield(u64),
 }
 
+/// Options for a single call into WebAssembly, such as with
+/// [`Func::call_with_options`](crate::Func::call_with_options).
+///
+/// These options configure a fuel and epoch budget which only apply for the
+/// duration of one call. The store's previous fuel and epoch deadline are
+/// restored once the call returns, regardless of whether it succeeded.
+///
+/// Options are only supported for synchronous calls: there are no
+/// `call_async` counterparts which take them, so async stores must continue
+/// to configure budgets on the [`Store`] itself.
+#[derive(Debug, Default, Clone)]
+pub struct CallOptions {
+    fuel: Option<u64>,
+    epoch_deadline: Option<u64>,
+}
+
+impl CallOptions {
+    /// Creates a new set of options which don't change the store's budgets.
+    pub fn new() -> CallOptions {
+        CallOptions::default()
+    }
+
+    /// Configures the amount of fuel the call may consume.
+    ///
+    /// This budget replaces the store's fuel for the duration of the call.
+    /// Afterwards the store's previous fuel is restored, less whatever the
+    /// call consumed.
+    ///
+    /// Running out of fuel behaves the same as it does for
+    /// [`Store::set_fuel`]. Calls using this option fail if fuel consumption
+    /// isn't enabled via [`Config::consume_fuel`](crate::Config::consume_fuel).
+    pub fn fuel(&mut self, fuel: u64) -> &mut Self {
+        self.fuel = Some(fuel);
+        self
+    }
+
+    /// Configures the epoch deadline of the call to `ticks_beyond_current`
+    /// ticks after the current epoch.
+    ///
+    /// Reaching the deadline behaves the same as it does for
+    /// [`Store::set_epoch_deadline`]. Calls using this option fail if epoch
+    /// interruption isn't enabled via
+    /// [`Config::epoch_interruption`](crate::Config::epoch_interruption).
+    pub fn epoch_deadline(&mut self, ticks_beyond_current: u64) -> &mut Self {
+        self.epoch_deadline = Some(ticks_beyond_current);
+        self
+    }
+}
+
+/// Information about a call made with [`CallOptions`].
+#[derive(Debug, Clone)]
+pub struct CallReport {
+    fuel_consumed: u64,
+}
+
+impl CallReport {
+    /// Returns the amount of fuel consumed by the call.
+    ///
+    /// This is zero if fuel consumption isn't enabled in the store's
+    /// [`Config`](crate::Config).
+    pub fn fuel_consumed(&self) -> u64 {
+        self.fuel_consumed
+    }
+}
+
 // Forward methods on `StoreOpaque` to also being on `StoreInner<T>`
 impl<T> Deref for StoreInner<T> {
     type Target = StoreOpaque;
@@ -318,14 +389,18 @@ pub struct StoreOpaque {
     memory_limit: usize,
     table_count: usize,
     table_limit: usize,
+    /// Maximum number of live handles in each component resource table.
+    #[cfg(feature = "component-model")]
+    resource_handle_limit: usize,
     #[cfg(feature = "async")]
     async_state: AsyncState,
-    // If fuel_yield_interval is enabled, then we store the remaining fuel (that isn't in
-    // runtime_limits) here. The total amount of fuel is the runtime limits and reserve added
-    // together. Then when we run out of gas, we inject the yield amount from the reserve
-    // until the reserve is empty.
+    // If fuel_yield_interval or fuel_callback_interval is enabled, then we store the remaining
+    // fuel (that isn't in runtime_limits) here. The total amount of fuel is the runtime limits
+    // and reserve added together. Then when we run out of gas, we inject the smaller of the two
+    // intervals from the reserve until the reserve is empty.
     fuel_reserve: u64,
     fuel_yield_interval: Option<NonZeroU64>,
+    fuel_callback_interval: Option<NonZeroU64>,
     /// Indexed data within this `Store`, used to store information about
     /// globals, functions, memories, etc.
     ///
@@ -505,6 +580,8 @@ impl<T> Store<T> {
                 memory_limit: crate::DEFAULT_MEMORY_LIMIT,
                 table_count: 0,
                 table_limit: crate::DEFAULT_TABLE_LIMIT,
+                #[cfg(feature = "component-model")]
+                resource_handle_limit: usize::MAX,
                 #[cfg(feature = "async")]
                 async_state: AsyncState {
                     current_suspend: UnsafeCell::new(ptr::null()),
@@ -512,6 +589,7 @@ impl<T> Store<T> {
                 },
                 fuel_reserve: 0,
                 fuel_yield_interval: None,
+                fuel_callback_interval: None,
                 store_data: ManuallyDrop::new(StoreData::new()),
                 default_caller: InstanceHandle::null(),
                 hostcall_val_storage: Vec::new(),
@@ -528,6 +606,9 @@ impl<T> Store<T> {
             limiter: None,
             call_hook: None,
             epoch_deadline_behavior: None,
+            fuel_callback: None,
+            #[cfg(feature = "component-model")]
+            resource_leak_handler: None,
             data: ManuallyDrop::new(data),
         });
 
@@ -591,6 +672,8 @@ impl<T> Store<T> {
 
     /// Consumes this [`Store`], destroying it, and returns the underlying data.
     pub fn into_data(mut self) -> T {
+        self.report_resource_leaks();
+
         // This is an unsafe operation because we want to avoid having a runtime
         // check or boolean for whether the data is actually contained within a
         // `Store`. The data itself is stored as `ManuallyDrop` since we're
@@ -679,14 +762,20 @@ impl<T> Store<T> {
     ) {
         // Apply the limits on instances, tables, and memory given by the limiter:
         let inner = &mut self.inner;
-        let (instance_limit, table_limit, memory_limit) = {
+        let (instance_limit, table_limit, memory_limit, resource_handle_limit) = {
             let l = limiter(&mut inner.data);
-            (l.instances(), l.tables(), l.memories())
+            (
+                l.instances(),
+                l.tables(),
+                l.memories(),
+                l.resource_handles(),
+            )
         };
         let innermost = &mut inner.inner;
         innermost.instance_limit = instance_limit;
         innermost.table_limit = table_limit;
         innermost.memory_limit = memory_limit;
+        innermost.set_resource_handle_limit(resource_handle_limit);
 
         // Save the limiter accessor function:
         inner.limiter = Some(ResourceLimiterInner::Sync(Box::new(limiter)));
@@ -727,14 +816,20 @@ impl<T> Store<T> {
         debug_assert!(self.inner.async_support());
         // Apply the limits on instances, tables, and memory given by the limiter:
         let inner = &mut self.inner;
-        let (instance_limit, table_limit, memory_limit) = {
+        let (instance_limit, table_limit, memory_limit, resource_handle_limit) = {
             let l = limiter(&mut inner.data);
-            (l.instances(), l.tables(), l.memories())
+            (
+                l.instances(),
+                l.tables(),
+                l.memories(),
+                l.resource_handles(),
+            )
         };
         let innermost = &mut inner.inner;
         innermost.instance_limit = instance_limit;
         innermost.table_limit = table_limit;
         innermost.memory_limit = memory_limit;
+        innermost.set_resource_handle_limit(resource_handle_limit);
 
         // Save the limiter accessor function:
         inner.limiter = Some(ResourceLimiterInner::Async(Box::new(limiter)));
@@ -782,6 +877,48 @@ impl<T> Store<T> {
         self.inner.call_hook = Some(CallHookInner::Sync(Box::new(hook)));
     }
 
+    /// Configures a function to report component resources which are leaked
+    /// when this store is destroyed.
+    ///
+    /// When this store is dropped, or consumed with [`Store::into_data`], the
+    /// `own` resource handles still held by component instances within it are
+    /// discarded without their destructors running. If there are any such
+    /// handles then `handler` is invoked with them just before the store's
+    /// data is dropped. The handler also receives the store's data, which can
+    /// be used to inspect host resources, for example with
+    /// [`ResourceTable::entries`](crate::component::ResourceTable::entries).
+    ///
+    /// This is intended as a debugging aid to track down resources which are
+    /// never released. Configuring a handler also makes the store record
+    /// which component function created each `own` handle, as reported by
+    /// [`ResourceLeak::func`](crate::component::ResourceLeak::func), which
+    /// adds some overhead to component calls. Only handles created by
+    /// functions looked up after the handler is configured are attributed,
+    /// so this should be configured before instantiating components.
+    #[cfg(feature = "component-model")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "component-model")))]
+    pub fn resource_leak_handler(
+        &mut self,
+        handler: impl FnOnce(&mut T, &[crate::component::ResourceLeak]) + Send + Sync + 'static,
+    ) {
+        self.inner.resource_leak_handler = Some(Box::new(handler));
+        self.inner.component_calls.track_origins();
+    }
+
+    /// Invokes the handler configured with [`Store::resource_leak_handler`],
+    /// if any, with the handles that are still live in this store.
+    fn report_resource_leaks(&mut self) {
+        #[cfg(feature = "component-model")]
+        {
+            if let Some(handler) = self.inner.resource_leak_handler.take() {
+                let leaks = self.inner.store_data().components.resource_leaks();
+                if !leaks.is_empty() {
+                    handler(self.inner.data_mut(), &leaks);
+                }
+            }
+        }
+    }
+
     /// Returns the [`Engine`] that this store is associated with.
     pub fn engine(&self) -> &Engine {
         self.inner.engine()
@@ -862,6 +999,36 @@ impl<T> Store<T> {
         self.inner.fuel_async_yield_interval(interval)
     }
 
+    /// Configures a callback to be invoked periodically while WebAssembly
+    /// consumes fuel.
+    ///
+    /// When a [`Store`] is configured to consume fuel with
+    /// [`Config::consume_fuel`](crate::Config::consume_fuel) this method will
+    /// invoke `callback` every `interval` units of fuel consumed, or more often
+    /// if a smaller [`Store::fuel_async_yield_interval`] is configured. The
+    /// exact amount of fuel consumed between invocations may overshoot
+    /// `interval` slightly since fuel is only checked at certain points in
+    /// compiled code, but unlike epochs the points at which the callback is
+    /// invoked are deterministic: running the same code with the same inputs
+    /// will invoke it at the same places. Use [`StoreContext::get_fuel`] in the
+    /// callback to learn exactly how much fuel remains.
+    ///
+    /// Returning an error from `callback` will terminate execution with a
+    /// trap. Calling this method again replaces the previous callback.
+    ///
+    /// # Errors
+    ///
+    /// This method will error if fuel consumption is not enabled via
+    /// [`Config::consume_fuel`](crate::Config::consume_fuel), or if
+    /// `interval` is 0.
+    pub fn fuel_callback(
+        &mut self,
+        interval: u64,
+        callback: impl FnMut(StoreContextMut<T>) -> Result<()> + Send + Sync + 'static,
+    ) -> Result<()> {
+        self.inner.fuel_callback(interval, Box::new(callback))
+    }
+
     /// Sets the epoch deadline to a certain number of ticks in the future.
     ///
     /// When the Wasm guest code is compiled with epoch-interruption
@@ -1019,6 +1186,56 @@ impl<'a, T> StoreContextMut<'a, T> {
         self.0.data()
     }
 
+    /// Invokes `f` with the budgets configured in `options` installed in
+    /// this store, restoring the previous budgets afterwards less the fuel
+    /// consumed by `f`.
+    pub(crate) fn with_call_options<R>(
+        &mut self,
+        options: &CallOptions,
+        f: impl FnOnce(StoreContextMut<'_, T>) -> Result<R>,
+    ) -> Result<(R, CallReport)> {
+        let consume_fuel = self.engine().tunables().consume_fuel;
+        if options.epoch_deadline.is_some() {
+            anyhow::ensure!(
+                self.engine().tunables().epoch_interruption,
+                "epoch interruption is not enabled in this store"
+            );
+        }
+
+        let prev_fuel = if consume_fuel {
+            Some(self.0.get_fuel()?)
+        } else {
+            None
+        };
+        if let Some(fuel) = options.fuel {
+            self.0.set_fuel(fuel)?;
+        }
+        let prev_epoch_deadline = options.epoch_deadline.map(|ticks| {
+            let prev = self.0.get_epoch_deadline();
+            self.0.set_epoch_deadline(ticks);
+            prev
+        });
+
+        let result = f(self.as_context_mut());
+
+        let mut fuel_consumed = 0;
+        if let Some(prev_fuel) = prev_fuel {
+            let remaining = self.0.get_fuel()?;
+            match options.fuel {
+                Some(fuel) => {
+                    fuel_consumed = fuel.saturating_sub(remaining);
+                    self.0.set_fuel(prev_fuel.saturating_sub(fuel_consumed))?;
+                }
+                None => fuel_consumed = prev_fuel.saturating_sub(remaining),
+            }
+        }
+        if let Some(deadline) = prev_epoch_deadline {
+            self.0.restore_epoch_deadline(deadline);
+        }
+
+        Ok((result?, CallReport { fuel_consumed }))
+    }
+
     /// Access the underlying data owned by this `Store`.
     ///
     /// Same as [`Store::data_mut`].
@@ -1199,6 +1416,22 @@ impl StoreOpaque {
         Ok(())
     }
 
+    #[cfg_attr(not(feature = "component-model"), allow(unused_variables))]
+    fn set_resource_handle_limit(&mut self, limit: usize) {
+        #[cfg(feature = "component-model")]
+        {
+            self.resource_handle_limit = limit;
+            self.component_host_table.set_max_handles(limit);
+        }
+    }
+
+    /// Returns the maximum number of live handles in each resource table of
+    /// component instances within this store.
+    #[cfg(feature = "component-model")]
+    pub(crate) fn resource_handle_limit(&self) -> usize {
+        self.resource_handle_limit
+    }
+
     #[inline]
     pub fn async_support(&self) -> bool {
         cfg!(feature = "async") && self.engine().config().async_support
@@ -1414,12 +1647,19 @@ impl StoreOpaque {
     }
 
     fn refuel(&mut self) -> bool {
+        let interval = self.fuel_injection_interval();
         let injected_fuel = unsafe { &mut *self.runtime_limits.fuel_consumed.get() };
-        refuel(
-            injected_fuel,
-            &mut self.fuel_reserve,
-            self.fuel_yield_interval,
-        )
+        refuel(injected_fuel, &mut self.fuel_reserve, interval)
+    }
+
+    /// Returns how much fuel to inject into the VM at once, so that control
+    /// returns to the store as often as is needed to yield or invoke the fuel
+    /// callback.
+    fn fuel_injection_interval(&self) -> Option<NonZeroU64> {
+        match (self.fuel_yield_interval, self.fuel_callback_interval) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        }
     }
 
     pub fn set_fuel(&mut self, fuel: u64) -> Result<()> {
@@ -1427,13 +1667,9 @@ impl StoreOpaque {
             self.engine().tunables().consume_fuel,
             "fuel is not configured in this store"
         );
+        let interval = self.fuel_injection_interval();
         let injected_fuel = unsafe { &mut *self.runtime_limits.fuel_consumed.get() };
-        set_fuel(
-            injected_fuel,
-            &mut self.fuel_reserve,
-            self.fuel_yield_interval,
-            fuel,
-        );
+        set_fuel(injected_fuel, &mut self.fuel_reserve, interval, fuel);
         Ok(())
     }
 
@@ -1455,6 +1691,17 @@ impl StoreOpaque {
         self.set_fuel(self.get_fuel()?)
     }
 
+    fn set_fuel_callback_interval(&mut self, interval: u64) -> Result<()> {
+        anyhow::ensure!(
+            self.engine().tunables().consume_fuel,
+            "fuel is not configured in this store"
+        );
+        anyhow::ensure!(interval != 0, "fuel callback interval must not be 0");
+        self.fuel_callback_interval = NonZeroU64::new(interval);
+        // Reset the fuel active + reserve states by resetting the amount.
+        self.set_fuel(self.get_fuel()?)
+    }
+
     /// Yields execution to the caller on out-of-gas or epoch interruption.
     ///
     /// This only works on async futures and stores, and assumes that we're
@@ -1645,6 +1892,15 @@ at https://bytecodealliance.org/security.
         )
     }
 
+    /// Returns whether the component functions which create `own` handles
+    /// are recorded, which is only the case when a resource leak handler is
+    /// configured.
+    #[inline]
+    #[cfg(feature = "component-model")]
+    pub(crate) fn component_tracks_resource_origins(&self) -> bool {
+        self.component_calls.tracks_origins()
+    }
+
     #[cfg(feature = "component-model")]
     pub(crate) fn push_component_instance(&mut self, instance: crate::component::Instance) {
         // We don't actually need the instance itself right now, but it seems
@@ -2117,6 +2373,13 @@ unsafe impl<T> wasmtime_runtime::Store for StoreInner<T> {
         if !self.refuel() {
             return Err(Trap::OutOfFuel.into());
         }
+        // Temporarily take the callback to avoid mutably borrowing multiple
+        // times, like with the epoch deadline callback below.
+        if let Some(mut callback) = self.fuel_callback.take() {
+            let result = callback((&mut *self).as_context_mut());
+            self.fuel_callback = Some(callback);
+            result?;
+        }
         #[cfg(feature = "async")]
         if self.fuel_yield_interval.is_some() {
             self.async_yield_impl()?;
@@ -2184,6 +2447,16 @@ impl<T> StoreInner<T> {
         self.epoch_deadline_behavior = None;
     }
 
+    fn fuel_callback(
+        &mut self,
+        interval: u64,
+        callback: Box<dyn FnMut(StoreContextMut<T>) -> Result<()> + Send + Sync>,
+    ) -> Result<()> {
+        self.inner.set_fuel_callback_interval(interval)?;
+        self.fuel_callback = Some(callback);
+        Ok(())
+    }
+
     fn epoch_deadline_callback(
         &mut self,
         callback: Box<dyn FnMut(StoreContextMut<T>) -> Result<UpdateDeadline> + Send + Sync>,
@@ -2204,6 +2477,12 @@ impl<T> StoreInner<T> {
         let _ = delta; // suppress warning in non-async build
     }
 
+    fn restore_epoch_deadline(&mut self, deadline: u64) {
+        // Safety: see `set_epoch_deadline` above.
+        let epoch_deadline = unsafe { (*self.vmruntime_limits()).epoch_deadline.get_mut() };
+        *epoch_deadline = deadline;
+    }
+
     fn get_epoch_deadline(&self) -> u64 {
         // Safety: this is safe because, as above, it is only invoked
         // from within `new_epoch` which is called from guest Wasm
@@ -2231,6 +2510,8 @@ impl<T: fmt::Debug> fmt::Debug for Store<T> {
 
 impl<T> Drop for Store<T> {
     fn drop(&mut self) {
+        self.report_resource_leaks();
+
         // for documentation on this `unsafe`, see `into_data`.
         unsafe {
             ManuallyDrop::drop(&mut self.inner.data);
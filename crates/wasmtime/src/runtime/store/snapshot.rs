@@ -0,0 +1,84 @@
+use crate::{AsContextMut, Global, Memory, Mutability, Store, Val};
+use anyhow::{bail, Result};
+
+/// A copy of the state of the WebAssembly instances in a [`Store`], taken
+/// with [`Store::snapshot`].
+///
+/// A snapshot holds the contents of each linear memory and the value of each
+/// mutable global that existed in the store when it was taken. Tables, shared
+/// memories, and any state held by the host in `T` are not part of a
+/// snapshot.
+pub struct StoreSnapshot {
+    memories: Vec<(Memory, Vec<u8>)>,
+    globals: Vec<(Global, Val)>,
+}
+
+impl StoreSnapshot {
+    /// Returns the total size, in bytes, of the linear memories captured in
+    /// this snapshot.
+    pub fn memory_size(&self) -> usize {
+        self.memories.iter().map(|(_, data)| data.len()).sum()
+    }
+}
+
+impl<T> Store<T> {
+    /// Captures the contents of all linear memories and the values of all
+    /// mutable globals in this store, so that they can later be put back with
+    /// [`Store::restore`].
+    ///
+    /// This can be used to return instances to a known state, such as the
+    /// state just after instantiation, rather than instantiating again.
+    pub fn snapshot(&mut self) -> StoreSnapshot {
+        let memories = self.inner.all_memories().collect::<Vec<_>>();
+        let memories = memories
+            .into_iter()
+            .filter(|memory| !memory.ty(&*self).is_shared())
+            .map(|memory| {
+                let data = memory.data(&*self).to_vec();
+                (memory, data)
+            })
+            .collect();
+
+        let globals = self.inner.all_globals().collect::<Vec<_>>();
+        let globals = globals
+            .into_iter()
+            .filter(|global| global.ty(&*self).mutability() == Mutability::Var)
+            .map(|global| {
+                let val = global.get(&mut *self);
+                (global, val)
+            })
+            .collect();
+
+        StoreSnapshot { memories, globals }
+    }
+
+    /// Restores the linear memories and mutable globals captured in
+    /// `snapshot` to their state when it was taken.
+    ///
+    /// Memories and globals created after the snapshot was taken are left
+    /// untouched.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error, without modifying the store, if any of the captured
+    /// memories has grown since the snapshot was taken, as memories cannot
+    /// shrink.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `snapshot` was taken from a different store.
+    pub fn restore(&mut self, snapshot: &StoreSnapshot) -> Result<()> {
+        for (memory, data) in snapshot.memories.iter() {
+            if memory.data_size(&*self) != data.len() {
+                bail!("cannot restore a memory which has grown since the snapshot was taken");
+            }
+        }
+        for (memory, data) in snapshot.memories.iter() {
+            memory.data_mut(&mut *self).copy_from_slice(data);
+        }
+        for (global, val) in snapshot.globals.iter() {
+            global.set(self.as_context_mut(), val.clone())?;
+        }
+        Ok(())
+    }
+}
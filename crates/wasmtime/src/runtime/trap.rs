@@ -139,8 +139,18 @@ pub(crate) fn from_runtime_box(
     let _ = &coredumpstack;
     #[cfg(feature = "coredump")]
     if let Some(coredump) = coredumpstack {
-        let bt = WasmBacktrace::from_captured(store, coredump.bt, pc);
-        let cd = WasmCoreDump::new(store, bt);
+        let wasmtime_runtime::CoreDumpStack {
+            bt,
+            locals: mut frame_locals,
+            operand_stack: mut frame_operand_stack,
+        } = coredump;
+        let mut locals = Vec::new();
+        let mut operand_stack = Vec::new();
+        let bt = WasmBacktrace::from_captured_with(store, bt, pc, |i| {
+            locals.push(std::mem::take(&mut frame_locals[i]));
+            operand_stack.push(std::mem::take(&mut frame_operand_stack[i]));
+        });
+        let cd = WasmCoreDump::new(store, bt, locals, operand_stack);
         error = error.context(cd);
     }
 
@@ -287,13 +297,29 @@ impl WasmBacktrace {
         store: &StoreOpaque,
         runtime_trace: wasmtime_runtime::Backtrace,
         trap_pc: Option<usize>,
+    ) -> Self {
+        Self::from_captured_with(store, runtime_trace, trap_pc, |_| {})
+    }
+
+    /// Same as `from_captured`, but additionally calls `kept` with the index
+    /// within `runtime_trace` of each frame that makes it into the resulting
+    /// backtrace.
+    ///
+    /// A native frame executing code inlined from another function produces
+    /// two frames, the inlined function's first, so `kept` is called twice
+    /// with its index.
+    fn from_captured_with(
+        store: &StoreOpaque,
+        runtime_trace: wasmtime_runtime::Backtrace,
+        trap_pc: Option<usize>,
+        mut kept: impl FnMut(usize),
     ) -> Self {
         let mut wasm_trace = Vec::<FrameInfo>::with_capacity(runtime_trace.frames().len());
         let mut hint_wasm_backtrace_details_env = false;
         let wasm_backtrace_details_env_used =
             store.engine().config().wasm_backtrace_details_env_used;
 
-        for frame in runtime_trace.frames() {
+        for (index, frame) in runtime_trace.frames().enumerate() {
             debug_assert!(frame.pc() != 0);
 
             // Note that we need to be careful about the pc we pass in
@@ -336,8 +362,13 @@ impl WasmBacktrace {
             // Some(..)` instead of the `unwrap` you might otherwise expect and
             // we ignore frames from modules that were not registered in this
             // store's module registry.
-            if let Some((info, module)) = store.modules().lookup_frame_info(pc_to_lookup) {
+            if let Some((mut info, module)) = store.modules().lookup_frame_info(pc_to_lookup) {
+                if let Some(inlined) = info.split_inlined() {
+                    wasm_trace.push(inlined);
+                    kept(index);
+                }
                 wasm_trace.push(info);
+                kept(index);
 
                 // If this frame has unparsed debug information and the
                 // store's configuration indicates that we were
@@ -435,6 +466,7 @@ pub struct FrameInfo {
     func_name: Option<String>,
     func_start: FilePos,
     instr: Option<FilePos>,
+    inlined_call: Option<FilePos>,
     symbols: Vec<FrameSymbol>,
 }
 
@@ -445,9 +477,14 @@ impl FrameInfo {
     /// if no information can be found.
     pub(crate) fn new(module: Module, text_offset: usize) -> Option<FrameInfo> {
         let compiled_module = module.compiled_module();
-        let (index, _func_offset) = compiled_module.func_by_text_offset(text_offset)?;
+        let (index, func_offset) = compiled_module.func_by_text_offset(text_offset)?;
         let info = compiled_module.wasm_func_info(index);
         let func_start = info.start_srcloc;
+        let inlined_call = info
+            .inlined_calls
+            .iter()
+            .find(|call| call.start <= func_offset && func_offset < call.end)
+            .map(|call| call.call_site);
         let instr = wasmtime_environ::lookup_file_pos(
             compiled_module.code_memory().address_map_data(),
             text_offset,
@@ -510,11 +547,38 @@ impl FrameInfo {
             func_index,
             func_name,
             instr,
+            inlined_call,
             func_start,
             symbols,
         })
     }
 
+    /// Splits off a frame for the function which this frame's instruction was
+    /// inlined from, if any.
+    ///
+    /// Inlined instructions keep the wasm offsets of the function they were
+    /// inlined from, so they lie outside of this frame's own function body.
+    /// The returned frame takes over the instruction and its symbols, and
+    /// this frame is left pointing at the call which was inlined.
+    fn split_inlined(&mut self) -> Option<FrameInfo> {
+        let offset = self.instr?.file_offset()?;
+        let compiled_module = self.module.compiled_module();
+        let index = compiled_module.func_by_wasm_offset(offset)?;
+        let func_index = compiled_module.module().func_index(index);
+        if func_index.index() as u32 == self.func_index {
+            return None;
+        }
+        Some(FrameInfo {
+            module: self.module.clone(),
+            func_index: func_index.index() as u32,
+            func_name: compiled_module.func_name(func_index).map(|s| s.to_string()),
+            func_start: compiled_module.wasm_func_info(index).start_srcloc,
+            instr: std::mem::replace(&mut self.instr, self.inlined_call.take()),
+            inlined_call: None,
+            symbols: std::mem::take(&mut self.symbols),
+        })
+    }
+
     /// Returns the WebAssembly function index for this frame.
     ///
     /// This function index is the index in the function index space of the
@@ -577,7 +641,8 @@ impl FrameInfo {
     /// configuration option.
     pub fn func_offset(&self) -> Option<usize> {
         let instr_offset = self.instr?.file_offset()?;
-        Some((instr_offset - self.func_start.file_offset()?) as usize)
+        let offset = instr_offset.checked_sub(self.func_start.file_offset()?)?;
+        Some(offset as usize)
     }
 
     /// Returns the debug symbols found, if any, for this function frame.
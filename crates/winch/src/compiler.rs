@@ -132,6 +132,9 @@ impl wasmtime_environ::Compiler for Compiler {
             WasmFunctionInfo {
                 start_srcloc,
                 stack_maps: Box::new([]),
+                frame_states: Box::new([]),
+                frame_saved_registers: None,
+                inlined_calls: Box::new([]),
             },
             Box::new(compiled_function),
         ))
@@ -44,6 +44,7 @@ struct Wasmtime {
     opts: Opts,
     import_interfaces: Vec<(String, InterfaceName)>,
     import_functions: Vec<ImportFunction>,
+    import_mocks: Vec<MockFunction>,
     exports: Exports,
     types: Types,
     sizes: SizeAlign,
@@ -58,6 +59,26 @@ struct ImportFunction {
     sig: String,
 }
 
+/// A function implemented by the mock generated for an interface or world.
+struct MockFunction {
+    /// The trait this function belongs to, such as `Host` or `HostFoo`.
+    trait_: String,
+    /// The name of this function's trait method.
+    name: String,
+    /// The snake-case name used for fields and methods of the mock.
+    field: String,
+    async_: bool,
+    /// Whether calls to this function are recorded in the mock's `calls`.
+    record: bool,
+    /// Rust names and types of this function's parameters.
+    params: Vec<(String, String)>,
+    /// The Rust type returned from this function.
+    ret: String,
+    /// An expression producing this function's return value when neither a
+    /// queued value nor a handler is available.
+    default: String,
+}
+
 #[derive(Default)]
 struct Exports {
     fields: BTreeMap<String, (String, String)>,
@@ -108,6 +129,10 @@ pub struct Opts {
     /// Remapping of interface names to rust module names.
     /// TODO: is there a better type to use for the value of this map?
     pub with: HashMap<String, String>,
+
+    /// Whether or not to generate a `HostMock` type for each imported
+    /// interface which implements its `Host` traits for use in tests.
+    pub mocks: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -289,6 +314,11 @@ impl Wasmtime {
                     gen.generate_function_trait_sig(func);
                 }
                 let sig = mem::take(&mut gen.src).into();
+                if gen.gen.opts.mocks {
+                    let world_camel = to_rust_upper_camel_case(&resolve.worlds[world].name);
+                    let mock = gen.mock_function(func, &format!("{world_camel}Imports"));
+                    gen.gen.import_mocks.push(mock);
+                }
                 gen.generate_add_function_to_linker(TypeOwner::World(world), func, "linker");
                 let add_to_linker = gen.src.into();
                 self.import_functions
@@ -304,6 +334,9 @@ impl Wasmtime {
                 let key_name = resolve.name_world_key(name);
 
                 gen.generate_add_to_linker(*id, &key_name);
+                if gen.gen.opts.mocks {
+                    gen.generate_mock(*id);
+                }
 
                 let module = &gen.src[..];
 
@@ -734,6 +767,23 @@ impl Wasmtime {
             self.src.push_str("\n");
         }
         uwriteln!(self.src, "}}");
+
+        if self.opts.mocks {
+            let mut funcs = mem::take(&mut self.import_mocks);
+            let mut traits = vec![format!("{world_camel}Imports")];
+            for name in get_world_resources(resolve, world) {
+                funcs.push(mock_resource_drop(name));
+                traits.push(format!("Host{}", name.to_upper_camel_case()));
+            }
+            emit_mock(
+                &mut self.src,
+                &format!("{world_camel}ImportsMock"),
+                &traits,
+                &funcs,
+                "",
+                self.opts.async_.maybe_async(),
+            );
+        }
     }
 
     fn toplevel_add_to_linker(&mut self, resolve: &Resolve, world: WorldId) {
@@ -1620,6 +1670,209 @@ impl<'a> InterfaceGenerator<'a> {
         uwriteln!(self.src, "}}");
     }
 
+    /// Generates `HostMock`, an implementation of the `Host` traits of the
+    /// imported interface `id` which records calls and returns configurable
+    /// values, along with `HostMockCall` which describes a recorded call.
+    fn generate_mock(&mut self, id: InterfaceId) {
+        let iface = &self.resolve.interfaces[id];
+
+        let mut funcs = Vec::new();
+        for func in iface.functions.values() {
+            funcs.push(self.mock_function(func, "Host"));
+        }
+        for name in get_resources(self.resolve, id) {
+            funcs.push(mock_resource_drop(name));
+        }
+
+        // Trappable errors defined or used by this interface which its `Host`
+        // trait converts are passed to a configurable handler, like calls.
+        // Errors from other interfaces are converted by those interfaces'
+        // `Host` traits instead, which this mock doesn't implement.
+        let mut other_traits = IndexSet::new();
+        let mut errors_converted = IndexSet::new();
+        let my_error_types = iface
+            .types
+            .values()
+            .filter(|id| self.gen.trappable_errors.contains_key(*id))
+            .copied();
+        let used_error_types = iface
+            .functions
+            .values()
+            .filter_map(|func| self.special_case_trappable_error(&func.results))
+            .map(|(_, id, _)| id);
+        for err in my_error_types.chain(used_error_types).collect::<Vec<_>>() {
+            let custom_name = &self.gen.trappable_errors[&err];
+            let err = &self.resolve.types[resolve_type_definition_id(self.resolve, err)];
+            let err_name = err.name.as_ref().unwrap();
+            let owner = match err.owner {
+                TypeOwner::Interface(i) => i,
+                _ => unimplemented!(),
+            };
+            if let Some(path) = self.path_to_interface(owner) {
+                other_traits.insert(format!("{path}::Host"));
+                continue;
+            }
+            if !errors_converted.insert(err_name) {
+                continue;
+            }
+            let field = format!("convert_{}", err_name.to_snake_case());
+            funcs.push(MockFunction {
+                trait_: "Host".to_string(),
+                name: field.clone(),
+                default: format!(
+                    "Err(anyhow::anyhow!(\"no conversion configured for mock of `{field}`\"))"
+                ),
+                field,
+                async_: false,
+                record: false,
+                params: vec![(
+                    "err".to_string(),
+                    format!("{}{custom_name}", self.path_to_root()),
+                )],
+                ret: format!("wasmtime::Result<{}>", err_name.to_upper_camel_case()),
+            });
+        }
+
+        let mut traits = vec!["Host".to_string()];
+        for name in get_resources(self.resolve, id) {
+            traits.push(format!("Host{}", name.to_upper_camel_case()));
+        }
+        let mut docs = String::new();
+        if iface
+            .functions
+            .values()
+            .any(|func| self.special_case_trappable_error(&func.results).is_some())
+        {
+            uwrite!(
+                docs,
+                "
+                    ///
+                    /// Calls to functions returning a trappable error type
+                    /// with neither a queued value nor a handler panic
+                    /// instead, as there's no error to return.
+                "
+            );
+        }
+        if !errors_converted.is_empty() {
+            uwrite!(
+                docs,
+                "
+                    ///
+                    /// Trappable errors are converted with the handlers
+                    /// configured with the `expect_convert_*` methods, or the
+                    /// values queued with `return_convert_*`, and trap
+                    /// otherwise. Conversions aren't recorded in `calls`.
+                "
+            );
+        }
+        if !other_traits.is_empty() {
+            let other_traits = other_traits
+                .iter()
+                .map(|t| format!("`{t}`"))
+                .collect::<Vec<_>>()
+                .join(", ");
+            uwrite!(
+                docs,
+                "
+                    ///
+                    /// Note that [`add_to_linker`] additionally requires
+                    /// {other_traits} to convert errors defined elsewhere,
+                    /// which this mock does not implement.
+                "
+            );
+        }
+        let maybe_async = self.gen.opts.async_.maybe_async();
+        emit_mock(
+            &mut self.src,
+            "HostMock",
+            &traits,
+            &funcs,
+            &docs,
+            maybe_async,
+        );
+    }
+
+    /// Describes how the mock of `func`, which belongs to the trait `trait_`
+    /// if it's a freestanding function, is implemented.
+    fn mock_function(&mut self, func: &Function, trait_: &str) -> MockFunction {
+        let (trait_, default) = match func.kind {
+            FunctionKind::Freestanding => (trait_.to_string(), None),
+            FunctionKind::Method(ty) | FunctionKind::Static(ty) => {
+                (self.mock_resource_trait(ty), None)
+            }
+            FunctionKind::Constructor(ty) => {
+                let name = self.resolve.types[ty].name.as_ref().unwrap();
+                let entry = mock_resource_entry(&name.to_upper_camel_case());
+                (
+                    self.mock_resource_trait(ty),
+                    Some(format!(
+                        "self.table.push(<{entry}>::default()).map(|r| wasmtime::component::Resource::new_own(r.rep())).map_err(wasmtime::Error::from)"
+                    )),
+                )
+            }
+        };
+        let trappable = self.special_case_trappable_error(&func.results);
+        let default = default.unwrap_or_else(|| {
+            if func.results.iter_types().len() == 0 {
+                "Ok(())".to_string()
+            } else if trappable.is_some() {
+                // Trappable error types can't necessarily be created from
+                // an arbitrary error, so the best that can be done is a panic.
+                format!(
+                    "panic!(\"no return value configured for mock of `{}`\")",
+                    func.name
+                )
+            } else {
+                format!(
+                    "Err(anyhow::anyhow!(\"no return value configured for mock of `{}`\"))",
+                    func.name
+                )
+            }
+        });
+        let params = func
+            .params
+            .iter()
+            .map(|(name, ty)| {
+                let prev = mem::take(&mut self.src);
+                self.print_ty(ty, TypeMode::Owned);
+                let ty = mem::replace(&mut self.src, prev).into();
+                (to_rust_ident(name), ty)
+            })
+            .collect::<Vec<(String, String)>>();
+        let prev = mem::take(&mut self.src);
+        match trappable {
+            Some((r, _, error_typename)) => {
+                self.push_str("Result<");
+                match r.ok {
+                    Some(ok) => self.print_ty(&ok, TypeMode::Owned),
+                    None => self.push_str("()"),
+                }
+                uwrite!(self.src, ", {error_typename}>");
+            }
+            None => {
+                self.push_str("wasmtime::Result<");
+                self.print_result_ty(&func.results, TypeMode::Owned);
+                self.push_str(">");
+            }
+        }
+        let ret = mem::replace(&mut self.src, prev).into();
+        MockFunction {
+            trait_,
+            name: rust_function_name(func),
+            field: mock_field_name(self.resolve, func),
+            async_: self.gen.opts.async_.is_import_async(&func.name),
+            record: true,
+            params,
+            ret,
+            default,
+        }
+    }
+
+    fn mock_resource_trait(&self, ty: TypeId) -> String {
+        let name = self.resolve.types[ty].name.as_ref().unwrap();
+        format!("Host{}", name.to_upper_camel_case())
+    }
+
     fn generate_add_function_to_linker(&mut self, owner: TypeOwner, func: &Function, linker: &str) {
         uwrite!(
             self.src,
@@ -2065,6 +2318,204 @@ fn func_field_name(resolve: &Resolve, func: &Function) -> String {
     name.to_snake_case()
 }
 
+/// The name used for the fields and methods of a `HostMock` related to
+/// `func`, such as `method_foo_bar` for `[method]foo.bar`.
+fn mock_field_name(resolve: &Resolve, func: &Function) -> String {
+    match func.kind {
+        // The item name of a constructor adds nothing beyond the resource's
+        // name, so it's omitted here unlike in `func_field_name`.
+        FunctionKind::Constructor(id) => {
+            let resource = resolve.types[id].name.as_ref().unwrap();
+            format!("constructor_{}", resource.to_snake_case())
+        }
+        _ => func_field_name(resolve, func),
+    }
+}
+
+/// The type of the values stored in a `HostMock`'s table for the resource
+/// `camel`, which is a marker since resource types may not be constructible.
+fn mock_resource_entry(camel: &str) -> String {
+    format!("core::marker::PhantomData<fn() -> {camel}>")
+}
+
+/// Emits `mock`, an implementation of `traits` with the functions `funcs`
+/// which records calls and returns configurable values, along with
+/// `{mock}Call` which describes a recorded call. The `docs` are appended to
+/// the documentation of `mock`.
+fn emit_mock(
+    src: &mut Source,
+    mock: &str,
+    traits: &[String],
+    funcs: &[MockFunction],
+    docs: &str,
+    maybe_async: bool,
+) {
+    uwriteln!(
+        src,
+        "
+            /// A call recorded by [`{mock}`].
+            #[derive(Debug)]
+            pub enum {mock}Call {{
+        "
+    );
+    for func in funcs.iter().filter(|f| f.record) {
+        uwrite!(src, "{} {{", func.field.to_upper_camel_case());
+        for (name, ty) in func.params.iter() {
+            uwrite!(src, "{name}: {ty},");
+        }
+        uwriteln!(src, "}},");
+    }
+    uwriteln!(src, "}}");
+
+    uwriteln!(
+        src,
+        "
+            /// A mock implementation of [`{}`] for use in tests.
+            ///
+            /// Every call made to this mock is recorded, in order, in
+            /// [`{mock}::calls`]. The value returned from a call is the
+            /// next value queued with the corresponding `return_*` method,
+            /// if any, or otherwise the result of the handler configured
+            /// with the corresponding `expect_*` method. Calls with
+            /// neither return an error unless the function has no results,
+            /// in which case they succeed. Resources are created in, and
+            /// dropped from, [`{mock}::table`] by default.
+            {docs}
+            #[derive(Default)]
+            pub struct {mock} {{
+                /// Calls made to this mock, in the order they were made.
+                pub calls: Vec<{mock}Call>,
+                /// The table backing resources created by this mock.
+                pub table: wasmtime::component::ResourceTable,
+        ",
+        traits[0],
+    );
+    for func in funcs.iter() {
+        let field = &func.field;
+        let ret = &func.ret;
+        uwrite!(
+            src,
+            "{field}_returns: std::collections::VecDeque<{ret}>,
+             {field}_handler: Option<Box<dyn FnMut("
+        );
+        for (_, ty) in func.params.iter() {
+            uwrite!(src, "&{ty},");
+        }
+        uwriteln!(src, ") -> {ret} + Send>>,");
+    }
+    uwriteln!(src, "}}");
+
+    uwriteln!(src, "impl {mock} {{");
+    for func in funcs.iter() {
+        let field = &func.field;
+        let ret = &func.ret;
+        let name = format!("{}::{}", func.trait_, func.name);
+        uwrite!(
+            src,
+            "
+                /// Queues `ret` to be returned from a future call to
+                /// `{name}`.
+                pub fn return_{field}(&mut self, ret: {ret}) -> &mut Self {{
+                    self.{field}_returns.push_back(ret);
+                    self
+                }}
+
+                /// Configures `handler` to be invoked for calls to
+                /// `{name}` which don't have a queued return value.
+                pub fn expect_{field}(
+                    &mut self,
+                    handler: impl FnMut(",
+        );
+        for (_, ty) in func.params.iter() {
+            uwrite!(src, "&{ty},");
+        }
+        uwriteln!(
+            src,
+            ") -> {ret} + Send + 'static,
+                ) -> &mut Self {{
+                    self.{field}_handler = Some(Box::new(handler));
+                    self
+                }}
+            "
+        );
+    }
+    uwriteln!(src, "}}");
+
+    for trait_ in traits {
+        if maybe_async {
+            uwriteln!(src, "#[wasmtime::component::__internal::async_trait]")
+        }
+        uwriteln!(src, "impl {trait_} for {mock} {{");
+        for func in funcs.iter().filter(|f| f.trait_ == *trait_) {
+            let field = &func.field;
+            let async_ = if func.async_ { "async " } else { "" };
+            uwrite!(src, "{async_}fn {}(&mut self, ", func.name);
+            for (name, ty) in func.params.iter() {
+                uwrite!(src, "{name}: {ty},");
+            }
+            uwrite!(
+                src,
+                ") -> {} {{
+                    let __ret = if let Some(__ret) = self.{field}_returns.pop_front() {{
+                        __ret
+                    }} else if let Some(__handler) = &mut self.{field}_handler {{
+                        __handler(",
+                func.ret,
+            );
+            for (name, _) in func.params.iter() {
+                uwrite!(src, "&{name},");
+            }
+            uwrite!(
+                src,
+                ")
+                    }} else {{
+                        {}
+                    }};",
+                func.default,
+            );
+            if func.record {
+                uwrite!(
+                    src,
+                    "self.calls.push({mock}Call::{} {{",
+                    field.to_upper_camel_case()
+                );
+                for (name, _) in func.params.iter() {
+                    uwrite!(src, "{name},");
+                }
+                uwriteln!(src, "}});");
+            }
+            uwriteln!(
+                src,
+                "__ret
+                }}"
+            );
+        }
+        uwriteln!(src, "}}");
+    }
+}
+
+/// Describes the mock of the destructor of the resource `name`, which deletes
+/// it from the mock's table by default.
+fn mock_resource_drop(name: &str) -> MockFunction {
+    let camel = name.to_upper_camel_case();
+    let entry = mock_resource_entry(&camel);
+    MockFunction {
+        trait_: format!("Host{camel}"),
+        name: "drop".to_string(),
+        field: format!("drop_{}", name.to_snake_case()),
+        async_: false,
+        record: true,
+        params: vec![(
+            "rep".to_string(),
+            format!("wasmtime::component::Resource<{camel}>"),
+        )],
+        ret: "wasmtime::Result<()>".to_string(),
+        default: format!(
+            "self.table.delete(wasmtime::component::Resource::<{entry}>::new_own(rep.rep())).map(|_| ()).map_err(wasmtime::Error::from)"
+        ),
+    }
+}
+
 fn get_resources<'a>(resolve: &'a Resolve, id: InterfaceId) -> impl Iterator<Item = &'a str> + 'a {
     resolve.interfaces[id]
         .types
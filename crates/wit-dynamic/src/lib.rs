@@ -0,0 +1,421 @@
+//! Dynamic host bindings for WIT worlds in Wasmtime.
+//!
+//! The [`bindgen!`] macro in the `wasmtime` crate generates statically typed
+//! Rust bindings for a WIT world at compile time. This crate is the dynamic
+//! counterpart: given a [`Resolve`] and a world parsed at runtime it defines
+//! the world's imports in a [`Linker`] with a single [`DynamicHost`]
+//! implementation, and invokes the world's exports, with all values
+//! represented as [`Val`].
+//!
+//! Values passed to the host can be inspected with the typed accessors on
+//! [`Val`], such as [`Record::get`] and [`Variant::get`].
+//!
+//! Resources imported by a world are implemented by the same [`DynamicHost`]:
+//! handles to them are passed as [`Val::Resource`] values of the
+//! [`DynamicResource`] type, and [`DynamicHost::resource_drop`] is invoked
+//! when the guest drops one it owns. The host state behind each handle is
+//! typically kept in a [`ResourceTable`], and [`DynamicResource::into_val`]
+//! and [`DynamicResource::from_val`] convert between the table's handles and
+//! values. Resources exported by a world are passed as [`Val::Resource`]
+//! values of the guest's resource types, and their constructors and methods
+//! can be invoked with [`DynamicWorld::call_export`].
+//!
+//! [`bindgen!`]: https://docs.rs/wasmtime/latest/wasmtime/component/macro.bindgen.html
+//! [`Record::get`]: wasmtime::component::Record::get
+//! [`Variant::get`]: wasmtime::component::Variant::get
+//! [`ResourceTable`]: wasmtime::component::ResourceTable
+
+#![deny(missing_docs)]
+
+use anyhow::{anyhow, bail, Result};
+use std::path::Path;
+use std::sync::Arc;
+use wasmtime::component::types::ComponentItem;
+use wasmtime::component::{
+    Component, Instance, Linker, LinkerInstance, Resource, ResourceAny, ResourceType, Val,
+};
+use wasmtime::{AsContextMut, StoreContextMut};
+use wit_parser::{
+    Function, InterfaceId, Resolve, TypeDefKind, TypeId, UnresolvedPackage, WorldId, WorldItem,
+};
+
+/// A host implementation of the imports of a WIT world.
+///
+/// A single implementation receives the calls of all functions imported by a
+/// world. The function being called is described by the [`ImportFunc`]
+/// argument, and `params` and `results` are as in
+/// [`LinkerInstance::func_new`].
+///
+/// This trait is implemented for closures with a matching signature.
+pub trait DynamicHost<T>: Send + Sync {
+    /// Invoked when the guest calls the imported function `func`.
+    ///
+    /// The `results` slice must be filled in with values matching the result
+    /// types of `func`.
+    fn call(
+        &self,
+        store: StoreContextMut<'_, T>,
+        func: &ImportFunc,
+        params: &[Val],
+        results: &mut [Val],
+    ) -> Result<()>;
+
+    /// Invoked when the guest drops an owned handle to `resource`, one of the
+    /// resources imported by the world, whose representation is `rep`.
+    ///
+    /// The default implementation does nothing.
+    fn resource_drop(
+        &self,
+        store: StoreContextMut<'_, T>,
+        resource: &ImportResource,
+        rep: u32,
+    ) -> Result<()> {
+        let _ = (store, resource, rep);
+        Ok(())
+    }
+}
+
+impl<T, F> DynamicHost<T> for F
+where
+    F: Fn(StoreContextMut<'_, T>, &ImportFunc, &[Val], &mut [Val]) -> Result<()> + Send + Sync,
+{
+    fn call(
+        &self,
+        store: StoreContextMut<'_, T>,
+        func: &ImportFunc,
+        params: &[Val],
+        results: &mut [Val],
+    ) -> Result<()> {
+        self(store, func, params, results)
+    }
+}
+
+/// Description of a function imported by a world, passed to
+/// [`DynamicHost::call`].
+#[derive(Clone, Debug)]
+pub struct ImportFunc {
+    interface: Option<String>,
+    func: Function,
+}
+
+impl ImportFunc {
+    /// Returns the name of the interface this function is imported from, or
+    /// `None` if it's imported directly by the world.
+    ///
+    /// For interfaces defined in a package this is the fully-qualified name,
+    /// such as `wasi:cli/environment`.
+    pub fn interface(&self) -> Option<&str> {
+        self.interface.as_deref()
+    }
+
+    /// Returns the name of this function.
+    pub fn name(&self) -> &str {
+        &self.func.name
+    }
+
+    /// Returns the WIT definition of this function.
+    pub fn function(&self) -> &Function {
+        &self.func
+    }
+}
+
+/// Description of a resource imported by a world, passed to
+/// [`DynamicHost::resource_drop`].
+#[derive(Clone, Debug)]
+pub struct ImportResource {
+    interface: Option<String>,
+    name: String,
+}
+
+impl ImportResource {
+    /// Returns the name of the interface this resource is imported from, or
+    /// `None` if it's imported directly by the world.
+    ///
+    /// For interfaces defined in a package this is the fully-qualified name,
+    /// such as `wasi:io/streams`.
+    pub fn interface(&self) -> Option<&str> {
+        self.interface.as_deref()
+    }
+
+    /// Returns the name of this resource.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// The host type of all resources imported with
+/// [`DynamicWorld::add_to_linker`].
+///
+/// Handles to these resources are passed to and from the guest as
+/// [`Val::Resource`] values whose type is [`DynamicResource::ty`]. As all
+/// imported resources share this type, the host is responsible for telling
+/// them apart, which a [`ResourceTable`] does by checking the type of each
+/// entry when it's accessed.
+///
+/// [`ResourceTable`]: wasmtime::component::ResourceTable
+pub struct DynamicResource;
+
+impl DynamicResource {
+    /// Returns the type of handles to resources imported with
+    /// [`DynamicWorld::add_to_linker`].
+    pub fn ty() -> ResourceType {
+        ResourceType::host::<DynamicResource>()
+    }
+
+    /// Converts `resource`, for example a handle returned by
+    /// [`ResourceTable::push`], into a value which can be passed to the guest
+    /// as a handle to an imported resource.
+    ///
+    /// The returned handle is owned if `resource` is, and borrowed otherwise.
+    ///
+    /// [`ResourceTable::push`]: wasmtime::component::ResourceTable::push
+    pub fn into_val<R: 'static>(resource: Resource<R>, store: impl AsContextMut) -> Result<Val> {
+        let resource = if resource.owned() {
+            Resource::<DynamicResource>::new_own(resource.rep())
+        } else {
+            Resource::<DynamicResource>::new_borrow(resource.rep())
+        };
+        let resource = ResourceAny::try_from_host_resource(resource, store)?;
+        Ok(Val::Resource(resource))
+    }
+
+    /// Converts a handle to an imported resource passed by the guest into a
+    /// [`Resource`], for example to look it up with [`ResourceTable::get`].
+    ///
+    /// The returned resource is owned if the guest passed ownership of the
+    /// handle, and borrowed otherwise.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `val` isn't a handle to an imported resource, or
+    /// if the handle has already been converted.
+    ///
+    /// [`ResourceTable::get`]: wasmtime::component::ResourceTable::get
+    pub fn from_val<R: 'static>(val: &Val, store: impl AsContextMut) -> Result<Resource<R>> {
+        let resource = match val {
+            Val::Resource(resource) => *resource,
+            _ => bail!("expected a resource handle"),
+        };
+        let resource = resource.try_into_resource::<DynamicResource>(store)?;
+        Ok(if resource.owned() {
+            Resource::new_own(resource.rep())
+        } else {
+            Resource::new_borrow(resource.rep())
+        })
+    }
+}
+
+/// A WIT world which can be used to link and invoke components dynamically.
+pub struct DynamicWorld {
+    resolve: Resolve,
+    world: WorldId,
+}
+
+impl DynamicWorld {
+    /// Creates a new `DynamicWorld` for `world` within `resolve`.
+    pub fn new(resolve: Resolve, world: WorldId) -> DynamicWorld {
+        DynamicWorld { resolve, world }
+    }
+
+    /// Parses the WIT package in `contents` and selects `world` from it.
+    ///
+    /// The `path` is only used for error messages. If `world` is `None` then
+    /// the package must contain exactly one world.
+    pub fn parse(path: impl AsRef<Path>, contents: &str, world: Option<&str>) -> Result<Self> {
+        let mut resolve = Resolve::default();
+        let pkg = resolve.push(UnresolvedPackage::parse(path.as_ref(), contents)?)?;
+        let world = resolve.select_world(pkg, world)?;
+        Ok(DynamicWorld::new(resolve, world))
+    }
+
+    /// Returns the [`Resolve`] that this world is defined in.
+    pub fn resolve(&self) -> &Resolve {
+        &self.resolve
+    }
+
+    /// Returns the identifier of this world within [`Self::resolve`].
+    pub fn world(&self) -> WorldId {
+        self.world
+    }
+
+    /// Defines the imports of this world in `linker`, implementing all
+    /// functions and resources with `host`.
+    ///
+    /// Only the imports which `component` actually uses are defined, so
+    /// `component` may target a subset of this world. Resources are defined
+    /// where the world defines them, and not in interfaces which `use` them
+    /// from another interface.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the component's import has a different type than
+    /// in this world, or if a name is already defined in `linker`.
+    pub fn add_to_linker<T: 'static>(
+        &self,
+        linker: &mut Linker<T>,
+        component: &Component,
+        host: Arc<dyn DynamicHost<T>>,
+    ) -> Result<()> {
+        let engine = linker.engine().clone();
+        let ty = component.component_type();
+        for (key, item) in self.resolve.worlds[self.world].imports.iter() {
+            let name = self.resolve.name_world_key(key);
+            let import = match ty.get_import(&engine, &name) {
+                Some(import) => import,
+                None => continue,
+            };
+            match item {
+                WorldItem::Function(func) => {
+                    self.func_new(&mut linker.root(), component, None, func, &host)?;
+                }
+                WorldItem::Interface(id) => {
+                    let instance = match import {
+                        ComponentItem::ComponentInstance(instance) => instance,
+                        _ => bail!("import `{name}` has the wrong type (expected an instance)"),
+                    };
+                    let mut linker = linker.instance(&name)?;
+                    for (ty_name, ty) in self.resolve.interfaces[*id].types.iter() {
+                        if !self.is_resource(*ty) || instance.get_export(&engine, ty_name).is_none()
+                        {
+                            continue;
+                        }
+                        self.resource(&mut linker, Some(&name), ty_name, &host)?;
+                    }
+                    for func in self.resolve.interfaces[*id].functions.values() {
+                        if instance.get_export(&engine, &func.name).is_none() {
+                            continue;
+                        }
+                        self.func_new(&mut linker, component, Some(&name), func, &host)?;
+                    }
+                }
+                WorldItem::Type(id) => {
+                    if self.is_resource(*id) {
+                        self.resource(&mut linker.root(), None, &name, &host)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Invokes the function `name` exported by this world from `instance`.
+    ///
+    /// If `interface` is `Some` then the function is looked up in the named
+    /// exported interface, otherwise it's a function exported directly by the
+    /// world. The results of the function are returned and its
+    /// post-return function is run before this returns.
+    ///
+    /// The constructors, methods and static functions of exported resources
+    /// are invoked by their names in the component model, such as
+    /// `[method]counter.get`. Owned handles returned by them must eventually
+    /// be destroyed with [`ResourceAny::resource_drop`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if this world doesn't export the function, if
+    /// `instance` doesn't export it, if `params` have the wrong types, or if
+    /// the function traps.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `store` has async support enabled.
+    pub fn call_export(
+        &self,
+        mut store: impl AsContextMut,
+        instance: &Instance,
+        interface: Option<&str>,
+        name: &str,
+        params: &[Val],
+    ) -> Result<Vec<Val>> {
+        let func = {
+            let mut exports = instance.exports(&mut store);
+            let mut exports = match interface {
+                Some(interface) => {
+                    self.find_export_interface(interface)?;
+                    exports
+                        .instance(interface)
+                        .ok_or_else(|| anyhow!("instance does not export `{interface}`"))?
+                }
+                None => exports.root(),
+            };
+            self.find_export_func(interface, name)?;
+            exports
+                .func(name)
+                .ok_or_else(|| anyhow!("instance does not export function `{name}`"))?
+        };
+        let mut results = vec![Val::Bool(false); func.results(&store).len()];
+        func.call(&mut store, params, &mut results)?;
+        func.post_return(&mut store)?;
+        Ok(results)
+    }
+
+    fn func_new<T: 'static>(
+        &self,
+        linker: &mut LinkerInstance<'_, T>,
+        component: &Component,
+        interface: Option<&str>,
+        func: &Function,
+        host: &Arc<dyn DynamicHost<T>>,
+    ) -> Result<()> {
+        let import = ImportFunc {
+            interface: interface.map(|s| s.to_string()),
+            func: func.clone(),
+        };
+        let host = host.clone();
+        linker.func_new(component, &func.name, move |store, params, results| {
+            host.call(store, &import, params, results)
+        })
+    }
+
+    fn resource<T: 'static>(
+        &self,
+        linker: &mut LinkerInstance<'_, T>,
+        interface: Option<&str>,
+        name: &str,
+        host: &Arc<dyn DynamicHost<T>>,
+    ) -> Result<()> {
+        let import = ImportResource {
+            interface: interface.map(|s| s.to_string()),
+            name: name.to_string(),
+        };
+        let host = host.clone();
+        linker.resource(name, DynamicResource::ty(), move |store, rep| {
+            host.resource_drop(store, &import, rep)
+        })?;
+        Ok(())
+    }
+
+    fn find_export_interface(&self, name: &str) -> Result<InterfaceId> {
+        for (key, item) in self.resolve.worlds[self.world].exports.iter() {
+            if let WorldItem::Interface(id) = item {
+                if self.resolve.name_world_key(key) == name {
+                    return Ok(*id);
+                }
+            }
+        }
+        bail!("world does not export interface `{name}`")
+    }
+
+    fn find_export_func(&self, interface: Option<&str>, name: &str) -> Result<()> {
+        let found = match interface {
+            Some(interface) => {
+                let id = self.find_export_interface(interface)?;
+                self.resolve.interfaces[id].functions.contains_key(name)
+            }
+            None => self.resolve.worlds[self.world]
+                .exports
+                .values()
+                .any(|item| matches!(item, WorldItem::Function(f) if f.name == name)),
+        };
+        if !found {
+            bail!("world does not export function `{name}`");
+        }
+        Ok(())
+    }
+
+    /// Returns whether `id` is the definition of a resource, as opposed to a
+    /// `use` of one defined elsewhere.
+    fn is_resource(&self, id: TypeId) -> bool {
+        matches!(self.resolve.types[id].kind, TypeDefKind::Resource)
+    }
+}
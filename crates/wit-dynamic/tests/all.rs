@@ -0,0 +1,359 @@
+use anyhow::{bail, Result};
+use std::sync::{Arc, Mutex};
+use wasmtime::component::types::ComponentItem;
+use wasmtime::component::{Component, Linker, Record, Resource, ResourceTable, Val, Variant};
+use wasmtime::{Config, Engine, Store, StoreContextMut};
+use wasmtime_wit_dynamic::{
+    DynamicHost, DynamicResource, DynamicWorld, ImportFunc, ImportResource,
+};
+
+const WIT: &str = r#"
+    package test:test;
+
+    interface host {
+        record point {
+            x: u32,
+            y: u32,
+        }
+
+        variant shape {
+            circle(u32),
+            square(point),
+            none,
+        }
+
+        add: func(p: point) -> u32;
+        area: func(s: shape) -> u32;
+        unused: func();
+    }
+
+    world example {
+        import host;
+        import double: func(v: u32) -> u32;
+        import not-imported: func();
+
+        export run: func() -> u32;
+    }
+"#;
+
+const COMPONENT: &str = r#"
+    (component
+      (type $host (instance
+        (type $point' (record (field "x" u32) (field "y" u32)))
+        (export "point" (type $point (eq $point')))
+        (type $shape' (variant (case "circle" u32) (case "square" $point) (case "none")))
+        (export "shape" (type $shape (eq $shape')))
+        (export "add" (func (param "p" $point) (result u32)))
+        (export "area" (func (param "s" $shape) (result u32)))
+      ))
+      (import "test:test/host" (instance $host (type $host)))
+      (import "double" (func $double (param "v" u32) (result u32)))
+    
+      (core func $add (canon lower (func $host "add")))
+      (core func $area (canon lower (func $host "area")))
+      (core func $double (canon lower (func $double)))
+    
+      (core module $m
+        (import "" "add" (func $add (param i32 i32) (result i32)))
+        (import "" "area" (func $area (param i32 i32 i32) (result i32)))
+        (import "" "double" (func $double (param i32) (result i32)))
+        (func (export "run") (result i32)
+          (i32.add
+            (call $double (call $add (i32.const 3) (i32.const 4)))
+            (call $area (i32.const 1) (i32.const 5) (i32.const 6))))
+      )
+      (core instance $i (instantiate $m
+        (with "" (instance
+          (export "add" (func $add))
+          (export "area" (func $area))
+          (export "double" (func $double))
+        ))
+      ))
+      (func (export "run") (result u32) (canon lift (core func $i "run")))
+    )
+"#;
+
+fn engine() -> Engine {
+    let mut config = Config::new();
+    config.wasm_component_model(true);
+    Engine::new(&config).unwrap()
+}
+
+#[test]
+fn call_imports_and_exports() -> Result<()> {
+    let engine = engine();
+    let component = Component::new(&engine, COMPONENT)?;
+    let world = DynamicWorld::parse("test.wit", WIT, None)?;
+
+    let calls = Arc::new(Mutex::new(Vec::new()));
+    let host = {
+        let calls = calls.clone();
+        move |_store: wasmtime::StoreContextMut<'_, ()>,
+              func: &ImportFunc,
+              params: &[Val],
+              results: &mut [Val]| {
+            calls.lock().unwrap().push((
+                func.interface().map(|s| s.to_string()),
+                func.name().to_string(),
+            ));
+            results[0] = match (func.interface(), func.name()) {
+                (Some("test:test/host"), "add") => {
+                    let p = <&Record>::try_from(&params[0])?;
+                    Val::U32(p.get::<u32>("x")? + p.get::<u32>("y")?)
+                }
+                (Some("test:test/host"), "area") => {
+                    let s = <&Variant>::try_from(&params[0])?;
+                    if let Some(r) = s.get::<u32>("circle")? {
+                        Val::U32(3 * r * r)
+                    } else if let Some(p) = s.get::<&Record>("square")? {
+                        Val::U32(p.get::<u32>("x")? * p.get::<u32>("y")?)
+                    } else {
+                        Val::U32(0)
+                    }
+                }
+                (None, "double") => Val::U32(u32::try_from(&params[0])? * 2),
+                (interface, name) => bail!("unexpected call to {interface:?} {name}"),
+            };
+            Ok(())
+        }
+    };
+
+    let mut linker = Linker::new(&engine);
+    world.add_to_linker(&mut linker, &component, Arc::new(host))?;
+    let mut store = Store::new(&engine, ());
+    let instance = linker.instantiate(&mut store, &component)?;
+
+    let results = world.call_export(&mut store, &instance, None, "run", &[])?;
+    assert_eq!(results, [Val::U32(2 * (3 + 4) + 5 * 6)]);
+    assert_eq!(
+        *calls.lock().unwrap(),
+        [
+            (Some("test:test/host".to_string()), "add".to_string()),
+            (None, "double".to_string()),
+            (Some("test:test/host".to_string()), "area".to_string()),
+        ]
+    );
+
+    let err = world
+        .call_export(&mut store, &instance, None, "missing", &[])
+        .unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("world does not export function `missing`"),
+        "{err:?}"
+    );
+    Ok(())
+}
+
+#[test]
+fn typed_accessor_errors() -> Result<()> {
+    let engine = engine();
+    let component = Component::new(&engine, COMPONENT)?;
+    let world = DynamicWorld::parse("test.wit", WIT, None)?;
+
+    let host = |_store: wasmtime::StoreContextMut<'_, ()>,
+                func: &ImportFunc,
+                params: &[Val],
+                results: &mut [Val]| {
+        if func.name() == "add" {
+            let p = <&Record>::try_from(&params[0])?;
+            p.get::<u64>("x")?;
+        }
+        if func.name() == "double" {
+            let err = <&str>::try_from(&params[0]).unwrap_err();
+            assert_eq!(err.to_string(), "expected `string`, found `u32`");
+        }
+        results[0] = Val::U32(0);
+        Ok(())
+    };
+
+    let mut linker = Linker::new(&engine);
+    world.add_to_linker(&mut linker, &component, Arc::new(host))?;
+    let mut store = Store::new(&engine, ());
+    let instance = linker.instantiate(&mut store, &component)?;
+    let err = world
+        .call_export(&mut store, &instance, None, "run", &[])
+        .unwrap_err();
+    assert!(
+        format!("{err:?}").contains("expected `u64`, found `u32`"),
+        "{err:?}"
+    );
+    Ok(())
+}
+
+const RESOURCE_WIT: &str = r#"
+    package test:res;
+
+    interface counters {
+        resource counter {
+            constructor(start: u32);
+            bump: func() -> u32;
+        }
+    }
+
+    interface user {
+        use counters.{counter};
+
+        total: func(c: borrow<counter>) -> u32;
+    }
+
+    world res {
+        import counters;
+        import user;
+
+        export run: func() -> u32;
+    }
+"#;
+
+const RESOURCE_COMPONENT: &str = r#"
+    (component
+      (import "test:res/counters" (instance $counters
+        (export "counter" (type $counter (sub resource)))
+        (export "[constructor]counter" (func (param "start" u32) (result (own $counter))))
+        (export "[method]counter.bump" (func (param "self" (borrow $counter)) (result u32)))
+      ))
+      (alias export $counters "counter" (type $counter))
+      (import "test:res/user" (instance $user
+        (alias outer 1 $counter (type $counter'))
+        (export "counter" (type $c (eq $counter')))
+        (export "total" (func (param "c" (borrow $c)) (result u32)))
+      ))
+
+      (core func $new (canon lower (func $counters "[constructor]counter")))
+      (core func $bump (canon lower (func $counters "[method]counter.bump")))
+      (core func $total (canon lower (func $user "total")))
+      (core func $drop (canon resource.drop $counter))
+
+      (core module $m
+        (import "" "new" (func $new (param i32) (result i32)))
+        (import "" "bump" (func $bump (param i32) (result i32)))
+        (import "" "total" (func $total (param i32) (result i32)))
+        (import "" "drop" (func $drop (param i32)))
+        (func (export "run") (result i32)
+          (local $c i32)
+          (local $r i32)
+          (local.set $c (call $new (i32.const 10)))
+          (drop (call $bump (local.get $c)))
+          (local.set $r (i32.add (call $bump (local.get $c)) (call $total (local.get $c))))
+          (call $drop (local.get $c))
+          local.get $r)
+      )
+      (core instance $i (instantiate $m
+        (with "" (instance
+          (export "new" (func $new))
+          (export "bump" (func $bump))
+          (export "total" (func $total))
+          (export "drop" (func $drop))
+        ))
+      ))
+      (func (export "run") (result u32) (canon lift (core func $i "run")))
+    )
+"#;
+
+struct Counter(u32);
+
+#[derive(Default)]
+struct ResourceData {
+    table: ResourceTable,
+    dropped: Vec<(Option<String>, String, u32)>,
+}
+
+struct ResourceHost;
+
+impl DynamicHost<ResourceData> for ResourceHost {
+    fn call(
+        &self,
+        mut store: StoreContextMut<'_, ResourceData>,
+        func: &ImportFunc,
+        params: &[Val],
+        results: &mut [Val],
+    ) -> Result<()> {
+        results[0] = match func.name() {
+            "[constructor]counter" => {
+                let start = u32::try_from(&params[0])?;
+                let counter = store.data_mut().table.push(Counter(start))?;
+                DynamicResource::into_val(counter, &mut store)?
+            }
+            "[method]counter.bump" => {
+                let counter = DynamicResource::from_val::<Counter>(&params[0], &mut store)?;
+                let counter = store.data_mut().table.get_mut(&counter)?;
+                counter.0 += 1;
+                Val::U32(counter.0)
+            }
+            "total" => {
+                let counter = DynamicResource::from_val::<Counter>(&params[0], &mut store)?;
+                Val::U32(store.data().table.get(&counter)?.0 * 100)
+            }
+            name => bail!("unexpected call to {name}"),
+        };
+        Ok(())
+    }
+
+    fn resource_drop(
+        &self,
+        mut store: StoreContextMut<'_, ResourceData>,
+        resource: &ImportResource,
+        rep: u32,
+    ) -> Result<()> {
+        let counter = store
+            .data_mut()
+            .table
+            .delete(Resource::<Counter>::new_own(rep))?;
+        store.data_mut().dropped.push((
+            resource.interface().map(|s| s.to_string()),
+            resource.name().to_string(),
+            counter.0,
+        ));
+        Ok(())
+    }
+}
+
+#[test]
+fn imported_resources() -> Result<()> {
+    let engine = engine();
+    let component = Component::new(&engine, RESOURCE_COMPONENT)?;
+    let world = DynamicWorld::parse("test.wit", RESOURCE_WIT, None)?;
+
+    let mut linker = Linker::new(&engine);
+    world.add_to_linker(&mut linker, &component, Arc::new(ResourceHost))?;
+    let mut store = Store::new(&engine, ResourceData::default());
+    let instance = linker.instantiate(&mut store, &component)?;
+
+    let results = world.call_export(&mut store, &instance, None, "run", &[])?;
+    assert_eq!(results, [Val::U32(12 + 12 * 100)]);
+    assert_eq!(
+        store.data().dropped,
+        [(
+            Some("test:res/counters".to_string()),
+            "counter".to_string(),
+            12
+        )]
+    );
+    assert_eq!(store.data().table.entries().count(), 0);
+    Ok(())
+}
+
+#[test]
+fn uninstantiated_resource_types() -> Result<()> {
+    let engine = engine();
+    let resource = |component: &Component| {
+        let ty = component.component_type();
+        let counters = match ty.get_import(&engine, "test:res/counters") {
+            Some(ComponentItem::ComponentInstance(instance)) => instance,
+            _ => unreachable!(),
+        };
+        match counters.get_export(&engine, "counter") {
+            Some(ComponentItem::Resource(ty)) => ty,
+            _ => unreachable!(),
+        }
+    };
+
+    // The same component's imported resource always has the same type, but
+    // it's distinct from that of another component and from host types.
+    let a = Component::new(&engine, RESOURCE_COMPONENT)?;
+    let b = Component::new(&engine, RESOURCE_COMPONENT)?;
+    assert_eq!(resource(&a), resource(&a));
+    assert_ne!(resource(&a), resource(&b));
+    assert_ne!(resource(&a), DynamicResource::ty());
+    Ok(())
+}
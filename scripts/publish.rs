@@ -69,6 +69,7 @@ const CRATES_TO_PUBLISH: &[&str] = &[
     "wasmtime-wasi-nn",
     "wasmtime-wasi-threads",
     "wasmtime-wast",
+    "wasmtime-wit-dynamic",
     "wasmtime-c-api-macros",
     "wasmtime-c-api-impl",
     "wasmtime-cli-flags",
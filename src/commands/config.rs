@@ -1,7 +1,11 @@
 //! The module that implements the `wasmtime config` command.
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 use clap::{Parser, Subcommand};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::SystemTime;
+use wasmtime_cache::CacheConfig;
 
 const CONFIG_NEW_AFTER_HELP: &str =
     "If no file path is specified, the system configuration file path will be used.";
@@ -18,6 +22,10 @@ enum ConfigSubcommand {
     /// Creates a new Wasmtime configuration file
     #[command(after_help = CONFIG_NEW_AFTER_HELP)]
     New(ConfigNewCommand),
+
+    /// Inspects and manages the compiled module cache
+    #[command(subcommand)]
+    Cache(ConfigCacheCommand),
 }
 
 impl ConfigCommand {
@@ -25,6 +33,7 @@ impl ConfigCommand {
     pub fn execute(self) -> Result<()> {
         match self.subcommand {
             ConfigSubcommand::New(c) => c.execute(),
+            ConfigSubcommand::Cache(c) => c.execute(),
         }
     }
 }
@@ -50,3 +59,123 @@ impl ConfigNewCommand {
         Ok(())
     }
 }
+
+/// Inspects and manages the compiled module cache
+#[derive(Subcommand, PartialEq)]
+pub enum ConfigCacheCommand {
+    /// Prints statistics about the modules in the cache
+    Stats(ConfigCacheStatsCommand),
+    /// Removes all modules which aren't pinned from the cache
+    Purge(ConfigCachePurgeCommand),
+}
+
+impl ConfigCacheCommand {
+    /// Executes the command.
+    pub fn execute(self) -> Result<()> {
+        match self {
+            ConfigCacheCommand::Stats(c) => c.execute(),
+            ConfigCacheCommand::Purge(c) => c.execute(),
+        }
+    }
+}
+
+/// Prints statistics about the modules in the cache
+#[derive(Parser, PartialEq)]
+pub struct ConfigCacheStatsCommand {
+    /// The cache configuration file to use, instead of the default one
+    #[arg(long, value_name = "FILE_PATH")]
+    config: Option<String>,
+
+    /// Also print every module in the cache
+    #[arg(long)]
+    entries: bool,
+}
+
+impl ConfigCacheStatsCommand {
+    /// Executes the command.
+    pub fn execute(self) -> Result<()> {
+        let cache = load_cache_config(self.config.as_deref())?;
+        let mut entries = cache.entries()?;
+
+        println!("cache directory: {}", cache.directory().display());
+        println!(
+            "modules: {} ({} pinned)",
+            entries.len(),
+            entries.iter().filter(|e| e.pinned()).count()
+        );
+        println!(
+            "total size: {} bytes",
+            entries.iter().map(|e| e.size()).sum::<u64>()
+        );
+        println!(
+            "total usages: {}",
+            entries.iter().map(|e| e.usages()).sum::<u64>()
+        );
+        let counters = cache.counters()?;
+        println!(
+            "hits: {}, misses: {}, evictions: {}",
+            counters.hits(),
+            counters.misses(),
+            counters.evictions()
+        );
+
+        let mut compilers = BTreeMap::new();
+        for entry in &entries {
+            let (count, size) = compilers.entry(entry.compiler()).or_insert((0, 0));
+            *count += 1;
+            *size += entry.size();
+        }
+        for (compiler, (count, size)) in compilers {
+            println!("  {compiler}: {count} modules, {size} bytes");
+        }
+
+        if self.entries {
+            // Most recently used first.
+            entries.sort_by(|a, b| b.last_used().cmp(&a.last_used()));
+            let now = SystemTime::now();
+            for entry in &entries {
+                let last_used = entry
+                    .last_used()
+                    .and_then(|t| now.duration_since(t).ok())
+                    .map_or("unknown".to_string(), |d| format!("{}s ago", d.as_secs()));
+                println!(
+                    "{}/{}: {} bytes, {} usages, last used {}{}",
+                    entry.compiler(),
+                    entry.key(),
+                    entry.size(),
+                    entry.usages(),
+                    last_used,
+                    if entry.pinned() { ", pinned" } else { "" },
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Removes all modules which aren't pinned from the cache
+#[derive(Parser, PartialEq)]
+pub struct ConfigCachePurgeCommand {
+    /// The cache configuration file to use, instead of the default one
+    #[arg(long, value_name = "FILE_PATH")]
+    config: Option<String>,
+}
+
+impl ConfigCachePurgeCommand {
+    /// Executes the command.
+    pub fn execute(self) -> Result<()> {
+        let cache = load_cache_config(self.config.as_deref())?;
+        let removed = cache.purge()?;
+        println!("Removed {removed} modules from the cache.");
+        Ok(())
+    }
+}
+
+fn load_cache_config(path: Option<&str>) -> Result<CacheConfig> {
+    let cache = CacheConfig::from_file(path.map(Path::new))?;
+    if !cache.enabled() {
+        bail!("the cache is disabled by its configuration");
+    }
+    Ok(cache)
+}
@@ -26,6 +26,9 @@ use wasmtime_wasi_threads::WasiThreadsCtx;
 #[cfg(feature = "wasi-http")]
 use wasmtime_wasi_http::WasiHttpCtx;
 
+#[cfg(feature = "component-model")]
+mod component_values;
+
 fn parse_env_var(s: &str) -> Result<(String, Option<String>)> {
     let mut parts = s.splitn(2, '=');
     Ok((
@@ -79,6 +82,12 @@ pub struct RunCommand {
     pub vars: Vec<(String, Option<String>)>,
 
     /// The name of the function to run
+    ///
+    /// For components this is a call expression such as `'add(1, 2)'`, with
+    /// arguments written in a textual syntax for component values: strings
+    /// are quoted, lists are `[a, b]`, records are `{field: value}`, options
+    /// are `some(x)` or `none` and results are `ok(x)` or `err(e)`. Functions
+    /// exported from an interface are named `interface#function`.
     #[arg(long, value_name = "FUNCTION")]
     pub invoke: Option<String>,
 
@@ -91,11 +100,29 @@ pub struct RunCommand {
     )]
     pub preloads: Vec<(String, PathBuf)>,
 
+    /// Record the results of the program's WASI calls to a trace file.
+    ///
+    /// The trace contains the results of clock, random, stdin, filesystem,
+    /// and `wasi:sockets` calls, and can be passed to `--replay` to reproduce
+    /// the same run.
+    #[arg(long, value_name = "TRACE", conflicts_with = "replay")]
+    pub record: Option<PathBuf>,
+
+    /// Replay the results of the program's WASI calls from a trace file
+    /// written by `--record`.
+    ///
+    /// Fails if the program doesn't make the same calls as when it was
+    /// recorded. Replayed socket calls never touch the network.
+    #[arg(long, value_name = "TRACE")]
+    pub replay: Option<PathBuf>,
+
     /// The WebAssembly module to run and arguments to pass to it.
     ///
     /// Arguments passed to the wasm module will be configured as WASI CLI
     /// arguments unless the `--invoke` CLI argument is passed in which case
     /// arguments will be interpreted as arguments to the function specified.
+    /// Components take the arguments of `--invoke` as part of its call
+    /// expression, so for them these are always WASI CLI arguments.
     #[arg(value_name = "WASM", trailing_var_arg = true, required = true)]
     pub module_and_args: Vec<OsString>,
 }
@@ -124,6 +151,10 @@ impl RunCommand {
                 // Further configured down below as well.
                 config.epoch_interruption(true);
             }
+            Some(Profile::GuestFuel { .. }) => {
+                // Further configured down below as well.
+                config.consume_fuel(true);
+            }
             None => {}
         }
 
@@ -171,6 +202,10 @@ impl RunCommand {
         // fuel amount to this store.
         if let Some(fuel) = self.run.common.wasm.fuel {
             store.set_fuel(fuel)?;
+        } else if let Some(Profile::GuestFuel { .. }) = &self.run.profile {
+            // Fuel is only consumed for the sake of profiling here, so make
+            // sure it never runs out.
+            store.set_fuel(u64::MAX)?;
         }
 
         // Load the preload wasm modules.
@@ -209,14 +244,25 @@ impl RunCommand {
         }
 
         // Load the main wasm module.
-        match self
+        let result = self
             .load_main_module(&mut store, &mut linker, &main, modules)
             .with_context(|| {
                 format!(
                     "failed to run main module `{}`",
                     self.module_and_args[0].to_string_lossy()
                 )
-            }) {
+            });
+
+        // Finish the trace before exiting below, as the program's exit is
+        // part of what was recorded.
+        if let Some(recorder) = &store.data().recorder {
+            recorder.flush().context("failed to write the WASI trace")?;
+        }
+        if let Some(replayer) = &store.data().replayer {
+            replayer.finish()?;
+        }
+
+        match result {
             Ok(()) => (),
             Err(e) => {
                 // Exit the process if Wasmtime understands the error;
@@ -325,6 +371,16 @@ impl RunCommand {
             });
         }
 
+        if let Some(Profile::GuestFuel { path, interval }) = &self.run.profile {
+            #[cfg(feature = "profiling")]
+            return self.setup_fuel_profiler(store, modules, path, *interval);
+            #[cfg(not(feature = "profiling"))]
+            {
+                let _ = (modules, path, interval);
+                bail!("support for profiling disabled at compile time");
+            }
+        }
+
         Ok(Box::new(|_store| {}))
     }
 
@@ -380,21 +436,40 @@ impl RunCommand {
             engine.increment_epoch();
         });
 
-        let path = path.to_string();
-        return Box::new(move |store| {
-            let profiler = Arc::try_unwrap(store.data_mut().guest_profiler.take().unwrap())
-                .expect("profiling doesn't support threads yet");
-            if let Err(e) = std::fs::File::create(&path)
-                .map_err(anyhow::Error::new)
-                .and_then(|output| profiler.finish(std::io::BufWriter::new(output)))
-            {
-                eprintln!("failed writing profile at {path}: {e:#}");
-            } else {
-                eprintln!();
-                eprintln!("Profile written to: {path}");
-                eprintln!("View this profile at https://profiler.firefox.com/.");
-            }
-        });
+        finish_guest_profiler(path)
+    }
+
+    #[cfg(feature = "profiling")]
+    fn setup_fuel_profiler(
+        &self,
+        store: &mut Store<Host>,
+        modules: Vec<(String, Module)>,
+        path: &str,
+        interval: u64,
+    ) -> Result<Box<dyn FnOnce(&mut Store<Host>)>> {
+        use wasmtime::GuestProfiler;
+
+        let module_name = self.module_and_args[0].to_str().unwrap_or("<main module>");
+        store.data_mut().guest_profiler = Some(Arc::new(GuestProfiler::new_with_fuel(
+            module_name,
+            interval,
+            modules,
+        )));
+
+        let mut last_fuel = store.get_fuel()?;
+        store.fuel_callback(interval, move |mut store| {
+            let fuel = store.get_fuel()?;
+            let consumed = last_fuel.saturating_sub(fuel);
+            last_fuel = fuel;
+            let mut profiler = store.data_mut().guest_profiler.take().unwrap();
+            Arc::get_mut(&mut profiler)
+                .expect("profiling doesn't support threads yet")
+                .sample_fuel(&store, consumed);
+            store.data_mut().guest_profiler = Some(profiler);
+            Ok(())
+        })?;
+
+        Ok(finish_guest_profiler(path))
     }
 
     fn load_main_module(
@@ -468,26 +543,30 @@ impl RunCommand {
             }
             #[cfg(feature = "component-model")]
             CliLinker::Component(linker) => {
-                if self.invoke.is_some() {
-                    bail!("using `--invoke` with components is not supported");
-                }
-
                 let component = module.unwrap_component();
 
-                let (command, _instance) =
-                    preview2::command::sync::Command::instantiate(&mut *store, component, linker)?;
-                let result = command
-                    .wasi_cli_run()
-                    .call_run(&mut *store)
-                    .context("failed to invoke `run` function")
-                    .map_err(|e| self.handle_core_dump(&mut *store, e));
-
-                // Translate the `Result<(),()>` produced by wasm into a feigned
-                // explicit exit here with status 1 if `Err(())` is returned.
-                result.and_then(|wasm_result| match wasm_result {
-                    Ok(()) => Ok(()),
-                    Err(()) => Err(wasmtime_wasi::preview2::I32Exit(1).into()),
-                })
+                if let Some(expr) = &self.invoke {
+                    self.invoke_component(store, component, linker, expr)
+                } else {
+                    let (command, _instance) = preview2::command::sync::Command::instantiate(
+                        &mut *store,
+                        component,
+                        linker,
+                    )?;
+                    let result = command
+                        .wasi_cli_run()
+                        .call_run(&mut *store)
+                        .context("failed to invoke `run` function")
+                        .map_err(|e| self.handle_core_dump(&mut *store, e));
+
+                    // Translate the `Result<(),()>` produced by wasm into a
+                    // feigned explicit exit here with status 1 if `Err(())` is
+                    // returned.
+                    result.and_then(|wasm_result| match wasm_result {
+                        Ok(()) => Ok(()),
+                        Err(()) => Err(wasmtime_wasi::preview2::I32Exit(1).into()),
+                    })
+                }
             }
         };
         finish_epoch_handler(store);
@@ -570,6 +649,50 @@ impl RunCommand {
         Ok(())
     }
 
+    #[cfg(feature = "component-model")]
+    fn invoke_component(
+        &self,
+        store: &mut Store<Host>,
+        component: &wasmtime::component::Component,
+        linker: &wasmtime::component::Linker<Host>,
+        expr: &str,
+    ) -> Result<()> {
+        let (name, args) = component_values::parse_call(expr)?;
+        let instance = linker.instantiate(&mut *store, component).context(format!(
+            "failed to instantiate {:?}",
+            self.module_and_args[0]
+        ))?;
+
+        // Functions exported from an interface are named `iface#func`.
+        let func = {
+            let mut exports = instance.exports(&mut *store);
+            let func = match name.rsplit_once('#') {
+                Some((iface, func)) => exports.instance(iface).and_then(|mut i| i.func(func)),
+                None => exports.root().func(name),
+            };
+            func.ok_or_else(|| anyhow!("no func export named `{}` found", name))?
+        };
+
+        let params = func.params(&*store);
+        let values = component_values::parse_values(args, &params)
+            .with_context(|| format!("failed to parse arguments to `{}`", name))?;
+
+        let mut results = vec![wasmtime::component::Val::Bool(false); func.results(&*store).len()];
+        let invoke_res = func
+            .call(&mut *store, &values, &mut results)
+            .with_context(|| format!("failed to invoke `{}`", name));
+        if let Err(err) = invoke_res {
+            return Err(self.handle_core_dump(&mut *store, err));
+        }
+
+        for result in results.iter() {
+            println!("{}", component_values::display(result));
+        }
+        func.post_return(&mut *store)?;
+
+        Ok(())
+    }
+
     #[cfg(feature = "coredump")]
     fn handle_core_dump(&self, store: &mut Store<Host>, err: Error) -> Error {
         let coredump_path = match &self.run.common.debug.coredump {
@@ -733,6 +856,10 @@ impl RunCommand {
     }
 
     fn set_preview1_ctx(&self, store: &mut Store<Host>) -> Result<()> {
+        if self.record.is_some() || self.replay.is_some() {
+            bail!("--record and --replay require the preview2 implementation of WASI");
+        }
+
         let mut builder = WasiCtxBuilder::new();
         builder.inherit_stdio().args(&self.compute_argv()?)?;
 
@@ -806,8 +933,27 @@ impl RunCommand {
             builder.allow_udp(enable);
         }
 
+        if let Some(path) = &self.record {
+            let recorder = preview2::Recorder::create(path)
+                .with_context(|| format!("failed to create trace `{}`", path.display()))?;
+            builder.record(recorder.clone());
+            store.data_mut().recorder = Some(recorder);
+        }
+        if let Some(path) = &self.replay {
+            let replayer = preview2::Replayer::open(path)
+                .with_context(|| format!("failed to read trace `{}`", path.display()))?;
+            builder.replay(replayer.clone());
+            store.data_mut().replayer = Some(replayer);
+        }
+
+        let mut table = wasmtime::component::ResourceTable::new();
+        if let Some(max) = self.run.common.wasm.max_resource_handles {
+            table.set_max_entries(max);
+        }
+
         let ctx = builder.build();
         store.data_mut().preview2_ctx = Some(Arc::new(Mutex::new(ctx)));
+        store.data_mut().preview2_table = Arc::new(Mutex::new(table));
         Ok(())
     }
 }
@@ -830,6 +976,11 @@ struct Host {
     // now when running core modules.
     preview2_adapter: Arc<preview2::preview1::WasiPreview1Adapter>,
 
+    // The `--record` and `--replay` traces, which are finished once the
+    // program exits.
+    recorder: Option<preview2::Recorder>,
+    replayer: Option<preview2::Replayer>,
+
     #[cfg(feature = "wasi-nn")]
     wasi_nn: Option<Arc<WasiNnCtx>>,
     #[cfg(feature = "wasi-threads")]
@@ -912,6 +1063,27 @@ fn ctx_set_listenfd(mut num_fd: usize, builder: &mut WasiCtxBuilder) -> Result<u
     Ok(num_fd)
 }
 
+/// Returns a closure which writes the store's guest profile to `path` once the
+/// guest has finished running.
+#[cfg(feature = "profiling")]
+fn finish_guest_profiler(path: &str) -> Box<dyn FnOnce(&mut Store<Host>)> {
+    let path = path.to_string();
+    Box::new(move |store| {
+        let profiler = Arc::try_unwrap(store.data_mut().guest_profiler.take().unwrap())
+            .expect("profiling doesn't support threads yet");
+        if let Err(e) = std::fs::File::create(&path)
+            .map_err(anyhow::Error::new)
+            .and_then(|output| profiler.finish(std::io::BufWriter::new(output)))
+        {
+            eprintln!("failed writing profile at {path}: {e:#}");
+        } else {
+            eprintln!();
+            eprintln!("Profile written to: {path}");
+            eprintln!("View this profile at https://profiler.firefox.com/.");
+        }
+    })
+}
+
 #[cfg(feature = "coredump")]
 fn write_core_dump(
     store: &mut Store<Host>,
@@ -0,0 +1,465 @@
+//! A textual syntax for component model values, used to pass arguments to and
+//! print the results of functions called with `wasmtime run --invoke`.
+//!
+//! The syntax follows the WIT type of each value:
+//!
+//! * `bool`: `true` or `false`
+//! * integers and floats: `42`, `-7`, `1.5`, `nan`, `inf`, `-inf`
+//! * `char`: `'x'`
+//! * `string`: `"hello\n"`
+//! * `list<T>`: `[1, 2, 3]`
+//! * `tuple<...>`: `(1, "a")`
+//! * records: `{x: 1, y: 2}`
+//! * variants and enums: `case` or `case(payload)`
+//! * `option<T>`: `some(value)` or `none`
+//! * `result<T, E>`: `ok`, `ok(value)`, `err`, or `err(value)`
+//! * flags: `{a, c}`
+//!
+//! Strings and chars support the escapes `\n`, `\r`, `\t`, `\\`, `\'`, `\"`,
+//! and `\u{...}`. Names which would otherwise be read as a keyword, such as a
+//! variant case named `none`, can be written with a leading `%`.
+
+use anyhow::{anyhow, bail, Context, Result};
+use std::fmt::{self, Write};
+use wasmtime::component::{Type, Val};
+
+/// Splits a call expression like `name(1, "two")` into the function name and
+/// the text of its arguments.
+///
+/// A bare `name` is a call with no arguments.
+pub fn parse_call(expr: &str) -> Result<(&str, &str)> {
+    let expr = expr.trim();
+    let Some(open) = expr.find('(') else {
+        return Ok((expr, ""));
+    };
+    let name = expr[..open].trim_end();
+    let args = expr[open + 1..]
+        .strip_suffix(')')
+        .ok_or_else(|| anyhow!("expected `)` at the end of `{expr}`"))?;
+    if name.is_empty() {
+        bail!("missing function name in `{expr}`");
+    }
+    Ok((name, args))
+}
+
+/// Parses the comma-separated `values`, whose types are given by `types`.
+pub fn parse_values(values: &str, types: &[Type]) -> Result<Vec<Val>> {
+    let mut parser = Parser {
+        src: values,
+        pos: 0,
+    };
+    let mut vals = Vec::with_capacity(types.len());
+    for (i, ty) in types.iter().enumerate() {
+        if i > 0 {
+            parser.expect(',')?;
+        }
+        let val = parser
+            .value(ty)
+            .with_context(|| format!("invalid value for argument {}", i + 1))?;
+        vals.push(val);
+    }
+    if !types.is_empty() {
+        parser.eat(',');
+    }
+    parser.skip_whitespace();
+    if parser.pos < parser.src.len() {
+        bail!(
+            "expected {} argument(s), found more at offset {}",
+            types.len(),
+            parser.pos
+        );
+    }
+    Ok(vals)
+}
+
+/// Returns an object which displays `val` in the syntax accepted by
+/// [`parse_values`].
+pub fn display(val: &Val) -> impl fmt::Display + '_ {
+    DisplayVal(val)
+}
+
+const KEYWORDS: &[&str] = &["true", "false", "some", "none", "ok", "err", "nan", "inf"];
+
+struct Parser<'a> {
+    src: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.src[self.pos..]
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_whitespace();
+        self.rest().chars().next()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<()> {
+        if self.eat(c) {
+            return Ok(());
+        }
+        match self.peek() {
+            Some(found) => bail!("expected `{c}`, found `{found}` at offset {}", self.pos),
+            None => bail!("expected `{c}`, found end of input"),
+        }
+    }
+
+    /// Reads a run of characters which can make up a name or a number.
+    fn token(&mut self) -> Result<&'a str> {
+        self.skip_whitespace();
+        let rest = self.rest();
+        let len = rest
+            .find(|c: char| !(c.is_alphanumeric() || matches!(c, '-' | '_' | '.' | '+' | '%')))
+            .unwrap_or(rest.len());
+        if len == 0 {
+            match rest.chars().next() {
+                Some(c) => bail!("unexpected `{c}` at offset {}", self.pos),
+                None => bail!("unexpected end of input"),
+            }
+        }
+        self.pos += len;
+        Ok(&rest[..len])
+    }
+
+    /// Reads a name, removing the `%` prefix which escapes keywords.
+    fn name(&mut self) -> Result<&'a str> {
+        let token = self.token()?;
+        Ok(token.strip_prefix('%').unwrap_or(token))
+    }
+
+    fn number<T: std::str::FromStr>(&mut self, ty: &str) -> Result<T>
+    where
+        T::Err: std::error::Error + Send + Sync + 'static,
+    {
+        let token = self.token()?;
+        token
+            .parse()
+            .with_context(|| format!("invalid `{ty}` value `{token}`"))
+    }
+
+    /// Parses the payload of a variant-like case, if there is one.
+    fn payload(&mut self, ty: Option<Type>) -> Result<Option<Val>> {
+        match ty {
+            Some(ty) => {
+                self.expect('(')?;
+                let val = self.value(&ty)?;
+                self.expect(')')?;
+                Ok(Some(val))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn value(&mut self, ty: &Type) -> Result<Val> {
+        Ok(match ty {
+            Type::Bool => match self.token()? {
+                "true" => Val::Bool(true),
+                "false" => Val::Bool(false),
+                other => bail!("expected `true` or `false`, found `{other}`"),
+            },
+            Type::S8 => Val::S8(self.number("s8")?),
+            Type::U8 => Val::U8(self.number("u8")?),
+            Type::S16 => Val::S16(self.number("s16")?),
+            Type::U16 => Val::U16(self.number("u16")?),
+            Type::S32 => Val::S32(self.number("s32")?),
+            Type::U32 => Val::U32(self.number("u32")?),
+            Type::S64 => Val::S64(self.number("s64")?),
+            Type::U64 => Val::U64(self.number("u64")?),
+            Type::Float32 => Val::Float32(self.number("f32")?),
+            Type::Float64 => Val::Float64(self.number("f64")?),
+            Type::Char => {
+                let s = self.quoted('\'')?;
+                let mut chars = s.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => Val::Char(c),
+                    _ => bail!("expected a single character, found '{s}'"),
+                }
+            }
+            Type::String => Val::String(self.quoted('"')?.into()),
+            Type::List(list) => {
+                let elem = list.ty();
+                let mut values = Vec::new();
+                self.expect('[')?;
+                while !self.eat(']') {
+                    values.push(self.value(&elem)?);
+                    if !self.eat(',') {
+                        self.expect(']')?;
+                        break;
+                    }
+                }
+                list.new_val(values.into())?
+            }
+            Type::Tuple(tuple) => {
+                let mut values = Vec::new();
+                self.expect('(')?;
+                for (i, ty) in tuple.types().enumerate() {
+                    if i > 0 {
+                        self.expect(',')?;
+                    }
+                    values.push(self.value(&ty)?);
+                }
+                self.eat(',');
+                self.expect(')')?;
+                tuple.new_val(values.into())?
+            }
+            Type::Record(record) => {
+                let mut values = Vec::new();
+                self.expect('{')?;
+                for (i, field) in record.fields().enumerate() {
+                    if i > 0 {
+                        self.expect(',')?;
+                    }
+                    let name = self.name()?;
+                    if name != field.name {
+                        bail!("expected field `{}`, found `{name}`", field.name);
+                    }
+                    self.expect(':')?;
+                    let val = self
+                        .value(&field.ty)
+                        .with_context(|| format!("invalid value for field `{name}`"))?;
+                    values.push((field.name, val));
+                }
+                self.eat(',');
+                self.expect('}')?;
+                record.new_val(values)?
+            }
+            Type::Variant(variant) => {
+                let name = self.name()?;
+                let case = variant
+                    .cases()
+                    .find(|case| case.name == name)
+                    .ok_or_else(|| anyhow!("unknown variant case `{name}`"))?;
+                let payload = self.payload(case.ty)?;
+                variant.new_val(name, payload)?
+            }
+            Type::Enum(enum_) => {
+                let name = self.name()?;
+                enum_.new_val(name)?
+            }
+            Type::Option(option) => match self.token()? {
+                "none" => option.new_val(None)?,
+                "some" => {
+                    let payload = self.payload(Some(option.ty()))?;
+                    option.new_val(payload)?
+                }
+                other => bail!("expected `some` or `none`, found `{other}`"),
+            },
+            Type::Result(result) => match self.token()? {
+                "ok" => {
+                    let payload = self.payload(result.ok())?;
+                    result.new_val(Ok(payload))?
+                }
+                "err" => {
+                    let payload = self.payload(result.err())?;
+                    result.new_val(Err(payload))?
+                }
+                other => bail!("expected `ok` or `err`, found `{other}`"),
+            },
+            Type::Flags(flags) => {
+                let mut names = Vec::new();
+                self.expect('{')?;
+                while !self.eat('}') {
+                    names.push(self.name()?);
+                    if !self.eat(',') {
+                        self.expect('}')?;
+                        break;
+                    }
+                }
+                flags.new_val(&names)?
+            }
+            Type::Own(_) | Type::Borrow(_) => bail!("resources cannot be written as values"),
+        })
+    }
+
+    /// Parses a string or char literal delimited by `quote`, returning its
+    /// contents with escapes resolved.
+    fn quoted(&mut self, quote: char) -> Result<String> {
+        self.expect(quote)?;
+        let mut ret = String::new();
+        let mut chars = self.rest().char_indices();
+        loop {
+            let (i, c) = chars
+                .next()
+                .ok_or_else(|| anyhow!("missing closing `{quote}`"))?;
+            match c {
+                c if c == quote => {
+                    self.pos += i + c.len_utf8();
+                    return Ok(ret);
+                }
+                '\\' => {
+                    let (_, escape) = chars.next().ok_or_else(|| anyhow!("unfinished escape"))?;
+                    ret.push(match escape {
+                        'n' => '\n',
+                        'r' => '\r',
+                        't' => '\t',
+                        '\\' | '\'' | '"' => escape,
+                        'u' => {
+                            let mut hex = String::new();
+                            match chars.next() {
+                                Some((_, '{')) => {}
+                                _ => bail!("expected `{{` after `\\u`"),
+                            }
+                            loop {
+                                match chars.next() {
+                                    Some((_, '}')) => break,
+                                    Some((_, c)) => hex.push(c),
+                                    None => bail!("unfinished `\\u{{...}}` escape"),
+                                }
+                            }
+                            u32::from_str_radix(&hex, 16)
+                                .ok()
+                                .and_then(char::from_u32)
+                                .ok_or_else(|| anyhow!("invalid `\\u{{{hex}}}` escape"))?
+                        }
+                        other => bail!("unknown escape `\\{other}`"),
+                    });
+                }
+                c => ret.push(c),
+            }
+        }
+    }
+}
+
+struct DisplayVal<'a>(&'a Val);
+
+impl fmt::Display for DisplayVal<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Val::Bool(b) => write!(f, "{b}"),
+            Val::S8(n) => write!(f, "{n}"),
+            Val::U8(n) => write!(f, "{n}"),
+            Val::S16(n) => write!(f, "{n}"),
+            Val::U16(n) => write!(f, "{n}"),
+            Val::S32(n) => write!(f, "{n}"),
+            Val::U32(n) => write!(f, "{n}"),
+            Val::S64(n) => write!(f, "{n}"),
+            Val::U64(n) => write!(f, "{n}"),
+            Val::Float32(n) => write_float(f, *n),
+            Val::Float64(n) => write_float(f, *n),
+            Val::Char(c) => {
+                f.write_char('\'')?;
+                write_escaped(f, *c)?;
+                f.write_char('\'')
+            }
+            Val::String(s) => {
+                f.write_char('"')?;
+                for c in s.chars() {
+                    write_escaped(f, c)?;
+                }
+                f.write_char('"')
+            }
+            Val::List(list) => {
+                f.write_char('[')?;
+                write_list(f, list.iter())?;
+                f.write_char(']')
+            }
+            Val::Tuple(tuple) => {
+                f.write_char('(')?;
+                write_list(f, tuple.values().iter())?;
+                f.write_char(')')
+            }
+            Val::Record(record) => {
+                f.write_char('{')?;
+                for (i, (name, val)) in record.fields().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{}: {}", DisplayName(name), DisplayVal(val))?;
+                }
+                f.write_char('}')
+            }
+            Val::Variant(variant) => write_case(f, variant.discriminant(), variant.payload()),
+            Val::Enum(enum_) => write!(f, "{}", DisplayName(enum_.discriminant())),
+            Val::Option(option) => match option.value() {
+                Some(val) => write_case(f, "some", Some(val)),
+                None => f.write_str("none"),
+            },
+            Val::Result(result) => match result.value() {
+                Ok(val) => write_case(f, "ok", val),
+                Err(val) => write_case(f, "err", val),
+            },
+            Val::Flags(flags) => {
+                f.write_char('{')?;
+                for (i, name) in flags.flags().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{}", DisplayName(name))?;
+                }
+                f.write_char('}')
+            }
+            Val::Resource(_) => f.write_str("<resource>"),
+        }
+    }
+}
+
+/// Displays a name, escaping it with `%` if it would otherwise be read as a
+/// keyword.
+struct DisplayName<'a>(&'a str);
+
+impl fmt::Display for DisplayName<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if KEYWORDS.contains(&self.0) {
+            f.write_char('%')?;
+        }
+        f.write_str(self.0)
+    }
+}
+
+fn write_float<T: Into<f64> + fmt::Display + Copy>(
+    f: &mut fmt::Formatter<'_>,
+    n: T,
+) -> fmt::Result {
+    let wide: f64 = n.into();
+    if wide.is_nan() {
+        f.write_str("nan")
+    } else if wide.is_infinite() {
+        f.write_str(if wide > 0.0 { "inf" } else { "-inf" })
+    } else {
+        write!(f, "{n}")
+    }
+}
+
+fn write_escaped(f: &mut fmt::Formatter<'_>, c: char) -> fmt::Result {
+    match c {
+        '\n' => f.write_str("\\n"),
+        '\r' => f.write_str("\\r"),
+        '\t' => f.write_str("\\t"),
+        '\\' | '\'' | '"' => write!(f, "\\{c}"),
+        c if c.is_control() => write!(f, "\\u{{{:x}}}", u32::from(c)),
+        c => f.write_char(c),
+    }
+}
+
+fn write_list<'a>(f: &mut fmt::Formatter<'_>, vals: impl Iterator<Item = &'a Val>) -> fmt::Result {
+    for (i, val) in vals.enumerate() {
+        if i > 0 {
+            f.write_str(", ")?;
+        }
+        write!(f, "{}", DisplayVal(val))?;
+    }
+    Ok(())
+}
+
+fn write_case(f: &mut fmt::Formatter<'_>, name: &str, payload: Option<&Val>) -> fmt::Result {
+    write!(f, "{}", DisplayName(name))?;
+    if let Some(val) = payload {
+        write!(f, "({})", DisplayVal(val))?;
+    }
+    Ok(())
+}
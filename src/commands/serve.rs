@@ -1,21 +1,26 @@
 use crate::common::{Profile, RunCommon, RunTarget};
-use anyhow::{anyhow, bail, Result};
+use anyhow::{anyhow, bail, Context, Result};
 use clap::Parser;
+use std::future::Future;
 use std::net::SocketAddr;
+use std::pin::Pin;
+use std::task::{Context as TaskContext, Poll};
 use std::{
     path::PathBuf,
     sync::{
         atomic::{AtomicBool, AtomicU64, Ordering},
-        Arc,
+        Arc, Mutex,
     },
 };
+use tokio::net::TcpStream;
 use wasmtime::component::{InstancePre, Linker};
-use wasmtime::{Engine, Store, StoreLimits};
+use wasmtime::{Engine, ResourceLimiter, Store, StoreLimits, StoreSnapshot};
 use wasmtime_wasi::preview2::{self, StreamError, StreamResult, WasiCtx, WasiCtxBuilder, WasiView};
 use wasmtime_wasi_http::io::TokioIo;
 use wasmtime_wasi_http::{
-    bindings::http::types as http_types, body::HyperOutgoingBody, hyper_response_error,
-    WasiHttpCtx, WasiHttpView,
+    bindings::http::types as http_types,
+    body::{HyperIncomingBody, HyperOutgoingBody},
+    hyper_response_error, WasiHttpCtx, WasiHttpView,
 };
 
 #[cfg(feature = "wasi-nn")]
@@ -28,10 +33,57 @@ struct Host {
 
     limits: StoreLimits,
 
+    /// The total size of the store's linear memories, which is tracked to
+    /// retire reused instances that have grown too much.
+    memory_size: usize,
+
     #[cfg(feature = "wasi-nn")]
     nn: Option<WasiNnCtx>,
 }
 
+impl ResourceLimiter for Host {
+    fn memory_growing(
+        &mut self,
+        current: usize,
+        desired: usize,
+        maximum: Option<usize>,
+    ) -> Result<bool> {
+        let allow = self.limits.memory_growing(current, desired, maximum)?;
+        if allow {
+            self.memory_size += desired - current;
+        }
+        Ok(allow)
+    }
+
+    fn memory_grow_failed(&mut self, error: anyhow::Error) -> Result<()> {
+        self.limits.memory_grow_failed(error)
+    }
+
+    fn table_growing(&mut self, current: u32, desired: u32, maximum: Option<u32>) -> Result<bool> {
+        self.limits.table_growing(current, desired, maximum)
+    }
+
+    fn table_grow_failed(&mut self, error: anyhow::Error) -> Result<()> {
+        self.limits.table_grow_failed(error)
+    }
+
+    fn instances(&self) -> usize {
+        self.limits.instances()
+    }
+
+    fn tables(&self) -> usize {
+        self.limits.tables()
+    }
+
+    fn memories(&self) -> usize {
+        self.limits.memories()
+    }
+
+    fn resource_handles(&self) -> usize {
+        self.limits.resource_handles()
+    }
+}
+
 impl WasiView for Host {
     fn table(&mut self) -> &mut wasmtime::component::ResourceTable {
         &mut self.table
@@ -67,6 +119,49 @@ pub struct ServeCommand {
     #[arg(long = "addr", value_name = "SOCKADDR", default_value_t = DEFAULT_ADDR )]
     addr: SocketAddr,
 
+    /// Serve HTTPS using the PEM-encoded certificate chain in this file.
+    ///
+    /// HTTP/2 is negotiated with clients over TLS using ALPN. Without TLS,
+    /// HTTP/2 is accepted from clients which use it with prior knowledge.
+    #[arg(long = "tls-cert", value_name = "PEM", requires = "tls_key")]
+    tls_cert: Option<PathBuf>,
+
+    /// The PEM-encoded private key for `--tls-cert`.
+    #[arg(long = "tls-key", value_name = "PEM", requires = "tls_cert")]
+    tls_key: Option<PathBuf>,
+
+    /// Reuse instances of the component across requests, rather than
+    /// instantiating it for every request.
+    ///
+    /// Each instance handles one request at a time, and idle instances are
+    /// kept in a pool. Instances which fail to handle a request, or which
+    /// leak resources while handling one, are discarded.
+    #[arg(long)]
+    reuse_instances: bool,
+
+    /// The number of instances to create before serving the first request,
+    /// which is also the number of idle instances kept in the pool (default:
+    /// 1).
+    #[arg(long, value_name = "N", requires = "reuse_instances")]
+    instance_pool_size: Option<usize>,
+
+    /// Discard an instance after it has handled this many requests.
+    #[arg(long, value_name = "N", requires = "reuse_instances")]
+    max_instance_reuse: Option<u64>,
+
+    /// Discard an instance once its linear memories have grown by more than
+    /// this many bytes since it was created.
+    #[arg(long, value_name = "BYTES", requires = "reuse_instances")]
+    max_instance_memory_growth: Option<usize>,
+
+    /// Restore the linear memories and globals of an instance to their state
+    /// just after instantiation before it handles another request.
+    ///
+    /// Instances whose memories have grown while handling a request can't be
+    /// restored, and are discarded instead.
+    #[arg(long, requires = "reuse_instances")]
+    reset_instances: bool,
+
     /// The WebAssembly component to run.
     #[arg(value_name = "WASM", required = true)]
     component: PathBuf,
@@ -86,7 +181,7 @@ impl ServeCommand {
             }
         }
 
-        if let Some(Profile::Guest { .. }) = &self.run.profile {
+        if let Some(Profile::Guest { .. } | Profile::GuestFuel { .. }) = &self.run.profile {
             bail!("Cannot use the guest profiler with components");
         }
 
@@ -130,27 +225,40 @@ impl ServeCommand {
         Ok(())
     }
 
-    fn new_store(&self, engine: &Engine, req_id: u64) -> Result<Store<Host>> {
+    /// Creates the WASI context for handling the request `req_id`, or for
+    /// instantiating a pooled instance ahead of any request if it's `None`.
+    fn new_wasi_ctx(&self, req_id: Option<u64>) -> WasiCtx {
         let mut builder = WasiCtxBuilder::new();
 
-        builder.envs(&[("REQUEST_ID", req_id.to_string())]);
+        let label = match req_id {
+            Some(req_id) => {
+                builder.envs(&[("REQUEST_ID", req_id.to_string())]);
+                req_id.to_string()
+            }
+            None => "init".to_string(),
+        };
 
         builder.stdout(LogStream {
-            prefix: format!("stdout [{req_id}] :: "),
+            prefix: format!("stdout [{label}] :: "),
             output: Output::Stdout,
         });
 
         builder.stderr(LogStream {
-            prefix: format!("stderr [{req_id}] :: "),
+            prefix: format!("stderr [{label}] :: "),
             output: Output::Stderr,
         });
 
+        builder.build()
+    }
+
+    fn new_store(&self, engine: &Engine, req_id: Option<u64>) -> Result<Store<Host>> {
         let mut host = Host {
             table: wasmtime::component::ResourceTable::new(),
-            ctx: builder.build(),
+            ctx: self.new_wasi_ctx(req_id),
             http: WasiHttpCtx,
 
             limits: StoreLimits::default(),
+            memory_size: 0,
 
             #[cfg(feature = "wasi-nn")]
             nn: None,
@@ -172,22 +280,33 @@ impl ServeCommand {
             }
         }
 
+        if let Some(max) = self.run.common.wasm.max_resource_handles {
+            host.table.set_max_entries(max);
+        }
+
         let mut store = Store::new(engine, host);
 
+        store.data_mut().limits = self.run.store_limits();
+        store.limiter(|t| t);
+
+        self.reset_budgets(&mut store)?;
+
+        Ok(store)
+    }
+
+    /// Resets the timeout and fuel of `store` for handling a new request.
+    fn reset_budgets(&self, store: &mut Store<Host>) -> Result<()> {
         if self.run.common.wasm.timeout.is_some() {
             store.set_epoch_deadline(u64::from(EPOCH_PRECISION) + 1);
         }
 
-        store.data_mut().limits = self.run.store_limits();
-        store.limiter(|t| &mut t.limits);
-
         // If fuel has been configured, we want to add the configured
         // fuel amount to this store.
         if let Some(fuel) = self.run.common.wasm.fuel {
             store.set_fuel(fuel)?;
         }
 
-        Ok(store)
+        Ok(())
     }
 
     fn add_to_linker(&self, linker: &mut Linker<Host>) -> Result<()> {
@@ -229,9 +348,59 @@ impl ServeCommand {
         Ok(())
     }
 
-    async fn serve(mut self) -> Result<()> {
-        use hyper::server::conn::http1;
+    /// Loads the certificate and key configured with `--tls-cert` and
+    /// `--tls-key`, if any.
+    fn tls_acceptor(&self) -> Result<Option<TlsAcceptor>> {
+        let (cert, key) = match (&self.tls_cert, &self.tls_key) {
+            (Some(cert), Some(key)) => (cert, key),
+            _ => return Ok(None),
+        };
 
+        #[cfg(any(target_arch = "riscv64", target_arch = "s390x"))]
+        {
+            let _ = (cert, key);
+            bail!("TLS is not supported on this architecture");
+        }
+
+        #[cfg(not(any(target_arch = "riscv64", target_arch = "s390x")))]
+        {
+            use tokio_rustls::rustls;
+
+            let certs = rustls_pemfile::certs(&mut open_pem(cert)?)
+                .with_context(|| format!("failed to parse `{}`", cert.display()))?
+                .into_iter()
+                .map(rustls::Certificate)
+                .collect::<Vec<_>>();
+            if certs.is_empty() {
+                bail!("no certificates found in `{}`", cert.display());
+            }
+
+            // PKCS#8, PKCS#1 and SEC1 keys are all accepted by rustls.
+            let mut reader = open_pem(key)?;
+            let key = loop {
+                match rustls_pemfile::read_one(&mut reader)
+                    .with_context(|| format!("failed to parse `{}`", key.display()))?
+                {
+                    Some(rustls_pemfile::Item::PKCS8Key(der))
+                    | Some(rustls_pemfile::Item::RSAKey(der))
+                    | Some(rustls_pemfile::Item::ECKey(der)) => break rustls::PrivateKey(der),
+                    Some(_) => {}
+                    None => bail!("no private key found in `{}`", key.display()),
+                }
+            };
+
+            let mut config = rustls::ServerConfig::builder()
+                .with_safe_defaults()
+                .with_no_client_auth()
+                .with_single_cert(certs, key)
+                .context("invalid TLS certificate or key")?;
+            config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+
+            Ok(Some(TlsAcceptor::from(Arc::new(config))))
+        }
+    }
+
+    async fn serve(mut self) -> Result<()> {
         let mut config = self.run.common.config(None)?;
         config.wasm_component_model(true);
         config.async_support(true);
@@ -246,7 +415,7 @@ impl ServeCommand {
             }
 
             // We bail early in `execute` if the guest profiler is configured.
-            Some(Profile::Guest { .. }) => unreachable!(),
+            Some(Profile::Guest { .. } | Profile::GuestFuel { .. }) => unreachable!(),
 
             None => {}
         }
@@ -263,6 +432,8 @@ impl ServeCommand {
 
         let instance = linker.instantiate_pre(&component)?;
 
+        let tls = self.tls_acceptor()?;
+
         // Tokio by default sets `SO_REUSEADDR` for listeners but that makes it
         // a bit confusing if you run Wasmtime but forget to close a previous
         // `serve` session. To avoid that we explicitly disable `SO_REUSEADDR`
@@ -275,7 +446,11 @@ impl ServeCommand {
         socket.bind(self.addr)?;
         let listener = socket.listen(100)?;
 
-        eprintln!("Serving HTTP on http://{}/", listener.local_addr()?);
+        if tls.is_some() {
+            eprintln!("Serving HTTPS on https://{}/", listener.local_addr()?);
+        } else {
+            eprintln!("Serving HTTP on http://{}/", listener.local_addr()?);
+        }
 
         let _epoch_thread = if let Some(timeout) = self.run.common.wasm.timeout {
             Some(EpochThread::spawn(
@@ -289,20 +464,14 @@ impl ServeCommand {
         log::info!("Listening on {}", self.addr);
 
         let handler = ProxyHandler::new(self, engine, instance);
+        handler.fill_pool().await?;
 
         loop {
             let (stream, _) = listener.accept().await?;
-            let stream = TokioIo::new(stream);
             let h = handler.clone();
+            let tls = tls.clone();
             tokio::task::spawn(async {
-                if let Err(e) = http1::Builder::new()
-                    .keep_alive(true)
-                    .serve_connection(
-                        stream,
-                        hyper::service::service_fn(move |req| handle_request(h.clone(), req)),
-                    )
-                    .await
-                {
+                if let Err(e) = serve_connection(h, stream, tls).await {
                     eprintln!("error: {e:?}");
                 }
             });
@@ -310,6 +479,179 @@ impl ServeCommand {
     }
 }
 
+#[cfg(not(any(target_arch = "riscv64", target_arch = "s390x")))]
+type TlsAcceptor = tokio_rustls::TlsAcceptor;
+
+/// TLS isn't available on these architectures, so an acceptor is never
+/// created.
+#[cfg(any(target_arch = "riscv64", target_arch = "s390x"))]
+#[derive(Clone)]
+enum TlsAcceptor {}
+
+/// Opens a PEM file for reading with `rustls_pemfile`.
+#[cfg(not(any(target_arch = "riscv64", target_arch = "s390x")))]
+fn open_pem(path: &std::path::Path) -> Result<std::io::BufReader<std::fs::File>> {
+    let file = std::fs::File::open(path)
+        .with_context(|| format!("failed to read `{}`", path.display()))?;
+    Ok(std::io::BufReader::new(file))
+}
+
+/// The preface which HTTP/2 clients send first, which identifies clients
+/// using HTTP/2 without TLS with prior knowledge.
+const H2_PREFACE: &[u8] = b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n";
+
+/// How long a client has to send enough data to tell whether it's speaking
+/// HTTP/1 or HTTP/2 before its connection is dropped.
+const H2_PREFACE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// Reads from `stream` until it's known whether the client is starting an
+/// HTTP/2 connection, returning that along with the data read so far.
+///
+/// Reading stops as soon as the data diverges from the HTTP/2 preface, so at
+/// most `H2_PREFACE.len()` bytes are read.
+async fn read_preface(stream: &mut TcpStream) -> Result<(bool, Vec<u8>)> {
+    use tokio::io::AsyncReadExt;
+
+    let mut buf = [0; H2_PREFACE.len()];
+    let mut len = 0;
+    let h2 = loop {
+        let n = stream.read(&mut buf[len..]).await?;
+        len += n;
+        if n == 0 || !H2_PREFACE.starts_with(&buf[..len]) {
+            break false;
+        }
+        if len == H2_PREFACE.len() {
+            break true;
+        }
+    };
+    Ok((h2, buf[..len].to_vec()))
+}
+
+/// A stream whose first bytes were already read by `read_preface`, which
+/// yields them again before reading any more from the underlying stream.
+struct Rewind<S> {
+    prefix: Vec<u8>,
+    pos: usize,
+    inner: S,
+}
+
+impl<S: tokio::io::AsyncRead + Unpin> tokio::io::AsyncRead for Rewind<S> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut TaskContext<'_>,
+        buf: &mut tokio::io::ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        let this = &mut *self;
+        if this.pos < this.prefix.len() {
+            let n = buf.remaining().min(this.prefix.len() - this.pos);
+            buf.put_slice(&this.prefix[this.pos..][..n]);
+            this.pos += n;
+            return Poll::Ready(Ok(()));
+        }
+        Pin::new(&mut this.inner).poll_read(cx, buf)
+    }
+}
+
+impl<S: tokio::io::AsyncWrite + Unpin> tokio::io::AsyncWrite for Rewind<S> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut TaskContext<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        Pin::new(&mut self.inner).poll_write(cx, buf)
+    }
+
+    fn poll_write_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut TaskContext<'_>,
+        bufs: &[std::io::IoSlice<'_>],
+    ) -> Poll<std::io::Result<usize>> {
+        Pin::new(&mut self.inner).poll_write_vectored(cx, bufs)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.inner.is_write_vectored()
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(
+        mut self: Pin<&mut Self>,
+        cx: &mut TaskContext<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
+async fn serve_connection(
+    handler: ProxyHandler,
+    mut stream: TcpStream,
+    tls: Option<TlsAcceptor>,
+) -> Result<()> {
+    if let Some(tls) = tls {
+        #[cfg(not(any(target_arch = "riscv64", target_arch = "s390x")))]
+        {
+            let stream = tls.accept(stream).await?;
+            let h2 = stream.get_ref().1.alpn_protocol() == Some(b"h2");
+            return serve_http(handler, TokioIo::new(stream), http::uri::Scheme::HTTPS, h2).await;
+        }
+        #[cfg(any(target_arch = "riscv64", target_arch = "s390x"))]
+        match tls {}
+    }
+
+    let (h2, prefix) = tokio::time::timeout(H2_PREFACE_TIMEOUT, read_preface(&mut stream))
+        .await
+        .context("timed out waiting for the client to send a request")??;
+    let stream = Rewind {
+        prefix,
+        pos: 0,
+        inner: stream,
+    };
+    serve_http(handler, TokioIo::new(stream), http::uri::Scheme::HTTP, h2).await
+}
+
+async fn serve_http<S>(
+    handler: ProxyHandler,
+    io: S,
+    scheme: http::uri::Scheme,
+    h2: bool,
+) -> Result<()>
+where
+    S: hyper::rt::Read + hyper::rt::Write + Unpin + Send + 'static,
+{
+    use hyper::server::conn::{http1, http2};
+
+    let service =
+        hyper::service::service_fn(move |req| handle_request(handler.clone(), scheme.clone(), req));
+    if h2 {
+        http2::Builder::new(TokioExecutor)
+            .serve_connection(io, service)
+            .await?;
+    } else {
+        http1::Builder::new()
+            .keep_alive(true)
+            .serve_connection(io, service)
+            .await?;
+    }
+    Ok(())
+}
+
+/// Runs the tasks hyper spawns for HTTP/2 streams on the tokio runtime.
+#[derive(Clone)]
+struct TokioExecutor;
+
+impl<F> hyper::rt::Executor<F> for TokioExecutor
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    fn execute(&self, fut: F) {
+        tokio::task::spawn(fut);
+    }
+}
+
 /// This is the number of epochs that we will observe before expiring a request handler. As
 /// instances may be started at any point within an epoch, and epochs are counted globally per
 /// engine, we expire after `EPOCH_PRECISION + 1` epochs have been observed. This gives a maximum
@@ -353,12 +695,143 @@ struct ProxyHandlerInner {
     engine: Engine,
     instance_pre: InstancePre<Host>,
     next_id: AtomicU64,
+    /// Idle instances, when `--reuse-instances` is enabled.
+    pool: Mutex<Vec<PooledInstance>>,
+}
+
+/// An instance of the component which is reused across requests.
+struct PooledInstance {
+    store: Store<Host>,
+    proxy: wasmtime_wasi_http::proxy::Proxy,
+    /// The number of requests this instance has handled.
+    uses: u64,
+    /// The size of the instance's memories once it was instantiated.
+    initial_memory_size: usize,
+    /// The state of the instance once it was instantiated, with
+    /// `--reset-instances`.
+    snapshot: Option<StoreSnapshot>,
+    /// The number of resources the instance may own between requests.
+    ///
+    /// This is the number after its first request, or after instantiation
+    /// with `--reset-instances` since restoring its state forgets any
+    /// resources acquired later.
+    resources: Option<usize>,
 }
 
 impl ProxyHandlerInner {
     fn next_req_id(&self) -> u64 {
         self.next_id.fetch_add(1, Ordering::Relaxed)
     }
+
+    fn pool_size(&self) -> usize {
+        self.cmd.instance_pool_size.unwrap_or(1)
+    }
+
+    async fn new_pooled_instance(&self) -> Result<PooledInstance> {
+        let mut store = self.cmd.new_store(&self.engine, None)?;
+        let (proxy, _inst) =
+            wasmtime_wasi_http::proxy::Proxy::instantiate_pre(&mut store, &self.instance_pre)
+                .await?;
+        let initial_memory_size = store.data().memory_size;
+        let (snapshot, resources) = if self.cmd.reset_instances {
+            let resources = store.data().table.entries().count();
+            (Some(store.snapshot()), Some(resources))
+        } else {
+            (None, None)
+        };
+        Ok(PooledInstance {
+            store,
+            proxy,
+            uses: 0,
+            initial_memory_size,
+            snapshot,
+            resources,
+        })
+    }
+
+    /// Takes an idle instance from the pool, creating one if there are none.
+    async fn take_instance(&self) -> Result<PooledInstance> {
+        let idle = self.pool.lock().unwrap().pop();
+        match idle {
+            Some(instance) => Ok(instance),
+            None => self.new_pooled_instance().await,
+        }
+    }
+
+    /// Returns an instance which successfully handled a request to the pool,
+    /// unless it has reached one of the configured limits.
+    fn return_instance(&self, mut instance: PooledInstance, req_id: u64) {
+        instance.uses += 1;
+        // Resources which the guest didn't drop are still referenced by its
+        // handle tables, so they can't be cleared without invalidating the
+        // instance. Guests may hold on to some resources for their whole
+        // lifetime though, such as stdio streams, so only instances which own
+        // more resources than after their first request are retired.
+        let resources = instance.store.data().table.entries().count();
+        match instance.resources {
+            Some(baseline) if resources > baseline => {
+                log::debug!(
+                    "[{req_id}] :: retiring instance which leaked {} resources",
+                    resources - baseline
+                );
+                return;
+            }
+            Some(_) => {}
+            None => instance.resources = Some(resources),
+        }
+        if let Some(max) = self.cmd.max_instance_reuse {
+            if instance.uses >= max {
+                log::debug!("[{req_id}] :: retiring instance after {max} requests");
+                return;
+            }
+        }
+        let growth = instance.store.data().memory_size - instance.initial_memory_size;
+        if let Some(max) = self.cmd.max_instance_memory_growth {
+            if growth > max {
+                log::debug!("[{req_id}] :: retiring instance after memory grew by {growth} bytes");
+                return;
+            }
+        }
+        if let Some(snapshot) = &instance.snapshot {
+            if let Err(e) = instance.store.restore(snapshot) {
+                log::debug!("[{req_id}] :: retiring instance: {e}");
+                return;
+            }
+        }
+        let mut pool = self.pool.lock().unwrap();
+        if pool.len() < self.pool_size() {
+            pool.push(instance);
+        }
+    }
+
+    /// Handles a request with an instance from the pool.
+    async fn handle_with_pooled_instance(
+        &self,
+        req_id: u64,
+        req: hyper::Request<HyperIncomingBody>,
+        sender: tokio::sync::oneshot::Sender<
+            Result<hyper::Response<HyperOutgoingBody>, http_types::ErrorCode>,
+        >,
+    ) -> Result<()> {
+        let mut instance = self.take_instance().await?;
+
+        // Each request gets its own environment and log prefixes, even though
+        // guests which cache their environment will only observe the first.
+        instance.store.data_mut().ctx = self.cmd.new_wasi_ctx(Some(req_id));
+        self.cmd.reset_budgets(&mut instance.store)?;
+
+        let req = instance.store.data_mut().new_incoming_request(req)?;
+        let out = instance.store.data_mut().new_response_outparam(sender)?;
+
+        instance
+            .proxy
+            .wasi_http_incoming_handler()
+            .call_handle(&mut instance.store, req, out)
+            .await?;
+
+        self.return_instance(instance, req_id);
+        Ok(())
+    }
 }
 
 #[derive(Clone)]
@@ -371,14 +844,29 @@ impl ProxyHandler {
             engine,
             instance_pre,
             next_id: AtomicU64::from(0),
+            pool: Mutex::new(Vec::new()),
         }))
     }
+
+    /// Creates the initial instances of the pool with `--reuse-instances`,
+    /// which also reports instantiation errors before serving any requests.
+    async fn fill_pool(&self) -> Result<()> {
+        if !self.0.cmd.reuse_instances {
+            return Ok(());
+        }
+        for _ in 0..self.0.pool_size() {
+            let instance = self.0.new_pooled_instance().await?;
+            self.0.pool.lock().unwrap().push(instance);
+        }
+        Ok(())
+    }
 }
 
 type Request = hyper::Request<hyper::body::Incoming>;
 
 async fn handle_request(
     ProxyHandler(inner): ProxyHandler,
+    scheme: http::uri::Scheme,
     req: Request,
 ) -> Result<hyper::Response<HyperOutgoingBody>> {
     use http_body_util::BodyExt;
@@ -393,17 +881,17 @@ async fn handle_request(
         parts.uri = {
             let uri_parts = parts.uri.into_parts();
 
-            let scheme = uri_parts.scheme.unwrap_or(http::uri::Scheme::HTTP);
-
-            let host = if let Some(val) = parts.headers.get(hyper::header::HOST) {
-                std::str::from_utf8(val.as_bytes())
-                    .map_err(|_| http_types::ErrorCode::HttpRequestUriInvalid)?
-            } else {
-                uri_parts
-                    .authority
-                    .as_ref()
-                    .ok_or(http_types::ErrorCode::HttpRequestUriInvalid)?
-                    .host()
+            // HTTP/2 requests carry the authority in the URI, while HTTP/1
+            // requests usually only have a `Host` header. The scheme is that
+            // of the connection, whatever the client claims.
+            let authority = match uri_parts.authority {
+                Some(authority) => authority,
+                None => parts
+                    .headers
+                    .get(hyper::header::HOST)
+                    .and_then(|val| val.to_str().ok())
+                    .and_then(|val| val.parse::<http::uri::Authority>().ok())
+                    .ok_or(http_types::ErrorCode::HttpRequestUriInvalid)?,
             };
 
             let path_with_query = uri_parts
@@ -412,7 +900,7 @@ async fn handle_request(
 
             hyper::Uri::builder()
                 .scheme(scheme)
-                .authority(host)
+                .authority(authority)
                 .path_and_query(path_with_query)
                 .build()
                 .map_err(|_| http_types::ErrorCode::HttpRequestUriInvalid)?
@@ -426,7 +914,15 @@ async fn handle_request(
             req.uri()
         );
 
-        let mut store = inner.cmd.new_store(&inner.engine, req_id)?;
+        if inner.cmd.reuse_instances {
+            if let Err(e) = inner.handle_with_pooled_instance(req_id, req, sender).await {
+                log::error!("[{req_id}] :: {:#?}", e);
+                return Err(e);
+            }
+            return Ok(());
+        }
+
+        let mut store = inner.cmd.new_store(&inner.engine, Some(req_id))?;
 
         let req = store.data_mut().new_incoming_request(req)?;
         let out = store.data_mut().new_response_outparam(sender)?;
@@ -49,7 +49,8 @@ pub struct RunCommon {
     #[arg(long = "allow-precompiled")]
     pub allow_precompiled: bool,
 
-    /// Profiling strategy (valid options are: perfmap, jitdump, vtune, guest)
+    /// Profiling strategy (valid options are: perfmap, jitdump, vtune, guest,
+    /// guest-fuel)
     ///
     /// The perfmap, jitdump, and vtune profiling strategies integrate Wasmtime
     /// with external profilers such as `perf`. The guest profiling strategy
@@ -64,6 +65,14 @@ pub struct RunCommon {
     /// where `path` is where to write the profile and `interval` is the
     /// duration between samples. When used with `--wasm-timeout` the timeout
     /// will be rounded up to the nearest multiple of this interval.
+    ///
+    /// The `guest-fuel` strategy is like `guest`, but samples every `interval`
+    /// units of fuel consumed (10000 by default) rather than periodically, and
+    /// attributes consumed fuel rather than time to each function. Its
+    /// profiles are identical between runs of the same program with the same
+    /// inputs. It's configured as:
+    ///
+    ///     --profile=guest-fuel[,path[,interval]]
     #[arg(
         long,
         value_name = "STRATEGY",
@@ -90,6 +99,9 @@ impl RunCommon {
         if let Some(max) = self.common.wasm.max_memories {
             limits = limits.memories(max);
         }
+        if let Some(max) = self.common.wasm.max_resource_handles {
+            limits = limits.resource_handles(max);
+        }
         if let Some(enable) = self.common.wasm.trap_on_grow_failure {
             limits = limits.trap_on_grow_failure(enable);
         }
@@ -220,6 +232,7 @@ impl RunCommon {
 pub enum Profile {
     Native(wasmtime::ProfilingStrategy),
     Guest { path: String, interval: Duration },
+    GuestFuel { path: String, interval: u64 },
 }
 
 impl Profile {
@@ -242,6 +255,18 @@ impl Profile {
                 path: path.to_string(),
                 interval: WasmtimeOptionValue::parse(Some(dur))?,
             }),
+            ["guest-fuel"] => Ok(Profile::GuestFuel {
+                path: "wasmtime-guest-profile.json".to_string(),
+                interval: 10_000,
+            }),
+            ["guest-fuel", path] => Ok(Profile::GuestFuel {
+                path: path.to_string(),
+                interval: 10_000,
+            }),
+            ["guest-fuel", path, fuel] => Ok(Profile::GuestFuel {
+                path: path.to_string(),
+                interval: WasmtimeOptionValue::parse(Some(fuel))?,
+            }),
             _ => bail!("unknown profiling strategy: {s}"),
         }
     }
@@ -826,6 +826,8 @@ impl RunCommand {
             vars,
             invoke,
             preloads,
+            record: None,
+            replay: None,
             module_and_args,
         }
     }
@@ -865,6 +865,105 @@ fn run_precompiled_component() -> Result<()> {
     Ok(())
 }
 
+#[test]
+#[cfg_attr(not(feature = "component-model"), ignore)]
+fn run_component_invoke() -> Result<()> {
+    let path = "tests/all/cli_tests/component-invoke.wat";
+    let invoke = |expr: &str| {
+        run_wasmtime(&[
+            "run",
+            "-Ccache=n",
+            "-Wcomponent-model",
+            "--invoke",
+            expr,
+            path,
+        ])
+    };
+
+    assert_eq!(invoke("add(1, 2)")?, "3\n");
+    assert_eq!(invoke("sum-point({x: 3, y: -5})")?, "-2\n");
+    assert_eq!(invoke("len(\"h\\u{e9}llo\")")?, "6\n");
+    assert_eq!(invoke("echo(\"a\\\"b\\n\")")?, "\"a\\\"b\\n\"\n");
+    assert_eq!(invoke("maybe-double(some(21))")?, "some(42)\n");
+    assert_eq!(invoke("maybe-double(none)")?, "none\n");
+
+    let err = invoke("add(1)").unwrap_err().to_string();
+    assert!(
+        err.contains("failed to parse arguments to `add`"),
+        "bad error: {err}"
+    );
+    let err = invoke("sum-point({y: 1, x: 2})").unwrap_err().to_string();
+    assert!(err.contains("expected field `x`"), "bad error: {err}");
+    let err = invoke("missing()").unwrap_err().to_string();
+    assert!(
+        err.contains("no func export named `missing` found"),
+        "bad error: {err}"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn config_cache_stats_and_purge() -> Result<()> {
+    let td = TempDir::new()?;
+    let config = td.path().join("cache.toml");
+    std::fs::write(
+        &config,
+        format!(
+            "[cache]\nenabled = true\ndirectory = {:?}\n",
+            td.path().join("cache")
+        ),
+    )?;
+    let config = config.to_str().unwrap();
+
+    let stdout = run_wasmtime(&["config", "cache", "stats", "--config", config])?;
+    assert!(
+        stdout.contains("modules: 0 (0 pinned)"),
+        "bad stdout: {stdout}"
+    );
+
+    assert!(
+        stdout.contains("hits: 0, misses: 0, evictions: 0"),
+        "bad stdout: {stdout}"
+    );
+
+    // The hit and miss counters persist across runs.
+    let run = || {
+        run_wasmtime(&[
+            "run",
+            &format!("-Ccache-config={config}"),
+            "--invoke",
+            "get_f32",
+            "tests/all/cli_tests/simple.wat",
+        ])
+    };
+    run()?;
+    let stdout = run_wasmtime(&["config", "cache", "stats", "--config", config, "--entries"])?;
+    assert!(
+        stdout.contains("modules: 1 (0 pinned)"),
+        "bad stdout: {stdout}"
+    );
+    assert!(
+        stdout.contains("hits: 0, misses: 1, evictions: 0"),
+        "bad stdout: {stdout}"
+    );
+    run()?;
+    let stdout = run_wasmtime(&["config", "cache", "stats", "--config", config])?;
+    assert!(
+        stdout.contains("hits: 1, misses: 1, evictions: 0"),
+        "bad stdout: {stdout}"
+    );
+
+    let stdout = run_wasmtime(&["config", "cache", "purge", "--config", config])?;
+    assert_eq!(stdout, "Removed 1 modules from the cache.\n");
+    let stdout = run_wasmtime(&["config", "cache", "stats", "--config", config])?;
+    assert!(
+        stdout.contains("modules: 0 (0 pinned)"),
+        "bad stdout: {stdout}"
+    );
+    Ok(())
+}
+
 #[test]
 fn memory_growth_failure() -> Result<()> {
     let output = get_wasmtime_command()?
@@ -1212,6 +1311,30 @@ warning: this CLI invocation of Wasmtime is going to break in the future -- for
     Ok(())
 }
 
+#[test]
+#[cfg_attr(not(feature = "profiling"), ignore)]
+fn profile_guest_fuel_is_deterministic() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let profile = |name: &str| -> Result<String> {
+        let path = dir.path().join(name);
+        run_wasmtime(&[
+            "run",
+            "-Ccache=n",
+            &format!("--profile=guest-fuel,{},100", path.display()),
+            "tests/all/cli_tests/profile-fuel.wat",
+        ])?;
+        Ok(std::fs::read_to_string(path)?)
+    };
+
+    let first = profile("first.json")?;
+    let second = profile("second.json")?;
+    assert_eq!(first, second);
+    for name in ["spin", "small", "large"] {
+        assert!(first.contains(name), "missing `{name}` in {first}");
+    }
+    Ok(())
+}
+
 #[test]
 fn float_args() -> Result<()> {
     let result = run_wasmtime(&[
@@ -1249,6 +1372,59 @@ fn mpk_without_pooling() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn record_and_replay() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let trace = dir.path().join("trace.bin");
+    let trace = trace.to_str().unwrap();
+    let wasm = "tests/all/cli_tests/print-random.wat";
+
+    let recorded = run_wasmtime_for_output(&["run", "--record", trace, wasm], None)?;
+    assert!(recorded.status.success());
+    assert_eq!(recorded.stdout.len(), 24);
+
+    // Replaying reproduces the same random bytes and time.
+    let replayed = run_wasmtime_for_output(&["run", "--replay", trace, wasm], None)?;
+    assert!(replayed.status.success());
+    assert_eq!(replayed.stdout, recorded.stdout);
+
+    // A program which makes different calls diverges from the trace.
+    let output = run_wasmtime_for_output(
+        &[
+            "run",
+            "--replay",
+            trace,
+            "tests/all/cli_tests/hello_wasi_snapshot1.wat",
+        ],
+        None,
+    )?;
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("replay diverged"), "{stderr}");
+
+    // Traces require the preview2 implementation of WASI.
+    let output = run_wasmtime_for_output(&["run", "-Spreview2=n", "--record", trace, wasm], None)?;
+    assert!(!output.status.success());
+
+    // Socket calls are recorded too, and replaying doesn't need the network.
+    let recorded = run_wasmtime_for_output(
+        &[
+            "run",
+            "-Sinherit-network",
+            "-Sallow-ip-name-lookup",
+            "--record",
+            trace,
+            wasm,
+        ],
+        None,
+    )?;
+    assert!(recorded.status.success());
+    let replayed = run_wasmtime_for_output(&["run", "--replay", trace, wasm], None)?;
+    assert!(replayed.status.success());
+    assert_eq!(replayed.stdout, recorded.stdout);
+    Ok(())
+}
+
 mod test_programs {
     use super::{get_wasmtime_command, run_wasmtime};
     use anyhow::Result;
@@ -1587,4 +1763,206 @@ mod test_programs {
         assert!(output.status.success());
         Ok(())
     }
+
+    /// Starts `wasmtime serve` for `API_PROXY_COMPONENT` on an available
+    /// port, returning the process and the address it's listening on.
+    #[cfg(feature = "serve")]
+    fn start_serve(args: &[&str]) -> Result<(std::process::Child, String)> {
+        use std::io::BufRead;
+
+        let mut child = get_wasmtime_command()?
+            .arg("serve")
+            .arg("--addr=127.0.0.1:0")
+            .args(args)
+            .arg(API_PROXY_COMPONENT)
+            .stderr(Stdio::piped())
+            .spawn()?;
+        let mut stderr = std::io::BufReader::new(child.stderr.take().unwrap());
+        let mut line = String::new();
+        stderr.read_line(&mut line)?;
+        let line = line.trim();
+        let addr = line
+            .strip_prefix("Serving HTTP on http://")
+            .or_else(|| line.strip_prefix("Serving HTTPS on https://"))
+            .and_then(|l| l.strip_suffix('/'))
+            .unwrap_or_else(|| panic!("unexpected output: {line}"))
+            .to_string();
+        Ok((child, addr))
+    }
+
+    /// Runs the tasks hyper spawns for HTTP/2 client connections.
+    #[cfg(feature = "serve")]
+    #[derive(Clone)]
+    struct TokioExecutor;
+
+    #[cfg(feature = "serve")]
+    impl<F> hyper::rt::Executor<F> for TokioExecutor
+    where
+        F: std::future::Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        fn execute(&self, fut: F) {
+            tokio::task::spawn(fut);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "serve")]
+    fn serve_http1_and_h2c() -> Result<()> {
+        use http_body_util::{BodyExt, Empty};
+        use wasmtime_wasi_http::io::TokioIo;
+
+        let (mut child, addr) = start_serve(&[])?;
+
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .build()?;
+        let result = runtime.block_on(async {
+            let req = || {
+                hyper::Request::get(format!("http://{addr}/"))
+                    .body(Empty::<bytes::Bytes>::new())
+                    .unwrap()
+            };
+
+            let stream = tokio::net::TcpStream::connect(&addr).await?;
+            let (mut sender, conn) =
+                hyper::client::conn::http1::handshake(TokioIo::new(stream)).await?;
+            tokio::task::spawn(conn);
+            let resp = sender.send_request(req()).await?;
+            assert_eq!(resp.status(), 200);
+            assert_eq!(resp.version(), hyper::Version::HTTP_11);
+            let body = resp.into_body().collect().await?.to_bytes();
+            assert_eq!(body, "hello, world!");
+
+            let stream = tokio::net::TcpStream::connect(&addr).await?;
+            let (mut sender, conn) =
+                hyper::client::conn::http2::handshake(TokioExecutor, TokioIo::new(stream)).await?;
+            tokio::task::spawn(conn);
+            let resp = sender.send_request(req()).await?;
+            assert_eq!(resp.status(), 200);
+            assert_eq!(resp.version(), hyper::Version::HTTP_2);
+            let body = resp.into_body().collect().await?.to_bytes();
+            assert_eq!(body, "hello, world!");
+            anyhow::Ok(())
+        });
+
+        child.kill()?;
+        child.wait()?;
+        result
+    }
+
+    #[test]
+    #[cfg(feature = "serve")]
+    fn serve_reuse_instances() -> Result<()> {
+        use http_body_util::{BodyExt, Empty};
+        use wasmtime_wasi_http::io::TokioIo;
+
+        let (mut child, addr) = start_serve(&[
+            "--reuse-instances",
+            "--instance-pool-size=2",
+            "--max-instance-reuse=3",
+            "--reset-instances",
+        ])?;
+
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .build()?;
+        let result = runtime.block_on(async {
+            // Enough requests that instances are both reused and retired.
+            for _ in 0..8 {
+                let stream = tokio::net::TcpStream::connect(&addr).await?;
+                let (mut sender, conn) =
+                    hyper::client::conn::http1::handshake(TokioIo::new(stream)).await?;
+                tokio::task::spawn(conn);
+                let req = hyper::Request::get(format!("http://{addr}/"))
+                    .body(Empty::<bytes::Bytes>::new())
+                    .unwrap();
+                let resp = sender.send_request(req).await?;
+                assert_eq!(resp.status(), 200);
+                let body = resp.into_body().collect().await?.to_bytes();
+                assert_eq!(body, "hello, world!");
+            }
+            anyhow::Ok(())
+        });
+
+        child.kill()?;
+        child.wait()?;
+        result
+    }
+
+    #[test]
+    #[cfg(all(
+        feature = "serve",
+        not(any(target_arch = "riscv64", target_arch = "s390x"))
+    ))]
+    fn serve_tls() -> Result<()> {
+        use http_body_util::{BodyExt, Empty};
+        use std::sync::Arc;
+        use tokio_rustls::rustls;
+        use wasmtime_wasi_http::io::TokioIo;
+
+        let cert = rcgen::generate_simple_self_signed(vec!["localhost".to_string()])?;
+        let dir = tempfile::tempdir()?;
+        let cert_path = dir.path().join("cert.pem");
+        let key_path = dir.path().join("key.pem");
+        std::fs::write(&cert_path, cert.serialize_pem()?)?;
+        std::fs::write(&key_path, cert.serialize_private_key_pem())?;
+
+        let (mut child, addr) = start_serve(&[
+            &format!("--tls-cert={}", cert_path.display()),
+            &format!("--tls-key={}", key_path.display()),
+        ])?;
+
+        let mut roots = rustls::RootCertStore::empty();
+        roots.add(&rustls::Certificate(cert.serialize_der()?))?;
+
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .build()?;
+        let result = runtime.block_on(async {
+            // Connect once negotiating HTTP/1.1 and once negotiating HTTP/2
+            // with ALPN.
+            for (alpn, version) in [
+                (&b"http/1.1"[..], hyper::Version::HTTP_11),
+                (&b"h2"[..], hyper::Version::HTTP_2),
+            ] {
+                let mut config = rustls::ClientConfig::builder()
+                    .with_safe_defaults()
+                    .with_root_certificates(roots.clone())
+                    .with_no_client_auth();
+                config.alpn_protocols = vec![alpn.to_vec()];
+                let connector = tokio_rustls::TlsConnector::from(Arc::new(config));
+                let stream = tokio::net::TcpStream::connect(&addr).await?;
+                let stream = connector
+                    .connect(rustls::ServerName::try_from("localhost")?, stream)
+                    .await?;
+                assert_eq!(stream.get_ref().1.alpn_protocol(), Some(alpn));
+
+                let req = hyper::Request::get("https://localhost/")
+                    .body(Empty::<bytes::Bytes>::new())
+                    .unwrap();
+                let resp = if version == hyper::Version::HTTP_2 {
+                    let (mut sender, conn) =
+                        hyper::client::conn::http2::handshake(TokioExecutor, TokioIo::new(stream))
+                            .await?;
+                    tokio::task::spawn(conn);
+                    sender.send_request(req).await?
+                } else {
+                    let (mut sender, conn) =
+                        hyper::client::conn::http1::handshake(TokioIo::new(stream)).await?;
+                    tokio::task::spawn(conn);
+                    sender.send_request(req).await?
+                };
+                assert_eq!(resp.status(), 200);
+                assert_eq!(resp.version(), version);
+                let body = resp.into_body().collect().await?.to_bytes();
+                assert_eq!(body, "hello, world!");
+            }
+            anyhow::Ok(())
+        });
+
+        child.kill()?;
+        child.wait()?;
+        result
+    }
 }
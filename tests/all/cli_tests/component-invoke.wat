@@ -0,0 +1,68 @@
+(component
+  (core module $m
+    (memory (export "memory") 1)
+    (global $heap (mut i32) (i32.const 1024))
+
+    (func (export "realloc") (param i32 i32 i32 i32) (result i32)
+      (local $ret i32)
+      global.get $heap
+      local.set $ret
+      global.get $heap
+      local.get 3
+      i32.add
+      global.set $heap
+      local.get $ret)
+
+    (func (export "add") (param i32 i32) (result i32)
+      local.get 0
+      local.get 1
+      i32.add)
+
+    (func (export "sum-point") (param i32 i32) (result i32)
+      local.get 0
+      local.get 1
+      i32.add)
+
+    (func (export "len") (param i32 i32) (result i32)
+      local.get 1)
+
+    ;; Returns its argument as-is, with the pointer and length stored at 0.
+    (func (export "echo") (param i32 i32) (result i32)
+      i32.const 0
+      local.get 0
+      i32.store
+      i32.const 4
+      local.get 1
+      i32.store
+      i32.const 0)
+
+    ;; Writes the resulting option to offset 8.
+    (func (export "maybe-double") (param i32 i32) (result i32)
+      i32.const 8
+      local.get 0
+      i32.store8
+      i32.const 12
+      local.get 1
+      i32.const 2
+      i32.mul
+      i32.store
+      i32.const 8)
+  )
+  (core instance $i (instantiate $m))
+
+  (type $point (record (field "x" s32) (field "y" s32)))
+  (export $point' "point" (type $point))
+
+  (func (export "add") (param "a" u32) (param "b" u32) (result u32)
+    (canon lift (core func $i "add")))
+  (func (export "sum-point") (param "p" $point') (result s32)
+    (canon lift (core func $i "sum-point")))
+  (func (export "len") (param "s" string) (result u32)
+    (canon lift (core func $i "len")
+      (memory $i "memory") (realloc (func $i "realloc"))))
+  (func (export "echo") (param "s" string) (result string)
+    (canon lift (core func $i "echo")
+      (memory $i "memory") (realloc (func $i "realloc"))))
+  (func (export "maybe-double") (param "x" (option u32)) (result (option u32))
+    (canon lift (core func $i "maybe-double") (memory $i "memory")))
+)
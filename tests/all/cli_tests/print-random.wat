@@ -0,0 +1,31 @@
+(module
+  (import "wasi_snapshot_preview1" "random_get"
+    (func $__wasi_random_get (param i32 i32) (result i32)))
+  (import "wasi_snapshot_preview1" "clock_time_get"
+    (func $__wasi_clock_time_get (param i32 i64 i32) (result i32)))
+  (import "wasi_snapshot_preview1" "fd_write"
+    (func $__wasi_fd_write (param i32 i32 i32 i32) (result i32)))
+  (import "wasi_snapshot_preview1" "proc_exit"
+    (func $__wasi_proc_exit (param i32)))
+  (func $_start
+    ;; 16 random bytes followed by the realtime clock, printed as raw bytes.
+    (block
+      (br_if 0 (call $__wasi_random_get (i32.const 0) (i32.const 16)))
+      (br_if 0 (call $__wasi_clock_time_get (i32.const 0) (i64.const 1) (i32.const 16)))
+      (i32.store (i32.const 32) (i32.const 0))
+      (i32.store (i32.const 36) (i32.const 24))
+      (br_if 0
+        (call $__wasi_fd_write
+          (i32.const 1)
+          (i32.const 32)
+          (i32.const 1)
+          (i32.const 40)))
+      (br_if 0 (i32.ne (i32.load (i32.const 40)) (i32.const 24)))
+      (return)
+    )
+    (call $__wasi_proc_exit (i32.const 1))
+  )
+  (memory 1)
+  (export "memory" (memory 0))
+  (export "_start" (func $_start))
+)
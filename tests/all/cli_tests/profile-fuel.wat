@@ -0,0 +1,10 @@
+(module
+  (func $spin (param $n i32)
+    (loop $l
+      (local.set $n (i32.sub (local.get $n) (i32.const 1)))
+      (br_if $l (local.get $n))))
+  (func $small (call $spin (i32.const 1000)))
+  (func $large (call $spin (i32.const 5000)))
+  (func (export "_start")
+    (call $small)
+    (call $large)))
@@ -638,3 +638,283 @@ mod exported_resources {
         Ok(())
     }
 }
+
+mod mocks {
+    use super::*;
+    use foo::foo::def::{HostMock, HostMockCall};
+
+    wasmtime::component::bindgen!({
+        inline: "
+            package foo:foo;
+
+            interface def {
+                resource x {
+                    constructor();
+                    get: func() -> u32;
+                }
+
+                add: func(a: u32, b: u32) -> u32;
+                notify: func(v: u32);
+            }
+
+            world mocks {
+                import def;
+                export run: func() -> u32;
+            }
+        ",
+        mocks: true,
+    });
+
+    const COMPONENT: &str = r#"
+        (component
+          (import (interface "foo:foo/def") (instance $i
+            (export $x "x" (type (sub resource)))
+            (export "[constructor]x" (func (result (own $x))))
+            (export "[method]x.get" (func (param "self" (borrow $x)) (result u32)))
+            (export "add" (func (param "a" u32) (param "b" u32) (result u32)))
+            (export "notify" (func (param "v" u32)))
+          ))
+          (alias export $i "x" (type $x))
+          (core func $dtor (canon resource.drop $x))
+          (core func $ctor (canon lower (func $i "[constructor]x")))
+          (core func $get (canon lower (func $i "[method]x.get")))
+          (core func $add (canon lower (func $i "add")))
+          (core func $notify (canon lower (func $i "notify")))
+        
+          (core module $m
+            (import "" "ctor" (func $ctor (result i32)))
+            (import "" "dtor" (func $dtor (param i32)))
+            (import "" "get" (func $get (param i32) (result i32)))
+            (import "" "add" (func $add (param i32 i32) (result i32)))
+            (import "" "notify" (func $notify (param i32)))
+        
+            (func (export "run") (result i32)
+              (local $x i32)
+              (local $r i32)
+              (local.set $x (call $ctor))
+              (local.set $r (call $add (call $get (local.get $x)) (i32.const 1)))
+              (call $notify (i32.const 5))
+              (call $dtor (local.get $x))
+              (local.get $r)
+            )
+          )
+          (core instance $m (instantiate $m
+            (with "" (instance
+              (export "ctor" (func $ctor))
+              (export "dtor" (func $dtor))
+              (export "get" (func $get))
+              (export "add" (func $add))
+              (export "notify" (func $notify))
+            ))
+          ))
+          (func (export "run") (result u32)
+            (canon lift (core func $m "run")))
+        )
+    "#;
+
+    fn instantiate(mock: HostMock) -> Result<(Store<HostMock>, Mocks)> {
+        let engine = engine();
+        let component = Component::new(&engine, COMPONENT)?;
+        let mut linker = Linker::new(&engine);
+        Mocks::add_to_linker(&mut linker, |m: &mut HostMock| m)?;
+        let mut store = Store::new(&engine, mock);
+        let (mocks, _) = Mocks::instantiate(&mut store, &component, &linker)?;
+        Ok((store, mocks))
+    }
+
+    #[test]
+    fn records_calls() -> Result<()> {
+        let mut mock = HostMock::default();
+        mock.return_method_x_get(Ok(41))
+            .expect_add(|a, b| Ok(a + b));
+        let (mut store, mocks) = instantiate(mock)?;
+
+        assert_eq!(mocks.call_run(&mut store)?, 42);
+        let calls = &store.data().calls;
+        assert!(
+            matches!(
+                calls[..],
+                [
+                    HostMockCall::ConstructorX {},
+                    HostMockCall::MethodXGet { .. },
+                    HostMockCall::Add { a: 41, b: 1 },
+                    HostMockCall::Notify { v: 5 },
+                    HostMockCall::DropX { .. },
+                ]
+            ),
+            "{calls:?}"
+        );
+
+        // The resource passed to the method and the destructor is the one
+        // created by the constructor, which is no longer in the table.
+        let (get, drop) = match &calls[..] {
+            [_, HostMockCall::MethodXGet { self_ }, _, _, HostMockCall::DropX { rep }] => {
+                (self_.rep(), rep.rep())
+            }
+            _ => unreachable!(),
+        };
+        assert_eq!(get, drop);
+        assert!(store.data_mut().table.get_any_mut(drop).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn unconfigured_return_value() -> Result<()> {
+        let mut mock = HostMock::default();
+        mock.return_method_x_get(Ok(1));
+        let (mut store, mocks) = instantiate(mock)?;
+
+        let err = mocks.call_run(&mut store).unwrap_err();
+        assert!(
+            format!("{err:?}").contains("no return value configured for mock of `add`"),
+            "{err:?}"
+        );
+        Ok(())
+    }
+}
+
+mod mocks_trappable_errors {
+    use super::*;
+    use foo::foo::errs::{Failure, HostMock, HostMockCall};
+
+    wasmtime::component::bindgen!({
+        inline: "
+            package foo:foo;
+
+            interface errs {
+                enum failure { bad }
+
+                check: func(v: u32) -> result<u32, failure>;
+            }
+
+            world trappable {
+                import errs;
+                import log: func(msg: string);
+                export run: func(v: u32) -> u32;
+            }
+        ",
+        trappable_error_type: {
+            "foo:foo/errs/failure" => MyError,
+        },
+        mocks: true,
+    });
+
+    pub enum MyError {
+        Failure(Failure),
+        Other,
+    }
+
+    const COMPONENT: &str = r#"
+        (component
+          (import (interface "foo:foo/errs") (instance $errs
+            (type $f (enum "bad"))
+            (export "failure" (type $failure (eq $f)))
+            (export "check" (func (param "v" u32) (result (result u32 (error $failure)))))
+          ))
+          (import "log" (func $log (param "msg" string)))
+
+          (core module $libc (memory (export "memory") 1))
+          (core instance $libc (instantiate $libc))
+          (core func $check (canon lower (func $errs "check") (memory $libc "memory")))
+          (core func $log (canon lower (func $log) (memory $libc "memory")))
+
+          (core module $m
+            (import "libc" "memory" (memory 1))
+            (import "" "check" (func $check (param i32 i32)))
+            (import "" "log" (func $log (param i32 i32)))
+            (data (i32.const 0) "hello")
+
+            ;; Returns the `ok` payload of `check`, or 100 plus the error's
+            ;; discriminant.
+            (func (export "run") (param i32) (result i32)
+              (call $log (i32.const 0) (i32.const 5))
+              (call $check (local.get 0) (i32.const 16))
+              (if (result i32) (i32.eqz (i32.load8_u (i32.const 16)))
+                (then (i32.load (i32.const 20)))
+                (else (i32.add (i32.const 100) (i32.load8_u (i32.const 20)))))
+            )
+          )
+          (core instance $m (instantiate $m
+            (with "libc" (instance $libc))
+            (with "" (instance
+              (export "check" (func $check))
+              (export "log" (func $log))
+            ))
+          ))
+          (func (export "run") (param "v" u32) (result u32)
+            (canon lift (core func $m "run")))
+        )
+    "#;
+
+    #[derive(Default)]
+    struct Host {
+        errs: HostMock,
+        world: TrappableImportsMock,
+    }
+
+    fn instantiate(host: Host) -> Result<(Store<Host>, Trappable)> {
+        let engine = engine();
+        let component = Component::new(&engine, COMPONENT)?;
+        let mut linker = Linker::new(&engine);
+        foo::foo::errs::add_to_linker(&mut linker, |h: &mut Host| &mut h.errs)?;
+        Trappable::add_root_to_linker(&mut linker, |h: &mut Host| &mut h.world)?;
+        let mut store = Store::new(&engine, host);
+        let (trappable, _) = Trappable::instantiate(&mut store, &component, &linker)?;
+        Ok((store, trappable))
+    }
+
+    #[test]
+    fn converts_errors() -> Result<()> {
+        let mut host = Host::default();
+        host.errs
+            .return_check(Ok(7))
+            .return_check(Err(MyError::Failure(Failure::Bad)))
+            .return_check(Err(MyError::Other))
+            .expect_convert_failure(|err| match err {
+                MyError::Failure(f) => Ok(*f),
+                MyError::Other => Err(anyhow::anyhow!("other error")),
+            });
+        let (mut store, trappable) = instantiate(host)?;
+
+        assert_eq!(trappable.call_run(&mut store, 1)?, 7);
+        assert_eq!(trappable.call_run(&mut store, 2)?, 100);
+        let err = trappable.call_run(&mut store, 3).unwrap_err();
+        assert!(format!("{err:?}").contains("other error"), "{err:?}");
+
+        // Conversions aren't recorded, only calls from the guest.
+        let calls = &store.data().errs.calls;
+        assert!(
+            matches!(
+                calls[..],
+                [
+                    HostMockCall::Check { v: 1 },
+                    HostMockCall::Check { v: 2 },
+                    HostMockCall::Check { v: 3 },
+                ]
+            ),
+            "{calls:?}"
+        );
+        let calls = &store.data().world.calls;
+        assert_eq!(calls.len(), 3);
+        for call in calls {
+            match call {
+                TrappableImportsMockCall::Log { msg } => assert_eq!(msg, "hello"),
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn unconfigured_conversion() -> Result<()> {
+        let mut host = Host::default();
+        host.errs.return_check(Err(MyError::Failure(Failure::Bad)));
+        let (mut store, trappable) = instantiate(host)?;
+
+        let err = trappable.call_run(&mut store, 1).unwrap_err();
+        assert!(
+            format!("{err:?}").contains("no conversion configured for mock of `convert_failure`"),
+            "{err:?}"
+        );
+        Ok(())
+    }
+}
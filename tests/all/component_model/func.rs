@@ -5,7 +5,7 @@ use anyhow::Result;
 use std::rc::Rc;
 use std::sync::Arc;
 use wasmtime::component::*;
-use wasmtime::{Store, StoreContextMut, Trap};
+use wasmtime::{CallOptions, Engine, Store, StoreContextMut, Trap};
 
 const CANON_32BIT_NAN: u32 = 0b01111111110000000000000000000000;
 const CANON_64BIT_NAN: u64 = 0b0111111111111000000000000000000000000000000000000000000000000000;
@@ -2159,6 +2159,99 @@ fn raw_slice_of_various_types() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn lower_bytes() -> Result<()> {
+    let component = format!(
+        r#"
+(component
+  (import "sum" (func $sum (param "a" (list u8)) (result u32)))
+  (import "make" (func $make (result (list u8))))
+
+  (core module $libc
+    (memory (export "memory") 1)
+    {REALLOC_AND_FREE}
+  )
+  (core instance $libc (instantiate $libc))
+
+  (core func $sum_lower
+    (canon lower (func $sum) (memory $libc "memory"))
+  )
+  (core func $make_lower
+    (canon lower (func $make) (memory $libc "memory") (realloc (func $libc "realloc")))
+  )
+
+  (core module $m
+    (func (export "read") (param i32) (result i32) local.get 0)
+  )
+  (core instance $m (instantiate $m))
+
+  ;; `sum` forwards its argument to the host's `sum` import
+  (func (export "sum") (param "a" (list u8)) (result u32)
+    (canon lift (core func $sum_lower)
+      (memory $libc "memory")
+      (realloc (func $libc "realloc"))
+    )
+  )
+  ;; `make` stores the result of the host's `make` import at `p`
+  (func (export "make") (param "p" u32)
+    (canon lift (core func $make_lower))
+  )
+  ;; `read` reads a list previously stored at `p`
+  (func (export "read") (param "p" u32) (result (list u8))
+    (canon lift (core func $m "read") (memory $libc "memory"))
+  )
+)
+    "#
+    );
+
+    let engine = super::engine();
+    let component = Component::new(&engine, component)?;
+    let mut store = Store::new(&engine, ());
+    let mut linker = Linker::new(&engine);
+    linker.root().func_wrap(
+        "sum",
+        |store: StoreContextMut<'_, ()>, (list,): (WasmList<u8>,)| {
+            let bytes = list.as_le_slice(&store);
+            Ok((bytes.iter().map(|b| u32::from(*b)).sum::<u32>(),))
+        },
+    )?;
+    linker.root().func_wrap("make", |_, ()| {
+        Ok((LowerBytes::new(5, |buf: &mut [u8]| {
+            buf.copy_from_slice(&[1, 2, 3, 4, 5]);
+            Ok(())
+        }),))
+    })?;
+    let instance = linker.instantiate(&mut store, &component)?;
+
+    // Lowering as a parameter is written directly into the buffer allocated
+    // by the guest.
+    let bytes = LowerBytes::new(1000, |buf: &mut [u8]| {
+        assert_eq!(buf.len(), 1000);
+        buf.fill(2);
+        Ok(())
+    });
+    let sum = instance.get_typed_func::<(LowerBytes<_>,), (u32,)>(&mut store, "sum")?;
+    assert_eq!(sum.call_and_post_return(&mut store, (bytes,))?, (2000,));
+
+    // Lowering as the result of a host function stores the list in memory.
+    let make = instance.get_typed_func::<(u32,), ()>(&mut store, "make")?;
+    make.call_and_post_return(&mut store, (0,))?;
+    let read = instance.get_typed_func::<(u32,), (WasmList<u8>,)>(&mut store, "read")?;
+    let (list,) = read.call(&mut store, (0,))?;
+    assert_eq!(list.as_le_slice(&store), [1, 2, 3, 4, 5]);
+    read.post_return(&mut store)?;
+
+    // Errors from the callback are propagated.
+    let bytes = LowerBytes::new(10, |_: &mut [u8]| -> Result<()> {
+        anyhow::bail!("failed to write")
+    });
+    let sum = instance.get_typed_func::<(LowerBytes<_>,), (u32,)>(&mut store, "sum")?;
+    let err = sum.call(&mut store, (bytes,)).unwrap_err();
+    assert!(err.to_string().contains("failed to write"), "{err:?}");
+
+    Ok(())
+}
+
 #[test]
 fn lower_then_lift() -> Result<()> {
     // First test simple integers when the import/export ABI happen to line up
@@ -2403,3 +2496,110 @@ fn run_export_with_internal_adapter() -> Result<()> {
     assert_eq!(run.call(&mut store, ())?, (5,));
     Ok(())
 }
+
+#[test]
+fn call_with_options() -> Result<()> {
+    let component = r#"
+(component
+  (core module $m
+    (func (export "spin") (param $n i32)
+      (loop $l
+        (local.set $n (i32.sub (local.get $n) (i32.const 1)))
+        (br_if $l (local.get $n))
+      )
+    )
+    (func (export "trap") unreachable)
+  )
+  (core instance $i (instantiate $m))
+  (func (export "spin") (param "n" u32)
+    (canon lift (core func $i "spin"))
+  )
+  (func (export "trap")
+    (canon lift (core func $i "trap"))
+  )
+)
+    "#;
+
+    let mut config = component_test_util::config();
+    config.consume_fuel(true);
+    let engine = Engine::new(&config)?;
+    let component = Component::new(&engine, component)?;
+    let mut store = Store::new(&engine, ());
+    store.set_fuel(100_000)?;
+    let linker = Linker::new(&engine);
+
+    // The call's fuel is reported and deducted from the outer budget, both
+    // for typed and untyped functions.
+    let instance = linker.instantiate(&mut store, &component)?;
+    let spin = instance.get_typed_func::<(u32,), ()>(&mut store, "spin")?;
+    let fuel = store.get_fuel()?;
+    let ((), report) =
+        spin.call_with_options(&mut store, (100,), CallOptions::new().fuel(10_000))?;
+    spin.post_return(&mut store)?;
+    assert!(report.fuel_consumed() > 100, "{report:?}");
+    assert_eq!(store.get_fuel()?, fuel - report.fuel_consumed());
+
+    let func = instance.get_func(&mut store, "spin").unwrap();
+    let report2 = func.call_with_options(
+        &mut store,
+        &[Val::U32(100)],
+        &mut [],
+        CallOptions::new().fuel(10_000),
+    )?;
+    func.post_return(&mut store)?;
+    assert_eq!(report2.fuel_consumed(), report.fuel_consumed());
+    assert_eq!(store.get_fuel()?, fuel - 2 * report.fuel_consumed());
+
+    // Without a budget the call consumes the store's fuel.
+    let ((), report3) = spin.call_with_options(&mut store, (100,), &CallOptions::new())?;
+    spin.post_return(&mut store)?;
+    assert_eq!(report3.fuel_consumed(), report.fuel_consumed());
+    assert_eq!(store.get_fuel()?, fuel - 3 * report.fuel_consumed());
+
+    // Running out of the call's budget traps, and only that budget is
+    // deducted from the outer one. The trap poisons the instance, so each case
+    // uses a new one.
+    store.set_fuel(100_000)?;
+    let err = spin
+        .call_with_options(&mut store, (1_000,), CallOptions::new().fuel(100))
+        .unwrap_err();
+    assert_eq!(err.downcast::<Trap>()?, Trap::OutOfFuel);
+    assert_eq!(store.get_fuel()?, 99_900);
+
+    let instance = linker.instantiate(&mut store, &component)?;
+    let func = instance.get_func(&mut store, "spin").unwrap();
+    let fuel = store.get_fuel()?;
+    let err = func
+        .call_with_options(
+            &mut store,
+            &[Val::U32(1_000)],
+            &mut [],
+            CallOptions::new().fuel(100),
+        )
+        .unwrap_err();
+    assert_eq!(err.downcast::<Trap>()?, Trap::OutOfFuel);
+    assert_eq!(store.get_fuel()?, fuel - 100);
+
+    // Other traps also deduct no more than the call's budget.
+    let instance = linker.instantiate(&mut store, &component)?;
+    let trap = instance.get_typed_func::<(), ()>(&mut store, "trap")?;
+    let fuel = store.get_fuel()?;
+    let err = trap
+        .call_with_options(&mut store, (), CallOptions::new().fuel(10_000))
+        .unwrap_err();
+    assert_eq!(err.downcast::<Trap>()?, Trap::UnreachableCodeReached);
+    let remaining = store.get_fuel()?;
+    assert!((fuel - 10_000..=fuel).contains(&remaining), "{remaining}");
+
+    let instance = linker.instantiate(&mut store, &component)?;
+    let func = instance.get_func(&mut store, "trap").unwrap();
+    let fuel = store.get_fuel()?;
+    let err = func
+        .call_with_options(&mut store, &[], &mut [], CallOptions::new().fuel(10_000))
+        .unwrap_err();
+    assert_eq!(err.downcast::<Trap>()?, Trap::UnreachableCodeReached);
+    let remaining = store.get_fuel()?;
+    assert!((fuel - 10_000..=fuel).contains(&remaining), "{remaining}");
+
+    Ok(())
+}
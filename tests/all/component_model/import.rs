@@ -985,3 +985,93 @@ fn use_types_across_component_boundaries() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn define_unknown_imports() -> Result<()> {
+    let engine = super::engine();
+    let component = Component::new(
+        &engine,
+        r#"
+            (component
+              (import "test:test/foo" (instance $i
+                (export "r" (type (sub resource)))
+                (export "num" (func (result u32)))
+                (export "flag" (func (result bool)))
+              ))
+              (import "missing" (func $missing))
+              (alias export $i "num" (func $num))
+              (alias export $i "flag" (func $flag))
+              (core func $num_lower (canon lower (func $num)))
+              (core func $flag_lower (canon lower (func $flag)))
+              (core func $missing_lower (canon lower (func $missing)))
+              (core module $m
+                (import "" "num" (func $num (result i32)))
+                (import "" "flag" (func $flag (result i32)))
+                (import "" "missing" (func $missing))
+                (func (export "num") (result i32) call $num)
+                (func (export "flag") (result i32) call $flag)
+                (func (export "missing") call $missing)
+              )
+              (core instance $inst (instantiate $m
+                (with "" (instance
+                  (export "num" (func $num_lower))
+                  (export "flag" (func $flag_lower))
+                  (export "missing" (func $missing_lower))
+                ))
+              ))
+              (func (export "num") (result u32) (canon lift (core func $inst "num")))
+              (func (export "flag") (result bool) (canon lift (core func $inst "flag")))
+              (func (export "missing") (canon lift (core func $inst "missing")))
+            )
+        "#,
+    )?;
+    let mut store = Store::new(&engine, ());
+
+    let err = Linker::new(&engine)
+        .instantiate(&mut store, &component)
+        .unwrap_err();
+    assert!(format!("{err:?}").contains("wrong type"), "{err:?}");
+
+    let call = |store: &mut Store<()>, instance: &Instance, name: &str| -> Result<Vec<Val>> {
+        let func = instance.get_func(&mut *store, name).unwrap();
+        let mut results = vec![Val::Bool(true); func.results(&*store).len()];
+        func.call(&mut *store, &[], &mut results)?;
+        func.post_return(&mut *store)?;
+        Ok(results)
+    };
+
+    // Only functions which aren't defined are stubbed.
+    let mut linker = Linker::new(&engine);
+    linker
+        .instance("test:test/foo")?
+        .func_wrap("num", |_, (): ()| Ok((7u32,)))?;
+    linker.define_unknown_imports_as_default_values(&component)?;
+    let instance = linker.instantiate(&mut store, &component)?;
+    assert!(matches!(
+        call(&mut store, &instance, "num")?[..],
+        [Val::U32(7)]
+    ));
+    assert!(matches!(
+        call(&mut store, &instance, "flag")?[..],
+        [Val::Bool(false)]
+    ));
+    assert!(call(&mut store, &instance, "missing")?.is_empty());
+
+    let mut linker = Linker::new(&engine);
+    linker.define_unknown_imports_as_traps(&component)?;
+    let instance = linker.instantiate(&mut store, &component)?;
+    let err = call(&mut store, &instance, "num").unwrap_err();
+    assert!(
+        format!("{err:?}").contains("unknown import: `test:test/foo#num` has not been defined"),
+        "{err:?}"
+    );
+    // Traps poison the instance, so use a new one.
+    let instance = linker.instantiate(&mut store, &component)?;
+    let err = call(&mut store, &instance, "missing").unwrap_err();
+    assert!(
+        format!("{err:?}").contains("unknown import: `missing` has not been defined"),
+        "{err:?}"
+    );
+
+    Ok(())
+}
@@ -55,3 +55,316 @@ fn instance_exports() -> Result<()> {
 
     Ok(())
 }
+
+const LINK_PROVIDER: &str = r#"
+    (component
+      (core module $m
+        (global $drops (mut i32) i32.const 0)
+        (global $last-drop (mut i32) i32.const 0)
+        (func (export "dtor") (param i32)
+          (global.set $drops (i32.add (global.get $drops) (i32.const 1)))
+          (global.set $last-drop (local.get 0)))
+        (func (export "drops") (result i32) global.get $drops)
+        (func (export "last-drop") (result i32) global.get $last-drop)
+        (func (export "add") (param i32 i32) (result i32)
+          (i32.add (local.get 0) (local.get 1)))
+        (func (export "rep") (param i32) (result i32) local.get 0)
+      )
+      (core instance $i (instantiate $m))
+      (type $r (resource (rep i32) (dtor (func $i "dtor"))))
+      (core func $ctor (canon resource.new $r))
+      (func $ctor (param "rep" u32) (result (own $r))
+        (canon lift (core func $ctor)))
+      (func $rep (param "self" (borrow $r)) (result u32)
+        (canon lift (core func $i "rep")))
+      (func $add (param "a" u32) (param "b" u32) (result u32)
+        (canon lift (core func $i "add")))
+      (component $shim
+        (import "import-type-r" (type $r (sub resource)))
+        (import "import-ctor" (func $ctor (param "rep" u32) (result (own $r))))
+        (import "import-rep" (func $rep (param "self" (borrow $r)) (result u32)))
+        (import "import-add" (func $add (param "a" u32) (param "b" u32) (result u32)))
+        (export $r2 "r" (type $r))
+        (export "[constructor]r" (func $ctor)
+          (func (param "rep" u32) (result (own $r2))))
+        (export "[method]r.rep" (func $rep)
+          (func (param "self" (borrow $r2)) (result u32)))
+        (export "add" (func $add))
+      )
+      (instance $foo (instantiate $shim
+        (with "import-type-r" (type $r))
+        (with "import-ctor" (func $ctor))
+        (with "import-rep" (func $rep))
+        (with "import-add" (func $add))
+      ))
+      (export "test:test/foo" (instance $foo))
+      (func (export "drops") (result u32) (canon lift (core func $i "drops")))
+      (func (export "last-drop") (result u32) (canon lift (core func $i "last-drop")))
+    )
+"#;
+const LINK_CONSUMER: &str = r#"
+    (component
+      (import "test:test/foo" (instance $foo
+        (export "r" (type $r (sub resource)))
+        (export "[constructor]r" (func (param "rep" u32) (result (own $r))))
+        (export "[method]r.rep" (func (param "self" (borrow $r)) (result u32)))
+        (export "add" (func (param "a" u32) (param "b" u32) (result u32)))
+      ))
+      (alias export $foo "r" (type $r))
+      (core func $add (canon lower (func $foo "add")))
+      (core func $ctor (canon lower (func $foo "[constructor]r")))
+      (core func $rep (canon lower (func $foo "[method]r.rep")))
+      (core func $drop (canon resource.drop $r))
+      (core module $m
+        (import "" "add" (func $add (param i32 i32) (result i32)))
+        (import "" "ctor" (func $ctor (param i32) (result i32)))
+        (import "" "rep" (func $rep (param i32) (result i32)))
+        (import "" "drop" (func $drop (param i32)))
+        (func (export "run") (result i32)
+          (local $r i32)
+          (local $ret i32)
+          (local.set $r (call $ctor (i32.const 42)))
+          (local.set $ret (call $add (call $rep (local.get $r)) (i32.const 100)))
+          (call $drop (local.get $r))
+          local.get $ret)
+      )
+      (core instance $i (instantiate $m
+        (with "" (instance
+          (export "add" (func $add))
+          (export "ctor" (func $ctor))
+          (export "rep" (func $rep))
+          (export "drop" (func $drop))
+        ))
+      ))
+      (func (export "run") (result u32) (canon lift (core func $i "run")))
+    )
+"#;
+
+#[test]
+fn link_instance_exports() -> Result<()> {
+    let engine = super::engine();
+    let provider = Component::new(&engine, LINK_PROVIDER)?;
+    let consumer = Component::new(&engine, LINK_CONSUMER)?;
+    let mut store = Store::new(&engine, ());
+
+    let linker = Linker::new(&engine);
+    let provider = linker.instantiate(&mut store, &provider)?;
+    let drops = provider.get_typed_func::<(), (u32,)>(&mut store, "drops")?;
+    let last_drop = provider.get_typed_func::<(), (u32,)>(&mut store, "last-drop")?;
+
+    let mut linker = Linker::new(&engine);
+    linker
+        .root()
+        .instance_exports(&mut store, &provider, "test:test/foo")?;
+    let consumer = linker.instantiate(&mut store, &consumer)?;
+    let run = consumer.get_typed_func::<(), (u32,)>(&mut store, "run")?;
+    assert_eq!(run.call(&mut store, ())?, (142,));
+    run.post_return(&mut store)?;
+
+    // Dropping the resource in the consumer ran the provider's destructor.
+    assert_eq!(drops.call(&mut store, ())?, (1,));
+    drops.post_return(&mut store)?;
+    assert_eq!(last_drop.call(&mut store, ())?, (42,));
+    last_drop.post_return(&mut store)?;
+
+    // Exports are typechecked structurally against imports.
+    let mismatch = Component::new(
+        &engine,
+        r#"
+            (component
+                (import "test:test/foo" (instance
+                    (export "add" (func (param "a" string) (result u32)))
+                ))
+            )
+        "#,
+    )?;
+    assert!(linker.instantiate(&mut store, &mismatch).is_err());
+
+    // Only exported instances can be linked.
+    let mut linker = Linker::new(&engine);
+    assert!(linker
+        .root()
+        .instance_exports(&mut store, &provider, "drops")
+        .is_err());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn link_instance_exports_async() -> Result<()> {
+    let engine = super::async_engine();
+    let provider = Component::new(&engine, LINK_PROVIDER)?;
+    let consumer = Component::new(&engine, LINK_CONSUMER)?;
+    let mut store = Store::new(&engine, ());
+
+    let linker = Linker::new(&engine);
+    let provider = linker.instantiate_async(&mut store, &provider).await?;
+    let drops = provider.get_typed_func::<(), (u32,)>(&mut store, "drops")?;
+
+    let mut linker = Linker::new(&engine);
+    linker
+        .root()
+        .instance_exports(&mut store, &provider, "test:test/foo")?;
+    let consumer = linker.instantiate_async(&mut store, &consumer).await?;
+    let run = consumer.get_typed_func::<(), (u32,)>(&mut store, "run")?;
+    assert_eq!(run.call_async(&mut store, ()).await?, (142,));
+    run.post_return_async(&mut store).await?;
+
+    assert_eq!(drops.call_async(&mut store, ()).await?, (1,));
+    drops.post_return_async(&mut store).await?;
+
+    Ok(())
+}
+
+const STRINGS_PROVIDER: &str = r#"
+    (component
+      (core module $m
+        (memory (export "memory") 1)
+        (global $heap (mut i32) i32.const 1024)
+        (func $realloc (export "realloc") (param i32 i32 i32 i32) (result i32)
+          (local $ret i32)
+          (local.set $ret
+            (i32.and
+              (i32.add (global.get $heap) (i32.sub (local.get 2) (i32.const 1)))
+              (i32.sub (i32.const 0) (local.get 2))))
+          (global.set $heap (i32.add (local.get $ret) (local.get 3)))
+          local.get $ret)
+        (func (export "concat") (param $a i32) (param $alen i32) (param $b i32) (param $blen i32)
+          (result i32)
+          (local $ptr i32)
+          (local.set $ptr
+            (call $realloc (i32.const 0) (i32.const 0) (i32.const 1)
+              (i32.add (local.get $alen) (local.get $blen))))
+          (memory.copy (local.get $ptr) (local.get $a) (local.get $alen))
+          (memory.copy (i32.add (local.get $ptr) (local.get $alen)) (local.get $b) (local.get $blen))
+          (i32.store (i32.const 8) (local.get $ptr))
+          (i32.store (i32.const 12) (i32.add (local.get $alen) (local.get $blen)))
+          i32.const 8)
+        (func (export "sum") (param $ptr i32) (param $len i32) (result i64)
+          (local $sum i64)
+          (block $done
+            (loop $loop
+              (br_if $done (i32.eqz (local.get $len)))
+              (local.set $sum
+                (i64.add (local.get $sum) (i64.extend_i32_u (i32.load (local.get $ptr)))))
+              (local.set $ptr (i32.add (local.get $ptr) (i32.const 4)))
+              (local.set $len (i32.sub (local.get $len) (i32.const 1)))
+              (br $loop)))
+          local.get $sum)
+        (func (export "or-zero") (param i32 i32) (result i32)
+          (select (local.get 1) (i32.const 0) (local.get 0)))
+      )
+      (core instance $i (instantiate $m))
+      (func $concat (param "a" string) (param "b" string) (result string)
+        (canon lift (core func $i "concat") (memory $i "memory") (realloc (func $i "realloc"))))
+      (func $sum (param "l" (list u32)) (result u64)
+        (canon lift (core func $i "sum") (memory $i "memory") (realloc (func $i "realloc"))))
+      (func $or-zero (param "x" (option u32)) (result u32)
+        (canon lift (core func $i "or-zero")))
+      (instance $strings
+        (export "concat" (func $concat))
+        (export "sum" (func $sum))
+        (export "or-zero" (func $or-zero))
+      )
+      (export "test:test/strings" (instance $strings))
+    )
+"#;
+const STRINGS_CONSUMER: &str = r#"
+    (component
+      (import "test:test/strings" (instance $s
+        (export "concat" (func (param "a" string) (param "b" string) (result string)))
+        (export "sum" (func (param "l" (list u32)) (result u64)))
+        (export "or-zero" (func (param "x" (option u32)) (result u32)))
+      ))
+      (core module $libc
+        (memory (export "memory") 1)
+        (global $heap (mut i32) i32.const 1024)
+        (func (export "realloc") (param i32 i32 i32 i32) (result i32)
+          (local $ret i32)
+          (local.set $ret
+            (i32.and
+              (i32.add (global.get $heap) (i32.sub (local.get 2) (i32.const 1)))
+              (i32.sub (i32.const 0) (local.get 2))))
+          (global.set $heap (i32.add (local.get $ret) (local.get 3)))
+          local.get $ret)
+      )
+      (core instance $libc (instantiate $libc))
+      (core func $concat (canon lower (func $s "concat")
+        (memory $libc "memory") (realloc (func $libc "realloc"))))
+      (core func $sum (canon lower (func $s "sum") (memory $libc "memory")))
+      (core func $or-zero (canon lower (func $s "or-zero")))
+      (core module $m
+        (import "libc" "memory" (memory 1))
+        (import "" "concat" (func $concat (param i32 i32 i32 i32 i32)))
+        (import "" "sum" (func $sum (param i32 i32) (result i64)))
+        (import "" "or-zero" (func $or-zero (param i32 i32) (result i32)))
+        (data (i32.const 16) "hello, ")
+        (data (i32.const 32) "world")
+        (data (i32.const 48) "\01\00\00\00\02\00\00\00\03\00\00\00\04\00\00\00")
+        (data (i32.const 64) "\ff")
+        (func (export "greet") (result i32)
+          (call $concat (i32.const 16) (i32.const 7) (i32.const 32) (i32.const 5) (i32.const 8))
+          i32.const 8)
+        (func (export "greet-invalid") (result i32)
+          (call $concat (i32.const 64) (i32.const 1) (i32.const 32) (i32.const 5) (i32.const 8))
+          i32.const 8)
+        (func (export "sum") (result i64)
+          (call $sum (i32.const 48) (i32.const 4)))
+        (func (export "or-zero") (result i32)
+          (i32.add
+            (call $or-zero (i32.const 1) (i32.const 5))
+            (call $or-zero (i32.const 0) (i32.const 7))))
+      )
+      (core instance $i (instantiate $m
+        (with "libc" (instance $libc))
+        (with "" (instance
+          (export "concat" (func $concat))
+          (export "sum" (func $sum))
+          (export "or-zero" (func $or-zero))
+        ))
+      ))
+      (func (export "greet") (result string)
+        (canon lift (core func $i "greet") (memory $libc "memory")))
+      (func (export "greet-invalid") (result string)
+        (canon lift (core func $i "greet-invalid") (memory $libc "memory")))
+      (func (export "sum") (result u64) (canon lift (core func $i "sum")))
+      (func (export "or-zero") (result u32) (canon lift (core func $i "or-zero")))
+    )
+"#;
+
+#[test]
+fn link_instance_exports_strings_and_lists() -> Result<()> {
+    let engine = super::engine();
+    let provider = Component::new(&engine, STRINGS_PROVIDER)?;
+    let consumer = Component::new(&engine, STRINGS_CONSUMER)?;
+    let mut store = Store::new(&engine, ());
+
+    let linker = Linker::new(&engine);
+    let provider = linker.instantiate(&mut store, &provider)?;
+
+    let mut linker = Linker::new(&engine);
+    linker
+        .root()
+        .instance_exports(&mut store, &provider, "test:test/strings")?;
+    let consumer = linker.instantiate(&mut store, &consumer)?;
+
+    // Strings and lists are copied between the two memories, both ways.
+    let greet = consumer.get_typed_func::<(), (String,)>(&mut store, "greet")?;
+    assert_eq!(greet.call(&mut store, ())?.0, "hello, world");
+    greet.post_return(&mut store)?;
+
+    let sum = consumer.get_typed_func::<(), (u64,)>(&mut store, "sum")?;
+    assert_eq!(sum.call(&mut store, ())?, (10,));
+    sum.post_return(&mut store)?;
+
+    // Options go through `Val`s instead.
+    let or_zero = consumer.get_typed_func::<(), (u32,)>(&mut store, "or-zero")?;
+    assert_eq!(or_zero.call(&mut store, ())?, (5,));
+    or_zero.post_return(&mut store)?;
+
+    // Strings are validated as they're copied.
+    let invalid = consumer.get_typed_func::<(), (String,)>(&mut store, "greet-invalid")?;
+    assert!(invalid.call(&mut store, ()).is_err());
+
+    Ok(())
+}
@@ -1,8 +1,9 @@
 #![cfg(not(miri))]
 
 use anyhow::Result;
+use std::sync::{Arc, Mutex};
 use wasmtime::component::*;
-use wasmtime::{Store, Trap};
+use wasmtime::{Store, StoreLimitsBuilder, Trap};
 
 #[test]
 fn host_resource_types() -> Result<()> {
@@ -1472,3 +1473,148 @@ fn guest_different_host_same() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn resource_handle_limit() -> Result<()> {
+    let engine = super::engine();
+    let c = Component::new(
+        &engine,
+        r#"
+            (component
+              (type $r' (resource (rep i32)))
+              (export $r "r" (type $r'))
+              (core func $new (canon resource.new $r))
+              (core module $m
+                (import "" "new" (func $new (param i32) (result i32)))
+                (func (export "make") (param i32)
+                  (local $i i32)
+                  (loop $l
+                    (if (i32.lt_u (local.get $i) (local.get 0))
+                      (then
+                        (drop (call $new (local.get $i)))
+                        (local.set $i (i32.add (local.get $i) (i32.const 1)))
+                        (br $l)))))
+              )
+              (core instance $i (instantiate $m
+                (with "" (instance (export "new" (func $new))))
+              ))
+              (func (export "make") (param "n" u32)
+                (canon lift (core func $i "make")))
+              (func (export "[constructor]r") (param "rep" u32) (result (own $r))
+                (canon lift (core func $new)))
+            )
+        "#,
+    )?;
+
+    let limits = StoreLimitsBuilder::new().resource_handles(10).build();
+    let mut store = Store::new(&engine, limits);
+    store.limiter(|limits| limits);
+    let linker = Linker::new(&engine);
+
+    // Handles created by the guest count against the guest's table.
+    let i = linker.instantiate(&mut store, &c)?;
+    let make = i.get_typed_func::<(u32,), ()>(&mut store, "make")?;
+    make.call(&mut store, (10,))?;
+    make.post_return(&mut store)?;
+    let err = make.call(&mut store, (1,)).unwrap_err();
+    assert!(
+        format!("{err:?}").contains("resource table limit exceeded"),
+        "bad error: {err:?}"
+    );
+
+    // Handles owned by the host count against the host's table.
+    let i = linker.instantiate(&mut store, &c)?;
+    let ctor = i.get_typed_func::<(u32,), (ResourceAny,)>(&mut store, "[constructor]r")?;
+    let mut handles = Vec::new();
+    for rep in 0..10 {
+        let (r,) = ctor.call(&mut store, (rep,))?;
+        ctor.post_return(&mut store)?;
+        handles.push(r);
+    }
+    let err = ctor.call(&mut store, (10,)).unwrap_err();
+    assert!(
+        format!("{err:?}").contains("resource table limit exceeded"),
+        "bad error: {err:?}"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn resource_leak_handler() -> Result<()> {
+    let engine = super::engine();
+    let c = Component::new(
+        &engine,
+        r#"
+            (component
+              (import "t" (type $t (sub resource)))
+              (import "[constructor]t" (func $ctor (result (own $t))))
+              (type $r (resource (rep i32)))
+
+              (core func $ctor (canon lower (func $ctor)))
+              (core func $new (canon resource.new $r))
+              (core module $m
+                (import "" "ctor" (func $ctor (result i32)))
+                (import "" "new" (func $new (param i32) (result i32)))
+                (func (export "leak")
+                  (drop (call $ctor))
+                  (drop (call $new (i32.const 7))))
+              )
+              (core instance $i (instantiate $m
+                (with "" (instance
+                  (export "ctor" (func $ctor))
+                  (export "new" (func $new))
+                ))
+              ))
+              (func (export "leak") (canon lift (core func $i "leak")))
+            )
+        "#,
+    )?;
+
+    struct MyType;
+
+    let mut linker = Linker::new(&engine);
+    linker
+        .root()
+        .resource("t", ResourceType::host::<MyType>(), |_, _| Ok(()))?;
+    linker.root().func_wrap("[constructor]t", |_cx, ()| {
+        Ok((Resource::<MyType>::new_own(300),))
+    })?;
+
+    // Handles left in the guest's tables are reported when the store is
+    // dropped.
+    let leaks = Arc::new(Mutex::new(Vec::new()));
+    let mut store = Store::new(&engine, ());
+    let reported = leaks.clone();
+    store.resource_leak_handler(move |_, leaks| {
+        reported.lock().unwrap().extend_from_slice(leaks);
+    });
+    let i = linker.instantiate(&mut store, &c)?;
+    let leak = i.get_typed_func::<(), ()>(&mut store, "leak")?;
+    leak.call(&mut store, ())?;
+    leak.post_return(&mut store)?;
+    drop(store);
+
+    let leaks = leaks.lock().unwrap();
+    assert_eq!(leaks.len(), 2);
+    let host = leaks
+        .iter()
+        .find(|l| l.ty() == ResourceType::host::<MyType>())
+        .unwrap();
+    assert_eq!(host.rep(), 300);
+    assert_eq!(host.func(), Some("[constructor]t"));
+    let guest = leaks
+        .iter()
+        .find(|l| l.ty() != ResourceType::host::<MyType>())
+        .unwrap();
+    assert_eq!(guest.rep(), 7);
+    assert_eq!(guest.func(), Some("leak"));
+
+    // The handler isn't invoked if nothing was leaked.
+    let mut store = Store::new(&engine, ());
+    store.resource_leak_handler(|_, _| panic!("no resources were leaked"));
+    linker.instantiate(&mut store, &c)?;
+    drop(store);
+
+    Ok(())
+}
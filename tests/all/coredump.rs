@@ -258,3 +258,73 @@ fn multiple_globals_memories_and_instances() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+#[cfg_attr(miri, ignore)]
+// Values held in registers are only recovered where the registers of the
+// trapping frame are read from the signal context.
+#[cfg(all(
+    target_os = "linux",
+    any(
+        target_arch = "x86_64",
+        target_arch = "aarch64",
+        target_arch = "riscv64"
+    )
+))]
+fn coredump_has_locals_and_operand_stack() -> Result<()> {
+    let mut config = Config::default();
+    config.coredump_on_trap(true);
+    let engine = Engine::new(&config).unwrap();
+    let mut store = Store::<()>::new(&engine, ());
+
+    let wat = r#"
+      (module
+          (func $a (export "a") (param i32 i64) (result i32)
+              local.get 0
+              local.get 0
+              call $b
+              i32.add
+              local.get 1
+              i32.wrap_i64
+              i32.add
+          )
+          (func $b (param i32) (result i32)
+              local.get 0
+              i32.const 0
+              i32.div_u
+          )
+      )
+    "#;
+
+    let module = Module::new(store.engine(), wat)?;
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let a_func = instance.get_typed_func::<(i32, i64), i32>(&mut store, "a")?;
+
+    let e = a_func.call(&mut store, (42, 7)).unwrap_err();
+    let cd = e.downcast_ref::<WasmCoreDump>().unwrap();
+    assert_eq!(cd.frames().len(), 2);
+    assert_eq!(cd.locals().len(), 2);
+    assert_eq!(cd.operand_stacks().len(), 2);
+
+    let i32s = |values: &[Option<Val>]| {
+        values
+            .iter()
+            .map(|v| v.as_ref().and_then(|v| v.i32()))
+            .collect::<Vec<_>>()
+    };
+
+    // `$b` trapped in `i32.div_u` with its operands on the stack.
+    assert_eq!(i32s(&cd.locals()[0]), [Some(42)]);
+    assert_eq!(i32s(&cd.operand_stacks()[0]), [Some(42), Some(0)]);
+
+    // `$a` is in the middle of calling `$b`, with the call's argument still
+    // on the stack.
+    let locals = &cd.locals()[1];
+    assert_eq!(locals.len(), 2);
+    assert_eq!(locals[0].as_ref().and_then(|v| v.i32()), Some(42));
+    assert_eq!(locals[1].as_ref().and_then(|v| v.i64()), Some(7));
+    assert_eq!(i32s(&cd.operand_stacks()[1]), [Some(42), Some(42)]);
+
+    let _ = cd.serialize(&mut store, "locals");
+    Ok(())
+}
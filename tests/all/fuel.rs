@@ -257,3 +257,146 @@ fn get_fuel_clamps_at_zero() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn fuel_callback() -> Result<()> {
+    let engine = Engine::new(Config::new().consume_fuel(true))?;
+    let module = Module::new(
+        &engine,
+        r#"
+(module
+  (func (export "spin") (param $n i32)
+    (loop $l
+      (local.set $n (i32.sub (local.get $n) (i32.const 1)))
+      (br_if $l (local.get $n))
+    )
+  )
+)
+        "#,
+    )?;
+
+    // Records the remaining fuel each time the callback is invoked.
+    let run = |interval: u64| -> Result<Vec<u64>> {
+        let mut store = Store::new(&engine, Vec::new());
+        store.set_fuel(100_000)?;
+        store.fuel_callback(interval, |mut store| {
+            let fuel = store.get_fuel()?;
+            store.data_mut().push(fuel);
+            Ok(())
+        })?;
+        let instance = Instance::new(&mut store, &module, &[])?;
+        let spin = instance.get_typed_func::<i32, ()>(&mut store, "spin")?;
+        spin.call(&mut store, 1_000)?;
+        Ok(store.into_data())
+    };
+
+    let samples = run(500)?;
+    assert!(samples.len() > 5, "{samples:?}");
+    for pair in samples.windows(2) {
+        let consumed = pair[0] - pair[1];
+        assert!(consumed >= 500 && consumed < 600, "{samples:?}");
+    }
+
+    // The callback is deterministic.
+    assert_eq!(run(500)?, samples);
+
+    // Errors from the callback trap.
+    let mut store = Store::new(&engine, ());
+    store.set_fuel(100_000)?;
+    store.fuel_callback(500, |_| anyhow::bail!("stop"))?;
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let spin = instance.get_typed_func::<i32, ()>(&mut store, "spin")?;
+    let err = spin.call(&mut store, 1_000).unwrap_err();
+    assert!(format!("{err:?}").contains("stop"), "{err:?}");
+
+    assert!(store.fuel_callback(0, |_| Ok(())).is_err());
+    let mut store = Store::new(&Engine::default(), ());
+    assert!(store.fuel_callback(500, |_| Ok(())).is_err());
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn call_with_options() -> Result<()> {
+    let wat = r#"
+(module
+  (func (export "spin") (param $n i32)
+    (loop $l
+      (local.set $n (i32.sub (local.get $n) (i32.const 1)))
+      (br_if $l (local.get $n))
+    )
+  )
+)
+    "#;
+    let engine = Engine::new(Config::new().consume_fuel(true))?;
+    let module = Module::new(&engine, wat)?;
+    let mut store = Store::new(&engine, ());
+    store.set_fuel(100_000)?;
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let spin = instance.get_typed_func::<i32, ()>(&mut store, "spin")?;
+
+    // The call's fuel is reported and deducted from the outer budget.
+    let ((), report) = spin.call_with_options(&mut store, 100, CallOptions::new().fuel(10_000))?;
+    let consumed = report.fuel_consumed();
+    assert!(consumed > 100, "{report:?}");
+    assert_eq!(store.get_fuel()?, 100_000 - consumed);
+
+    // Without a budget the call consumes the store's fuel.
+    let ((), report2) = spin.call_with_options(&mut store, 100, &CallOptions::new())?;
+    assert_eq!(report2.fuel_consumed(), consumed);
+    assert_eq!(store.get_fuel()?, 100_000 - 2 * consumed);
+
+    // Running out of the call's budget traps, and only that budget is
+    // deducted from the outer one.
+    store.set_fuel(100_000)?;
+    let err = spin
+        .call_with_options(&mut store, 1_000, CallOptions::new().fuel(100))
+        .unwrap_err();
+    assert_eq!(err.downcast::<Trap>()?, Trap::OutOfFuel);
+    assert_eq!(store.get_fuel()?, 99_900);
+
+    // The same works with untyped functions.
+    let func = instance.get_func(&mut store, "spin").unwrap();
+    let report3 = func.call_with_options(
+        &mut store,
+        &[Val::I32(100)],
+        &mut [],
+        CallOptions::new().fuel(10_000),
+    )?;
+    assert_eq!(report3.fuel_consumed(), consumed);
+    assert_eq!(store.get_fuel()?, 99_900 - consumed);
+
+    // A budget larger than the store's fuel leaves it empty once the call
+    // has consumed more than it had.
+    store.set_fuel(1)?;
+    let ((), report4) = spin.call_with_options(&mut store, 100, CallOptions::new().fuel(10_000))?;
+    assert_eq!(report4.fuel_consumed(), consumed);
+    assert_eq!(store.get_fuel()?, 0);
+
+    // Epoch deadlines require epoch interruption to be enabled.
+    assert!(spin
+        .call_with_options(&mut store, 1, CallOptions::new().epoch_deadline(1))
+        .is_err());
+
+    let engine = Engine::new(Config::new().epoch_interruption(true))?;
+    let module = Module::new(&engine, wat)?;
+    let mut store = Store::new(&engine, ());
+    store.set_epoch_deadline(1);
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let spin = instance.get_typed_func::<i32, ()>(&mut store, "spin")?;
+
+    // A deadline of zero ticks interrupts the call immediately, but the outer
+    // deadline is restored afterwards.
+    let err = spin
+        .call_with_options(&mut store, 100, CallOptions::new().epoch_deadline(0))
+        .unwrap_err();
+    assert_eq!(err.downcast::<Trap>()?, Trap::Interrupt);
+    spin.call(&mut store, 100)?;
+
+    // Fuel budgets require fuel to be enabled.
+    assert!(spin
+        .call_with_options(&mut store, 1, CallOptions::new().fuel(100))
+        .is_err());
+    Ok(())
+}
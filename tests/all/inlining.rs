@@ -0,0 +1,152 @@
+use anyhow::Result;
+use wasmtime::*;
+
+fn engine(inlining: bool, consume_fuel: bool) -> Result<Engine> {
+    let mut config = Config::new();
+    config.cranelift_inlining(inlining);
+    config.consume_fuel(consume_fuel);
+    config.cranelift_debug_verifier(true);
+    Engine::new(&config)
+}
+
+const MODULE: &str = r#"
+    (module
+        (memory 1)
+        (table 1 funcref)
+        (elem (i32.const 0) $add)
+        (global $g (mut i32) (i32.const 10))
+
+        (func $add (param i32 i32) (result i32)
+            (i32.add (local.get 0) (local.get 1)))
+
+        (func $store_and_load (param i32) (result i32)
+            (i32.store (i32.const 8) (local.get 0))
+            (i32.load (i32.const 8)))
+
+        (func $select (param i32) (result i32)
+            (block
+                (block
+                    (br_table 0 1 (local.get 0)))
+                (return (i32.const 100)))
+            (i32.const 200))
+
+        (func $multi (param i32) (result i32 i64)
+            (i32.add (local.get 0) (global.get $g))
+            (i64.const 3))
+
+        (func $indirect (param i32) (result i32)
+            (call_indirect (param i32 i32) (result i32)
+                (local.get 0) (i32.const 1) (i32.const 0)))
+
+        (func $loop (param i32) (result i32) (local i32)
+            (loop $l
+                (local.set 1 (i32.add (local.get 1) (i32.const 1)))
+                (br_if $l (i32.lt_u (local.get 1) (local.get 0))))
+            (local.get 1))
+
+        (func (export "run") (param i32) (result i32)
+            (call $add (local.get 0) (i32.const 1))
+            call $store_and_load
+            call $select
+            (call $select (local.get 0))
+            i32.add
+            call $multi
+            i32.wrap_i64
+            i32.add
+            call $indirect
+            call $loop)
+    )
+"#;
+
+fn run(engine: &Engine, arg: i32) -> Result<(i32, u64)> {
+    let mut store = Store::new(engine, ());
+    // This fails, and fuel is reported as zero below, without `consume_fuel`.
+    let _ = store.set_fuel(u64::MAX);
+    let module = Module::new(engine, MODULE)?;
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let run = instance.get_typed_func::<i32, i32>(&mut store, "run")?;
+    let result = run.call(&mut store, arg)?;
+    let fuel = store.get_fuel().map(|fuel| u64::MAX - fuel).unwrap_or(0);
+    Ok((result, fuel))
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn inlined_results_match() -> Result<()> {
+    let inlined = engine(true, false)?;
+    let not_inlined = engine(false, false)?;
+    for arg in [0, 1, 2, 1000] {
+        assert_eq!(run(&inlined, arg)?, run(&not_inlined, arg)?);
+    }
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn inlined_fuel_matches() -> Result<()> {
+    let inlined = engine(true, true)?;
+    let not_inlined = engine(false, true)?;
+    for arg in [0, 1, 2, 1000] {
+        assert_eq!(run(&inlined, arg)?, run(&not_inlined, arg)?);
+    }
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn inlined_trap_reports_callee_offset() -> Result<()> {
+    let mut store = Store::new(&engine(true, false)?, ());
+    let wat = r#"
+        (module $hello_mod
+            (func (export "run") (call $hello))
+            (func $hello (unreachable))
+        )
+    "#;
+
+    let module = Module::new(store.engine(), wat)?;
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let run_func = instance.get_typed_func::<(), ()>(&mut store, "run")?;
+
+    let e = run_func.call(&mut store, ()).unwrap_err();
+
+    // The inlined `$hello` is recovered from the trapping instruction's offset
+    // and reported above its caller, which points at the inlined call.
+    let trace = e.downcast_ref::<WasmBacktrace>().unwrap().frames();
+    assert_eq!(trace.len(), 2);
+    assert_eq!(trace[0].func_index(), 1);
+    assert_eq!(trace[0].func_name(), Some("hello"));
+    assert_eq!(trace[0].module_offset(), Some(0x26));
+    assert_eq!(trace[1].func_index(), 0);
+    assert_eq!(trace[1].module_offset(), Some(0x21));
+    assert_eq!(e.downcast::<Trap>()?, Trap::UnreachableCodeReached);
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn inlining_preserves_recursion_and_imports() -> Result<()> {
+    let mut store = Store::new(&engine(true, false)?, ());
+    let wat = r#"
+        (module
+            (import "" "double" (func $double (param i32) (result i32)))
+            (func $fact (export "fact") (param i32) (result i32)
+                (if (result i32) (i32.eqz (local.get 0))
+                    (then (i32.const 1))
+                    (else
+                        (i32.mul
+                            (local.get 0)
+                            (call $fact (i32.sub (local.get 0) (i32.const 1)))))))
+            (func (export "run") (param i32) (result i32)
+                (call $double (call $fact (local.get 0))))
+        )
+    "#;
+
+    let module = Module::new(store.engine(), wat)?;
+    let double = Func::wrap(&mut store, |x: i32| x * 2);
+    let instance = Instance::new(&mut store, &module, &[double.into()])?;
+    let run = instance.get_typed_func::<i32, i32>(&mut store, "run")?;
+    assert_eq!(run.call(&mut store, 5)?, 240);
+
+    Ok(())
+}
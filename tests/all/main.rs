@@ -18,6 +18,7 @@ mod host_funcs;
 mod iloop;
 mod import_calling_export;
 mod import_indexes;
+mod inlining;
 mod instance;
 mod invoke_func_via_table;
 mod limits;
@@ -39,8 +40,8 @@ mod traps;
 mod wait_notify;
 mod wasi_testsuite;
 mod wast;
-// Currently Winch is only supported in x86_64.
-#[cfg(all(target_arch = "x86_64"))]
+// Currently Winch is only supported in x86_64 and aarch64.
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
 mod winch;
 
 /// A helper to compile a module in a new store with reference types enabled.
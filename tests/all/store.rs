@@ -20,3 +20,49 @@ fn into_inner() {
     Store::new(&engine, A).into_data();
     assert_eq!(HITS.load(SeqCst), 2);
 }
+
+#[test]
+fn snapshot_and_restore() -> anyhow::Result<()> {
+    let engine = Engine::default();
+    let module = wasmtime::Module::new(
+        &engine,
+        r#"
+            (module
+                (memory (export "memory") 1)
+                (global (export "g") (mut i32) (i32.const 1))
+                (global (export "c") i32 (i32.const 2))
+                (func (export "bump")
+                    (global.set 0 (i32.add (global.get 0) (i32.const 1)))
+                    (i32.store (i32.const 100) (global.get 0))))
+        "#,
+    )?;
+    let mut store = Store::new(&engine, ());
+    let instance = wasmtime::Instance::new(&mut store, &module, &[])?;
+    let memory = instance.get_memory(&mut store, "memory").unwrap();
+    let g = instance.get_global(&mut store, "g").unwrap();
+    let bump = instance.get_typed_func::<(), ()>(&mut store, "bump")?;
+
+    let snapshot = store.snapshot();
+    assert_eq!(snapshot.memory_size(), 65536);
+
+    bump.call(&mut store, ())?;
+    bump.call(&mut store, ())?;
+    assert_eq!(g.get(&mut store).unwrap_i32(), 3);
+    assert_eq!(memory.data(&store)[100], 3);
+
+    store.restore(&snapshot)?;
+    assert_eq!(g.get(&mut store).unwrap_i32(), 1);
+    assert_eq!(memory.data(&store)[100], 0);
+
+    // Restoring can be repeated.
+    bump.call(&mut store, ())?;
+    store.restore(&snapshot)?;
+    assert_eq!(g.get(&mut store).unwrap_i32(), 1);
+
+    // Grown memories can't be restored, and nothing is changed.
+    bump.call(&mut store, ())?;
+    memory.grow(&mut store, 1)?;
+    assert!(store.restore(&snapshot).is_err());
+    assert_eq!(g.get(&mut store).unwrap_i32(), 2);
+    Ok(())
+}
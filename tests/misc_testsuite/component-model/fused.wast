@@ -1420,3 +1420,120 @@
   (instance $c1 (instantiate $c (with "backend" (instance $root))))
   (instance $c2 (instantiate $c (with "backend" (instance $c1))))
 )
+
+;; lists of scalars are copied between memories
+(component
+  (core module $m
+    (memory (export "memory") 1)
+    (global $next (mut i32) (i32.const 1000))
+    (func (export "realloc") (param i32 i32) (param $align i32) (param $size i32) (result i32)
+      (local $ret i32)
+      (local.set $ret
+        (i32.and
+          (i32.add (global.get $next) (i32.sub (local.get $align) (i32.const 1)))
+          (i32.sub (i32.const 0) (local.get $align))))
+      (global.set $next (i32.add (local.get $ret) (local.get $size)))
+      local.get $ret)
+    (func (export "take") (param $a i32) (param $alen i32) (param $b i32) (param $blen i32)
+      (if (i32.ne (local.get $a) (i32.const 1000)) (then (unreachable)))
+      (if (i32.ne (local.get $alen) (i32.const 3)) (then (unreachable)))
+      (if (i32.ne (i32.load16_u offset=0 (local.get $a)) (i32.const 1)) (then (unreachable)))
+      (if (i32.ne (i32.load16_u offset=2 (local.get $a)) (i32.const 0xffff)) (then (unreachable)))
+      (if (i32.ne (i32.load16_u offset=4 (local.get $a)) (i32.const 3)) (then (unreachable)))
+
+      (if (i32.ne (local.get $b) (i32.const 1008)) (then (unreachable)))
+      (if (i32.ne (local.get $blen) (i32.const 2)) (then (unreachable)))
+      (if (f64.ne (f64.load offset=0 (local.get $b)) (f64.const 1.5)) (then (unreachable)))
+      (if (i64.ne (i64.load offset=8 (local.get $b)) (i64.const 0x7ff4000000000001)) (then (unreachable)))
+    )
+  )
+  (core instance $m (instantiate $m))
+
+  (func $take (param "a" (list u16)) (param "b" (list float64))
+    (canon lift (core func $m "take") (memory $m "memory")
+      (realloc (func $m "realloc")))
+  )
+
+  (component $c
+    (import "take" (func $take (param "a" (list u16)) (param "b" (list float64))))
+
+    (core module $libc
+      (memory (export "memory") 1)
+    )
+    (core instance $libc (instantiate $libc))
+    (core func $take
+      (canon lower (func $take) (memory $libc "memory"))
+    )
+
+    (core module $m2
+      (import "libc" "memory" (memory 1))
+      (import "" "take" (func $take (param i32 i32 i32 i32)))
+
+      (func $start
+        (i32.store16 offset=100 (i32.const 0) (i32.const 1))
+        (i32.store16 offset=102 (i32.const 0) (i32.const 0xffff))
+        (i32.store16 offset=104 (i32.const 0) (i32.const 3))
+        (f64.store offset=200 (i32.const 0) (f64.const 1.5))
+        (i64.store offset=208 (i32.const 0) (i64.const 0x7ff4000000000001))
+        (call $take (i32.const 100) (i32.const 3) (i32.const 200) (i32.const 2))
+      )
+      (start $start)
+    )
+    (core instance $m2 (instantiate $m2
+      (with "libc" (instance $libc))
+      (with "" (instance (export "take" (func $take))))
+    ))
+  )
+
+  (instance $c (instantiate $c
+    (with "take" (func $take))
+  ))
+)
+
+;; out-of-bounds lists of scalars still trap
+(assert_trap
+  (component
+    (core module $m
+      (memory (export "memory") 1)
+      (func (export "realloc") (param i32 i32 i32 i32) (result i32)
+        i32.const 1000)
+      (func (export "take") (param i32 i32))
+    )
+    (core instance $m (instantiate $m))
+
+    (func $take (param "a" (list u32))
+      (canon lift (core func $m "take") (memory $m "memory")
+        (realloc (func $m "realloc")))
+    )
+
+    (component $c
+      (import "take" (func $take (param "a" (list u32))))
+
+      (core module $libc
+        (memory (export "memory") 1)
+      )
+      (core instance $libc (instantiate $libc))
+      (core func $take
+        (canon lower (func $take) (memory $libc "memory"))
+      )
+
+      (core module $m2
+        (import "libc" "memory" (memory 1))
+        (import "" "take" (func $take (param i32 i32)))
+
+        (func $start
+          (call $take (i32.const 65532) (i32.const 2))
+        )
+        (start $start)
+      )
+      (core instance $m2 (instantiate $m2
+        (with "libc" (instance $libc))
+        (with "" (instance (export "take" (func $take))))
+      ))
+    )
+
+    (instance $c (instantiate $c
+      (with "take" (func $take))
+    ))
+  )
+  "unreachable")
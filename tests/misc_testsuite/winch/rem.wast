@@ -0,0 +1,21 @@
+;; Negative results of `i32.rem_s` must be zero-extended in 64-bit registers.
+
+(module
+  (memory 1)
+  (func (export "load-rem") (param i32 i32) (result i32)
+    (i32.load (i32.rem_s (local.get 0) (local.get 1)))
+  )
+  (func (export "extend-rem") (param i32 i32) (result i64)
+    (i64.extend_i32_u (i32.rem_s (local.get 0) (local.get 1)))
+  )
+  (func (export "load-rem-const") (result i32)
+    (i32.load (i32.rem_s (i32.const -7) (i32.const 2)))
+  )
+)
+
+(assert_trap (invoke "load-rem" (i32.const -7) (i32.const 2)) "out of bounds memory access")
+(assert_trap (invoke "load-rem-const") "out of bounds memory access")
+(assert_return (invoke "load-rem" (i32.const 7) (i32.const 4)) (i32.const 0))
+(assert_return (invoke "extend-rem" (i32.const -7) (i32.const 2)) (i64.const 0xffffffff))
+(assert_return (invoke "extend-rem" (i32.const -8) (i32.const 3)) (i64.const 0xfffffffe))
+(assert_return (invoke "extend-rem" (i32.const 7) (i32.const -2)) (i64.const 1))
@@ -0,0 +1,65 @@
+(module
+  (type $i64_i64 (func (param i64) (result i64)))
+  (table 2 funcref)
+  (elem (i32.const 0) $even_indirect $odd_indirect)
+
+  (func $fact (export "fact") (param i64 i64) (result i64)
+    (if (result i64) (i64.eqz (local.get 0))
+      (then (local.get 1))
+      (else
+        (return_call $fact
+          (i64.sub (local.get 0) (i64.const 1))
+          (i64.mul (local.get 0) (local.get 1))))))
+
+  ;; Mutual recursion between functions with a different amount of stack
+  ;; arguments.
+  (func $count (export "count") (param i64) (result i64)
+    (if (result i64) (i64.eqz (local.get 0))
+      (then (i64.const 0))
+      (else
+        (return_call $count_stack_args
+          (i64.const 1) (i64.const 2) (i64.const 3) (i64.const 4)
+          (i64.const 5) (i64.const 6) (i64.const 7) (i64.const 8)
+          (i64.sub (local.get 0) (i64.const 1))))))
+
+  (func $count_stack_args
+    (param i64 i64 i64 i64 i64 i64 i64 i64 i64) (result i64)
+    (return_call $count (local.get 8)))
+
+  (func $even_indirect (export "even") (param i64) (result i64)
+    (if (result i64) (i64.eqz (local.get 0))
+      (then (i64.const 44))
+      (else
+        (return_call_indirect (type $i64_i64)
+          (i64.sub (local.get 0) (i64.const 1)) (i32.const 1)))))
+
+  (func $odd_indirect (param i64) (result i64)
+    (if (result i64) (i64.eqz (local.get 0))
+      (then (i64.const 99))
+      (else
+        (return_call_indirect (type $i64_i64)
+          (i64.sub (local.get 0) (i64.const 1)) (i32.const 0)))))
+
+  (func $results (export "results") (param i64) (result i64 i64 i64 i64 i64 i64 i64 i64)
+    (if (result i64 i64 i64 i64 i64 i64 i64 i64) (i64.eqz (local.get 0))
+      (then
+        (i64.const 1) (i64.const 2) (i64.const 3) (i64.const 4)
+        (i64.const 5) (i64.const 6) (i64.const 7) (i64.const 8))
+      (else
+        (return_call $results (i64.sub (local.get 0) (i64.const 1))))))
+
+  (func (export "call_results") (result i64)
+    (call $results (i64.const 1000))
+    (i64.add) (i64.add) (i64.add) (i64.add) (i64.add) (i64.add) (i64.add))
+)
+
+(assert_return (invoke "fact" (i64.const 5) (i64.const 1)) (i64.const 120))
+(assert_return (invoke "fact" (i64.const 1_000_000) (i64.const 1)) (i64.const 0))
+(assert_return (invoke "count" (i64.const 1_000_000)) (i64.const 0))
+(assert_return (invoke "even" (i64.const 1_000_000)) (i64.const 44))
+(assert_return (invoke "even" (i64.const 1_000_001)) (i64.const 99))
+(assert_return
+  (invoke "results" (i64.const 1_000_000))
+  (i64.const 1) (i64.const 2) (i64.const 3) (i64.const 4)
+  (i64.const 5) (i64.const 6) (i64.const 7) (i64.const 8))
+(assert_return (invoke "call_results") (i64.const 36))
@@ -0,0 +1,67 @@
+(module
+  (memory 1 1 shared)
+
+  (func (export "init") (param $value i64) (i64.store (i32.const 0) (local.get $value)))
+
+  (func (export "i32.atomic.load") (param $addr i32) (result i32) (i32.atomic.load (local.get $addr)))
+  (func (export "i64.atomic.load") (param $addr i32) (result i64) (i64.atomic.load (local.get $addr)))
+  (func (export "i32.atomic.load8_u") (param $addr i32) (result i32) (i32.atomic.load8_u (local.get $addr)))
+  (func (export "i64.atomic.load32_u") (param $addr i32) (result i64) (i64.atomic.load32_u (local.get $addr)))
+
+  (func (export "i32.atomic.store") (param $addr i32) (param $value i32) (i32.atomic.store (local.get $addr) (local.get $value)))
+  (func (export "i64.atomic.store16") (param $addr i32) (param $value i64) (i64.atomic.store16 (local.get $addr) (local.get $value)))
+
+  (func (export "i32.atomic.rmw.add") (param $addr i32) (param $value i32) (result i32) (i32.atomic.rmw.add (local.get $addr) (local.get $value)))
+  (func (export "i64.atomic.rmw.sub") (param $addr i32) (param $value i64) (result i64) (i64.atomic.rmw.sub (local.get $addr) (local.get $value)))
+  (func (export "i32.atomic.rmw8.and_u") (param $addr i32) (param $value i32) (result i32) (i32.atomic.rmw8.and_u (local.get $addr) (local.get $value)))
+  (func (export "i64.atomic.rmw16.or_u") (param $addr i32) (param $value i64) (result i64) (i64.atomic.rmw16.or_u (local.get $addr) (local.get $value)))
+  (func (export "i64.atomic.rmw32.xor_u") (param $addr i32) (param $value i64) (result i64) (i64.atomic.rmw32.xor_u (local.get $addr) (local.get $value)))
+  (func (export "i32.atomic.rmw.xchg") (param $addr i32) (param $value i32) (result i32) (i32.atomic.rmw.xchg (local.get $addr) (local.get $value)))
+
+  (func (export "i32.atomic.rmw.cmpxchg") (param $addr i32) (param $expected i32) (param $value i32) (result i32) (i32.atomic.rmw.cmpxchg (local.get $addr) (local.get $expected) (local.get $value)))
+  (func (export "i64.atomic.rmw8.cmpxchg_u") (param $addr i32) (param $expected i64) (param $value i64) (result i64) (i64.atomic.rmw8.cmpxchg_u (local.get $addr) (local.get $expected) (local.get $value)))
+
+  (func (export "fence") (atomic.fence))
+  (func (export "notify") (param $addr i32) (param $count i32) (result i32) (memory.atomic.notify (local.get $addr) (local.get $count)))
+  (func (export "wait32") (param $addr i32) (param $expected i32) (param $timeout i64) (result i32) (memory.atomic.wait32 (local.get $addr) (local.get $expected) (local.get $timeout)))
+)
+
+(invoke "init" (i64.const 0x0706050403020100))
+(assert_return (invoke "i32.atomic.load" (i32.const 0)) (i32.const 0x03020100))
+(assert_return (invoke "i64.atomic.load" (i32.const 0)) (i64.const 0x0706050403020100))
+(assert_return (invoke "i32.atomic.load8_u" (i32.const 3)) (i32.const 0x03))
+(assert_return (invoke "i64.atomic.load32_u" (i32.const 4)) (i64.const 0x07060504))
+
+(invoke "i32.atomic.store" (i32.const 0) (i32.const 0xcafef00d))
+(assert_return (invoke "i32.atomic.load" (i32.const 0)) (i32.const 0xcafef00d))
+(invoke "i64.atomic.store16" (i32.const 0) (i64.const 0x1234))
+(assert_return (invoke "i32.atomic.load" (i32.const 0)) (i32.const 0xcafe1234))
+
+(invoke "init" (i64.const 0x0706050403020100))
+(assert_return (invoke "i32.atomic.rmw.add" (i32.const 0) (i32.const 0x01010101)) (i32.const 0x03020100))
+(assert_return (invoke "i32.atomic.load" (i32.const 0)) (i32.const 0x04030201))
+(assert_return (invoke "i64.atomic.rmw.sub" (i32.const 0) (i64.const 0x0101010101010101)) (i64.const 0x0706050404030201))
+(assert_return (invoke "i64.atomic.load" (i32.const 0)) (i64.const 0x0605040303020100))
+(assert_return (invoke "i32.atomic.rmw8.and_u" (i32.const 1) (i32.const 0xfe)) (i32.const 0x01))
+(assert_return (invoke "i32.atomic.load8_u" (i32.const 1)) (i32.const 0x00))
+(assert_return (invoke "i64.atomic.rmw16.or_u" (i32.const 0) (i64.const 0xff00)) (i64.const 0x0000))
+(assert_return (invoke "i64.atomic.rmw32.xor_u" (i32.const 0) (i64.const 0xffffffff)) (i64.const 0x0302ff00))
+(assert_return (invoke "i32.atomic.load" (i32.const 0)) (i32.const 0xfcfd00ff))
+(assert_return (invoke "i32.atomic.rmw.xchg" (i32.const 0) (i32.const 42)) (i32.const 0xfcfd00ff))
+(assert_return (invoke "i32.atomic.load" (i32.const 0)) (i32.const 42))
+
+(assert_return (invoke "i32.atomic.rmw.cmpxchg" (i32.const 0) (i32.const 0) (i32.const 7)) (i32.const 42))
+(assert_return (invoke "i32.atomic.load" (i32.const 0)) (i32.const 42))
+(assert_return (invoke "i32.atomic.rmw.cmpxchg" (i32.const 0) (i32.const 42) (i32.const 7)) (i32.const 42))
+(assert_return (invoke "i32.atomic.load" (i32.const 0)) (i32.const 7))
+(assert_return (invoke "i64.atomic.rmw8.cmpxchg_u" (i32.const 0) (i64.const 0x107) (i64.const 0x1ff)) (i64.const 0x07))
+(assert_return (invoke "i32.atomic.load" (i32.const 0)) (i32.const 0xff))
+
+(assert_return (invoke "fence"))
+(assert_return (invoke "notify" (i32.const 0) (i32.const 1)) (i32.const 0))
+(assert_return (invoke "wait32" (i32.const 0) (i32.const 0) (i64.const 0)) (i32.const 1))
+(assert_return (invoke "wait32" (i32.const 0) (i32.const 0xff) (i64.const 0)) (i32.const 2))
+
+(assert_trap (invoke "i32.atomic.load" (i32.const 1)) "unaligned atomic")
+(assert_trap (invoke "i32.atomic.rmw.add" (i32.const 2) (i32.const 1)) "unaligned atomic")
+(assert_trap (invoke "i32.atomic.load" (i32.const 65536)) "out of bounds memory access")
@@ -15,6 +15,11 @@
 //! certain fixed count for arguments and return values, and then the
 //! stack is used for all additional arguments.
 //!
+//! Like Cranelift's `tail` calling convention, the callee is responsible
+//! for deallocating the stack space used by its arguments when
+//! returning, which makes it possible to emit tail calls to functions
+//! regardless of the size of their stack arguments.
+//!
 //! Generally the stack layout looks like:
 //! +-------------------------------+
 //! |                               |
@@ -563,7 +568,7 @@ impl ABIParams {
 }
 
 /// An ABI-specific representation of a function signature.
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone)]
 pub(crate) struct ABISig {
     /// Function parameters.
     pub params: ABIParams,
@@ -571,11 +576,13 @@ pub(crate) struct ABISig {
     pub results: ABIResults,
     /// A unique set of registers used in the entire [`ABISig`].
     pub regs: HashSet<Reg>,
+    /// The calling convention of the signature.
+    pub call_conv: CallingConvention,
 }
 
 impl ABISig {
     /// Create a new ABI signature.
-    pub fn new(params: ABIParams, results: ABIResults) -> Self {
+    pub fn new(call_conv: CallingConvention, params: ABIParams, results: ABIResults) -> Self {
         let regs = params
             .operands
             .regs
@@ -586,6 +593,7 @@ impl ABISig {
             params,
             results,
             regs,
+            call_conv,
         }
     }
 
@@ -614,6 +622,17 @@ impl ABISig {
         self.params.operands.bytes
     }
 
+    /// Returns the stack size, in bytes, of the stack arguments that the
+    /// callee deallocates when returning, which is the size of the stack
+    /// arguments aligned to the call stack alignment.
+    pub fn callee_pop_size<A: ABI>(&self) -> u32 {
+        if self.call_conv.callee_pops_stack_args() {
+            align_to(self.params_stack_size(), A::call_stack_align().into())
+        } else {
+            0
+        }
+    }
+
     /// Returns the stack size, in bytes, needed for results on the stack.
     pub fn results_stack_size(&self) -> u32 {
         self.results.operands.bytes
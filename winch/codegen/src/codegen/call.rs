@@ -1,7 +1,7 @@
 //! Function call emission.  For more details around the ABI and
 //! calling convention, see [ABI].
 //!
-//! This module exposes a single function [`FnCall::emit`], which is responsible
+//! This module exposes [`FnCall::emit`], which is responsible
 //! of orchestrating the emission of calls. In general such orchestration
 //! takes place in 6 steps:
 //!
@@ -55,6 +55,14 @@
 //! │                                                  │
 //! │                                                  │
 //! └──────────────────────────────────────────────────┘ ------> Stack pointer when emitting the call
+//!
+//! Tail calls are emitted through [`FnCall::emit_return_call`], which follows
+//! the same steps up to the emission, with two differences: the callee reuses
+//! the caller's return area, if any; and instead of cleaning up the stack
+//! space after the call, the callee's stack arguments are moved over the
+//! caller's stack arguments, replacing the caller's frame. This is possible
+//! because, in Winch's default calling convention, the callee is responsible
+//! for deallocating its stack arguments.
 
 use crate::{
     abi::{ABIOperand, ABISig, RetArea, ABI},
@@ -98,7 +106,7 @@ impl FnCall {
         context.spill(masm);
         let ret_area = Self::make_ret_area(&sig, masm);
         let arg_stack_space = sig.params_stack_size();
-        let reserved_stack = masm.call(arg_stack_space, |masm| {
+        let reserved_stack = masm.call(arg_stack_space, &sig.call_conv, |masm| {
             Self::assign(sig.as_ref(), ret_area.as_ref(), context, masm);
             kind
         });
@@ -111,6 +119,64 @@ impl FnCall {
         Self::cleanup(&mut sig, reserved_stack, ret_area, masm, context);
     }
 
+    /// Orchestrates the emission of a tail call, replacing the frame of the
+    /// caller, described by the given signature, with the frame of the
+    /// callee:
+    /// 1. Resolves the [`Callee`] through the given callback.
+    /// 2. Maps the resolved [`Callee`] to the [`CalleeKind`].
+    /// 3. Spills the value stack.
+    /// 4. Assigns the arguments, reusing the caller's return area, if any.
+    /// 5. Emits the tail call.
+    ///
+    /// The code following the tail call is unreachable.
+    pub fn emit_return_call<M: MacroAssembler, P: PtrSize, R>(
+        masm: &mut M,
+        context: &mut CodeGenContext,
+        caller_sig: &ABISig,
+        mut resolve: R,
+    ) where
+        R: FnMut(&mut CodeGenContext) -> Callee,
+    {
+        let callee = resolve(context);
+        let ptr_type = ptr_type_from_ptr_size(context.vmoffsets.ptr.size());
+        let sig = Self::get_sig::<M>(&callee, ptr_type);
+        let kind = Self::map(&context.vmoffsets, &callee, sig.as_ref(), context, masm);
+
+        context.spill(masm);
+        let base = masm.sp_offset();
+        let callee_stack_args_size = sig.callee_pop_size::<M::ABI>();
+        let caller_stack_args_size = caller_sig.callee_pop_size::<M::ABI>();
+        masm.reserve_stack(callee_stack_args_size);
+        // Tail calls require the result types of the caller and the callee to
+        // match, so the callee can write its results directly into the
+        // caller's return area.
+        Self::assign(sig.as_ref(), caller_sig.results.ret_area(), context, masm);
+        masm.return_call(
+            context,
+            kind,
+            &sig.regs,
+            callee_stack_args_size,
+            caller_stack_args_size,
+        );
+
+        match kind {
+            CalleeKind::Indirect(r) => context.free_reg(r),
+            _ => {}
+        }
+
+        // Drop the arguments from the value stack. No machine instructions
+        // are needed to reclaim the stack space consumed by them, since the
+        // code following the tail call is unreachable.
+        let mut stack_consumed = 0;
+        context.drop_last(sig.params.len_without_retptr(), |_regalloc, v| {
+            debug_assert!(v.is_mem() || v.is_const());
+            if let Val::Memory(mem) = v {
+                stack_consumed += mem.slot.size;
+            }
+        });
+        masm.reset_stack_pointer(SPOffset::from_u32(base.as_u32() - stack_consumed));
+    }
+
     /// Calculates the return area for the callee, if any.
     fn make_ret_area<M: MacroAssembler>(callee_sig: &ABISig, masm: &mut M) -> Option<RetArea> {
         callee_sig.has_stack_results().then(|| {
@@ -281,25 +347,44 @@ impl FnCall {
 
         if sig.has_stack_results() {
             let operand = sig.params.unwrap_results_area_operand();
-            let base = ret_area.unwrap().unwrap_sp();
-            let addr = masm.address_from_sp(base);
+            let ret_area = ret_area.unwrap();
 
             match operand {
                 &ABIOperand::Reg { ty, reg, .. } => {
-                    masm.load_addr(addr, reg, ty.into());
+                    Self::load_ret_area_base(ret_area, reg, ty.into(), masm);
                 }
                 &ABIOperand::Stack { ty, offset, .. } => {
                     let slot = masm.address_at_sp(SPOffset::from_u32(offset));
                     // Don't rely on `ABI::scratch_for` as we always use
                     // an int register as the return pointer.
                     let scratch = <M::ABI as ABI>::scratch_reg();
-                    masm.load_addr(addr, scratch, ty.into());
+                    Self::load_ret_area_base(ret_area, scratch, ty.into(), masm);
                     masm.store(scratch.into(), slot, ty.into());
                 }
             }
         }
     }
 
+    /// Loads the base address of the given return area to the given register.
+    fn load_ret_area_base<M: MacroAssembler>(
+        ret_area: &RetArea,
+        dst: Reg,
+        size: OperandSize,
+        masm: &mut M,
+    ) {
+        match ret_area {
+            RetArea::SP(base) => {
+                let addr = masm.address_from_sp(*base);
+                masm.load_addr(addr, dst, size);
+            }
+            RetArea::Slot(slot) => {
+                let addr = masm.local_address(slot);
+                masm.load(addr, dst, size);
+            }
+            RetArea::Uninit => unreachable!("return area must be resolved"),
+        }
+    }
+
     /// Cleanup stack space, handle multiple results, and free registers after
     /// emitting the call.
     fn cleanup<M: MacroAssembler>(
@@ -50,13 +50,13 @@ pub enum HeapStyle {
 pub struct HeapData {
     /// The offset to the base of the heap.
     /// Relative to the VMContext pointer if the WebAssembly memory is locally
-    /// defined. Else this is relative to the location of the imported WebAssembly
-    /// memory location.
+    /// defined and not shared. Else this is relative to the location of the
+    /// imported or shared WebAssembly memory definition.
     pub offset: u32,
     /// The offset to the current length field.
     pub current_length_offset: u32,
-    /// If the WebAssembly memory is imported, this field contains the offset to locate the
-    /// base of the heap.
+    /// If the WebAssembly memory is imported or shared, this field contains the offset to
+    /// locate the base of the heap.
     pub import_from: Option<u32>,
     /// The memory type (32 or 64).
     pub ty: WasmValType,
@@ -240,8 +240,20 @@ impl<'a, 'translation, 'data, P: PtrSize> FuncEnv<'a, 'translation, 'data, P> {
         match self.resolved_heaps.entry(index) {
             Occupied(entry) => *entry.get(),
             Vacant(entry) => {
+                let plan = &self.translation.module.memory_plans[index];
                 let (import_from, base_offset, current_length_offset) =
                     match self.translation.module.defined_memory_index(index) {
+                        // Defined shared memories are accessed through a
+                        // pointer to their definition, just like imported
+                        // memories.
+                        Some(defined) if plan.memory.shared => (
+                            Some(self.vmoffsets.vmctx_vmmemory_pointer(defined)),
+                            self.vmoffsets.ptr.vmmemory_definition_base().into(),
+                            self.vmoffsets
+                                .ptr
+                                .vmmemory_definition_current_length()
+                                .into(),
+                        ),
                         Some(defined) => {
                             let owned = self.translation.module.owned_memory_index(defined);
                             (
@@ -261,7 +273,6 @@ impl<'a, 'translation, 'data, P: PtrSize> FuncEnv<'a, 'translation, 'data, P> {
                         ),
                     };
 
-                let plan = &self.translation.module.memory_plans[index];
                 let (min_size, max_size) = heap_limits(&plan);
                 let (style, offset_guard_size) = heap_style_and_offset_guard_size(&plan);
 
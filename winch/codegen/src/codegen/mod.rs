@@ -2,7 +2,9 @@ use crate::{
     abi::{ABIOperand, ABISig, RetArea, ABI},
     codegen::BlockSig,
     isa::reg::Reg,
-    masm::{ExtendKind, IntCmpKind, MacroAssembler, OperandSize, RegImm, SPOffset, TrapCode},
+    masm::{
+        ExtendKind, IntCmpKind, MacroAssembler, OperandSize, RegImm, RmwOp, SPOffset, TrapCode,
+    },
     stack::TypedReg,
 };
 use anyhow::Result;
@@ -309,7 +311,10 @@ where
             self.masm.reset_stack_pointer(base);
         }
         debug_assert_eq!(self.context.stack.len(), 0);
-        self.masm.epilogue(self.context.frame.locals_size);
+        self.masm.epilogue(
+            self.context.frame.locals_size,
+            self.sig.callee_pop_size::<M::ABI>(),
+        );
         Ok(())
     }
 
@@ -635,6 +640,116 @@ where
             self.context.free_reg(src);
         }
     }
+
+    /// Emit a check that traps if the effective address of an atomic access,
+    /// with the index at the top of the value stack, is not naturally
+    /// aligned to the access size.
+    fn emit_check_align(&mut self, arg: &MemArg, size: OperandSize) {
+        if size.bytes() <= 1 {
+            return;
+        }
+
+        let mask = (size.bytes() - 1) as u64;
+        let index = self.context.pop_to_reg(self.masm, None);
+        let tmp = self.context.any_gpr(self.masm);
+        self.masm.mov(index.reg.into(), tmp, OperandSize::S32);
+        // Only the low bits of the offset can affect the alignment of the
+        // effective address.
+        if arg.offset & mask != 0 {
+            self.masm.add(
+                tmp,
+                tmp,
+                RegImm::i32((arg.offset & mask) as i32),
+                OperandSize::S32,
+            );
+        }
+        self.masm
+            .and(tmp, tmp, RegImm::i32(mask as i32), OperandSize::S32);
+        self.masm.cmp(RegImm::i32(0), tmp, OperandSize::S32);
+        self.masm.trapif(IntCmpKind::Ne, TrapCode::HeapMisaligned);
+        self.context.free_reg(tmp);
+        self.context.stack.push(index.into());
+    }
+
+    /// Emit a WebAssembly atomic load.
+    pub fn emit_wasm_atomic_load(&mut self, arg: &MemArg, ty: WasmValType, size: OperandSize) {
+        self.emit_check_align(arg, size);
+        if let Some(addr) = self.emit_compute_heap_address(&arg, size) {
+            let dst = self.context.any_gpr(self.masm);
+            let src = self.masm.address_at_reg(addr, 0);
+            self.masm.atomic_load(src, dst, size);
+            self.context.stack.push(TypedReg::new(ty, dst).into());
+            self.context.free_reg(addr);
+        }
+    }
+
+    /// Emit a WebAssembly atomic store.
+    pub fn emit_wasm_atomic_store(&mut self, arg: &MemArg, size: OperandSize) {
+        let src = self.context.pop_to_reg(self.masm, None);
+        self.emit_check_align(arg, size);
+        if let Some(addr) = self.emit_compute_heap_address(&arg, size) {
+            self.masm
+                .atomic_store(src.reg, self.masm.address_at_reg(addr, 0), size);
+
+            self.context.free_reg(addr);
+        }
+        self.context.free_reg(src);
+    }
+
+    /// Emit a WebAssembly atomic read-modify-write operation.
+    pub fn emit_wasm_atomic_rmw(&mut self, arg: &MemArg, op: RmwOp, size: OperandSize) {
+        let operand = self.context.pop_to_reg(self.masm, None);
+        self.emit_check_align(arg, size);
+        if let Some(addr) = self.emit_compute_heap_address(&arg, size) {
+            let addr = TypedReg::new(self.env.ptr_type(), addr);
+            self.context.stack.extend([addr.into(), operand.into()]);
+            self.masm.atomic_rmw(&mut self.context, op, size);
+        } else {
+            self.context.free_reg(operand);
+        }
+    }
+
+    /// Emit a WebAssembly atomic compare-and-exchange.
+    pub fn emit_wasm_atomic_cmpxchg(&mut self, arg: &MemArg, size: OperandSize) {
+        let replacement = self.context.pop_to_reg(self.masm, None);
+        let expected = self.context.pop_to_reg(self.masm, None);
+        self.emit_check_align(arg, size);
+        if let Some(addr) = self.emit_compute_heap_address(&arg, size) {
+            let addr = TypedReg::new(self.env.ptr_type(), addr);
+            self.context
+                .stack
+                .extend([addr.into(), expected.into(), replacement.into()]);
+            self.masm.atomic_cas(&mut self.context, size);
+        } else {
+            self.context.free_reg(replacement);
+            self.context.free_reg(expected);
+        }
+    }
+
+    /// Prepares the leading arguments of the `memory.atomic.{notify,wait}`
+    /// builtins: pops the index at the top of the value stack and pushes the
+    /// VM context, the memory index and the effective address, which is the
+    /// index plus the static offset, zero-extended to 64 bits.
+    pub fn emit_atomic_builtin_args(&mut self, arg: &MemArg) {
+        let index = self.context.pop_to_reg(self.masm, None);
+        if arg.offset != 0 {
+            self.masm.checked_uadd(
+                index.reg,
+                index.reg,
+                RegImm::i32(arg.offset as i32),
+                OperandSize::S32,
+                TrapCode::HeapOutOfBounds,
+            );
+        }
+        self.masm
+            .extend(index.reg, index.reg, ExtendKind::I64ExtendI32U);
+        let vmctx = TypedReg::new(self.env.ptr_type(), <M::ABI as ABI>::vmctx_reg());
+        self.context.stack.extend([
+            vmctx.into(),
+            arg.memory.try_into().unwrap(),
+            TypedReg::i64(index.reg).into(),
+        ]);
+    }
 }
 
 /// Returns the index of the [`ControlStackFrame`] for the given
@@ -92,7 +92,7 @@ impl ABI for Aarch64ABI {
         returns: &[WasmValType],
         call_conv: &CallingConvention,
     ) -> ABISig {
-        assert!(call_conv.is_apple_aarch64() || call_conv.is_default());
+        assert!(call_conv.is_apple_aarch64() || call_conv.is_systemv() || call_conv.is_default());
 
         let mut params_index_env = RegIndexEnv::default();
         let results = Self::abi_results(returns, call_conv);
@@ -106,11 +106,11 @@ impl ABI for Aarch64ABI {
                 )
             });
 
-        ABISig::new(params, results)
+        ABISig::new(*call_conv, params, results)
     }
 
     fn abi_results(returns: &[WasmValType], call_conv: &CallingConvention) -> ABIResults {
-        assert!(call_conv.is_apple_aarch64() || call_conv.is_default());
+        assert!(call_conv.is_apple_aarch64() || call_conv.is_systemv() || call_conv.is_default());
 
         let mut returns_index_env = RegIndexEnv::with_limit(1);
         ABIResults::from(returns, call_conv, |ty, stack_offset| {
@@ -128,11 +128,11 @@ impl ABI for Aarch64ABI {
     }
 
     fn float_scratch_reg() -> Reg {
-        todo!()
+        regs::float_scratch()
     }
 
     fn sp_reg() -> Reg {
-        todo!()
+        regs::sp()
     }
 
     fn fp_reg() -> Reg {
@@ -176,6 +176,11 @@ impl Aarch64ABI {
         params_or_returns: ParamsOrReturns,
     ) -> (ABIOperand, u32) {
         let (reg, ty) = match wasm_arg {
+            ty @ WasmValType::Ref(rt) => match rt.heap_type {
+                WasmHeapType::Func => (index_env.next_xreg().map(regs::xreg), ty),
+                ht => unimplemented!("Support for WasmHeapType: {ht}"),
+            },
+
             ty @ (WasmValType::I32 | WasmValType::I64) => {
                 (index_env.next_xreg().map(regs::xreg), ty)
             }
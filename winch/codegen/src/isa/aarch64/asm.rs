@@ -1,14 +1,22 @@
 //! Assembler library implementation for Aarch64.
 
 use super::{address::Address, regs};
-use crate::{masm::OperandSize, reg::Reg};
+use crate::masm::{
+    DivKind, ExtendKind, IntCmpKind, OperandSize, RemKind, RmwOp, RoundingMode, ShiftKind,
+    TruncKind,
+};
+use crate::reg::Reg;
 use cranelift_codegen::{
-    ir::MemFlags,
+    ir::{types, ExternalName, LibCall, MemFlags, Opcode, TrapCode, Type, UserExternalNameRef},
     isa::aarch64::inst::{
         self,
         emit::{EmitInfo, EmitState},
-        ALUOp, ALUOp3, AMode, ExtendOp, Imm12, Inst, PairAMode,
+        ALUOp, ALUOp3, AMode, AtomicRMWLoopOp, BitOp, BranchTarget, CallIndInfo, CallInfo, Cond,
+        CondBrKind, ExtendOp, FPULeftShiftImm, FPUOp1, FPUOp2, FPUOpRI, FPUOpRIMod,
+        FPURightShiftImm, FpuRoundMode, FpuToIntOp, Imm12, ImmLogic, ImmShift, Inst, IntToFpuOp,
+        PairAMode, ReturnCallInfo, ScalarSize, UImm5, VecLanesOp, VecMisc2, VectorSize, NZCV,
     },
+    isa::{unwind::UnwindInst, CallConv},
     settings, Final, MachBuffer, MachBufferFinalized, MachInstEmit, MachInstEmitState, MachLabel,
     Writable,
 };
@@ -23,6 +31,74 @@ impl From<OperandSize> for inst::OperandSize {
     }
 }
 
+impl From<OperandSize> for ScalarSize {
+    fn from(size: OperandSize) -> Self {
+        match size {
+            OperandSize::S8 => Self::Size8,
+            OperandSize::S16 => Self::Size16,
+            OperandSize::S32 => Self::Size32,
+            OperandSize::S64 => Self::Size64,
+            OperandSize::S128 => Self::Size128,
+        }
+    }
+}
+
+impl From<IntCmpKind> for Cond {
+    fn from(value: IntCmpKind) -> Self {
+        match value {
+            IntCmpKind::Eq => Cond::Eq,
+            IntCmpKind::Ne => Cond::Ne,
+            IntCmpKind::LtS => Cond::Lt,
+            IntCmpKind::LtU => Cond::Lo,
+            IntCmpKind::GtS => Cond::Gt,
+            IntCmpKind::GtU => Cond::Hi,
+            IntCmpKind::LeS => Cond::Le,
+            IntCmpKind::LeU => Cond::Ls,
+            IntCmpKind::GeS => Cond::Ge,
+            IntCmpKind::GeU => Cond::Hs,
+        }
+    }
+}
+
+impl From<ShiftKind> for ALUOp {
+    fn from(kind: ShiftKind) -> Self {
+        match kind {
+            ShiftKind::Shl => ALUOp::Lsl,
+            ShiftKind::ShrS => ALUOp::Asr,
+            ShiftKind::ShrU => ALUOp::Lsr,
+            ShiftKind::Rotr => ALUOp::RotR,
+            // There's no rotate left instruction in Aarch64; rotate
+            // left is expected to be lowered as a rotate right by
+            // the negated amount.
+            ShiftKind::Rotl => unreachable!(),
+        }
+    }
+}
+
+impl From<RmwOp> for AtomicRMWLoopOp {
+    fn from(op: RmwOp) -> Self {
+        match op {
+            RmwOp::Add => AtomicRMWLoopOp::Add,
+            RmwOp::Sub => AtomicRMWLoopOp::Sub,
+            RmwOp::And => AtomicRMWLoopOp::And,
+            RmwOp::Or => AtomicRMWLoopOp::Orr,
+            RmwOp::Xor => AtomicRMWLoopOp::Eor,
+            RmwOp::Xchg => AtomicRMWLoopOp::Xchg,
+        }
+    }
+}
+
+/// Returns the integer type used for memory accesses of the given size.
+fn access_ty(size: OperandSize) -> Type {
+    match size {
+        OperandSize::S8 => types::I8,
+        OperandSize::S16 => types::I16,
+        OperandSize::S32 => types::I32,
+        OperandSize::S64 => types::I64,
+        OperandSize::S128 => unreachable!(),
+    }
+}
+
 /// Low level assembler implementation for Aarch64.
 pub(crate) struct Assembler {
     /// The machine instruction buffer.
@@ -58,6 +134,11 @@ impl Assembler {
         inst.emit(&[], &mut self.buffer, &self.emit_info, &mut self.emit_state);
     }
 
+    /// Emit an unwind instruction.
+    pub fn emit_unwind_inst(&mut self, inst: UnwindInst) {
+        self.emit(Inst::Unwind { inst })
+    }
+
     /// Load a constant into a register.
     pub fn load_constant(&mut self, imm: u64, rd: Reg) {
         let writable = Writable::from_reg(rd.into());
@@ -78,43 +159,81 @@ impl Assembler {
     }
 
     /// Store a register.
-    pub fn str(&mut self, reg: Reg, addr: Address, size: OperandSize) {
+    pub fn str(&mut self, reg: Reg, addr: Address, size: OperandSize, flags: MemFlags) {
         let mem: AMode = addr.try_into().unwrap();
-        let flags = MemFlags::trusted();
 
         use OperandSize::*;
-        let inst = match size {
-            S64 => Inst::Store64 {
+        let inst = match (reg.is_int(), size) {
+            (true, S8) => Inst::Store8 {
                 rd: reg.into(),
                 mem,
                 flags,
             },
-            S32 => Inst::Store32 {
+            (true, S16) => Inst::Store16 {
+                rd: reg.into(),
+                mem,
+                flags,
+            },
+            (true, S32) => Inst::Store32 {
+                rd: reg.into(),
+                mem,
+                flags,
+            },
+            (true, S64) => Inst::Store64 {
+                rd: reg.into(),
+                mem,
+                flags,
+            },
+            (false, S32) => Inst::FpuStore32 {
+                rd: reg.into(),
+                mem,
+                flags,
+            },
+            (false, S64) => Inst::FpuStore64 {
                 rd: reg.into(),
                 mem,
                 flags,
             },
-
             _ => unreachable!(),
         };
 
         self.emit(inst);
     }
 
-    /// Load a register.
-    pub fn ldr(&mut self, addr: Address, rd: Reg, size: OperandSize) {
+    /// Load a register, zero-extending integer loads narrower than 64
+    /// bits.
+    pub fn uload(&mut self, addr: Address, rd: Reg, size: OperandSize, flags: MemFlags) {
         use OperandSize::*;
         let writable_reg = Writable::from_reg(rd.into());
         let mem: AMode = addr.try_into().unwrap();
-        let flags = MemFlags::trusted();
 
-        let inst = match size {
-            S64 => Inst::ULoad64 {
+        let inst = match (rd.is_int(), size) {
+            (true, S8) => Inst::ULoad8 {
                 rd: writable_reg,
                 mem,
                 flags,
             },
-            S32 => Inst::ULoad32 {
+            (true, S16) => Inst::ULoad16 {
+                rd: writable_reg,
+                mem,
+                flags,
+            },
+            (true, S32) => Inst::ULoad32 {
+                rd: writable_reg,
+                mem,
+                flags,
+            },
+            (true, S64) => Inst::ULoad64 {
+                rd: writable_reg,
+                mem,
+                flags,
+            },
+            (false, S32) => Inst::FpuLoad32 {
+                rd: writable_reg,
+                mem,
+                flags,
+            },
+            (false, S64) => Inst::FpuLoad64 {
                 rd: writable_reg,
                 mem,
                 flags,
@@ -125,6 +244,40 @@ impl Assembler {
         self.emit(inst);
     }
 
+    /// Load a register, sign-extending the loaded value according to the
+    /// given extend kind.
+    pub fn sload(&mut self, addr: Address, rd: Reg, kind: ExtendKind, flags: MemFlags) {
+        let writable_reg = Writable::from_reg(rd.into());
+        let mem: AMode = addr.try_into().unwrap();
+
+        use ExtendKind::*;
+        let inst = match kind {
+            I32Extend8S | I64Extend8S => Inst::SLoad8 {
+                rd: writable_reg,
+                mem,
+                flags,
+            },
+            I32Extend16S | I64Extend16S => Inst::SLoad16 {
+                rd: writable_reg,
+                mem,
+                flags,
+            },
+            I64Extend32S | I64ExtendI32S => Inst::SLoad32 {
+                rd: writable_reg,
+                mem,
+                flags,
+            },
+            I64ExtendI32U => unreachable!(),
+        };
+        self.emit(inst);
+
+        // Signed loads always extend to 64 bits; clear the upper
+        // bits so that 32-bit values are kept zero-extended.
+        if matches!(kind, I32Extend8S | I32Extend16S) {
+            self.mov_rr(rd, rd, OperandSize::S32);
+        }
+    }
+
     /// Load a pair of registers.
     pub fn ldp(&mut self, xt1: Reg, xt2: Reg, addr: Address) {
         let writable_xt1 = Writable::from_reg(xt1.into());
@@ -139,6 +292,15 @@ impl Assembler {
         });
     }
 
+    /// Load the effective address of the given addressing mode.
+    pub fn load_addr(&mut self, addr: Address, rd: Reg) {
+        let mem: AMode = addr.try_into().unwrap();
+        self.emit(Inst::LoadAddr {
+            rd: Writable::from_reg(rd.into()),
+            mem,
+        });
+    }
+
     /// Register to register move.
     pub fn mov_rr(&mut self, rm: Reg, rd: Reg, size: OperandSize) {
         let writable_rd = Writable::from_reg(rd.into());
@@ -149,6 +311,35 @@ impl Assembler {
         });
     }
 
+    /// Floating point register to floating point register move.
+    pub fn fmov_rr(&mut self, rn: Reg, rd: Reg) {
+        self.emit(Inst::FpuMove64 {
+            rd: Writable::from_reg(rd.into()),
+            rn: rn.into(),
+        });
+    }
+
+    /// Move the bits of a general purpose register into a floating point
+    /// register.
+    pub fn mov_to_fpu(&mut self, rn: Reg, rd: Reg, size: OperandSize) {
+        self.emit(Inst::MovToFpu {
+            rd: Writable::from_reg(rd.into()),
+            rn: rn.into(),
+            size: size.into(),
+        });
+    }
+
+    /// Move the bits of the lowest lane of a floating point register into a
+    /// general purpose register.
+    pub fn mov_from_vec(&mut self, rn: Reg, rd: Reg, size: OperandSize) {
+        self.emit(Inst::MovFromVec {
+            rd: Writable::from_reg(rd.into()),
+            rn: rn.into(),
+            idx: 0,
+            size: size.into(),
+        });
+    }
+
     /// Add with three registers.
     pub fn add_rrr(&mut self, rm: Reg, rn: Reg, rd: Reg, size: OperandSize) {
         self.emit_alu_rrr_extend(ALUOp::Add, rm, rn, rd, size);
@@ -166,6 +357,23 @@ impl Assembler {
         }
     }
 
+    /// Add with three registers, setting overflow flags.
+    pub fn adds_rrr(&mut self, rm: Reg, rn: Reg, rd: Reg, size: OperandSize) {
+        self.emit_alu_rrr_extend(ALUOp::AddS, rm, rn, rd, size);
+    }
+
+    /// Add immediate and register, setting overflow flags.
+    pub fn adds_ir(&mut self, imm: u64, rn: Reg, rd: Reg, size: OperandSize) {
+        let alu_op = ALUOp::AddS;
+        if let Some(imm) = Imm12::maybe_from_u64(imm) {
+            self.emit_alu_rri(alu_op, imm, rn, rd, size);
+        } else {
+            let scratch = regs::scratch();
+            self.load_constant(imm, scratch);
+            self.emit_alu_rrr_extend(alu_op, scratch, rn, rd, size);
+        }
+    }
+
     /// Substract with three registers.
     pub fn sub_rrr(&mut self, rm: Reg, rn: Reg, rd: Reg, size: OperandSize) {
         self.emit_alu_rrr_extend(ALUOp::Sub, rm, rn, rd, size);
@@ -195,11 +403,616 @@ impl Assembler {
         self.emit_alu_rrrr(ALUOp3::MAdd, scratch, rn, rd, regs::zero(), size);
     }
 
+    /// Multiply-subtract with four registers: rd = ra - (rn * rm).
+    pub fn msub_rrrr(&mut self, rm: Reg, rn: Reg, ra: Reg, rd: Reg, size: OperandSize) {
+        self.emit_alu_rrrr(ALUOp3::MSub, rm, rn, rd, ra, size);
+    }
+
+    /// Integer division with three registers: rd = rn / rm.
+    ///
+    /// The caller is responsible for emitting the checks needed
+    /// to match WebAssembly's trapping semantics.
+    pub fn div_rrr(&mut self, rm: Reg, rn: Reg, rd: Reg, kind: DivKind, size: OperandSize) {
+        let op = match kind {
+            DivKind::Signed => ALUOp::SDiv,
+            DivKind::Unsigned => ALUOp::UDiv,
+        };
+        self.emit_alu_rrr(op, rm, rn, rd, size);
+    }
+
+    /// Integer remainder with three registers: rd = rn % rm.
+    ///
+    /// The quotient is calculated into the scratch register, which
+    /// must be different from all the given registers.
+    pub fn rem_rrr(&mut self, rm: Reg, rn: Reg, rd: Reg, kind: RemKind, size: OperandSize) {
+        let scratch = regs::scratch();
+        let div_kind = match kind {
+            RemKind::Signed => DivKind::Signed,
+            RemKind::Unsigned => DivKind::Unsigned,
+        };
+        self.div_rrr(rm, rn, scratch, div_kind, size);
+        self.msub_rrrr(rm, scratch, rn, rd, size);
+    }
+
+    /// Logical and with three registers.
+    pub fn and_rrr(&mut self, rm: Reg, rn: Reg, rd: Reg, size: OperandSize) {
+        self.emit_alu_rrr(ALUOp::And, rm, rn, rd, size);
+    }
+
+    /// Logical and immediate and register.
+    pub fn and_ir(&mut self, imm: u64, rn: Reg, rd: Reg, size: OperandSize) {
+        self.emit_alu_logic_ri(ALUOp::And, imm, rn, rd, size);
+    }
+
+    /// Logical or with three registers.
+    pub fn or_rrr(&mut self, rm: Reg, rn: Reg, rd: Reg, size: OperandSize) {
+        self.emit_alu_rrr(ALUOp::Orr, rm, rn, rd, size);
+    }
+
+    /// Logical or immediate and register.
+    pub fn or_ir(&mut self, imm: u64, rn: Reg, rd: Reg, size: OperandSize) {
+        self.emit_alu_logic_ri(ALUOp::Orr, imm, rn, rd, size);
+    }
+
+    /// Logical exclusive or with three registers.
+    pub fn xor_rrr(&mut self, rm: Reg, rn: Reg, rd: Reg, size: OperandSize) {
+        self.emit_alu_rrr(ALUOp::Eor, rm, rn, rd, size);
+    }
+
+    /// Logical exclusive or immediate and register.
+    pub fn xor_ir(&mut self, imm: u64, rn: Reg, rd: Reg, size: OperandSize) {
+        self.emit_alu_logic_ri(ALUOp::Eor, imm, rn, rd, size);
+    }
+
+    /// Shift with three registers.
+    pub fn shift_rrr(&mut self, rm: Reg, rn: Reg, rd: Reg, kind: ShiftKind, size: OperandSize) {
+        match kind {
+            ShiftKind::Rotl => {
+                // Rotate left by `rm` is equivalent to rotate right by
+                // `-rm`, given that the shift amount is taken modulo the
+                // operand size.
+                let scratch = regs::scratch();
+                self.emit_alu_rrr(ALUOp::Sub, rm, regs::zero(), scratch, size);
+                self.emit_alu_rrr(ALUOp::RotR, scratch, rn, rd, size);
+            }
+            _ => self.emit_alu_rrr(kind.into(), rm, rn, rd, size),
+        }
+    }
+
+    /// Shift immediate and register.
+    pub fn shift_ir(&mut self, imm: u64, rn: Reg, rd: Reg, kind: ShiftKind, size: OperandSize) {
+        let bits = size.num_bits() as u64;
+        let amt = imm % bits;
+        let (op, amt) = match kind {
+            ShiftKind::Rotl => (ALUOp::RotR, (bits - amt) % bits),
+            _ => (kind.into(), amt),
+        };
+        self.emit(Inst::AluRRImmShift {
+            alu_op: op,
+            size: size.into(),
+            rd: Writable::from_reg(rd.into()),
+            rn: rn.into(),
+            immshift: ImmShift::maybe_from_u64(amt).unwrap(),
+        });
+    }
+
+    /// Count leading zeros.
+    pub fn clz(&mut self, rn: Reg, rd: Reg, size: OperandSize) {
+        self.emit_bit_rr(BitOp::Clz, rn, rd, size);
+    }
+
+    /// Reverse the bits in a register.
+    pub fn rbit(&mut self, rn: Reg, rd: Reg, size: OperandSize) {
+        self.emit_bit_rr(BitOp::RBit, rn, rd, size);
+    }
+
+    /// Population count. Uses the given floating point register as
+    /// a temporary to perform the count.
+    pub fn cnt(&mut self, rn: Reg, rd: Reg, tmp: Reg) {
+        let writable_tmp = Writable::from_reg(tmp.into());
+        self.mov_to_fpu(rn, tmp, OperandSize::S64);
+        self.emit(Inst::VecMisc {
+            op: VecMisc2::Cnt,
+            rd: writable_tmp,
+            rn: tmp.into(),
+            size: VectorSize::Size8x8,
+        });
+        self.emit(Inst::VecLanes {
+            op: VecLanesOp::Addv,
+            rd: writable_tmp,
+            rn: tmp.into(),
+            size: VectorSize::Size8x8,
+        });
+        self.mov_from_vec(tmp, rd, OperandSize::S8);
+    }
+
+    /// Sign extend the value in a register.
+    pub fn extend(&mut self, rn: Reg, rd: Reg, kind: ExtendKind) {
+        let (signed, from_bits, to_bits) = match kind {
+            ExtendKind::I64ExtendI32S | ExtendKind::I64Extend32S => (true, 32, 64),
+            ExtendKind::I64ExtendI32U => (false, 32, 64),
+            ExtendKind::I32Extend8S => (true, 8, 32),
+            ExtendKind::I32Extend16S => (true, 16, 32),
+            ExtendKind::I64Extend8S => (true, 8, 64),
+            ExtendKind::I64Extend16S => (true, 16, 64),
+        };
+        self.emit(Inst::Extend {
+            rd: Writable::from_reg(rd.into()),
+            rn: rn.into(),
+            signed,
+            from_bits,
+            to_bits,
+        });
+    }
+
+    /// Compare a register against another register. Sets the flags
+    /// according to `rn - rm`.
+    pub fn cmp_rr(&mut self, rm: Reg, rn: Reg, size: OperandSize) {
+        if rn == regs::sp() {
+            // The shifted register form of `subs` interprets register
+            // 31 as the zero register, so use the extended register
+            // form when comparing against the stack pointer.
+            self.emit_alu_rrr_extend(ALUOp::SubS, rm, rn, regs::zero(), size);
+        } else {
+            self.emit_alu_rrr(ALUOp::SubS, rm, rn, regs::zero(), size);
+        }
+    }
+
+    /// Compare a register against an immediate. Sets the flags
+    /// according to `rn - imm`.
+    pub fn cmp_ir(&mut self, imm: u64, rn: Reg, size: OperandSize) {
+        if let Some(imm12) = Imm12::maybe_from_u64(imm) {
+            self.emit_alu_rri(ALUOp::SubS, imm12, rn, regs::zero(), size);
+        } else {
+            let scratch = regs::scratch();
+            self.load_constant(imm, scratch);
+            self.cmp_rr(scratch, rn, size);
+        }
+    }
+
+    /// Test a register against another register, by performing a
+    /// logical and without storing its result.
+    pub fn tst_rr(&mut self, rm: Reg, rn: Reg, size: OperandSize) {
+        self.emit_alu_rrr(ALUOp::AndS, rm, rn, regs::zero(), size);
+    }
+
+    /// Conditional comparison against an immediate, setting the flags
+    /// to the given `nzcv` value if the condition doesn't hold.
+    pub fn ccmp_ir(&mut self, imm: u8, rn: Reg, nzcv: NZCV, cond: Cond, size: OperandSize) {
+        self.emit(Inst::CCmpImm {
+            size: size.into(),
+            rn: rn.into(),
+            imm: UImm5::maybe_from_u8(imm).unwrap(),
+            nzcv,
+            cond,
+        });
+    }
+
+    /// Set a register to 1 if the condition holds, else to 0.
+    pub fn cset(&mut self, rd: Reg, cond: Cond) {
+        self.emit(Inst::CSet {
+            rd: Writable::from_reg(rd.into()),
+            cond,
+        });
+    }
+
+    /// Conditional select: rd = cond ? rn : rm.
+    pub fn csel(&mut self, rn: Reg, rm: Reg, rd: Reg, cond: Cond) {
+        self.emit(Inst::CSel {
+            rd: Writable::from_reg(rd.into()),
+            cond,
+            rn: rn.into(),
+            rm: rm.into(),
+        });
+    }
+
+    /// Floating point conditional select: rd = cond ? rn : rm.
+    pub fn fpu_csel(&mut self, rn: Reg, rm: Reg, rd: Reg, cond: Cond, size: OperandSize) {
+        let rd = Writable::from_reg(rd.into());
+        let (rn, rm) = (rn.into(), rm.into());
+        let inst = match size {
+            OperandSize::S32 => Inst::FpuCSel32 { rd, rn, rm, cond },
+            OperandSize::S64 => Inst::FpuCSel64 { rd, rn, rm, cond },
+            _ => unreachable!(),
+        };
+        self.emit(inst);
+    }
+
+    /// Floating point comparison. Sets the flags according to `rn - rm`.
+    pub fn fcmp(&mut self, rm: Reg, rn: Reg, size: OperandSize) {
+        self.emit(Inst::FpuCmp {
+            size: size.into(),
+            rn: rn.into(),
+            rm: rm.into(),
+        });
+    }
+
+    /// Floating point add with three registers.
+    pub fn fadd_rrr(&mut self, rm: Reg, rn: Reg, rd: Reg, size: OperandSize) {
+        self.emit_fpu_rrr(FPUOp2::Add, rm, rn, rd, size);
+    }
+
+    /// Floating point subtract with three registers.
+    pub fn fsub_rrr(&mut self, rm: Reg, rn: Reg, rd: Reg, size: OperandSize) {
+        self.emit_fpu_rrr(FPUOp2::Sub, rm, rn, rd, size);
+    }
+
+    /// Floating point multiply with three registers.
+    pub fn fmul_rrr(&mut self, rm: Reg, rn: Reg, rd: Reg, size: OperandSize) {
+        self.emit_fpu_rrr(FPUOp2::Mul, rm, rn, rd, size);
+    }
+
+    /// Floating point divide with three registers.
+    pub fn fdiv_rrr(&mut self, rm: Reg, rn: Reg, rd: Reg, size: OperandSize) {
+        self.emit_fpu_rrr(FPUOp2::Div, rm, rn, rd, size);
+    }
+
+    /// Floating point minimum with three registers.
+    pub fn fmin_rrr(&mut self, rm: Reg, rn: Reg, rd: Reg, size: OperandSize) {
+        self.emit_fpu_rrr(FPUOp2::Min, rm, rn, rd, size);
+    }
+
+    /// Floating point maximum with three registers.
+    pub fn fmax_rrr(&mut self, rm: Reg, rn: Reg, rd: Reg, size: OperandSize) {
+        self.emit_fpu_rrr(FPUOp2::Max, rm, rn, rd, size);
+    }
+
+    /// Floating point negation.
+    pub fn fneg_rr(&mut self, rn: Reg, rd: Reg, size: OperandSize) {
+        self.emit_fpu_rr(FPUOp1::Neg, rn, rd, size);
+    }
+
+    /// Floating point absolute value.
+    pub fn fabs_rr(&mut self, rn: Reg, rd: Reg, size: OperandSize) {
+        self.emit_fpu_rr(FPUOp1::Abs, rn, rd, size);
+    }
+
+    /// Floating point square root.
+    pub fn fsqrt_rr(&mut self, rn: Reg, rd: Reg, size: OperandSize) {
+        self.emit_fpu_rr(FPUOp1::Sqrt, rn, rd, size);
+    }
+
+    /// Convert a floating point value to another precision.
+    pub fn cvt_float_to_float(
+        &mut self,
+        rn: Reg,
+        rd: Reg,
+        src_size: OperandSize,
+        dst_size: OperandSize,
+    ) {
+        let op = match (src_size, dst_size) {
+            (OperandSize::S32, OperandSize::S64) => FPUOp1::Cvt32To64,
+            (OperandSize::S64, OperandSize::S32) => FPUOp1::Cvt64To32,
+            _ => unreachable!(),
+        };
+        self.emit_fpu_rr(op, rn, rd, src_size);
+    }
+
+    /// Round a floating point value to an integral value, according to the
+    /// given rounding mode.
+    pub fn fround_rr(&mut self, rn: Reg, rd: Reg, mode: RoundingMode, size: OperandSize) {
+        use OperandSize::*;
+        use RoundingMode::*;
+        let op = match (mode, size) {
+            (Nearest, S32) => FpuRoundMode::Nearest32,
+            (Nearest, S64) => FpuRoundMode::Nearest64,
+            (Up, S32) => FpuRoundMode::Plus32,
+            (Up, S64) => FpuRoundMode::Plus64,
+            (Down, S32) => FpuRoundMode::Minus32,
+            (Down, S64) => FpuRoundMode::Minus64,
+            (Zero, S32) => FpuRoundMode::Zero32,
+            (Zero, S64) => FpuRoundMode::Zero64,
+            _ => unreachable!(),
+        };
+        self.emit(Inst::FpuRound {
+            op,
+            rd: Writable::from_reg(rd.into()),
+            rn: rn.into(),
+        });
+    }
+
+    /// Copy the sign of `rm` into the magnitude of `rd`.
+    ///
+    /// The floating point scratch register is used as a temporary to
+    /// hold the sign bit.
+    pub fn fcopysign(&mut self, rm: Reg, rd: Reg, size: OperandSize) {
+        let scratch = regs::float_scratch();
+        let bits = size.num_bits();
+        let max_shift = bits - 1;
+        let ushr = FPURightShiftImm::maybe_from_u8(max_shift, bits).unwrap();
+        let sli = FPULeftShiftImm::maybe_from_u8(max_shift, bits).unwrap();
+        let (ushr, sli) = match size {
+            OperandSize::S32 => (FPUOpRI::UShr32(ushr), FPUOpRIMod::Sli32(sli)),
+            OperandSize::S64 => (FPUOpRI::UShr64(ushr), FPUOpRIMod::Sli64(sli)),
+            _ => unreachable!(),
+        };
+        self.emit(Inst::FpuRRI {
+            fpu_op: ushr,
+            rd: Writable::from_reg(scratch.into()),
+            rn: rm.into(),
+        });
+        self.emit(Inst::FpuRRIMod {
+            fpu_op: sli,
+            rd: Writable::from_reg(rd.into()),
+            ri: rd.into(),
+            rn: scratch.into(),
+        });
+    }
+
+    /// Convert a floating point value to an integer.
+    ///
+    /// The conversion saturates on overflow and produces zero for NaN
+    /// inputs. When `kind` is [`TruncKind::Unchecked`], checks are emitted
+    /// before the conversion to trap on NaN and out-of-range inputs, per
+    /// WebAssembly semantics.
+    pub fn fpu_to_int(
+        &mut self,
+        rn: Reg,
+        rd: Reg,
+        src_size: OperandSize,
+        dst_size: OperandSize,
+        kind: TruncKind,
+        signed: bool,
+    ) {
+        use OperandSize::*;
+
+        if !kind.is_checked() {
+            self.fpu_to_int_bound_checks(rn, src_size, dst_size, signed);
+        }
+
+        let op = match (signed, src_size, dst_size) {
+            (true, S32, S32) => FpuToIntOp::F32ToI32,
+            (true, S32, S64) => FpuToIntOp::F32ToI64,
+            (true, S64, S32) => FpuToIntOp::F64ToI32,
+            (true, S64, S64) => FpuToIntOp::F64ToI64,
+            (false, S32, S32) => FpuToIntOp::F32ToU32,
+            (false, S32, S64) => FpuToIntOp::F32ToU64,
+            (false, S64, S32) => FpuToIntOp::F64ToU32,
+            (false, S64, S64) => FpuToIntOp::F64ToU64,
+            _ => unreachable!(),
+        };
+        self.emit(Inst::FpuToInt {
+            op,
+            rd: Writable::from_reg(rd.into()),
+            rn: rn.into(),
+        });
+    }
+
+    /// Convert an integer to a floating point value.
+    pub fn int_to_fpu(
+        &mut self,
+        rn: Reg,
+        rd: Reg,
+        src_size: OperandSize,
+        dst_size: OperandSize,
+        signed: bool,
+    ) {
+        use OperandSize::*;
+        let op = match (signed, src_size, dst_size) {
+            (true, S32, S32) => IntToFpuOp::I32ToF32,
+            (true, S32, S64) => IntToFpuOp::I32ToF64,
+            (true, S64, S32) => IntToFpuOp::I64ToF32,
+            (true, S64, S64) => IntToFpuOp::I64ToF64,
+            (false, S32, S32) => IntToFpuOp::U32ToF32,
+            (false, S32, S64) => IntToFpuOp::U32ToF64,
+            (false, S64, S32) => IntToFpuOp::U64ToF32,
+            (false, S64, S64) => IntToFpuOp::U64ToF64,
+            _ => unreachable!(),
+        };
+        self.emit(Inst::IntToFpu {
+            op,
+            rd: Writable::from_reg(rd.into()),
+            rn: rn.into(),
+        });
+    }
+
+    /// Load-acquire register: `ldar{b,h}`. The loaded value is
+    /// zero-extended to the register width.
+    pub fn ldar(&mut self, rn: Reg, rt: Reg, size: OperandSize, flags: MemFlags) {
+        self.emit(Inst::LoadAcquire {
+            access_ty: access_ty(size),
+            rt: Writable::from_reg(rt.into()),
+            rn: rn.into(),
+            flags,
+        });
+    }
+
+    /// Store-release register: `stlr{b,h}`.
+    pub fn stlr(&mut self, rt: Reg, rn: Reg, size: OperandSize, flags: MemFlags) {
+        self.emit(Inst::StoreRelease {
+            access_ty: access_ty(size),
+            rt: rt.into(),
+            rn: rn.into(),
+            flags,
+        });
+    }
+
+    /// Full memory barrier: `dmb ish`.
+    pub fn fence(&mut self) {
+        self.emit(Inst::Fence {});
+    }
+
+    /// Atomic read-modify-write, emitted as a load-exclusive /
+    /// store-exclusive loop.
+    ///
+    /// The sequence uses fixed registers: x25 holds the address, x26
+    /// the operand and x27 receives the previous value, zero-extended
+    /// from `size`. x24 and x28 are clobbered.
+    pub fn atomic_rmw_loop(&mut self, op: RmwOp, size: OperandSize, flags: MemFlags) {
+        self.emit(Inst::AtomicRMWLoop {
+            ty: access_ty(size),
+            op: op.into(),
+            flags,
+            addr: regs::xreg(25).into(),
+            operand: regs::xreg(26).into(),
+            oldval: Writable::from_reg(regs::xreg(27).into()),
+            scratch1: Writable::from_reg(regs::xreg(24).into()),
+            scratch2: Writable::from_reg(regs::xreg(28).into()),
+        });
+    }
+
+    /// Atomic compare-and-exchange, emitted as a load-exclusive /
+    /// store-exclusive loop.
+    ///
+    /// The sequence uses fixed registers: x25 holds the address, x26
+    /// the expected value, x28 the replacement value and x27 receives
+    /// the previous value, zero-extended from `size`. x24 is clobbered.
+    pub fn atomic_cas_loop(&mut self, size: OperandSize, flags: MemFlags) {
+        self.emit(Inst::AtomicCASLoop {
+            ty: access_ty(size),
+            flags,
+            addr: regs::xreg(25).into(),
+            expected: regs::xreg(26).into(),
+            replacement: regs::xreg(28).into(),
+            oldval: Writable::from_reg(regs::xreg(27).into()),
+            scratch: Writable::from_reg(regs::xreg(24).into()),
+        });
+    }
+
     /// Return instruction.
     pub fn ret(&mut self) {
         self.emit(Inst::Ret {});
     }
 
+    /// Emit a direct call to a function defined locally and
+    /// referenced by index.
+    pub fn call_with_index(&mut self, index: u32) {
+        let dest = ExternalName::user(UserExternalNameRef::from_u32(index));
+        self.emit(Inst::Call {
+            info: Box::new(CallInfo {
+                dest,
+                uses: Default::default(),
+                defs: Default::default(),
+                clobbers: Default::default(),
+                opcode: Opcode::Call,
+                caller_callconv: CallConv::SystemV,
+                callee_callconv: CallConv::SystemV,
+                callee_pop_size: 0,
+            }),
+        });
+    }
+
+    /// Emit an indirect call to the address held by the given
+    /// register.
+    pub fn call_with_reg(&mut self, callee: Reg) {
+        self.emit(Inst::CallInd {
+            info: Box::new(CallIndInfo {
+                rn: callee.into(),
+                uses: Default::default(),
+                defs: Default::default(),
+                clobbers: Default::default(),
+                opcode: Opcode::CallIndirect,
+                caller_callconv: CallConv::SystemV,
+                callee_callconv: CallConv::SystemV,
+                callee_pop_size: 0,
+            }),
+        });
+    }
+
+    /// Emit a tail call to a function defined locally and referenced by
+    /// index.
+    ///
+    /// The frame record at the frame pointer is popped, restoring the
+    /// frame pointer and the link register, before jumping to the
+    /// callee.
+    pub fn return_call_with_index(&mut self, index: u32) {
+        let callee = ExternalName::user(UserExternalNameRef::from_u32(index));
+        self.emit(Inst::ReturnCall {
+            callee: Box::new(callee),
+            info: Box::new(Self::return_call_info(Opcode::ReturnCall)),
+        });
+    }
+
+    /// Emit an indirect tail call to a function whose address is
+    /// stored in the given register. See
+    /// [`Assembler::return_call_with_index`].
+    pub fn return_call_with_reg(&mut self, callee: Reg) {
+        self.emit(Inst::ReturnCallInd {
+            callee: callee.into(),
+            info: Box::new(Self::return_call_info(Opcode::ReturnCallIndirect)),
+        });
+    }
+
+    /// The stack arguments are moved into place before emitting the tail
+    /// call, so there's nothing left to copy when popping the frame.
+    fn return_call_info(opcode: Opcode) -> ReturnCallInfo {
+        ReturnCallInfo {
+            uses: Default::default(),
+            opcode,
+            old_stack_arg_size: 0,
+            new_stack_arg_size: 0,
+            key: None,
+        }
+    }
+
+    /// Emit a call to a well-known libcall.
+    ///
+    /// Libcalls are resolved through an absolute 8-byte relocation,
+    /// so the address is first loaded into the scratch register.
+    pub fn call_with_lib(&mut self, lib: LibCall) {
+        let scratch = regs::scratch();
+        self.emit(Inst::LoadExtName {
+            rd: Writable::from_reg(scratch.into()),
+            name: Box::new(ExternalName::LibCall(lib)),
+            offset: 0,
+        });
+        self.call_with_reg(scratch);
+    }
+
+    /// Emit a conditional branch to the given label. Falls through if the
+    /// condition doesn't hold.
+    pub fn jmp_if(&mut self, kind: CondBrKind, taken: MachLabel) {
+        let not_taken = self.get_label();
+        self.emit(Inst::CondBr {
+            taken: BranchTarget::Label(taken),
+            not_taken: BranchTarget::Label(not_taken),
+            kind,
+        });
+        self.buffer
+            .bind_label(not_taken, self.emit_state.ctrl_plane_mut());
+    }
+
+    /// Emit an unconditional jump to the given label.
+    pub fn jmp(&mut self, target: MachLabel) {
+        self.emit(Inst::Jump {
+            dest: BranchTarget::Label(target),
+        });
+    }
+
+    /// Emit a jump table sequence. Expects the flags to be set from a
+    /// comparison between the index and the number of targets; the
+    /// default target is taken if the index is greater than or equal to the
+    /// number of targets.
+    pub fn jmp_table(
+        &mut self,
+        targets: &[MachLabel],
+        default: MachLabel,
+        index: Reg,
+        tmp1: Reg,
+        tmp2: Reg,
+    ) {
+        self.emit(Inst::JTSequence {
+            default,
+            targets: Box::new(targets.to_vec()),
+            ridx: index.into(),
+            rtmp1: Writable::from_reg(tmp1.into()),
+            rtmp2: Writable::from_reg(tmp2.into()),
+        });
+    }
+
+    /// Emit a trap instruction.
+    pub fn udf(&mut self, code: TrapCode) {
+        self.emit(Inst::Udf { trap_code: code });
+    }
+
+    /// Conditional trap.
+    pub fn trapif(&mut self, kind: CondBrKind, code: TrapCode) {
+        self.emit(Inst::TrapIf {
+            kind,
+            trap_code: code,
+        });
+    }
+
     // Helpers for ALU operations.
 
     fn emit_alu_rri(&mut self, op: ALUOp, imm: Imm12, rn: Reg, rd: Reg, size: OperandSize) {
@@ -212,6 +1025,16 @@ impl Assembler {
         });
     }
 
+    fn emit_alu_rrr(&mut self, op: ALUOp, rm: Reg, rn: Reg, rd: Reg, size: OperandSize) {
+        self.emit(Inst::AluRRR {
+            alu_op: op,
+            size: size.into(),
+            rd: Writable::from_reg(rd.into()),
+            rn: rn.into(),
+            rm: rm.into(),
+        });
+    }
+
     fn emit_alu_rrr_extend(&mut self, op: ALUOp, rm: Reg, rn: Reg, rd: Reg, size: OperandSize) {
         self.emit(Inst::AluRRRExtend {
             alu_op: op,
@@ -234,6 +1057,112 @@ impl Assembler {
         });
     }
 
+    fn emit_alu_logic_ri(&mut self, op: ALUOp, imm: u64, rn: Reg, rd: Reg, size: OperandSize) {
+        let ty = match size {
+            OperandSize::S32 => types::I32,
+            OperandSize::S64 => types::I64,
+            _ => unreachable!(),
+        };
+
+        if let Some(imml) = ImmLogic::maybe_from_u64(imm, ty) {
+            self.emit(Inst::AluRRImmLogic {
+                alu_op: op,
+                size: size.into(),
+                rd: Writable::from_reg(rd.into()),
+                rn: rn.into(),
+                imml,
+            });
+        } else {
+            let scratch = regs::scratch();
+            self.load_constant(imm, scratch);
+            self.emit_alu_rrr(op, scratch, rn, rd, size);
+        }
+    }
+
+    fn emit_bit_rr(&mut self, op: BitOp, rn: Reg, rd: Reg, size: OperandSize) {
+        self.emit(Inst::BitRR {
+            op,
+            size: size.into(),
+            rd: Writable::from_reg(rd.into()),
+            rn: rn.into(),
+        });
+    }
+
+    fn emit_fpu_rr(&mut self, op: FPUOp1, rn: Reg, rd: Reg, size: OperandSize) {
+        self.emit(Inst::FpuRR {
+            fpu_op: op,
+            size: size.into(),
+            rd: Writable::from_reg(rd.into()),
+            rn: rn.into(),
+        });
+    }
+
+    fn emit_fpu_rrr(&mut self, op: FPUOp2, rm: Reg, rn: Reg, rd: Reg, size: OperandSize) {
+        self.emit(Inst::FpuRRR {
+            fpu_op: op,
+            size: size.into(),
+            rd: Writable::from_reg(rd.into()),
+            rn: rn.into(),
+            rm: rm.into(),
+        });
+    }
+
+    /// Emit the checks required by the trapping float to integer
+    /// conversions: trap if the source is NaN, or if it's not within the
+    /// range of the destination integer type.
+    ///
+    /// Follows Cranelift's lowering of `fcvt_to_{s,u}int`.
+    fn fpu_to_int_bound_checks(
+        &mut self,
+        rn: Reg,
+        src_size: OperandSize,
+        dst_size: OperandSize,
+        signed: bool,
+    ) {
+        use OperandSize::*;
+
+        // NaN check.
+        self.fcmp(rn, rn, src_size);
+        self.trapif(CondBrKind::Cond(Cond::Vs), TrapCode::BadConversionToInteger);
+
+        let scratch = regs::scratch();
+        let float_scratch = regs::float_scratch();
+
+        // Lower bound check. For conversions in which the minimum
+        // value of the destination type is precisely representable
+        // in the source type, the bound is inclusive.
+        let (min, cond) = match (signed, src_size, dst_size) {
+            (true, S32, S32) => ((i32::MIN as f32).to_bits() as u64, Cond::Lt),
+            (true, S32, S64) => ((i64::MIN as f32).to_bits() as u64, Cond::Lt),
+            (true, S64, S32) => ((i32::MIN as f64 - 1.).to_bits(), Cond::Le),
+            (true, S64, S64) => ((i64::MIN as f64).to_bits(), Cond::Lt),
+            (false, S32, _) => ((-1f32).to_bits() as u64, Cond::Le),
+            (false, S64, _) => ((-1f64).to_bits(), Cond::Le),
+            _ => unreachable!(),
+        };
+        self.load_constant(min, scratch);
+        self.mov_to_fpu(scratch, float_scratch, src_size);
+        self.fcmp(float_scratch, rn, src_size);
+        self.trapif(CondBrKind::Cond(cond), TrapCode::IntegerOverflow);
+
+        // Upper bound check.
+        let max = match (signed, src_size, dst_size) {
+            (true, S32, S32) => ((i32::MAX as u64 + 1) as f32).to_bits() as u64,
+            (true, S32, S64) => ((i64::MAX as u64 + 1) as f32).to_bits() as u64,
+            (true, S64, S32) => (i32::MAX as f64 + 1.).to_bits(),
+            (true, S64, S64) => ((i64::MAX as u64 + 1) as f64).to_bits(),
+            (false, S32, S32) => ((u32::MAX as u64 + 1) as f32).to_bits() as u64,
+            (false, S32, S64) => ((u64::MAX as u128 + 1) as f32).to_bits() as u64,
+            (false, S64, S32) => (u32::MAX as f64 + 1.).to_bits(),
+            (false, S64, S64) => ((u64::MAX as u128 + 1) as f64).to_bits(),
+            _ => unreachable!(),
+        };
+        self.load_constant(max, scratch);
+        self.mov_to_fpu(scratch, float_scratch, src_size);
+        self.fcmp(float_scratch, rn, src_size);
+        self.trapif(CondBrKind::Cond(Cond::Ge), TrapCode::IntegerOverflow);
+    }
+
     /// Get a label from the underlying machine code buffer.
     pub fn get_label(&mut self) -> MachLabel {
         self.buffer.get_label()
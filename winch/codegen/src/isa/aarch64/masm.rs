@@ -1,16 +1,26 @@
 use super::{abi::Aarch64ABI, address::Address, asm::Assembler, regs};
 use crate::{
-    abi::{self, local::LocalSlot},
+    abi::{self, align_to, calculate_frame_adjustment, local::LocalSlot},
     codegen::{ptr_type_from_ptr_size, CodeGenContext, HeapData, TableData},
-    isa::reg::Reg,
+    isa::{
+        reg::{Reg, RegClass},
+        CallingConvention,
+    },
     masm::{
         CalleeKind, DivKind, ExtendKind, FloatCmpKind, Imm as I, IntCmpKind,
-        MacroAssembler as Masm, OperandSize, RegImm, RemKind, RoundingMode, SPOffset, ShiftKind,
-        StackSlot, TrapCode, TruncKind,
+        MacroAssembler as Masm, OperandSize, RegImm, RemKind, RmwOp, RoundingMode, SPOffset,
+        ShiftKind, StackSlot, TrapCode, TruncKind, TRUSTED_FLAGS, UNTRUSTED_FLAGS,
     },
+    stack::{TypedReg, Val},
+};
+use cranelift_codegen::{
+    ir::MemFlags,
+    isa::aarch64::inst::{Cond, CondBrKind, NZCV},
+    isa::unwind::UnwindInst,
+    settings, Final, MachBufferFinalized, MachLabel,
 };
-use cranelift_codegen::{settings, Final, MachBufferFinalized, MachLabel};
-use wasmtime_environ::PtrSize;
+use std::collections::HashSet;
+use wasmtime_environ::{PtrSize, WasmValType, WASM_PAGE_SIZE};
 
 /// Aarch64 MacroAssembler.
 pub(crate) struct MacroAssembler {
@@ -20,15 +30,18 @@ pub(crate) struct MacroAssembler {
     sp_offset: u32,
     /// The target pointer size.
     ptr_size: OperandSize,
+    /// Shared flags.
+    shared_flags: settings::Flags,
 }
 
 impl MacroAssembler {
     /// Create an Aarch64 MacroAssembler.
     pub fn new(ptr_size: impl PtrSize, shared_flags: settings::Flags) -> Self {
         Self {
-            asm: Assembler::new(shared_flags),
+            asm: Assembler::new(shared_flags.clone()),
             sp_offset: 0u32,
             ptr_size: ptr_type_from_ptr_size(ptr_size.size()).into(),
+            shared_flags,
         }
     }
 }
@@ -45,15 +58,63 @@ impl Masm for MacroAssembler {
         let addr = Address::pre_indexed_from_sp(-16);
 
         self.asm.stp(fp, lr, addr);
+        if self.shared_flags.unwind_info() {
+            self.asm.emit_unwind_inst(UnwindInst::PushFrameRegs {
+                offset_upward_to_caller_sp: <Self::ABI as abi::ABI>::arg_base_offset().into(),
+            })
+        }
+
         self.asm.mov_rr(sp, fp, OperandSize::S64);
+        if self.shared_flags.unwind_info() {
+            self.asm.emit_unwind_inst(UnwindInst::DefineNewFrame {
+                offset_upward_to_caller_sp: <Self::ABI as abi::ABI>::arg_base_offset().into(),
+                // The shadow stack pointer is saved right after the frame
+                // pointer and the link register.
+                offset_downward_to_clobbers: 16,
+            })
+        }
+
+        // Save the shadow stack pointer, keeping the real stack
+        // pointer 16-byte aligned.
+        let shadow_sp = regs::shadow_sp();
+        self.asm.str(
+            shadow_sp,
+            Address::pre_indexed_from_sp(-16),
+            OperandSize::S64,
+            TRUSTED_FLAGS,
+        );
+        if self.shared_flags.unwind_info() {
+            self.asm.emit_unwind_inst(UnwindInst::SaveReg {
+                clobber_offset: 0,
+                reg: shadow_sp.into(),
+            })
+        }
+
         self.move_sp_to_shadow_sp();
     }
 
     fn check_stack(&mut self) {
-        // TODO: Implement when we have more complete assembler support.
+        let ptr_size: u8 = self.ptr_size.bytes().try_into().unwrap();
+        let scratch = regs::scratch();
+
+        self.load_ptr(
+            self.address_at_vmctx(ptr_size.vmcontext_runtime_limits().into()),
+            scratch,
+        );
+
+        self.load_ptr(
+            Address::offset(scratch, ptr_size.vmruntime_limits_stack_limit().into()),
+            scratch,
+        );
+
+        self.asm.cmp_rr(scratch, regs::sp(), self.ptr_size);
+        self.asm.trapif(
+            CondBrKind::Cond(IntCmpKind::LtU.into()),
+            TrapCode::StackOverflow,
+        );
     }
 
-    fn epilogue(&mut self, locals_size: u32) {
+    fn epilogue(&mut self, locals_size: u32, callee_pop_size: u32) {
         assert!(self.sp_offset == locals_size);
 
         let sp = regs::sp();
@@ -63,11 +124,23 @@ impl Masm for MacroAssembler {
             self.move_sp_to_shadow_sp();
         }
 
+        self.asm.uload(
+            Address::post_indexed_from_sp(16),
+            regs::shadow_sp(),
+            OperandSize::S64,
+            TRUSTED_FLAGS,
+        );
+
         let lr = regs::lr();
         let fp = regs::fp();
         let addr = Address::post_indexed_from_sp(16);
 
         self.asm.ldp(fp, lr, addr);
+        if callee_pop_size > 0 {
+            let sp = regs::sp();
+            self.asm
+                .add_ir(callee_pop_size as u64, sp, sp, OperandSize::S64);
+        }
         self.asm.ret();
     }
 
@@ -83,8 +156,16 @@ impl Masm for MacroAssembler {
         self.increment_sp(bytes);
     }
 
-    fn free_stack(&mut self, _bytes: u32) {
-        todo!()
+    fn free_stack(&mut self, bytes: u32) {
+        if bytes == 0 {
+            return;
+        }
+
+        let sp = regs::sp();
+        self.asm.add_ir(bytes as u64, sp, sp, OperandSize::S64);
+        self.move_sp_to_shadow_sp();
+
+        self.decrement_sp(bytes);
     }
 
     fn reset_stack_pointer(&mut self, offset: SPOffset) {
@@ -108,32 +189,125 @@ impl Masm for MacroAssembler {
 
     fn table_elem_address(
         &mut self,
-        _index: Reg,
-        _base: Reg,
-        _table_data: &TableData,
-        _context: &mut CodeGenContext,
+        index: Reg,
+        ptr_base: Reg,
+        table_data: &TableData,
+        context: &mut CodeGenContext,
     ) -> Self::Address {
-        todo!()
+        let vmctx = <Self::ABI as abi::ABI>::vmctx_reg();
+        let scratch = regs::scratch();
+        let bound = context.any_gpr(self);
+        let tmp = context.any_gpr(self);
+
+        if let Some(offset) = table_data.import_from {
+            // If the table data declares a particular offset base,
+            // load the address into a register to further use it as
+            // the table address.
+            self.load_ptr(self.address_at_vmctx(offset), ptr_base);
+        } else {
+            // Else, simply move the vmctx register into the addr register as
+            // the base to calculate the table address.
+            self.asm.mov_rr(vmctx, ptr_base, self.ptr_size);
+        };
+
+        // OOB check.
+        let bound_addr = self.address_at_reg(ptr_base, table_data.current_elems_offset);
+        let bound_size = table_data.current_elements_size;
+        self.load(bound_addr, bound, bound_size);
+        self.asm.cmp_rr(bound, index, bound_size);
+        self.asm.trapif(
+            CondBrKind::Cond(IntCmpKind::GeU.into()),
+            TrapCode::TableOutOfBounds,
+        );
+
+        // Calculate the offset of the table element into the scratch
+        // register. The index is kept intact.
+        self.asm
+            .load_constant(table_data.element_size.bytes() as u64, scratch);
+        self.asm
+            .mul_rrr(scratch, index, scratch, table_data.element_size);
+        self.load_ptr(self.address_at_reg(ptr_base, table_data.offset), ptr_base);
+        // Copy the value of the table base into a temporary register
+        // so that we can use it later in case of a misspeculation.
+        self.asm.mov_rr(ptr_base, tmp, self.ptr_size);
+        // Calculate the address of the table element.
+        self.asm.add_rrr(scratch, ptr_base, ptr_base, self.ptr_size);
+        if self.shared_flags.enable_table_access_spectre_mitigation() {
+            // Perform a bounds check and override the value of the
+            // table element address in case the index is out of bounds.
+            self.asm.cmp_rr(bound, index, OperandSize::S32);
+            self.asm
+                .csel(tmp, ptr_base, ptr_base, IntCmpKind::GeU.into());
+        }
+        context.free_reg(bound);
+        context.free_reg(tmp);
+        self.address_at_reg(ptr_base, 0)
     }
 
-    fn table_size(&mut self, _table_data: &TableData, _context: &mut CodeGenContext) {
-        todo!()
+    fn table_size(&mut self, table_data: &TableData, context: &mut CodeGenContext) {
+        let vmctx = <Self::ABI as abi::ABI>::vmctx_reg();
+        let scratch = regs::scratch();
+        let size = context.any_gpr(self);
+
+        if let Some(offset) = table_data.import_from {
+            self.load_ptr(self.address_at_vmctx(offset), scratch);
+        } else {
+            self.asm.mov_rr(vmctx, scratch, self.ptr_size);
+        };
+
+        let size_addr = Address::offset(scratch, table_data.current_elems_offset.into());
+        self.load(size_addr, size, table_data.current_elements_size);
+
+        context.stack.push(TypedReg::i32(size).into());
     }
 
-    fn memory_size(&mut self, _heap_data: &HeapData, _context: &mut CodeGenContext) {
-        todo!()
+    fn memory_size(&mut self, heap_data: &HeapData, context: &mut CodeGenContext) {
+        let size_reg = context.any_gpr(self);
+        let scratch = regs::scratch();
+        let vmctx = <Self::ABI as abi::ABI>::vmctx_reg();
+
+        let base = if let Some(offset) = heap_data.import_from {
+            self.load_ptr(self.address_at_vmctx(offset), scratch);
+            scratch
+        } else {
+            vmctx
+        };
+
+        let size_addr = Address::offset(base, heap_data.current_length_offset.into());
+        self.load_ptr(size_addr, size_reg);
+        // Prepare the stack to emit a shift to get the size in pages rather
+        // than in bytes.
+        context
+            .stack
+            .push(TypedReg::new(heap_data.ty, size_reg).into());
+
+        // Since the page size is a power-of-two, verify that 2^16, equals the
+        // defined constant. This is mostly a safeguard in case the constant
+        // value ever changes.
+        let pow = 16;
+        debug_assert_eq!(2u32.pow(pow), WASM_PAGE_SIZE);
+
+        // Ensure that the constant is correctly typed according to the heap
+        // type to reduce register pressure when emitting the shift operation.
+        match heap_data.ty {
+            WasmValType::I32 => context.stack.push(Val::i32(pow as i32)),
+            WasmValType::I64 => context.stack.push(Val::i64(pow as i64)),
+            _ => unreachable!(),
+        }
+
+        self.shift(context, ShiftKind::ShrU, heap_data.ty.into());
     }
 
-    fn address_from_sp(&self, _offset: SPOffset) -> Self::Address {
-        todo!()
+    fn address_from_sp(&self, offset: SPOffset) -> Self::Address {
+        Address::from_shadow_sp((self.sp_offset - offset.as_u32()) as i64)
     }
 
-    fn address_at_sp(&self, _offset: SPOffset) -> Self::Address {
-        todo!()
+    fn address_at_sp(&self, offset: SPOffset) -> Self::Address {
+        Address::from_shadow_sp(offset.as_u32() as i64)
     }
 
-    fn address_at_vmctx(&self, _offset: u32) -> Self::Address {
-        todo!()
+    fn address_at_vmctx(&self, offset: u32) -> Self::Address {
+        Address::offset(<Self::ABI as abi::ABI>::vmctx_reg(), offset as i64)
     }
 
     fn store_ptr(&mut self, src: Reg, dst: Self::Address) {
@@ -141,59 +315,225 @@ impl Masm for MacroAssembler {
     }
 
     fn store(&mut self, src: RegImm, dst: Address, size: OperandSize) {
-        let src = match src {
-            RegImm::Imm(v) => {
-                let imm = match v {
-                    I::I32(v) => v as u64,
-                    I::I64(v) => v,
-                    _ => unreachable!(),
-                };
-                let scratch = regs::scratch();
-                self.asm.load_constant(imm, scratch);
-                scratch
-            }
-            RegImm::Reg(reg) => reg,
-        };
-
-        self.asm.str(src, dst, size);
+        self.store_impl(src, dst, size, TRUSTED_FLAGS);
     }
 
-    fn wasm_store(&mut self, _src: Reg, _dst: Self::Address, _size: OperandSize) {
-        todo!()
+    fn wasm_store(&mut self, src: Reg, dst: Self::Address, size: OperandSize) {
+        self.store_impl(src.into(), dst, size, UNTRUSTED_FLAGS);
     }
 
     fn call(
         &mut self,
-        _stack_args_size: u32,
-        _load_callee: impl FnMut(&mut Self) -> CalleeKind,
+        stack_args_size: u32,
+        call_conv: &CallingConvention,
+        mut load_callee: impl FnMut(&mut Self) -> CalleeKind,
     ) -> u32 {
-        todo!()
+        let alignment: u32 = <Self::ABI as abi::ABI>::call_stack_align().into();
+        let addend: u32 = <Self::ABI as abi::ABI>::arg_base_offset().into();
+        let delta = calculate_frame_adjustment(self.sp_offset().as_u32(), addend, alignment);
+        let aligned_args_size = align_to(stack_args_size, alignment);
+        let total_stack = delta + aligned_args_size;
+        self.reserve_stack(total_stack);
+        let callee = load_callee(self);
+        match callee {
+            CalleeKind::Indirect(reg) => self.asm.call_with_reg(reg),
+            CalleeKind::Direct(idx) => self.asm.call_with_index(idx),
+            CalleeKind::Known(lib) => self.asm.call_with_lib(lib),
+        };
+
+        if call_conv.callee_pops_stack_args() && aligned_args_size > 0 {
+            // The callee deallocated the stack arguments when returning;
+            // re-synchronize the shadow stack pointer, which was restored to
+            // its value at the callsite.
+            self.decrement_sp(aligned_args_size);
+            self.move_sp_to_shadow_sp();
+            total_stack - aligned_args_size
+        } else {
+            total_stack
+        }
+    }
+
+    fn return_call(
+        &mut self,
+        context: &mut CodeGenContext,
+        callee: CalleeKind,
+        callee_regs: &HashSet<Reg>,
+        callee_stack_args_size: u32,
+        caller_stack_args_size: u32,
+    ) {
+        let fp = regs::fp();
+        let lr = regs::lr();
+        let shadow_sp = regs::shadow_sp();
+        // Addresses with large offsets are materialized in the scratch
+        // register, so the stack arguments are copied through an
+        // allocatable register and the caller's frame pointer is kept in
+        // the second intra-procedure-call register.
+        let tmp = context.without::<Reg, Self, _>(callee_regs, self, |cx, masm| cx.any_gpr(masm));
+        let caller_fp = regs::ip1();
+
+        // The callee's stack arguments end where the caller's do, right
+        // above the frame record.
+        let fp_to_callee_sp =
+            16 + i64::from(caller_stack_args_size) - i64::from(callee_stack_args_size);
+
+        // Load the frame record first, since it may get overwritten by the
+        // callee's stack arguments.
+        self.asm.uload(
+            Address::offset(fp, 0),
+            caller_fp,
+            OperandSize::S64,
+            TRUSTED_FLAGS,
+        );
+        self.asm
+            .uload(Address::offset(fp, 8), lr, OperandSize::S64, TRUSTED_FLAGS);
+
+        // Move the callee's stack arguments, at the top of the stack, over
+        // the caller's. The destination is always above the source, so the
+        // words are copied starting from the highest one.
+        let word_bytes = <Self::ABI as abi::ABI>::word_bytes();
+        for i in (0..i64::from(callee_stack_args_size / word_bytes as u32)).rev() {
+            let offset = i * word_bytes as i64;
+            self.asm.uload(
+                Address::offset(shadow_sp, offset),
+                tmp,
+                OperandSize::S64,
+                TRUSTED_FLAGS,
+            );
+            self.asm.str(
+                tmp,
+                Address::offset(fp, fp_to_callee_sp + offset),
+                OperandSize::S64,
+                TRUSTED_FLAGS,
+            );
+        }
+
+        // Restore the caller's shadow stack pointer, saved right below the
+        // frame record.
+        self.asm.uload(
+            Address::offset(fp, -16),
+            shadow_sp,
+            OperandSize::S64,
+            TRUSTED_FLAGS,
+        );
+
+        // Write the frame record right below the callee's stack arguments,
+        // so that popping it leaves the stack pointer pointing to them.
+        let frame_record = fp_to_callee_sp - 16;
+        if frame_record >= 0 {
+            self.asm
+                .add_ir(frame_record as u64, fp, fp, OperandSize::S64);
+        } else {
+            self.asm
+                .sub_ir(frame_record.unsigned_abs(), fp, fp, OperandSize::S64);
+        }
+        self.asm.str(
+            caller_fp,
+            Address::offset(fp, 0),
+            OperandSize::S64,
+            TRUSTED_FLAGS,
+        );
+        self.asm
+            .str(lr, Address::offset(fp, 8), OperandSize::S64, TRUSTED_FLAGS);
+
+        match callee {
+            CalleeKind::Indirect(reg) => self.asm.return_call_with_reg(reg),
+            CalleeKind::Direct(idx) => self.asm.return_call_with_index(idx),
+            CalleeKind::Known(_) => unreachable!("tail calls to libcalls are not supported"),
+        }
+
+        context.free_reg(tmp);
     }
 
     fn load(&mut self, src: Address, dst: Reg, size: OperandSize) {
-        self.asm.ldr(src, dst, size);
+        self.asm.uload(src, dst, size, TRUSTED_FLAGS);
     }
 
-    fn load_ptr(&mut self, _src: Self::Address, _dst: Reg) {
-        todo!()
+    fn load_ptr(&mut self, src: Self::Address, dst: Reg) {
+        self.load(src, dst, self.ptr_size);
     }
 
     fn wasm_load(
         &mut self,
-        _src: Self::Address,
-        _dst: Reg,
-        _size: OperandSize,
-        _kind: Option<ExtendKind>,
+        src: Self::Address,
+        dst: Reg,
+        size: OperandSize,
+        kind: Option<ExtendKind>,
     ) {
-        todo!()
+        if let Some(ext) = kind {
+            self.asm.sload(src, dst, ext, UNTRUSTED_FLAGS);
+        } else {
+            self.asm.uload(src, dst, size, UNTRUSTED_FLAGS);
+        }
+    }
+
+    fn load_addr(&mut self, src: Self::Address, dst: Reg, _size: OperandSize) {
+        self.asm.load_addr(src, dst);
+    }
+
+    fn pop(&mut self, dst: Reg, size: OperandSize) {
+        let addr = self.address_from_sp(SPOffset::from_u32(self.sp_offset));
+        self.load(addr, dst, size);
+        self.free_stack(<Self::ABI as abi::ABI>::word_bytes() as u32);
+    }
+
+    fn atomic_load(&mut self, src: Self::Address, dst: Reg, size: OperandSize) {
+        // `ldar` only accepts a base register, without an offset.
+        let scratch = regs::scratch();
+        self.asm.load_addr(src, scratch);
+        self.asm.ldar(scratch, dst, size, UNTRUSTED_FLAGS);
+    }
+
+    fn atomic_store(&mut self, src: Reg, dst: Self::Address, size: OperandSize) {
+        // `stlr` only accepts a base register, without an offset.
+        let scratch = regs::scratch();
+        self.asm.load_addr(dst, scratch);
+        self.asm.stlr(src, scratch, size, UNTRUSTED_FLAGS);
     }
 
-    fn load_addr(&mut self, _src: Self::Address, _dst: Reg, _size: OperandSize) {
-        todo!()
+    fn atomic_rmw(&mut self, context: &mut CodeGenContext, op: RmwOp, size: OperandSize) {
+        // The exclusive load/store loop works on fixed registers, see
+        // `Assembler::atomic_rmw_loop`.
+        let operand = context.pop_to_reg(self, Some(regs::xreg(26)));
+        let addr = context.pop_to_reg(self, Some(regs::xreg(25)));
+        let oldval = context.reg(regs::xreg(27), self);
+        let status = context.reg(regs::xreg(24), self);
+
+        self.asm.atomic_rmw_loop(op, size, UNTRUSTED_FLAGS);
+        // The loop clobbers the shadow stack pointer.
+        self.move_sp_to_shadow_sp();
+
+        context.free_reg(status);
+        context.free_reg(addr);
+        context.free_reg(operand.reg);
+        context.stack.push(TypedReg::new(operand.ty, oldval).into());
+    }
+
+    fn atomic_cas(&mut self, context: &mut CodeGenContext, size: OperandSize) {
+        // The exclusive load/store loop works on fixed registers, see
+        // `Assembler::atomic_cas_loop`. The replacement value must be in
+        // the shadow stack pointer register, which isn't allocatable, so
+        // it's first popped into x24, which the loop only clobbers after
+        // it has been moved.
+        let replacement = context.pop_to_reg(self, Some(regs::xreg(24)));
+        let expected = context.pop_to_reg(self, Some(regs::xreg(26)));
+        let addr = context.pop_to_reg(self, Some(regs::xreg(25)));
+        let oldval = context.reg(regs::xreg(27), self);
+
+        self.asm
+            .mov_rr(replacement.reg, regs::shadow_sp(), OperandSize::S64);
+        self.asm.atomic_cas_loop(size, UNTRUSTED_FLAGS);
+        self.move_sp_to_shadow_sp();
+
+        context.free_reg(replacement);
+        context.free_reg(addr);
+        context.free_reg(expected.reg);
+        context
+            .stack
+            .push(TypedReg::new(expected.ty, oldval).into());
     }
 
-    fn pop(&mut self, _dst: Reg, _size: OperandSize) {
-        todo!()
+    fn fence(&mut self) {
+        self.asm.fence();
     }
 
     fn sp_offset(&self) -> SPOffset {
@@ -207,24 +547,40 @@ impl Masm for MacroAssembler {
     fn mov(&mut self, src: RegImm, dst: Reg, size: OperandSize) {
         match (src, dst) {
             (RegImm::Imm(v), rd) => {
-                let imm = match v {
-                    I::I32(v) => v as u64,
-                    I::I64(v) => v,
-                    _ => panic!(),
-                };
-
                 let scratch = regs::scratch();
-                self.asm.load_constant(imm, scratch);
-                self.asm.mov_rr(scratch, rd, size);
-            }
-            (RegImm::Reg(rs), rd) => {
-                self.asm.mov_rr(rs, rd, size);
+                match v {
+                    I::I32(v) => {
+                        self.asm.load_constant(v as u64, scratch);
+                        self.asm.mov_rr(scratch, rd, size);
+                    }
+                    I::I64(v) => {
+                        self.asm.load_constant(v, scratch);
+                        self.asm.mov_rr(scratch, rd, size);
+                    }
+                    I::F32(v) => {
+                        self.asm.load_constant(v as u64, scratch);
+                        self.asm.mov_to_fpu(scratch, rd, size);
+                    }
+                    I::F64(v) => {
+                        self.asm.load_constant(v, scratch);
+                        self.asm.mov_to_fpu(scratch, rd, size);
+                    }
+                }
             }
+            (RegImm::Reg(rs), rd) => match (rs.class(), rd.class()) {
+                (RegClass::Int, RegClass::Int) => self.asm.mov_rr(rs, rd, size),
+                (RegClass::Float, RegClass::Float) => self.asm.fmov_rr(rs, rd),
+                _ => Self::handle_invalid_operand_combination(rs, rd),
+            },
         }
     }
 
-    fn cmov(&mut self, _src: Reg, _dst: Reg, _cc: IntCmpKind, _size: OperandSize) {
-        todo!()
+    fn cmov(&mut self, src: Reg, dst: Reg, cc: IntCmpKind, size: OperandSize) {
+        match (src.class(), dst.class()) {
+            (RegClass::Int, RegClass::Int) => self.asm.csel(src, dst, dst, cc.into()),
+            (RegClass::Float, RegClass::Float) => self.asm.fpu_csel(src, dst, dst, cc.into(), size),
+            _ => Self::handle_invalid_operand_combination(src, dst),
+        }
     }
 
     fn add(&mut self, dst: Reg, lhs: Reg, rhs: RegImm, size: OperandSize) {
@@ -245,15 +601,24 @@ impl Masm for MacroAssembler {
         }
     }
 
-    fn checked_uadd(
-        &mut self,
-        _dst: Reg,
-        _lhs: Reg,
-        _rhs: RegImm,
-        _size: OperandSize,
-        _trap: TrapCode,
-    ) {
-        todo!()
+    fn checked_uadd(&mut self, dst: Reg, lhs: Reg, rhs: RegImm, size: OperandSize, trap: TrapCode) {
+        match (rhs, lhs, dst) {
+            (RegImm::Imm(v), rn, rd) => {
+                let imm = match v {
+                    I::I32(v) => v as u64,
+                    I::I64(v) => v,
+                    _ => unreachable!(),
+                };
+
+                self.asm.adds_ir(imm, rn, rd, size);
+            }
+
+            (RegImm::Reg(rm), rn, rd) => {
+                self.asm.adds_rrr(rm, rn, rd, size);
+            }
+        }
+        // The carry flag is set on unsigned overflow.
+        self.asm.trapif(CondBrKind::Cond(Cond::Hs), trap);
     }
 
     fn sub(&mut self, dst: Reg, lhs: Reg, rhs: RegImm, size: OperandSize) {
@@ -292,156 +657,298 @@ impl Masm for MacroAssembler {
         }
     }
 
-    fn float_add(&mut self, _dst: Reg, _lhs: Reg, _rhs: Reg, _size: OperandSize) {
-        todo!()
+    fn float_add(&mut self, dst: Reg, lhs: Reg, rhs: Reg, size: OperandSize) {
+        self.asm.fadd_rrr(rhs, lhs, dst, size);
     }
 
-    fn float_sub(&mut self, _dst: Reg, _lhs: Reg, _rhs: Reg, _size: OperandSize) {
-        todo!()
+    fn float_sub(&mut self, dst: Reg, lhs: Reg, rhs: Reg, size: OperandSize) {
+        self.asm.fsub_rrr(rhs, lhs, dst, size);
     }
 
-    fn float_mul(&mut self, _dst: Reg, _lhs: Reg, _rhs: Reg, _size: OperandSize) {
-        todo!()
+    fn float_mul(&mut self, dst: Reg, lhs: Reg, rhs: Reg, size: OperandSize) {
+        self.asm.fmul_rrr(rhs, lhs, dst, size);
     }
 
-    fn float_div(&mut self, _dst: Reg, _lhs: Reg, _rhs: Reg, _size: OperandSize) {
-        todo!()
+    fn float_div(&mut self, dst: Reg, lhs: Reg, rhs: Reg, size: OperandSize) {
+        self.asm.fdiv_rrr(rhs, lhs, dst, size);
     }
 
-    fn float_min(&mut self, _dst: Reg, _lhs: Reg, _rhs: Reg, _size: OperandSize) {
-        todo!()
+    fn float_min(&mut self, dst: Reg, lhs: Reg, rhs: Reg, size: OperandSize) {
+        self.asm.fmin_rrr(rhs, lhs, dst, size);
     }
 
-    fn float_max(&mut self, _dst: Reg, _lhs: Reg, _rhs: Reg, _size: OperandSize) {
-        todo!()
+    fn float_max(&mut self, dst: Reg, lhs: Reg, rhs: Reg, size: OperandSize) {
+        self.asm.fmax_rrr(rhs, lhs, dst, size);
     }
 
-    fn float_copysign(&mut self, _dst: Reg, _lhs: Reg, _rhs: Reg, _size: OperandSize) {
-        todo!()
+    fn float_copysign(&mut self, dst: Reg, lhs: Reg, rhs: Reg, size: OperandSize) {
+        if dst != lhs {
+            self.asm.fmov_rr(lhs, dst);
+        }
+        self.asm.fcopysign(rhs, dst, size);
     }
 
-    fn float_neg(&mut self, _dst: Reg, _size: OperandSize) {
-        todo!()
+    fn float_neg(&mut self, dst: Reg, size: OperandSize) {
+        self.asm.fneg_rr(dst, dst, size);
     }
 
-    fn float_abs(&mut self, _dst: Reg, _size: OperandSize) {
-        todo!()
+    fn float_abs(&mut self, dst: Reg, size: OperandSize) {
+        self.asm.fabs_rr(dst, dst, size);
     }
 
-    fn float_round(
-        &mut self,
-        _mode: RoundingMode,
-        _context: &mut CodeGenContext,
-        _size: OperandSize,
-    ) {
-        todo!();
+    fn float_round(&mut self, mode: RoundingMode, context: &mut CodeGenContext, size: OperandSize) {
+        let src = context.pop_to_reg(self, None);
+        self.asm.fround_rr(src.into(), src.into(), mode, size);
+        context.stack.push(src.into());
     }
 
-    fn float_sqrt(&mut self, _dst: Reg, _src: Reg, _size: OperandSize) {
-        todo!()
+    fn float_sqrt(&mut self, dst: Reg, src: Reg, size: OperandSize) {
+        self.asm.fsqrt_rr(src, dst, size);
     }
 
-    fn and(&mut self, _dst: Reg, _lhs: Reg, _rhs: RegImm, _size: OperandSize) {
-        todo!()
-    }
+    fn and(&mut self, dst: Reg, lhs: Reg, rhs: RegImm, size: OperandSize) {
+        match (rhs, lhs, dst) {
+            (RegImm::Imm(v), rn, rd) => {
+                let imm = match v {
+                    I::I32(v) => v as u64,
+                    I::I64(v) => v,
+                    _ => unreachable!(),
+                };
+
+                self.asm.and_ir(imm, rn, rd, size);
+            }
 
-    fn or(&mut self, _dst: Reg, _lhs: Reg, _rhs: RegImm, _size: OperandSize) {
-        todo!()
+            (RegImm::Reg(rm), rn, rd) => {
+                self.asm.and_rrr(rm, rn, rd, size);
+            }
+        }
     }
 
-    fn xor(&mut self, _dst: Reg, _lhs: Reg, _rhs: RegImm, _size: OperandSize) {
-        todo!()
+    fn or(&mut self, dst: Reg, lhs: Reg, rhs: RegImm, size: OperandSize) {
+        match (rhs, lhs, dst) {
+            (RegImm::Imm(v), rn, rd) => {
+                let imm = match v {
+                    I::I32(v) => v as u64,
+                    I::I64(v) => v,
+                    _ => unreachable!(),
+                };
+
+                self.asm.or_ir(imm, rn, rd, size);
+            }
+
+            (RegImm::Reg(rm), rn, rd) => {
+                self.asm.or_rrr(rm, rn, rd, size);
+            }
+        }
     }
 
-    fn shift(&mut self, _context: &mut CodeGenContext, _kind: ShiftKind, _size: OperandSize) {
-        todo!()
+    fn xor(&mut self, dst: Reg, lhs: Reg, rhs: RegImm, size: OperandSize) {
+        match (rhs, lhs, dst) {
+            (RegImm::Imm(v), rn, rd) => {
+                let imm = match v {
+                    I::I32(v) => v as u64,
+                    I::I64(v) => v,
+                    _ => unreachable!(),
+                };
+
+                self.asm.xor_ir(imm, rn, rd, size);
+            }
+
+            (RegImm::Reg(rm), rn, rd) => {
+                self.asm.xor_rrr(rm, rn, rd, size);
+            }
+        }
     }
 
-    fn div(&mut self, _context: &mut CodeGenContext, _kind: DivKind, _size: OperandSize) {
-        todo!()
+    fn shift(&mut self, context: &mut CodeGenContext, kind: ShiftKind, size: OperandSize) {
+        let top = context.stack.peek().expect("value at stack top");
+
+        if size == OperandSize::S32 && top.is_i32_const() {
+            let val = context
+                .stack
+                .pop_i32_const()
+                .expect("i32 const value at stack top");
+            let typed_reg = context.pop_to_reg(self, None);
+
+            self.asm
+                .shift_ir(val as u64, typed_reg.into(), typed_reg.into(), kind, size);
+
+            context.stack.push(typed_reg.into());
+        } else if size == OperandSize::S64 && top.is_i64_const() {
+            let val = context
+                .stack
+                .pop_i64_const()
+                .expect("i64 const value at stack top");
+            let typed_reg = context.pop_to_reg(self, None);
+
+            self.asm
+                .shift_ir(val as u64, typed_reg.into(), typed_reg.into(), kind, size);
+
+            context.stack.push(typed_reg.into());
+        } else {
+            let src = context.pop_to_reg(self, None);
+            let dst = context.pop_to_reg(self, None);
+
+            self.asm
+                .shift_rrr(src.into(), dst.into(), dst.into(), kind, size);
+
+            context.free_reg(src);
+            context.stack.push(dst.into());
+        }
     }
 
-    fn rem(&mut self, _context: &mut CodeGenContext, _kind: RemKind, _size: OperandSize) {
-        todo!()
+    fn div(&mut self, context: &mut CodeGenContext, kind: DivKind, size: OperandSize) {
+        let divisor = context.pop_to_reg(self, None);
+        let dividend = context.pop_to_reg(self, None);
+        let signed = matches!(kind, DivKind::Signed);
+        self.extend_div_operands(divisor.into(), dividend.into(), signed, size);
+
+        // Aarch64 doesn't trap on division by zero nor on signed
+        // overflow, so emit explicit checks.
+        self.asm.trapif(
+            CondBrKind::Zero(divisor.reg.into()),
+            TrapCode::IntegerDivisionByZero,
+        );
+        if let DivKind::Signed = kind {
+            // Check if the divisor is -1.
+            self.asm.adds_ir(1, divisor.into(), regs::zero(), size);
+            // If it is, check if the dividend is the minimum value, by
+            // subtracting one and checking for overflow.
+            self.asm.ccmp_ir(
+                1,
+                dividend.into(),
+                NZCV::new(false, false, false, false),
+                Cond::Eq,
+                size,
+            );
+            self.asm
+                .trapif(CondBrKind::Cond(Cond::Vs), TrapCode::IntegerOverflow);
+        }
+
+        self.asm.div_rrr(
+            divisor.into(),
+            dividend.into(),
+            dividend.into(),
+            kind,
+            OperandSize::S64,
+        );
+        if signed && size == OperandSize::S32 {
+            // Clear the upper 32 bits of the sign extended result.
+            self.asm
+                .mov_rr(dividend.into(), dividend.into(), OperandSize::S32);
+        }
+
+        context.free_reg(divisor);
+        context.stack.push(dividend.into());
+    }
+
+    fn rem(&mut self, context: &mut CodeGenContext, kind: RemKind, size: OperandSize) {
+        let divisor = context.pop_to_reg(self, None);
+        let dividend = context.pop_to_reg(self, None);
+        let signed = matches!(kind, RemKind::Signed);
+        self.extend_div_operands(divisor.into(), dividend.into(), signed, size);
+
+        // The signed remainder of the minimum value and -1 is 0, which
+        // is the result of the instruction sequence emitted for the
+        // remainder, so only the divisor needs to be checked.
+        self.asm.trapif(
+            CondBrKind::Zero(divisor.reg.into()),
+            TrapCode::IntegerDivisionByZero,
+        );
+        self.asm.rem_rrr(
+            divisor.into(),
+            dividend.into(),
+            dividend.into(),
+            kind,
+            OperandSize::S64,
+        );
+        if signed && size == OperandSize::S32 {
+            // Clear the upper 32 bits of the sign extended result.
+            self.asm
+                .mov_rr(dividend.into(), dividend.into(), OperandSize::S32);
+        }
+
+        context.free_reg(divisor);
+        context.stack.push(dividend.into());
     }
 
     fn zero(&mut self, reg: Reg) {
         self.asm.load_constant(0, reg);
     }
 
-    fn popcnt(&mut self, _context: &mut CodeGenContext, _size: OperandSize) {
-        todo!()
+    fn popcnt(&mut self, context: &mut CodeGenContext, _size: OperandSize) {
+        let src = context.pop_to_reg(self, None);
+        self.asm.cnt(src.into(), src.into(), regs::float_scratch());
+        context.stack.push(src.into());
     }
 
     fn signed_truncate(
         &mut self,
-        _src: Reg,
-        _dst: Reg,
-        _src_size: OperandSize,
-        _dst_size: OperandSize,
-        _kind: TruncKind,
+        src: Reg,
+        dst: Reg,
+        src_size: OperandSize,
+        dst_size: OperandSize,
+        kind: TruncKind,
     ) {
-        todo!()
+        self.asm
+            .fpu_to_int(src, dst, src_size, dst_size, kind, true);
     }
 
     fn unsigned_truncate(
         &mut self,
-        _src: Reg,
-        _dst: Reg,
+        src: Reg,
+        dst: Reg,
         _tmp_fpr: Reg,
-        _src_size: OperandSize,
-        _dst_size: OperandSize,
-        _kind: TruncKind,
+        src_size: OperandSize,
+        dst_size: OperandSize,
+        kind: TruncKind,
     ) {
-        todo!()
+        self.asm
+            .fpu_to_int(src, dst, src_size, dst_size, kind, false);
     }
 
-    fn signed_convert(
-        &mut self,
-        _src: Reg,
-        _dst: Reg,
-        _src_size: OperandSize,
-        _dst_size: OperandSize,
-    ) {
-        todo!()
+    fn signed_convert(&mut self, src: Reg, dst: Reg, src_size: OperandSize, dst_size: OperandSize) {
+        self.asm.int_to_fpu(src, dst, src_size, dst_size, true);
     }
 
     fn unsigned_convert(
         &mut self,
-        _src: Reg,
-        _dst: Reg,
+        src: Reg,
+        dst: Reg,
         _tmp_gpr: Reg,
-        _src_size: OperandSize,
-        _dst_size: OperandSize,
+        src_size: OperandSize,
+        dst_size: OperandSize,
     ) {
-        todo!()
+        self.asm.int_to_fpu(src, dst, src_size, dst_size, false);
     }
 
-    fn reinterpret_float_as_int(&mut self, _src: Reg, _dst: Reg, _size: OperandSize) {
-        todo!()
+    fn reinterpret_float_as_int(&mut self, src: Reg, dst: Reg, size: OperandSize) {
+        self.asm.mov_from_vec(src, dst, size);
     }
 
-    fn reinterpret_int_as_float(&mut self, _src: Reg, _dst: Reg, _size: OperandSize) {
-        todo!()
+    fn reinterpret_int_as_float(&mut self, src: Reg, dst: Reg, size: OperandSize) {
+        self.asm.mov_to_fpu(src, dst, size);
     }
 
-    fn demote(&mut self, _src: Reg, _dst: Reg) {
-        todo!()
+    fn demote(&mut self, src: Reg, dst: Reg) {
+        self.asm
+            .cvt_float_to_float(src, dst, OperandSize::S64, OperandSize::S32);
     }
 
-    fn promote(&mut self, _src: Reg, _dst: Reg) {
-        todo!()
+    fn promote(&mut self, src: Reg, dst: Reg) {
+        self.asm
+            .cvt_float_to_float(src, dst, OperandSize::S32, OperandSize::S64);
     }
 
-    fn push(&mut self, reg: Reg, _size: OperandSize) -> StackSlot {
-        let size = <Self::ABI as abi::ABI>::word_bytes();
-        self.reserve_stack(size as u32);
-        let address = Address::from_shadow_sp(size as i64);
-        self.asm.str(reg, address, OperandSize::S64);
+    fn push(&mut self, reg: Reg, size: OperandSize) -> StackSlot {
+        let bytes = <Self::ABI as abi::ABI>::word_bytes() as u32;
+        self.reserve_stack(bytes);
+        let address = self.address_from_sp(SPOffset::from_u32(self.sp_offset));
+        self.asm.str(reg, address, size, TRUSTED_FLAGS);
 
         StackSlot {
             offset: SPOffset::from_u32(self.sp_offset),
-            size: size as u32,
+            size: bytes,
         }
     }
 
@@ -449,39 +956,68 @@ impl Masm for MacroAssembler {
         Address::offset(reg, offset as i64)
     }
 
-    fn cmp_with_set(&mut self, _src: RegImm, _dst: Reg, _kind: IntCmpKind, _size: OperandSize) {
-        todo!()
+    fn cmp_with_set(&mut self, src: RegImm, dst: Reg, kind: IntCmpKind, size: OperandSize) {
+        self.cmp(src, dst, size);
+        self.asm.cset(dst, kind.into());
     }
 
-    fn cmp(&mut self, _src: RegImm, _dest: Reg, _size: OperandSize) {
-        todo!()
+    fn cmp(&mut self, src: RegImm, dst: Reg, size: OperandSize) {
+        match src {
+            RegImm::Imm(v) => {
+                let imm = match v {
+                    I::I32(v) => v as u64,
+                    I::I64(v) => v,
+                    _ => unreachable!(),
+                };
+
+                self.asm.cmp_ir(imm, dst, size);
+            }
+            RegImm::Reg(rm) => {
+                self.asm.cmp_rr(rm, dst, size);
+            }
+        }
     }
 
     fn float_cmp_with_set(
         &mut self,
-        _src1: Reg,
-        _src2: Reg,
-        _dst: Reg,
-        _kind: FloatCmpKind,
-        _size: OperandSize,
+        src1: Reg,
+        src2: Reg,
+        dst: Reg,
+        kind: FloatCmpKind,
+        size: OperandSize,
     ) {
-        todo!()
+        // The conditions are chosen so that an unordered result, that
+        // is, when either of the operands is NaN, produces 0 for every
+        // comparison but not equal.
+        let cond = match kind {
+            FloatCmpKind::Eq => Cond::Eq,
+            FloatCmpKind::Ne => Cond::Ne,
+            FloatCmpKind::Lt => Cond::Mi,
+            FloatCmpKind::Gt => Cond::Gt,
+            FloatCmpKind::Le => Cond::Ls,
+            FloatCmpKind::Ge => Cond::Ge,
+        };
+        self.asm.fcmp(src1, src2, size);
+        self.asm.cset(dst, cond);
     }
 
-    fn clz(&mut self, _src: Reg, _dst: Reg, _size: OperandSize) {
-        todo!()
+    fn clz(&mut self, src: Reg, dst: Reg, size: OperandSize) {
+        self.asm.clz(src, dst, size);
     }
 
-    fn ctz(&mut self, _src: Reg, _dst: Reg, _size: OperandSize) {
-        todo!()
+    fn ctz(&mut self, src: Reg, dst: Reg, size: OperandSize) {
+        // The number of trailing zeros is the number of leading zeros
+        // of the value with its bits reversed.
+        self.asm.rbit(src, dst, size);
+        self.asm.clz(dst, dst, size);
     }
 
-    fn wrap(&mut self, _src: Reg, _dst: Reg) {
-        todo!()
+    fn wrap(&mut self, src: Reg, dst: Reg) {
+        self.asm.mov_rr(src, dst, OperandSize::S32);
     }
 
-    fn extend(&mut self, _src: Reg, _dst: Reg, _kind: ExtendKind) {
-        todo!()
+    fn extend(&mut self, src: Reg, dst: Reg, kind: ExtendKind) {
+        self.asm.extend(src, dst, kind);
     }
 
     fn get_label(&mut self) -> MachLabel {
@@ -495,37 +1031,61 @@ impl Masm for MacroAssembler {
 
     fn branch(
         &mut self,
-        _kind: IntCmpKind,
-        _lhs: RegImm,
-        _rhs: Reg,
-        _taken: MachLabel,
-        _size: OperandSize,
+        kind: IntCmpKind,
+        lhs: RegImm,
+        rhs: Reg,
+        taken: MachLabel,
+        size: OperandSize,
     ) {
-        todo!()
+        use IntCmpKind::*;
+
+        match &(lhs, rhs) {
+            (RegImm::Reg(rlhs), rrhs) => {
+                // If the comparision kind is zero or not zero and both operands
+                // are the same register, emit a test instruction. Else we emit
+                // a normal comparison.
+                if (kind == Eq || kind == Ne) && (rlhs == rrhs) {
+                    self.asm.tst_rr(*rlhs, *rrhs, size);
+                } else {
+                    self.cmp(lhs, rhs, size);
+                }
+            }
+            _ => self.cmp(lhs, rhs, size),
+        }
+        self.asm.jmp_if(CondBrKind::Cond(kind.into()), taken);
     }
 
-    fn jmp(&mut self, _target: MachLabel) {
-        todo!()
+    fn jmp(&mut self, target: MachLabel) {
+        self.asm.jmp(target);
     }
 
     fn unreachable(&mut self) {
-        todo!()
+        self.asm.udf(TrapCode::UnreachableCodeReached);
     }
 
-    fn jmp_table(&mut self, _targets: &[MachLabel], _index: Reg, _tmp: Reg) {
-        todo!()
+    fn jmp_table(&mut self, targets: &[MachLabel], index: Reg, tmp: Reg) {
+        // At least one default target.
+        assert!(targets.len() >= 1);
+        let default_index = targets.len() - 1;
+        let default = targets[default_index];
+        let rest = &targets[0..default_index];
+        // The jump table sequence branches to the default target if the
+        // index is greater or equal than the number of targets.
+        self.cmp(RegImm::i32(rest.len() as i32), index, OperandSize::S32);
+        let tmp1 = regs::scratch();
+        self.asm.jmp_table(rest, default, index, tmp1, tmp);
     }
 
-    fn trap(&mut self, _code: TrapCode) {
-        todo!()
+    fn trap(&mut self, code: TrapCode) {
+        self.asm.udf(code);
     }
 
-    fn trapz(&mut self, _src: Reg, _code: TrapCode) {
-        todo!()
+    fn trapz(&mut self, src: Reg, code: TrapCode) {
+        self.asm.trapif(CondBrKind::Zero(src.into()), code);
     }
 
-    fn trapif(&mut self, _cc: IntCmpKind, _code: TrapCode) {
-        todo!()
+    fn trapif(&mut self, cc: IntCmpKind, code: TrapCode) {
+        self.asm.trapif(CondBrKind::Cond(cc.into()), code);
     }
 }
 
@@ -534,6 +1094,16 @@ impl MacroAssembler {
         self.sp_offset += bytes;
     }
 
+    fn decrement_sp(&mut self, bytes: u32) {
+        assert!(
+            self.sp_offset >= bytes,
+            "sp offset = {}; bytes = {}",
+            self.sp_offset,
+            bytes
+        );
+        self.sp_offset -= bytes;
+    }
+
     // Copies the value of the stack pointer to the shadow stack
     // pointer: mov x28, sp
 
@@ -547,4 +1117,60 @@ impl MacroAssembler {
         let shadow_sp = regs::shadow_sp();
         self.asm.mov_rr(sp, shadow_sp, OperandSize::S64);
     }
+
+    /// Extends the operands of a 32-bit division or remainder to 64
+    /// bits, since the division instructions are emitted with 64-bit
+    /// operands only.
+    fn extend_div_operands(
+        &mut self,
+        divisor: Reg,
+        dividend: Reg,
+        signed: bool,
+        size: OperandSize,
+    ) {
+        if size == OperandSize::S32 {
+            let kind = || {
+                if signed {
+                    ExtendKind::I64ExtendI32S
+                } else {
+                    ExtendKind::I64ExtendI32U
+                }
+            };
+            self.asm.extend(divisor, divisor, kind());
+            self.asm.extend(dividend, dividend, kind());
+        }
+    }
+
+    /// A common implemenation for stores.
+    fn store_impl(&mut self, src: RegImm, dst: Address, size: OperandSize, flags: MemFlags) {
+        let src = match src {
+            RegImm::Imm(v) => {
+                let imm = match v {
+                    I::I32(v) | I::F32(v) => v as u64,
+                    I::I64(v) | I::F64(v) => v,
+                };
+                let scratch = regs::scratch();
+                self.asm.load_constant(imm, scratch);
+                scratch
+            }
+            // The stack pointer can't be used as the source of a store,
+            // since its encoding is interpreted as the zero register.
+            RegImm::Reg(reg) if reg == regs::sp() => {
+                let tmp = regs::ip1();
+                self.asm.mov_rr(reg, tmp, OperandSize::S64);
+                tmp
+            }
+            RegImm::Reg(reg) => reg,
+        };
+
+        self.asm.str(src, dst, size, flags);
+    }
+
+    fn handle_invalid_operand_combination<T>(src: impl Into<RegImm>, dst: Reg) -> T {
+        panic!(
+            "Invalid operand combination; src={:?}, dst={:?}",
+            src.into(),
+            dst
+        );
+    }
 }
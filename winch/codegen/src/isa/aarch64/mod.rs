@@ -1,4 +1,4 @@
-use self::regs::{ALL_GPR, MAX_FPR, MAX_GPR, NON_ALLOCATABLE_GPR};
+use self::regs::{ALL_FPR, ALL_GPR, MAX_FPR, MAX_GPR, NON_ALLOCATABLE_FPR, NON_ALLOCATABLE_GPR};
 use crate::{
     abi::ABI,
     codegen::{CodeGen, CodeGenContext, FuncEnv},
@@ -8,6 +8,7 @@ use crate::{
     regalloc::RegAlloc,
     regset::RegBitSet,
     stack::Stack,
+    trampoline::Trampoline,
     BuiltinFunctions, TrampolineKind,
 };
 use anyhow::Result;
@@ -39,8 +40,6 @@ pub(crate) fn isa_builder(triple: Triple) -> Builder {
 }
 
 /// Aarch64 ISA.
-// Until Aarch64 emission is supported.
-#[allow(dead_code)]
 pub(crate) struct Aarch64 {
     /// The target triple.
     triple: Triple,
@@ -106,8 +105,11 @@ impl TargetIsa for Aarch64 {
             NON_ALLOCATABLE_GPR.into(),
             usize::try_from(MAX_GPR).unwrap(),
         );
-        // TODO: Add floating point bitmask
-        let fpr = RegBitSet::float(0, 0, usize::try_from(MAX_FPR).unwrap());
+        let fpr = RegBitSet::float(
+            ALL_FPR.into(),
+            NON_ALLOCATABLE_FPR.into(),
+            usize::try_from(MAX_FPR).unwrap(),
+        );
         let regalloc = RegAlloc::from(gpr, fpr);
         let codegen_context = CodeGenContext::new(regalloc, stack, frame, builtins, &vmoffsets);
         let mut codegen = CodeGen::new(&mut masm, codegen_context, env, abi_sig);
@@ -129,17 +131,42 @@ impl TargetIsa for Aarch64 {
 
     fn compile_trampoline(
         &self,
-        _ty: &WasmFuncType,
-        _kind: TrampolineKind,
+        ty: &WasmFuncType,
+        kind: TrampolineKind,
     ) -> Result<MachBufferFinalized<Final>> {
-        todo!()
+        use TrampolineKind::*;
+
+        let mut masm = Aarch64Masm::new(self.pointer_bytes(), self.shared_flags.clone());
+        let call_conv = self.wasmtime_call_conv();
+
+        let mut trampoline = Trampoline::new(
+            &mut masm,
+            regs::scratch(),
+            regs::argv(),
+            &call_conv,
+            self.pointer_bytes(),
+        );
+
+        match kind {
+            ArrayToWasm(idx) => trampoline.emit_array_to_wasm(ty, idx)?,
+            NativeToWasm(idx) => trampoline.emit_native_to_wasm(ty, idx)?,
+            WasmToNative => trampoline.emit_wasm_to_native(ty)?,
+        }
+
+        Ok(masm.finalize())
     }
 
     fn emit_unwind_info(
         &self,
-        _result: &MachBufferFinalized<Final>,
-        _kind: cranelift_codegen::isa::unwind::UnwindInfoKind,
+        buffer: &MachBufferFinalized<Final>,
+        kind: cranelift_codegen::isa::unwind::UnwindInfoKind,
     ) -> Result<Option<cranelift_codegen::isa::unwind::UnwindInfo>> {
-        todo!()
+        Ok(cranelift_codegen::isa::aarch64::emit_unwind_info(
+            buffer, kind,
+        )?)
+    }
+
+    fn create_systemv_cie(&self) -> Option<gimli::write::CommonInformationEntry> {
+        Some(cranelift_codegen::isa::aarch64::create_cie())
     }
 }
@@ -38,6 +38,27 @@ pub(crate) const fn ip1() -> Reg {
     xreg(17)
 }
 
+/// This register is used as a scratch register, in the context of trampolines only,
+/// where we assume that callee-saved registers are given the correct handling
+/// according to the system ABI. x19 is chosen given that it's a callee-saved,
+/// non-argument register.
+///
+/// In the context of all other internal functions, this register is not excluded
+/// from register allocation, so no extra assumptions should be made regarding
+/// its availability.
+pub(crate) const fn argv() -> Reg {
+    xreg(19)
+}
+
+/// Scratch register for floating point operations.
+///
+/// This register is excluded from register allocation and is used
+/// when a temporary FPR is needed to materialize a constant or an
+/// intermediate value, e.g. during float to integer truncation.
+pub(crate) const fn float_scratch() -> Reg {
+    vreg(31)
+}
+
 /// Register used to carry platform state.
 const fn platform() -> Reg {
     xreg(18)
@@ -114,6 +135,11 @@ pub(crate) const fn sp() -> Reg {
 /// +-----------+----- At epilogue restore x28 (callee-saved)
 /// +-----------+
 ///
+/// Since Winch-compiled functions call each other directly, x28 is
+/// saved right after the frame pointer and link register during the
+/// function prologue and restored in the epilogue, so that callers
+/// can keep relying on it after a call returns.
+///
 /// In summary, the following invariants must be respected:
 ///
 /// * The real stack pointer is always primary, and must be used to
@@ -149,11 +175,18 @@ pub(crate) const NON_ALLOCATABLE_GPR: u32 = (1 << ip0().hw_enc())
 /// Bitmask to represent the available general purpose registers.
 pub(crate) const ALL_GPR: u32 = u32::MAX & !NON_ALLOCATABLE_GPR;
 
+/// Bitmask for non-allocatable FPR.
+pub(crate) const NON_ALLOCATABLE_FPR: u32 = 1 << float_scratch().hw_enc();
+
+/// Bitmask to represent the available floating point registers.
+pub(crate) const ALL_FPR: u32 = u32::MAX & !NON_ALLOCATABLE_FPR;
+
 /// Returns the callee-saved registers.
 ///
 /// This function will return the set of registers that need to be saved
 /// according to the system ABI and that are known not to be saved during the
-/// prologue emission.
+/// prologue emission. The shadow stack pointer (x28) is saved as part of the
+/// prologue, so it's not included.
 pub(crate) fn callee_saved() -> SmallVec<[(Reg, OperandSize); 18]> {
     use OperandSize::*;
     let regs: SmallVec<[_; 18]> = smallvec![
@@ -166,7 +199,6 @@ pub(crate) fn callee_saved() -> SmallVec<[(Reg, OperandSize); 18]> {
         xreg(25),
         xreg(26),
         xreg(27),
-        xreg(28),
         vreg(8),
         vreg(9),
         vreg(10),
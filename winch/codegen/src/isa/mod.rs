@@ -94,8 +94,9 @@ pub enum CallingConvention {
     /// See [cranelift_codegen::isa::CallConv::AppleAarch64]
     AppleAarch64,
     /// The default calling convention for Winch. It largely follows SystemV
-    /// for parameter and result handling. This calling convention is part of
-    /// Winch's default ABI [crate::abi::ABI].
+    /// for parameter and result handling, except that the callee deallocates
+    /// its stack arguments. This calling convention is part of Winch's
+    /// default ABI [crate::abi::ABI].
     Default,
 }
 
@@ -131,6 +132,12 @@ impl CallingConvention {
             _ => false,
         }
     }
+
+    /// Returns true if functions using the current calling convention are
+    /// responsible for deallocating their stack arguments when returning.
+    pub fn callee_pops_stack_args(&self) -> bool {
+        self.is_default()
+    }
 }
 
 /// A trait representing commonalities between the supported
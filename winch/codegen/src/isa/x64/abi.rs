@@ -130,7 +130,7 @@ impl ABI for X64ABI {
             },
         );
 
-        ABISig::new(params, results)
+        ABISig::new(*call_conv, params, results)
     }
 
     fn sig(wasm_sig: &WasmFuncType, call_conv: &CallingConvention) -> ABISig {
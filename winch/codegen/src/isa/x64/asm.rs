@@ -2,7 +2,7 @@
 
 use crate::{
     isa::reg::Reg,
-    masm::{DivKind, ExtendKind, IntCmpKind, OperandSize, RemKind, RoundingMode, ShiftKind},
+    masm::{DivKind, ExtendKind, IntCmpKind, OperandSize, RemKind, RmwOp, RoundingMode, ShiftKind},
 };
 use cranelift_codegen::{
     entity::EntityRef,
@@ -13,17 +13,17 @@ use cranelift_codegen::{
         unwind::UnwindInst,
         x64::{
             args::{
-                self, AluRmiROpcode, Amode, CmpOpcode, DivSignedness, ExtMode, FromWritableReg,
-                Gpr, GprMem, GprMemImm, Imm8Gpr, Imm8Reg, RegMem, RegMemImm,
+                self, AluRmiROpcode, Amode, CmpOpcode, DivSignedness, ExtMode, FenceKind,
+                FromWritableReg, Gpr, GprMem, GprMemImm, Imm8Gpr, Imm8Reg, RegMem, RegMemImm,
                 ShiftKind as CraneliftShiftKind, SseOpcode, SyntheticAmode, WritableGpr,
                 WritableXmm, Xmm, XmmMem, XmmMemAligned, CC,
             },
-            settings as x64_settings, CallInfo, EmitInfo, EmitState, Inst,
+            settings as x64_settings, CallInfo, EmitInfo, EmitState, Inst, ReturnCallInfo,
         },
         CallConv,
     },
-    settings, Final, MachBuffer, MachBufferFinalized, MachInstEmit, MachInstEmitState, MachLabel,
-    VCodeConstantData, VCodeConstants, Writable,
+    settings, Final, MachAtomicRmwOp, MachBuffer, MachBufferFinalized, MachInstEmit,
+    MachInstEmitState, MachLabel, VCodeConstantData, VCodeConstants, Writable,
 };
 
 use super::address::Address;
@@ -137,6 +137,19 @@ impl From<ShiftKind> for CraneliftShiftKind {
     }
 }
 
+impl From<RmwOp> for MachAtomicRmwOp {
+    fn from(value: RmwOp) -> Self {
+        match value {
+            RmwOp::Add => MachAtomicRmwOp::Add,
+            RmwOp::Sub => MachAtomicRmwOp::Sub,
+            RmwOp::And => MachAtomicRmwOp::And,
+            RmwOp::Or => MachAtomicRmwOp::Or,
+            RmwOp::Xor => MachAtomicRmwOp::Xor,
+            RmwOp::Xchg => MachAtomicRmwOp::Xchg,
+        }
+    }
+}
+
 impl From<ExtendKind> for ExtMode {
     fn from(value: ExtendKind) -> Self {
         match value {
@@ -267,11 +280,10 @@ impl Assembler {
         self.emit(Inst::Pop64 { dst });
     }
 
-    /// Return instruction.
-    pub fn ret(&mut self) {
-        self.emit(Inst::Ret {
-            stack_bytes_to_pop: 0,
-        });
+    /// Return instruction, popping the given amount of bytes from the stack
+    /// after popping the return address.
+    pub fn ret(&mut self, stack_bytes_to_pop: u32) {
+        self.emit(Inst::Ret { stack_bytes_to_pop });
     }
 
     /// Register-to-register move.
@@ -1289,6 +1301,23 @@ impl Assembler {
         });
     }
 
+    /// Emit a tail call to a locally defined function through an index.
+    pub fn return_call_with_index(&mut self, index: u32, info: ReturnCallInfo) {
+        let callee = ExternalName::user(UserExternalNameRef::new(index as usize));
+        self.emit(Inst::ReturnCallKnown {
+            callee,
+            info: Box::new(info),
+        });
+    }
+
+    /// Emit a tail call to an unknown location through a register.
+    pub fn return_call_with_reg(&mut self, callee: Reg, info: ReturnCallInfo) {
+        self.emit(Inst::ReturnCallUnknown {
+            callee: RegMem::reg(callee.into()),
+            info: Box::new(info),
+        });
+    }
+
     /// Emits a conditional jump to the given label.
     pub fn jmp_if(&mut self, cc: impl Into<CC>, taken: MachLabel) {
         self.emit(Inst::JmpIf {
@@ -1333,6 +1362,80 @@ impl Assembler {
         });
     }
 
+    /// Atomic read-modify-write sequence, built around a `lock cmpxchg`
+    /// loop. The previous value at the memory location is written to
+    /// `dst_old`, which must be `rax`.
+    pub fn atomic_rmw_seq(
+        &mut self,
+        addr: &Address,
+        operand: Reg,
+        temp: Reg,
+        dst_old: Reg,
+        op: RmwOp,
+        size: OperandSize,
+        flags: MemFlags,
+    ) {
+        let mem = Self::to_synthetic_amode(
+            addr,
+            &mut self.pool,
+            &mut self.constants,
+            &mut self.buffer,
+            flags,
+        );
+        self.emit(Inst::AtomicRmwSeq {
+            ty: Self::int_type(size),
+            op: op.into(),
+            mem,
+            operand: operand.into(),
+            temp: Writable::from_reg(temp.into()),
+            dst_old: Writable::from_reg(dst_old.into()),
+        });
+    }
+
+    /// Locked compare-and-exchange. Both `expected` and `dst_old` must be
+    /// `rax`.
+    pub fn cmpxchg(
+        &mut self,
+        addr: &Address,
+        replacement: Reg,
+        expected: Reg,
+        dst_old: Reg,
+        size: OperandSize,
+        flags: MemFlags,
+    ) {
+        let mem = Self::to_synthetic_amode(
+            addr,
+            &mut self.pool,
+            &mut self.constants,
+            &mut self.buffer,
+            flags,
+        );
+        self.emit(Inst::LockCmpxchg {
+            ty: Self::int_type(size),
+            replacement: replacement.into(),
+            expected: expected.into(),
+            mem,
+            dst_old: Writable::from_reg(dst_old.into()),
+        });
+    }
+
+    /// Memory fence.
+    pub fn mfence(&mut self) {
+        self.emit(Inst::Fence {
+            kind: FenceKind::MFence,
+        });
+    }
+
+    fn int_type(size: OperandSize) -> types::Type {
+        match size {
+            OperandSize::S8 => types::I8,
+            OperandSize::S16 => types::I16,
+            OperandSize::S32 => types::I32,
+            OperandSize::S64 => types::I64,
+            OperandSize::S128 => unreachable!(),
+        }
+    }
+
     /// Load effective address.
     pub fn lea(&mut self, addr: &Address, dst: Reg, size: OperandSize) {
         let addr = Self::to_synthetic_amode(
@@ -7,7 +7,8 @@ use super::{
 
 use crate::masm::{
     DivKind, ExtendKind, FloatCmpKind, Imm as I, IntCmpKind, MacroAssembler as Masm, OperandSize,
-    RegImm, RemKind, RoundingMode, ShiftKind, TrapCode, TruncKind, TRUSTED_FLAGS, UNTRUSTED_FLAGS,
+    RegImm, RemKind, RmwOp, RoundingMode, ShiftKind, TrapCode, TruncKind, TRUSTED_FLAGS,
+    UNTRUSTED_FLAGS,
 };
 use crate::{
     abi::ABI,
@@ -20,7 +21,10 @@ use crate::{
     stack::Val,
 };
 use crate::{
-    isa::reg::{Reg, RegClass},
+    isa::{
+        reg::{Reg, RegClass},
+        CallingConvention,
+    },
     masm::CalleeKind,
 };
 use cranelift_codegen::{
@@ -28,10 +32,11 @@ use cranelift_codegen::{
     isa::unwind::UnwindInst,
     isa::x64::{
         args::{ExtMode, CC},
-        settings as x64_settings,
+        settings as x64_settings, ReturnCallInfo,
     },
     settings, Final, MachBufferFinalized, MachLabel,
 };
+use std::collections::HashSet;
 
 use wasmtime_environ::{PtrSize, WasmValType, WASM_PAGE_SIZE};
 
@@ -366,6 +371,7 @@ impl Masm for MacroAssembler {
     fn call(
         &mut self,
         stack_args_size: u32,
+        call_conv: &CallingConvention,
         mut load_callee: impl FnMut(&mut Self) -> CalleeKind,
     ) -> u32 {
         let alignment: u32 = <Self::ABI as abi::ABI>::call_stack_align().into();
@@ -380,7 +386,57 @@ impl Masm for MacroAssembler {
             CalleeKind::Direct(idx) => self.asm.call_with_index(idx),
             CalleeKind::Known(lib) => self.asm.call_with_lib(lib),
         };
-        total_stack
+
+        if call_conv.callee_pops_stack_args() {
+            // The callee deallocated the stack arguments when returning.
+            self.decrement_sp(aligned_args_size);
+            total_stack - aligned_args_size
+        } else {
+            total_stack
+        }
+    }
+
+    fn return_call(
+        &mut self,
+        context: &mut CodeGenContext,
+        callee: CalleeKind,
+        callee_regs: &HashSet<Reg>,
+        callee_stack_args_size: u32,
+        caller_stack_args_size: u32,
+    ) {
+        // The return address only needs to be moved if the size of the stack
+        // arguments differs between the caller and the callee.
+        let needs_ret_addr = callee_stack_args_size != caller_stack_args_size;
+        let (fp, ret_addr) =
+            context.without::<(Reg, Option<Reg>), Self, _>(callee_regs, self, |cx, masm| {
+                (cx.any_gpr(masm), needs_ret_addr.then(|| cx.any_gpr(masm)))
+            });
+
+        self.asm.mov_rr(rbp(), fp, OperandSize::S64);
+        if let Some(ret_addr) = ret_addr {
+            let addr = Address::offset(rbp(), self.ptr_size.bytes());
+            self.load(addr, ret_addr, OperandSize::S64);
+        }
+
+        let info = ReturnCallInfo {
+            new_stack_arg_size: callee_stack_args_size,
+            old_stack_arg_size: caller_stack_args_size,
+            ret_addr: ret_addr.map(Into::into),
+            fp: fp.into(),
+            tmp: regs::scratch().into(),
+            uses: Default::default(),
+        };
+
+        match callee {
+            CalleeKind::Indirect(reg) => self.asm.return_call_with_reg(reg, info),
+            CalleeKind::Direct(idx) => self.asm.return_call_with_index(idx, info),
+            CalleeKind::Known(_) => unreachable!("tail calls to libcalls are not supported"),
+        }
+
+        context.free_reg(fp);
+        if let Some(ret_addr) = ret_addr {
+            context.free_reg(ret_addr);
+        }
     }
 
     fn load_ptr(&mut self, src: Self::Address, dst: Reg) {
@@ -409,6 +465,79 @@ impl Masm for MacroAssembler {
         }
     }
 
+    fn atomic_load(&mut self, src: Self::Address, dst: Reg, size: OperandSize) {
+        // Aligned loads are sequentially consistent on x64, given that
+        // atomic stores are followed by a fence.
+        self.load_impl::<Self>(src, dst, size, UNTRUSTED_FLAGS)
+    }
+
+    fn atomic_store(&mut self, src: Reg, dst: Self::Address, size: OperandSize) {
+        self.store_impl(src.into(), dst, size, UNTRUSTED_FLAGS);
+        self.asm.mfence();
+    }
+
+    fn atomic_rmw(&mut self, context: &mut CodeGenContext, op: RmwOp, size: OperandSize) {
+        // The read-modify-write sequence loads the previous value into rax.
+        let rax = context.reg(regs::rax(), self);
+        let operand = context.pop_to_reg(self, None);
+        let addr = context.pop_to_reg(self, None);
+        let temp = context.any_gpr(self);
+
+        self.asm.atomic_rmw_seq(
+            &Address::offset(addr.reg, 0),
+            operand.reg,
+            temp,
+            rax,
+            op,
+            size,
+            UNTRUSTED_FLAGS,
+        );
+
+        context.free_reg(temp);
+        context.free_reg(addr);
+        context.free_reg(operand.reg);
+        context.stack.push(TypedReg::new(operand.ty, rax).into());
+    }
+
+    fn atomic_cas(&mut self, context: &mut CodeGenContext, size: OperandSize) {
+        // Allocate rax, which holds the expected value and receives the
+        // previous value at the memory location.
+        let rax = context.reg(regs::rax(), self);
+        let replacement = context.pop_to_reg(self, None);
+        // Mark rax as allocatable.
+        context.free_reg(rax);
+        // Move the expected value to rax.
+        let expected = context.pop_to_reg(self, Some(rax));
+        let addr = context.pop_to_reg(self, None);
+
+        self.asm.cmpxchg(
+            &Address::offset(addr.reg, 0),
+            replacement.reg,
+            rax,
+            rax,
+            size,
+            UNTRUSTED_FLAGS,
+        );
+
+        // `cmpxchg` leaves the accumulator untouched if the exchange
+        // succeeds, so clear the bits outside of the access size.
+        match size {
+            OperandSize::S8 => self.asm.and_ir(0xFF, rax, OperandSize::S32),
+            OperandSize::S16 => self.asm.and_ir(0xFFFF, rax, OperandSize::S32),
+            OperandSize::S32 => self.asm.mov_rr(rax, rax, OperandSize::S32),
+            OperandSize::S64 => {}
+            OperandSize::S128 => unreachable!(),
+        }
+
+        context.free_reg(addr);
+        context.free_reg(replacement);
+        context.stack.push(expected.into());
+    }
+
+    fn fence(&mut self) {
+        self.asm.mfence();
+    }
+
     fn sp_offset(&self) -> SPOffset {
         SPOffset::from_u32(self.sp_offset)
     }
@@ -770,7 +899,7 @@ impl Masm for MacroAssembler {
         context.stack.push(Val::reg(rdx, divisor.ty));
     }
 
-    fn epilogue(&mut self, locals_size: u32) {
+    fn epilogue(&mut self, locals_size: u32, callee_pop_size: u32) {
         assert_eq!(self.sp_offset, locals_size);
 
         let rsp = rsp();
@@ -778,7 +907,7 @@ impl Masm for MacroAssembler {
             self.asm.add_ir(locals_size as i32, rsp, OperandSize::S64);
         }
         self.asm.pop_r(rbp());
-        self.asm.ret();
+        self.asm.ret(callee_pop_size);
     }
 
     fn finalize(self) -> MachBufferFinalized<Final> {
@@ -1,11 +1,11 @@
 use crate::abi::{self, align_to, LocalSlot};
 use crate::codegen::{CodeGenContext, HeapData, TableData};
-use crate::isa::reg::Reg;
+use crate::isa::{reg::Reg, CallingConvention};
 use cranelift_codegen::{
     ir::{Endianness, LibCall, MemFlags},
     Final, MachBufferFinalized, MachLabel,
 };
-use std::{fmt::Debug, ops::Range};
+use std::{collections::HashSet, fmt::Debug, ops::Range};
 use wasmtime_environ::PtrSize;
 
 pub(crate) use cranelift_codegen::ir::TrapCode;
@@ -133,6 +133,25 @@ pub(crate) enum FloatCmpKind {
     Ge,
 }
 
+/// Kinds of atomic read-modify-write operations in WebAssembly. The [`masm`]
+/// implementation for each ISA is responsible for emitting the correct
+/// sequence of instructions when lowering to machine code.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub(crate) enum RmwOp {
+    /// Add.
+    Add,
+    /// Subtract.
+    Sub,
+    /// Bitwise and.
+    And,
+    /// Bitwise or.
+    Or,
+    /// Bitwise exclusive or.
+    Xor,
+    /// Exchange.
+    Xchg,
+}
+
 /// Kinds of shifts in WebAssembly.The [`masm`] implementation for each ISA is
 /// responsible for emitting the correct sequence of instructions when
 /// lowering to machine code.
@@ -400,8 +419,9 @@ pub(crate) trait MacroAssembler {
     /// Emit a stack check.
     fn check_stack(&mut self);
 
-    /// Emit the function epilogue.
-    fn epilogue(&mut self, locals_size: u32);
+    /// Emit the function epilogue, deallocating `callee_pop_size` bytes of
+    /// stack arguments when returning.
+    fn epilogue(&mut self, locals_size: u32, callee_pop_size: u32);
 
     /// Reserve stack space.
     fn reserve_stack(&mut self, bytes: u32);
@@ -453,7 +473,32 @@ pub(crate) trait MacroAssembler {
     fn address_at_reg(&self, reg: Reg, offset: u32) -> Self::Address;
 
     /// Emit a function call to either a local or external function.
-    fn call(&mut self, stack_args_size: u32, f: impl FnMut(&mut Self) -> CalleeKind) -> u32;
+    ///
+    /// Returns the stack space reserved for the call that must be freed by
+    /// the caller, which excludes the stack arguments space if the callee
+    /// deallocates it, according to the given calling convention.
+    fn call(
+        &mut self,
+        stack_args_size: u32,
+        call_conv: &CallingConvention,
+        f: impl FnMut(&mut Self) -> CalleeKind,
+    ) -> u32;
+
+    /// Emit a tail call, replacing the current frame with the callee's
+    /// frame and jumping to the callee.
+    ///
+    /// The callee's stack arguments, `callee_stack_args_size` bytes, must
+    /// be laid out at the top of the stack; they get moved over the current
+    /// function's stack arguments area, `caller_stack_args_size` bytes.
+    /// The given registers must not be used by the callee's arguments.
+    fn return_call(
+        &mut self,
+        context: &mut CodeGenContext,
+        callee: CalleeKind,
+        callee_regs: &HashSet<Reg>,
+        callee_stack_args_size: u32,
+        caller_stack_args_size: u32,
+    );
 
     /// Get stack pointer offset.
     fn sp_offset(&self) -> SPOffset;
@@ -498,6 +543,30 @@ pub(crate) trait MacroAssembler {
     /// to the pointer size of the target.
     fn load_ptr(&mut self, src: Self::Address, dst: Reg);
 
+    /// Perform a WebAssembly atomic load.
+    /// Narrow loads are zero-extended to the size of the destination
+    /// register.
+    fn atomic_load(&mut self, src: Self::Address, dst: Reg, size: OperandSize);
+
+    /// Perform a WebAssembly atomic store.
+    fn atomic_store(&mut self, src: Reg, dst: Self::Address, size: OperandSize);
+
+    /// Perform an atomic read-modify-write operation.
+    /// The value stack is expected to contain the address of the memory
+    /// location followed by the operand. The previous value at the memory
+    /// location, zero-extended from `size`, is pushed to the value stack.
+    fn atomic_rmw(&mut self, context: &mut CodeGenContext, op: RmwOp, size: OperandSize);
+
+    /// Perform an atomic compare-and-exchange.
+    /// The value stack is expected to contain the address of the memory
+    /// location followed by the expected and the replacement values. The
+    /// previous value at the memory location, zero-extended from `size`, is
+    /// pushed to the value stack.
+    fn atomic_cas(&mut self, context: &mut CodeGenContext, size: OperandSize);
+
+    /// Emit a sequentially consistent memory fence.
+    fn fence(&mut self);
+
     /// Loads the effective address into destination.
     fn load_addr(&mut self, _src: Self::Address, _dst: Reg, _size: OperandSize);
 
@@ -115,7 +115,8 @@ where
         let (offsets, spill_size) = self.spill(array_sig.params());
 
         // Call the function that was passed into the trampoline.
-        let allocated_stack = self.masm.call(wasm_sig.params_stack_size(), |masm| {
+        let args_size = wasm_sig.params_stack_size();
+        let allocated_stack = self.masm.call(args_size, &wasm_sig.call_conv, |masm| {
             // Save the SP when entering Wasm.
             // TODO: Once Winch supports comparison operators,
             // check that the caller VM context is what we expect.
@@ -211,7 +212,8 @@ where
         let ret_area = self.make_ret_area(&wasm_sig);
         let (offsets, spill_size) = self.spill(native_sig.params());
 
-        let reserved_stack = self.masm.call(wasm_sig.params_stack_size(), |masm| {
+        let args_size = wasm_sig.params_stack_size();
+        let reserved_stack = self.masm.call(args_size, &wasm_sig.call_conv, |masm| {
             // Save the SP when entering Wasm.
             // TODO: Once Winch supports comparison operators,
             // check that the caller VM context is what we expect.
@@ -393,7 +395,8 @@ where
         let ret_area = self.make_ret_area(&native_sig);
         let (offsets, spill_size) = self.spill(wasm_sig.params());
 
-        let reserved_stack = self.masm.call(native_sig.params_stack_size(), |masm| {
+        let args_size = native_sig.params_stack_size();
+        let reserved_stack = self.masm.call(args_size, &native_sig.call_conv, |masm| {
             // Move the VM context into one of the scratch registers.
             masm.mov(
                 vmctx.into(),
@@ -426,7 +429,7 @@ where
             self.masm.free_stack(native_sig.results.size());
         }
 
-        self.epilogue(spill_size);
+        self.epilogue(spill_size, wasm_sig.callee_pop_size::<M::ABI>());
 
         Ok(())
     }
@@ -721,13 +724,14 @@ where
         for (r, s) in self.callee_saved_regs.iter().rev() {
             self.masm.pop(*r, *s);
         }
-        self.masm.epilogue(0);
+        self.masm.epilogue(0, 0);
     }
 
-    /// The trampoline's epilogue.
-    fn epilogue(&mut self, arg_size: u32) {
+    /// The trampoline's epilogue, which deallocates `callee_pop_size` bytes
+    /// of stack arguments when returning.
+    fn epilogue(&mut self, arg_size: u32, callee_pop_size: u32) {
         // Free the stack space allocated by pushing the trampoline arguments.
         self.masm.free_stack(arg_size);
-        self.masm.epilogue(0);
+        self.masm.epilogue(0, callee_pop_size);
     }
 }
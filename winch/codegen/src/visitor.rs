@@ -8,7 +8,7 @@ use crate::abi::{RetArea, ABI};
 use crate::codegen::{control_index, Callee, CodeGen, ControlStackFrame, FnCall};
 use crate::masm::{
     DivKind, ExtendKind, FloatCmpKind, IntCmpKind, MacroAssembler, MemMoveDirection, OperandSize,
-    RegImm, RemKind, RoundingMode, SPOffset, ShiftKind, TruncKind,
+    RegImm, RemKind, RmwOp, RoundingMode, SPOffset, ShiftKind, TruncKind,
 };
 use crate::stack::{TypedReg, Val};
 use cranelift_codegen::ir::TrapCode;
@@ -193,6 +193,8 @@ macro_rules! def_unsupported {
     (emit Drop $($rest:tt)*) => {};
     (emit BrTable $($rest:tt)*) => {};
     (emit CallIndirect $($rest:tt)*) => {};
+    (emit ReturnCall $($rest:tt)*) => {};
+    (emit ReturnCallIndirect $($rest:tt)*) => {};
     (emit TableInit $($rest:tt)*) => {};
     (emit TableCopy $($rest:tt)*) => {};
     (emit TableGet $($rest:tt)*) => {};
@@ -239,6 +241,73 @@ macro_rules! def_unsupported {
     (emit I64TruncSatF32U $($rest:tt)*) => {};
     (emit I64TruncSatF64S $($rest:tt)*) => {};
     (emit I64TruncSatF64U $($rest:tt)*) => {};
+    (emit MemoryAtomicNotify $($rest:tt)*) => {};
+    (emit MemoryAtomicWait32 $($rest:tt)*) => {};
+    (emit MemoryAtomicWait64 $($rest:tt)*) => {};
+    (emit AtomicFence $($rest:tt)*) => {};
+    (emit I32AtomicLoad $($rest:tt)*) => {};
+    (emit I64AtomicLoad $($rest:tt)*) => {};
+    (emit I32AtomicLoad8U $($rest:tt)*) => {};
+    (emit I32AtomicLoad16U $($rest:tt)*) => {};
+    (emit I64AtomicLoad8U $($rest:tt)*) => {};
+    (emit I64AtomicLoad16U $($rest:tt)*) => {};
+    (emit I64AtomicLoad32U $($rest:tt)*) => {};
+    (emit I32AtomicStore $($rest:tt)*) => {};
+    (emit I64AtomicStore $($rest:tt)*) => {};
+    (emit I32AtomicStore8 $($rest:tt)*) => {};
+    (emit I32AtomicStore16 $($rest:tt)*) => {};
+    (emit I64AtomicStore8 $($rest:tt)*) => {};
+    (emit I64AtomicStore16 $($rest:tt)*) => {};
+    (emit I64AtomicStore32 $($rest:tt)*) => {};
+    (emit I32AtomicRmwAdd $($rest:tt)*) => {};
+    (emit I64AtomicRmwAdd $($rest:tt)*) => {};
+    (emit I32AtomicRmw8AddU $($rest:tt)*) => {};
+    (emit I32AtomicRmw16AddU $($rest:tt)*) => {};
+    (emit I64AtomicRmw8AddU $($rest:tt)*) => {};
+    (emit I64AtomicRmw16AddU $($rest:tt)*) => {};
+    (emit I64AtomicRmw32AddU $($rest:tt)*) => {};
+    (emit I32AtomicRmwSub $($rest:tt)*) => {};
+    (emit I64AtomicRmwSub $($rest:tt)*) => {};
+    (emit I32AtomicRmw8SubU $($rest:tt)*) => {};
+    (emit I32AtomicRmw16SubU $($rest:tt)*) => {};
+    (emit I64AtomicRmw8SubU $($rest:tt)*) => {};
+    (emit I64AtomicRmw16SubU $($rest:tt)*) => {};
+    (emit I64AtomicRmw32SubU $($rest:tt)*) => {};
+    (emit I32AtomicRmwAnd $($rest:tt)*) => {};
+    (emit I64AtomicRmwAnd $($rest:tt)*) => {};
+    (emit I32AtomicRmw8AndU $($rest:tt)*) => {};
+    (emit I32AtomicRmw16AndU $($rest:tt)*) => {};
+    (emit I64AtomicRmw8AndU $($rest:tt)*) => {};
+    (emit I64AtomicRmw16AndU $($rest:tt)*) => {};
+    (emit I64AtomicRmw32AndU $($rest:tt)*) => {};
+    (emit I32AtomicRmwOr $($rest:tt)*) => {};
+    (emit I64AtomicRmwOr $($rest:tt)*) => {};
+    (emit I32AtomicRmw8OrU $($rest:tt)*) => {};
+    (emit I32AtomicRmw16OrU $($rest:tt)*) => {};
+    (emit I64AtomicRmw8OrU $($rest:tt)*) => {};
+    (emit I64AtomicRmw16OrU $($rest:tt)*) => {};
+    (emit I64AtomicRmw32OrU $($rest:tt)*) => {};
+    (emit I32AtomicRmwXor $($rest:tt)*) => {};
+    (emit I64AtomicRmwXor $($rest:tt)*) => {};
+    (emit I32AtomicRmw8XorU $($rest:tt)*) => {};
+    (emit I32AtomicRmw16XorU $($rest:tt)*) => {};
+    (emit I64AtomicRmw8XorU $($rest:tt)*) => {};
+    (emit I64AtomicRmw16XorU $($rest:tt)*) => {};
+    (emit I64AtomicRmw32XorU $($rest:tt)*) => {};
+    (emit I32AtomicRmwXchg $($rest:tt)*) => {};
+    (emit I64AtomicRmwXchg $($rest:tt)*) => {};
+    (emit I32AtomicRmw8XchgU $($rest:tt)*) => {};
+    (emit I32AtomicRmw16XchgU $($rest:tt)*) => {};
+    (emit I64AtomicRmw8XchgU $($rest:tt)*) => {};
+    (emit I64AtomicRmw16XchgU $($rest:tt)*) => {};
+    (emit I64AtomicRmw32XchgU $($rest:tt)*) => {};
+    (emit I32AtomicRmwCmpxchg $($rest:tt)*) => {};
+    (emit I64AtomicRmwCmpxchg $($rest:tt)*) => {};
+    (emit I32AtomicRmw8CmpxchgU $($rest:tt)*) => {};
+    (emit I32AtomicRmw16CmpxchgU $($rest:tt)*) => {};
+    (emit I64AtomicRmw8CmpxchgU $($rest:tt)*) => {};
+    (emit I64AtomicRmw16CmpxchgU $($rest:tt)*) => {};
+    (emit I64AtomicRmw32CmpxchgU $($rest:tt)*) => {};
 
 
     (emit $unsupported:tt $($rest:tt)*) => {$($rest)*};
@@ -1326,6 +1395,38 @@ where
         })
     }
 
+    fn visit_return_call(&mut self, index: u32) {
+        let callee = self.env.callee_from_index(FuncIndex::from_u32(index));
+        FnCall::emit_return_call::<M, M::Ptr, _>(self.masm, &mut self.context, &self.sig, |_| {
+            callee.clone()
+        });
+        self.emit_after_return_call();
+    }
+
+    fn visit_return_call_indirect(&mut self, type_index: u32, table_index: u32) {
+        // Spill now for the same reasons as in `visit_call_indirect`.
+        self.context.spill(self.masm);
+
+        let type_index = TypeIndex::from_u32(type_index);
+        let table_index = TableIndex::from_u32(table_index);
+
+        self.emit_lazy_init_funcref(table_index);
+
+        match self.env.translation.module.table_plans[table_index].style {
+            TableStyle::CallerChecksSignature => {
+                let funcref_ptr = self.context.stack.peek().map(|v| v.unwrap_reg()).unwrap();
+                self.masm
+                    .trapz(funcref_ptr.into(), TrapCode::IndirectCallToNull);
+                self.emit_typecheck_funcref(funcref_ptr.into(), type_index);
+            }
+        }
+
+        FnCall::emit_return_call::<M, M::Ptr, _>(self.masm, &mut self.context, &self.sig, |_| {
+            self.env.funcref(type_index)
+        });
+        self.emit_after_return_call();
+    }
+
     fn visit_table_init(&mut self, elem: u32, table: u32) {
         let ptr_type = self.env.ptr_type();
         let vmctx = TypedReg::new(ptr_type, <M::ABI as ABI>::vmctx_reg());
@@ -2041,6 +2142,303 @@ where
         );
     }
 
+    fn visit_atomic_fence(&mut self) {
+        self.masm.fence();
+    }
+
+    fn visit_memory_atomic_notify(&mut self, memarg: MemArg) {
+        // The stack at this point contains: [ addr, count ]
+        // The desired state is
+        //   [ vmctx, memory, addr, count ]
+        let count = self.context.pop_to_reg(self.masm, None);
+        self.emit_atomic_builtin_args(&memarg);
+        self.context.stack.push(count.into());
+
+        FnCall::emit::<M, M::Ptr, _>(self.masm, &mut self.context, |cx| {
+            Callee::Builtin(cx.builtins.memory_atomic_notify::<M::ABI, M::Ptr>())
+        });
+    }
+
+    fn visit_memory_atomic_wait32(&mut self, memarg: MemArg) {
+        // The stack at this point contains: [ addr, expected, timeout ]
+        // The desired state is
+        //   [ vmctx, memory, addr, expected, timeout ]
+        let timeout = self.context.pop_to_reg(self.masm, None);
+        let expected = self.context.pop_to_reg(self.masm, None);
+        self.emit_atomic_builtin_args(&memarg);
+        self.context.stack.extend([expected.into(), timeout.into()]);
+
+        FnCall::emit::<M, M::Ptr, _>(self.masm, &mut self.context, |cx| {
+            Callee::Builtin(cx.builtins.memory_atomic_wait32::<M::ABI, M::Ptr>())
+        });
+    }
+
+    fn visit_memory_atomic_wait64(&mut self, memarg: MemArg) {
+        // The stack at this point contains: [ addr, expected, timeout ]
+        // The desired state is
+        //   [ vmctx, memory, addr, expected, timeout ]
+        let timeout = self.context.pop_to_reg(self.masm, None);
+        let expected = self.context.pop_to_reg(self.masm, None);
+        self.emit_atomic_builtin_args(&memarg);
+        self.context.stack.extend([expected.into(), timeout.into()]);
+
+        FnCall::emit::<M, M::Ptr, _>(self.masm, &mut self.context, |cx| {
+            Callee::Builtin(cx.builtins.memory_atomic_wait64::<M::ABI, M::Ptr>())
+        });
+    }
+
+    fn visit_i32_atomic_load(&mut self, memarg: MemArg) {
+        self.emit_wasm_atomic_load(&memarg, WasmValType::I32, OperandSize::S32)
+    }
+
+    fn visit_i64_atomic_load(&mut self, memarg: MemArg) {
+        self.emit_wasm_atomic_load(&memarg, WasmValType::I64, OperandSize::S64)
+    }
+
+    fn visit_i32_atomic_load8_u(&mut self, memarg: MemArg) {
+        self.emit_wasm_atomic_load(&memarg, WasmValType::I32, OperandSize::S8)
+    }
+
+    fn visit_i32_atomic_load16_u(&mut self, memarg: MemArg) {
+        self.emit_wasm_atomic_load(&memarg, WasmValType::I32, OperandSize::S16)
+    }
+
+    fn visit_i64_atomic_load8_u(&mut self, memarg: MemArg) {
+        self.emit_wasm_atomic_load(&memarg, WasmValType::I64, OperandSize::S8)
+    }
+
+    fn visit_i64_atomic_load16_u(&mut self, memarg: MemArg) {
+        self.emit_wasm_atomic_load(&memarg, WasmValType::I64, OperandSize::S16)
+    }
+
+    fn visit_i64_atomic_load32_u(&mut self, memarg: MemArg) {
+        self.emit_wasm_atomic_load(&memarg, WasmValType::I64, OperandSize::S32)
+    }
+
+    fn visit_i32_atomic_store(&mut self, memarg: MemArg) {
+        self.emit_wasm_atomic_store(&memarg, OperandSize::S32)
+    }
+
+    fn visit_i64_atomic_store(&mut self, memarg: MemArg) {
+        self.emit_wasm_atomic_store(&memarg, OperandSize::S64)
+    }
+
+    fn visit_i32_atomic_store8(&mut self, memarg: MemArg) {
+        self.emit_wasm_atomic_store(&memarg, OperandSize::S8)
+    }
+
+    fn visit_i32_atomic_store16(&mut self, memarg: MemArg) {
+        self.emit_wasm_atomic_store(&memarg, OperandSize::S16)
+    }
+
+    fn visit_i64_atomic_store8(&mut self, memarg: MemArg) {
+        self.emit_wasm_atomic_store(&memarg, OperandSize::S8)
+    }
+
+    fn visit_i64_atomic_store16(&mut self, memarg: MemArg) {
+        self.emit_wasm_atomic_store(&memarg, OperandSize::S16)
+    }
+
+    fn visit_i64_atomic_store32(&mut self, memarg: MemArg) {
+        self.emit_wasm_atomic_store(&memarg, OperandSize::S32)
+    }
+
+    fn visit_i32_atomic_rmw_add(&mut self, memarg: MemArg) {
+        self.emit_wasm_atomic_rmw(&memarg, RmwOp::Add, OperandSize::S32)
+    }
+
+    fn visit_i64_atomic_rmw_add(&mut self, memarg: MemArg) {
+        self.emit_wasm_atomic_rmw(&memarg, RmwOp::Add, OperandSize::S64)
+    }
+
+    fn visit_i32_atomic_rmw8_add_u(&mut self, memarg: MemArg) {
+        self.emit_wasm_atomic_rmw(&memarg, RmwOp::Add, OperandSize::S8)
+    }
+
+    fn visit_i32_atomic_rmw16_add_u(&mut self, memarg: MemArg) {
+        self.emit_wasm_atomic_rmw(&memarg, RmwOp::Add, OperandSize::S16)
+    }
+
+    fn visit_i64_atomic_rmw8_add_u(&mut self, memarg: MemArg) {
+        self.emit_wasm_atomic_rmw(&memarg, RmwOp::Add, OperandSize::S8)
+    }
+
+    fn visit_i64_atomic_rmw16_add_u(&mut self, memarg: MemArg) {
+        self.emit_wasm_atomic_rmw(&memarg, RmwOp::Add, OperandSize::S16)
+    }
+
+    fn visit_i64_atomic_rmw32_add_u(&mut self, memarg: MemArg) {
+        self.emit_wasm_atomic_rmw(&memarg, RmwOp::Add, OperandSize::S32)
+    }
+
+    fn visit_i32_atomic_rmw_sub(&mut self, memarg: MemArg) {
+        self.emit_wasm_atomic_rmw(&memarg, RmwOp::Sub, OperandSize::S32)
+    }
+
+    fn visit_i64_atomic_rmw_sub(&mut self, memarg: MemArg) {
+        self.emit_wasm_atomic_rmw(&memarg, RmwOp::Sub, OperandSize::S64)
+    }
+
+    fn visit_i32_atomic_rmw8_sub_u(&mut self, memarg: MemArg) {
+        self.emit_wasm_atomic_rmw(&memarg, RmwOp::Sub, OperandSize::S8)
+    }
+
+    fn visit_i32_atomic_rmw16_sub_u(&mut self, memarg: MemArg) {
+        self.emit_wasm_atomic_rmw(&memarg, RmwOp::Sub, OperandSize::S16)
+    }
+
+    fn visit_i64_atomic_rmw8_sub_u(&mut self, memarg: MemArg) {
+        self.emit_wasm_atomic_rmw(&memarg, RmwOp::Sub, OperandSize::S8)
+    }
+
+    fn visit_i64_atomic_rmw16_sub_u(&mut self, memarg: MemArg) {
+        self.emit_wasm_atomic_rmw(&memarg, RmwOp::Sub, OperandSize::S16)
+    }
+
+    fn visit_i64_atomic_rmw32_sub_u(&mut self, memarg: MemArg) {
+        self.emit_wasm_atomic_rmw(&memarg, RmwOp::Sub, OperandSize::S32)
+    }
+
+    fn visit_i32_atomic_rmw_and(&mut self, memarg: MemArg) {
+        self.emit_wasm_atomic_rmw(&memarg, RmwOp::And, OperandSize::S32)
+    }
+
+    fn visit_i64_atomic_rmw_and(&mut self, memarg: MemArg) {
+        self.emit_wasm_atomic_rmw(&memarg, RmwOp::And, OperandSize::S64)
+    }
+
+    fn visit_i32_atomic_rmw8_and_u(&mut self, memarg: MemArg) {
+        self.emit_wasm_atomic_rmw(&memarg, RmwOp::And, OperandSize::S8)
+    }
+
+    fn visit_i32_atomic_rmw16_and_u(&mut self, memarg: MemArg) {
+        self.emit_wasm_atomic_rmw(&memarg, RmwOp::And, OperandSize::S16)
+    }
+
+    fn visit_i64_atomic_rmw8_and_u(&mut self, memarg: MemArg) {
+        self.emit_wasm_atomic_rmw(&memarg, RmwOp::And, OperandSize::S8)
+    }
+
+    fn visit_i64_atomic_rmw16_and_u(&mut self, memarg: MemArg) {
+        self.emit_wasm_atomic_rmw(&memarg, RmwOp::And, OperandSize::S16)
+    }
+
+    fn visit_i64_atomic_rmw32_and_u(&mut self, memarg: MemArg) {
+        self.emit_wasm_atomic_rmw(&memarg, RmwOp::And, OperandSize::S32)
+    }
+
+    fn visit_i32_atomic_rmw_or(&mut self, memarg: MemArg) {
+        self.emit_wasm_atomic_rmw(&memarg, RmwOp::Or, OperandSize::S32)
+    }
+
+    fn visit_i64_atomic_rmw_or(&mut self, memarg: MemArg) {
+        self.emit_wasm_atomic_rmw(&memarg, RmwOp::Or, OperandSize::S64)
+    }
+
+    fn visit_i32_atomic_rmw8_or_u(&mut self, memarg: MemArg) {
+        self.emit_wasm_atomic_rmw(&memarg, RmwOp::Or, OperandSize::S8)
+    }
+
+    fn visit_i32_atomic_rmw16_or_u(&mut self, memarg: MemArg) {
+        self.emit_wasm_atomic_rmw(&memarg, RmwOp::Or, OperandSize::S16)
+    }
+
+    fn visit_i64_atomic_rmw8_or_u(&mut self, memarg: MemArg) {
+        self.emit_wasm_atomic_rmw(&memarg, RmwOp::Or, OperandSize::S8)
+    }
+
+    fn visit_i64_atomic_rmw16_or_u(&mut self, memarg: MemArg) {
+        self.emit_wasm_atomic_rmw(&memarg, RmwOp::Or, OperandSize::S16)
+    }
+
+    fn visit_i64_atomic_rmw32_or_u(&mut self, memarg: MemArg) {
+        self.emit_wasm_atomic_rmw(&memarg, RmwOp::Or, OperandSize::S32)
+    }
+
+    fn visit_i32_atomic_rmw_xor(&mut self, memarg: MemArg) {
+        self.emit_wasm_atomic_rmw(&memarg, RmwOp::Xor, OperandSize::S32)
+    }
+
+    fn visit_i64_atomic_rmw_xor(&mut self, memarg: MemArg) {
+        self.emit_wasm_atomic_rmw(&memarg, RmwOp::Xor, OperandSize::S64)
+    }
+
+    fn visit_i32_atomic_rmw8_xor_u(&mut self, memarg: MemArg) {
+        self.emit_wasm_atomic_rmw(&memarg, RmwOp::Xor, OperandSize::S8)
+    }
+
+    fn visit_i32_atomic_rmw16_xor_u(&mut self, memarg: MemArg) {
+        self.emit_wasm_atomic_rmw(&memarg, RmwOp::Xor, OperandSize::S16)
+    }
+
+    fn visit_i64_atomic_rmw8_xor_u(&mut self, memarg: MemArg) {
+        self.emit_wasm_atomic_rmw(&memarg, RmwOp::Xor, OperandSize::S8)
+    }
+
+    fn visit_i64_atomic_rmw16_xor_u(&mut self, memarg: MemArg) {
+        self.emit_wasm_atomic_rmw(&memarg, RmwOp::Xor, OperandSize::S16)
+    }
+
+    fn visit_i64_atomic_rmw32_xor_u(&mut self, memarg: MemArg) {
+        self.emit_wasm_atomic_rmw(&memarg, RmwOp::Xor, OperandSize::S32)
+    }
+
+    fn visit_i32_atomic_rmw_xchg(&mut self, memarg: MemArg) {
+        self.emit_wasm_atomic_rmw(&memarg, RmwOp::Xchg, OperandSize::S32)
+    }
+
+    fn visit_i64_atomic_rmw_xchg(&mut self, memarg: MemArg) {
+        self.emit_wasm_atomic_rmw(&memarg, RmwOp::Xchg, OperandSize::S64)
+    }
+
+    fn visit_i32_atomic_rmw8_xchg_u(&mut self, memarg: MemArg) {
+        self.emit_wasm_atomic_rmw(&memarg, RmwOp::Xchg, OperandSize::S8)
+    }
+
+    fn visit_i32_atomic_rmw16_xchg_u(&mut self, memarg: MemArg) {
+        self.emit_wasm_atomic_rmw(&memarg, RmwOp::Xchg, OperandSize::S16)
+    }
+
+    fn visit_i64_atomic_rmw8_xchg_u(&mut self, memarg: MemArg) {
+        self.emit_wasm_atomic_rmw(&memarg, RmwOp::Xchg, OperandSize::S8)
+    }
+
+    fn visit_i64_atomic_rmw16_xchg_u(&mut self, memarg: MemArg) {
+        self.emit_wasm_atomic_rmw(&memarg, RmwOp::Xchg, OperandSize::S16)
+    }
+
+    fn visit_i64_atomic_rmw32_xchg_u(&mut self, memarg: MemArg) {
+        self.emit_wasm_atomic_rmw(&memarg, RmwOp::Xchg, OperandSize::S32)
+    }
+
+    fn visit_i32_atomic_rmw_cmpxchg(&mut self, memarg: MemArg) {
+        self.emit_wasm_atomic_cmpxchg(&memarg, OperandSize::S32)
+    }
+
+    fn visit_i64_atomic_rmw_cmpxchg(&mut self, memarg: MemArg) {
+        self.emit_wasm_atomic_cmpxchg(&memarg, OperandSize::S64)
+    }
+
+    fn visit_i32_atomic_rmw8_cmpxchg_u(&mut self, memarg: MemArg) {
+        self.emit_wasm_atomic_cmpxchg(&memarg, OperandSize::S8)
+    }
+
+    fn visit_i32_atomic_rmw16_cmpxchg_u(&mut self, memarg: MemArg) {
+        self.emit_wasm_atomic_cmpxchg(&memarg, OperandSize::S16)
+    }
+
+    fn visit_i64_atomic_rmw8_cmpxchg_u(&mut self, memarg: MemArg) {
+        self.emit_wasm_atomic_cmpxchg(&memarg, OperandSize::S8)
+    }
+
+    fn visit_i64_atomic_rmw16_cmpxchg_u(&mut self, memarg: MemArg) {
+        self.emit_wasm_atomic_cmpxchg(&memarg, OperandSize::S16)
+    }
+
+    fn visit_i64_atomic_rmw32_cmpxchg_u(&mut self, memarg: MemArg) {
+        self.emit_wasm_atomic_cmpxchg(&memarg, OperandSize::S32)
+    }
+
     wasmparser::for_each_operator!(def_unsupported);
 }
 
@@ -2062,6 +2460,15 @@ where
                 TypedReg::i32(dst) // Return value for comparisons is an `i32`.
             });
     }
+
+    /// Marks the code following a tail call as unreachable. Similar to
+    /// `unreachable`, the implicit outermost frame is set as target to
+    /// perform the necessary stack clean up.
+    fn emit_after_return_call(&mut self) {
+        self.context.reachable = false;
+        let outermost = &mut self.control_frames[0];
+        outermost.set_as_target();
+    }
 }
 
 impl From<WasmValType> for OperandSize {
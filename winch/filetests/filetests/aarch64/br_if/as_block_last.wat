@@ -0,0 +1,47 @@
+;;! target = "aarch64"
+(module
+  (func $dummy)
+  (func (export "as-block-last") (param i32)
+    (block (call $dummy) (call $dummy) (br_if 0 (local.get 0)))
+  )
+)
+;;      	 fd7bbfa9             	stp	x29, x30, [sp, #-0x10]!
+;;      	 fd030091             	mov	x29, sp
+;;      	 fc0f1ff8             	str	x28, [sp, #-0x10]!
+;;      	 fc030091             	mov	x28, sp
+;;      	 ff2300d1             	sub	sp, sp, #8
+;;      	 fc030091             	mov	x28, sp
+;;      	 308140f8             	ldur	x16, [x9, #8]
+;;      	 100240f8             	ldur	x16, [x16]
+;;      	 ff6330eb             	cmp	sp, x16
+;;      	 e3000054             	b.lo	#0x40
+;;   28:	 890300f8             	stur	x9, [x28]
+;;      	 ff230091             	add	sp, sp, #8
+;;      	 fc030091             	mov	x28, sp
+;;      	 fc0741f8             	ldr	x28, [sp], #0x10
+;;      	 fd7bc1a8             	ldp	x29, x30, [sp], #0x10
+;;      	 c0035fd6             	ret	
+;;   40:	 1fc10000             	.byte	0x1f, 0xc1, 0x00, 0x00
+;;
+;;      	 fd7bbfa9             	stp	x29, x30, [sp, #-0x10]!
+;;      	 fd030091             	mov	x29, sp
+;;      	 fc0f1ff8             	str	x28, [sp, #-0x10]!
+;;      	 fc030091             	mov	x28, sp
+;;      	 ff4300d1             	sub	sp, sp, #0x10
+;;      	 fc030091             	mov	x28, sp
+;;      	 308140f8             	ldur	x16, [x9, #8]
+;;      	 100240f8             	ldur	x16, [x16]
+;;      	 ff6330eb             	cmp	sp, x16
+;;      	 83010054             	b.lo	#0x54
+;;   28:	 80c300b8             	stur	w0, [x28, #0xc]
+;;      	 890300f8             	stur	x9, [x28]
+;;      	 00000094             	bl	#0x30
+;;      	 00000094             	bl	#0x34
+;;   38:	 80c340b8             	ldur	w0, [x28, #0xc]
+;;      	 1f00006a             	tst	w0, w0
+;;      	 ff430091             	add	sp, sp, #0x10
+;;      	 fc030091             	mov	x28, sp
+;;      	 fc0741f8             	ldr	x28, [sp], #0x10
+;;      	 fd7bc1a8             	ldp	x29, x30, [sp], #0x10
+;;      	 c0035fd6             	ret	
+;;   54:	 1fc10000             	.byte	0x1f, 0xc1, 0x00, 0x00
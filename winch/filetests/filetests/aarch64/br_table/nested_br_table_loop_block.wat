@@ -0,0 +1,65 @@
+;;! target = "aarch64"
+(module
+  (func (export "nested-br_table-loop-block") (param i32) (result i32)
+    (local.set 0
+      (loop (result i32)
+        (block
+          (br_table 1 0 0 (local.get 0))
+        )
+        (i32.const 0)
+      )
+    )
+    (loop (result i32)
+      (block
+        (br_table 0 1 1 (local.get 0))
+      )
+      (i32.const 3)
+    )
+  )
+)
+;;      	 fd7bbfa9             	stp	x29, x30, [sp, #-0x10]!
+;;      	 fd030091             	mov	x29, sp
+;;      	 fc0f1ff8             	str	x28, [sp, #-0x10]!
+;;      	 fc030091             	mov	x28, sp
+;;      	 ff4300d1             	sub	sp, sp, #0x10
+;;      	 fc030091             	mov	x28, sp
+;;      	 308140f8             	ldur	x16, [x9, #8]
+;;      	 100240f8             	ldur	x16, [x16]
+;;      	 ff6330eb             	cmp	sp, x16
+;;      	 83040054             	b.lo	#0xb4
+;;   28:	 80c300b8             	stur	w0, [x28, #0xc]
+;;      	 890300f8             	stur	x9, [x28]
+;;      	 80c340b8             	ldur	w0, [x28, #0xc]
+;;      	 1f080071             	cmp	w0, #2
+;;      	 42010054             	b.hs	#0x60
+;;   3c:	 e123809a             	csel	x1, xzr, x0, hs
+;;      	 9f2203d5             	csdb	
+;;      	 90000010             	adr	x16, #0x54
+;;      	 015aa1b8             	ldrsw	x1, [x16, w1, uxtw #2]
+;;      	 1002018b             	add	x16, x16, x1
+;;      	 00021fd6             	br	x16
+;;   54:	 dcffffff             	.byte	0xdc, 0xff, 0xff, 0xff
+;;      	 0c000000             	.byte	0x0c, 0x00, 0x00, 0x00
+;;      	 f5ffff17             	b	#0x30
+;;   60:	 100080d2             	mov	x16, #0
+;;      	 e003102a             	mov	w0, w16
+;;      	 80c300b8             	stur	w0, [x28, #0xc]
+;;      	 80c340b8             	ldur	w0, [x28, #0xc]
+;;      	 1f080071             	cmp	w0, #2
+;;      	 c2ffff54             	b.hs	#0x6c
+;;   78:	 e123809a             	csel	x1, xzr, x0, hs
+;;      	 9f2203d5             	csdb	
+;;      	 90000010             	adr	x16, #0x90
+;;      	 015aa1b8             	ldrsw	x1, [x16, w1, uxtw #2]
+;;      	 1002018b             	add	x16, x16, x1
+;;      	 00021fd6             	br	x16
+;;   90:	 08000000             	.byte	0x08, 0x00, 0x00, 0x00
+;;      	 dcffffff             	.byte	0xdc, 0xff, 0xff, 0xff
+;;      	 700080d2             	mov	x16, #3
+;;      	 e003102a             	mov	w0, w16
+;;      	 ff430091             	add	sp, sp, #0x10
+;;      	 fc030091             	mov	x28, sp
+;;      	 fc0741f8             	ldr	x28, [sp], #0x10
+;;      	 fd7bc1a8             	ldp	x29, x30, [sp], #0x10
+;;      	 c0035fd6             	ret	
+;;   b4:	 1fc10000             	.byte	0x1f, 0xc1, 0x00, 0x00
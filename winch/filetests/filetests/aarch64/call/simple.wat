@@ -0,0 +1,72 @@
+;;! target = "aarch64"
+
+(module
+  (func $main (result i32)
+    (local $var i32)
+    (call $product (i32.const 20) (i32.const 80))
+    (local.set $var (i32.const 2))
+    (local.get $var)
+    (i32.div_u))
+
+  (func $product (param i32 i32) (result i32)
+    (local.get 0)
+    (local.get 1)
+    (i32.mul))
+)
+;;      	 fd7bbfa9             	stp	x29, x30, [sp, #-0x10]!
+;;      	 fd030091             	mov	x29, sp
+;;      	 fc0f1ff8             	str	x28, [sp, #-0x10]!
+;;      	 fc030091             	mov	x28, sp
+;;      	 ff4300d1             	sub	sp, sp, #0x10
+;;      	 fc030091             	mov	x28, sp
+;;      	 308140f8             	ldur	x16, [x9, #8]
+;;      	 100240f8             	ldur	x16, [x16]
+;;      	 ff6330eb             	cmp	sp, x16
+;;      	 c3020054             	b.lo	#0x7c
+;;   28:	 100080d2             	mov	x16, #0
+;;      	 908300f8             	stur	x16, [x28, #8]
+;;      	 890300f8             	stur	x9, [x28]
+;;      	 900280d2             	mov	x16, #0x14
+;;      	 e003102a             	mov	w0, w16
+;;      	 100a80d2             	mov	x16, #0x50
+;;      	 e103102a             	mov	w1, w16
+;;      	 00000094             	bl	#0x44
+;;   48:	 500080d2             	mov	x16, #2
+;;      	 e103102a             	mov	w1, w16
+;;      	 81c300b8             	stur	w1, [x28, #0xc]
+;;      	 81c340b8             	ldur	w1, [x28, #0xc]
+;;      	 e103012a             	mov	w1, w1
+;;      	 e003002a             	mov	w0, w0
+;;      	 010100b4             	cbz	x1, #0x80
+;;   64:	 0008c19a             	udiv	x0, x0, x1
+;;      	 ff430091             	add	sp, sp, #0x10
+;;      	 fc030091             	mov	x28, sp
+;;      	 fc0741f8             	ldr	x28, [sp], #0x10
+;;      	 fd7bc1a8             	ldp	x29, x30, [sp], #0x10
+;;      	 c0035fd6             	ret	
+;;   7c:	 1fc10000             	.byte	0x1f, 0xc1, 0x00, 0x00
+;;   80:	 1fc10000             	.byte	0x1f, 0xc1, 0x00, 0x00
+;;
+;;      	 fd7bbfa9             	stp	x29, x30, [sp, #-0x10]!
+;;      	 fd030091             	mov	x29, sp
+;;      	 fc0f1ff8             	str	x28, [sp, #-0x10]!
+;;      	 fc030091             	mov	x28, sp
+;;      	 ff4300d1             	sub	sp, sp, #0x10
+;;      	 fc030091             	mov	x28, sp
+;;      	 308140f8             	ldur	x16, [x9, #8]
+;;      	 100240f8             	ldur	x16, [x16]
+;;      	 ff6330eb             	cmp	sp, x16
+;;      	 a3010054             	b.lo	#0x58
+;;   28:	 80c300b8             	stur	w0, [x28, #0xc]
+;;      	 818300b8             	stur	w1, [x28, #8]
+;;      	 890300f8             	stur	x9, [x28]
+;;      	 808340b8             	ldur	w0, [x28, #8]
+;;      	 81c340b8             	ldur	w1, [x28, #0xc]
+;;      	 217c001b             	mul	w1, w1, w0
+;;      	 e003012a             	mov	w0, w1
+;;      	 ff430091             	add	sp, sp, #0x10
+;;      	 fc030091             	mov	x28, sp
+;;      	 fc0741f8             	ldr	x28, [sp], #0x10
+;;      	 fd7bc1a8             	ldp	x29, x30, [sp], #0x10
+;;      	 c0035fd6             	ret	
+;;   58:	 1fc10000             	.byte	0x1f, 0xc1, 0x00, 0x00
@@ -0,0 +1,33 @@
+;;! target = "aarch64"
+
+(module
+    (func (param f32) (param f32) (result f32)
+        (local.get 0)
+        (local.get 1)
+        (f32.copysign)
+    )
+)
+;;      	 fd7bbfa9             	stp	x29, x30, [sp, #-0x10]!
+;;      	 fd030091             	mov	x29, sp
+;;      	 fc0f1ff8             	str	x28, [sp, #-0x10]!
+;;      	 fc030091             	mov	x28, sp
+;;      	 ff4300d1             	sub	sp, sp, #0x10
+;;      	 fc030091             	mov	x28, sp
+;;      	 308140f8             	ldur	x16, [x9, #8]
+;;      	 100240f8             	ldur	x16, [x16]
+;;      	 ff6330eb             	cmp	sp, x16
+;;      	 c3010054             	b.lo	#0x5c
+;;   28:	 80c300bc             	stur	s0, [x28, #0xc]
+;;      	 818300bc             	stur	s1, [x28, #8]
+;;      	 890300f8             	stur	x9, [x28]
+;;      	 808340bc             	ldur	s0, [x28, #8]
+;;      	 81c340bc             	ldur	s1, [x28, #0xc]
+;;      	 1f04212f             	ushr	v31.2s, v0.2s, #0x1f
+;;      	 e1573f2f             	sli	v1.2s, v31.2s, #0x1f
+;;      	 2040601e             	fmov	d0, d1
+;;      	 ff430091             	add	sp, sp, #0x10
+;;      	 fc030091             	mov	x28, sp
+;;      	 fc0741f8             	ldr	x28, [sp], #0x10
+;;      	 fd7bc1a8             	ldp	x29, x30, [sp], #0x10
+;;      	 c0035fd6             	ret	
+;;   5c:	 1fc10000             	.byte	0x1f, 0xc1, 0x00, 0x00
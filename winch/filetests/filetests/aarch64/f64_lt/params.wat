@@ -0,0 +1,32 @@
+;;! target = "aarch64"
+
+(module
+    (func (param f64) (param f64) (result i32)
+        (local.get 0)
+        (local.get 1)
+        (f64.lt)
+    )
+)
+;;      	 fd7bbfa9             	stp	x29, x30, [sp, #-0x10]!
+;;      	 fd030091             	mov	x29, sp
+;;      	 fc0f1ff8             	str	x28, [sp, #-0x10]!
+;;      	 fc030091             	mov	x28, sp
+;;      	 ff6300d1             	sub	sp, sp, #0x18
+;;      	 fc030091             	mov	x28, sp
+;;      	 308140f8             	ldur	x16, [x9, #8]
+;;      	 100240f8             	ldur	x16, [x16]
+;;      	 ff6330eb             	cmp	sp, x16
+;;      	 a3010054             	b.lo	#0x58
+;;   28:	 800301fc             	stur	d0, [x28, #0x10]
+;;      	 818300fc             	stur	d1, [x28, #8]
+;;      	 890300f8             	stur	x9, [x28]
+;;      	 808340fc             	ldur	d0, [x28, #8]
+;;      	 810341fc             	ldur	d1, [x28, #0x10]
+;;      	 2020601e             	fcmp	d1, d0
+;;      	 e0579f9a             	cset	x0, mi
+;;      	 ff630091             	add	sp, sp, #0x18
+;;      	 fc030091             	mov	x28, sp
+;;      	 fc0741f8             	ldr	x28, [sp], #0x10
+;;      	 fd7bc1a8             	ldp	x29, x30, [sp], #0x10
+;;      	 c0035fd6             	ret	
+;;   58:	 1fc10000             	.byte	0x1f, 0xc1, 0x00, 0x00
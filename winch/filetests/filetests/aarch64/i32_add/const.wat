@@ -9,14 +9,21 @@
 )
 ;;      	 fd7bbfa9             	stp	x29, x30, [sp, #-0x10]!
 ;;      	 fd030091             	mov	x29, sp
+;;      	 fc0f1ff8             	str	x28, [sp, #-0x10]!
 ;;      	 fc030091             	mov	x28, sp
 ;;      	 ff2300d1             	sub	sp, sp, #8
 ;;      	 fc030091             	mov	x28, sp
-;;      	 890300f8             	stur	x9, [x28]
+;;      	 308140f8             	ldur	x16, [x9, #8]
+;;      	 100240f8             	ldur	x16, [x16]
+;;      	 ff6330eb             	cmp	sp, x16
+;;      	 43010054             	b.lo	#0x4c
+;;   28:	 890300f8             	stur	x9, [x28]
 ;;      	 500180d2             	mov	x16, #0xa
 ;;      	 e003102a             	mov	w0, w16
 ;;      	 00500011             	add	w0, w0, #0x14
 ;;      	 ff230091             	add	sp, sp, #8
 ;;      	 fc030091             	mov	x28, sp
+;;      	 fc0741f8             	ldr	x28, [sp], #0x10
 ;;      	 fd7bc1a8             	ldp	x29, x30, [sp], #0x10
 ;;      	 c0035fd6             	ret	
+;;   4c:	 1fc10000             	.byte	0x1f, 0xc1, 0x00, 0x00
@@ -9,10 +9,15 @@
 )
 ;;      	 fd7bbfa9             	stp	x29, x30, [sp, #-0x10]!
 ;;      	 fd030091             	mov	x29, sp
+;;      	 fc0f1ff8             	str	x28, [sp, #-0x10]!
 ;;      	 fc030091             	mov	x28, sp
 ;;      	 ff4300d1             	sub	sp, sp, #0x10
 ;;      	 fc030091             	mov	x28, sp
-;;      	 80c300b8             	stur	w0, [x28, #0xc]
+;;      	 308140f8             	ldur	x16, [x9, #8]
+;;      	 100240f8             	ldur	x16, [x16]
+;;      	 ff6330eb             	cmp	sp, x16
+;;      	 a3010054             	b.lo	#0x58
+;;   28:	 80c300b8             	stur	w0, [x28, #0xc]
 ;;      	 818300b8             	stur	w1, [x28, #8]
 ;;      	 890300f8             	stur	x9, [x28]
 ;;      	 808340b8             	ldur	w0, [x28, #8]
@@ -21,5 +26,7 @@
 ;;      	 e003012a             	mov	w0, w1
 ;;      	 ff430091             	add	sp, sp, #0x10
 ;;      	 fc030091             	mov	x28, sp
+;;      	 fc0741f8             	ldr	x28, [sp], #0x10
 ;;      	 fd7bc1a8             	ldp	x29, x30, [sp], #0x10
 ;;      	 c0035fd6             	ret	
+;;   58:	 1fc10000             	.byte	0x1f, 0xc1, 0x00, 0x00
@@ -0,0 +1,41 @@
+;;! target = "aarch64"
+
+(module
+    (func (param f32) (result i32)
+        (local.get 0)
+        (i32.trunc_f32_s)
+    )
+)
+;;      	 fd7bbfa9             	stp	x29, x30, [sp, #-0x10]!
+;;      	 fd030091             	mov	x29, sp
+;;      	 fc0f1ff8             	str	x28, [sp, #-0x10]!
+;;      	 fc030091             	mov	x28, sp
+;;      	 ff4300d1             	sub	sp, sp, #0x10
+;;      	 fc030091             	mov	x28, sp
+;;      	 308140f8             	ldur	x16, [x9, #8]
+;;      	 100240f8             	ldur	x16, [x16]
+;;      	 ff6330eb             	cmp	sp, x16
+;;      	 83020054             	b.lo	#0x74
+;;   28:	 80c300bc             	stur	s0, [x28, #0xc]
+;;      	 890300f8             	stur	x9, [x28]
+;;      	 80c340bc             	ldur	s0, [x28, #0xc]
+;;      	 0020201e             	fcmp	s0, s0
+;;      	 06020054             	b.vs	#0x78
+;;   3c:	 10e0b9d2             	mov	x16, #0xcf000000
+;;      	 1f02271e             	fmov	s31, w16
+;;      	 00203f1e             	fcmp	s0, s31
+;;      	 ab010054             	b.lt	#0x7c
+;;   4c:	 10e0a9d2             	mov	x16, #0x4f000000
+;;      	 1f02271e             	fmov	s31, w16
+;;      	 00203f1e             	fcmp	s0, s31
+;;      	 4a010054             	b.ge	#0x80
+;;   5c:	 0000381e             	fcvtzs	w0, s0
+;;      	 ff430091             	add	sp, sp, #0x10
+;;      	 fc030091             	mov	x28, sp
+;;      	 fc0741f8             	ldr	x28, [sp], #0x10
+;;      	 fd7bc1a8             	ldp	x29, x30, [sp], #0x10
+;;      	 c0035fd6             	ret	
+;;   74:	 1fc10000             	.byte	0x1f, 0xc1, 0x00, 0x00
+;;   78:	 1fc10000             	.byte	0x1f, 0xc1, 0x00, 0x00
+;;   7c:	 1fc10000             	.byte	0x1f, 0xc1, 0x00, 0x00
+;;   80:	 1fc10000             	.byte	0x1f, 0xc1, 0x00, 0x00
@@ -18,10 +18,15 @@
 )
 ;;      	 fd7bbfa9             	stp	x29, x30, [sp, #-0x10]!
 ;;      	 fd030091             	mov	x29, sp
+;;      	 fc0f1ff8             	str	x28, [sp, #-0x10]!
 ;;      	 fc030091             	mov	x28, sp
 ;;      	 ff6300d1             	sub	sp, sp, #0x18
 ;;      	 fc030091             	mov	x28, sp
-;;      	 100080d2             	mov	x16, #0
+;;      	 308140f8             	ldur	x16, [x9, #8]
+;;      	 100240f8             	ldur	x16, [x16]
+;;      	 ff6330eb             	cmp	sp, x16
+;;      	 83020054             	b.lo	#0x74
+;;   28:	 100080d2             	mov	x16, #0
 ;;      	 900301f8             	stur	x16, [x28, #0x10]
 ;;      	 908300f8             	stur	x16, [x28, #8]
 ;;      	 890300f8             	stur	x9, [x28]
@@ -37,5 +42,7 @@
 ;;      	 e00301aa             	mov	x0, x1
 ;;      	 ff630091             	add	sp, sp, #0x18
 ;;      	 fc030091             	mov	x28, sp
+;;      	 fc0741f8             	ldr	x28, [sp], #0x10
 ;;      	 fd7bc1a8             	ldp	x29, x30, [sp], #0x10
 ;;      	 c0035fd6             	ret	
+;;   74:	 1fc10000             	.byte	0x1f, 0xc1, 0x00, 0x00
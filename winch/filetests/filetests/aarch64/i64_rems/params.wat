@@ -0,0 +1,35 @@
+;;! target = "aarch64"
+
+(module
+    (func (param i64) (param i64) (result i64)
+	(local.get 0)
+	(local.get 1)
+	(i64.rem_s)
+    )
+)
+;;      	 fd7bbfa9             	stp	x29, x30, [sp, #-0x10]!
+;;      	 fd030091             	mov	x29, sp
+;;      	 fc0f1ff8             	str	x28, [sp, #-0x10]!
+;;      	 fc030091             	mov	x28, sp
+;;      	 ff6300d1             	sub	sp, sp, #0x18
+;;      	 fc030091             	mov	x28, sp
+;;      	 308140f8             	ldur	x16, [x9, #8]
+;;      	 100240f8             	ldur	x16, [x16]
+;;      	 ff6330eb             	cmp	sp, x16
+;;      	 e3010054             	b.lo	#0x60
+;;   28:	 800301f8             	stur	x0, [x28, #0x10]
+;;      	 818300f8             	stur	x1, [x28, #8]
+;;      	 890300f8             	stur	x9, [x28]
+;;      	 808340f8             	ldur	x0, [x28, #8]
+;;      	 810341f8             	ldur	x1, [x28, #0x10]
+;;      	 400100b4             	cbz	x0, #0x64
+;;   40:	 300cc09a             	sdiv	x16, x1, x0
+;;      	 0186009b             	msub	x1, x16, x0, x1
+;;      	 e00301aa             	mov	x0, x1
+;;      	 ff630091             	add	sp, sp, #0x18
+;;      	 fc030091             	mov	x28, sp
+;;      	 fc0741f8             	ldr	x28, [sp], #0x10
+;;      	 fd7bc1a8             	ldp	x29, x30, [sp], #0x10
+;;      	 c0035fd6             	ret	
+;;   60:	 1fc10000             	.byte	0x1f, 0xc1, 0x00, 0x00
+;;   64:	 1fc10000             	.byte	0x1f, 0xc1, 0x00, 0x00
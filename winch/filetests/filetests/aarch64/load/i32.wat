@@ -0,0 +1,29 @@
+;;! target = "aarch64"
+(module
+  (memory 1)
+  (func (export "as-br-value") (result i32)
+    (block (result i32) (br 0 (i32.load (i32.const 0))))
+  )
+)
+;;      	 fd7bbfa9             	stp	x29, x30, [sp, #-0x10]!
+;;      	 fd030091             	mov	x29, sp
+;;      	 fc0f1ff8             	str	x28, [sp, #-0x10]!
+;;      	 fc030091             	mov	x28, sp
+;;      	 ff2300d1             	sub	sp, sp, #8
+;;      	 fc030091             	mov	x28, sp
+;;      	 308140f8             	ldur	x16, [x9, #8]
+;;      	 100240f8             	ldur	x16, [x16]
+;;      	 ff6330eb             	cmp	sp, x16
+;;      	 83010054             	b.lo	#0x54
+;;   28:	 890300f8             	stur	x9, [x28]
+;;      	 100080d2             	mov	x16, #0
+;;      	 e003102a             	mov	w0, w16
+;;      	 210145f8             	ldur	x1, [x9, #0x50]
+;;      	 2160208b             	add	x1, x1, x0, uxtx
+;;      	 200040b8             	ldur	w0, [x1]
+;;      	 ff230091             	add	sp, sp, #8
+;;      	 fc030091             	mov	x28, sp
+;;      	 fc0741f8             	ldr	x28, [sp], #0x10
+;;      	 fd7bc1a8             	ldp	x29, x30, [sp], #0x10
+;;      	 c0035fd6             	ret	
+;;   54:	 1fc10000             	.byte	0x1f, 0xc1, 0x00, 0x00
@@ -0,0 +1,33 @@
+;;! target = "aarch64"
+
+(module
+  (func (export "select-i32") (param i32 i32 i32) (result i32)
+    (select (local.get 0) (local.get 1) (local.get 2))
+  )
+)
+;;      	 fd7bbfa9             	stp	x29, x30, [sp, #-0x10]!
+;;      	 fd030091             	mov	x29, sp
+;;      	 fc0f1ff8             	str	x28, [sp, #-0x10]!
+;;      	 fc030091             	mov	x28, sp
+;;      	 ff6300d1             	sub	sp, sp, #0x18
+;;      	 fc030091             	mov	x28, sp
+;;      	 308140f8             	ldur	x16, [x9, #8]
+;;      	 100240f8             	ldur	x16, [x16]
+;;      	 ff6330eb             	cmp	sp, x16
+;;      	 03020054             	b.lo	#0x64
+;;   28:	 804301b8             	stur	w0, [x28, #0x14]
+;;      	 810301b8             	stur	w1, [x28, #0x10]
+;;      	 82c300b8             	stur	w2, [x28, #0xc]
+;;      	 890300f8             	stur	x9, [x28]
+;;      	 80c340b8             	ldur	w0, [x28, #0xc]
+;;      	 810341b8             	ldur	w1, [x28, #0x10]
+;;      	 824341b8             	ldur	w2, [x28, #0x14]
+;;      	 1f000071             	cmp	w0, #0
+;;      	 4110819a             	csel	x1, x2, x1, ne
+;;      	 e003012a             	mov	w0, w1
+;;      	 ff630091             	add	sp, sp, #0x18
+;;      	 fc030091             	mov	x28, sp
+;;      	 fc0741f8             	ldr	x28, [sp], #0x10
+;;      	 fd7bc1a8             	ldp	x29, x30, [sp], #0x10
+;;      	 c0035fd6             	ret	
+;;   64:	 1fc10000             	.byte	0x1f, 0xc1, 0x00, 0x00
@@ -0,0 +1,32 @@
+;;! target = "aarch64"
+(module
+  (memory 1)
+
+  (func (export "as-block-value")
+    (block (i32.store (i32.const 0) (i32.const 1)))
+  )
+)
+;;      	 fd7bbfa9             	stp	x29, x30, [sp, #-0x10]!
+;;      	 fd030091             	mov	x29, sp
+;;      	 fc0f1ff8             	str	x28, [sp, #-0x10]!
+;;      	 fc030091             	mov	x28, sp
+;;      	 ff2300d1             	sub	sp, sp, #8
+;;      	 fc030091             	mov	x28, sp
+;;      	 308140f8             	ldur	x16, [x9, #8]
+;;      	 100240f8             	ldur	x16, [x16]
+;;      	 ff6330eb             	cmp	sp, x16
+;;      	 c3010054             	b.lo	#0x5c
+;;   28:	 890300f8             	stur	x9, [x28]
+;;      	 300080d2             	mov	x16, #1
+;;      	 e003102a             	mov	w0, w16
+;;      	 100080d2             	mov	x16, #0
+;;      	 e103102a             	mov	w1, w16
+;;      	 220145f8             	ldur	x2, [x9, #0x50]
+;;      	 4260218b             	add	x2, x2, x1, uxtx
+;;      	 400000b8             	stur	w0, [x2]
+;;      	 ff230091             	add	sp, sp, #8
+;;      	 fc030091             	mov	x28, sp
+;;      	 fc0741f8             	ldr	x28, [sp], #0x10
+;;      	 fd7bc1a8             	ldp	x29, x30, [sp], #0x10
+;;      	 c0035fd6             	ret	
+;;   5c:	 1fc10000             	.byte	0x1f, 0xc1, 0x00, 0x00
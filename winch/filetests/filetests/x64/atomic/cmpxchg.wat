@@ -0,0 +1,82 @@
+;;! target = "x86_64"
+(module
+  (memory 1 1 shared)
+  (func (export "i32_cmpxchg") (param i32 i32 i32) (result i32)
+    (i32.atomic.rmw.cmpxchg (local.get 0) (local.get 1) (local.get 2)))
+  (func (export "i64_rmw16_cmpxchg_u") (param i32 i64 i64) (result i64)
+    (i64.atomic.rmw16.cmpxchg_u offset=2 (local.get 0) (local.get 1) (local.get 2)))
+)
+;;      	 55                   	push	rbp
+;;      	 4889e5               	mov	rbp, rsp
+;;      	 4883ec18             	sub	rsp, 0x18
+;;      	 4d8b5e08             	mov	r11, qword ptr [r14 + 8]
+;;      	 4d8b1b               	mov	r11, qword ptr [r11]
+;;      	 4939e3               	cmp	r11, rsp
+;;      	 0f875e000000         	ja	0x76
+;;   18:	 897c2414             	mov	dword ptr [rsp + 0x14], edi
+;;      	 89742410             	mov	dword ptr [rsp + 0x10], esi
+;;      	 8954240c             	mov	dword ptr [rsp + 0xc], edx
+;;      	 4c893424             	mov	qword ptr [rsp], r14
+;;      	 8b44240c             	mov	eax, dword ptr [rsp + 0xc]
+;;      	 8b4c2410             	mov	ecx, dword ptr [rsp + 0x10]
+;;      	 8b542414             	mov	edx, dword ptr [rsp + 0x14]
+;;      	 89d3                 	mov	ebx, edx
+;;      	 83e303               	and	ebx, 3
+;;      	 83fb00               	cmp	ebx, 0
+;;      	 0f8536000000         	jne	0x78
+;;   42:	 4d8b5e48             	mov	r11, qword ptr [r14 + 0x48]
+;;      	 498b1b               	mov	rbx, qword ptr [r11]
+;;      	 4801d3               	add	rbx, rdx
+;;      	 53                   	push	rbx
+;;      	 4883ec04             	sub	rsp, 4
+;;      	 890c24               	mov	dword ptr [rsp], ecx
+;;      	 4883ec04             	sub	rsp, 4
+;;      	 890424               	mov	dword ptr [rsp], eax
+;;      	 8b0c24               	mov	ecx, dword ptr [rsp]
+;;      	 4883c404             	add	rsp, 4
+;;      	 8b0424               	mov	eax, dword ptr [rsp]
+;;      	 4883c404             	add	rsp, 4
+;;      	 5a                   	pop	rdx
+;;      	 f00fb10a             	lock cmpxchg	dword ptr [rdx], ecx
+;;      	 89c0                 	mov	eax, eax
+;;      	 4883c418             	add	rsp, 0x18
+;;      	 5d                   	pop	rbp
+;;      	 c3                   	ret	
+;;   76:	 0f0b                 	ud2	
+;;   78:	 0f0b                 	ud2	
+;;
+;;      	 55                   	push	rbp
+;;      	 4889e5               	mov	rbp, rsp
+;;      	 4883ec20             	sub	rsp, 0x20
+;;      	 4d8b5e08             	mov	r11, qword ptr [r14 + 8]
+;;      	 4d8b1b               	mov	r11, qword ptr [r11]
+;;      	 4939e3               	cmp	r11, rsp
+;;      	 0f8753000000         	ja	0x6b
+;;   18:	 897c241c             	mov	dword ptr [rsp + 0x1c], edi
+;;      	 4889742410           	mov	qword ptr [rsp + 0x10], rsi
+;;      	 4889542408           	mov	qword ptr [rsp + 8], rdx
+;;      	 4c893424             	mov	qword ptr [rsp], r14
+;;      	 488b442408           	mov	rax, qword ptr [rsp + 8]
+;;      	 488b4c2410           	mov	rcx, qword ptr [rsp + 0x10]
+;;      	 8b54241c             	mov	edx, dword ptr [rsp + 0x1c]
+;;      	 89d3                 	mov	ebx, edx
+;;      	 83e301               	and	ebx, 1
+;;      	 83fb00               	cmp	ebx, 0
+;;      	 0f8527000000         	jne	0x6d
+;;   46:	 4d8b5e48             	mov	r11, qword ptr [r14 + 0x48]
+;;      	 498b1b               	mov	rbx, qword ptr [r11]
+;;      	 4801d3               	add	rbx, rdx
+;;      	 4883c302             	add	rbx, 2
+;;      	 53                   	push	rbx
+;;      	 51                   	push	rcx
+;;      	 50                   	push	rax
+;;      	 59                   	pop	rcx
+;;      	 58                   	pop	rax
+;;      	 5a                   	pop	rdx
+;;      	 66f00fb10a           	lock cmpxchg	word ptr [rdx], cx
+;;      	 81e0ffff0000         	and	eax, 0xffff
+;;      	 4883c420             	add	rsp, 0x20
+;;      	 5d                   	pop	rbp
+;;      	 c3                   	ret	
+;;   6b:	 0f0b                 	ud2	
+;;   6d:	 0f0b                 	ud2	
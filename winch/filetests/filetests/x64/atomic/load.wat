@@ -0,0 +1,51 @@
+;;! target = "x86_64"
+(module
+  (memory 1 1 shared)
+  (func (export "i32_load") (param i32) (result i32)
+    (i32.atomic.load offset=4 (local.get 0)))
+  (func (export "i64_load8_u") (param i32) (result i64)
+    (i64.atomic.load8_u (local.get 0)))
+)
+;;      	 55                   	push	rbp
+;;      	 4889e5               	mov	rbp, rsp
+;;      	 4883ec10             	sub	rsp, 0x10
+;;      	 4d8b5e08             	mov	r11, qword ptr [r14 + 8]
+;;      	 4d8b1b               	mov	r11, qword ptr [r11]
+;;      	 4939e3               	cmp	r11, rsp
+;;      	 0f8730000000         	ja	0x48
+;;   18:	 897c240c             	mov	dword ptr [rsp + 0xc], edi
+;;      	 4c893424             	mov	qword ptr [rsp], r14
+;;      	 8b44240c             	mov	eax, dword ptr [rsp + 0xc]
+;;      	 89c1                 	mov	ecx, eax
+;;      	 83e103               	and	ecx, 3
+;;      	 83f900               	cmp	ecx, 0
+;;      	 0f8518000000         	jne	0x4a
+;;   32:	 4d8b5e48             	mov	r11, qword ptr [r14 + 0x48]
+;;      	 498b0b               	mov	rcx, qword ptr [r11]
+;;      	 4801c1               	add	rcx, rax
+;;      	 4883c104             	add	rcx, 4
+;;      	 8b01                 	mov	eax, dword ptr [rcx]
+;;      	 4883c410             	add	rsp, 0x10
+;;      	 5d                   	pop	rbp
+;;      	 c3                   	ret	
+;;   48:	 0f0b                 	ud2	
+;;   4a:	 0f0b                 	ud2	
+;;
+;;      	 55                   	push	rbp
+;;      	 4889e5               	mov	rbp, rsp
+;;      	 4883ec10             	sub	rsp, 0x10
+;;      	 4d8b5e08             	mov	r11, qword ptr [r14 + 8]
+;;      	 4d8b1b               	mov	r11, qword ptr [r11]
+;;      	 4939e3               	cmp	r11, rsp
+;;      	 0f8720000000         	ja	0x38
+;;   18:	 897c240c             	mov	dword ptr [rsp + 0xc], edi
+;;      	 4c893424             	mov	qword ptr [rsp], r14
+;;      	 8b44240c             	mov	eax, dword ptr [rsp + 0xc]
+;;      	 4d8b5e48             	mov	r11, qword ptr [r14 + 0x48]
+;;      	 498b0b               	mov	rcx, qword ptr [r11]
+;;      	 4801c1               	add	rcx, rax
+;;      	 480fb601             	movzx	rax, byte ptr [rcx]
+;;      	 4883c410             	add	rsp, 0x10
+;;      	 5d                   	pop	rbp
+;;      	 c3                   	ret	
+;;   38:	 0f0b                 	ud2	
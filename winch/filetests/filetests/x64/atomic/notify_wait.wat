@@ -0,0 +1,78 @@
+;;! target = "x86_64"
+(module
+  (memory 1 1 shared)
+  (func (export "notify") (param i32 i32) (result i32)
+    (memory.atomic.notify offset=4 (local.get 0) (local.get 1)))
+  (func (export "wait32") (param i32 i32 i64) (result i32)
+    (memory.atomic.wait32 (local.get 0) (local.get 1) (local.get 2)))
+)
+;;      	 55                   	push	rbp
+;;      	 4889e5               	mov	rbp, rsp
+;;      	 4883ec10             	sub	rsp, 0x10
+;;      	 4d8b5e08             	mov	r11, qword ptr [r14 + 8]
+;;      	 4d8b1b               	mov	r11, qword ptr [r11]
+;;      	 4939e3               	cmp	r11, rsp
+;;      	 0f875b000000         	ja	0x73
+;;   18:	 897c240c             	mov	dword ptr [rsp + 0xc], edi
+;;      	 89742408             	mov	dword ptr [rsp + 8], esi
+;;      	 4c893424             	mov	qword ptr [rsp], r14
+;;      	 8b442408             	mov	eax, dword ptr [rsp + 8]
+;;      	 8b4c240c             	mov	ecx, dword ptr [rsp + 0xc]
+;;      	 83c104               	add	ecx, 4
+;;      	 0f8240000000         	jb	0x75
+;;   35:	 8bc9                 	mov	ecx, ecx
+;;      	 4156                 	push	r14
+;;      	 51                   	push	rcx
+;;      	 4883ec04             	sub	rsp, 4
+;;      	 890424               	mov	dword ptr [rsp], eax
+;;      	 4d8b5e38             	mov	r11, qword ptr [r14 + 0x38]
+;;      	 498b9b90000000       	mov	rbx, qword ptr [r11 + 0x90]
+;;      	 4883ec0c             	sub	rsp, 0xc
+;;      	 488b7c2418           	mov	rdi, qword ptr [rsp + 0x18]
+;;      	 be00000000           	mov	esi, 0
+;;      	 488b542410           	mov	rdx, qword ptr [rsp + 0x10]
+;;      	 8b4c240c             	mov	ecx, dword ptr [rsp + 0xc]
+;;      	 ffd3                 	call	rbx
+;;      	 4883c40c             	add	rsp, 0xc
+;;      	 4883c414             	add	rsp, 0x14
+;;      	 4883c410             	add	rsp, 0x10
+;;      	 5d                   	pop	rbp
+;;      	 c3                   	ret	
+;;   73:	 0f0b                 	ud2	
+;;   75:	 0f0b                 	ud2	
+;;
+;;      	 55                   	push	rbp
+;;      	 4889e5               	mov	rbp, rsp
+;;      	 4883ec18             	sub	rsp, 0x18
+;;      	 4d8b5e08             	mov	r11, qword ptr [r14 + 8]
+;;      	 4d8b1b               	mov	r11, qword ptr [r11]
+;;      	 4939e3               	cmp	r11, rsp
+;;      	 0f8762000000         	ja	0x7a
+;;   18:	 897c2414             	mov	dword ptr [rsp + 0x14], edi
+;;      	 89742410             	mov	dword ptr [rsp + 0x10], esi
+;;      	 4889542408           	mov	qword ptr [rsp + 8], rdx
+;;      	 4c893424             	mov	qword ptr [rsp], r14
+;;      	 488b442408           	mov	rax, qword ptr [rsp + 8]
+;;      	 8b4c2410             	mov	ecx, dword ptr [rsp + 0x10]
+;;      	 8b542414             	mov	edx, dword ptr [rsp + 0x14]
+;;      	 8bd2                 	mov	edx, edx
+;;      	 4156                 	push	r14
+;;      	 52                   	push	rdx
+;;      	 4883ec04             	sub	rsp, 4
+;;      	 890c24               	mov	dword ptr [rsp], ecx
+;;      	 50                   	push	rax
+;;      	 4d8b5e38             	mov	r11, qword ptr [r14 + 0x38]
+;;      	 498b9b98000000       	mov	rbx, qword ptr [r11 + 0x98]
+;;      	 4883ec0c             	sub	rsp, 0xc
+;;      	 488b7c2420           	mov	rdi, qword ptr [rsp + 0x20]
+;;      	 be00000000           	mov	esi, 0
+;;      	 488b542418           	mov	rdx, qword ptr [rsp + 0x18]
+;;      	 8b4c2414             	mov	ecx, dword ptr [rsp + 0x14]
+;;      	 4c8b44240c           	mov	r8, qword ptr [rsp + 0xc]
+;;      	 ffd3                 	call	rbx
+;;      	 4883c40c             	add	rsp, 0xc
+;;      	 4883c41c             	add	rsp, 0x1c
+;;      	 4883c418             	add	rsp, 0x18
+;;      	 5d                   	pop	rbp
+;;      	 c3                   	ret	
+;;   7a:	 0f0b                 	ud2	
@@ -0,0 +1,72 @@
+;;! target = "x86_64"
+(module
+  (memory 1 1 shared)
+  (func (export "i32_add") (param i32 i32) (result i32)
+    (i32.atomic.rmw.add (local.get 0) (local.get 1)))
+  (func (export "i64_rmw8_xchg_u") (param i32 i64) (result i64)
+    (i64.atomic.rmw8.xchg_u (local.get 0) (local.get 1)))
+)
+;;      	 55                   	push	rbp
+;;      	 4889e5               	mov	rbp, rsp
+;;      	 4883ec10             	sub	rsp, 0x10
+;;      	 4d8b5e08             	mov	r11, qword ptr [r14 + 8]
+;;      	 4d8b1b               	mov	r11, qword ptr [r11]
+;;      	 4939e3               	cmp	r11, rsp
+;;      	 0f8754000000         	ja	0x6c
+;;   18:	 897c240c             	mov	dword ptr [rsp + 0xc], edi
+;;      	 89742408             	mov	dword ptr [rsp + 8], esi
+;;      	 4c893424             	mov	qword ptr [rsp], r14
+;;      	 8b442408             	mov	eax, dword ptr [rsp + 8]
+;;      	 8b4c240c             	mov	ecx, dword ptr [rsp + 0xc]
+;;      	 89ca                 	mov	edx, ecx
+;;      	 83e203               	and	edx, 3
+;;      	 83fa00               	cmp	edx, 0
+;;      	 0f8534000000         	jne	0x6e
+;;   3a:	 4d8b5e48             	mov	r11, qword ptr [r14 + 0x48]
+;;      	 498b13               	mov	rdx, qword ptr [r11]
+;;      	 4801ca               	add	rdx, rcx
+;;      	 52                   	push	rdx
+;;      	 4883ec04             	sub	rsp, 4
+;;      	 890424               	mov	dword ptr [rsp], eax
+;;      	 8b0c24               	mov	ecx, dword ptr [rsp]
+;;      	 4883c404             	add	rsp, 4
+;;      	 5a                   	pop	rdx
+;;      	 8b02                 	mov	eax, dword ptr [rdx]
+;;      	 4889c3               	mov	rbx, rax
+;;      	 4801cb               	add	rbx, rcx
+;;      	 f00fb11a             	lock cmpxchg	dword ptr [rdx], ebx
+;;      	 0f85f0ffffff         	jne	0x56
+;;   66:	 4883c410             	add	rsp, 0x10
+;;      	 5d                   	pop	rbp
+;;      	 c3                   	ret	
+;;   6c:	 0f0b                 	ud2	
+;;   6e:	 0f0b                 	ud2	
+;;
+;;      	 55                   	push	rbp
+;;      	 4889e5               	mov	rbp, rsp
+;;      	 4883ec18             	sub	rsp, 0x18
+;;      	 4d8b5e08             	mov	r11, qword ptr [r14 + 8]
+;;      	 4d8b1b               	mov	r11, qword ptr [r11]
+;;      	 4939e3               	cmp	r11, rsp
+;;      	 0f873e000000         	ja	0x56
+;;   18:	 897c2414             	mov	dword ptr [rsp + 0x14], edi
+;;      	 4889742408           	mov	qword ptr [rsp + 8], rsi
+;;      	 4c893424             	mov	qword ptr [rsp], r14
+;;      	 488b442408           	mov	rax, qword ptr [rsp + 8]
+;;      	 8b4c2414             	mov	ecx, dword ptr [rsp + 0x14]
+;;      	 4d8b5e48             	mov	r11, qword ptr [r14 + 0x48]
+;;      	 498b13               	mov	rdx, qword ptr [r11]
+;;      	 4801ca               	add	rdx, rcx
+;;      	 52                   	push	rdx
+;;      	 50                   	push	rax
+;;      	 59                   	pop	rcx
+;;      	 5a                   	pop	rdx
+;;      	 480fb602             	movzx	rax, byte ptr [rdx]
+;;      	 4889c3               	mov	rbx, rax
+;;      	 4889cb               	mov	rbx, rcx
+;;      	 f00fb01a             	lock cmpxchg	byte ptr [rdx], bl
+;;      	 0f85f0ffffff         	jne	0x40
+;;   50:	 4883c418             	add	rsp, 0x18
+;;      	 5d                   	pop	rbp
+;;      	 c3                   	ret	
+;;   56:	 0f0b                 	ud2	
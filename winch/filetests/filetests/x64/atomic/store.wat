@@ -0,0 +1,62 @@
+;;! target = "x86_64"
+(module
+  (memory 1 1 shared)
+  (func (export "i32_store16") (param i32 i32)
+    (i32.atomic.store16 (local.get 0) (local.get 1)))
+  (func (export "i64_store") (param i32 i64)
+    (i64.atomic.store offset=8 (local.get 0) (local.get 1)))
+)
+;;      	 55                   	push	rbp
+;;      	 4889e5               	mov	rbp, rsp
+;;      	 4883ec10             	sub	rsp, 0x10
+;;      	 4d8b5e08             	mov	r11, qword ptr [r14 + 8]
+;;      	 4d8b1b               	mov	r11, qword ptr [r11]
+;;      	 4939e3               	cmp	r11, rsp
+;;      	 0f8738000000         	ja	0x50
+;;   18:	 897c240c             	mov	dword ptr [rsp + 0xc], edi
+;;      	 89742408             	mov	dword ptr [rsp + 8], esi
+;;      	 4c893424             	mov	qword ptr [rsp], r14
+;;      	 8b442408             	mov	eax, dword ptr [rsp + 8]
+;;      	 8b4c240c             	mov	ecx, dword ptr [rsp + 0xc]
+;;      	 89ca                 	mov	edx, ecx
+;;      	 83e201               	and	edx, 1
+;;      	 83fa00               	cmp	edx, 0
+;;      	 0f8518000000         	jne	0x52
+;;   3a:	 4d8b5e48             	mov	r11, qword ptr [r14 + 0x48]
+;;      	 498b13               	mov	rdx, qword ptr [r11]
+;;      	 4801ca               	add	rdx, rcx
+;;      	 668902               	mov	word ptr [rdx], ax
+;;      	 0faef0               	mfence	
+;;      	 4883c410             	add	rsp, 0x10
+;;      	 5d                   	pop	rbp
+;;      	 c3                   	ret	
+;;   50:	 0f0b                 	ud2	
+;;   52:	 0f0b                 	ud2	
+;;
+;;      	 55                   	push	rbp
+;;      	 4889e5               	mov	rbp, rsp
+;;      	 4883ec18             	sub	rsp, 0x18
+;;      	 4d8b5e08             	mov	r11, qword ptr [r14 + 8]
+;;      	 4d8b1b               	mov	r11, qword ptr [r11]
+;;      	 4939e3               	cmp	r11, rsp
+;;      	 0f873e000000         	ja	0x56
+;;   18:	 897c2414             	mov	dword ptr [rsp + 0x14], edi
+;;      	 4889742408           	mov	qword ptr [rsp + 8], rsi
+;;      	 4c893424             	mov	qword ptr [rsp], r14
+;;      	 488b442408           	mov	rax, qword ptr [rsp + 8]
+;;      	 8b4c2414             	mov	ecx, dword ptr [rsp + 0x14]
+;;      	 89ca                 	mov	edx, ecx
+;;      	 83e207               	and	edx, 7
+;;      	 83fa00               	cmp	edx, 0
+;;      	 0f851c000000         	jne	0x58
+;;   3c:	 4d8b5e48             	mov	r11, qword ptr [r14 + 0x48]
+;;      	 498b13               	mov	rdx, qword ptr [r11]
+;;      	 4801ca               	add	rdx, rcx
+;;      	 4883c208             	add	rdx, 8
+;;      	 488902               	mov	qword ptr [rdx], rax
+;;      	 0faef0               	mfence	
+;;      	 4883c418             	add	rsp, 0x18
+;;      	 5d                   	pop	rbp
+;;      	 c3                   	ret	
+;;   56:	 0f0b                 	ud2	
+;;   58:	 0f0b                 	ud2	
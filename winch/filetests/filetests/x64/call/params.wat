@@ -65,7 +65,7 @@
 ;;      	 41bb08000000         	mov	r11d, 8
 ;;      	 44895c2410           	mov	dword ptr [rsp + 0x10], r11d
 ;;      	 e800000000           	call	0x7f
-;;      	 4883c42c             	add	rsp, 0x2c
+;;      	 4883c40c             	add	rsp, 0xc
 ;;      	 4883c404             	add	rsp, 4
 ;;      	 4883ec04             	sub	rsp, 4
 ;;      	 890424               	mov	dword ptr [rsp], eax
@@ -97,7 +97,7 @@
 ;;      	 41bb08000000         	mov	r11d, 8
 ;;      	 44895c2410           	mov	dword ptr [rsp + 0x10], r11d
 ;;      	 e800000000           	call	0x108
-;;      	 4883c428             	add	rsp, 0x28
+;;      	 4883c408             	add	rsp, 8
 ;;      	 4883c408             	add	rsp, 8
 ;;      	 4883c410             	add	rsp, 0x10
 ;;      	 5d                   	pop	rbp
@@ -110,7 +110,7 @@
 ;;      	 4d8b5e08             	mov	r11, qword ptr [r14 + 8]
 ;;      	 4d8b1b               	mov	r11, qword ptr [r11]
 ;;      	 4939e3               	cmp	r11, rsp
-;;      	 0f8757000000         	ja	0x6f
+;;      	 0f8759000000         	ja	0x71
 ;;   18:	 897c241c             	mov	dword ptr [rsp + 0x1c], edi
 ;;      	 89742418             	mov	dword ptr [rsp + 0x18], esi
 ;;      	 89542414             	mov	dword ptr [rsp + 0x14], edx
@@ -138,5 +138,5 @@
 ;;      	 89c8                 	mov	eax, ecx
 ;;      	 4883c420             	add	rsp, 0x20
 ;;      	 5d                   	pop	rbp
-;;      	 c3                   	ret	
-;;   6f:	 0f0b                 	ud2	
+;;      	 c22000               	ret	0x20
+;;   71:	 0f0b                 	ud2	
@@ -0,0 +1,71 @@
+;;! target = "x86_64"
+
+(module
+  (type $t (func (param i32) (result i32)))
+  (table 1 funcref)
+  (elem (i32.const 0) $f)
+
+  (func $f (param i32) (result i32)
+    (return_call_indirect (type $t) (local.get 0) (i32.const 0)))
+)
+;;      	 55                   	push	rbp
+;;      	 4889e5               	mov	rbp, rsp
+;;      	 4883ec10             	sub	rsp, 0x10
+;;      	 4d8b5e08             	mov	r11, qword ptr [r14 + 8]
+;;      	 4d8b1b               	mov	r11, qword ptr [r11]
+;;      	 4939e3               	cmp	r11, rsp
+;;      	 0f87ae000000         	ja	0xc6
+;;   18:	 897c240c             	mov	dword ptr [rsp + 0xc], edi
+;;      	 4c893424             	mov	qword ptr [rsp], r14
+;;      	 448b5c240c           	mov	r11d, dword ptr [rsp + 0xc]
+;;      	 4883ec04             	sub	rsp, 4
+;;      	 44891c24             	mov	dword ptr [rsp], r11d
+;;      	 b900000000           	mov	ecx, 0
+;;      	 4c89f2               	mov	rdx, r14
+;;      	 8b5a50               	mov	ebx, dword ptr [rdx + 0x50]
+;;      	 39d9                 	cmp	ecx, ebx
+;;      	 0f8388000000         	jae	0xc8
+;;   40:	 4189cb               	mov	r11d, ecx
+;;      	 4d6bdb08             	imul	r11, r11, 8
+;;      	 488b5248             	mov	rdx, qword ptr [rdx + 0x48]
+;;      	 4889d6               	mov	rsi, rdx
+;;      	 4c01da               	add	rdx, r11
+;;      	 39d9                 	cmp	ecx, ebx
+;;      	 480f43d6             	cmovae	rdx, rsi
+;;      	 488b02               	mov	rax, qword ptr [rdx]
+;;      	 4885c0               	test	rax, rax
+;;      	 0f8529000000         	jne	0x8c
+;;   63:	 4d8b5e38             	mov	r11, qword ptr [r14 + 0x38]
+;;      	 498b5b48             	mov	rbx, qword ptr [r11 + 0x48]
+;;      	 4156                 	push	r14
+;;      	 4883ec04             	sub	rsp, 4
+;;      	 890c24               	mov	dword ptr [rsp], ecx
+;;      	 488b7c2404           	mov	rdi, qword ptr [rsp + 4]
+;;      	 be00000000           	mov	esi, 0
+;;      	 8b1424               	mov	edx, dword ptr [rsp]
+;;      	 ffd3                 	call	rbx
+;;      	 4883c40c             	add	rsp, 0xc
+;;      	 e904000000           	jmp	0x90
+;;   8c:	 4883e0fe             	and	rax, 0xfffffffffffffffe
+;;      	 4885c0               	test	rax, rax
+;;      	 0f8431000000         	je	0xca
+;;   99:	 4d8b5e40             	mov	r11, qword ptr [r14 + 0x40]
+;;      	 418b0b               	mov	ecx, dword ptr [r11]
+;;      	 8b5018               	mov	edx, dword ptr [rax + 0x18]
+;;      	 39d1                 	cmp	ecx, edx
+;;      	 0f8521000000         	jne	0xcc
+;;   ab:	 50                   	push	rax
+;;      	 59                   	pop	rcx
+;;      	 488b5110             	mov	rdx, qword ptr [rcx + 0x10]
+;;      	 8b3c24               	mov	edi, dword ptr [rsp]
+;;      	 4889e9               	mov	rcx, rbp
+;;      	 488b29               	mov	rbp, qword ptr [rcx]
+;;      	 488d6108             	lea	rsp, [rcx + 8]
+;;      	 ffe2                 	jmp	rdx
+;;   c0:	 4883c410             	add	rsp, 0x10
+;;      	 5d                   	pop	rbp
+;;      	 c3                   	ret	
+;;   c6:	 0f0b                 	ud2	
+;;   c8:	 0f0b                 	ud2	
+;;   ca:	 0f0b                 	ud2	
+;;   cc:	 0f0b                 	ud2	
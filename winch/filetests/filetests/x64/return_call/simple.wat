@@ -0,0 +1,46 @@
+;;! target = "x86_64"
+
+(module
+  (func $fact (param i64 i64) (result i64)
+    (if (result i64) (i64.eqz (local.get 0))
+      (then (local.get 1))
+      (else
+        (return_call $fact
+          (i64.sub (local.get 0) (i64.const 1))
+          (i64.mul (local.get 0) (local.get 1))))))
+)
+;;      	 55                   	push	rbp
+;;      	 4889e5               	mov	rbp, rsp
+;;      	 4883ec18             	sub	rsp, 0x18
+;;      	 4d8b5e08             	mov	r11, qword ptr [r14 + 8]
+;;      	 4d8b1b               	mov	r11, qword ptr [r11]
+;;      	 4939e3               	cmp	r11, rsp
+;;      	 0f8769000000         	ja	0x81
+;;   18:	 48897c2410           	mov	qword ptr [rsp + 0x10], rdi
+;;      	 4889742408           	mov	qword ptr [rsp + 8], rsi
+;;      	 4c893424             	mov	qword ptr [rsp], r14
+;;      	 488b442410           	mov	rax, qword ptr [rsp + 0x10]
+;;      	 4883f800             	cmp	rax, 0
+;;      	 b800000000           	mov	eax, 0
+;;      	 400f94c0             	sete	al
+;;      	 85c0                 	test	eax, eax
+;;      	 0f840a000000         	je	0x4a
+;;   40:	 488b442408           	mov	rax, qword ptr [rsp + 8]
+;;      	 e931000000           	jmp	0x7b
+;;   4a:	 488b442410           	mov	rax, qword ptr [rsp + 0x10]
+;;      	 4883e801             	sub	rax, 1
+;;      	 488b4c2408           	mov	rcx, qword ptr [rsp + 8]
+;;      	 488b542410           	mov	rdx, qword ptr [rsp + 0x10]
+;;      	 480fafd1             	imul	rdx, rcx
+;;      	 50                   	push	rax
+;;      	 52                   	push	rdx
+;;      	 488b7c2408           	mov	rdi, qword ptr [rsp + 8]
+;;      	 488b3424             	mov	rsi, qword ptr [rsp]
+;;      	 4889e9               	mov	rcx, rbp
+;;      	 488b29               	mov	rbp, qword ptr [rcx]
+;;      	 488d6108             	lea	rsp, [rcx + 8]
+;;      	 e900000000           	jmp	0x7b
+;;   7b:	 4883c418             	add	rsp, 0x18
+;;      	 5d                   	pop	rbp
+;;      	 c3                   	ret	
+;;   81:	 0f0b                 	ud2	
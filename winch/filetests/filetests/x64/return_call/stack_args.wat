@@ -0,0 +1,77 @@
+;;! target = "x86_64"
+
+(module
+  (func $caller (param i32) (result i32)
+    (return_call $callee
+      (local.get 0) (i32.const 1) (i32.const 2) (i32.const 3) (i32.const 4)
+      (i32.const 5) (i32.const 6) (i32.const 7)))
+
+  (func $callee (param i32 i32 i32 i32 i32 i32 i32 i32) (result i32)
+    (return_call $caller (local.get 7)))
+)
+;;      	 55                   	push	rbp
+;;      	 4889e5               	mov	rbp, rsp
+;;      	 4883ec10             	sub	rsp, 0x10
+;;      	 4d8b5e08             	mov	r11, qword ptr [r14 + 8]
+;;      	 4d8b1b               	mov	r11, qword ptr [r11]
+;;      	 4939e3               	cmp	r11, rsp
+;;      	 0f877a000000         	ja	0x92
+;;   18:	 897c240c             	mov	dword ptr [rsp + 0xc], edi
+;;      	 4c893424             	mov	qword ptr [rsp], r14
+;;      	 448b5c240c           	mov	r11d, dword ptr [rsp + 0xc]
+;;      	 4883ec04             	sub	rsp, 4
+;;      	 44891c24             	mov	dword ptr [rsp], r11d
+;;      	 4883ec10             	sub	rsp, 0x10
+;;      	 8b7c2410             	mov	edi, dword ptr [rsp + 0x10]
+;;      	 be01000000           	mov	esi, 1
+;;      	 ba02000000           	mov	edx, 2
+;;      	 b903000000           	mov	ecx, 3
+;;      	 41b804000000         	mov	r8d, 4
+;;      	 41b905000000         	mov	r9d, 5
+;;      	 41bb06000000         	mov	r11d, 6
+;;      	 44891c24             	mov	dword ptr [rsp], r11d
+;;      	 41bb07000000         	mov	r11d, 7
+;;      	 44895c2408           	mov	dword ptr [rsp + 8], r11d
+;;      	 4889eb               	mov	rbx, rbp
+;;      	 4c8b5508             	mov	r10, qword ptr [rbp + 8]
+;;      	 488b2b               	mov	rbp, qword ptr [rbx]
+;;      	 4c8b5c2408           	mov	r11, qword ptr [rsp + 8]
+;;      	 4c895b08             	mov	qword ptr [rbx + 8], r11
+;;      	 4c8b1c24             	mov	r11, qword ptr [rsp]
+;;      	 4c891b               	mov	qword ptr [rbx], r11
+;;      	 488d63f8             	lea	rsp, [rbx - 8]
+;;      	 4c891424             	mov	qword ptr [rsp], r10
+;;      	 e900000000           	jmp	0x8c
+;;   8c:	 4883c410             	add	rsp, 0x10
+;;      	 5d                   	pop	rbp
+;;      	 c3                   	ret	
+;;   92:	 0f0b                 	ud2	
+;;
+;;      	 55                   	push	rbp
+;;      	 4889e5               	mov	rbp, rsp
+;;      	 4883ec20             	sub	rsp, 0x20
+;;      	 4d8b5e08             	mov	r11, qword ptr [r14 + 8]
+;;      	 4d8b1b               	mov	r11, qword ptr [r11]
+;;      	 4939e3               	cmp	r11, rsp
+;;      	 0f874c000000         	ja	0x64
+;;   18:	 897c241c             	mov	dword ptr [rsp + 0x1c], edi
+;;      	 89742418             	mov	dword ptr [rsp + 0x18], esi
+;;      	 89542414             	mov	dword ptr [rsp + 0x14], edx
+;;      	 894c2410             	mov	dword ptr [rsp + 0x10], ecx
+;;      	 448944240c           	mov	dword ptr [rsp + 0xc], r8d
+;;      	 44894c2408           	mov	dword ptr [rsp + 8], r9d
+;;      	 4c893424             	mov	qword ptr [rsp], r14
+;;      	 448b5d18             	mov	r11d, dword ptr [rbp + 0x18]
+;;      	 4883ec04             	sub	rsp, 4
+;;      	 44891c24             	mov	dword ptr [rsp], r11d
+;;      	 8b3c24               	mov	edi, dword ptr [rsp]
+;;      	 4889e9               	mov	rcx, rbp
+;;      	 488b5508             	mov	rdx, qword ptr [rbp + 8]
+;;      	 488b29               	mov	rbp, qword ptr [rcx]
+;;      	 488d6118             	lea	rsp, [rcx + 0x18]
+;;      	 48891424             	mov	qword ptr [rsp], rdx
+;;      	 e900000000           	jmp	0x5c
+;;   5c:	 4883c420             	add	rsp, 0x20
+;;      	 5d                   	pop	rbp
+;;      	 c21000               	ret	0x10
+;;   64:	 0f0b                 	ud2	
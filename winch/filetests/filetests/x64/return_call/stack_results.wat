@@ -0,0 +1,87 @@
+;;! target = "x86_64"
+
+(module
+  (func $a (param i32) (result i64 i64 i64 i64 i64 i64 i64)
+    (return_call $b (local.get 0)))
+
+  (func $b (param i32) (result i64 i64 i64 i64 i64 i64 i64)
+    (i64.const 1) (i64.const 2) (i64.const 3) (i64.const 4)
+    (i64.const 5) (i64.const 6) (i64.const 7))
+)
+;;      	 55                   	push	rbp
+;;      	 4889e5               	mov	rbp, rsp
+;;      	 4883ec18             	sub	rsp, 0x18
+;;      	 4d8b5e08             	mov	r11, qword ptr [r14 + 8]
+;;      	 4d8b1b               	mov	r11, qword ptr [r11]
+;;      	 4939e3               	cmp	r11, rsp
+;;      	 0f875f000000         	ja	0x77
+;;   18:	 897c2414             	mov	dword ptr [rsp + 0x14], edi
+;;      	 4c89742408           	mov	qword ptr [rsp + 8], r14
+;;      	 48893424             	mov	qword ptr [rsp], rsi
+;;      	 448b5c2414           	mov	r11d, dword ptr [rsp + 0x14]
+;;      	 4883ec04             	sub	rsp, 4
+;;      	 44891c24             	mov	dword ptr [rsp], r11d
+;;      	 8b3c24               	mov	edi, dword ptr [rsp]
+;;      	 488b742404           	mov	rsi, qword ptr [rsp + 4]
+;;      	 4889e9               	mov	rcx, rbp
+;;      	 488b29               	mov	rbp, qword ptr [rcx]
+;;      	 488d6108             	lea	rsp, [rcx + 8]
+;;      	 e900000000           	jmp	0x49
+;;   49:	 488b4c2430           	mov	rcx, qword ptr [rsp + 0x30]
+;;      	 415b                 	pop	r11
+;;      	 4c8919               	mov	qword ptr [rcx], r11
+;;      	 415b                 	pop	r11
+;;      	 4c895908             	mov	qword ptr [rcx + 8], r11
+;;      	 415b                 	pop	r11
+;;      	 4c895910             	mov	qword ptr [rcx + 0x10], r11
+;;      	 415b                 	pop	r11
+;;      	 4c895918             	mov	qword ptr [rcx + 0x18], r11
+;;      	 415b                 	pop	r11
+;;      	 4c895920             	mov	qword ptr [rcx + 0x20], r11
+;;      	 415b                 	pop	r11
+;;      	 4c895928             	mov	qword ptr [rcx + 0x28], r11
+;;      	 4883c418             	add	rsp, 0x18
+;;      	 5d                   	pop	rbp
+;;      	 c3                   	ret	
+;;   77:	 0f0b                 	ud2	
+;;
+;;      	 55                   	push	rbp
+;;      	 4889e5               	mov	rbp, rsp
+;;      	 4883ec18             	sub	rsp, 0x18
+;;      	 4d8b5e08             	mov	r11, qword ptr [r14 + 8]
+;;      	 4d8b1b               	mov	r11, qword ptr [r11]
+;;      	 4939e3               	cmp	r11, rsp
+;;      	 0f877b000000         	ja	0x93
+;;   18:	 897c2414             	mov	dword ptr [rsp + 0x14], edi
+;;      	 4c89742408           	mov	qword ptr [rsp + 8], r14
+;;      	 48893424             	mov	qword ptr [rsp], rsi
+;;      	 48c7c007000000       	mov	rax, 7
+;;      	 4883ec30             	sub	rsp, 0x30
+;;      	 48c7042406000000     	mov	qword ptr [rsp], 6
+;;      	 48c744240805000000   	
+;; 				mov	qword ptr [rsp + 8], 5
+;;      	 48c744241004000000   	
+;; 				mov	qword ptr [rsp + 0x10], 4
+;;      	 48c744241803000000   	
+;; 				mov	qword ptr [rsp + 0x18], 3
+;;      	 48c744242002000000   	
+;; 				mov	qword ptr [rsp + 0x20], 2
+;;      	 48c744242801000000   	
+;; 				mov	qword ptr [rsp + 0x28], 1
+;;      	 488b4c2430           	mov	rcx, qword ptr [rsp + 0x30]
+;;      	 415b                 	pop	r11
+;;      	 4c8919               	mov	qword ptr [rcx], r11
+;;      	 415b                 	pop	r11
+;;      	 4c895908             	mov	qword ptr [rcx + 8], r11
+;;      	 415b                 	pop	r11
+;;      	 4c895910             	mov	qword ptr [rcx + 0x10], r11
+;;      	 415b                 	pop	r11
+;;      	 4c895918             	mov	qword ptr [rcx + 0x18], r11
+;;      	 415b                 	pop	r11
+;;      	 4c895920             	mov	qword ptr [rcx + 0x20], r11
+;;      	 415b                 	pop	r11
+;;      	 4c895928             	mov	qword ptr [rcx + 0x28], r11
+;;      	 4883c418             	add	rsp, 0x18
+;;      	 5d                   	pop	rbp
+;;      	 c3                   	ret	
+;;   93:	 0f0b                 	ud2	
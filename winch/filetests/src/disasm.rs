@@ -29,12 +29,16 @@ pub fn disasm(
         .map(|i| {
             use capstone::InsnGroupType::{CS_GRP_JUMP, CS_GRP_RET};
 
-            let detail = dis.insn_detail(&i).unwrap();
+            // Skipped data, e.g. the trap instructions emitted by the
+            // aarch64 backend, carries no instruction details.
+            let detail = dis.insn_detail(&i).ok();
+            let in_group = |group| {
+                detail.as_ref().map_or(false, |d| {
+                    d.groups().find(|g| g.0 as u32 == group).is_some()
+                })
+            };
 
-            let is_jump = detail
-                .groups()
-                .find(|g| g.0 as u32 == CS_GRP_JUMP)
-                .is_some();
+            let is_jump = in_group(CS_GRP_JUMP);
 
             let mut line = String::new();
 
@@ -67,8 +71,7 @@ pub fn disasm(
 
             // Flip write_offsets to true once we've seen a `ret`, as instructions that follow the
             // return are often related to trap tables.
-            write_offsets =
-                write_offsets || detail.groups().find(|g| g.0 as u32 == CS_GRP_RET).is_some();
+            write_offsets = write_offsets || in_group(CS_GRP_RET);
 
             line
         })
@@ -94,7 +94,11 @@ mod test {
         let binding = parse_expected_result(wat);
         let expected = binding.as_str();
 
-        let shared_flags = settings::Flags::new(settings::builder());
+        // Mirror Wasmtime's configuration, which always preserves frame
+        // pointers; tail calls rely on them.
+        let mut shared_flags = settings::builder();
+        shared_flags.enable("preserve_frame_pointers").unwrap();
+        let shared_flags = settings::Flags::new(shared_flags);
         let mut isa_builder = lookup(triple).unwrap();
         for flag in config.flags.iter().flat_map(|f| f) {
             isa_builder.set(&flag, "true").unwrap();